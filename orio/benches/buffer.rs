@@ -1,9 +1,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::mem;
-use criterion::{BatchSize, Bencher, black_box, Criterion, criterion_group, criterion_main};
+use criterion::{BatchSize, Bencher, black_box, Criterion, criterion_group, criterion_main, Throughput};
 use orio::{Buffer, DefaultBuffer, SIZE};
-use orio::streams::{BufSink, BufSource};
+use orio::streams::{BufSink, BufSource, HashSource, pattern_source, random_source, repeat_source, Source};
 
 const DATA: &[u8] = include_bytes!("../../test-data/cantrbry/fields_c");
 
@@ -137,6 +137,34 @@ fn hash(c: &mut Criterion) {
 	}));
 }
 
+fn generate(c: &mut Criterion) {
+	const COUNT: usize = 64 * SIZE;
+	let mut group = c.benchmark_group("generate");
+	group.throughput(Throughput::Bytes(COUNT as u64));
+	let mut buffer = DefaultBuffer::default();
+
+	macro_rules! gen {
+		($($name:literal $source:expr),+) => {
+			$(
+			let mut source = $source;
+			group.bench_function($name, |b| b.iter(|| {
+				source.fill(&mut buffer, COUNT).unwrap();
+				buffer.clear();
+			}));
+			)+
+		};
+	}
+
+	gen!(
+		"repeat" repeat_source(0),
+		"pattern" pattern_source(b"orio"),
+		"random" random_source(0),
+		"hash random" HashSource::sha256(random_source(0))
+	);
+	group.finish();
+}
+
 criterion_group!(write, write_slice, write_numbers);
 criterion_group!(read, read_slice, read_numbers, skip, find, hash);
-criterion_main!(write, read);
+criterion_group!(streams, generate);
+criterion_main!(write, read, streams);
@@ -3,30 +3,205 @@
 mod read;
 mod write;
 mod options;
+mod pin;
+mod send;
+mod view;
 
 pub use options::*;
+pub use pin::*;
+pub use send::*;
+pub use view::*;
 
-use std::cmp::min;
 use std::{fmt, mem, slice};
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::iter::FusedIterator;
 use std::fmt::{Debug, Formatter};
-use std::ops::{Range, RangeBounds};
+use std::ops::{Index, IndexMut, Range, RangeBounds};
 use all_asserts::assert_ge;
 use itertools::Itertools;
 use crate::pool::{DefaultPoolContainer, Pool, pool, PoolExt};
-use crate::{BufferResult as Result, ByteStr, ResultContext, ResultSetContext, Seg, StreamResult};
+use crate::{BufferResult as Result, ByteStr, ByteString, Chars, HexDump, SharedByteStr, ResultContext, ResultSetContext, Seg, StreamResult};
 use crate::BufferContext::{Copy, Reserve, Resize};
-use crate::pattern::Pattern;
+use crate::pattern::{self, Matcher, Pattern};
 use crate::segment::RBuf;
 use crate::streams::{BufSink, BufStream, Seekable, SeekOffset, Stream};
+use crate::util::{compare, trace};
 use crate::util::partial_utf8::*;
 
 // Todo: track how much space is reserved to keep empty segments after resize-on-read.
 
 pub type DefaultBuffer<'d> = Buffer<'d>;
 
+/// A snapshot of a [`Buffer`]'s write position, returned by [`Buffer::checkpoint`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[must_use = "a checkpoint does nothing unless restored"]
+pub struct Checkpoint {
+	count: usize,
+	capacity: usize,
+}
+
+/// A range of reserved bytes in a [`Buffer`], returned by [`Buffer::reserve_slot`].
+/// Slots are patched once their data is known, such as a length prefix written
+/// after the data following it:
+///
+/// ```ignore
+/// let slot = buffer.reserve_slot(4)?;
+/// let len = message.encode(&mut buffer)?;
+/// slot.write_u32(&mut buffer, len as u32)?;
+/// ```
+///
+/// A slot's position is counted from the front of the buffer, so it stays valid
+/// as data is appended, but not once data is read or prepended.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[must_use = "a slot does nothing unless written"]
+pub struct Slot {
+	pos: usize,
+	len: usize,
+}
+
+impl Slot {
+	/// Returns the position of the slot in the buffer.
+	pub fn pos(&self) -> usize { self.pos }
+
+	/// Returns the length of the slot in bytes.
+	pub fn len(&self) -> usize { self.len }
+
+	/// Returns `true` if the slot has a length of zero.
+	pub fn is_empty(&self) -> bool { self.len == 0 }
+
+	/// Returns the range of bytes covered by the slot.
+	pub fn range(&self) -> Range<usize> { self.pos..self.pos + self.len }
+
+	/// Writes `value` into the slot in `buffer`. See [`Buffer::write_slice_at`] for
+	/// details.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the slot is in a shared segment and segments to copy into
+	/// couldn't be claimed with allocation set to [`Never`].
+	///
+	/// # Panics
+	///
+	/// Panics if the length of `value` isn't the length of the slot, or if the slot
+	/// is out of bounds of `buffer`.
+	///
+	/// [`Never`]: Allocate::Never
+	pub fn write_slice<const N: usize>(&self, buffer: &mut Buffer<'_, N, impl Pool<N>>, value: &[u8]) -> Result {
+		assert_eq!(value.len(), self.len, "value length should match the slot length");
+		buffer.write_slice_at(self.pos, value)
+	}
+
+	/// Writes a [`u8`] into the slot in `buffer`.
+	#[inline]
+	pub fn write_u8<const N: usize>(&self, buffer: &mut Buffer<'_, N, impl Pool<N>>, value: u8) -> Result {
+		self.write_pod(buffer, value)
+	}
+
+	/// Writes a big-endian [`u16`] into the slot in `buffer`.
+	#[inline]
+	pub fn write_u16<const N: usize>(&self, buffer: &mut Buffer<'_, N, impl Pool<N>>, value: u16) -> Result {
+		self.write_int(buffer, value)
+	}
+
+	/// Writes a little-endian [`u16`] into the slot in `buffer`.
+	#[inline]
+	pub fn write_u16_le<const N: usize>(&self, buffer: &mut Buffer<'_, N, impl Pool<N>>, value: u16) -> Result {
+		self.write_int_le(buffer, value)
+	}
+
+	/// Writes a big-endian [`u32`] into the slot in `buffer`.
+	#[inline]
+	pub fn write_u32<const N: usize>(&self, buffer: &mut Buffer<'_, N, impl Pool<N>>, value: u32) -> Result {
+		self.write_int(buffer, value)
+	}
+
+	/// Writes a little-endian [`u32`] into the slot in `buffer`.
+	#[inline]
+	pub fn write_u32_le<const N: usize>(&self, buffer: &mut Buffer<'_, N, impl Pool<N>>, value: u32) -> Result {
+		self.write_int_le(buffer, value)
+	}
+
+	/// Writes a big-endian [`u64`] into the slot in `buffer`.
+	#[inline]
+	pub fn write_u64<const N: usize>(&self, buffer: &mut Buffer<'_, N, impl Pool<N>>, value: u64) -> Result {
+		self.write_int(buffer, value)
+	}
+
+	/// Writes a little-endian [`u64`] into the slot in `buffer`.
+	#[inline]
+	pub fn write_u64_le<const N: usize>(&self, buffer: &mut Buffer<'_, N, impl Pool<N>>, value: u64) -> Result {
+		self.write_int_le(buffer, value)
+	}
+
+	/// Writes a big-endian integer into the slot in `buffer`.
+	#[inline]
+	pub fn write_int<const N: usize, T: num_traits::PrimInt + bytemuck::Pod>(
+		&self,
+		buffer: &mut Buffer<'_, N, impl Pool<N>>,
+		value: T
+	) -> Result {
+		self.write_pod(buffer, value.to_be())
+	}
+
+	/// Writes a little-endian integer into the slot in `buffer`.
+	#[inline]
+	pub fn write_int_le<const N: usize, T: num_traits::PrimInt + bytemuck::Pod>(
+		&self,
+		buffer: &mut Buffer<'_, N, impl Pool<N>>,
+		value: T
+	) -> Result {
+		self.write_pod(buffer, value.to_le())
+	}
+
+	/// Writes an arbitrary [`Pod`] data type into the slot in `buffer`.
+	///
+	/// [`Pod`]: bytemuck::Pod
+	#[inline]
+	pub fn write_pod<const N: usize, T: bytemuck::Pod>(&self, buffer: &mut Buffer<'_, N, impl Pool<N>>, value: T) -> Result {
+		self.write_slice(buffer, bytemuck::bytes_of(&value))
+	}
+}
+
+/// Memory statistics of a [`Buffer`], returned by [`Buffer::stats`].
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct BufferStats {
+	/// The number of segments containing data.
+	pub segments: usize,
+	/// The number of bytes in exclusively-owned segments.
+	pub owned_bytes: usize,
+	/// The number of bytes in segments sharing memory with another segment.
+	pub shared_bytes: usize,
+	/// The number of bytes in segments borrowing slices.
+	pub borrowed_bytes: usize,
+	/// The number of bytes that can be written without claiming more segments.
+	pub spare_capacity: usize,
+	/// The fraction of memory occupied by segments containing data which doesn't
+	/// hold data, from `0.0` to `1.0`. High fragmentation means memory is wasted
+	/// by many partially filled segments.
+	pub fragmentation: f64,
+}
+
+/// A "capacity exceeded" error, returned when writing to a [`Buffer`] would grow
+/// it past its [maximum capacity](BufferOptions::max_capacity).
+#[derive(Copy, Clone, Debug, Default, thiserror::Error)]
+#[error("buffer capacity exceeded")]
+pub struct CapacityExceeded;
+
+/// A pattern match in a [`Buffer`], returned by [`Buffer::find_match`] and
+/// [`Buffer::matches`].
+#[derive(Clone, Debug)]
+pub struct Match<'b> {
+	/// The range of matched bytes.
+	pub range: Range<usize>,
+	/// The matched bytes, borrowed from the buffer.
+	pub text: ByteStr<'b>,
+}
+
 /// A dynamically-resizing byte buffer which borrows and returns pool memory as
 /// needed.
-#[derive(Clone, Eq)]
+#[derive(Clone)]
 pub struct Buffer<
 	'd,
 	const N: usize = 8192,
@@ -36,6 +211,18 @@ pub struct Buffer<
 	pool: P,
 	share_threshold: usize,
 	borrow_threshold: usize,
+	max_capacity: usize,
+	high_watermark: usize,
+	low_watermark: usize,
+	yield_after: usize,
+	flush_threshold: usize,
+	pin_capacity: usize,
+	retain_spare: usize,
+	jumbo_threshold: usize,
+	fragmentation_threshold: usize,
+	compaction_policy: CompactionPolicy,
+	#[cfg(feature = "zeroize")]
+	sensitive: bool,
 	allocation: Allocate,
 }
 
@@ -57,11 +244,23 @@ impl<const N: usize, P: Pool<N>> From<P> for Buffer<'_, N, P> {
 
 impl<const N: usize, P: Pool<N>> Debug for Buffer<'_, N, P> {
 	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-		f.debug_struct("Buffer")
-			.field("data", &self.data)
+		let mut f = f.debug_struct("Buffer");
+		f.field("data", &self.data)
 			.field("share_threshold", &self.share_threshold)
 			.field("borrow_threshold", &self.borrow_threshold)
-			.field("allocation", &self.allocation)
+			.field("max_capacity", &self.max_capacity)
+			.field("high_watermark", &self.high_watermark)
+			.field("low_watermark", &self.low_watermark)
+			.field("yield_after", &self.yield_after)
+			.field("flush_threshold", &self.flush_threshold)
+			.field("pin_capacity", &self.pin_capacity)
+			.field("retain_spare", &self.retain_spare)
+			.field("jumbo_threshold", &self.jumbo_threshold)
+			.field("fragmentation_threshold", &self.fragmentation_threshold)
+			.field("compaction_policy", &self.compaction_policy);
+		#[cfg(feature = "zeroize")]
+		f.field("sensitive", &self.sensitive);
+		f.field("allocation", &self.allocation)
 			.finish_non_exhaustive()
 	}
 }
@@ -135,11 +334,18 @@ impl<'d> Buffer<'d> {
 	/// Creates a new buffer from a [byte string](ByteStr) without copying its
 	/// contents.
 	pub fn from_byte_str(value: ByteStr<'d>) -> Self {
+		Self::from_segments(
+			value.slices()
+				 .map(Seg::from_slice)
+				 .collect()
+		)
+	}
+
+	/// Creates a new buffer from a vector of segments, which must be partitioned
+	/// into non-empty and empty segments.
+	pub(crate) fn from_segments(segments: Vec<Seg<'d>>) -> Self {
 		let mut buf = Self::default();
-		buf.data = value.slices()
-						.map(Seg::from_slice)
-						.collect::<Vec<_>>()
-						.into();
+		buf.data = segments.into();
 		buf
 	}
 }
@@ -174,6 +380,51 @@ impl<'d> FromIterator<u8> for Buffer<'d> {
 	}
 }
 
+impl<'d> FromIterator<&'d [u8]> for Buffer<'d> {
+	/// Creates a buffer from slices, pushed without copying as with
+	/// [`push_slice`].
+	///
+	/// [`push_slice`]: Buffer::push_slice
+	fn from_iter<T: IntoIterator<Item = &'d [u8]>>(iter: T) -> Self {
+		let mut buf = Self::default();
+		buf.extend(iter);
+		buf
+	}
+}
+
+impl<const N: usize, P: Pool<N>> Extend<u8> for Buffer<'_, N, P> {
+	/// Writes bytes from an iterator.
+	///
+	/// # Panics
+	///
+	/// Panics if writing fails, which can only occur if the buffer can't claim or
+	/// allocate segments.
+	fn extend<T: IntoIterator<Item = u8>>(&mut self, iter: T) {
+		let mut chunk = [0; 256];
+		let mut len = 0;
+		for byte in iter {
+			chunk[len] = byte;
+			len += 1;
+			if len == chunk.len() {
+				self.write_from_slice(&chunk).expect("bytes should be written");
+				len = 0;
+			}
+		}
+		self.write_from_slice(&chunk[..len]).expect("bytes should be written");
+	}
+}
+
+impl<'d, const N: usize, P: Pool<N>> Extend<&'d [u8]> for Buffer<'d, N, P> {
+	/// Pushes slices without copying, as with [`push_slice`].
+	///
+	/// [`push_slice`]: Buffer::push_slice
+	fn extend<T: IntoIterator<Item = &'d [u8]>>(&mut self, iter: T) {
+		for slice in iter {
+			self.push_slice(slice);
+		}
+	}
+}
+
 impl<'d, const N: usize, P: Pool<N>> Buffer<'d, N, P> {
 	/// Creates a new buffer.
 	pub const fn new(
@@ -181,14 +432,46 @@ impl<'d, const N: usize, P: Pool<N>> Buffer<'d, N, P> {
 		BufferOptions {
 			share_threshold,
 			borrow_threshold,
+			max_capacity,
+			high_watermark,
+			low_watermark,
+			yield_after,
+			flush_threshold,
+			pin_capacity,
+			retain_spare,
+			jumbo_threshold,
+			fragmentation_threshold,
+			compaction_policy,
+			#[cfg(feature = "zeroize")]
+			sensitive,
 			allocation,
 		}: BufferOptions
 	) -> Self {
+		// Sensitive data is never shared or borrowed.
+		#[cfg(feature = "zeroize")]
+		let (share_threshold, borrow_threshold) = if sensitive {
+			(usize::MAX, usize::MAX)
+		} else {
+			(share_threshold, borrow_threshold)
+		};
+
 		Self {
 			data: RBuf::new(),
 			pool,
 			share_threshold,
 			borrow_threshold,
+			max_capacity,
+			high_watermark,
+			low_watermark,
+			yield_after,
+			flush_threshold,
+			pin_capacity,
+			retain_spare,
+			jumbo_threshold,
+			fragmentation_threshold,
+			compaction_policy,
+			#[cfg(feature = "zeroize")]
+			sensitive,
 			allocation,
 		}
 	}
@@ -200,6 +483,17 @@ impl<'d, const N: usize, P: Pool<N>> Buffer<'d, N, P> {
 		new
 	}
 
+	/// Creates a new buffer claiming at least `capacity` bytes from `pool` upfront,
+	/// and [pinning] this capacity such that the buffer recycles its own segments
+	/// rather than returning them to the pool after reading.
+	///
+	/// [pinning]: BufferOptions::pin_capacity
+	pub fn with_reserved(pool: P, capacity: usize) -> Self {
+		let mut new = Self::new(pool, BufferOptions::default().with_pin_capacity(capacity));
+		new.claim_or_alloc(capacity);
+		new
+	}
+
 	/// Creates a new buffer with `data` as its internal ring buffer.
 	fn new_buf(
 		pool: P,
@@ -207,6 +501,18 @@ impl<'d, const N: usize, P: Pool<N>> Buffer<'d, N, P> {
 		BufferOptions {
 			share_threshold,
 			borrow_threshold,
+			max_capacity,
+			high_watermark,
+			low_watermark,
+			yield_after,
+			flush_threshold,
+			pin_capacity,
+			retain_spare,
+			jumbo_threshold,
+			fragmentation_threshold,
+			compaction_policy,
+			#[cfg(feature = "zeroize")]
+			sensitive,
 			allocation,
 		}: BufferOptions
 	) -> Self {
@@ -215,6 +521,18 @@ impl<'d, const N: usize, P: Pool<N>> Buffer<'d, N, P> {
 			data: data.into(),
 			share_threshold,
 			borrow_threshold,
+			max_capacity,
+			high_watermark,
+			low_watermark,
+			yield_after,
+			flush_threshold,
+			pin_capacity,
+			retain_spare,
+			jumbo_threshold,
+			fragmentation_threshold,
+			compaction_policy,
+			#[cfg(feature = "zeroize")]
+			sensitive,
 			allocation,
 		}
 	}
@@ -224,6 +542,18 @@ impl<'d, const N: usize, P: Pool<N>> Buffer<'d, N, P> {
 		BufferOptions {
 			share_threshold: self.share_threshold,
 			borrow_threshold: self.borrow_threshold,
+			max_capacity: self.max_capacity,
+			high_watermark: self.high_watermark,
+			low_watermark: self.low_watermark,
+			yield_after: self.yield_after,
+			flush_threshold: self.flush_threshold,
+			pin_capacity: self.pin_capacity,
+			retain_spare: self.retain_spare,
+			jumbo_threshold: self.jumbo_threshold,
+			fragmentation_threshold: self.fragmentation_threshold,
+			compaction_policy: self.compaction_policy,
+			#[cfg(feature = "zeroize")]
+			sensitive: self.sensitive,
 			allocation: self.allocation,
 		}
 	}
@@ -234,6 +564,31 @@ impl<'d, const N: usize, P: Pool<N>> Buffer<'d, N, P> {
 	pub fn count(&self) -> usize { self.data.count() }
 	/// Returns the total number of bytes that can be written to the buffer.
 	pub fn capacity(&self) -> usize { self.data.byte_capacity() }
+	/// Returns memory statistics for the buffer, computed in `O(n)` time, where `n`
+	/// is the number of segments.
+	pub fn stats(&self) -> BufferStats {
+		let mut stats = BufferStats {
+			spare_capacity: self.limit(),
+			..BufferStats::default()
+		};
+		let mut size = 0;
+		for seg in self.data.iter() {
+			stats.segments += 1;
+			size += seg.size();
+			if seg.is_borrowed() {
+				stats.borrowed_bytes += seg.len();
+			} else if seg.is_shared() {
+				stats.shared_bytes += seg.len();
+			} else {
+				stats.owned_bytes += seg.len();
+			}
+		}
+		if size > 0 {
+			stats.fragmentation = 1.0 - self.count() as f64 / size as f64;
+		}
+		stats
+	}
+
 	/// Returns `true` if the buffer is empty.
 	pub fn is_empty(&self) -> bool { self.data.is_empty() }
 	/// Returns `true` if the buffer is not empty.
@@ -285,17 +640,25 @@ impl<'d, const N: usize, P: Pool<N>> Buffer<'d, N, P> {
 
 	/// Clears data from the buffer.
 	pub fn clear(&mut self) {
-		let Err(_) = self.pool.try_use(|mut pool| {
-			use crate::pool::MutPool;
+		if self.is_sensitive() {
+			let scrub = self.scrubber();
+			self.data.buf.iter_mut().for_each(scrub);
+		}
+
+		if !self.keeps_capacity() {
+			let Err(_) = self.pool.try_use(|mut pool| {
+				use crate::pool::MutPool;
 
-			// Take the internal ring buffer instead of draining. This should be
-			// significantly faster.
-			let segments = mem::take(&mut self.data).buf;
+				// Take the internal ring buffer instead of draining. This should be
+				// significantly faster.
+				let segments = mem::take(&mut self.data).buf;
 
-			(&mut pool).collect(segments);
-		}) else { return };
+				(&mut pool).collect(segments);
+			}) else { return };
+		}
 
-		// Returning segments to the pool failed, clear and retain them instead.
+		// Capacity is kept or returning segments to the pool failed, clear and
+		// retain them instead.
 
 		for seg in self.data.iter_mut() {
 			seg.clear();
@@ -309,11 +672,63 @@ impl<'d, const N: usize, P: Pool<N>> Buffer<'d, N, P> {
 
 		// Drop shared segments
 		self.data.buf.retain(Seg::is_exclusive);
+		self.release_unpinned();
+	}
+
+	/// Returns the number of bytes that can be added before the buffer reaches its
+	/// maximum capacity.
+	pub fn remaining_capacity(&self) -> usize {
+		self.max_capacity.saturating_sub(self.count())
+	}
+
+	/// Returns `true` if the buffer holds more bytes than its high watermark. Data
+	/// should stop being written until the buffer is drained below its low
+	/// watermark.
+	pub fn is_above_high_watermark(&self) -> bool {
+		self.count() > self.high_watermark
+	}
+
+	/// Returns `true` if the buffer holds no more bytes than its low watermark.
+	pub fn is_below_low_watermark(&self) -> bool {
+		self.count() <= self.low_watermark
+	}
+
+	/// Returns `true` if an operation which has written `count` bytes to the buffer
+	/// should yield, returning its partial progress.
+	pub(crate) fn should_yield(&self, count: usize) -> bool {
+		count >= self.yield_after
+	}
+
+	/// Returns `true` if the buffer holds at least its flush threshold, and should
+	/// be drained completely by a buffered sink.
+	pub(crate) fn should_flush(&self) -> bool {
+		self.count() >= self.flush_threshold
+	}
+
+	/// Sets the number of bytes after which buffered sinks drain the buffer.
+	pub(crate) fn set_flush_threshold(&mut self, value: usize) {
+		self.flush_threshold = value;
+	}
+
+	/// Returns a [`CapacityExceeded`] error if adding `count` bytes would grow the
+	/// buffer past its maximum capacity.
+	fn check_capacity(&self, count: usize) -> Result {
+		if count > self.remaining_capacity() {
+			Err(CapacityExceeded).context(Reserve)
+		} else {
+			Ok(())
+		}
 	}
 
 	/// Reserves at least `count` bytes of additional memory in the buffer.
+	///
+	/// # Errors
+	///
+	/// Returns a [`CapacityExceeded`] error if `count` more bytes would exceed the
+	/// buffer's maximum capacity.
 	pub fn reserve(&mut self, mut count: usize) -> Result {
-		let Self { data, pool, allocation, .. } = self;
+		self.check_capacity(count)?;
+		let Self { data, pool, allocation, jumbo_threshold, .. } = self;
 
 		let limit = data.limit();
 		if count <= limit {
@@ -321,7 +736,19 @@ impl<'d, const N: usize, P: Pool<N>> Buffer<'d, N, P> {
 		}
 
 		count -= limit;
+		if count >= *jumbo_threshold {
+			trace::event!("reserving jumbo segment", bytes = count);
+			let jumbo = match allocation {
+				Allocate::Always => Vec::with_capacity(count).into(),
+				Allocate::OnError => pool.claim_jumbo(count).unwrap_or_else(|_| Vec::with_capacity(count).into()),
+				Allocate::Never => pool.claim_jumbo(count).context(Reserve)?
+			};
+			data.extend_one(jumbo);
+			return Ok(())
+		}
+
 		let seg_count = count.div_ceil(N);
+		trace::event!("reserving segments", bytes = count, segments = seg_count);
 		match allocation {
 			Allocate::Always => {
 				data.allocate(seg_count);
@@ -343,31 +770,227 @@ impl<'d, const N: usize, P: Pool<N>> Buffer<'d, N, P> {
 		}
 	}
 
-	/// Returns empty segments to the pool after reading.
+	/// Returns empty segments to the pool after reading, keeping enough segments
+	/// to hold the pinned capacity and retained spare capacity.
 	fn resize(&mut self) -> Result {
+		let scrub = self.scrubber();
+		let Self { pool, data, pin_capacity, retain_spare, .. } = self;
+		let pinned = pin_capacity.div_ceil(N);
+		let retained = data.len() + retain_spare.div_ceil(N);
+		let excess = data.capacity().saturating_sub(pinned.max(retained));
+		pool.collect(data.drain_empty(excess).update(scrub))
+			.context(Resize)
+	}
+
+	/// Returns `true` if the buffer keeps some empty segments rather than returning
+	/// all of them to the pool.
+	fn keeps_capacity(&self) -> bool {
+		self.pin_capacity > 0 || self.retain_spare > 0
+	}
+
+	/// Returns `true` if the buffer holds [sensitive data].
+	///
+	/// [sensitive data]: BufferOptions#sensitive-data
+	#[cfg(feature = "zeroize")]
+	pub(crate) fn is_sensitive(&self) -> bool { self.sensitive }
+
+	/// Returns `true` if the buffer holds sensitive data.
+	#[cfg(not(feature = "zeroize"))]
+	pub(crate) fn is_sensitive(&self) -> bool { false }
+
+	/// Returns a function zeroing segments before they're returned to the pool, if
+	/// the buffer holds sensitive data.
+	#[cfg(feature = "zeroize")]
+	fn scrubber<'a>(&self) -> impl Fn(&mut Seg<'a, N>) {
+		let sensitive = self.sensitive;
+		move |seg| if sensitive { seg.zeroize() }
+	}
+
+	/// Returns a function zeroing segments before they're returned to the pool, if
+	/// the buffer holds sensitive data.
+	#[cfg(not(feature = "zeroize"))]
+	fn scrubber<'a>(&self) -> impl Fn(&mut Seg<'a, N>) { |_| { } }
+
+	/// Returns empty segments beyond the pinned and retained capacity to the pool,
+	/// if the buffer keeps capacity. Segments are retained if the pool can't be
+	/// borrowed.
+	fn release_unpinned(&mut self) {
+		if self.keeps_capacity() {
+			let _ = self.resize();
+		}
+	}
+
+	/// Returns empty segments to the pool immediately, keeping only the pinned and
+	/// [retained spare] capacity. Useful to give back memory after a transient peak.
+	///
+	/// [retained spare]: BufferOptions::retain_spare
+	pub fn release_spare(&mut self) -> Result {
+		self.resize()
+	}
+
+	/// Returns all empty segments to the pool immediately, ignoring the pinned and
+	/// retained spare capacity.
+	pub fn shrink_to_fit(&mut self) -> Result {
+		let scrub = self.scrubber();
 		let Self { pool, data, .. } = self;
-		pool.collect(data.drain_all_empty())
+		pool.collect(data.drain_all_empty().update(scrub))
 			.context(Resize)
 	}
 
+	/// Copies shared and borrowed data into exclusively owned segments claimed from
+	/// the pool, in one pass. Afterward, data can be modified in place, such as with
+	/// [`write_slice_at`], without forking segments or affecting other buffers.
+	///
+	/// # Errors
+	///
+	/// Returns an error if segments couldn't be claimed with allocation set to
+	/// [`Never`].
+	///
+	/// [`write_slice_at`]: Self::write_slice_at
+	/// [`Never`]: Allocate::Never
+	pub fn make_exclusive(&mut self) -> Result {
+		// Runs of adjacent shared segments are packed together, so count the segments
+		// needed for each run.
+		let mut seg_count = 0;
+		let mut run = 0;
+		for seg in self.data.iter().take(self.data.len()) {
+			if seg.is_shared() {
+				run += seg.len();
+			} else {
+				seg_count += run.div_ceil(N);
+				run = 0;
+			}
+		}
+		seg_count += run.div_ceil(N);
+		if seg_count == 0 { return Ok(()) }
+
+		let claimed = self.claim_exclusive(seg_count)?;
+		let segments = self.data.drain(self.data.len()).collect_vec();
+		let mut claimed = claimed.into_iter();
+		let mut target: Option<Seg<'d, N>> = None;
+		for mut seg in segments {
+			if seg.is_exclusive() {
+				if let Some(dst) = target.take() {
+					self.data.push_back(dst);
+				}
+				self.data.push_back(seg);
+				continue
+			}
+
+			while seg.is_not_empty() {
+				if target.as_ref().is_none_or(Seg::is_full) {
+					let next = claimed.next().expect("enough segments should have been claimed");
+					if let Some(dst) = target.replace(next) {
+						self.data.push_back(dst);
+					}
+				}
+
+				if let Some(dst) = &mut target {
+					dst.write_from(&mut seg).expect("claimed segment should be writable");
+				}
+			}
+		}
+
+		if let Some(dst) = target {
+			self.data.push_back(dst);
+		}
+		Ok(())
+	}
+
+	/// Claims `seg_count` segments to copy shared data into, allocating them as set
+	/// by the allocation option.
+	fn claim_exclusive(&mut self, seg_count: usize) -> Result<Vec<Seg<'d, N>>> {
+		let mut claimed = Vec::with_capacity(seg_count);
+		match self.allocation {
+			Allocate::Always => claimed.resize_with(seg_count, Seg::default),
+			Allocate::OnError =>
+				if self.pool.claim_count(&mut claimed, seg_count).is_err() {
+					claimed.resize_with(seg_count, Seg::default);
+				},
+			Allocate::Never => self.pool.claim_count(&mut claimed, seg_count).context(Reserve)?
+		}
+		Ok(claimed)
+	}
+
+	/// Copies the shared segment at `index` into segments claimed from the pool, or
+	/// allocated as set by the allocation option. Each copy is filled to the segment
+	/// size, except the last.
+	fn fork_segment(&mut self, index: usize) -> Result {
+		let mut shared = self.data[index].share_all();
+		let mut forked = self.claim_exclusive(shared.len().div_ceil(N))?;
+		for dst in &mut forked {
+			dst.write_from(&mut shared).expect("claimed segment should be writable");
+		}
+		self.data.replace(index, forked);
+		Ok(())
+	}
+
 	/// Copies `count` bytes into `sink`. Memory is either actually copied or
 	/// shared for performance; the tradeoff between wasted space by sharing small
 	/// segments and large, expensive mem-copies is managed by the implementation.
-	pub fn copy_to(&self, sink: &mut Buffer<'d, N, impl Pool<N>>, mut count: usize) -> Result {
-		if count == 0 { return Ok(()) }
-		let share_threshold = sink.share_threshold;
+	#[inline]
+	pub fn copy_to(&self, sink: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> Result {
+		self.copy_to_with(sink, count, CopyOptions::new())
+	}
 
-		let result: Result = try {
-			for seg in self.data.iter() {
-				if count == 0 { break }
+	/// Copies `count` bytes into `sink`, sharing or copying memory as set by
+	/// `options` rather than by the sink's share threshold alone.
+	#[inline]
+	pub fn copy_to_with(
+		&self,
+		sink: &mut Buffer<'d, N, impl Pool<N>>,
+		count: usize,
+		options: CopyOptions
+	) -> Result {
+		self.copy_range_to_with(..count.min(self.count()), sink, options)
+	}
+
+	/// Copies bytes within `range` into `sink`, without consuming them. Memory is
+	/// either actually copied or shared, as with [`copy_to`]. This allows copying
+	/// from anywhere within the buffer, such as to fan out parts of a buffer to
+	/// several sinks.
+	///
+	/// # Panics
+	///
+	/// Panics if `range` is out of bounds.
+	///
+	/// [`copy_to`]: Self::copy_to
+	#[inline]
+	pub fn copy_range_to<R: RangeBounds<usize>>(
+		&self,
+		range: R,
+		sink: &mut Buffer<'d, N, impl Pool<N>>
+	) -> Result {
+		self.copy_range_to_with(range, sink, CopyOptions::new())
+	}
 
-				let size = min(seg.len(), count);
-				let mut shared = seg.share(..size);
-				if size > share_threshold {
+	/// Copies bytes within `range` into `sink`, sharing or copying memory as set by
+	/// `options`.
+	///
+	/// # Panics
+	///
+	/// Panics if `range` is out of bounds.
+	pub fn copy_range_to_with<R: RangeBounds<usize>>(
+		&self,
+		range: R,
+		sink: &mut Buffer<'d, N, impl Pool<N>>,
+		options: CopyOptions
+	) -> Result {
+		let range = slice::range(range, ..self.count());
+		if range.is_empty() { return Ok(()) }
+		sink.check_capacity(range.len()).set_context(Copy)?;
+		let share_threshold = if self.is_sensitive() {
+			usize::MAX
+		} else {
+			options.resolve_share_threshold(sink.share_threshold)
+		};
+
+		let result: Result = try {
+			for mut shared in self.data.share_range(range) {
+				if shared.len() > share_threshold {
 					sink.data.push_back(shared);
-					count -= size;
 				} else {
-					sink.reserve(size)?;
+					sink.reserve(shared.len())?;
 
 					while let Some(mut dst) = shared.is_not_empty().then(||
 						sink.data
@@ -380,6 +1003,7 @@ impl<'d, const N: usize, P: Pool<N>> Buffer<'d, N, P> {
 				}
 			}
 		};
+		sink.compact_if_fragmented();
 		result.set_context(Copy)
 	}
 
@@ -393,47 +1017,191 @@ impl<'d, const N: usize, P: Pool<N>> Buffer<'d, N, P> {
 
 	/// Skips up to `count` bytes.
 	pub fn skip(&mut self, count: usize) -> usize {
-		if count == self.count() {
+		if count >= self.count() {
+			let count = self.count();
 			self.clear();
 			return count
 		}
 
 		let mut seg_count = 0;
 		let mut skipped = 0;
-		for (i, seg) in self.data.iter_mut().enumerate() {
+		for seg in self.data.iter_mut() {
 			let remaining = count - skipped;
-			if remaining > 0 {
-				skipped += seg.consume(remaining);
-			} else {
-				seg_count = i;
+			if remaining == 0 {
 				break
 			}
-		}
-
-		let Err(_) = self.pool.try_use(|mut pool| {
-			use crate::pool::MutPool;
 
-			(&mut pool).collect(self.data.drain(seg_count));
-		}) else { return skipped };
-
-		// Returning segments to the pool failed, retain them instead.
+			skipped += seg.consume(remaining);
+			if seg.is_empty() {
+				seg_count += 1;
+			}
+		}
 
 		unsafe {
 			// Safety: bytes have been skipped in these segments.
 			self.data.dec_count(skipped);
 		}
+
+		if !self.keeps_capacity() {
+			let scrub = self.scrubber();
+			let Err(_) = self.pool.try_use(|mut pool| {
+				use crate::pool::MutPool;
+
+				(&mut pool).collect(self.data.drain(seg_count).update(scrub));
+			}) else { return skipped };
+		}
+
+		// Capacity is kept or returning segments to the pool failed, retain them
+		// instead.
+
 		self.data.rotate_back(seg_count);
 		// Drop empty, shared segments
 		self.data.buf.retain(|seg| seg.is_not_empty() || seg.is_exclusive());
+		self.release_unpinned();
 		skipped
 	}
 
+	/// Truncates the buffer to its first `len` bytes, dropping bytes from the back
+	/// and returning segments emptied by this to the pool. Useful to roll back an
+	/// over-long speculative write. Does nothing if the buffer holds `len` bytes or
+	/// fewer.
+	pub fn truncate(&mut self, len: usize) {
+		let emptied = self.data.truncate(len);
+		self.release_emptied(emptied);
+	}
+
+	/// Discards up to `count` bytes from the back of the buffer, returning the
+	/// number of bytes discarded.
+	pub fn discard_back(&mut self, count: usize) -> usize {
+		let count = count.min(self.count());
+		self.truncate(self.count() - count);
+		count
+	}
+
+	/// Returns the number of bytes of unused space left between segments, which
+	/// can be reclaimed by [compacting].
+	///
+	/// [compacting]: Self::compact
+	pub fn fragmentation(&self) -> usize { self.data.fragmentation() }
+
+	/// Compacts the buffer, moving data into unused space left in partially filled
+	/// segments, then returning emptied segments to the pool. Buffers are compacted
+	/// automatically as set by their [compaction policy]; this compacts regardless
+	/// of the policy.
+	///
+	/// [compaction policy]: BufferOptions#compaction
+	pub fn compact(&mut self) {
+		let emptied = self.data.compact();
+		self.release_emptied(emptied);
+	}
+
+	/// Returns `emptied` empty segments to the pool, or releases segments beyond
+	/// the pinned and retained capacity if the buffer keeps capacity.
+	fn release_emptied(&mut self, emptied: usize) {
+		if emptied == 0 {
+			return
+		}
+
+		if self.keeps_capacity() {
+			self.release_unpinned();
+			return
+		}
+
+		let scrub = self.scrubber();
+		let _ = self.pool.try_use(|mut pool| {
+			use crate::pool::MutPool;
+
+			(&mut pool).collect(self.data.drain_empty(emptied).update(scrub));
+		});
+	}
+
+	/// Compacts the buffer if its fragmentation exceeds the limit set by its
+	/// compaction policy.
+	fn compact_if_fragmented(&mut self) {
+		let threshold = match self.compaction_policy {
+			CompactionPolicy::Never      => return,
+			CompactionPolicy::Threshold  => self.fragmentation_threshold.max(1),
+			CompactionPolicy::Aggressive => 1,
+		};
+
+		if self.data.is_fragmented(threshold) {
+			self.compact();
+		}
+	}
+
+	/// Records the current write position, which can be rolled back to with
+	/// [`restore`]. This allows writing data transactionally, such as to frame a
+	/// message which must be written completely or not at all:
+	///
+	/// ```ignore
+	/// let checkpoint = buffer.checkpoint();
+	/// if let Err(error) = message.encode(&mut buffer) {
+	///     buffer.restore(checkpoint)?;
+	///     return Err(error)
+	/// }
+	/// ```
+	///
+	/// [`restore`]: Self::restore
+	pub fn checkpoint(&self) -> Checkpoint {
+		Checkpoint {
+			count: self.count(),
+			capacity: self.data.capacity(),
+		}
+	}
+
+	/// Rolls back data written since `checkpoint`, releasing segments claimed
+	/// since to the pool. Data must not be read from the buffer between the
+	/// checkpoint and the restore; if the buffer contains less data than it did at
+	/// the checkpoint, nothing is rolled back.
+	pub fn restore(&mut self, checkpoint: Checkpoint) -> Result {
+		let Checkpoint { count, capacity } = checkpoint;
+		if count >= self.count() {
+			return Ok(())
+		}
+
+		self.data.truncate(count);
+		let excess = self.data.capacity().saturating_sub(capacity);
+		let scrub = self.scrubber();
+		let Self { pool, data, .. } = self;
+		pool.collect(data.drain_empty(excess).update(scrub))
+			.context(Resize)
+	}
+
+	/// Returns `true` if the buffer starts with `prefix`. Only the leading segments
+	/// overlapping the prefix are compared.
+	pub fn starts_with(&self, prefix: &[u8]) -> bool {
+		prefix.len() <= self.count() &&
+			compare::common_prefix_len(self.data.iter_slices(), [prefix]) == prefix.len()
+	}
+
+	/// Returns `true` if the buffer ends with `suffix`. Only the trailing segments
+	/// overlapping the suffix are compared.
+	pub fn ends_with(&self, suffix: &[u8]) -> bool {
+		suffix.len() <= self.count() &&
+			compare::common_suffix_len(self.data.iter_slices(), [suffix]) == suffix.len()
+	}
+
 	/// Finds `pattern` within `range` in the buffer, returning the matching byte
 	/// range if found.
 	pub fn find(&self, pattern: impl Pattern) -> Option<Range<usize>> {
 		pattern.find_in(self.data.iter_slices())
 	}
 
+	/// Finds a match from `matcher` in the buffer, returning the matching byte range
+	/// if found. The matcher is reset and borrowed rather than moved, so one matcher
+	/// can be reused across searches.
+	pub fn find_with(&self, matcher: &mut impl Matcher) -> Option<Range<usize>> {
+		self.find(matcher)
+	}
+
+	/// Resumes a search with `matcher` over `range`, returning the first complete
+	/// match. A partial match at the end of `range` is kept in the matcher, so the
+	/// search can be resumed from `range.end` once more bytes are written.
+	pub(crate) fn resume_find(&self, matcher: &mut impl Matcher, range: Range<usize>) -> Option<Range<usize>> {
+		let offset = range.start;
+		pattern::resume_find(matcher, self.data.iter_slices_in_range(range), offset)
+	}
+
 	/// Finds `pattern` within `range` in the buffer, returning the matching byte
 	/// range if found.
 	pub fn find_in_range<R: RangeBounds<usize>>(&self, pattern: impl Pattern, range: R) -> Option<Range<usize>> {
@@ -441,19 +1209,248 @@ impl<'d, const N: usize, P: Pool<N>> Buffer<'d, N, P> {
 		pattern.find_in(self.data.iter_slices_in_range(range))
 	}
 
-	/// Returns the byte at position `pos`, or `None` if `pos` is out of bounds.
-	pub fn get(&self, mut pos: usize) -> Option<u8> {
-		if pos > self.count() { return None }
+	/// Finds `pattern` in the buffer, returning the matching range and bytes if
+	/// found. The matched bytes are borrowed from the buffer without copying.
+	pub fn find_match(&self, pattern: impl Pattern) -> Option<Match<'_>> {
+		self.find(pattern).map(|range| self.get_match(range))
+	}
+
+	/// Returns an iterator over all non-overlapping matches of `pattern` in the
+	/// buffer, with their ranges and bytes borrowed from the buffer.
+	pub fn matches<'b>(&'b self, pattern: impl Pattern + 'b) -> impl Iterator<Item = Match<'b>> + 'b {
+		pattern.matches_in(self.data.iter_slices())
+			   .map(|range| self.get_match(range))
+	}
+
+	/// Returns `true` if `pattern` is found in the buffer.
+	pub fn contains(&self, pattern: impl Pattern) -> bool {
+		pattern.contained_in(self.data.iter_slices())
+	}
+
+	/// Returns the number of non-overlapping matches of `pattern` in the buffer.
+	pub fn count_matches(&self, pattern: impl Pattern) -> usize {
+		pattern.matches_in(self.data.iter_slices()).count()
+	}
+
+	/// Returns an iterator over sub-buffers separated by non-overlapping matches
+	/// of `pattern`, sharing data with this buffer as with [`range`]. The buffer
+	/// is left unchanged.
+	///
+	/// [`range`]: Self::range
+	pub fn split<'b>(&'b self, pattern: impl Pattern + 'b) -> impl Iterator<Item = Self> + 'b {
+		self.split_ranges(pattern)
+			.map(|range| self.range(range))
+	}
+
+	/// Returns an iterator over the lines of the buffer as sub-buffers, sharing
+	/// data with this buffer as with [`range`]. Lines are split at line feeds or
+	/// carriage return-line feed sequences, which are not included. As with
+	/// [`str::lines`], a trailing empty line is not returned.
+	///
+	/// [`range`]: Self::range
+	pub fn lines(&self) -> impl Iterator<Item = Self> + '_ {
+		let count = self.count();
+		self.split_ranges(b'\n')
+			// Only the range after a trailing line feed starts at the end.
+			.filter(move |range| range.start < count)
+			.map(|Range { start, mut end }| {
+				if end > start && self.get(end - 1) == Some(b'\r') {
+					end -= 1;
+				}
+				self.range(start..end)
+			})
+	}
+
+	/// Returns an iterator over the ranges between non-overlapping matches of
+	/// `pattern`, searching lazily.
+	fn split_ranges<'b>(&'b self, pattern: impl Pattern + 'b) -> impl Iterator<Item = Range<usize>> + 'b {
+		let count = self.count();
+		let mut matches = pattern.matches_in(self.data.iter_slices());
+		let mut start = Some(0);
+		std::iter::from_fn(move || {
+			let from = start?;
+			let end = match matches.next() {
+				Some(found) => {
+					start = Some(found.end);
+					found.start
+				}
+				None => {
+					start = None;
+					count
+				}
+			};
+			Some(from..end)
+		})
+	}
+
+	fn get_match(&self, range: Range<usize>) -> Match<'_> {
+		let text = self.data.iter_slices_in_range(range.clone()).collect();
+		Match { range, text }
+	}
+
+	/// Replaces all occurrences of `pattern` with `replacement`, returning the
+	/// number of occurrences replaced. If every match has the same length as
+	/// `replacement` and lies in writable segments, matches are overwritten in
+	/// place. Otherwise, the data between matches is spliced into new segments,
+	/// shared or copied as with [`copy_range_to`].
+	///
+	/// [`copy_range_to`]: Self::copy_range_to
+	pub fn replace(&mut self, pattern: impl Pattern, replacement: &[u8]) -> Result<usize> {
+		let matches: Vec<_> = self.as_byte_str().matches(pattern).collect();
+		if matches.is_empty() { return Ok(0) }
+
+		if matches.iter().all(|range| range.len() == replacement.len()) &&
+			self.overwrite_ranges(&matches, replacement) {
+			return Ok(matches.len())
+		}
+
+		let mut spliced = Self::new(self.pool.clone(), self.options());
+		let mut last = 0;
+		for &Range { start, end } in &matches {
+			self.copy_range_to(last..start, &mut spliced)?;
+			spliced.write_from_slice(replacement)?;
+			last = end;
+		}
+		self.copy_range_to(last.., &mut spliced)?;
+		self.swap(&mut spliced);
+		Ok(matches.len())
+	}
+
+	/// Fills bytes within `range` with `byte`. Bytes in writable segments are
+	/// overwritten in place. If any byte lies in a shared segment, the buffer is
+	/// spliced into new segments as with [`replace`], leaving the data seen by
+	/// other buffers sharing it unchanged.
+	///
+	/// # Panics
+	///
+	/// Panics if the range is out of bounds.
+	///
+	/// [`replace`]: Self::replace
+	pub fn fill_range<R: RangeBounds<usize>>(&mut self, range: R, byte: u8) -> Result {
+		let range = slice::range(range, ..self.count());
+		if range.is_empty() ||
+			self.overwrite_ranges_with(slice::from_ref(&range), |dst, _| dst.fill(byte)) {
+			return Ok(())
+		}
+
+		let chunk = [byte; 256];
+		let mut spliced = Self::new(self.pool.clone(), self.options());
+		self.copy_range_to(..range.start, &mut spliced)?;
+		let mut remaining = range.len();
+		while remaining > 0 {
+			let len = remaining.min(chunk.len());
+			spliced.write_from_slice(&chunk[..len])?;
+			remaining -= len;
+		}
+		self.copy_range_to(range.end.., &mut spliced)?;
+		self.swap(&mut spliced);
+		Ok(())
+	}
+
+	/// Overwrites all bytes with zeros, including the spare capacity of owned
+	/// segments, then clears the buffer. The wipe uses volatile writes which the
+	/// compiler can't optimize away, making this suitable for scrubbing secrets
+	/// before segments are returned to the pool. Shared and borrowed segments are
+	/// left untouched, since their memory is owned elsewhere.
+	#[cfg(feature = "zeroize")]
+	pub fn zeroize(&mut self) {
+		for seg in self.data.buf.iter_mut() {
+			seg.zeroize();
+		}
+		self.clear();
+	}
+
+	/// Overwrites the sorted, non-overlapping `ranges` with `replacement`, which
+	/// must be the same length as each range. Returns `false` without writing if
+	/// any range lies in a shared segment.
+	fn overwrite_ranges(&mut self, ranges: &[Range<usize>], replacement: &[u8]) -> bool {
+		self.overwrite_ranges_with(ranges, |dst, pos| dst.copy_from_slice(&replacement[pos..][..dst.len()]))
+	}
 
+	/// Overwrites the sorted, non-overlapping `ranges`, calling `write` with each
+	/// writable slice within a range and its position relative to the range start.
+	/// Returns `false` without writing if any range lies in a shared segment.
+	fn overwrite_ranges_with(&mut self, ranges: &[Range<usize>], mut write: impl FnMut(&mut [u8], usize)) -> bool {
+		fn overlapping(ranges: &[Range<usize>], offset: usize, len: usize) -> &[Range<usize>] {
+			if len == 0 { return &[] }
+			let start = ranges.partition_point(|range| range.end <= offset);
+			let end = ranges.partition_point(|range| range.start < offset + len);
+			&ranges[start..end.max(start)]
+		}
+
+		let mut offset = 0;
 		for seg in self.data.iter() {
-			if seg.len() < pos {
-				pos -= seg.len();
-			} else {
-				return Some(seg[pos])
+			if seg.is_shared() && !overlapping(ranges, offset, seg.len()).is_empty() {
+				return false
 			}
+			offset += seg.len();
 		}
 
-		None
+		let mut offset = 0;
+		for seg in self.data.iter_mut() {
+			let len = seg.len();
+			for range in overlapping(ranges, offset, len) {
+				let start = range.start.max(offset);
+				let end = range.end.min(offset + len);
+				let mut src = start - range.start;
+				let mut remaining = end - start;
+				let mut pos = start - offset;
+				let (a, b) = seg.as_mut_slices().expect("segment should be exclusive");
+				for dst in [a, b] {
+					if pos >= dst.len() {
+						pos -= dst.len();
+						continue
+					}
+
+					let n = remaining.min(dst.len() - pos);
+					write(&mut dst[pos..][..n], src);
+					src += n;
+					remaining -= n;
+					pos = 0;
+				}
+			}
+			offset += len;
+		}
+		true
+	}
+
+	/// Returns the byte at position `pos`, or `None` if `pos` is out of bounds.
+	pub fn get(&self, pos: usize) -> Option<u8> {
+		(pos < self.count()).then(|| self[pos])
+	}
+
+	/// Returns a mutable reference to the byte at `pos`, or `None` if `pos` is out
+	/// of bounds. If the byte is in a segment sharing memory with another buffer or
+	/// borrowing a slice, the segment is copied first, as with [`write_slice_at`].
+	/// `None` is also returned if segments to copy into couldn't be claimed with
+	/// allocation set to [`Never`].
+	///
+	/// [`write_slice_at`]: Self::write_slice_at
+	/// [`Never`]: Allocate::Never
+	pub fn get_mut(&mut self, mut pos: usize) -> Option<&mut u8> {
+		if pos >= self.count() { return None }
+
+		let mut index = 0;
+		loop {
+			let len = self.data[index].len();
+			if pos >= len {
+				pos -= len;
+				index += 1;
+				continue
+			}
+
+			if self.data[index].is_shared() {
+				self.fork_segment(index).ok()?;
+				continue
+			}
+
+			let (a, b) = self.data[index].as_mut_slices()?;
+			return if pos < a.len() {
+				Some(&mut a[pos])
+			} else {
+				Some(&mut b[pos - a.len()])
+			}
+		}
 	}
 
 	/// Returns a new buffer containing data shared with this buffer in `range`.
@@ -478,6 +1475,84 @@ impl<'d, const N: usize, P: Pool<N>> Buffer<'d, N, P> {
 		(&self.data).into()
 	}
 
+	/// Returns the length of the longest prefix shared with `other`. Bytes are
+	/// compared a segment at a time.
+	pub fn common_prefix_len<const O: usize>(&self, other: &Buffer<'_, O, impl Pool<O>>) -> usize {
+		compare::common_prefix_len(self.data.iter_slices(), other.data.iter_slices())
+	}
+
+	/// Returns the length of the longest suffix shared with `other`. Bytes are
+	/// compared a segment at a time.
+	pub fn common_suffix_len<const O: usize>(&self, other: &Buffer<'_, O, impl Pool<O>>) -> usize {
+		compare::common_suffix_len(self.data.iter_slices(), other.data.iter_slices())
+	}
+
+	/// Returns the index of the first byte differing from `other`, or `None` if the
+	/// buffers are equal. If one buffer is a prefix of the other, the mismatch is at
+	/// the end of the shorter one.
+	pub fn first_mismatch<const O: usize>(&self, other: &Buffer<'_, O, impl Pool<O>>) -> Option<usize> {
+		let len = self.common_prefix_len(other);
+		(len < self.count().max(other.count())).then_some(len)
+	}
+
+	/// Iterates over UTF-8 characters decoded from the buffer, replacing invalid
+	/// sequences with [`U+FFFD REPLACEMENT CHARACTER`][U+FFFD]. Characters split
+	/// across segments are decoded without copying the buffer.
+	///
+	/// [U+FFFD]: char::REPLACEMENT_CHARACTER
+	pub fn chars(&self) -> Chars<impl FusedIterator<Item = u8> + '_> {
+		Chars::new(self.data.iter_slices().flatten().copied().fuse())
+	}
+
+	/// Decodes the contents of the buffer as UTF-8, replacing invalid sequences with
+	/// [`U+FFFD REPLACEMENT CHARACTER`][U+FFFD]. Borrows the buffer if its contents
+	/// are valid and contained in a single slice.
+	///
+	/// [U+FFFD]: char::REPLACEMENT_CHARACTER
+	pub fn to_utf8_lossy(&self) -> Cow<'_, str> {
+		self.as_byte_str().to_utf8_lossy()
+	}
+
+	/// Returns a [shared byte string](SharedByteStr) containing the contents of the
+	/// buffer, sharing its segments rather than borrowing the buffer. The byte
+	/// string can outlive this borrow, and doesn't copy data. Runs in `O(n)` time,
+	/// where `n` is the number of segments.
+	pub fn to_shared_byte_str(&self) -> SharedByteStr<'d, N> {
+		self.data.share_range(..).collect()
+	}
+
+	/// Returns an xxd-style [`HexDump`] of the buffer data for display.
+	pub fn hexdump(&self) -> HexDump<'_> {
+		self.as_byte_str().hexdump()
+	}
+
+	/// Moves all data out of the buffer, appending it to `vec` and returning the
+	/// number of bytes moved. The vector is grown with at most one allocation, and
+	/// segments are returned to the pool.
+	pub fn drain_into_vec(&mut self, vec: &mut Vec<u8>) -> usize {
+		let count = self.count();
+		vec.reserve_exact(count);
+		for slice in self.data.iter_slices() {
+			vec.extend_from_slice(slice);
+		}
+		self.clear();
+		count
+	}
+
+	/// Consumes the buffer, returning its data in a vector allocated once. Segments
+	/// are returned to the pool.
+	pub fn into_bytes(mut self) -> Vec<u8> {
+		let mut vec = Vec::new();
+		self.drain_into_vec(&mut vec);
+		vec
+	}
+
+	/// Consumes the buffer, returning its data as a [`ByteString`] allocated once.
+	/// Segments are returned to the pool.
+	pub fn into_byte_string(self) -> ByteString {
+		self.into_bytes().into()
+	}
+
 	/// Updates `hasher` with buffer data.
 	#[cfg(feature = "hash")]
 	pub fn hash(&self, hasher: &mut impl digest::Digest) {
@@ -497,13 +1572,48 @@ impl<'d, const N: usize, P: Pool<N>> Buffer<'d, N, P> {
 			hasher.update(slice);
 		}
 	}
+
+	/// Updates a keyed [`Mac`](digest::Mac) with buffer data.
+	#[cfg(feature = "hash")]
+	pub fn update_mac(&self, mac: &mut impl digest::Mac) {
+		for slice in self.data.iter_slices() {
+			mac.update(slice);
+		}
+	}
+
+	/// Updates a keyed [`Mac`](digest::Mac) with buffer data within `range`.
+	#[cfg(feature = "hash")]
+	pub fn update_mac_in_range<R: RangeBounds<usize>>(
+		&self,
+		range: R,
+		mac: &mut impl digest::Mac
+	) {
+		for slice in self.data.iter_slices_in_range(range) {
+			mac.update(slice);
+		}
+	}
+
+	/// Updates a hash function or [`Mac`](digest::Mac) with buffer data within
+	/// `range`.
+	#[cfg(feature = "hash")]
+	pub(crate) fn update_in_range<R: RangeBounds<usize>>(
+		&self,
+		range: R,
+		hasher: &mut impl digest::Update
+	) {
+		for slice in self.data.iter_slices_in_range(range) {
+			hasher.update(slice);
+		}
+	}
 }
 
 impl<'d, const N: usize, P: Pool<N>> Buffer<'d, N, P> {
 	pub(crate) fn full_segment_count(&self) -> usize {
-		let len = self.data.back_index().filter(|&i| {
-			self.data[i].is_full()
-		}).unwrap_or(self.data.len());
+		// The writable back segment, if any, isn't full.
+		let len = self.data
+					  .back_index()
+					  .unwrap_or(self.data.len())
+					  .min(self.data.len());
 
 		self.data.buf.range(..len).map(|seg|
 			seg.len()
@@ -584,6 +1694,44 @@ impl<const N: usize, P: Pool<N>> Seekable for Buffer<'_, N, P> {
 	fn seek_pos(&mut self) -> StreamResult<usize> { Ok(0) }
 }
 
+impl<const N: usize, P: Pool<N>> Index<usize> for Buffer<'_, N, P> {
+	type Output = u8;
+
+	/// Returns a reference to the byte at `index`.
+	///
+	/// # Panics
+	///
+	/// Panics if `index` is out of bounds.
+	fn index(&self, mut index: usize) -> &u8 {
+		let count = self.count();
+		assert!(index < count, "index {index} should be less than the buffer length {count}");
+		for seg in self.data.iter() {
+			if seg.len() <= index {
+				index -= seg.len();
+			} else {
+				return &seg[index]
+			}
+		}
+
+		unreachable!("index should be within a segment")
+	}
+}
+
+impl<const N: usize, P: Pool<N>> IndexMut<usize> for Buffer<'_, N, P> {
+	/// Returns a mutable reference to the byte at `index`, copying its segment first
+	/// if shared. See [`get_mut`](Buffer::get_mut) for details.
+	///
+	/// # Panics
+	///
+	/// Panics if `index` is out of bounds, or if its segment is shared and couldn't
+	/// be copied.
+	fn index_mut(&mut self, index: usize) -> &mut u8 {
+		let count = self.count();
+		assert!(index < count, "index {index} should be less than the buffer length {count}");
+		self.get_mut(index).expect("shared segment should be copied")
+	}
+}
+
 impl<const N: usize, Pa: Pool<N>, const O: usize, Pb: Pool<O>> PartialEq<Buffer<'_, O, Pb>> for Buffer<'_, N, Pa> {
 	fn eq(&self, other: &Buffer<'_, O, Pb>) -> bool {
 		self.data.iter().eq(other.data.iter())
@@ -610,3 +1758,91 @@ impl<const N: usize, P: Pool<N>, T: AsRef<[u8]>> PartialEq<T> for Buffer<'_, N,
 		self == other.as_ref()
 	}
 }
+
+impl<const N: usize, P: Pool<N>> PartialEq<ByteStr<'_>> for Buffer<'_, N, P> {
+	fn eq(&self, other: &ByteStr<'_>) -> bool {
+		self.count() == other.len() &&
+		self.data.iter_slices().flatten().eq(other.bytes())
+	}
+}
+
+impl<const N: usize, P: Pool<N>> PartialEq<Buffer<'_, N, P>> for ByteStr<'_> {
+	#[inline]
+	fn eq(&self, other: &Buffer<'_, N, P>) -> bool {
+		other == self
+	}
+}
+
+impl<const N: usize, P: Pool<N>> PartialEq<Buffer<'_, N, P>> for ByteString {
+	#[inline]
+	fn eq(&self, other: &Buffer<'_, N, P>) -> bool {
+		other == self
+	}
+}
+
+impl<const N: usize, P: Pool<N>> Eq for Buffer<'_, N, P> { }
+
+impl<const N: usize, P: Pool<N>> Hash for Buffer<'_, N, P> {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		// Hash the length then each slice, consistent with ByteStr and `[u8]`, so
+		// buffers with the same bytes have the same hash regardless of how they're
+		// segmented.
+		state.write_length_prefix(self.count());
+		for slice in self.data.iter_slices() {
+			state.write(slice);
+		}
+	}
+}
+
+impl<const N: usize, Pa: Pool<N>, const O: usize, Pb: Pool<O>> PartialOrd<Buffer<'_, O, Pb>> for Buffer<'_, N, Pa> {
+	fn partial_cmp(&self, other: &Buffer<'_, O, Pb>) -> Option<Ordering> {
+		Some(
+			self.data
+				.iter_slices()
+				.flatten()
+				.cmp(other.data.iter_slices().flatten())
+		)
+	}
+}
+
+impl<const N: usize, P: Pool<N>> Ord for Buffer<'_, N, P> {
+	fn cmp(&self, other: &Self) -> Ordering {
+		self.data
+			.iter_slices()
+			.flatten()
+			.cmp(other.data.iter_slices().flatten())
+	}
+}
+
+impl<const N: usize, P: Pool<N>> PartialOrd<[u8]> for Buffer<'_, N, P> {
+	fn partial_cmp(&self, other: &[u8]) -> Option<Ordering> {
+		Some(self.data.iter_slices().flatten().cmp(other))
+	}
+}
+
+impl<const N: usize, P: Pool<N>, T: AsRef<[u8]>> PartialOrd<T> for Buffer<'_, N, P> {
+	#[inline]
+	fn partial_cmp(&self, other: &T) -> Option<Ordering> {
+		self.partial_cmp(other.as_ref())
+	}
+}
+
+impl<const N: usize, P: Pool<N>> PartialOrd<ByteStr<'_>> for Buffer<'_, N, P> {
+	fn partial_cmp(&self, other: &ByteStr<'_>) -> Option<Ordering> {
+		Some(self.data.iter_slices().flatten().cmp(other.bytes()))
+	}
+}
+
+impl<const N: usize, P: Pool<N>> PartialOrd<Buffer<'_, N, P>> for ByteStr<'_> {
+	#[inline]
+	fn partial_cmp(&self, other: &Buffer<'_, N, P>) -> Option<Ordering> {
+		other.partial_cmp(self).map(Ordering::reverse)
+	}
+}
+
+impl<const N: usize, P: Pool<N>> PartialOrd<Buffer<'_, N, P>> for ByteString {
+	#[inline]
+	fn partial_cmp(&self, other: &Buffer<'_, N, P>) -> Option<Ordering> {
+		other.partial_cmp(self).map(Ordering::reverse)
+	}
+}
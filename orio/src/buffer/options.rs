@@ -27,6 +27,93 @@ use crate::SIZE;
 /// As with sharing, borrowing data is significantly faster than copying it, but
 /// the cost of storing many small segments may outweigh this speedup.
 ///
+/// # Maximum capacity
+///
+/// The maximum number of bytes the buffer may hold. Defaults to unlimited. Writes
+/// and reservations that would grow the buffer past this limit fail with a
+/// [`CapacityExceeded`] error rather than claiming or allocating more memory,
+/// protecting against unbounded growth when a peer sends more data than expected.
+/// Slices and segments pushed without copying, such as with [`Buffer::push_slice`],
+/// aren't limited.
+///
+/// # Watermarks
+///
+/// The high and low watermarks signal backpressure: a buffer holding more than its
+/// high watermark should stop being filled until it has been drained to its low
+/// watermark or below. Producers can poll [`Buffer::is_above_high_watermark`] and
+/// [`Buffer::is_below_low_watermark`] to pause and resume. Buffered sinks drain
+/// down to the low watermark whenever their buffer rises above the high watermark,
+/// rather than only draining full segments. By default, the high watermark is
+/// unlimited and the low watermark is `0`.
+///
+/// # Yielding
+///
+/// The number of bytes after which long-running operations filling the buffer,
+/// such as [`Source::fill_all`], return early with partial progress rather than
+/// looping until end-of-stream. Defaults to unlimited. This lets single-threaded
+/// servers interleave other work between chunks of a large transfer; callers
+/// should call the operation again until it reads no more bytes.
+///
+/// # Flush threshold
+///
+/// The number of buffered bytes at which a [`BufferedSink`] drains its entire
+/// buffer to the underlying sink, rather than only draining full segments.
+/// Defaults to unlimited. Lower values write data through sooner, trading
+/// throughput for latency; with a value of `0`, every write is drained through.
+/// The threshold can also be changed after the sink is created, with
+/// [`BufferedSink::set_flush_threshold`].
+///
+/// # Pinned capacity
+///
+/// The number of bytes of segment capacity the buffer keeps for itself after its
+/// data is read, rather than returning emptied segments to the pool. Defaults to
+/// `0`, returning all emptied segments. Long-lived buffers, such as per-connection
+/// buffers, can pin the capacity they typically use to recycle their own segments
+/// instead of round-tripping through the pool on every read. See also
+/// [`Buffer::with_reserved`].
+///
+/// # Retained spare capacity
+///
+/// The number of bytes of empty segment capacity the buffer keeps beyond its data
+/// after reading, rather than returning emptied segments to the pool. Defaults to
+/// `0`. Unlike pinned capacity, this is relative to the buffer's data, so a full
+/// buffer keeps spare room for writes while still returning segments after large
+/// transient peaks. Spare segments can be returned early with [`Buffer::release_spare`]
+/// or [`Buffer::shrink_to_fit`].
+///
+/// # Jumbo threshold
+///
+/// The minimum size of a reservation to be satisfied by a single "jumbo" segment,
+/// claimed with [`Pool::claim_jumbo`], rather than many segments of the pool's
+/// segment size. Defaults to unlimited, never claiming jumbo segments. Writing
+/// multi-megabyte payloads into one large segment avoids the overhead of claiming
+/// and tracking thousands of small ones. Jumbo segments aren't pooled by default.
+///
+/// # Compaction
+///
+/// Segments written to a buffer out of order, such as shared or borrowed segments
+/// pushed after a partially filled segment, leave gaps of unused space behind them,
+/// called *fragmentation*. The fragmentation threshold is the number of bytes of
+/// these gaps at which the buffer is compacted, moving data into the gaps and
+/// returning emptied segments to the pool. Defaults to `4096B`, half the default
+/// segment size. Free space in the front segment, left by reading, and in the back
+/// segment, left for writing, isn't counted.
+///
+/// The compaction policy decides when to compact: after writes which fragment the
+/// buffer past the threshold by default, after any fragmenting write, or never.
+/// Compaction is O(n) in the number of bytes after the first gap, so compacting
+/// aggressively trades write speed for memory. [`Buffer::compact`] compacts
+/// manually, regardless of the policy.
+///
+/// # Sensitive data
+///
+/// Whether the buffer holds sensitive data, such as keys or passwords. Defaults to
+/// `false`, and requires the `zeroize` feature. Sensitive buffers never share or
+/// borrow data, even with buffers they copy to, and zero their segments before
+/// returning them to the pool, so secrets don't linger in memory claimed by other
+/// buffers. Segments moved out of the buffer, such as by [`Source::fill`], are
+/// owned by the receiving buffer. [`BufferOptions::sensitive`] presets this mode.
+///
 /// # Allocation
 ///
 /// By default, the buffer will fallback to allocating memory if borrowing the pool
@@ -34,11 +121,36 @@ use crate::SIZE;
 /// allocate.
 ///
 /// [`Buffer::push_slice`]: super::Buffer::push_slice
+/// [`CapacityExceeded`]: super::CapacityExceeded
+/// [`Buffer::is_above_high_watermark`]: super::Buffer::is_above_high_watermark
+/// [`Buffer::is_below_low_watermark`]: super::Buffer::is_below_low_watermark
+/// [`Source::fill_all`]: crate::streams::Source::fill_all
+/// [`BufferedSink`]: crate::streams::BufferedSink
+/// [`BufferedSink::set_flush_threshold`]: crate::streams::BufferedSink::set_flush_threshold
+/// [`Buffer::with_reserved`]: super::Buffer::with_reserved
+/// [`Buffer::release_spare`]: super::Buffer::release_spare
+/// [`Buffer::shrink_to_fit`]: super::Buffer::shrink_to_fit
+/// [`Pool::claim_jumbo`]: crate::pool::Pool::claim_jumbo
+/// [`Source::fill`]: crate::streams::Source::fill
+/// [`BufferOptions::sensitive`]: Self::sensitive
+/// [`Buffer::compact`]: super::Buffer::compact
 #[derive(Copy, Clone, Debug)]
 #[non_exhaustive]
 pub struct BufferOptions {
 	pub share_threshold: usize,
 	pub borrow_threshold: usize,
+	pub max_capacity: usize,
+	pub high_watermark: usize,
+	pub low_watermark: usize,
+	pub yield_after: usize,
+	pub flush_threshold: usize,
+	pub pin_capacity: usize,
+	pub retain_spare: usize,
+	pub jumbo_threshold: usize,
+	pub fragmentation_threshold: usize,
+	pub compaction_policy: CompactionPolicy,
+	#[cfg(feature = "zeroize")]
+	pub sensitive: bool,
 	pub allocation: Allocate,
 }
 
@@ -72,6 +184,37 @@ impl Allocate {
 	}
 }
 
+/// The policy deciding when a buffer is compacted.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum CompactionPolicy {
+	/// Never compact automatically, only when [`Buffer::compact`] is called.
+	///
+	/// [`Buffer::compact`]: super::Buffer::compact
+	Never,
+	/// Compact when fragmentation reaches the fragmentation threshold.
+	#[default]
+	Threshold,
+	/// Compact whenever a write fragments the buffer, ignoring the threshold.
+	Aggressive,
+}
+
+impl CompactionPolicy {
+	/// Returns `true` if the policy is [`Never`](Self::Never).
+	pub fn is_never(&self) -> bool {
+		matches!(self, Self::Never)
+	}
+
+	/// Returns `true` if the policy is [`Threshold`](Self::Threshold).
+	pub fn is_threshold(&self) -> bool {
+		matches!(self, Self::Threshold)
+	}
+
+	/// Returns `true` if the policy is [`Aggressive`](Self::Aggressive).
+	pub fn is_aggressive(&self) -> bool {
+		matches!(self, Self::Aggressive)
+	}
+}
+
 impl Default for BufferOptions {
 	fn default() -> Self { Self::new() }
 }
@@ -82,6 +225,18 @@ impl BufferOptions {
 		Self {
 			share_threshold: SIZE / 8,
 			borrow_threshold: SIZE / 8,
+			max_capacity: usize::MAX,
+			high_watermark: usize::MAX,
+			low_watermark: 0,
+			yield_after: usize::MAX,
+			flush_threshold: usize::MAX,
+			pin_capacity: 0,
+			retain_spare: 0,
+			jumbo_threshold: usize::MAX,
+			fragmentation_threshold: SIZE / 2,
+			compaction_policy: CompactionPolicy::Threshold,
+			#[cfg(feature = "zeroize")]
+			sensitive: false,
 			allocation: Allocate::OnError,
 		}
 	}
@@ -98,6 +253,20 @@ impl BufferOptions {
 		}
 	}
 
+	/// Presets the options to create a buffer holding [sensitive data], such as
+	/// keys. The buffer never shares or borrows data, and zeroes its segments
+	/// before returning them to the pool.
+	///
+	/// [sensitive data]: Self#sensitive-data
+	#[cfg(feature = "zeroize")]
+	#[inline]
+	pub const fn sensitive() -> Self {
+		Self {
+			sensitive: true,
+			..Self::lean()
+		}
+	}
+
 	/// Returns the segment share threshold.
 	#[inline]
 	pub const fn share_threshold(&self) -> usize { self.share_threshold }
@@ -106,6 +275,51 @@ impl BufferOptions {
 	#[inline]
 	pub const fn borrow_threshold(&self) -> usize { self.borrow_threshold }
 
+	/// Returns the maximum number of bytes the buffer may hold.
+	#[inline]
+	pub const fn max_capacity(&self) -> usize { self.max_capacity }
+
+	/// Returns the high watermark.
+	#[inline]
+	pub const fn high_watermark(&self) -> usize { self.high_watermark }
+
+	/// Returns the low watermark.
+	#[inline]
+	pub const fn low_watermark(&self) -> usize { self.low_watermark }
+
+	/// Returns the number of bytes after which long-running operations yield.
+	#[inline]
+	pub const fn yield_after(&self) -> usize { self.yield_after }
+
+	/// Returns the number of bytes after which buffered sinks drain the buffer.
+	#[inline]
+	pub const fn flush_threshold(&self) -> usize { self.flush_threshold }
+
+	/// Returns the number of bytes of segment capacity kept by the buffer.
+	#[inline]
+	pub const fn pin_capacity(&self) -> usize { self.pin_capacity }
+
+	/// Returns the number of bytes of spare segment capacity kept by the buffer.
+	#[inline]
+	pub const fn retain_spare(&self) -> usize { self.retain_spare }
+
+	/// Returns the minimum reservation size for jumbo segments.
+	#[inline]
+	pub const fn jumbo_threshold(&self) -> usize { self.jumbo_threshold }
+
+	/// Returns the number of bytes of fragmentation at which the buffer is compacted.
+	#[inline]
+	pub const fn fragmentation_threshold(&self) -> usize { self.fragmentation_threshold }
+
+	/// Returns the compaction policy.
+	#[inline]
+	pub const fn compaction_policy(&self) -> CompactionPolicy { self.compaction_policy }
+
+	/// Returns `true` if the buffer holds sensitive data.
+	#[cfg(feature = "zeroize")]
+	#[inline]
+	pub const fn is_sensitive(&self) -> bool { self.sensitive }
+
 	/// Returns the segment allocation mode.
 	#[inline]
 	pub const fn allocation(&self) -> Allocate { self.allocation }
@@ -122,6 +336,73 @@ impl BufferOptions {
 		self.borrow_threshold = value;
 	}
 
+	/// Sets the maximum number of bytes the buffer may hold.
+	#[inline]
+	pub fn set_max_capacity(&mut self, value: usize) {
+		self.max_capacity = value;
+	}
+
+	/// Sets the high watermark.
+	#[inline]
+	pub fn set_high_watermark(&mut self, value: usize) {
+		self.high_watermark = value;
+	}
+
+	/// Sets the low watermark.
+	#[inline]
+	pub fn set_low_watermark(&mut self, value: usize) {
+		self.low_watermark = value;
+	}
+
+	/// Sets the number of bytes after which long-running operations yield.
+	#[inline]
+	pub fn set_yield_after(&mut self, value: usize) {
+		self.yield_after = value;
+	}
+
+	/// Sets the number of bytes after which buffered sinks drain the buffer.
+	#[inline]
+	pub fn set_flush_threshold(&mut self, value: usize) {
+		self.flush_threshold = value;
+	}
+
+	/// Sets the number of bytes of segment capacity kept by the buffer.
+	#[inline]
+	pub fn set_pin_capacity(&mut self, value: usize) {
+		self.pin_capacity = value;
+	}
+
+	/// Sets the number of bytes of spare segment capacity kept by the buffer.
+	#[inline]
+	pub fn set_retain_spare(&mut self, value: usize) {
+		self.retain_spare = value;
+	}
+
+	/// Sets the minimum reservation size for jumbo segments.
+	#[inline]
+	pub fn set_jumbo_threshold(&mut self, value: usize) {
+		self.jumbo_threshold = value;
+	}
+
+	/// Sets the number of bytes of fragmentation at which the buffer is compacted.
+	#[inline]
+	pub fn set_fragmentation_threshold(&mut self, value: usize) {
+		self.fragmentation_threshold = value;
+	}
+
+	/// Sets the compaction policy.
+	#[inline]
+	pub fn set_compaction_policy(&mut self, value: CompactionPolicy) {
+		self.compaction_policy = value;
+	}
+
+	/// Sets whether the buffer holds sensitive data.
+	#[cfg(feature = "zeroize")]
+	#[inline]
+	pub fn set_sensitive(&mut self, value: bool) {
+		self.sensitive = value;
+	}
+
 	/// Sets the segment allocation mode.
 	#[inline]
 	pub fn set_allocation(&mut self, value: Allocate) {
@@ -160,6 +441,78 @@ impl BufferOptions {
 		self
 	}
 
+	/// Sets the maximum number of bytes the buffer may hold.
+	#[inline]
+	pub const fn with_max_capacity(mut self, value: usize) -> Self {
+		self.max_capacity = value;
+		self
+	}
+
+	/// Sets the high and low watermarks.
+	#[inline]
+	pub const fn with_watermarks(mut self, high: usize, low: usize) -> Self {
+		self.high_watermark = high;
+		self.low_watermark = low;
+		self
+	}
+
+	/// Sets the number of bytes after which long-running operations yield.
+	#[inline]
+	pub const fn with_yield_after(mut self, value: usize) -> Self {
+		self.yield_after = value;
+		self
+	}
+
+	/// Sets the number of bytes after which buffered sinks drain the buffer.
+	#[inline]
+	pub const fn with_flush_threshold(mut self, value: usize) -> Self {
+		self.flush_threshold = value;
+		self
+	}
+
+	/// Sets the number of bytes of segment capacity kept by the buffer.
+	#[inline]
+	pub const fn with_pin_capacity(mut self, value: usize) -> Self {
+		self.pin_capacity = value;
+		self
+	}
+
+	/// Sets the number of bytes of spare segment capacity kept by the buffer.
+	#[inline]
+	pub const fn with_retain_spare(mut self, value: usize) -> Self {
+		self.retain_spare = value;
+		self
+	}
+
+	/// Sets the minimum reservation size for jumbo segments.
+	#[inline]
+	pub const fn with_jumbo_threshold(mut self, value: usize) -> Self {
+		self.jumbo_threshold = value;
+		self
+	}
+
+	/// Sets the number of bytes of fragmentation at which the buffer is compacted.
+	#[inline]
+	pub const fn with_fragmentation_threshold(mut self, value: usize) -> Self {
+		self.fragmentation_threshold = value;
+		self
+	}
+
+	/// Sets the compaction policy.
+	#[inline]
+	pub const fn with_compaction_policy(mut self, value: CompactionPolicy) -> Self {
+		self.compaction_policy = value;
+		self
+	}
+
+	/// Sets whether the buffer holds sensitive data.
+	#[cfg(feature = "zeroize")]
+	#[inline]
+	pub const fn with_sensitive(mut self, value: bool) -> Self {
+		self.sensitive = value;
+		self
+	}
+
 	/// Sets the segment allocation mode.
 	#[inline]
 	pub const fn with_allocation(mut self, value: Allocate) -> Self {
@@ -185,3 +538,74 @@ impl BufferOptions {
 		self.with_allocation(Allocate::OnError)
 	}
 }
+
+/// Options for a single copy between buffers, overriding the sink's [share
+/// threshold] for that copy. Passed to [`Buffer::copy_to_with`].
+///
+/// Data from a buffer holding [sensitive data] is always copied, regardless of
+/// these options.
+///
+/// [share threshold]: BufferOptions#share-threshold
+/// [sensitive data]: BufferOptions#sensitive-data
+/// [`Buffer::copy_to_with`]: super::Buffer::copy_to_with
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct CopyOptions {
+	/// The share threshold to copy with, or `None` to use the sink's.
+	pub share_threshold: Option<usize>,
+	/// Whether to always copy data, never sharing it. Useful when the source will
+	/// be modified after the copy, to avoid forking shared segments then. Takes
+	/// precedence over [`force_share`](Self::force_share).
+	pub force_copy: bool,
+	/// Whether to always share data, regardless of its size. Useful when memory
+	/// matters more than the cost of forking shared segments on later writes.
+	pub force_share: bool,
+}
+
+impl CopyOptions {
+	/// Creates a new set of copy options, using the sink's share threshold.
+	pub const fn new() -> Self {
+		Self {
+			share_threshold: None,
+			force_copy: false,
+			force_share: false,
+		}
+	}
+
+	/// Presets the options to always copy data.
+	#[inline]
+	pub const fn deep() -> Self {
+		Self {
+			force_copy: true,
+			..Self::new()
+		}
+	}
+
+	/// Presets the options to always share data.
+	#[inline]
+	pub const fn shared() -> Self {
+		Self {
+			force_share: true,
+			..Self::new()
+		}
+	}
+
+	/// Sets the share threshold.
+	#[inline]
+	pub const fn with_share_threshold(mut self, value: usize) -> Self {
+		self.share_threshold = Some(value);
+		self
+	}
+
+	/// Returns the share threshold to copy with, given the sink's share threshold.
+	pub const fn resolve_share_threshold(&self, sink_threshold: usize) -> usize {
+		if self.force_copy {
+			usize::MAX
+		} else if self.force_share {
+			0
+		} else if let Some(value) = self.share_threshold {
+			value
+		} else {
+			sink_threshold
+		}
+	}
+}
@@ -0,0 +1,79 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::ops::Deref;
+use std::slice;
+use crate::Buffer;
+use crate::pool::Pool;
+
+/// A guard pinning the readable data of a [`Buffer`] in place, returned by
+/// [`Buffer::pin_slices`].
+///
+/// The guard borrows the buffer, so it can't be written to, read from, compacted,
+/// or have its segments returned to the pool while the guard is alive. Pointers
+/// from the guard remain valid until it's dropped, making it sound to hand them
+/// to foreign code, such as a C codec, as long as that code doesn't keep them
+/// past the guard's lifetime or write through them.
+#[must_use = "the data is only pinned while the guard is alive"]
+pub struct PinnedSlices<'a> {
+	slices: Vec<&'a [u8]>,
+	count: usize,
+}
+
+/// A pointer to and length of a pinned slice, as passed to foreign code.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(C)]
+pub struct RawSlice {
+	/// A pointer to the first byte of the slice.
+	pub ptr: *const u8,
+	/// The number of bytes in the slice.
+	pub len: usize,
+}
+
+impl<'a> PinnedSlices<'a> {
+	/// Returns the total number of pinned bytes.
+	pub fn count(&self) -> usize { self.count }
+
+	/// Returns a pointer and length for each pinned slice, in order.
+	pub fn raw_slices(&self) -> Vec<RawSlice> {
+		self.slices
+			.iter()
+			.map(|slice| RawSlice {
+				ptr: slice.as_ptr(),
+				len: slice.len(),
+			})
+			.collect()
+	}
+}
+
+impl<'a> Deref for PinnedSlices<'a> {
+	type Target = [&'a [u8]];
+
+	fn deref(&self) -> &Self::Target {
+		&self.slices
+	}
+}
+
+impl<'a, 'b> IntoIterator for &'b PinnedSlices<'a> {
+	type Item = &'b &'a [u8];
+	type IntoIter = slice::Iter<'b, &'a [u8]>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.slices.iter()
+	}
+}
+
+impl<'d, const N: usize, P: Pool<N>> Buffer<'d, N, P> {
+	/// Pins the readable data in place, returning a guard containing a slice for
+	/// each non-empty segment. The buffer can't be modified until the guard is
+	/// dropped, so pointers into the slices can be passed to foreign code.
+	pub fn pin_slices(&self) -> PinnedSlices<'_> {
+		let slices: Vec<_> = self.data
+								 .iter_slices()
+								 .filter(|slice| !slice.is_empty())
+								 .collect();
+		PinnedSlices {
+			count: self.count(),
+			slices,
+		}
+	}
+}
@@ -1,14 +1,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::io;
-use std::io::{ErrorKind, IoSlice, Write};
-use crate::{Buffer, StreamResult as Result, BufferResult, StreamResult, ResultSetContext, ResultContext};
-use crate::BufferContext::{Drain, Fill};
+use std::ops::RangeBounds;
+use std::io::{BorrowedCursor, ErrorKind, IoSlice, Write};
+use num_traits::PrimInt;
+use crate::{Buffer, ByteString, StreamResult as Result, BufferResult, StreamResult, ResultSetContext, ResultContext};
+use crate::BufferContext::{self, Drain, Fill};
 use crate::pattern::{LineTerminator, Pattern};
 use crate::pool::Pool;
-use crate::segment::SliceRangeIter;
-use crate::streams::{BufSink, BufSource, Source, Utf8Match};
+use crate::streams::{BufSink, BufSource, EndOfStream, Source, Utf8Match};
 use crate::StreamContext::Read;
+use crate::util::trace;
 use super::read_partial_utf8_into;
 
 impl<'d, const N: usize, P: Pool<N>> Source<'d, N> for Buffer<'d, N, P> {
@@ -16,8 +18,18 @@ impl<'d, const N: usize, P: Pool<N>> Source<'d, N> for Buffer<'d, N, P> {
 		self.is_empty()
 	}
 
+	/// Moves up to `count` bytes into `sink`, limited by its remaining capacity.
+	///
+	/// # Errors
+	///
+	/// Returns a [`CapacityExceeded`] error if `sink` is at its maximum capacity.
+	///
+	/// [`CapacityExceeded`]: crate::CapacityExceeded
 	fn fill(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
-		if count == 0 { return Ok(0) }
+		if count == 0 || self.is_empty() { return Ok(0) }
+		let _span = trace::span!("fill", requested = count);
+		sink.check_capacity(1).set_context(Fill)?;
+		let count = count.min(sink.remaining_capacity());
 
 		// Use faster fill_all.
 		if count >= self.count() {
@@ -35,19 +47,23 @@ impl<'d, const N: usize, P: Pool<N>> Source<'d, N> for Buffer<'d, N, P> {
 			}
 		}).unwrap();
 
+		trace::event!("moving segments", bytes = moved, segments = full_count);
 		sink.data.extend(
 			self.data.drain(full_count)
 		);
 
 		if moved < count {
 			let remaining = count - moved;
+			let share = remaining >= sink.share_threshold && !self.is_sensitive();
 			let mut front = self.data
 							   .front_mut()
 							   .expect("should have one remaining segment");
-			if remaining >= sink.share_threshold {
+			if share {
+				trace::event!("sharing segment", bytes = remaining);
 				let shared = front.share(..remaining);
 				sink.data.push_back(shared);
 			} else {
+				trace::event!("copying bytes", bytes = remaining);
 				let (a, b) = front.as_slices_in_range(..remaining);
 				sink.write_from_slice(a).context(Fill)?;
 				sink.write_from_slice(b).context(Fill)?;
@@ -56,6 +72,7 @@ impl<'d, const N: usize, P: Pool<N>> Source<'d, N> for Buffer<'d, N, P> {
 			front.consume(remaining);
 		}
 
+		sink.compact_if_fragmented();
 		self.resize().set_context(Fill)?;
 		Ok(count)
 	}
@@ -64,16 +81,21 @@ impl<'d, const N: usize, P: Pool<N>> Source<'d, N> for Buffer<'d, N, P> {
 		self.resize().set_context(Fill)?;
 		let count = self.count();
 		if count == 0 { return Ok(0) }
+		let _span = trace::span!("fill_all");
+		sink.check_capacity(count).set_context(Fill)?;
+		trace::event!("moving segments", bytes = count, segments = self.data.len());
 
 		if self.data.len() == 1 {
 			let seg = self.data.pop_front().unwrap();
 			let len = seg.len();
 			sink.data.push_back(seg);
+			sink.compact_if_fragmented();
 			Ok(len)
 		} else {
 			// Take the internal ring buffer instead of draining, which should be
 			// significantly faster; similar to Buffer::clear.
 			sink.data.extend(self.take_buf());
+			sink.compact_if_fragmented();
 			Ok(count)
 		}
 	}
@@ -111,6 +133,14 @@ impl<'d, const N: usize, P: Pool<N>> BufSource<'d, N> for Buffer<'d, N, P> {
 		Ok(count)
 	}
 
+	fn read_into_cursor(&mut self, mut cursor: BorrowedCursor) -> Result<usize> {
+		let count = cursor.capacity().min(self.count());
+		for slice in self.data.iter_slices_in_range(..count) {
+			cursor.append(slice);
+		}
+		Ok(self.skip(count))
+	}
+
 	fn read_utf8<'s>(&mut self, buf: &'s mut String, mut count: usize) -> Result<&'s str> {
 		let len = buf.len();
 		count = count.min(self.count());
@@ -165,16 +195,161 @@ impl<'d, const N: usize, P: Pool<N>> BufSource<'d, N> for Buffer<'d, N, P> {
 				.map(|str| (str.len(), false).into())
 		}
 	}
+
+	fn read_line_bytes(&mut self, buf: &mut ByteString) -> Result<Utf8Match> {
+		let (count, skip, found) = match self.find(LineTerminator) {
+			Some(range) => (range.start, range.len(), true),
+			None => (self.count(), 0, false)
+		};
+
+		for slice in self.data.iter_slices_in_range(..count) {
+			buf.extend_from_slice(slice);
+		}
+		self.skip(count + skip);
+		Ok((count, found).into())
+	}
 }
 
-impl<'a: 'b, 'b, const N: usize> SliceRangeIter<'a, 'b, N> {
+impl<'d, const N: usize, P: Pool<N>> Buffer<'d, N, P> {
+	/// Returns [`IoSlice`]s over the readable data in the buffer, for passing to
+	/// vectored writes such as [`Write::write_vectored`] without copying. Data is
+	/// not consumed; call [`skip`] to advance past bytes that were written:
+	///
+	/// ```ignore
+	/// let written = socket.write_vectored(&buffer.as_io_slices())?;
+	/// buffer.skip(written);
+	/// ```
+	///
+	/// [`skip`]: Self::skip
+	pub fn as_io_slices(&self) -> Vec<IoSlice<'_>> {
+		self.data.iter_slices().collect_io_slices()
+	}
+
+	/// Returns [`IoSlice`]s over readable data within `range`, as with
+	/// [`as_io_slices`].
+	///
+	/// # Panics
+	///
+	/// Panics if `range` is out of bounds.
+	///
+	/// [`as_io_slices`]: Self::as_io_slices
+	pub fn as_io_slices_in_range<R: RangeBounds<usize>>(&self, range: R) -> Vec<IoSlice<'_>> {
+		self.data.iter_slices_in_range(range).collect_io_slices()
+	}
+
+	/// Returns the readable bytes in the first segment without consuming them, or
+	/// an empty slice if the buffer is empty.
+	pub fn first_slice(&self) -> &[u8] {
+		self.data.iter_slices().next().unwrap_or_default()
+	}
+
+	/// Copies bytes starting at `pos` into `buf` without consuming them, returning
+	/// the number of bytes copied. This is less than the length of `buf` if the end
+	/// of the buffer is reached first.
+	pub fn read_slice_at(&self, pos: usize, buf: &mut [u8]) -> usize {
+		if pos >= self.count() {
+			return 0
+		}
+
+		let end = self.count().min(pos + buf.len());
+		let mut count = 0;
+		for slice in self.data.iter_slices_in_range(pos..end) {
+			buf[count..][..slice.len()].copy_from_slice(slice);
+			count += slice.len();
+		}
+		count
+	}
+
+	/// Returns the next `count` bytes as one contiguous slice without consuming them,
+	/// moving data as needed. If fewer than `count` bytes are buffered, all buffered
+	/// bytes are returned. This is useful for parsers expecting contiguous input,
+	/// similar to [`BufRead::fill_buf`] with a minimum size.
+	///
+	/// If the bytes are already contiguous at the front of the buffer, they're
+	/// returned without copying. Otherwise they're copied into a new segment at the
+	/// front of the buffer, which may be larger than the segment size `N`.
+	///
+	/// [`BufRead::fill_buf`]: std::io::BufRead::fill_buf
+	pub fn contiguous(&mut self, count: usize) -> BufferResult<&[u8]> {
+		let count = count.min(self.count());
+		let front_len = self.data.iter_slices().next().map_or(0, <[u8]>::len);
+		if front_len < count {
+			let mut seg = if count <= N {
+				self.pool.claim_one().context(BufferContext::Read)?
+			} else {
+				Vec::with_capacity(count).into()
+			};
+			for slice in self.data.iter_slices_in_range(..count) {
+				seg.write(slice).expect("new segment should be writable");
+			}
+			seg.shift();
+			self.skip(count);
+			self.data.push_front(seg);
+		}
+
+		let front = self.data.iter().next().map_or(&[][..], |seg| seg.as_slices().0);
+		Ok(&front[..count])
+	}
+
+	/// Copies exactly the length of `buf` bytes starting at `pos` into `buf`
+	/// without consuming them, returning an end-of-stream error if the buffer ends
+	/// before `buf` could be filled.
+	pub fn read_slice_exact_at(&self, pos: usize, buf: &mut [u8]) -> BufferResult<usize> {
+		if self.count().saturating_sub(pos) < buf.len() {
+			return Err(EndOfStream::from(buf.len())).context(BufferContext::Read)
+		}
+
+		Ok(self.read_slice_at(pos, buf))
+	}
+
+	/// Reads an array with a size of `T` bytes at `pos`, without consuming it.
+	pub fn read_array_at<const T: usize>(&self, pos: usize) -> BufferResult<[u8; T]> {
+		let mut array = [0; T];
+		self.read_slice_exact_at(pos, &mut array)?;
+		Ok(array)
+	}
+
+	/// Reads a [`u8`] at `pos`, without consuming it.
+	#[inline]
+	pub fn read_u8_at(&self, pos: usize) -> BufferResult<u8> {
+		self.read_pod_at(pos)
+	}
+
+	/// Reads a big-endian integer at `pos`, without consuming it.
+	#[inline]
+	pub fn read_int_at<T: PrimInt + bytemuck::Pod>(&self, pos: usize) -> BufferResult<T> {
+		self.read_pod_at(pos).map(T::to_be)
+	}
+
+	/// Reads a little-endian integer at `pos`, without consuming it.
+	#[inline]
+	pub fn read_int_le_at<T: PrimInt + bytemuck::Pod>(&self, pos: usize) -> BufferResult<T> {
+		self.read_pod_at(pos).map(T::to_le)
+	}
+
+	/// Reads an arbitrary [`Pod`] data type at `pos`, without consuming it.
+	///
+	/// [`Pod`]: bytemuck::Pod
+	pub fn read_pod_at<T: bytemuck::Pod>(&self, pos: usize) -> BufferResult<T> {
+		let mut value = T::zeroed();
+		self.read_slice_exact_at(
+			pos,
+			bytemuck::bytes_of_mut(&mut value)
+		)?;
+		Ok(value)
+	}
+}
+
+trait CollectIoSlices<'b>: Iterator<Item = &'b [u8]> + Sized {
 	fn collect_io_slices(self) -> Vec<IoSlice<'b>> {
-		let mut vec: Vec<_> = self.map(IoSlice::new).collect();
-		vec.retain(|s| !s.is_empty());
-		vec
+		self.filter(|s| !s.is_empty())
+			.map(IoSlice::new)
+			.collect()
 	}
 }
 
+impl<'b, I: Iterator<Item = &'b [u8]>> CollectIoSlices<'b> for I { }
+
 impl<'a, const N: usize, P: Pool<N>> Buffer<'a, N, P> {
 	pub(crate) fn drain_into_writer(
 		&mut self,
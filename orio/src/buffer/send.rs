@@ -0,0 +1,103 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fmt;
+use std::fmt::{Debug, Formatter};
+use itertools::Itertools;
+use crate::{Buffer, BufferOptions, Seg, SIZE};
+use crate::pool::Pool;
+
+/// A buffer's data detached from its segment pool, which can be sent between
+/// threads, returned by [`Buffer::into_send`].
+///
+/// Without the `shared-pool` feature, segments share memory with non-atomic
+/// reference counts, so buffers can't be sent between threads directly. Converting
+/// a buffer with [`Buffer::into_send`] copies the data of segments sharing memory
+/// with other segments, such that every segment exclusively owns or borrows its
+/// memory. With the `shared-pool` feature, reference counts are atomic and buffers
+/// are [`Send`] themselves, so no data is copied. The receiving thread reattaches
+/// the data to a pool with [`into_buffer`] or [`From`].
+///
+/// [`into_buffer`]: Self::into_buffer
+pub struct SendBuffer<'d, const N: usize = SIZE> {
+	data: Vec<Seg<'d, N>>,
+	count: usize,
+	options: BufferOptions,
+}
+
+// Safety: without the `shared-pool` feature, every segment exclusively owns or
+// borrows its memory, so no reference count is reachable from outside the buffer,
+// and none is accessed through shared references. With it, reference counts are
+// atomic.
+unsafe impl<const N: usize> Send for SendBuffer<'_, N> { }
+unsafe impl<const N: usize> Sync for SendBuffer<'_, N> { }
+
+impl<'d, const N: usize> SendBuffer<'d, N> {
+	/// Returns the number of bytes in the buffer.
+	pub fn count(&self) -> usize { self.count }
+	/// Returns `true` if the buffer is empty.
+	pub fn is_empty(&self) -> bool { self.count == 0 }
+
+	/// Returns the options of the original buffer.
+	pub fn options(&self) -> BufferOptions { self.options }
+
+	/// Reattaches the data to `pool`, returning a buffer with the original buffer's
+	/// options.
+	pub fn into_buffer<P: Pool<N>>(self, pool: P) -> Buffer<'d, N, P> {
+		Buffer::new_buf(pool, self.data, self.options)
+	}
+}
+
+impl<'d, const N: usize, P: Pool<N>> From<SendBuffer<'d, N>> for Buffer<'d, N, P> {
+	/// Reattaches the data to the current thread's pool.
+	fn from(value: SendBuffer<'d, N>) -> Self {
+		value.into_buffer(P::get())
+	}
+}
+
+impl<const N: usize> Debug for SendBuffer<'_, N> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.debug_struct("SendBuffer")
+			.field("data", &self.data)
+			.field("options", &self.options)
+			.finish()
+	}
+}
+
+impl<'d, const N: usize, P: Pool<N>> Buffer<'d, N, P> {
+	/// Detaches the buffer's data from its pool so it can be sent to another thread.
+	/// Empty segments are returned to the pool.
+	///
+	/// Without the `shared-pool` feature, segments sharing memory with other
+	/// segments, such as those from [`copy_to`] or [`to_shared_byte_str`], have
+	/// their data copied into newly allocated, unpooled segments. This costs a copy
+	/// and an allocation per shared segment, so sending data shared by many buffers
+	/// is as expensive as writing it again. With the `shared-pool` feature, shared
+	/// memory is reference-counted atomically and is sent without copying.
+	///
+	/// [`copy_to`]: Self::copy_to
+	/// [`to_shared_byte_str`]: Self::to_shared_byte_str
+	pub fn into_send(mut self) -> SendBuffer<'d, N> {
+		let scrub = self.scrubber();
+		let mut data = self.take_buf();
+		let count = data.count();
+		let segments = data.drain(data.len())
+						   .map(|seg|
+							   if cfg!(not(feature = "shared-pool")) && seg.is_shared() && !seg.is_borrowed() {
+								   let (a, b) = seg.as_slices();
+								   let mut copy: Seg<N> = Vec::with_capacity(seg.len()).into();
+								   copy.write(a).expect("new segment should be writable");
+								   copy.write(b).expect("new segment should be writable");
+								   copy
+							   } else {
+								   seg
+							   }
+						   )
+						   .collect();
+		let _ = self.pool.collect(data.drain_all_empty().update(scrub));
+		SendBuffer {
+			data: segments,
+			count,
+			options: self.options(),
+		}
+	}
+}
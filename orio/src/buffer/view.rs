@@ -0,0 +1,191 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::io;
+use std::mem::size_of;
+use std::ops::Range;
+use num_traits::PrimInt;
+use crate::{Buffer, BufferResult as Result, ResultContext, SIZE};
+use crate::BufferContext::Read;
+use crate::pattern::Pattern;
+use crate::pool::{DefaultPoolContainer, Pool};
+use crate::streams::EndOfStream;
+use super::read_partial_utf8_into;
+
+/// A read-only view of a [`Buffer`] with its own read position, returned by
+/// [`Buffer::view`].
+///
+/// Reading from a view advances only the view's position, leaving the buffer's
+/// data in place, like the buffer's `*_at` methods such as [`read_slice_at`]. Any
+/// number of views can read the same buffer at once, so that several parsers can
+/// inspect buffered data without copying or consuming it. Views borrow the buffer,
+/// so it can't be modified while they're alive.
+///
+/// [`read_slice_at`]: Buffer::read_slice_at
+pub struct BufferView<'b, 'd, const N: usize = SIZE, P: Pool<N> = DefaultPoolContainer> {
+	buffer: &'b Buffer<'d, N, P>,
+	pos: usize,
+}
+
+impl<const N: usize, P: Pool<N>> Clone for BufferView<'_, '_, N, P> {
+	fn clone(&self) -> Self { *self }
+}
+
+impl<const N: usize, P: Pool<N>> Copy for BufferView<'_, '_, N, P> { }
+
+impl<'b, 'd, const N: usize, P: Pool<N>> BufferView<'b, 'd, N, P> {
+	/// Returns the viewed buffer.
+	pub fn buffer(&self) -> &'b Buffer<'d, N, P> { self.buffer }
+	/// Returns the read position, from the front of the buffer.
+	pub fn pos(&self) -> usize { self.pos }
+	/// Returns the number of bytes after the read position.
+	pub fn remaining(&self) -> usize { self.buffer.count() - self.pos }
+	/// Returns `true` if no bytes remain after the read position.
+	pub fn is_empty(&self) -> bool { self.remaining() == 0 }
+
+	/// Sets the read position, clamped to the buffer length.
+	pub fn set_pos(&mut self, pos: usize) {
+		self.pos = pos.min(self.buffer.count());
+	}
+
+	/// Returns `true` if at least `count` bytes remain, mirroring
+	/// [`BufSource::request`].
+	///
+	/// [`BufSource::request`]: crate::streams::BufSource::request
+	pub fn request(&self, count: usize) -> bool {
+		self.remaining() >= count
+	}
+
+	/// Returns an end-of-stream error if fewer than `count` bytes remain, mirroring
+	/// [`BufSource::require`].
+	///
+	/// [`BufSource::require`]: crate::streams::BufSource::require
+	pub fn require(&self, count: usize) -> Result {
+		if self.request(count) {
+			Ok(())
+		} else {
+			Err(EndOfStream::from(count)).context(Read)
+		}
+	}
+
+	/// Advances the read position by up to `count` bytes, returning the number of
+	/// bytes skipped.
+	pub fn skip(&mut self, count: usize) -> usize {
+		let count = count.min(self.remaining());
+		self.pos += count;
+		count
+	}
+
+	/// Returns the remaining bytes in the segment at the read position. An empty
+	/// slice is returned if no bytes remain.
+	pub fn first_slice(&self) -> &'b [u8] {
+		self.buffer
+			.data
+			.iter_slices_in_range(self.pos..)
+			.find(|slice| !slice.is_empty())
+			.unwrap_or_default()
+	}
+
+	/// Reads bytes into a slice, returning the number of bytes read.
+	pub fn read_slice(&mut self, buf: &mut [u8]) -> usize {
+		let count = self.buffer.read_slice_at(self.pos, buf);
+		self.pos += count;
+		count
+	}
+
+	/// Reads the exact length of bytes into a slice, or returns an end-of-stream
+	/// error if the slice could not be filled. The position isn't advanced if an
+	/// error is returned.
+	pub fn read_slice_exact(&mut self, buf: &mut [u8]) -> Result<usize> {
+		let count = self.buffer.read_slice_exact_at(self.pos, buf)?;
+		self.pos += count;
+		Ok(count)
+	}
+
+	/// Reads an array with a size of `T` bytes.
+	pub fn read_array<const T: usize>(&mut self) -> Result<[u8; T]> {
+		let array = self.buffer.read_array_at(self.pos)?;
+		self.pos += T;
+		Ok(array)
+	}
+
+	/// Reads a [`u8`].
+	pub fn read_u8(&mut self) -> Result<u8> { self.read_pod() }
+	/// Reads a big-endian [`u16`].
+	pub fn read_u16(&mut self) -> Result<u16> { self.read_int() }
+	/// Reads a little-endian [`u16`].
+	pub fn read_u16_le(&mut self) -> Result<u16> { self.read_int_le() }
+	/// Reads a big-endian [`u32`].
+	pub fn read_u32(&mut self) -> Result<u32> { self.read_int() }
+	/// Reads a little-endian [`u32`].
+	pub fn read_u32_le(&mut self) -> Result<u32> { self.read_int_le() }
+	/// Reads a big-endian [`u64`].
+	pub fn read_u64(&mut self) -> Result<u64> { self.read_int() }
+	/// Reads a little-endian [`u64`].
+	pub fn read_u64_le(&mut self) -> Result<u64> { self.read_int_le() }
+
+	/// Reads a big-endian integer.
+	pub fn read_int<T: PrimInt + bytemuck::Pod>(&mut self) -> Result<T> {
+		self.read_pod().map(T::to_be)
+	}
+
+	/// Reads a little-endian integer.
+	pub fn read_int_le<T: PrimInt + bytemuck::Pod>(&mut self) -> Result<T> {
+		self.read_pod().map(T::to_le)
+	}
+
+	/// Reads an arbitrary [`Pod`] data type.
+	///
+	/// [`Pod`]: bytemuck::Pod
+	pub fn read_pod<T: bytemuck::Pod>(&mut self) -> Result<T> {
+		let value = self.buffer.read_pod_at(self.pos)?;
+		self.pos += size_of::<T>();
+		Ok(value)
+	}
+
+	/// Reads up to `count` UTF-8 bytes into `buf`, returning a slice of `buf`
+	/// containing the read data. If the bytes aren't valid UTF-8, or `count` falls
+	/// within a character, a UTF-8 error is returned and `buf` will contain the
+	/// valid data before the error. The position isn't advanced if an error is
+	/// returned.
+	pub fn read_utf8<'s>(&mut self, buf: &'s mut String, count: usize) -> Result<&'s str> {
+		let len = buf.len();
+		let count = count.min(self.remaining());
+		buf.reserve(count);
+		let read = read_partial_utf8_into(
+			self.buffer.data.iter_slices_in_range(self.pos..self.pos + count),
+			buf
+		).context(Read)?;
+		self.pos += read;
+		Ok(&buf[len..])
+	}
+
+	/// Finds `pattern` after the read position, returning the matching byte range
+	/// relative to the read position if found.
+	pub fn find(&self, pattern: impl Pattern) -> Option<Range<usize>> {
+		self.buffer.find_in_range(pattern, self.pos..)
+	}
+}
+
+impl<const N: usize, P: Pool<N>> io::Read for BufferView<'_, '_, N, P> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		Ok(self.read_slice(buf))
+	}
+}
+
+impl<const N: usize, P: Pool<N>> io::BufRead for BufferView<'_, '_, N, P> {
+	fn fill_buf(&mut self) -> io::Result<&[u8]> {
+		Ok(self.first_slice())
+	}
+
+	fn consume(&mut self, amt: usize) {
+		self.skip(amt);
+	}
+}
+
+impl<'d, const N: usize, P: Pool<N>> Buffer<'d, N, P> {
+	/// Returns a read-only [view](BufferView) of the buffer, with its own read
+	/// position starting at the front.
+	pub fn view(&self) -> BufferView<'_, 'd, N, P> {
+		BufferView { buffer: self, pos: 0 }
+	}
+}
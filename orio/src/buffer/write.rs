@@ -2,16 +2,21 @@
 
 use std::collections::vec_deque;
 use std::io;
-use std::io::{BorrowedBuf, ErrorKind, IoSliceMut, Read};
+use std::io::{BorrowedBuf, BorrowedCursor, ErrorKind, IoSliceMut, Read};
 use std::iter::FilterMap;
 use std::mem::MaybeUninit;
 use std::ops::RangeTo;
-use crate::{Buffer, BufferResult, ResultContext, Seg, StreamResult as Result};
-use crate::BufferContext::{Drain, Fill};
+use num_traits::PrimInt;
+use crate::{Allocate, Buffer, BufferResult, ResultContext, ResultSetContext, Seg, Slot, SIZE, StreamResult as Result};
+use crate::BufferContext::{self, Drain, Fill};
 use crate::streams::{BufSink, Sink, Source};
 use crate::pool::Pool;
 use crate::segment::RBuf;
 use crate::StreamContext::Write;
+use crate::util::trace;
+
+/// A block of zeroes, borrowed or copied from to write zeroes.
+static ZEROES: [u8; SIZE] = [0; SIZE];
 
 impl<'d, const N: usize, P: Pool<N>> Buffer<'d, N, P> {
 	/// Pushes a string reference to the buffer without copying its data. This is
@@ -37,18 +42,247 @@ impl<'d, const N: usize, P: Pool<N>> Buffer<'d, N, P> {
 		}
 	}
 
-	/// Pushes a segment to the buffer.
+	/// Pushes a segment to the buffer, compacting if this fragments the buffer
+	/// past its [compaction] limit.
+	///
+	/// [compaction]: crate::BufferOptions#compaction
 	pub fn push_segment(&mut self, value: Seg<'d, N>) {
 		self.data.push_back(value);
+		self.compact_if_fragmented();
+	}
+
+	/// Writes `value` to the front of the buffer, before existing data. Bytes are
+	/// written into free space in front of the first segment if it's writable,
+	/// then into new segments pushed to the front, so existing data is never moved.
+	/// This allows a header to be prepended once the length of the payload after it
+	/// is known.
+	pub fn push_front_slice(&mut self, mut value: &[u8]) -> BufferResult {
+		self.check_capacity(value.len())?;
+		if let Some(mut front) = self.data.front_mut() {
+			let count = front.prepend(value).unwrap_or_default();
+			value = &value[..value.len() - count];
+		}
+
+		while !value.is_empty() {
+			let mut seg = match self.allocation {
+				Allocate::Always => Seg::default(),
+				Allocate::OnError => self.pool.claim_one().unwrap_or_default(),
+				Allocate::Never => self.pool.claim_one().context(BufferContext::Write)?
+			};
+			let count = seg.prepend(value).expect("claimed segment should be writable");
+			value = &value[..value.len() - count];
+			self.data.push_front(seg);
+		}
+		Ok(())
+	}
+
+	/// Writes a [`u8`] to the front of the buffer. See [`push_front_slice`].
+	///
+	/// [`push_front_slice`]: Self::push_front_slice
+	#[inline]
+	pub fn write_front_u8(&mut self, value: u8) -> BufferResult { self.write_front_pod(value) }
+
+	/// Writes a big-endian [`u16`] to the front of the buffer.
+	#[inline]
+	pub fn write_front_u16(&mut self, value: u16) -> BufferResult { self.write_front_int(value) }
+
+	/// Writes a little-endian [`u16`] to the front of the buffer.
+	#[inline]
+	pub fn write_front_u16_le(&mut self, value: u16) -> BufferResult { self.write_front_int_le(value) }
+
+	/// Writes a big-endian [`u32`] to the front of the buffer.
+	#[inline]
+	pub fn write_front_u32(&mut self, value: u32) -> BufferResult { self.write_front_int(value) }
+
+	/// Writes a little-endian [`u32`] to the front of the buffer.
+	#[inline]
+	pub fn write_front_u32_le(&mut self, value: u32) -> BufferResult { self.write_front_int_le(value) }
+
+	/// Writes a big-endian [`u64`] to the front of the buffer.
+	#[inline]
+	pub fn write_front_u64(&mut self, value: u64) -> BufferResult { self.write_front_int(value) }
+
+	/// Writes a little-endian [`u64`] to the front of the buffer.
+	#[inline]
+	pub fn write_front_u64_le(&mut self, value: u64) -> BufferResult { self.write_front_int_le(value) }
+
+	/// Writes a big-endian integer to the front of the buffer.
+	#[inline]
+	pub fn write_front_int<T: PrimInt + bytemuck::Pod>(&mut self, value: T) -> BufferResult {
+		self.write_front_pod(value.to_be())
+	}
+
+	/// Writes a little-endian integer to the front of the buffer.
+	#[inline]
+	pub fn write_front_int_le<T: PrimInt + bytemuck::Pod>(&mut self, value: T) -> BufferResult {
+		self.write_front_pod(value.to_le())
+	}
+
+	/// Writes an arbitrary [`Pod`] data type to the front of the buffer.
+	///
+	/// [`Pod`]: bytemuck::Pod
+	#[inline]
+	pub fn write_front_pod<T: bytemuck::Pod>(&mut self, value: T) -> BufferResult {
+		self.push_front_slice(bytemuck::bytes_of(&value))
+	}
+
+	/// Reserves a [`Slot`] of `len` zeroed bytes at the back of the buffer, to be
+	/// patched once its data is known. The slot is always written into memory owned
+	/// by the buffer, never borrowed.
+	///
+	/// # Errors
+	///
+	/// Returns a [`CapacityExceeded`] error if `len` more bytes would exceed the
+	/// buffer's maximum capacity.
+	///
+	/// [`CapacityExceeded`]: crate::CapacityExceeded
+	pub fn reserve_slot(&mut self, len: usize) -> BufferResult<Slot> {
+		let pos = self.count();
+		self.reserve(len)?;
+		let mut count = len;
+		while count > 0 {
+			let mut zeroes = &ZEROES[..count.min(ZEROES.len())];
+			count -= self.data.write_back(
+				&mut zeroes,
+				"buffer should have writable segments after reserve"
+			);
+		}
+		Ok(Slot { pos, len })
+	}
+
+	/// Writes `value` over bytes starting at `pos`, without changing the length of
+	/// the buffer.
+	///
+	/// Segments sharing memory with another buffer or borrowing a slice can't be
+	/// written in place, so they're first copied into segments claimed from the
+	/// pool, or allocated as set by the allocation option. This costs a copy of each
+	/// shared segment written, up to the segment size; [`make_exclusive`] can be
+	/// called beforehand to pay this cost upfront, in one pass.
+	///
+	/// # Errors
+	///
+	/// Returns an error if segments to copy into couldn't be claimed with allocation
+	/// set to [`Never`]. Bytes in segments before the failed copy are written.
+	///
+	/// # Panics
+	///
+	/// Panics if the written range is out of bounds.
+	///
+	/// [`make_exclusive`]: Self::make_exclusive
+	/// [`Never`]: crate::Allocate::Never
+	pub fn write_slice_at(&mut self, mut pos: usize, mut value: &[u8]) -> BufferResult {
+		let end = pos + value.len();
+		assert!(
+			end <= self.count(),
+			"write range end {end} should be within the buffer length {}",
+			self.count()
+		);
+
+		let mut index = 0;
+		while !value.is_empty() {
+			let len = self.data[index].len();
+			if pos >= len {
+				pos -= len;
+				index += 1;
+				continue
+			}
+
+			if self.data[index].is_shared() {
+				self.fork_segment(index)?;
+				continue
+			}
+
+			let (a, b) = self.data[index].as_mut_slices().expect("exclusive segment should be writable");
+			for slice in [a, b] {
+				if pos >= slice.len() {
+					pos -= slice.len();
+					continue
+				}
+
+				let count = slice[pos..].len().min(value.len());
+				slice[pos..][..count].copy_from_slice(&value[..count]);
+				value = &value[count..];
+				pos = 0;
+			}
+			index += 1;
+		}
+		Ok(())
+	}
+
+	/// Writes a [`u8`] at `pos`. See [`write_slice_at`] for details.
+	///
+	/// [`write_slice_at`]: Self::write_slice_at
+	#[inline]
+	pub fn write_u8_at(&mut self, pos: usize, value: u8) -> BufferResult {
+		self.write_pod_at(pos, value)
+	}
+
+	/// Writes a big-endian integer at `pos`.
+	#[inline]
+	pub fn write_int_at<T: PrimInt + bytemuck::Pod>(&mut self, pos: usize, value: T) -> BufferResult {
+		self.write_pod_at(pos, value.to_be())
+	}
+
+	/// Writes a little-endian integer at `pos`.
+	#[inline]
+	pub fn write_int_le_at<T: PrimInt + bytemuck::Pod>(&mut self, pos: usize, value: T) -> BufferResult {
+		self.write_pod_at(pos, value.to_le())
+	}
+
+	/// Writes an arbitrary [`Pod`] data type at `pos`.
+	///
+	/// [`Pod`]: bytemuck::Pod
+	#[inline]
+	pub fn write_pod_at<T: bytemuck::Pod>(&mut self, pos: usize, value: T) -> BufferResult {
+		self.write_slice_at(pos, bytemuck::bytes_of(&value))
+	}
+
+	/// Reserves space for up to `count` bytes, then calls `fill` with a cursor over
+	/// contiguous spare capacity at the back of the buffer. Bytes written to the
+	/// cursor are added to the buffer without an intermediate copy, returning the
+	/// number of bytes written along with the result of `fill`. This allows custom
+	/// [`Source`] implementations to read directly into buffer memory.
+	///
+	/// The cursor may have a capacity of less than `count` bytes, as it's limited
+	/// to the spare capacity of one segment and the buffer's remaining capacity;
+	/// call repeatedly to write more.
+	pub fn with_spare_capacity<R>(
+		&mut self,
+		count: usize,
+		fill: impl FnOnce(BorrowedCursor<'_>) -> R
+	) -> BufferResult<(usize, R)> {
+		if count == 0 {
+			let mut empty = BorrowedBuf::from(&mut [] as &mut [MaybeUninit<u8>]);
+			return Ok((0, fill(empty.unfilled())))
+		}
+
+		self.check_capacity(1)?;
+		let count = count.min(self.remaining_capacity());
+		self.reserve(count.min(N))?;
+		let mut seg = self.data
+						  .back_mut()
+						  .expect("buffer should have a writable segment after reserve");
+		let (spare, _) = seg.spare_capacity_mut();
+		let len = count.min(spare.len());
+		let mut buf = BorrowedBuf::from(&mut spare[..len]);
+		let result = fill(buf.unfilled());
+		let written = buf.len();
+		unsafe {
+			// Safety: the borrowed buffer guarantees its filled bytes are initialized.
+			seg.inc_len(written);
+		}
+		Ok((written, result))
 	}
 }
 
 impl<'d, const N: usize, P: Pool<N>> Sink<'d, N> for Buffer<'d, N, P> {
 	fn drain(&mut self, source: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		let _span = trace::span!("drain", requested = count);
 		source.fill(self, count).context(Drain)
 	}
 
 	fn drain_all(&mut self, source: &mut Buffer<'d, N, impl Pool<N>>) -> BufferResult<usize> {
+		let _span = trace::span!("drain_all");
 		source.fill_all(self).context(Drain)
 	}
 }
@@ -74,6 +308,28 @@ impl<'d, const N: usize, P: Pool<N>> BufSink<'d, N> for Buffer<'d, N, P> {
 		Ok(count)
 	}
 
+	/// Writes `count` zero bytes. Runs of zeroes at least as long as the borrow
+	/// threshold are borrowed from a static block of zeroes rather than written,
+	/// the rest are written without a temporary slice.
+	fn write_zeroes(&mut self, mut count: usize) -> Result {
+		self.check_capacity(count).context(Write)?;
+		while count > 0 && count >= self.borrow_threshold {
+			let len = count.min(ZEROES.len());
+			self.push_segment(Seg::from_slice(&ZEROES[..len]));
+			count -= len;
+		}
+
+		self.reserve(count).context(Write)?;
+		while count > 0 {
+			let mut zeroes = &ZEROES[..count.min(ZEROES.len())];
+			count -= self.data.write_back(
+				&mut zeroes,
+				"buffer should have writable segments after reserve"
+			);
+		}
+		Ok(())
+	}
+
 	fn write_u8(&mut self, value: u8) -> Result {
 		self.reserve(1).context(Write)?;
 		let mut seg = self.data.back_mut().expect(
@@ -161,9 +417,11 @@ impl<'a, const N: usize, P: Pool<N>> Buffer<'a, N, P> {
 		if count == 0 {
 			return Ok(0)
 		}
+		self.check_capacity(1).set_context(Fill)?;
+		let count = count.min(self.remaining_capacity());
 
 		let mut read = 0;
-		if self.capacity() > 0 {
+		if self.limit() > 0 {
 			read = self.fill_spare_from_reader(reader, count, allow_vectored)?;
 			if read >= count || read == 0 {
 				return Ok(read)
@@ -229,25 +487,20 @@ impl<'a, const N: usize, P: Pool<N>> Buffer<'a, N, P> {
 }
 
 fn read_into_buf(reader: &mut impl Read, mut buf: BorrowedBuf, count: &mut usize) -> io::Result<()> {
-	let mut written;
 	let result = try {
 		while buf.len() < buf.capacity() {
-			let mut cursor = buf.unfilled();
-			written = cursor.written();
-			match reader.read_buf(cursor.reborrow()) {
-				Ok(_) => {
-					*count += cursor.written();
-					if cursor.written() == written {
-						// No more bytes read.
-						break
-					}
-				}
+			// Count from the filled length; the cursor's written count may include
+			// bytes filled before it was created.
+			let filled = buf.len();
+			let result = reader.read_buf(buf.unfilled());
+			let read = buf.len() - filled;
+			*count += read;
+			match result {
+				Ok(()) if read == 0 => break, // No more bytes read.
+				Ok(()) => { }
 				Err(e) if e.kind() == ErrorKind::Interrupted => { }
-				error => {
-					*count += cursor.written();
-					error?
-				}
-			};
+				error => error?
+			}
 		}
 	};
 	result
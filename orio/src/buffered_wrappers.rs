@@ -1,16 +1,66 @@
 // SPDX-License-Identifier: Apache-2.0
 
+use std::mem;
 use crate::{Buffer, BufferResult, ResultContext, SIZE, StreamResult};
 use crate::BufferContext::{Drain, Fill};
-use crate::pool::Pool;
+use crate::pool::{DefaultPoolContainer, Pool};
 use crate::streams::{Sink, Source, BufStream, BufSource, BufSink, Seekable, SeekOffset, Stream, SeekableExt};
 use crate::StreamContext::{Flush, Read, Seek, Write};
+use crate::util::trace;
 
-pub struct BufferedSource<'d, S: Source<'d, SIZE>, P: Pool<SIZE>> {
-	buffer: Buffer<'d, SIZE, P>,
+pub struct BufferedSource<'d, S: Source<'d, N>, const N: usize = SIZE, P: Pool<N> = DefaultPoolContainer> {
+	buffer: Buffer<'d, N, P>,
 	source: Option<S>,
 	closed: bool,
 	eos: bool,
+	read_ahead: ReadAhead,
+	read_ahead_len: usize,
+}
+
+/// A policy deciding how many bytes a [`BufferedSource`] requests from its source
+/// when its buffer runs short. Reads are always rounded up to a whole number of
+/// segments, and never request less than the consumer needs. The first read of a
+/// refill always requests at least the free space in the buffer, so the policy
+/// only takes effect when reading ahead more than that.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ReadAhead {
+	/// Reads ahead a fixed number of bytes, or the free space in the buffer if
+	/// greater.
+	Fixed(usize),
+	/// Reads ahead between `min` and `max` bytes, starting at `min`, or the free
+	/// space in the buffer if greater. Refills the source satisfies in full double
+	/// the read-ahead, as the consumer is keeping up with the source and benefits
+	/// from fewer, larger reads. Refills returning less than half the read-ahead
+	/// halve it, since the source has less data ready than is being requested.
+	Adaptive { min: usize, max: usize },
+}
+
+impl Default for ReadAhead {
+	/// Reads ahead one segment.
+	fn default() -> Self { Self::Fixed(SIZE) }
+}
+
+impl ReadAhead {
+	fn initial_len(self) -> usize {
+		match self {
+			Self::Fixed(len) |
+			Self::Adaptive { min: len, .. } => len
+		}
+	}
+
+	/// Returns the next read-ahead length after requesting `requested` bytes and
+	/// receiving `read` bytes.
+	fn next_len(self, len: usize, requested: usize, read: usize) -> usize {
+		let Self::Adaptive { min, max } = self else { return len };
+		let max = max.max(min);
+		if read >= requested {
+			requested.saturating_mul(2).clamp(min, max)
+		} else if read < requested / 2 {
+			(len / 2).clamp(min, max)
+		} else {
+			len
+		}
+	}
 }
 
 #[inline]
@@ -23,11 +73,19 @@ fn read_size(requested: usize, buffer_limit: usize, segment_size: usize) -> usiz
 	requested.clamp(segment_size, max_read_size(buffer_limit, segment_size))
 }
 
-impl<'d, S: Source<'d, SIZE>, P: Pool<SIZE>> BufferedSource<'d, S, P> {
+impl<'d, const N: usize, S: Source<'d, N>, P: Pool<N>> BufferedSource<'d, S, N, P> {
 	#[inline]
-	pub(crate) fn new(source: S, buffer: Buffer<'d, SIZE, P>) -> Self {
+	pub(crate) fn new(source: S, buffer: Buffer<'d, N, P>) -> Self {
 		let closed = source.is_closed();
-		Self { buffer, source: Some(source), closed, eos: false }
+		let read_ahead = ReadAhead::default();
+		Self {
+			buffer,
+			source: Some(source),
+			closed,
+			eos: false,
+			read_ahead,
+			read_ahead_len: read_ahead.initial_len(),
+		}
 	}
 
 	/// Consumes the buffered sink without closing, returning the inner sink.
@@ -39,6 +97,19 @@ impl<'d, S: Source<'d, SIZE>, P: Pool<SIZE>> BufferedSource<'d, S, P> {
 		}
 	}
 
+	/// Returns the read-ahead policy. See [`ReadAhead`].
+	pub fn read_ahead(&self) -> ReadAhead { self.read_ahead }
+
+	/// Sets the read-ahead policy, resetting an adaptive read-ahead to its minimum.
+	pub fn set_read_ahead(&mut self, policy: ReadAhead) {
+		self.read_ahead = policy;
+		self.read_ahead_len = policy.initial_len();
+	}
+
+	/// Returns the number of bytes currently read ahead when the buffer runs short.
+	/// This changes between reads with an [adaptive](ReadAhead::Adaptive) policy.
+	pub fn read_ahead_len(&self) -> usize { self.read_ahead_len }
+
 	fn source_mut(&mut self) -> &mut S {
 		unsafe {
 			// Safety: option will only be None if into_inner is called, but this
@@ -55,7 +126,7 @@ impl<'d, S: Source<'d, SIZE>, P: Pool<SIZE>> BufferedSource<'d, S, P> {
 		}
 	}
 
-	fn internals(&mut self) -> (&mut Buffer<'d, SIZE, P>, &mut S, &mut bool) {
+	fn internals(&mut self) -> (&mut Buffer<'d, N, P>, &mut S, &mut bool) {
 		let source = unsafe {
 			// Safety: see `source_mut`
 			self.source.as_mut().unwrap_unchecked()
@@ -66,7 +137,7 @@ impl<'d, S: Source<'d, SIZE>, P: Pool<SIZE>> BufferedSource<'d, S, P> {
 
 	#[inline]
 	fn max_request_size(&self) -> usize {
-		max_read_size(self.buffer.limit(), SIZE)
+		max_read_size(self.buffer.limit(), N)
 	}
 
 	/// Determines the request size for a read of `count` bytes. Requests are at
@@ -75,11 +146,33 @@ impl<'d, S: Source<'d, SIZE>, P: Pool<SIZE>> BufferedSource<'d, S, P> {
 	/// better efficiency, while limiting allocation during very large reads.
 	#[inline]
 	fn request_size(&self, count: usize) -> usize {
-		read_size(count, self.buffer.limit(), SIZE)
+		read_size(count, self.buffer.limit(), N)
+	}
+
+	/// Fills the buffer with at least `count` bytes, reading ahead up to `requested`
+	/// bytes or the free space in the buffer, whichever is greater.
+	fn fill_ahead(&mut self, count: usize, requested: usize) -> StreamResult<bool> {
+		let (buffer, source, eos) = self.internals();
+		let mut fill_size = requested.max(buffer.limit());
+		while buffer.count() < count && !source.is_eos() {
+			match source.fill(buffer, fill_size) {
+				Ok(0) => break,
+				Ok(_) => { }
+				Err(err) if err.is_eos() => {
+					*eos = true;
+					break
+				}
+				// A non-blocking source has no data right now, stop softly.
+				Err(err) if err.is_would_block() => break,
+				Err(err) => return Err(err.into())
+			}
+			fill_size = count.checked_next_multiple_of(N).unwrap_or(usize::MAX).saturating_sub(buffer.count());
+		}
+		Ok(buffer.count() >= count)
 	}
 }
 
-impl<'d, S: Source<'d, SIZE>, P: Pool<SIZE>> Stream<SIZE> for BufferedSource<'d, S, P> {
+impl<'d, const N: usize, S: Source<'d, N>, P: Pool<N>> Stream<N> for BufferedSource<'d, S, N, P> {
 	#[inline]
 	fn is_closed(&self) -> bool { self.closed }
 
@@ -95,72 +188,57 @@ impl<'d, S: Source<'d, SIZE>, P: Pool<SIZE>> Stream<SIZE> for BufferedSource<'d,
 	}
 }
 
-impl<'d, S: Source<'d, SIZE>, P: Pool<SIZE>> Source<'d, SIZE> for BufferedSource<'d, S, P> {
+impl<'d, const N: usize, S: Source<'d, N>, P: Pool<N>> Source<'d, N> for BufferedSource<'d, S, N, P> {
 	fn is_eos(&self) -> bool {
 		self.eos || self.source().is_eos()
 	}
 
-	fn fill(&mut self, sink: &mut Buffer<'d, SIZE, impl Pool>, mut count: usize) -> BufferResult<usize> {
+	fn fill(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>, mut count: usize) -> BufferResult<usize> {
 		self.check_open(Fill)?;
+		let _span = trace::span!("buffered_fill", requested = count, buffered = self.buffer.count());
 		let mut read = self.buffer.fill(sink, count)?;
 		count -= read;
 		read += self.source_mut().fill(sink, count)?;
 		Ok(read)
 	}
 
-	fn fill_all(&mut self, sink: &mut Buffer<'d, SIZE, impl Pool>) -> BufferResult<usize> {
+	fn fill_all(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>) -> BufferResult<usize> {
 		self.check_open(Fill)?;
+		let _span = trace::span!("buffered_fill_all", buffered = self.buffer.count());
 		let mut count = self.buffer.fill_all(sink)?;
 		count +=  self.source_mut().fill_all(sink)?;
 		Ok(count)
 	}
 }
 
-impl<'d, S: Source<'d, SIZE>, P: Pool<SIZE>> BufStream<'d, SIZE> for BufferedSource<'d, S, P> {
+impl<'d, const N: usize, S: Source<'d, N>, P: Pool<N>> BufStream<'d, N> for BufferedSource<'d, S, N, P> {
 	type Pool = P;
 
-	fn buf<'b>(&'b self) -> &'b Buffer<'d, SIZE, P> { &self.buffer }
-	fn buf_mut<'b>(&'b mut self) -> &'b mut Buffer<'d, SIZE, P> { &mut self.buffer }
+	fn buf<'b>(&'b self) -> &'b Buffer<'d, N, P> { &self.buffer }
+	fn buf_mut<'b>(&'b mut self) -> &'b mut Buffer<'d, N, P> { &mut self.buffer }
 }
 
-impl<'d, S: Source<'d, SIZE>, P: Pool<SIZE>> BufSource<'d, SIZE> for BufferedSource<'d, S, P> {
+impl<'d, const N: usize, S: Source<'d, N>, P: Pool<N>> BufSource<'d, N> for BufferedSource<'d, S, N, P> {
 	fn request(&mut self, count: usize) -> StreamResult<bool> {
 		self.check_open(Read)?;
 		if self.is_eos() && self.buffer.is_empty() { return Ok(false) }
 
-		let (buffer, source, eos) = self.internals();
-
 		// No fill necessary
-		if buffer.request(count)? {
+		if self.buffer.request(count)? {
 			return Ok(true)
 		}
 
-		if buffer.capacity() > 0 {
-			// Fill buffer to its limit
-			source.fill_free(buffer)?;
-			if buffer.count() >= count {
-				return Ok(true)
-			}
-		} else {
-			buffer.reserve(SIZE).context(Read)?;
-		}
-
-		while buffer.count() < count && !source.is_eos() {
-			let fill_size = count.next_multiple_of(SIZE) - buffer.count();
-			match source.fill(buffer, fill_size) {
-				Ok(0) => break,
-				Ok(_) => { }
-				Err(err) if err.is_eos() => {
-					*eos = true;
-					break
-				}
-				Err(err) => return Err(err.into())
-			}
-		}
-		Ok(buffer.count() >= count)
+		let start = self.buffer.count();
+		let requested = count.max(self.read_ahead_len)
+							 .checked_next_multiple_of(N)
+							 .unwrap_or(usize::MAX) - start;
+		let result = self.fill_ahead(count, requested);
+		let read = self.buffer.count().saturating_sub(start);
+		self.read_ahead_len = self.read_ahead.next_len(self.read_ahead_len, requested, read);
+		result
 	}
 
-	fn read(&mut self, sink: &mut impl Sink<'d, SIZE>, count: usize) -> StreamResult<usize> {
+	fn read(&mut self, sink: &mut impl Sink<'d, N>, count: usize) -> StreamResult<usize> {
 		self.check_open(Read)?;
 
 		let mut read = 0;
@@ -174,12 +252,15 @@ impl<'d, S: Source<'d, SIZE>, P: Pool<SIZE>> BufSource<'d, SIZE> for BufferedSou
 		Ok(read)
 	}
 
-	fn read_all(&mut self, sink: &mut impl Sink<'d, SIZE>) -> StreamResult<usize> {
+	fn read_all(&mut self, sink: &mut impl Sink<'d, N>) -> StreamResult<usize> {
 		self.check_open(Read)?;
 
 		let mut read = 0;
 		while !self.is_eos() || self.buf().is_not_empty() {
 			read += sink.drain_all(self.buf_mut()).context(Read)?;
+			if self.buffer.should_yield(read) {
+				break
+			}
 			self.request(self.max_request_size())?;
 		}
 
@@ -187,7 +268,7 @@ impl<'d, S: Source<'d, SIZE>, P: Pool<SIZE>> BufSource<'d, SIZE> for BufferedSou
 	}
 }
 
-impl<'d, S: Source<'d, SIZE> + Seekable, P: Pool<SIZE>> BufferedSource<'d, S, P> {
+impl<'d, const N: usize, S: Source<'d, N> + Seekable, P: Pool<N>> BufferedSource<'d, S, N, P> {
 	fn seek_back_buf(&mut self, off: usize) -> StreamResult<usize> {
 		let cur_pos = self.seek_pos()?;
 		let new_pos = self.source_mut().seek_back(off)?;
@@ -197,7 +278,7 @@ impl<'d, S: Source<'d, SIZE> + Seekable, P: Pool<SIZE>> BufferedSource<'d, S, P>
 			return Ok(new_pos)
 		}
 
-		let mut seek_buf = Buffer::<SIZE, P>::default();
+		let mut seek_buf = Buffer::<N, P>::default();
 		self.source_mut()
 			.fill(&mut seek_buf, count)
 			.context(Seek)?;
@@ -213,7 +294,7 @@ impl<'d, S: Source<'d, SIZE> + Seekable, P: Pool<SIZE>> BufferedSource<'d, S, P>
 	}
 }
 
-impl<'d, S: Source<'d, SIZE> + Seekable, P: Pool<SIZE>> Seekable for BufferedSource<'d, S, P> {
+impl<'d, const N: usize, S: Source<'d, N> + Seekable, P: Pool<N>> Seekable for BufferedSource<'d, S, N, P> {
 	fn seek(&mut self, offset: SeekOffset) -> StreamResult<usize> {
 		return match offset {
 			SeekOffset::Forward(0) |
@@ -251,7 +332,7 @@ impl<'d, S: Source<'d, SIZE> + Seekable, P: Pool<SIZE>> Seekable for BufferedSou
 	}
 }
 
-impl<'d, S: Source<'d, SIZE>, P: Pool<SIZE>> Drop for BufferedSource<'d, S, P> {
+impl<'d, const N: usize, S: Source<'d, N>, P: Pool<N>> Drop for BufferedSource<'d, S, N, P> {
 	fn drop(&mut self) {
 		if self.source.is_some() {
 			let _ = self.close();
@@ -259,17 +340,20 @@ impl<'d, S: Source<'d, SIZE>, P: Pool<SIZE>> Drop for BufferedSource<'d, S, P> {
 	}
 }
 
-pub struct BufferedSink<'d, S: Sink<'d, SIZE>, P: Pool<SIZE>> {
-	buffer: Buffer<'d, SIZE, P>,
+pub struct BufferedSink<'d, S: Sink<'d, N>, const N: usize = SIZE, P: Pool<N> = DefaultPoolContainer> {
+	buffer: Buffer<'d, N, P>,
 	sink: Option<S>,
-	closed: bool
+	closed: bool,
+	line_buffered: bool,
+	/// The number of buffered bytes already searched for a line feed.
+	line_scanned: usize,
 }
 
-impl<'d, S: Sink<'d, SIZE>, P: Pool<SIZE>> BufferedSink<'d, S, P> {
+impl<'d, const N: usize, S: Sink<'d, N>, P: Pool<N>> BufferedSink<'d, S, N, P> {
 	#[inline]
-	pub(crate) fn new(sink: S, buffer: Buffer<'d, SIZE, P>) -> Self {
+	pub(crate) fn new(sink: S, buffer: Buffer<'d, N, P>) -> Self {
 		let closed = sink.is_closed();
-		Self { buffer, sink: Some(sink), closed }
+		Self { buffer, sink: Some(sink), closed, line_buffered: false, line_scanned: 0 }
 	}
 
 	/// Consumes the buffered sink without closing, returning the inner sink.
@@ -282,6 +366,32 @@ impl<'d, S: Sink<'d, SIZE>, P: Pool<SIZE>> BufferedSink<'d, S, P> {
 		}
 	}
 
+	/// Returns the number of buffered bytes at which the buffer is drained to the
+	/// underlying sink. See [`BufferOptions::flush_threshold`].
+	///
+	/// [`BufferOptions::flush_threshold`]: crate::BufferOptions::flush_threshold
+	pub fn flush_threshold(&self) -> usize {
+		self.buffer.options().flush_threshold()
+	}
+
+	/// Sets the number of buffered bytes at which the buffer is drained to the
+	/// underlying sink. Lower thresholds write data through sooner, trading
+	/// throughput for latency.
+	pub fn set_flush_threshold(&mut self, bytes: usize) {
+		self.buffer.set_flush_threshold(bytes);
+	}
+
+	/// Returns `true` if the sink is line-buffered.
+	pub fn is_line_buffered(&self) -> bool { self.line_buffered }
+
+	/// Sets whether the sink is line-buffered. Like line-buffered standard output,
+	/// a line-buffered sink writes buffered data through and flushes the underlying
+	/// sink whenever a line feed is written, leaving only the incomplete last line
+	/// buffered.
+	pub fn set_line_buffered(&mut self, value: bool) {
+		self.line_buffered = value;
+	}
+
 	fn sink_mut(&mut self) -> &mut S {
 		unsafe {
 			// Safety: option will only be None if into_inner is called, but this
@@ -291,7 +401,7 @@ impl<'d, S: Sink<'d, SIZE>, P: Pool<SIZE>> BufferedSink<'d, S, P> {
 		}
 	}
 
-	fn internals(&mut self) -> (&mut Buffer<'d, SIZE, P>, &mut S) {
+	fn internals(&mut self) -> (&mut Buffer<'d, N, P>, &mut S) {
 		let sink = unsafe {
 			// Safety: see `sink_mut`
 			self.sink.as_mut().unwrap_unchecked()
@@ -301,7 +411,7 @@ impl<'d, S: Sink<'d, SIZE>, P: Pool<SIZE>> BufferedSink<'d, S, P> {
 	}
 }
 
-impl<'d, S: Sink<'d, SIZE>, P: Pool<SIZE>> Stream<SIZE> for BufferedSink<'d, S, P> {
+impl<'d, const N: usize, S: Sink<'d, N>, P: Pool<N>> Stream<N> for BufferedSink<'d, S, N, P> {
 	#[inline]
 	fn is_closed(&self) -> bool { self.closed }
 
@@ -320,13 +430,13 @@ impl<'d, S: Sink<'d, SIZE>, P: Pool<SIZE>> Stream<SIZE> for BufferedSink<'d, S,
 	}
 }
 
-impl<'d, S: Sink<'d, SIZE>, P: Pool<SIZE>> Sink<'d, SIZE> for BufferedSink<'d, S, P> {
-	fn drain(&mut self, source: &mut Buffer<'d, SIZE, impl Pool>, count: usize) -> BufferResult<usize> {
+impl<'d, const N: usize, S: Sink<'d, N>, P: Pool<N>> Sink<'d, N> for BufferedSink<'d, S, N, P> {
+	fn drain(&mut self, source: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
 		self.check_open(Drain)?;
 		self.sink_mut().drain(source, count)
 	}
 
-	fn drain_all(&mut self, source: &mut Buffer<'d, SIZE, impl Pool>) -> BufferResult<usize> {
+	fn drain_all(&mut self, source: &mut Buffer<'d, N, impl Pool<N>>) -> BufferResult<usize> {
 		self.check_open(Drain)?;
 		self.sink_mut().drain_all(source)
 	}
@@ -342,15 +452,15 @@ impl<'d, S: Sink<'d, SIZE>, P: Pool<SIZE>> Sink<'d, SIZE> for BufferedSink<'d, S
 	}
 }
 
-impl<'d, S: Sink<'d, SIZE>, P: Pool<SIZE>> BufStream<'d, SIZE> for BufferedSink<'d, S, P> {
+impl<'d, const N: usize, S: Sink<'d, N>, P: Pool<N>> BufStream<'d, N> for BufferedSink<'d, S, N, P> {
 	type Pool = P;
 
-	fn buf<'b>(&'b self) -> &'b Buffer<'d, SIZE, P> { &self.buffer }
-	fn buf_mut<'b>(&'b mut self) -> &'b mut Buffer<'d, SIZE, P> { &mut self.buffer }
+	fn buf<'b>(&'b self) -> &'b Buffer<'d, N, P> { &self.buffer }
+	fn buf_mut<'b>(&'b mut self) -> &'b mut Buffer<'d, N, P> { &mut self.buffer }
 }
 
-impl<'d, S: Sink<'d, SIZE>, P: Pool<SIZE>> BufSink<'d, SIZE> for BufferedSink<'d, S, P> {
-	fn write(&mut self, source: &mut impl Source<'d, SIZE>, count: usize) -> StreamResult<usize> {
+impl<'d, const N: usize, S: Sink<'d, N>, P: Pool<N>> BufSink<'d, N> for BufferedSink<'d, S, N, P> {
+	fn write(&mut self, source: &mut impl Source<'d, N>, count: usize) -> StreamResult<usize> {
 		self.check_open(Write)?;
 
 		let mut written = 0;
@@ -369,7 +479,7 @@ impl<'d, S: Sink<'d, SIZE>, P: Pool<SIZE>> BufSink<'d, SIZE> for BufferedSink<'d
 		Ok(written)
 	}
 
-	fn write_all(&mut self, source: &mut impl Source<'d, SIZE>) -> StreamResult<usize> {
+	fn write_all(&mut self, source: &mut impl Source<'d, N>) -> StreamResult<usize> {
 		self.check_open(Write)?;
 
 		let mut count = 0;
@@ -377,7 +487,7 @@ impl<'d, S: Sink<'d, SIZE>, P: Pool<SIZE>> BufSink<'d, SIZE> for BufferedSink<'d
 			self.drain_buffered().context(Write)?;
 
 			if self.buffer.capacity() == 0 {
-				self.buffer.reserve(SIZE).context(Write)?;
+				self.buffer.reserve(N).context(Write)?;
 			}
 
 			let written = source.fill_free(self.buf_mut()).context(Write)?;
@@ -386,6 +496,9 @@ impl<'d, S: Sink<'d, SIZE>, P: Pool<SIZE>> BufSink<'d, SIZE> for BufferedSink<'d
 			}
 
 			count += written;
+			if self.buffer.should_yield(count) {
+				break
+			}
 		}
 		self.drain_all_buffered().context(Write)?;
 		Ok(count)
@@ -393,6 +506,8 @@ impl<'d, S: Sink<'d, SIZE>, P: Pool<SIZE>> BufSink<'d, SIZE> for BufferedSink<'d
 
 	fn drain_all_buffered(&mut self) -> BufferResult {
 		self.check_open(Drain)?;
+		let _span = trace::span!("drain_all_buffered", buffered = self.buffer.count());
+		self.line_scanned = 0;
 		let (buf, sink) = self.internals();
 		sink.drain_all(buf)?;
 		Ok(())
@@ -400,13 +515,55 @@ impl<'d, S: Sink<'d, SIZE>, P: Pool<SIZE>> BufSink<'d, SIZE> for BufferedSink<'d
 
 	fn drain_buffered(&mut self) -> BufferResult {
 		self.check_open(Drain)?;
+		let _span = trace::span!("drain_buffered", buffered = self.buffer.count());
+		let line_buffered = self.line_buffered;
+		let scanned = mem::take(&mut self.line_scanned);
 		let (buf, sink) = self.internals();
+		let start_count = buf.count();
 		sink.drain_full(buf)?;
+		if buf.should_flush() {
+			sink.drain_all(buf)?;
+		}
+		let mut is_scanned = false;
+		if line_buffered {
+			// Write through the last complete line, then flush. Only bytes written
+			// since the last search are searched, drained bytes were at the front.
+			let mut pos = scanned.saturating_sub(start_count - buf.count()).min(buf.count());
+			let mut line_end = None;
+			while let Some(found) = buf.find_in_range(b'\n', pos..) {
+				pos += found.end;
+				line_end = Some(pos);
+			}
+			is_scanned = true;
+			if let Some(mut count) = line_end {
+				while count > 0 {
+					match sink.drain(buf, count)? {
+						0 => break,
+						drained => count -= drained
+					}
+				}
+				// Search undrained lines again.
+				is_scanned = count == 0;
+				sink.flush()?;
+			}
+		}
+		// Relieve backpressure by draining down to the low watermark.
+		if buf.is_above_high_watermark() {
+			let low_watermark = buf.options().low_watermark();
+			while !buf.is_below_low_watermark() {
+				if sink.drain(buf, buf.count() - low_watermark)? == 0 {
+					break
+				}
+			}
+		}
+		if is_scanned {
+			self.line_scanned = self.buffer.count();
+		}
 		Ok(())
 	}
 }
 
-impl<'d, S: Sink<'d, SIZE> + Seekable, P: Pool> Seekable for BufferedSink<'d, S, P> {
+impl<'d, const N: usize, S: Sink<'d, N> + Seekable, P: Pool<N>> Seekable for BufferedSink<'d, S, N, P> {
 	fn seek(&mut self, offset: SeekOffset) -> StreamResult<usize> {
 		self.check_open(Seek)?;
 		// Todo: Is there some less naive approach than flushing then seeking?
@@ -419,10 +576,191 @@ impl<'d, S: Sink<'d, SIZE> + Seekable, P: Pool> Seekable for BufferedSink<'d, S,
 	}
 }
 
-impl<'d, S: Sink<'d, SIZE>, P: Pool<SIZE>> Drop for BufferedSink<'d, S, P> {
+impl<'d, const N: usize, S: Sink<'d, N>, P: Pool<N>> Drop for BufferedSink<'d, S, N, P> {
 	fn drop(&mut self) {
 		if self.sink.is_some() {
 			let _ = self.close();
 		}
 	}
 }
+
+#[cfg(test)]
+mod test {
+	use std::cell::{RefCell, RefMut};
+	use std::collections::VecDeque;
+	use std::io::{self, ErrorKind, Read};
+	use std::rc::Rc;
+	use pretty_assertions::assert_eq;
+	use crate::{Buffer, BufferOptions, DefaultBuffer, Seg};
+	use crate::pool::{DefaultPoolContainer, MutPool, Pool, Result};
+	use crate::streams::{BufSink, BufSource, BufStream, ReaderSource, Sink, SinkExt, SourceExt};
+	use super::{BufferedSink, BufferedSource};
+
+	const SMALL: usize = 16;
+
+	/// A pool allocating small boxed segments, counting claimed segments.
+	#[derive(Clone, Default)]
+	struct SmallPool(Rc<RefCell<Claims>>);
+
+	#[derive(Default)]
+	struct Claims(usize);
+
+	impl Pool<SMALL> for SmallPool {
+		type Pool = Claims;
+		type Ref<'p> = RefMut<'p, Claims>;
+
+		fn get() -> Self { Self::default() }
+
+		fn try_borrow(&self) -> Result<Self::Ref<'_>> {
+			Ok(self.0.try_borrow_mut()?)
+		}
+	}
+
+	impl MutPool<SMALL> for Claims {
+		fn claim_reserve(&mut self, _count: usize) { }
+
+		fn claim_one<'d>(&mut self) -> Seg<'d, SMALL> {
+			self.0 += 1;
+			Vec::with_capacity(SMALL).into()
+		}
+
+		fn claim_count<'d>(&mut self, target: &mut impl Extend<Seg<'d, SMALL>>, count: usize) {
+			target.extend((0..count).map(|_| self.claim_one()));
+		}
+
+		fn collect_reserve(&mut self, _count: usize) { }
+		fn collect_one(&mut self, _segment: Seg<SMALL>) { }
+		fn collect<'d>(&mut self, _segments: impl IntoIterator<Item = Seg<'d, SMALL>>) { }
+		fn shed(&mut self) { }
+	}
+
+	/// A reader returning chunks, with `None` standing in for "no data right now".
+	struct NonBlocking(VecDeque<Option<&'static [u8]>>);
+
+	impl Read for NonBlocking {
+		fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+			match self.0.pop_front() {
+				Some(Some(chunk)) => {
+					buf[..chunk.len()].copy_from_slice(chunk);
+					Ok(chunk.len())
+				}
+				Some(None) => Err(ErrorKind::WouldBlock.into()),
+				None => Ok(0)
+			}
+		}
+	}
+
+	#[test]
+	fn request_would_block() {
+		let reader = NonBlocking([Some(&b"abc"[..]), None, Some(b"def")].into());
+		let mut source = BufferedSource::new(ReaderSource::from(reader), Buffer::<_, DefaultPoolContainer>::default());
+		assert!(!source.request(6).unwrap(), "would-block should stop the request softly");
+		assert_eq!(source.available(), 3);
+		assert!(source.request(6).unwrap());
+		assert_eq!(source.buffer, b"abcdef");
+	}
+
+	#[test]
+	fn fill_buf() {
+		let reader = NonBlocking([Some(&b"abc"[..]), Some(b"def")].into());
+		let mut source = BufferedSource::new(ReaderSource::from(reader), Buffer::<_, DefaultPoolContainer>::default());
+		assert_eq!(source.fill_buf().unwrap(), b"abcdef");
+		source.consume(2);
+		assert_eq!(source.fill_buf().unwrap(), b"cdef", "consumed bytes shouldn't be returned");
+		source.consume(4);
+		assert_eq!(source.fill_buf().unwrap(), b"", "an empty slice should be returned at end-of-stream");
+	}
+
+	#[test]
+	fn read_utf8_until_refill() {
+		let reader = NonBlocking([Some(&b"key=va"[..]), Some(b"lue\r"), Some(b"\nkey=")].into());
+		let mut source = BufferedSource::new(ReaderSource::from(reader), Buffer::<_, DefaultPoolContainer>::default());
+		let terminator = "\r\n";
+		let mut line = String::new();
+		let read: (usize, bool) = source.read_utf8_until(&mut line, terminator).unwrap().into();
+		assert_eq!(read, (9, true), "terminator should be found across reads");
+		assert_eq!(line, "key=value");
+		line.clear();
+		let read: (usize, bool) = source.read_utf8_until(&mut line, terminator).unwrap().into();
+		assert_eq!(read, (4, false));
+		assert_eq!(line, "key=");
+	}
+
+	#[test]
+	fn drain_to_low_watermark() {
+		let options = BufferOptions::default().with_watermarks(16, 4);
+		let mut sink = BufferedSink::new(DefaultBuffer::default(), Buffer::<_, DefaultPoolContainer>::from(options));
+		sink.write_from_slice(&[1; 16]).unwrap();
+		assert_eq!(sink.buf().count(), 16, "buffer at the high watermark shouldn't be drained");
+		assert!(!sink.buf().is_above_high_watermark());
+
+		sink.write_u8(2).unwrap();
+		assert_eq!(sink.buf().count(), 4, "buffer above the high watermark should be drained to the low watermark");
+		assert!(sink.buf().is_below_low_watermark());
+		assert_eq!(sink.into_inner().count(), 17);
+	}
+
+	#[test]
+	fn flush_threshold() {
+		let options = BufferOptions::default().with_flush_threshold(8);
+		let mut sink = BufferedSink::new(DefaultBuffer::default(), Buffer::<_, DefaultPoolContainer>::from(options));
+		sink.write_from_slice(&[1; 4]).unwrap();
+		assert_eq!(sink.buf().count(), 4, "buffer below the flush threshold shouldn't be drained");
+		sink.write_from_slice(&[2; 4]).unwrap();
+		assert!(sink.buf().is_empty(), "buffer at the flush threshold should be drained");
+
+		sink.set_flush_threshold(usize::MAX);
+		assert_eq!(sink.flush_threshold(), usize::MAX);
+		sink.write_from_slice(&[3; 16]).unwrap();
+		assert_eq!(sink.buf().count(), 16);
+		assert_eq!(sink.into_inner().count(), 24);
+	}
+
+	#[test]
+	fn line_buffered() {
+		let mut sink = SinkExt::line_buffered(DefaultBuffer::default());
+		assert!(sink.is_line_buffered());
+		sink.write_from_slice(b"abc").unwrap();
+		assert_eq!(sink.buf().count(), 3, "incomplete lines shouldn't be written");
+		sink.write_from_slice(b"def\nghi\r\njk").unwrap();
+		assert_eq!(sink.buf(), b"jk", "complete lines should be written");
+		assert_eq!(sink.sink.as_ref().unwrap(), b"abcdef\nghi\r\n");
+		sink.write_from_slice(b"l\rm").unwrap();
+		assert_eq!(sink.buf(), b"jkl\rm", "lone carriage returns shouldn't end lines");
+		sink.write_from_slice(b"n\no").unwrap();
+		assert_eq!(sink.buf(), b"o");
+		assert_eq!(sink.sink.as_ref().unwrap(), b"abcdef\nghi\r\njkl\rmn\n");
+
+		sink.set_line_buffered(false);
+		sink.write_from_slice(b"\n").unwrap();
+		assert_eq!(sink.buf(), b"o\n");
+	}
+
+	#[test]
+	fn buffered_in_pool() {
+		let pool = SmallPool::default();
+		let mut data = Buffer::<SMALL, _>::new(pool.clone(), BufferOptions::default());
+		data.write_from_slice(&[1; 40]).unwrap();
+
+		let mut source = SourceExt::buffered_in(data, pool.clone());
+		assert!(source.request(20).unwrap());
+		let sink = Buffer::<SMALL, _>::new(pool.clone(), BufferOptions::default());
+		let mut sink = SinkExt::buffered_in(sink, pool.clone());
+		assert_eq!(sink.write_all(&mut source).unwrap(), 40);
+		sink.flush().unwrap();
+		assert_eq!(sink.into_inner(), [1; 40]);
+		assert!(pool.0.borrow().0 > 0, "segments should be claimed from the explicit pool");
+	}
+
+	#[test]
+	fn buffered_with_options() {
+		let pool = SmallPool::default();
+		let options = BufferOptions::default().with_watermarks(32, 8);
+		let sink = Buffer::<SMALL, _>::new(pool.clone(), BufferOptions::default());
+		let mut sink = SinkExt::buffered_with_options(sink, pool.clone(), options);
+		assert_eq!(sink.buf().options().high_watermark(), 32);
+		sink.write_from_slice(&[1; 40]).unwrap();
+		assert!(pool.0.borrow().0 > 0, "segments should be claimed from the explicit pool");
+		assert_eq!(sink.into_inner(), [1; 40]);
+	}
+}
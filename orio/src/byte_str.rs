@@ -10,24 +10,33 @@ mod conv;
 mod decoding;
 mod encoding;
 mod hash;
+mod hexdump;
 mod iter;
 mod parsing;
+mod shared;
+#[cfg(feature = "winnow")]
+mod winnow;
 
 use std::borrow::{Borrow, Cow};
-use std::ops::{Add, AddAssign, Deref, DerefMut, Index, Range, RangeBounds};
+use std::ops::{Add, AddAssign, Deref, Index, Range, RangeBounds};
 use std::{fmt, mem, slice};
 use std::cmp::Ordering;
 use std::hash::{Hash, Hasher};
-use std::iter::once;
+use std::iter::{once, Copied};
 use all_asserts::assert_le;
 use simdutf8::compat::from_utf8;
 use crate::Utf8Error;
+use crate::util::compare;
 use crate::util::partial_utf8::read_partial_utf8_into;
 use crate::pattern::Pattern;
+pub use decoding::DecodeError;
+pub(crate) use decoding::{decode_hex, STANDARD_PAD_INDIFFERENT, URL_SAFE_PAD_INDIFFERENT};
 pub use encoding::EncodeBytes;
 pub use iter::*;
 pub use hash::*;
+pub use hexdump::HexDump;
 pub use parsing::*;
+pub use shared::*;
 
 /// A borrowed, segmented string of bytes.
 #[derive(Clone)]
@@ -37,7 +46,8 @@ pub struct ByteStr<'a> {
 	len: usize,
 }
 
-/// An owned string of bytes.
+/// An owned string of bytes. Short byte strings are stored inline, without
+/// allocating.
 #[derive(Clone, amplify_derive::From)]
 pub struct ByteString {
 	// Todo: change this to a "valid range", to allow invalid bytes to be pushed but
@@ -48,10 +58,22 @@ pub struct ByteString {
 	data: Data,
 }
 
+/// The maximum length of byte strings stored inline, without allocating.
+const INLINE_LEN: usize = 22;
+
 #[derive(Clone, amplify_derive::From)]
 enum Data {
 	Bytes(#[from] Vec<u8>),
-	String(#[from] String)
+	String(#[from] String),
+	Inline(Inline),
+}
+
+/// Short bytes stored inline, marked as valid UTF-8 or not.
+#[derive(Copy, Clone)]
+struct Inline {
+	buf: [u8; INLINE_LEN],
+	len: u8,
+	utf8: bool,
 }
 
 impl<'a> ByteStr<'a> {
@@ -113,6 +135,66 @@ impl<'a> ByteStr<'a> {
 			.into_byte_str(utf8)
 	}
 
+	/// Returns the length of the longest prefix shared with `other`. Bytes are
+	/// compared a slice at a time.
+	pub fn common_prefix_len(&self, other: &ByteStr) -> usize {
+		compare::common_prefix_len(self.data.iter().copied(), other.data.iter().copied())
+	}
+
+	/// Returns the length of the longest suffix shared with `other`. Bytes are
+	/// compared a slice at a time.
+	pub fn common_suffix_len(&self, other: &ByteStr) -> usize {
+		compare::common_suffix_len(self.data.iter().copied(), other.data.iter().copied())
+	}
+
+	/// Returns the longest prefix shared with `other`, borrowed from this byte
+	/// string.
+	pub fn common_prefix(&self, other: &ByteStr) -> ByteStr<'a> {
+		self.range(..self.common_prefix_len(other))
+	}
+
+	/// Returns the longest suffix shared with `other`, borrowed from this byte
+	/// string.
+	pub fn common_suffix(&self, other: &ByteStr) -> ByteStr<'a> {
+		self.range(self.len - self.common_suffix_len(other)..)
+	}
+
+	/// Returns the index of the first byte differing from `other`, or `None` if the
+	/// byte strings are equal. If one byte string is a prefix of the other, the
+	/// mismatch is at the end of the shorter one.
+	pub fn first_mismatch(&self, other: &ByteStr) -> Option<usize> {
+		let len = self.common_prefix_len(other);
+		(len < self.len.max(other.len)).then_some(len)
+	}
+
+	/// Returns `true` if all bytes in the byte string are ASCII.
+	pub fn is_ascii(&self) -> bool {
+		self.data.iter().all(|slice| slice.is_ascii())
+	}
+
+	/// Returns `true` if the byte string is equal to `other`, ignoring ASCII case.
+	/// Bytes are compared a slice at a time.
+	pub fn eq_ignore_ascii_case(&self, other: &ByteStr) -> bool {
+		self.len == other.len &&
+			compare::eq_ignore_ascii_case(self.data.iter().copied(), other.data.iter().copied())
+	}
+
+	/// Returns an owned copy of the byte string with ASCII letters converted to
+	/// lowercase. Other bytes are unchanged.
+	pub fn to_ascii_lowercase(&self) -> ByteString {
+		let mut data = self.to_byte_string();
+		data.make_ascii_lowercase();
+		data
+	}
+
+	/// Returns an owned copy of the byte string with ASCII letters converted to
+	/// uppercase. Other bytes are unchanged.
+	pub fn to_ascii_uppercase(&self) -> ByteString {
+		let mut data = self.to_byte_string();
+		data.make_ascii_uppercase();
+		data
+	}
+
 	/// Decodes and caches the bytes as UTF-8, returning a borrow of the cache.
 	/// Subsequent calls to this function will borrow from the cache, and calls to
 	/// [`utf8`] will clone from it.
@@ -145,6 +227,23 @@ impl<'a> ByteStr<'a> {
 		self.utf8.as_deref()
 	}
 
+	/// Decodes the bytes as UTF-8, replacing invalid sequences with
+	/// [`U+FFFD REPLACEMENT CHARACTER`][U+FFFD]. Borrows the bytes if they're
+	/// contained in a single valid slice, or the cached string if any.
+	///
+	/// [U+FFFD]: char::REPLACEMENT_CHARACTER
+	pub fn to_utf8_lossy(&self) -> Cow<'a, str> {
+		if let Some(utf8) = self.utf8.clone() {
+			return utf8
+		}
+
+		match self.data[..] {
+			[] => Cow::Borrowed(""),
+			[slice] => String::from_utf8_lossy(slice),
+			_ => String::from_utf8_lossy(&self.data.concat()).into_owned().into()
+		}
+	}
+
 	/// Finds the first range matching `pattern` in the byte string.
 	pub fn find(&self, pattern: impl Pattern) -> Option<Range<usize>> {
 		match self.cached_utf8() {
@@ -424,6 +523,14 @@ impl<'a> ByteStr<'a> {
 		Bytes::new(self.slices(), self.len)
 	}
 
+	/// Iterates over UTF-8 characters decoded from this byte string, replacing
+	/// invalid sequences with [`U+FFFD REPLACEMENT CHARACTER`][U+FFFD].
+	///
+	/// [U+FFFD]: char::REPLACEMENT_CHARACTER
+	pub fn chars(&self) -> Chars<Copied<Bytes<'a, '_>>> {
+		Chars::new(self.bytes().copied())
+	}
+
 	/// Clones the borrowed data into an owned [`ByteString`].
 	pub fn to_byte_string(&self) -> ByteString {
 		if let Some(utf8) = self.utf8.clone() {
@@ -433,6 +540,11 @@ impl<'a> ByteStr<'a> {
 		}
 	}
 
+	/// Returns an xxd-style [`HexDump`] of the bytes for display.
+	pub fn hexdump(&self) -> HexDump<'a> {
+		HexDump::new(self.clone())
+	}
+
 	/// Returns the internal data.
 	pub fn into_vec(self) -> Vec<&'a [u8]> {
 		self.data
@@ -463,6 +575,10 @@ impl<'a> ByteStr<'a> {
 		}
 	}
 
+	pub(crate) fn as_slices(&self) -> &[&'a [u8]] {
+		&self.data
+	}
+
 	fn slices_in_range(&self, range: Range<usize>) -> SlicesInRange<'a, '_> {
 		SlicesInRange::new(range, self.slices())
 	}
@@ -480,6 +596,16 @@ impl fmt::Debug for ByteStr<'_> {
 	}
 }
 
+impl fmt::Display for ByteStr<'_> {
+	/// Formats the bytes as UTF-8, replacing invalid sequences with
+	/// [`U+FFFD REPLACEMENT CHARACTER`][U+FFFD].
+	///
+	/// [U+FFFD]: char::REPLACEMENT_CHARACTER
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Display::fmt(&self.to_utf8_lossy(), f)
+	}
+}
+
 impl<'a> Default for ByteStr<'a> {
 	#[inline]
 	fn default() -> Self { Self::new() }
@@ -487,13 +613,12 @@ impl<'a> Default for ByteStr<'a> {
 
 impl Hash for ByteStr<'_> {
 	fn hash<H: Hasher>(&self, state: &mut H) {
-		// Hash each byte one-by-one, to ensure byte strings with the same bytes
-		// have the same hash. If `self.data` was hashed, different slices of the
-		// same byte sequence would may have different hashes.
+		// Hash the length then each slice, consistent with `[u8]`, to ensure byte
+		// strings with the same bytes have the same hash. If `self.data` was hashed,
+		// different slices of the same byte sequence may have different hashes.
+		state.write_length_prefix(self.len());
 		for slice in self.slices() {
-			for b in slice {
-				b.hash(state);
-			}
+			state.write(slice);
 		}
 	}
 }
@@ -590,6 +715,13 @@ impl PartialOrd<str> for ByteStr<'_> {
 	}
 }
 
+impl PartialOrd<ByteString> for ByteStr<'_> {
+	#[inline]
+	fn partial_cmp(&self, other: &ByteString) -> Option<Ordering> {
+		self.partial_cmp(other.as_slice())
+	}
+}
+
 impl Add for ByteStr<'_> {
 	type Output = Self;
 
@@ -616,7 +748,7 @@ impl ByteString {
 	/// Creates an empty byte string.
 	#[inline]
 	pub const fn new() -> Self {
-		Self::from_data(Data::String(String::new()))
+		Self::from_data(Data::Inline(Inline::EMPTY))
 	}
 
 	/// Returns the length in bytes of the byte string.
@@ -648,30 +780,33 @@ impl ByteString {
 	/// string if successful. If the result is `Ok`, subsequent calls skip this
 	/// check.
 	pub fn check_utf8(&mut self) -> Result<&str, Utf8Error> {
-		// ...wat?
-		let Self { data } = self;
-		if let Data::Bytes(bytes) = data {
-			from_utf8(bytes)?;
-			*data = Data::String(
-				unsafe {
-					String::from_utf8_unchecked(mem::take(bytes))
-				}
-			)
-		}
-
-		let Data::String(str) = data else {
+		if !self.data.is_utf8() {
+			from_utf8(&self.data)?;
 			unsafe {
-				std::hint::unreachable_unchecked()
+				// Safety: the data was just checked.
+				self.data.mark_utf8_unchecked();
 			}
-		};
-		Ok(str)
+		}
+
+		Ok(self.checked_utf8().expect("data should be marked as UTF-8"))
 	}
 
 	/// Decodes the bytes as UTF-8.
 	pub fn utf8(&self) -> Result<&str, Utf8Error> {
-		match &self.data {
-			Data::String(str) => Ok(str),
-			Data::Bytes(bytes) => Ok(from_utf8(bytes)?)
+		match self.data.as_str() {
+			Some(str) => Ok(str),
+			None => Ok(from_utf8(&self.data)?)
+		}
+	}
+
+	/// Decodes the bytes as UTF-8, replacing invalid sequences with
+	/// [`U+FFFD REPLACEMENT CHARACTER`][U+FFFD]. Borrows the bytes if they're valid.
+	///
+	/// [U+FFFD]: char::REPLACEMENT_CHARACTER
+	pub fn to_utf8_lossy(&self) -> Cow<'_, str> {
+		match self.data.as_str() {
+			Some(str) => Cow::Borrowed(str),
+			None => String::from_utf8_lossy(&self.data)
 		}
 	}
 
@@ -680,10 +815,7 @@ impl ByteString {
 	///
 	/// [`check_utf8`]: Self::check_utf8
 	pub fn checked_utf8(&self) -> Option<&str> {
-		let Data::String(str) = &self.data else {
-			return None
-		};
-		Some(str)
+		self.data.as_str()
 	}
 
 	/// Finds the first range matching `pattern` in the byte string.
@@ -764,13 +896,7 @@ impl ByteString {
 	/// valid UTF-8, and the index falls on a character boundary.
 	pub fn split_off(&mut self, at: usize) -> Self {
 		self.check_utf8_split(at);
-		let split = self.data.split_off(at);
-		let data = if matches!(&self.data, Data::String(_)) {
-			Data::from_utf8_unchecked(split)
-		} else {
-			split.into()
-		};
-		Self { data }
+		Self { data: self.data.split_off(at) }
 	}
 
 	/// Splits the byte string into a pair of borrowed sequences at the first match
@@ -818,18 +944,152 @@ impl ByteString {
 		self.data.extend_from_slice(slice.as_bytes());
 	}
 
+	/// Inserts `byte` at `idx`, shifting all bytes after it to the right.
+	///
+	/// # Panics
+	///
+	/// Panics if `idx` is greater than the byte string's length.
+	pub fn insert(&mut self, idx: usize, byte: u8) {
+		self.insert_slice(idx, &[byte]);
+	}
+
+	/// Inserts `slice` at `idx`, shifting all bytes after it to the right.
+	///
+	/// The byte string remains marked as valid UTF-8 if `slice` is valid UTF-8,
+	/// and `idx` falls on a character boundary.
+	///
+	/// # Panics
+	///
+	/// Panics if `idx` is greater than the byte string's length.
+	pub fn insert_slice(&mut self, idx: usize, slice: &[u8]) {
+		assert_le!(idx, self.len(), "insertion index out of bounds");
+		if self.data.is_utf8() && from_utf8(slice).is_err() {
+			self.unmark_utf8();
+		} else {
+			self.check_utf8_split(idx);
+		}
+		self.data.splice(idx..idx, slice);
+	}
+
+	/// Inserts `str` at `idx`, shifting all bytes after it to the right.
+	///
+	/// # Panics
+	///
+	/// Panics if `idx` is greater than the byte string's length.
+	pub fn insert_str(&mut self, idx: usize, str: &str) {
+		assert_le!(idx, self.len(), "insertion index out of bounds");
+		self.check_utf8_split(idx);
+		self.data.splice(idx..idx, str.as_bytes());
+	}
+
+	/// Removes bytes in `range`, shifting all bytes after it to the left.
+	///
+	/// The byte string remains marked as valid UTF-8 if both ends of the range fall
+	/// on character boundaries.
+	///
+	/// # Panics
+	///
+	/// Panics if the range is out of bounds.
+	pub fn remove<R: RangeBounds<usize>>(&mut self, range: R) {
+		let range = slice::range(range, ..self.len());
+		self.check_utf8_range(&range);
+		self.data.splice(range, &[]);
+	}
+
+	/// Removes bytes in `range`, shifting all bytes after it to the left, and
+	/// returns them in a new byte string.
+	///
+	/// Both byte strings will be marked as valid UTF-8 if the current one is, and
+	/// both ends of the range fall on character boundaries.
+	///
+	/// # Panics
+	///
+	/// Panics if the range is out of bounds.
+	pub fn drain<R: RangeBounds<usize>>(&mut self, range: R) -> Self {
+		let range = slice::range(range, ..self.len());
+		self.check_utf8_range(&range);
+		let drained = Data::from_slice(&self.data[range.clone()], self.data.is_utf8());
+		self.data.splice(range, &[]);
+		Self::from_data(drained)
+	}
+
+	/// Retains only bytes for which `f` returns `true`, removing all others in
+	/// place and preserving the order of the retained bytes.
+	///
+	/// If the byte string is marked as valid UTF-8, it remains marked unless a
+	/// non-ASCII byte was removed and the remaining bytes are no longer valid.
+	pub fn retain(&mut self, mut f: impl FnMut(u8) -> bool) {
+		let was_utf8 = self.data.is_utf8();
+		let mut removed_non_ascii = false;
+		// Unmark first to avoid invalid strings while mutating.
+		self.unmark_utf8();
+		self.data.retain(|byte| {
+			let keep = f(byte);
+			removed_non_ascii |= !keep && !byte.is_ascii();
+			keep
+		});
+
+		if was_utf8 && (!removed_non_ascii || from_utf8(&self.data).is_ok()) {
+			unsafe {
+				// Safety: the data was valid before removing bytes, and either only
+				// ASCII bytes were removed or the data was just checked.
+				self.data.mark_utf8_unchecked();
+			}
+		}
+	}
+
+	/// Returns `true` if all bytes in the byte string are ASCII.
+	pub fn is_ascii(&self) -> bool {
+		self.as_slice().is_ascii()
+	}
+
+	/// Returns `true` if the byte string is equal to `other`, ignoring ASCII case.
+	pub fn eq_ignore_ascii_case(&self, other: &ByteStr) -> bool {
+		self.len() == other.len() &&
+			compare::eq_ignore_ascii_case([self.as_slice()], other.slices())
+	}
+
+	/// Returns a copy of the byte string with ASCII letters converted to lowercase.
+	/// Other bytes are unchanged.
+	pub fn to_ascii_lowercase(&self) -> Self {
+		let mut data = self.clone();
+		data.make_ascii_lowercase();
+		data
+	}
+
+	/// Returns a copy of the byte string with ASCII letters converted to uppercase.
+	/// Other bytes are unchanged.
+	pub fn to_ascii_uppercase(&self) -> Self {
+		let mut data = self.clone();
+		data.make_ascii_uppercase();
+		data
+	}
+
+	/// Converts ASCII letters in the byte string to lowercase in place. Other bytes
+	/// are unchanged, so the byte string stays valid UTF-8 if it was.
+	pub fn make_ascii_lowercase(&mut self) {
+		self.data.as_mut_slice().make_ascii_lowercase();
+	}
+
+	/// Converts ASCII letters in the byte string to uppercase in place. Other bytes
+	/// are unchanged, so the byte string stays valid UTF-8 if it was.
+	pub fn make_ascii_uppercase(&mut self) {
+		self.data.as_mut_slice().make_ascii_uppercase();
+	}
+
 	/// Borrows the data into a [`ByteStr`].
 	pub fn as_byte_str(&self) -> ByteStr<'_> {
 		ByteStr::from_slice(&self.data, self.checked_utf8())
 	}
 
 	/// Returns the internal data as a slice of bytes.
-	pub fn as_slice(&self) -> &[u8] { self.data.as_slice() }
+	pub fn as_slice(&self) -> &[u8] { &self.data }
 	/// Returns the internal data.
 	pub fn into_bytes(self) -> Vec<u8> {
 		match self.data {
 			Data::Bytes(bytes) => bytes,
-			Data::String(utf8) => utf8.into_bytes()
+			Data::String(utf8) => utf8.into_bytes(),
+			Data::Inline(inline) => inline.as_slice().to_vec()
 		}
 	}
 	/// Returns the internal data as a UTF-8 string.
@@ -842,10 +1102,7 @@ impl ByteString {
 impl Borrow<[u8]> for Data {
 	#[inline]
 	fn borrow(&self) -> &[u8] {
-		match self {
-			Self::Bytes(vec) => vec,
-			Self::String(str) => str.as_bytes()
-		}
+		self
 	}
 }
 
@@ -857,29 +1114,14 @@ impl AsRef<[u8]> for Data {
 }
 
 impl Deref for Data {
-	type Target = Vec<u8>;
+	type Target = [u8];
 
 	#[inline]
-	fn deref(&self) -> &Vec<u8> {
+	fn deref(&self) -> &[u8] {
 		match self {
 			Self::Bytes(vec) => vec,
-			Self::String(str) => unsafe {
-				// Safety: String and Vec<u8> have the same memory layout.
-				mem::transmute(str)
-			}
-		}
-	}
-}
-
-impl DerefMut for Data {
-	#[inline]
-	fn deref_mut(&mut self) -> &mut Vec<u8> {
-		match self {
-			Self::Bytes(vec) => vec,
-			Self::String(str) => unsafe {
-				// Safety: data is checked before mutating the string.
-				str.as_mut_vec()
-			}
+			Self::String(str) => str.as_bytes(),
+			Self::Inline(inline) => inline.as_slice()
 		}
 	}
 }
@@ -893,6 +1135,14 @@ impl Data {
 		}
 	}
 
+	/// Copies `data`, storing it inline if it's short enough.
+	fn from_slice(data: &[u8], is_utf8: bool) -> Self {
+		match Inline::new(data, is_utf8) {
+			Some(inline) => Self::Inline(inline),
+			None => Self::new(data.to_vec(), is_utf8)
+		}
+	}
+
 	fn from_utf8_unchecked(data: Vec<u8>) -> Self {
 		unsafe {
 			Self::String(String::from_utf8_unchecked(data))
@@ -900,21 +1150,200 @@ impl Data {
 	}
 
 	fn is_utf8(&self) -> bool {
-		matches!(self, Self::String(_))
+		match self {
+			Self::Bytes(_) => false,
+			Self::String(_) => true,
+			Self::Inline(inline) => inline.utf8
+		}
+	}
+
+	fn as_str(&self) -> Option<&str> {
+		match self {
+			Self::Bytes(_) => None,
+			Self::String(str) => Some(str),
+			Self::Inline(inline) => inline.as_str()
+		}
+	}
+
+	/// Marks the data as valid UTF-8.
+	///
+	/// # Safety
+	///
+	/// The data must be valid UTF-8.
+	unsafe fn mark_utf8_unchecked(&mut self) {
+		match self {
+			Self::Bytes(bytes) => *self = Self::from_utf8_unchecked(mem::take(bytes)),
+			Self::String(_) => { }
+			Self::Inline(inline) => inline.utf8 = true
+		}
 	}
 
 	fn unmark_utf8(&mut self) {
-		*self = self.take_bytes().into();
+		match self {
+			Self::Bytes(_) => { }
+			Self::String(str) => *self = Self::Bytes(mem::take(str).into_bytes()),
+			Self::Inline(inline) => inline.utf8 = false
+		}
 	}
 
 	fn take_bytes(&mut self) -> Vec<u8> {
 		match self {
 			Self::Bytes(bytes) => mem::take(bytes),
-			Self::String(str) => mem::take(str).into_bytes()
+			Self::String(str) => mem::take(str).into_bytes(),
+			Self::Inline(inline) => {
+				let bytes = inline.as_slice().to_vec();
+				inline.len = 0;
+				bytes
+			}
+		}
+	}
+
+	/// Returns the data as a mutable vector, moving inline data to the heap with
+	/// capacity for `additional` more bytes. Whether the data remains valid UTF-8
+	/// must be checked by the caller.
+	fn vec_mut(&mut self, additional: usize) -> &mut Vec<u8> {
+		if let Self::Inline(inline) = self {
+			let mut vec = Vec::with_capacity(inline.len as usize + additional);
+			vec.extend_from_slice(inline.as_slice());
+			*self = Self::new(vec, inline.utf8);
+		}
+
+		match self {
+			Self::Bytes(vec) => vec,
+			Self::String(str) => unsafe {
+				// Safety: data is checked before mutating the string.
+				str.as_mut_vec()
+			}
+			Self::Inline(_) => unreachable!("inline data should have been moved")
+		}
+	}
+
+	fn truncate(&mut self, len: usize) {
+		match self {
+			Self::Inline(inline) => inline.len = len.min(inline.len as usize) as u8,
+			_ => self.vec_mut(0).truncate(len)
+		}
+	}
+
+	fn extend_from_slice(&mut self, slice: &[u8]) {
+		if let Self::Inline(inline) = self {
+			if inline.push_slice(slice) { return }
+		}
+
+		self.vec_mut(slice.len()).extend_from_slice(slice);
+	}
+
+	fn extend<T: IntoIterator<Item = u8>>(&mut self, iter: T) {
+		let mut iter = iter.into_iter().peekable();
+		if let Self::Inline(inline) = self {
+			while let Some(&byte) = iter.peek() {
+				if !inline.push_slice(&[byte]) { break }
+				iter.next();
+			}
+		}
+
+		if iter.peek().is_some() {
+			self.vec_mut(iter.size_hint().0).extend(iter);
+		}
+	}
+
+	/// Splits off bytes after `at`, keeping the UTF-8 marker. Whether `at` falls on
+	/// a character boundary must be checked by the caller.
+	fn split_off(&mut self, at: usize) -> Self {
+		let split = Self::from_slice(&self[at..], self.is_utf8());
+		self.truncate(at);
+		split
+	}
+
+	/// Returns the data as a mutable slice. Whether the data remains valid UTF-8
+	/// must be checked by the caller.
+	fn as_mut_slice(&mut self) -> &mut [u8] {
+		match self {
+			Self::Inline(inline) => &mut inline.buf[..inline.len as usize],
+			_ => self.vec_mut(0)
+		}
+	}
+
+	/// Replaces bytes in `range` with `slice`. Whether the data remains valid UTF-8
+	/// must be checked by the caller.
+	fn splice(&mut self, range: Range<usize>, slice: &[u8]) {
+		if let Self::Inline(inline) = self {
+			if inline.splice(range.clone(), slice) { return }
+		}
+
+		self.vec_mut(slice.len()).splice(range, slice.iter().copied());
+	}
+
+	/// Retains bytes for which `f` returns `true`. Whether the data remains valid
+	/// UTF-8 must be checked by the caller.
+	fn retain(&mut self, mut f: impl FnMut(u8) -> bool) {
+		match self {
+			Self::Inline(inline) => inline.retain(f),
+			_ => self.vec_mut(0).retain(|&byte| f(byte))
 		}
 	}
 }
 
+impl Inline {
+	const EMPTY: Self = Self {
+		buf: [0; INLINE_LEN],
+		len: 0,
+		utf8: true,
+	};
+
+	fn new(data: &[u8], utf8: bool) -> Option<Self> {
+		let mut inline = Self { utf8, ..Self::EMPTY };
+		inline.push_slice(data).then_some(inline)
+	}
+
+	fn as_slice(&self) -> &[u8] {
+		&self.buf[..self.len as usize]
+	}
+
+	fn as_str(&self) -> Option<&str> {
+		self.utf8.then(|| unsafe {
+			// Safety: the bytes are marked as valid UTF-8.
+			std::str::from_utf8_unchecked(self.as_slice())
+		})
+	}
+
+	/// Appends `slice` if it fits, returning `true` if successful.
+	fn push_slice(&mut self, slice: &[u8]) -> bool {
+		let len = self.len as usize;
+		let Some(target) = self.buf.get_mut(len..len + slice.len()) else {
+			return false
+		};
+		target.copy_from_slice(slice);
+		self.len += slice.len() as u8;
+		true
+	}
+
+	/// Replaces bytes in `range` with `slice` if the result fits, returning `true`
+	/// if successful.
+	fn splice(&mut self, range: Range<usize>, slice: &[u8]) -> bool {
+		let len = self.len as usize;
+		let new_len = len - range.len() + slice.len();
+		if new_len > INLINE_LEN { return false }
+
+		self.buf.copy_within(range.end..len, range.start + slice.len());
+		self.buf[range.start..][..slice.len()].copy_from_slice(slice);
+		self.len = new_len as u8;
+		true
+	}
+
+	fn retain(&mut self, mut f: impl FnMut(u8) -> bool) {
+		let mut len = 0;
+		for i in 0..self.len as usize {
+			let byte = self.buf[i];
+			if f(byte) {
+				self.buf[len] = byte;
+				len += 1;
+			}
+		}
+		self.len = len as u8;
+	}
+}
+
 impl ByteString {
 	#[inline]
 	const fn from_data(data: Data) -> Self {
@@ -923,20 +1352,25 @@ impl ByteString {
 
 	fn into_utf8_unchecked(self) -> String {
 		match self.data {
-			Data::Bytes(bytes) => unsafe {
-				String::from_utf8_unchecked(bytes)
-			},
-			Data::String(utf8) => utf8
+			Data::String(utf8) => utf8,
+			data => unsafe {
+				String::from_utf8_unchecked(Self::from_data(data).into_bytes())
+			}
 		}
 	}
 
 	fn check_utf8_split(&mut self, idx: usize) {
-		match &self.data {
-			Data::String(str) if !str.is_char_boundary(idx) => self.unmark_utf8(),
+		match self.data.as_str() {
+			Some(str) if !str.is_char_boundary(idx) => self.unmark_utf8(),
 			_ => { }
 		}
 	}
 
+	fn check_utf8_range(&mut self, range: &Range<usize>) {
+		self.check_utf8_split(range.start);
+		self.check_utf8_split(range.end);
+	}
+
 	fn unmark_utf8(&mut self) {
 		self.data.unmark_utf8();
 	}
@@ -952,14 +1386,24 @@ impl Default for ByteString {
 impl fmt::Debug for ByteString {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		let mut repr = f.debug_struct("ByteString");
-		let repr = match &self.data {
-			Data::Bytes (bytes) => repr.field("data", bytes),
-			Data::String(str  ) => repr.field("data", &str)
+		let repr = match self.data.as_str() {
+			Some(str) => repr.field("data", &str),
+			None => repr.field("data", &&*self.data)
 		};
 		repr.finish()
 	}
 }
 
+impl fmt::Display for ByteString {
+	/// Formats the bytes as UTF-8, replacing invalid sequences with
+	/// [`U+FFFD REPLACEMENT CHARACTER`][U+FFFD].
+	///
+	/// [U+FFFD]: char::REPLACEMENT_CHARACTER
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Display::fmt(&self.to_utf8_lossy(), f)
+	}
+}
+
 impl<I> Index<I> for ByteString where [u8]: Index<I> {
 	type Output = <[u8] as Index<I>>::Output;
 
@@ -995,6 +1439,23 @@ impl PartialOrd for ByteString {
 	}
 }
 
+impl<'a> PartialOrd<ByteStr<'a>> for ByteString {
+	#[inline]
+	fn partial_cmp(&self, other: &ByteStr<'a>) -> Option<Ordering> {
+		other.partial_cmp(self.as_slice()).map(Ordering::reverse)
+	}
+}
+
+impl Hash for ByteString {
+	/// Hashes the bytes as a slice, consistent with its [`Borrow<[u8]>`] impl.
+	///
+	/// [`Borrow<[u8]>`]: std::borrow::Borrow
+	#[inline]
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.as_slice().hash(state)
+	}
+}
+
 impl Extend<u8> for ByteString {
 	fn extend<T: IntoIterator<Item = u8>>(&mut self, iter: T) {
 		self.unmark_utf8();
@@ -1006,10 +1467,10 @@ impl Extend<u8> for ByteString {
 mod test {
 	use base16ct::{lower, upper};
 	use base64::Engine;
-	use base64::engine::general_purpose::{STANDARD_NO_PAD, URL_SAFE_NO_PAD};
+	use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD};
 	use quickcheck::TestResult;
 	use quickcheck_macros::quickcheck;
-	use crate::{ByteStr, ByteString, EncodeBytes};
+	use crate::{ByteStr, ByteString, DecodeError, EncodeBytes};
 
 	#[quickcheck]
 	fn same_size_eq(data: Vec<u8>) {
@@ -1023,6 +1484,141 @@ mod test {
 		assert_eq!(a2, a1, "ByteString == ByteStr");
 	}
 
+	#[quickcheck]
+	fn inline(data: Vec<u8>, text: String, len: usize) {
+		let mut bytes = ByteString::from(&text[..]);
+		assert_eq!(bytes.checked_utf8(), Some(&text[..]));
+		bytes.extend_from_slice(&data);
+		assert_eq!(bytes.as_slice(), [text.as_bytes(), &data].concat());
+		assert_eq!(bytes.checked_utf8(), None, "extending with bytes should unmark UTF-8");
+
+		let len = len % (bytes.len() + 300);
+		let mut expected = bytes.clone().into_bytes();
+		expected.truncate(len);
+		bytes.truncate(len);
+		assert_eq!(bytes.as_slice(), expected);
+
+		let split = bytes.split_off(bytes.len() / 2);
+		assert_eq!([bytes.as_slice(), split.as_slice()].concat(), expected);
+	}
+
+	#[test]
+	fn inline_utf8() {
+		let mut bytes = ByteString::new();
+		bytes.extend_from_str("short");
+		assert_eq!(bytes.checked_utf8(), Some("short"));
+		bytes.extend_from_str(" string, now on the heap");
+		assert_eq!(bytes.checked_utf8(), Some("short string, now on the heap"));
+
+		let mut bytes = ByteString::from(&b"\xE2\x82\xAC"[..]);
+		assert_eq!(bytes.checked_utf8(), None);
+		assert_eq!(bytes.check_utf8().ok(), Some("\u{20AC}"));
+		bytes.truncate(1);
+		assert_eq!(bytes.checked_utf8(), None, "truncating within a character should unmark UTF-8");
+		assert_eq!(bytes.into_bytes(), b"\xE2");
+	}
+
+	#[quickcheck]
+	fn mutate(data: Vec<u8>, slice: Vec<u8>, idx: usize, len: usize, byte: u8) {
+		let idx = idx % (data.len() + 1);
+		let end = idx + len % (data.len() - idx + 1);
+		let mut bytes = ByteString::from(&data[..]);
+		let mut expected = data.clone();
+		bytes.insert_slice(idx, &slice);
+		expected.splice(idx..idx, slice.iter().copied());
+		assert_eq!(bytes.as_slice(), expected, "insert_slice");
+		bytes.insert(idx, byte);
+		expected.insert(idx, byte);
+		assert_eq!(bytes.as_slice(), expected, "insert");
+		let drained = bytes.drain(idx..end);
+		assert_eq!(drained.as_slice(), expected.drain(idx..end).as_slice(), "drain");
+		assert_eq!(bytes.as_slice(), expected, "drain");
+		bytes.remove(..idx);
+		expected.drain(..idx);
+		assert_eq!(bytes.as_slice(), expected, "remove");
+		bytes.retain(|b| b % 3 != 0);
+		expected.retain(|b| b % 3 != 0);
+		assert_eq!(bytes.as_slice(), expected, "retain");
+	}
+
+	#[test]
+	fn mutate_utf8() {
+		let mut bytes = ByteString::from("a\u{20AC}b");
+		bytes.insert_str(1, "\u{E9}");
+		assert_eq!(bytes.checked_utf8(), Some("a\u{E9}\u{20AC}b"));
+		bytes.insert(0, b'x');
+		assert_eq!(bytes.checked_utf8(), Some("xa\u{E9}\u{20AC}b"));
+		let drained = bytes.drain(2..4);
+		assert_eq!(drained.checked_utf8(), Some("\u{E9}"));
+		assert_eq!(bytes.checked_utf8(), Some("xa\u{20AC}b"));
+		bytes.retain(|b| b != b'a');
+		assert_eq!(bytes.checked_utf8(), Some("x\u{20AC}b"));
+		bytes.retain(|b| b != 0x82);
+		assert_eq!(bytes.checked_utf8(), None, "removing part of a character should unmark UTF-8");
+
+		let mut bytes = ByteString::from("\u{20AC}\u{20AC}");
+		bytes.retain(|b| b == b'?');
+		assert_eq!(bytes.checked_utf8(), Some(""), "removing whole characters should keep UTF-8");
+		let mut bytes = ByteString::from("\u{20AC}");
+		bytes.remove(1..);
+		assert_eq!(bytes.checked_utf8(), None);
+		let mut bytes = ByteString::from("ab");
+		bytes.insert(1, 0xFF);
+		assert_eq!(bytes.checked_utf8(), None);
+	}
+
+	#[quickcheck]
+	fn ascii_case(data: Vec<u8>, split: usize) {
+		let split = split % (data.len() + 1);
+		let (a, b) = data.split_at(split);
+		let bytes = ByteStr::from(vec![a, b]);
+		let lower = bytes.to_ascii_lowercase();
+		let upper = bytes.to_ascii_uppercase();
+		assert_eq!(lower.as_slice(), data.to_ascii_lowercase());
+		assert_eq!(upper.as_slice(), data.to_ascii_uppercase());
+		assert_eq!(bytes.is_ascii(), data.is_ascii());
+		assert_eq!(lower.is_ascii(), data.is_ascii());
+		assert!(bytes.eq_ignore_ascii_case(&upper.as_byte_str()));
+		assert!(lower.eq_ignore_ascii_case(&bytes));
+	}
+
+	#[test]
+	fn ascii_case_utf8() {
+		let bytes = ByteStr::from(vec![&b"Content-"[..], b"Type: \xC3\x89t\xC3\xA9"]);
+		assert!(bytes.eq_ignore_ascii_case(&ByteStr::from_utf8("CONTENT-TYPE: \u{C9}T\u{E9}")));
+		assert!(!bytes.eq_ignore_ascii_case(&ByteStr::from_utf8("content-type: \u{E9}t\u{E9}")));
+		assert!(!bytes.eq_ignore_ascii_case(&ByteStr::from_utf8("content-type")));
+		assert!(!bytes.is_ascii());
+
+		let mut owned = ByteString::from("Mixed \u{C9}Case");
+		owned.make_ascii_uppercase();
+		assert_eq!(owned.checked_utf8(), Some("MIXED \u{C9}CASE"), "case conversion should keep UTF-8");
+		assert_eq!(owned.to_ascii_lowercase().checked_utf8(), Some("mixed \u{C9}case"));
+	}
+
+	#[quickcheck]
+	fn chars(data: Vec<u8>, text: String, split: usize) {
+		let data = [data, text.into_bytes()].concat();
+		let split = split % (data.len() + 1);
+		let (a, b) = data.split_at(split);
+		let bytes = ByteStr::from(vec![a, b]);
+		assert!(bytes.chars().eq(String::from_utf8_lossy(&data).chars()));
+	}
+
+	#[test]
+	fn utf8_lossy() {
+		use std::borrow::Cow;
+
+		let split = ByteStr::from(vec![&b"ab\xE2\x82"[..], b"\xACcd\xFF"]);
+		assert_eq!(split.to_utf8_lossy(), "ab\u{20AC}cd\u{FFFD}");
+		assert_eq!(split.to_string(), "ab\u{20AC}cd\u{FFFD}");
+		assert!(matches!(ByteStr::from(&b"valid"[..]).to_utf8_lossy(), Cow::Borrowed("valid")));
+
+		let owned = ByteString::from(b"ok\xC0".to_vec());
+		assert_eq!(owned.to_utf8_lossy(), "ok\u{FFFD}");
+		assert_eq!(format!("{owned:>5}"), "  ok\u{FFFD}");
+	}
+
 	#[quickcheck]
 	fn split_eq(data: Vec<u8>, split: usize) -> TestResult {
 		if split >= data.len() {
@@ -1072,4 +1668,50 @@ mod test {
 		);
 		TestResult::passed()
 	}
+
+	#[quickcheck]
+	fn decode_rolling(data: Vec<u8>, split: usize) -> TestResult {
+		let base64 = STANDARD_NO_PAD.encode(&data);
+		let hex = upper::encode_string(&data);
+		if split >= base64.len() {
+			return TestResult::discard()
+		}
+
+		let (a, b) = base64.as_bytes().split_at(split);
+		let bstr = ByteStr::from(a) + ByteStr::from(b);
+		assert_eq!(bstr.decode_base64().unwrap().as_slice(), data, "standard base64");
+		assert_eq!(ByteString::from_base64(&base64).unwrap().as_slice(), data, "standard base64");
+		assert_eq!(ByteString::from_base64(STANDARD.encode(&data)).unwrap().as_slice(), data, "padded base64");
+		assert_eq!(ByteString::from_base64_url(URL_SAFE.encode(&data)).unwrap().as_slice(), data, "padded URL-safe base64");
+		assert_eq!(ByteStr::from(hex.as_bytes()).decode_hex().unwrap().as_slice(), data, "uppercase hex");
+		assert_eq!(ByteString::from_hex(&hex).unwrap().as_slice(), data, "uppercase hex");
+		TestResult::passed()
+	}
+
+	#[test]
+	fn decode_errors() {
+		assert_eq!(
+			ByteString::from_base64("AB*D"),
+			Err(DecodeError::InvalidByte { offset: 2, byte: b'*' })
+		);
+		assert_eq!(ByteString::from_base64("AAA=").unwrap().as_slice(), [0, 0], "padding should be accepted");
+		assert_eq!(ByteString::from_base64_with("AAA=", &STANDARD_NO_PAD), Err(DecodeError::InvalidPadding));
+		assert_eq!(ByteString::from_base64("A"), Err(DecodeError::InvalidLength));
+		assert_eq!(
+			ByteStr::from(&b"0fx0"[..]).decode_hex(),
+			Err(DecodeError::InvalidByte { offset: 2, byte: b'x' })
+		);
+		assert_eq!(ByteString::from_hex("abc"), Err(DecodeError::InvalidLength));
+	}
+
+	#[quickcheck]
+	fn extend(a: Vec<u8>, b: Vec<Vec<u8>>) {
+		let mut str = ByteStr::from_utf8("");
+		str.extend([&a[..]]);
+		str.extend(b.iter().map(Vec::as_slice));
+		let expected = [a.clone(), b.concat()].concat();
+		assert_eq!(str.len(), expected.len());
+		assert_eq!(str.cached_utf8(), None);
+		assert_eq!(str, expected[..]);
+	}
 }
@@ -1,7 +1,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::borrow::{Borrow, Cow};
-use super::{ByteStr, ByteString};
+use super::{ByteStr, ByteString, Data};
 use crate::{Seg, segment::RBuf};
 
 impl<'a> From<Vec<&'a [u8]>> for ByteStr<'a> {
@@ -38,6 +38,16 @@ impl<'a> FromIterator<&'a [u8]> for ByteStr<'a> {
 	}
 }
 
+impl<'a> Extend<&'a [u8]> for ByteStr<'a> {
+	fn extend<T: IntoIterator<Item = &'a [u8]>>(&mut self, iter: T) {
+		let start = self.data.len();
+		self.data.extend(iter);
+		self.len += self.data[start..].iter().copied().map(<[u8]>::len).sum::<usize>();
+		// The cached string no longer covers all data.
+		self.utf8 = None;
+	}
+}
+
 impl<'a> AsRef<[&'a [u8]]> for ByteStr<'a> {
 	fn as_ref(&self) -> &[&'a [u8]] {
 		&self.data
@@ -52,25 +62,31 @@ impl<'a> Borrow<[&'a [u8]]> for ByteStr<'a> {
 
 impl From<&str> for ByteString {
 	fn from(value: &str) -> Self {
-		value.to_owned().into()
+		Self::from_data(Data::from_slice(value.as_bytes(), true))
 	}
 }
 
 impl From<&[u8]> for ByteString {
 	fn from(value: &[u8]) -> Self {
-		value.to_owned().into()
+		Self::from_data(Data::from_slice(value, false))
 	}
 }
 
 impl<'a> From<Cow<'a, str>> for ByteString {
 	fn from(value: Cow<'a, str>) -> Self {
-		value.to_owned().into()
+		match value {
+			Cow::Borrowed(str) => str.into(),
+			Cow::Owned(string) => string.into()
+		}
 	}
 }
 
 impl<'a> From<Cow<'a, [u8]>> for ByteString {
 	fn from(value: Cow<'a, [u8]>) -> Self {
-		value.to_owned().into()
+		match value {
+			Cow::Borrowed(bytes) => bytes.into(),
+			Cow::Owned(vec) => vec.into()
+		}
 	}
 }
 
@@ -84,9 +100,9 @@ impl<'a> FromIterator<&'a u8> for ByteString {
 
 impl FromIterator<u8> for ByteString {
 	fn from_iter<T: IntoIterator<Item = u8>>(iter: T) -> Self {
-		iter.into_iter()
-			.collect::<Vec<_>>()
-			.into()
+		let mut bytes = Self::new();
+		bytes.extend(iter);
+		bytes
 	}
 }
 
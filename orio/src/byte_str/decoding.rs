@@ -1,52 +1,153 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use base64::prelude::{BASE64_STANDARD_NO_PAD, BASE64_URL_SAFE_NO_PAD};
-use super::{ByteString, Data};
+use std::borrow::Cow;
+use base64::alphabet;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use super::{ByteStr, ByteString, Data};
+
+/// Decodes base64 with or without padding.
+const PAD_INDIFFERENT: GeneralPurposeConfig =
+	GeneralPurposeConfig::new()
+		.with_encode_padding(false)
+		.with_decode_padding_mode(DecodePaddingMode::Indifferent);
+pub(crate) const STANDARD_PAD_INDIFFERENT: GeneralPurpose = GeneralPurpose::new(&alphabet::STANDARD, PAD_INDIFFERENT);
+pub(crate) const URL_SAFE_PAD_INDIFFERENT: GeneralPurpose = GeneralPurpose::new(&alphabet::URL_SAFE, PAD_INDIFFERENT);
+
+/// A base64 or hex decode error.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, thiserror::Error)]
+pub enum DecodeError {
+	/// A byte outside the encoding alphabet was found in the input. Padding found
+	/// before the end of the input is treated as an invalid byte.
+	#[error("invalid byte {byte:#04X} at offset {offset}")]
+	InvalidByte {
+		/// The offset of the invalid byte in the input.
+		offset: usize,
+		/// The invalid byte.
+		byte: u8
+	},
+	/// The last base64 symbol encodes trailing bits that would be discarded,
+	/// indicating truncated or corrupt input.
+	#[error("invalid last symbol {byte:#04X} at offset {offset}")]
+	InvalidLastSymbol {
+		/// The offset of the last symbol in the input.
+		offset: usize,
+		/// The last symbol.
+		byte: u8
+	},
+	/// The input length is invalid for the encoding.
+	#[error("invalid encoded length")]
+	InvalidLength,
+	/// Padding is absent, malformed, or present where it must be absent.
+	#[error("invalid padding")]
+	InvalidPadding,
+}
+
+impl From<base64::DecodeError> for DecodeError {
+	fn from(value: base64::DecodeError) -> Self {
+		use base64::DecodeError::*;
+		match value {
+			InvalidByte(offset, byte) => Self::InvalidByte { offset, byte },
+			InvalidLastSymbol(offset, byte) => Self::InvalidLastSymbol { offset, byte },
+			InvalidLength => Self::InvalidLength,
+			InvalidPadding => Self::InvalidPadding,
+		}
+	}
+}
+
+/// Decodes hex digits of either case from `input`.
+pub(crate) fn decode_hex(input: &[u8]) -> Result<Vec<u8>, DecodeError> {
+	base16ct::mixed::decode_vec(input).map_err(|_|
+		// base16ct doesn't report where decoding failed, find it ourselves.
+		match input.iter().position(|byte| !byte.is_ascii_hexdigit()) {
+			Some(offset) => DecodeError::InvalidByte { offset, byte: input[offset] },
+			None => DecodeError::InvalidLength
+		}
+	)
+}
 
 impl ByteString {
-	/// Decodes base64-encoded bytes into the byte string.
-	pub fn decode_base64<T: AsRef<[u8]>>(&mut self, input: T) -> Result<(), base64::DecodeError> {
-		self.decode_base64_with(input, &BASE64_STANDARD_NO_PAD)
+	/// Decodes base64-encoded bytes into the byte string, with or without
+	/// padding.
+	pub fn decode_base64<T: AsRef<[u8]>>(&mut self, input: T) -> Result<(), DecodeError> {
+		self.decode_base64_with(input, &STANDARD_PAD_INDIFFERENT)
 	}
 
-	/// Decodes URL-safe base64-encoded bytes into the byte string.
-	pub fn decode_base64_url<T: AsRef<[u8]>>(&mut self, input: T) -> Result<(), base64::DecodeError> {
-		self.decode_base64_with(input, &BASE64_URL_SAFE_NO_PAD)
+	/// Decodes URL-safe base64-encoded bytes into the byte string, with or without
+	/// padding.
+	pub fn decode_base64_url<T: AsRef<[u8]>>(&mut self, input: T) -> Result<(), DecodeError> {
+		self.decode_base64_with(input, &URL_SAFE_PAD_INDIFFERENT)
 	}
 
 	/// Decodes base64-encoded bytes into the byte string with a custom `decoder`.
-	pub fn decode_base64_with<T: AsRef<[u8]>>(&mut self, input: T, decoder: &impl base64::Engine) -> Result<(), base64::DecodeError> {
+	pub fn decode_base64_with<T: AsRef<[u8]>>(&mut self, input: T, decoder: &impl base64::Engine) -> Result<(), DecodeError> {
 		let mut buf = self.data.take_bytes();
-		decoder.decode_vec(input, &mut buf)?;
+		let result = decoder.decode_vec(input, &mut buf);
 		self.data = Data::Bytes(buf);
-		Ok(())
+		Ok(result?)
 	}
 
 	/// Decodes hex bytes into the byte string.
-	pub fn decode_hex<T: AsRef<[u8]>>(&mut self, input: T) -> Result<(), base16ct::Error> {
+	pub fn decode_hex<T: AsRef<[u8]>>(&mut self, input: T) -> Result<(), DecodeError> {
 		self.extend_from_slice(
 			Self::from_hex(input)?.as_slice()
 		);
 		Ok(())
 	}
 
-	/// Decodes base64-encoded bytes to a new byte string.
-	pub fn from_base64<T: AsRef<[u8]>>(input: T) -> Result<Self, base64::DecodeError> {
-		Self::from_base64_with(input, &BASE64_STANDARD_NO_PAD)
+	/// Decodes base64-encoded bytes to a new byte string, with or without
+	/// padding.
+	pub fn from_base64<T: AsRef<[u8]>>(input: T) -> Result<Self, DecodeError> {
+		Self::from_base64_with(input, &STANDARD_PAD_INDIFFERENT)
 	}
 
-	/// Decodes URL-safe base64-encoded bytes to a new byte string.
-	pub fn from_base64_url<T: AsRef<[u8]>>(input: T) -> Result<Self, base64::DecodeError> {
-		Self::from_base64_with(input, &BASE64_URL_SAFE_NO_PAD)
+	/// Decodes URL-safe base64-encoded bytes to a new byte string, with or without
+	/// padding.
+	pub fn from_base64_url<T: AsRef<[u8]>>(input: T) -> Result<Self, DecodeError> {
+		Self::from_base64_with(input, &URL_SAFE_PAD_INDIFFERENT)
 	}
 
 	/// Decodes base64-encoded bytes to a new byte string with a custom `decoder`.
-	pub fn from_base64_with<T: AsRef<[u8]>>(input: T, decoder: &impl base64::Engine) -> Result<Self, base64::DecodeError> {
-		decoder.decode(input).map(Into::into)
+	pub fn from_base64_with<T: AsRef<[u8]>>(input: T, decoder: &impl base64::Engine) -> Result<Self, DecodeError> {
+		Ok(decoder.decode(input)?.into())
 	}
 
 	/// Decodes hex bytes into a new byte string.
-	pub fn from_hex<T: AsRef<[u8]>>(input: T) -> Result<Self, base16ct::Error> {
-		base16ct::mixed::decode_vec(input).map(Into::into)
+	pub fn from_hex<T: AsRef<[u8]>>(input: T) -> Result<Self, DecodeError> {
+		decode_hex(input.as_ref()).map(Into::into)
+	}
+}
+
+impl ByteStr<'_> {
+	/// Decodes the base64-encoded byte string to a new byte string, with or
+	/// without padding.
+	pub fn decode_base64(&self) -> Result<ByteString, DecodeError> {
+		self.decode_base64_with(&STANDARD_PAD_INDIFFERENT)
+	}
+
+	/// Decodes the URL-safe base64-encoded byte string to a new byte string, with or
+	/// without padding.
+	pub fn decode_base64_url(&self) -> Result<ByteString, DecodeError> {
+		self.decode_base64_with(&URL_SAFE_PAD_INDIFFERENT)
+	}
+
+	/// Decodes the base64-encoded byte string to a new byte string with a custom
+	/// `decoder`.
+	pub fn decode_base64_with(&self, decoder: &impl base64::Engine) -> Result<ByteString, DecodeError> {
+		ByteString::from_base64_with(self.contiguous(), decoder)
+	}
+
+	/// Decodes the hex byte string to a new byte string.
+	pub fn decode_hex(&self) -> Result<ByteString, DecodeError> {
+		ByteString::from_hex(self.contiguous())
+	}
+
+	/// Returns the bytes as a contiguous slice, borrowing if the byte string has
+	/// at most one slice and copying otherwise.
+	fn contiguous(&self) -> Cow<'_, [u8]> {
+		match &self.data[..] {
+			[] => Cow::Borrowed(&[]),
+			&[slice] => Cow::Borrowed(slice),
+			slices => Cow::Owned(slices.concat())
+		}
 	}
 }
@@ -4,6 +4,8 @@
 
 use digest::Digest;
 use crate::{ByteStr, ByteString};
+#[cfg(feature = "xxhash")]
+use crate::xxhash;
 
 mod sealed {
 	pub trait HashBytes { }
@@ -40,6 +42,14 @@ macro_rules! hash {
 		/// cryptography is ***not*** secure. Use for checksums only.
 		$method
 	};
+    (fast $name:literal$method:item) => {
+		/// Computes a
+		#[doc = $name]
+		/// hash of the byte string. This hash function is fast, but not designed
+		/// for cryptography; its use there is ***not*** secure. Use for checksums
+		/// and deduplication only.
+		$method
+	};
 }
 
 pub trait HashBytes: sealed::HashBytes {
@@ -93,6 +103,18 @@ pub trait HashBytes: sealed::HashBytes {
 		secure "whirlpool" whirlpool
 		"Whirlpool" whirlpool Whirlpool
 	}
+
+	hash! {
+		secure "blake3" blake3
+		"BLAKE3" blake3 Hasher
+	}
+
+	hash! {
+		fast "xxhash" xxhash
+		"XXH3-64" xxh3_64 Xxh3_64
+		"XXH3-128" xxh3_128 Xxh3_128
+		"XXH64" xxh64 Xxh64
+	}
 }
 
 impl HashBytes for ByteStr<'_> {
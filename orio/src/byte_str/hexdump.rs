@@ -0,0 +1,100 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fmt;
+use std::ops::RangeBounds;
+use std::slice;
+use super::ByteStr;
+
+/// A [`Display`]able xxd-style dump of bytes, with a line for each `width` bytes
+/// containing the offset, the bytes in hex grouped in pairs, and the bytes as
+/// ASCII, with non-graphic characters replaced by `.`:
+///
+/// ```text
+/// 00000000: 4865 6c6c 6f2c 2077 6f72 6c64 210a       Hello, world!.
+/// ```
+///
+/// [`Display`]: fmt::Display
+#[derive(Clone, Debug)]
+pub struct HexDump<'a> {
+	data: ByteStr<'a>,
+	offset: usize,
+	width: usize,
+}
+
+impl<'a> HexDump<'a> {
+	/// The default number of bytes per line.
+	pub const DEFAULT_WIDTH: usize = 16;
+
+	pub(crate) fn new(data: ByteStr<'a>) -> Self {
+		Self {
+			data,
+			offset: 0,
+			width: Self::DEFAULT_WIDTH,
+		}
+	}
+
+	/// Sets the number of bytes per line.
+	///
+	/// # Panics
+	///
+	/// Panics if `width` is zero.
+	pub fn width(mut self, width: usize) -> Self {
+		assert!(width > 0, "width must be greater than zero");
+		self.width = width;
+		self
+	}
+
+	/// Limits the dump to bytes within `range`. Offsets are still displayed
+	/// relative to the start of the data.
+	///
+	/// # Panics
+	///
+	/// Panics if `range` is out of bounds.
+	pub fn range<R: RangeBounds<usize>>(mut self, range: R) -> Self {
+		let range = slice::range(range, ..self.data.len());
+		self.offset += range.start;
+		self.data = self.data.range(range);
+		self
+	}
+
+	fn fmt_line(&self, f: &mut fmt::Formatter<'_>, offset: usize, line: &[u8]) -> fmt::Result {
+		write!(f, "{offset:08x}:")?;
+		for i in 0..self.width {
+			if i % 2 == 0 {
+				f.write_str(" ")?;
+			}
+
+			match line.get(i) {
+				Some(byte) => write!(f, "{byte:02x}")?,
+				None => f.write_str("  ")?
+			}
+		}
+
+		f.write_str("  ")?;
+		for &byte in line {
+			let char = if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' };
+			write!(f, "{char}")?;
+		}
+		writeln!(f)
+	}
+}
+
+impl fmt::Display for HexDump<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let mut line = Vec::with_capacity(self.width);
+		let mut offset = self.offset;
+		for &byte in self.data.bytes() {
+			line.push(byte);
+			if line.len() == self.width {
+				self.fmt_line(f, offset, &line)?;
+				offset += line.len();
+				line.clear();
+			}
+		}
+
+		if !line.is_empty() {
+			self.fmt_line(f, offset, &line)?;
+		}
+		Ok(())
+	}
+}
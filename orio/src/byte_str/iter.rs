@@ -2,8 +2,8 @@
 
 use std::borrow::Cow;
 use std::cmp::min;
-use std::iter::{Copied, Flatten, FusedIterator};
-use std::ops::Range;
+use std::iter::{Copied, Flatten, FusedIterator, Peekable};
+use std::ops::{Range, RangeInclusive};
 use std::slice::Iter;
 use super::ByteStr;
 
@@ -15,6 +15,18 @@ pub struct Bytes<'a, 'b> {
 	len: usize
 }
 
+/// An iterator decoding UTF-8 characters from bytes, returned by [`ByteStr::chars`]
+/// and [`Buffer::chars`]. Characters are decoded lazily, including those split
+/// across slices. Invalid sequences are replaced with [`U+FFFD REPLACEMENT CHARACTER`][U+FFFD],
+/// one per maximal invalid subpart as with [`String::from_utf8_lossy`].
+///
+/// [`Buffer::chars`]: crate::Buffer::chars
+/// [U+FFFD]: char::REPLACEMENT_CHARACTER
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct Chars<I: Iterator<Item = u8>> {
+	iter: Peekable<I>
+}
+
 #[must_use = "iterators are lazy and do nothing unless consumed"]
 pub(super) struct SlicesInRange<'a, 'b> {
 	iter: Slices<'a, 'b>,
@@ -56,6 +68,62 @@ impl<'a: 'b, 'b> ExactSizeIterator for Bytes<'a, 'b> { }
 impl<'a: 'b, 'b> FusedIterator for Bytes<'a, 'b> { }
 // unsafe impl<'a: 'b, 'b> TrustedLen for Bytes<'a, 'b> { }
 
+impl<I: Iterator<Item = u8>> Chars<I> {
+	pub(crate) fn new(bytes: I) -> Self {
+		Self { iter: bytes.peekable() }
+	}
+
+	/// Consumes the next byte if it's within `range`, adding its payload bits to
+	/// `code`.
+	fn next_continuation(&mut self, range: RangeInclusive<u8>, code: &mut u32) -> bool {
+		match self.iter.next_if(|b| range.contains(b)) {
+			Some(b) => {
+				*code = *code << 6 | (b & 0x3F) as u32;
+				true
+			}
+			None => false
+		}
+	}
+}
+
+impl<I: Iterator<Item = u8>> Iterator for Chars<I> {
+	type Item = char;
+
+	fn next(&mut self) -> Option<char> {
+		const CONT: RangeInclusive<u8> = 0x80..=0xBF;
+
+		let first = self.iter.next()?;
+		let (code, second, rest) = match first {
+			0x00..=0x7F => return Some(first as char),
+			0xC2..=0xDF => (first & 0x1F, CONT, 0),
+			0xE0        => (first & 0x0F, 0xA0..=0xBF, 1),
+			0xED        => (first & 0x0F, 0x80..=0x9F, 1),
+			0xE1..=0xEF => (first & 0x0F, CONT, 1),
+			0xF0        => (first & 0x07, 0x90..=0xBF, 2),
+			0xF4        => (first & 0x07, 0x80..=0x8F, 2),
+			0xF1..=0xF3 => (first & 0x07, CONT, 2),
+			_ => return Some(char::REPLACEMENT_CHARACTER)
+		};
+		let mut code = code as u32;
+		if !self.next_continuation(second, &mut code) {
+			return Some(char::REPLACEMENT_CHARACTER)
+		}
+		for _ in 0..rest {
+			if !self.next_continuation(CONT, &mut code) {
+				return Some(char::REPLACEMENT_CHARACTER)
+			}
+		}
+		Some(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER))
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let (min, max) = self.iter.size_hint();
+		(min.div_ceil(4), max)
+	}
+}
+
+impl<I: Iterator<Item = u8> + FusedIterator> FusedIterator for Chars<I> { }
+
 impl<'a, 'b> SlicesInRange<'a, 'b> {
 	pub fn new(range: Range<usize>, iter: Slices<'a, 'b>) -> Self {
 		Self {
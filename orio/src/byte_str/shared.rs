@@ -0,0 +1,136 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fmt;
+use std::ops::RangeBounds;
+use std::slice;
+use crate::{Seg, SIZE};
+use super::{ByteStr, ByteString};
+
+/// A segmented string of bytes sharing segments with a [`Buffer`], returned by
+/// [`Buffer::to_shared_byte_str`]. Unlike [`ByteStr`], which borrows the buffer, a
+/// shared byte string holds its own references to the segment memory, so it can
+/// outlive the buffer borrow and be cloned in `O(n)` time, where `n` is the number
+/// of segments, without copying any bytes. Shared segments are reference-counted.
+/// With the `shared-pool` feature the count is atomic, so shared byte strings can
+/// be sent between threads; without it, they cannot.
+///
+/// Bytes are shared rather than borrowed only if they're in owned segments. Bytes
+/// borrowed by the buffer (i.e. with [`Buffer::push_slice`]) are borrowed for the
+/// same lifetime `'d`. Use [`Buffer::detached`] to produce a `'static` buffer first
+/// if needed.
+///
+/// [`Buffer`]: crate::Buffer
+/// [`Buffer::to_shared_byte_str`]: crate::Buffer::to_shared_byte_str
+/// [`Buffer::push_slice`]: crate::Buffer::push_slice
+/// [`Buffer::detached`]: crate::Buffer::detached
+#[derive(Clone, Default)]
+pub struct SharedByteStr<'d, const N: usize = SIZE> {
+	data: Vec<Seg<'d, N>>,
+	len: usize,
+}
+
+impl<'d, const N: usize> SharedByteStr<'d, N> {
+	/// Creates an empty shared byte string.
+	#[inline]
+	pub const fn new() -> Self {
+		Self {
+			data: Vec::new(),
+			len: 0,
+		}
+	}
+
+	/// Returns the length in bytes of the byte string.
+	#[inline]
+	pub fn len(&self) -> usize { self.len }
+	/// Returns `true` if the byte string is empty.
+	#[inline]
+	pub fn is_empty(&self) -> bool { self.len == 0 }
+	/// Returns `true` if the byte string is not empty.
+	#[inline]
+	pub fn is_not_empty(&self) -> bool { self.len > 0 }
+
+	/// Returns a shared byte string containing bytes within `range`, sharing
+	/// segments with this byte string.
+	pub fn range<R: RangeBounds<usize>>(&self, range: R) -> Self {
+		let range = slice::range(range, ..self.len);
+		let mut data = Vec::new();
+		let mut offset = 0;
+		for seg in &self.data {
+			let seg_range = offset..offset + seg.len();
+			offset = seg_range.end;
+			let start = range.start.max(seg_range.start);
+			let end = range.end.min(seg_range.end);
+			if start < end {
+				data.push(seg.share(start - seg_range.start..end - seg_range.start));
+			}
+		}
+		Self { data, len: range.len() }
+	}
+
+	/// Borrows the bytes as a [byte string](ByteStr).
+	pub fn as_byte_str(&self) -> ByteStr<'_> {
+		self.data
+			.iter()
+			.flat_map(|seg| {
+				let (a, b) = seg.as_slices();
+				[a, b]
+			})
+			.filter(|slice| !slice.is_empty())
+			.collect()
+	}
+
+	/// Copies the bytes into an owned [byte string](ByteString).
+	pub fn to_byte_string(&self) -> ByteString {
+		self.as_byte_str().to_byte_string()
+	}
+
+	/// Consumes the byte string, returning its shared segments.
+	pub fn into_segments(self) -> Vec<Seg<'d, N>> {
+		self.data
+	}
+}
+
+impl<'d, const N: usize> FromIterator<Seg<'d, N>> for SharedByteStr<'d, N> {
+	/// Creates a shared byte string from segments, skipping empty segments.
+	fn from_iter<T: IntoIterator<Item = Seg<'d, N>>>(iter: T) -> Self {
+		let data: Vec<_> = iter.into_iter()
+							   .filter(Seg::is_not_empty)
+							   .collect();
+		let len = data.iter().map(Seg::len).sum();
+		Self { data, len }
+	}
+}
+
+impl<const N: usize> fmt::Debug for SharedByteStr<'_, N> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("SharedByteStr")
+		 .field("data", &self.as_byte_str())
+		 .finish()
+	}
+}
+
+impl<const N: usize> Eq for SharedByteStr<'_, N> { }
+
+impl<const N: usize> PartialEq for SharedByteStr<'_, N> {
+	fn eq(&self, other: &Self) -> bool {
+		self.as_byte_str() == other.as_byte_str()
+	}
+}
+
+impl<const N: usize> PartialEq<[u8]> for SharedByteStr<'_, N> {
+	fn eq(&self, other: &[u8]) -> bool {
+		self.as_byte_str() == *other
+	}
+}
+
+impl<const N: usize, const L: usize> PartialEq<[u8; L]> for SharedByteStr<'_, N> {
+	fn eq(&self, other: &[u8; L]) -> bool {
+		self == other.as_slice()
+	}
+}
+
+impl<const N: usize> PartialEq<str> for SharedByteStr<'_, N> {
+	fn eq(&self, other: &str) -> bool {
+		self == other.as_bytes()
+	}
+}
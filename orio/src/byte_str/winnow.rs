@@ -0,0 +1,213 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! [`winnow`] input trait implementations for [`ByteStr`].
+
+use std::borrow::Cow;
+use std::iter::{Copied, Enumerate, Flatten};
+use std::num::NonZeroUsize;
+use std::ops::Range;
+use std::vec;
+use all_asserts::assert_le;
+use winnow::error::Needed;
+use winnow::stream::{Compare, CompareResult, FindSlice, Offset, SliceLen, Stream, StreamIsPartial, UpdateSlice};
+use super::ByteStr;
+
+impl<'a> ByteStr<'a> {
+	/// Removes `count` bytes from the front of the byte string without copying.
+	fn advance(&mut self, mut count: usize) {
+		assert_le!(count, self.len, "advance count out of bounds");
+		self.utf8 = match self.utf8.take() {
+			Some(Cow::Borrowed(str)) => str.get(count..).map(Cow::Borrowed),
+			// Dropped rather than copied, since this may be called for every token.
+			_ => None
+		};
+		self.len -= count;
+
+		let consumed = self.data
+						   .iter()
+						   .take_while(|slice|
+							   if count >= slice.len() {
+								   count -= slice.len();
+								   true
+							   } else {
+								   false
+							   }
+						   ).count();
+		self.data.drain(..consumed);
+		if count > 0 {
+			self.data[0] = &self.data[0][count..];
+		}
+	}
+
+	/// Compares the start of the byte string with `other`, as [`Compare`] expects.
+	fn compare_prefix(&self, other: &[u8]) -> CompareResult {
+		if self.bytes().zip(other).any(|(a, b)| a != b) {
+			CompareResult::Error
+		} else if self.len < other.len() {
+			CompareResult::Incomplete
+		} else {
+			CompareResult::Ok(other.len())
+		}
+	}
+}
+
+impl<'a> Stream for ByteStr<'a> {
+	type Token = u8;
+	type Slice = ByteStr<'a>;
+	type IterOffsets = Enumerate<Copied<Flatten<vec::IntoIter<&'a [u8]>>>>;
+	type Checkpoint = ByteStr<'a>;
+
+	fn iter_offsets(&self) -> Self::IterOffsets {
+		self.data
+			.clone()
+			.into_iter()
+			.flatten()
+			.copied()
+			.enumerate()
+	}
+
+	#[inline]
+	fn eof_offset(&self) -> usize { self.len }
+
+	fn next_token(&mut self) -> Option<u8> {
+		let token = self.peek_token()?;
+		self.advance(1);
+		Some(token)
+	}
+
+	#[inline]
+	fn peek_token(&self) -> Option<u8> {
+		self.get(0).copied()
+	}
+
+	fn offset_for<P: Fn(u8) -> bool>(&self, predicate: P) -> Option<usize> {
+		self.bytes().position(|&b| predicate(b))
+	}
+
+	fn offset_at(&self, tokens: usize) -> Result<usize, Needed> {
+		match tokens.checked_sub(self.len).and_then(NonZeroUsize::new) {
+			Some(needed) => Err(Needed::Size(needed)),
+			None => Ok(tokens)
+		}
+	}
+
+	fn next_slice(&mut self, offset: usize) -> ByteStr<'a> {
+		let slice = self.peek_slice(offset);
+		self.advance(offset);
+		slice
+	}
+
+	#[inline]
+	fn peek_slice(&self, offset: usize) -> ByteStr<'a> {
+		self.range(..offset)
+	}
+
+	#[inline]
+	fn checkpoint(&self) -> ByteStr<'a> { self.clone() }
+
+	#[inline]
+	fn reset(&mut self, checkpoint: &ByteStr<'a>) {
+		self.clone_from(checkpoint)
+	}
+
+	#[inline]
+	fn raw(&self) -> &dyn std::fmt::Debug { self }
+}
+
+impl StreamIsPartial for ByteStr<'_> {
+	type PartialState = ();
+
+	#[inline]
+	fn complete(&mut self) { }
+
+	#[inline]
+	fn restore_partial(&mut self, _state: ()) { }
+
+	#[inline]
+	fn is_partial_supported() -> bool { false }
+}
+
+impl Offset for ByteStr<'_> {
+	/// Returns the number of bytes consumed since `start`, which must be a previous
+	/// state of the same byte string.
+	#[inline]
+	fn offset_from(&self, start: &Self) -> usize {
+		start.len - self.len
+	}
+}
+
+impl SliceLen for ByteStr<'_> {
+	#[inline]
+	fn slice_len(&self) -> usize { self.len }
+}
+
+impl UpdateSlice for ByteStr<'_> {
+	#[inline]
+	fn update_slice(self, inner: Self::Slice) -> Self { inner }
+}
+
+impl Compare<u8> for ByteStr<'_> {
+	#[inline]
+	fn compare(&self, t: u8) -> CompareResult {
+		self.compare_prefix(&[t])
+	}
+}
+
+impl Compare<&[u8]> for ByteStr<'_> {
+	#[inline]
+	fn compare(&self, t: &[u8]) -> CompareResult {
+		self.compare_prefix(t)
+	}
+}
+
+impl<const LEN: usize> Compare<[u8; LEN]> for ByteStr<'_> {
+	#[inline]
+	fn compare(&self, t: [u8; LEN]) -> CompareResult {
+		self.compare_prefix(&t)
+	}
+}
+
+impl<const LEN: usize> Compare<&[u8; LEN]> for ByteStr<'_> {
+	#[inline]
+	fn compare(&self, t: &[u8; LEN]) -> CompareResult {
+		self.compare_prefix(t)
+	}
+}
+
+impl Compare<&str> for ByteStr<'_> {
+	#[inline]
+	fn compare(&self, t: &str) -> CompareResult {
+		self.compare_prefix(t.as_bytes())
+	}
+}
+
+impl FindSlice<u8> for ByteStr<'_> {
+	#[inline]
+	fn find_slice(&self, substr: u8) -> Option<Range<usize>> {
+		self.find(substr)
+	}
+}
+
+impl FindSlice<&[u8]> for ByteStr<'_> {
+	fn find_slice(&self, substr: &[u8]) -> Option<Range<usize>> {
+		if substr.is_empty() {
+			Some(0..0)
+		} else {
+			self.find(substr)
+		}
+	}
+}
+
+impl<const LEN: usize> FindSlice<&[u8; LEN]> for ByteStr<'_> {
+	#[inline]
+	fn find_slice(&self, substr: &[u8; LEN]) -> Option<Range<usize>> {
+		self.find_slice(&substr[..])
+	}
+}
+
+impl FindSlice<&str> for ByteStr<'_> {
+	#[inline]
+	fn find_slice(&self, substr: &str) -> Option<Range<usize>> {
+		self.find_slice(substr.as_bytes())
+	}
+}
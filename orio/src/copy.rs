@@ -0,0 +1,74 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Buffer, ResultContext, StreamResult};
+use crate::pool::{DefaultPoolContainer, Pool};
+use crate::streams::{Sink, Source};
+use crate::StreamContext::{Read, Write};
+
+/// Copies all data from `source` into `sink` until no more bytes can be read,
+/// returning the number of bytes copied. Data is moved through pooled segments,
+/// or moved directly if `source` is a [`Buffer`].
+pub fn copy<'d, const N: usize>(
+	source: &mut impl Source<'d, N>,
+	sink: &mut impl Sink<'d, N>
+) -> StreamResult<u64> where DefaultPoolContainer: Pool<N> {
+	copy_with_progress(source, sink, |_| { })
+}
+
+/// Copies all data from `source` into `sink` until no more bytes can be read,
+/// returning the number of bytes copied. `progress` is called with the total
+/// number of bytes copied so far each time data is written to `sink`.
+pub fn copy_with_progress<'d, const N: usize>(
+	source: &mut impl Source<'d, N>,
+	sink: &mut impl Sink<'d, N>,
+	mut progress: impl FnMut(u64)
+) -> StreamResult<u64> where DefaultPoolContainer: Pool<N> {
+	source.copy_spec(sink, &mut progress)
+}
+
+trait CopySpec<'d, const N: usize>: Source<'d, N> {
+	fn copy_spec(
+		&mut self,
+		sink: &mut impl Sink<'d, N>,
+		progress: &mut impl FnMut(u64)
+	) -> StreamResult<u64>;
+}
+
+impl<'d, const N: usize, S: Source<'d, N>> CopySpec<'d, N> for S
+where DefaultPoolContainer: Pool<N> {
+	default fn copy_spec(
+		&mut self,
+		sink: &mut impl Sink<'d, N>,
+		progress: &mut impl FnMut(u64)
+	) -> StreamResult<u64> {
+		let mut buffer = Buffer::<'d, N, DefaultPoolContainer>::default();
+		let mut total = 0;
+		loop {
+			let read = self.fill(&mut buffer, N).context(Read)?;
+			if read == 0 {
+				break
+			}
+
+			sink.drain_all(&mut buffer).context(Write)?;
+			total += read as u64;
+			progress(total);
+		}
+		Ok(total)
+	}
+}
+
+impl<'d, const N: usize, P: Pool<N>> CopySpec<'d, N> for Buffer<'d, N, P>
+where DefaultPoolContainer: Pool<N> {
+	/// Drains the buffer directly into the sink, without an intermediate buffer.
+	fn copy_spec(
+		&mut self,
+		sink: &mut impl Sink<'d, N>,
+		progress: &mut impl FnMut(u64)
+	) -> StreamResult<u64> {
+		let total = sink.drain_all(self).context(Write)? as u64;
+		if total > 0 {
+			progress(total);
+		}
+		Ok(total)
+	}
+}
@@ -6,8 +6,9 @@ use std::{error, fmt, io};
 use std::rc::Rc;
 use amplify_derive::{Display, From};
 use thiserror::Error;
-use crate::streams::{EndOfStream, StreamClosed};
+use crate::streams::{EndOfStream, StreamClosed, TimedOut};
 use crate::pool::PoolError;
+use crate::CapacityExceeded;
 pub use utf8::*;
 
 pub(crate) mod sealed {
@@ -101,18 +102,41 @@ pub enum ErrorSource {
 	Closed(#[from(StreamClosed)] StreamClosed),
 	/// End-of-stream was reached prematurely.
 	Eos(#[from(EndOfStream)] EndOfStream),
+	/// An operation didn't complete before its timeout.
+	TimedOut(#[from(TimedOut)] TimedOut),
 	/// An IO error.
 	Io(#[from(io::Error)] Rc<io::Error>), // Rc to get around io::Error not implementing Clone
 	/// A UTF-8 decode error.
 	Utf8(#[from(Utf8Error)] Utf8Error),
 	/// A pool error.
 	Pool(#[from(PoolError)] PoolError),
+	/// A buffer's maximum capacity would be exceeded.
+	CapacityExceeded(#[from(CapacityExceeded)] CapacityExceeded),
 	/// A stream error.
 	Stream(#[from(StreamError)] Box<StreamError>),
 	/// A buffer error.
 	Buffer(#[from(BufferError)] Box<BufferError>),
 }
 
+/// A classification of an [`Error`] by its underlying source, see [`Error::kind`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ErrorKind {
+	/// The underlying stream is closed.
+	Closed,
+	/// End-of-stream was reached prematurely.
+	Eos,
+	/// An operation didn't complete before its timeout.
+	TimedOut,
+	/// An IO error of the contained kind.
+	Io(io::ErrorKind),
+	/// A UTF-8 decode error.
+	Utf8,
+	/// A pool error.
+	Pool,
+	/// A buffer's maximum capacity would be exceeded.
+	CapacityExceeded,
+}
+
 pub trait ResultContext<T, C: sealed::Context> {
 	fn context(self, context: C) -> Result<T, Error<C>>;
 }
@@ -148,6 +172,15 @@ impl<C: sealed::Context + Default> From<EndOfStream> for Error<C> {
 	}
 }
 
+impl<C: sealed::Context + Default> From<TimedOut> for Error<C> {
+	fn from(value: TimedOut) -> Self {
+		Self {
+			source: value.into(),
+			context: C::default(),
+		}
+	}
+}
+
 impl<C: sealed::Context + Default> From<io::Error> for Error<C> {
 	fn from(value: io::Error) -> Self {
 		Self {
@@ -175,6 +208,15 @@ impl<C: sealed::Context + Default> From<PoolError> for Error<C> {
 	}
 }
 
+impl<C: sealed::Context + Default> From<CapacityExceeded> for Error<C> {
+	fn from(value: CapacityExceeded) -> Self {
+		Self {
+			source: value.into(),
+			context: C::default(),
+		}
+	}
+}
+
 impl From<BufferError> for StreamError {
 	fn from(value: BufferError) -> Self {
 		Self {
@@ -212,6 +254,12 @@ impl<C: sealed::Context> Error<C> {
 		self.context
 	}
 
+	/// Classifies the error by its source. Wrapped stream and buffer errors are
+	/// classified by their own source.
+	pub fn kind(&self) -> ErrorKind {
+		self.source.kind()
+	}
+
 	/// Returns true if the inner error is a "closed stream".
 	pub fn is_closed(&self) -> bool {
 		matches!(&self.source, ErrorSource::Closed(_))
@@ -222,11 +270,36 @@ impl<C: sealed::Context> Error<C> {
 		matches!(&self.source, ErrorSource::Eos(_))
 	}
 
+	/// Returns true if the inner error, or an error it wraps, is an "operation timed
+	/// out", or an IO error of kind [`TimedOut`].
+	///
+	/// [`TimedOut`]: io::ErrorKind::TimedOut
+	pub fn is_timed_out(&self) -> bool {
+		matches!(self.kind(), ErrorKind::TimedOut | ErrorKind::Io(io::ErrorKind::TimedOut))
+	}
+
 	/// Returns true if the inner error is an IO error.
 	pub fn is_io_error(&self) -> bool {
 		self.as_io_error().is_some()
 	}
 
+	/// Returns true if the inner error, or an error it wraps, is an IO error of
+	/// kind [`WouldBlock`]. This means a non-blocking stream has no data available
+	/// right now; unlike end-of-stream, more data may become available later.
+	///
+	/// [`WouldBlock`]: io::ErrorKind::WouldBlock
+	pub fn is_would_block(&self) -> bool {
+		self.kind() == ErrorKind::Io(io::ErrorKind::WouldBlock)
+	}
+
+	/// Returns true if the inner error, or an error it wraps, is an IO error of
+	/// kind [`Interrupted`]. The operation can typically be retried.
+	///
+	/// [`Interrupted`]: io::ErrorKind::Interrupted
+	pub fn is_interrupted(&self) -> bool {
+		self.kind() == ErrorKind::Io(io::ErrorKind::Interrupted)
+	}
+
 	/// Returns true if the inner error is a UTF-8 decode error.
 	pub fn is_utf8_error(&self) -> bool {
 		self.as_utf8_error().is_some()
@@ -237,6 +310,11 @@ impl<C: sealed::Context> Error<C> {
 		self.as_pool_error().is_some()
 	}
 
+	/// Returns true if the inner error is a "capacity exceeded" error.
+	pub fn is_capacity_exceeded(&self) -> bool {
+		matches!(&self.source, ErrorSource::CapacityExceeded(_))
+	}
+
 	/// Returns true if the inner error is a stream error.
 	pub fn is_stream_error(&self) -> bool {
 		self.as_stream_error().is_some()
@@ -290,6 +368,37 @@ impl<C: sealed::Context> Error<C> {
 	}
 }
 
+impl ErrorSource {
+	/// Classifies the error source, unwrapping stream and buffer errors.
+	pub fn kind(&self) -> ErrorKind {
+		match self {
+			Self::Closed(_) => ErrorKind::Closed,
+			Self::Eos(_) => ErrorKind::Eos,
+			Self::TimedOut(_) => ErrorKind::TimedOut,
+			Self::Io(error) => ErrorKind::Io(error.kind()),
+			Self::Utf8(_) => ErrorKind::Utf8,
+			Self::Pool(_) => ErrorKind::Pool,
+			Self::CapacityExceeded(_) => ErrorKind::CapacityExceeded,
+			Self::Stream(error) => error.kind(),
+			Self::Buffer(error) => error.kind(),
+		}
+	}
+}
+
+impl From<ErrorKind> for io::ErrorKind {
+	fn from(value: ErrorKind) -> Self {
+		match value {
+			ErrorKind::Closed => Self::BrokenPipe,
+			ErrorKind::Eos => Self::UnexpectedEof,
+			ErrorKind::TimedOut => Self::TimedOut,
+			ErrorKind::Io(kind) => kind,
+			ErrorKind::Utf8 => Self::InvalidData,
+			ErrorKind::Pool => Self::Other,
+			ErrorKind::CapacityExceeded => Self::OutOfMemory,
+		}
+	}
+}
+
 impl<T, C: sealed::Context, E: Into<ErrorSource>> ResultContext<T, C> for Result<T, E> {
 	fn context(self, context: C) -> Result<T, Error<C>> {
 		self.map_err(|err| Error { source: err.into(), context })
@@ -314,21 +423,63 @@ impl<C: sealed::Context> From<Error<C>> for io::Error {
 impl From<ErrorSource> for io::Error {
 	fn from(value: ErrorSource) -> Self {
 		use crate::error::ErrorSource::*;
-		use io::ErrorKind::UnexpectedEof;
 
+		let kind = value.kind().into();
 		match value {
-			Eos(err) => Self::new(UnexpectedEof, err),
+			Eos(err) => Self::new(kind, err),
+			TimedOut(err) => Self::new(kind, err),
 			Io(err) =>
 				Rc::try_unwrap(err)
-					.unwrap_or_else(|err|
+					.unwrap_or_else(|_|
 						// Can't completely reconstruct a shared IO error
-						err.kind().into()
+						kind.into()
 					),
-			Closed(err) => Self::other(err),
-			Utf8(err) => Self::other(err),
-			Pool(err) => Self::other(err),
+			Closed(err) => Self::new(kind, err),
+			Utf8(err) => Self::new(kind, err),
+			Pool(err) => Self::new(kind, err),
+			CapacityExceeded(err) => Self::new(kind, err),
 			Stream(err) => err.source.into(),
 			Buffer(err) => err.source.into(),
 		}
 	}
 }
+
+#[cfg(test)]
+mod test {
+	use std::io;
+	use crate::streams::{EndOfStream, StreamClosed, TimedOut};
+	use super::{BufferContext, BufferError, ErrorKind, StreamContext, StreamError};
+
+	#[test]
+	fn kind() {
+		let error = StreamError::from(io::Error::from(io::ErrorKind::Interrupted));
+		assert_eq!(error.kind(), ErrorKind::Io(io::ErrorKind::Interrupted));
+		assert!(error.is_interrupted());
+		assert!(!error.is_would_block());
+
+		let error = StreamError::from(BufferError::closed(BufferContext::Fill));
+		assert_eq!(error.kind(), ErrorKind::Closed);
+		let error = BufferError::from(StreamError::from(EndOfStream::from(1)));
+		assert_eq!(error.kind(), ErrorKind::Eos);
+		let error = BufferError::from(StreamError::from(io::Error::from(io::ErrorKind::WouldBlock)));
+		assert!(error.is_would_block());
+		let error = BufferError::from(StreamError::from(TimedOut));
+		assert!(error.is_timed_out());
+		let error = StreamError::from(io::Error::from(BufferError::from(TimedOut)));
+		assert!(error.is_timed_out());
+	}
+
+	#[test]
+	fn into_io_error() {
+		let error = io::Error::from(StreamError::closed(StreamContext::Read));
+		assert_eq!(error.kind(), io::ErrorKind::BrokenPipe);
+		let error = io::Error::from(StreamError::from(EndOfStream::from(1)));
+		assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+		let error = io::Error::from(BufferError::from(StreamError::from(StreamClosed)));
+		assert_eq!(error.kind(), io::ErrorKind::BrokenPipe);
+		let error = io::Error::from(BufferError::from(crate::CapacityExceeded));
+		assert_eq!(error.kind(), io::ErrorKind::OutOfMemory);
+		let error = io::Error::from(StreamError::from(io::Error::other("custom")));
+		assert_eq!(error.to_string(), "custom");
+	}
+}
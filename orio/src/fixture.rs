@@ -0,0 +1,183 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Fixtures for property testing and fuzzing code consuming orio types, enabled
+//! by the `quickcheck` and `arbitrary` features.
+//!
+//! Buffers read from real streams are fragmented across many partially-filled and
+//! shared segments. [`BufferFixture`] generates this fragmentation, producing
+//! [`Buffer`]s and [`ByteStr`]s with arbitrary segment layouts.
+
+use crate::{Buffer, ByteStr, Seg, SIZE};
+
+/// A segment layout, see [`BufferFixture`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SegmentFixture {
+	/// The segment data. Its length plus `offset` is at most [`SIZE`].
+	pub data: Vec<u8>,
+	/// The number of bytes consumed from the start of the segment before the data.
+	pub offset: usize,
+	/// Whether the segment is shared. Shared segments of two or more bytes are
+	/// split into two segments sharing one block, which must be forked to write.
+	pub shared: bool,
+}
+
+/// A layout of bytes across buffer segments, producing fragmented [`Buffer`]s and
+/// [`ByteStr`]s for property tests and fuzzing.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BufferFixture {
+	/// The non-empty segments.
+	pub segments: Vec<SegmentFixture>,
+}
+
+impl SegmentFixture {
+	fn new(mut data: Vec<u8>, offset: usize, shared: bool) -> Self {
+		let offset = offset % SIZE;
+		data.truncate(SIZE - offset);
+		Self { data, offset, shared }
+	}
+
+	/// Appends the segment, or the two segments sharing its block, to `segments`.
+	fn push_to(&self, segments: &mut Vec<Seg<'static>>) {
+		let mut seg = Seg::new_block();
+		let _ = seg.write(&vec![0; self.offset]);
+		seg.consume(self.offset);
+		let _ = seg.write(&self.data);
+		if self.shared && self.data.len() > 1 {
+			let mid = self.data.len() / 2;
+			segments.extend([seg.share(..mid), seg.share(mid..)]);
+		} else {
+			segments.push(seg);
+		}
+	}
+}
+
+impl BufferFixture {
+	/// Returns the total number of bytes in the fixture.
+	pub fn len(&self) -> usize {
+		self.segments.iter().map(|seg| seg.data.len()).sum()
+	}
+
+	/// Returns `true` if the fixture contains no bytes.
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Returns a buffer with the fixture's segment layout.
+	pub fn to_buffer(&self) -> Buffer<'_> {
+		let mut segments = Vec::with_capacity(self.segments.len());
+		for seg in self.segments.iter().filter(|seg| !seg.data.is_empty()) {
+			seg.push_to(&mut segments);
+		}
+		Buffer::from_segments(segments)
+	}
+
+	/// Returns a byte string with a slice for each segment.
+	pub fn as_byte_str(&self) -> ByteStr<'_> {
+		self.segments
+			.iter()
+			.map(|seg| &seg.data[..])
+			.filter(|seg| !seg.is_empty())
+			.collect::<Vec<_>>()
+			.into()
+	}
+
+	/// Returns the bytes in the fixture, concatenated.
+	pub fn to_vec(&self) -> Vec<u8> {
+		self.segments
+			.iter()
+			.flat_map(|seg| seg.data.iter().copied())
+			.collect()
+	}
+}
+
+#[cfg(feature = "quickcheck")]
+mod quickcheck_impl {
+	use quickcheck::{Arbitrary, Gen};
+	use crate::ByteString;
+	use super::{BufferFixture, SegmentFixture};
+
+	impl Arbitrary for SegmentFixture {
+		fn arbitrary(g: &mut Gen) -> Self {
+			Self::new(
+				Vec::arbitrary(g),
+				usize::arbitrary(g),
+				bool::arbitrary(g)
+			)
+		}
+
+		fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+			let Self { offset, shared, .. } = *self;
+			Box::new(
+				self.data
+					.shrink()
+					.map(move |data| Self { data, offset, shared })
+			)
+		}
+	}
+
+	impl Arbitrary for BufferFixture {
+		fn arbitrary(g: &mut Gen) -> Self {
+			Self { segments: Vec::arbitrary(g) }
+		}
+
+		fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+			Box::new(
+				self.segments
+					.shrink()
+					.map(|segments| Self { segments })
+			)
+		}
+	}
+
+	impl Arbitrary for ByteString {
+		fn arbitrary(g: &mut Gen) -> Self {
+			if bool::arbitrary(g) {
+				String::arbitrary(g).into()
+			} else {
+				Vec::<u8>::arbitrary(g).into()
+			}
+		}
+
+		fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+			Box::new(
+				self.as_slice()
+					.to_vec()
+					.shrink()
+					.map(Into::into)
+			)
+		}
+	}
+}
+
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impl {
+	use arbitrary::{Arbitrary, Result, Unstructured};
+	use crate::ByteString;
+	use super::{BufferFixture, SegmentFixture};
+
+	impl<'a> Arbitrary<'a> for SegmentFixture {
+		fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+			Ok(Self::new(
+				u.arbitrary()?,
+				u.arbitrary()?,
+				u.arbitrary()?
+			))
+		}
+	}
+
+	impl<'a> Arbitrary<'a> for BufferFixture {
+		fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+			Ok(Self { segments: u.arbitrary()? })
+		}
+	}
+
+	impl<'a> Arbitrary<'a> for ByteString {
+		fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+			Ok(if u.arbitrary()? {
+				String::arbitrary(u)?.into()
+			} else {
+				Vec::<u8>::arbitrary(u)?.into()
+			})
+		}
+	}
+}
@@ -25,7 +25,9 @@
 //! Compacting these on every write could be costly, but keeping them is less space
 //! efficient which would lead to more allocations. As fragmentation size reaches a
 //! threshold, 4096B by default, all segments are compacted. This can also be triggered
-//! manually with the `compact` function.
+//! manually with [`Buffer::compact`], or tuned with the [compaction options].
+//!
+//! [compaction options]: BufferOptions#compaction
 //!
 //! Segments can be allocated when: 1) a buffer requests one but the pool has none
 //! left, or 2) a shared segment is created then written to.
@@ -40,6 +42,7 @@
 	extend_one,
 	extract_if,
 	generic_const_exprs,
+	hasher_prefixfree_extras,
 	int_roundings,
 	iter_is_partitioned,
 	maybe_uninit_slice,
@@ -50,15 +53,16 @@
 	slice_as_chunks,
 	slice_range,
 	specialization,
-	thread_local,
 	try_blocks,
 	type_alias_impl_trait,
 )]
 #![feature(iter_advance_by)]
+#![cfg_attr(not(feature = "shared-pool"), feature(thread_local))]
 
 mod buffer;
 mod buffered_wrappers;
 mod byte_str;
+mod copy;
 mod error;
 pub mod streams;
 mod segment;
@@ -66,8 +70,15 @@ mod element;
 pub mod pool;
 mod util;
 pub mod pattern;
+#[cfg(any(feature = "quickcheck", feature = "arbitrary"))]
+pub mod fixture;
+#[cfg(feature = "winnow")]
+pub mod winnow;
+#[cfg(feature = "xxhash")]
+pub mod xxhash;
 
 pub use error::*;
 pub use buffer::*;
 pub use byte_str::*;
+pub use copy::*;
 pub use segment::*;
@@ -11,6 +11,7 @@ mod matchers;
 
 use std::ops::Range;
 pub use matchers::*;
+use crate::{ByteStr, ByteString};
 use crate::util::{AssertNonZero, IsTrue};
 
 pub trait Pattern: Sized {
@@ -51,6 +52,32 @@ pub trait Pattern: Sized {
 #[derive(Copy, Clone, Debug, Default)]
 pub struct LineTerminator;
 
+/// A pattern matching a byte sequence, ignoring ASCII case. Created by
+/// [`ascii_ignore_case`].
+#[derive(Copy, Clone, Debug)]
+pub struct AsciiIgnoreCase<'p>(&'p [u8]);
+
+/// Creates a pattern matching `pattern` with ASCII letters compared case-insensitively,
+/// without copying or lowercasing the haystack. Useful for matching header names
+/// such as `Content-Length` in HTTP-style protocols. Non-ASCII bytes must match
+/// exactly.
+///
+/// # Example
+///
+/// ```
+/// use orio::pattern::{ascii_ignore_case, Pattern};
+///
+/// let range = ascii_ignore_case(b"content-length").find_in([
+///     &b"Host: example.com\r\nContent-"[..],
+///     &b"Length: 5\r\n"[..],
+/// ]);
+/// assert_eq!(range, Some(19..33));
+/// ```
+#[inline]
+pub fn ascii_ignore_case(pattern: &[u8]) -> AsciiIgnoreCase<'_> {
+	AsciiIgnoreCase(pattern)
+}
+
 /// A pattern matching either [`Ascii`] or [`Unicode`] whitespace greedily.
 ///
 /// [`Ascii`]: Whitespace::Ascii,
@@ -127,6 +154,69 @@ impl<'p> Pattern for &'p str {
 	}
 }
 
+impl<'p, const N: usize> Pattern for &'p [u8; N] where AssertNonZero<N>: IsTrue {
+	type Matcher = SliceMatcher<'p>;
+
+	/// Creates a matcher for a non-zero length byte array.
+	#[inline]
+	fn into_matcher(self) -> Self::Matcher {
+		self[..].into()
+	}
+}
+
+impl<const N: usize> Pattern for [u8; N] where AssertNonZero<N>: IsTrue {
+	type Matcher = OwnedSliceMatcher<Self>;
+
+	/// Creates a matcher for a non-zero length byte array.
+	#[inline]
+	fn into_matcher(self) -> Self::Matcher {
+		self.into()
+	}
+}
+
+impl Pattern for Vec<u8> {
+	type Matcher = OwnedSliceMatcher<Self>;
+
+	/// Creates a matcher for the bytes. Panics if the vector is empty.
+	#[inline]
+	fn into_matcher(self) -> Self::Matcher {
+		assert!(!self.is_empty(), "pattern slice length should be non-zero");
+		self.into()
+	}
+}
+
+impl Pattern for String {
+	type Matcher = OwnedSliceMatcher<Vec<u8>>;
+
+	/// Creates a matcher for the string. Panics if the string is empty.
+	#[inline]
+	fn into_matcher(self) -> Self::Matcher {
+		self.into_bytes().into_matcher()
+	}
+}
+
+impl<'p> Pattern for &'p ByteString {
+	type Matcher = SliceMatcher<'p>;
+
+	/// Creates a matcher for the byte string. Panics if the byte string is empty.
+	#[inline]
+	fn into_matcher(self) -> Self::Matcher {
+		self.as_slice().into_matcher()
+	}
+}
+
+impl<'p> Pattern for &'p ByteStr<'_> {
+	type Matcher = SegmentedSliceMatcher<'p>;
+
+	/// Creates a matcher for the byte string's slices, matched as one contiguous
+	/// sequence. Panics if the byte string is empty.
+	#[inline]
+	fn into_matcher(self) -> Self::Matcher {
+		assert!(self.is_not_empty(), "pattern slice length should be non-zero");
+		self.as_slices().into()
+	}
+}
+
 // Pattern trait can't be implemented for both FnMut(&u8) and FnMut(&char), or we
 // get the "conflicting implementations" error. We can only do blanket impls for
 // either one or neither, so we'll just do the latter for now. This can be revisited
@@ -192,3 +282,27 @@ impl Pattern for Whitespace {
 		self.into()
 	}
 }
+
+impl<'p> Pattern for AsciiIgnoreCase<'p> {
+	type Matcher = CaseInsensitiveSliceMatcher<'p>;
+
+	/// Creates a case-insensitive matcher for the slice. Panics if the slice is
+	/// empty.
+	#[inline]
+	fn into_matcher(self) -> Self::Matcher {
+		assert!(!self.0.is_empty(), "pattern slice length should be non-zero");
+		self.0.into()
+	}
+}
+
+impl<M: Matcher + ?Sized> Pattern for &mut M {
+	type Matcher = Self;
+
+	/// Resets and borrows the matcher, allowing a matcher to be reused across
+	/// searches rather than created for each one.
+	#[inline]
+	fn into_matcher(self) -> Self::Matcher {
+		self.reset();
+		self
+	}
+}
@@ -145,6 +145,13 @@ pub trait Matcher {
 	/// the longest whitespace sequence found in the input.
 	#[inline]
 	fn end(&mut self) -> Option<MatchStep> { None }
+	/// Resets the matcher state, discarding any partial match, so it can be reused
+	/// for another search. By default, this calls [`end`](Self::end) and discards
+	/// the result.
+	#[inline]
+	fn reset(&mut self) {
+		self.end();
+	}
 	/// Returns the pattern alignment. When [`Char`] is returned, haystack fragments
 	/// must be aligned such that they contains only valid UTF-8 characters before
 	/// being passed to [`next`](Self::next). If alignment is not respected, the
@@ -161,6 +168,33 @@ pub trait Matcher {
 	}
 }
 
+impl<M: Matcher + ?Sized> Matcher for &mut M {
+	#[inline]
+	fn next(&mut self, haystack: &[u8], offset: usize) -> Option<MatchStep> {
+		M::next(self, haystack, offset)
+	}
+
+	#[inline]
+	fn next_in_str(&mut self, haystack: &str, offset: usize) -> Option<MatchStep> {
+		M::next_in_str(self, haystack, offset)
+	}
+
+	#[inline]
+	fn end(&mut self) -> Option<MatchStep> {
+		M::end(self)
+	}
+
+	#[inline]
+	fn reset(&mut self) {
+		M::reset(self)
+	}
+
+	#[inline]
+	fn alignment(&self) -> Alignment {
+		M::alignment(self)
+	}
+}
+
 /// Provides methods for iterating over matcher steps.
 pub trait MatchIter: Matcher + Sized {
 	/// Iterates over all match steps in a `haystack` iterator. [`alignment`] is
@@ -269,10 +303,6 @@ impl PartialMatch {
 		self.count += count;
 		self.count
 	}
-
-	fn remaining_in<'a>(&self, pattern: &'a [u8]) -> &'a [u8] {
-		&pattern[self.count..]
-	}
 }
 
 /// A matcher for a byte sequence slice.
@@ -299,41 +329,107 @@ impl<'a> From<&'a str> for SliceMatcher<'a> {
 
 impl Matcher for SliceMatcher<'_> {
 	fn next(&mut self, haystack: &[u8], offset: usize) -> Option<MatchStep> {
-		if haystack.is_empty() {
-			return None
+		next_slice_step(self.pattern, &mut self.partial, haystack, offset, <[u8]>::eq)
+	}
+
+	fn end(&mut self) -> Option<MatchStep> {
+		match self.partial.reset() {
+			(_, 0) => None,
+			(_, _) => Some(MatchStep::reject(0))
 		}
+	}
+}
 
-		self.partial.reset_invalid(offset);
+/// A matcher for an owned byte sequence, such as a [`Vec<u8>`] or byte array. This
+/// allows matchers to be created from delimiters stored by value.
+#[derive(Copy, Clone, Debug)]
+pub struct OwnedSliceMatcher<P> {
+	pattern: P,
+	partial: PartialMatch
+}
 
-		let step = if self.partial.is_empty() {
-			if let Some((start, count)) = find_partial(haystack, &self.pattern) {
-				if count == self.pattern.len() {
-					MatchStep::complete(start + offset, count, start + self.pattern.len())
-				} else {
-					self.partial.start(start + offset, count);
-					MatchStep::partial(start + offset, count)
-				}
-			} else {
-				MatchStep::reject(haystack.len())
-			}
-		} else if let Some(count) = extend_partial(
-			haystack,
-			self.partial.remaining_in(&self.pattern)
-		) {
-			let partial_count = self.partial.extend_by(count);
-			assert_le!(partial_count, self.pattern.len());
-			if partial_count == self.pattern.len() {
-				let consumed = count;
-				let (start, count) = self.partial.reset();
-				MatchStep::complete(start, count, consumed)
-			} else {
-				MatchStep::partial(self.partial.start, count)
-			}
-		} else {
-			MatchStep::reject(haystack.len())
-		};
+impl<P: Borrow<[u8]>> From<P> for OwnedSliceMatcher<P> {
+	fn from(pattern: P) -> Self {
+		Self {
+			pattern,
+			partial: PartialMatch::default()
+		}
+	}
+}
 
-		Some(step)
+impl<P: Borrow<[u8]>> Matcher for OwnedSliceMatcher<P> {
+	fn next(&mut self, haystack: &[u8], offset: usize) -> Option<MatchStep> {
+		next_slice_step(self.pattern.borrow(), &mut self.partial, haystack, offset, <[u8]>::eq)
+	}
+
+	fn end(&mut self) -> Option<MatchStep> {
+		match self.partial.reset() {
+			(_, 0) => None,
+			(_, _) => Some(MatchStep::reject(0))
+		}
+	}
+}
+
+/// A matcher for a byte sequence split across multiple slices, such as a segmented
+/// [`ByteStr`]. The pattern is matched as if it were contiguous, without copying.
+///
+/// [`ByteStr`]: crate::ByteStr
+#[derive(Copy, Clone, Debug)]
+pub struct SegmentedSliceMatcher<'a> {
+	pattern: &'a [&'a [u8]],
+	partial: PartialMatch
+}
+
+impl<'a> From<&'a [&'a [u8]]> for SegmentedSliceMatcher<'a> {
+	fn from(pattern: &'a [&'a [u8]]) -> Self {
+		Self {
+			pattern,
+			partial: PartialMatch::default()
+		}
+	}
+}
+
+impl Matcher for SegmentedSliceMatcher<'_> {
+	fn next(&mut self, haystack: &[u8], offset: usize) -> Option<MatchStep> {
+		next_slice_step(self.pattern, &mut self.partial, haystack, offset, <[u8]>::eq)
+	}
+
+	fn end(&mut self) -> Option<MatchStep> {
+		match self.partial.reset() {
+			(_, 0) => None,
+			(_, _) => Some(MatchStep::reject(0))
+		}
+	}
+}
+
+/// A matcher for a byte sequence slice, ignoring ASCII case. Non-ASCII bytes must
+/// match exactly. Created by [`ascii_ignore_case`].
+///
+/// [`ascii_ignore_case`]: super::ascii_ignore_case
+#[derive(Copy, Clone, Debug)]
+pub struct CaseInsensitiveSliceMatcher<'a> {
+	pattern: &'a [u8],
+	partial: PartialMatch
+}
+
+impl<'a> From<&'a [u8]> for CaseInsensitiveSliceMatcher<'a> {
+	fn from(pattern: &'a [u8]) -> Self {
+		Self {
+			pattern,
+			partial: PartialMatch::default()
+		}
+	}
+}
+
+impl<'a> From<&'a str> for CaseInsensitiveSliceMatcher<'a> {
+	fn from(pattern: &'a str) -> Self {
+		pattern.as_bytes().into()
+	}
+}
+
+impl Matcher for CaseInsensitiveSliceMatcher<'_> {
+	fn next(&mut self, haystack: &[u8], offset: usize) -> Option<MatchStep> {
+		next_slice_step(self.pattern, &mut self.partial, haystack, offset, <[u8]>::eq_ignore_ascii_case)
 	}
 
 	fn end(&mut self) -> Option<MatchStep> {
@@ -834,30 +930,139 @@ impl<'a, T> Iterator for ShrinkingWindows<'a, T> {
 	}
 }
 
-fn find_partial(haystack: &[u8], needle: &[u8]) -> Option<(usize, usize)> {
-	if let [byte] = needle {
-		return haystack.iter().position(|b| b == byte).map(|i| (i, 1))
+/// A slice matcher's pattern, either contiguous or split across fragments.
+trait Needle {
+	/// Returns the pattern length in bytes.
+	fn byte_len(&self) -> usize;
+
+	/// Returns `true` if `bytes` is equal to pattern bytes starting at `start`,
+	/// comparing with `eq`.
+	fn eq_at(&self, start: usize, bytes: &[u8], eq: impl Fn(&[u8], &[u8]) -> bool) -> bool;
+}
+
+impl Needle for [u8] {
+	#[inline]
+	fn byte_len(&self) -> usize { self.len() }
+
+	#[inline]
+	fn eq_at(&self, start: usize, bytes: &[u8], eq: impl Fn(&[u8], &[u8]) -> bool) -> bool {
+		eq(bytes, &self[start..start + bytes.len()])
+	}
+}
+
+impl Needle for [&[u8]] {
+	fn byte_len(&self) -> usize {
+		self.iter().map(|fragment| fragment.len()).sum()
+	}
+
+	fn eq_at(&self, mut start: usize, mut bytes: &[u8], eq: impl Fn(&[u8], &[u8]) -> bool) -> bool {
+		for &fragment in self {
+			if bytes.is_empty() { break }
+			if start >= fragment.len() {
+				start -= fragment.len();
+				continue
+			}
+
+			let fragment = &fragment[start..];
+			start = 0;
+			let len = min(fragment.len(), bytes.len());
+			let (head, tail) = bytes.split_at(len);
+			if !eq(head, &fragment[..len]) {
+				return false
+			}
+			bytes = tail;
+		}
+
+		bytes.is_empty()
+	}
+}
+
+/// Steps a slice matcher through `haystack`, comparing pattern bytes with `eq`.
+fn next_slice_step(
+	pattern: &(impl Needle + ?Sized),
+	partial: &mut PartialMatch,
+	haystack: &[u8],
+	offset: usize,
+	eq: impl Fn(&[u8], &[u8]) -> bool + Copy
+) -> Option<MatchStep> {
+	if haystack.is_empty() {
+		return None
+	}
+
+	partial.reset_invalid(offset);
+
+	let pattern_len = pattern.byte_len();
+	let step = if partial.is_empty() {
+		if let Some((start, count)) = find_partial(haystack, pattern, pattern_len, eq) {
+			if count == pattern_len {
+				MatchStep::complete(start + offset, count, start + pattern_len)
+			} else {
+				partial.start(start + offset, count);
+				MatchStep::partial(start + offset, count)
+			}
+		} else {
+			MatchStep::reject(haystack.len())
+		}
+	} else if let Some(count) = extend_partial(
+		haystack,
+		pattern,
+		partial.count,
+		pattern_len,
+		eq
+	) {
+		let partial_count = partial.extend_by(count);
+		assert_le!(partial_count, pattern_len);
+		if partial_count == pattern_len {
+			let consumed = count;
+			let (start, count) = partial.reset();
+			MatchStep::complete(start, count, consumed)
+		} else {
+			MatchStep::partial(partial.start, count)
+		}
+	} else {
+		MatchStep::reject(haystack.len())
+	};
+
+	Some(step)
+}
+
+fn find_partial(
+	haystack: &[u8],
+	needle: &(impl Needle + ?Sized),
+	needle_len: usize,
+	eq: impl Fn(&[u8], &[u8]) -> bool + Copy
+) -> Option<(usize, usize)> {
+	if needle_len == 1 {
+		return haystack.iter().position(|b| needle.eq_at(0, slice::from_ref(b), eq)).map(|i| (i, 1))
 	}
 
-	let last_start = if haystack.len() >= needle.len() {
-		haystack.len() - needle.len() + 1
+	let last_start = if haystack.len() >= needle_len {
+		haystack.len() - needle_len + 1
 	} else {
 		0
 	};
 	let windows = ShrinkingWindows {
 		last: &haystack[last_start..],
-		windows: haystack.windows(needle.len()),
+		windows: haystack.windows(needle_len),
 	};
 	windows.enumerate().find_map(|(i, window)|
-		(window == &needle[..window.len()]).then(||
+		needle.eq_at(0, window, eq).then_some(
 			(i, window.len())
 		)
 	)
 }
 
-fn extend_partial(haystack: &[u8], needle: &[u8]) -> Option<usize> {
-	let len = min(haystack.len(), needle.len());
-	(haystack[..len] == needle[..len]).then_some(len)
+/// Extends a partial match of `count` bytes into `haystack`, returning the number
+/// of bytes matched.
+fn extend_partial(
+	haystack: &[u8],
+	needle: &(impl Needle + ?Sized),
+	count: usize,
+	needle_len: usize,
+	eq: impl Fn(&[u8], &[u8]) -> bool
+) -> Option<usize> {
+	let len = min(haystack.len(), needle_len - count);
+	needle.eq_at(count, &haystack[..len], eq).then_some(len)
 }
 
 fn find_byte(haystack: &[u8], offset: usize, predicate: impl FnMut(&u8) -> bool) -> Option<MatchStep> {
@@ -1,8 +1,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::borrow::Cow;
+use std::iter;
 use std::ops::Range;
-use crate::pattern::{Matcher, MatchStep};
+use crate::pattern::{Alignment, Matcher, MatchStep};
 use crate::pattern::internal::AlignedUtf8Iter;
 
 // Todo: this code is very trait-constraint heavy and ugly, refactor.
@@ -31,7 +32,7 @@ impl<M: Matcher> Iterator for FragmentSteps<'_, M, [u8]> {
 
 	fn next(&mut self) -> Option<Self::Item> {
 		let Self { matcher, current, matched, offset } = self;
-		let step = matcher.next(&current[*matched..], *offset)?;
+		let step = matcher.next(&current[*matched..], *offset + *matched)?;
 		let consumed = step.consumed_bytes(..current.len() - *matched);
 		*matched += consumed;
 		Some(step)
@@ -43,7 +44,7 @@ impl<M: Matcher> Iterator for FragmentSteps<'_, M, str> {
 
 	fn next(&mut self) -> Option<Self::Item> {
 		let Self { matcher, current, matched, offset } = self;
-		let step = matcher.next_in_str(&current[*matched..], *offset)?;
+		let step = matcher.next_in_str(&current[*matched..], *offset + *matched)?;
 		let consumed = step.consumed_bytes(..current.len() - *matched);
 		*matched += consumed;
 		Some(step)
@@ -54,13 +55,18 @@ impl<'a, I: Iterator, M: Matcher, F: ToOwned + ?Sized> StepsInner<'a, I, M, F>
 where FragmentSteps<'a, M, F>: Iterator<Item = MatchStep>,
 	  Cow<'a, F>: From<I::Item> {
 	fn next_spec(&mut self) -> Option<MatchStep> where F: Slice {
+		self.next_in_fragments().or_else(|| self.steps.matcher.end())
+	}
+
+	/// Returns the next step, or `None` once the fragments are exhausted. Unlike
+	/// [`next_spec`](Self::next_spec), the matcher isn't ended, keeping a partial
+	/// match at the end of the fragments.
+	fn next_in_fragments(&mut self) -> Option<MatchStep> where F: Slice {
 		let Self { fragments, steps } = self;
 		loop {
 			let len = steps.current.len_spec();
 			if steps.matched >= len {
-				let Some(fragment) = fragments.next() else {
-					return steps.matcher.end()
-				};
+				let fragment = fragments.next()?;
 				steps.current = fragment.into();
 				steps.offset += len;
 				steps.matched = 0;
@@ -124,6 +130,31 @@ impl<'a, I: Iterator, M: Matcher, F: ToOwned<Owned: Default> + ?Sized> StepsInne
 	}
 }
 
+/// Finds the first complete match in a `haystack` iterator starting `offset` bytes
+/// into the searched data. The matcher isn't ended, so a partial match at the end
+/// of `haystack` is kept for the search to be resumed with the following bytes.
+/// For char-aligned matchers, `haystack` must not end within a character.
+pub(crate) fn resume_find<'a, M: Matcher>(
+	matcher: M,
+	haystack: impl IntoIterator<Item = &'a [u8]>,
+	offset: usize
+) -> Option<Range<usize>> {
+	fn find<'a, I: Iterator, M: Matcher, F: Slice + ?Sized>(
+		mut steps: StepsInner<'a, I, M, F>,
+		offset: usize
+	) -> Option<Range<usize>>
+	where FragmentSteps<'a, M, F>: Iterator<Item = MatchStep>,
+		  Cow<'a, F>: From<I::Item> {
+		steps.steps.offset = offset;
+		iter::from_fn(|| steps.next_in_fragments()).find_map(MatchStep::into_range)
+	}
+
+	match matcher.alignment() {
+		Alignment::Char => find(StepsInner::<_, _, str>::new(AlignedUtf8Iter::from(haystack), matcher), offset),
+		Alignment::Byte => find(StepsInner::<_, _, [u8]>::new(haystack.into_iter(), matcher), offset)
+	}
+}
+
 pub struct CompleteMatches<'a, I: Iterator, M, F: ToOwned + ?Sized>(StepsInner<'a, I, M, F>);
 
 pub enum Matches<'a, I: Iterator<Item = &'a [u8]>, M> {
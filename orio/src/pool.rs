@@ -2,15 +2,21 @@
 
 mod hack;
 
-use std::cell::{BorrowMutError, RefCell, RefMut};
+use std::cell::BorrowMutError;
+#[cfg(not(feature = "shared-pool"))]
+use std::cell::{RefCell, RefMut};
 use std::default::Default;
 use std::iter::Map;
 use std::mem::MaybeUninit;
 use std::ops::{DerefMut, Range};
+#[cfg(not(feature = "shared-pool"))]
 use std::rc::Rc;
 use std::result;
+#[cfg(feature = "shared-pool")]
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 use once_cell::sync::Lazy;
 use super::segment::{alloc_block, Block, Seg, SIZE};
+use super::util::trace;
 
 #[derive(Copy, Clone, Debug, thiserror::Error)]
 #[error("failed to borrow the pool")]
@@ -22,6 +28,11 @@ impl From<BorrowMutError> for PoolError {
 	fn from(_: BorrowMutError) -> Self { Self }
 }
 
+#[cfg(feature = "shared-pool")]
+impl<T> From<PoisonError<T>> for PoolError {
+	fn from(_: PoisonError<T>) -> Self { Self }
+}
+
 pub trait Pool<const N: usize = SIZE>: Clone {
 	type Pool: MutPool<N> + ?Sized;
 	type Ref<'p>: DerefMut<Target = Self::Pool> where Self: 'p;
@@ -51,6 +62,12 @@ pub trait Pool<const N: usize = SIZE>: Clone {
 		Ok(self.try_borrow()?.claim_size_spec(target, min_size))
 	}
 
+	/// Claims a single "jumbo" segment with capacity for at least `size` bytes,
+	/// which may be larger than the segment size.
+	fn claim_jumbo<'d>(&self, size: usize) -> Result<Seg<'d, N>> {
+		Ok(self.try_borrow()?.claim_jumbo(size))
+	}
+
 	/// Collects a single segment back into the pool.
 	fn collect_one(&self, segment: Seg<N>) -> Result {
 		if segment.is_shared() { return Ok(()) }
@@ -71,6 +88,23 @@ pub trait Pool<const N: usize = SIZE>: Clone {
 	fn shed(&self) -> Result {
 		Ok(self.try_borrow()?.shed())
 	}
+
+	/// Allocates segments until at least `count` segments are pooled, so they can
+	/// be claimed later without allocating. Useful to allocate an expected working
+	/// set at startup.
+	fn preallocate(&self, count: usize) -> Result {
+		Ok(self.try_borrow()?.claim_reserve(count))
+	}
+
+	/// Allocates segments until at least `bytes` bytes worth of segments are pooled.
+	fn warm(&self, bytes: usize) -> Result {
+		self.preallocate(bytes.div_ceil(N))
+	}
+
+	/// Returns the number of segments currently in the pool.
+	fn pooled_count(&self) -> Result<usize> {
+		Ok(self.try_borrow()?.pooled_count())
+	}
 }
 
 pub trait PoolExt<const N: usize>: Pool<N> {
@@ -111,6 +145,13 @@ pub trait MutPool<const N: usize = SIZE> {
 		self.claim_count(target, min_size.div_ceil(N))
 	}
 
+	/// Claims a single "jumbo" segment with capacity for at least `size` bytes,
+	/// which may be larger than the segment size. The default implementation
+	/// allocates a boxed segment, which isn't collected back into the pool.
+	fn claim_jumbo<'d>(&mut self, size: usize) -> Seg<'d, N> {
+		Vec::with_capacity(size).into()
+	}
+
 	/// Reserves space to collect at least `count` segments into the pool.
 	fn collect_reserve(&mut self, count: usize);
 
@@ -124,65 +165,131 @@ pub trait MutPool<const N: usize = SIZE> {
 	/// Clears segments from the pool to free space. The actual segment count to be
 	/// cleared is left up to implementation.
 	fn shed(&mut self);
+
+	/// Returns the number of segments in the pool. The default implementation
+	/// returns zero, for pools which don't keep segments or can't count them.
+	fn pooled_count(&self) -> usize { 0 }
 }
 
 #[derive(Default)]
-pub struct DefaultPool(Vec<Box<[MaybeUninit<u8>; SIZE]>>);
+pub struct DefaultPool {
+	blocks: Vec<Box<[MaybeUninit<u8>; SIZE]>>,
+	#[cfg(feature = "zeroize")]
+	zero_on_collect: bool,
+}
 
+/// A shared reference to a [`DefaultPool`]. With the `shared-pool` feature, the
+/// pool is shared between threads behind a [`Mutex`]; otherwise, it's local to a
+/// thread behind a [`RefCell`].
+///
+/// [`Mutex`]: std::sync::Mutex
+/// [`RefCell`]: std::cell::RefCell
+#[cfg(not(feature = "shared-pool"))]
 #[derive(Clone)]
 pub struct DefaultPoolContainer(Rc<RefCell<DefaultPool>>);
 
+/// A shared reference to a [`DefaultPool`]. With the `shared-pool` feature, the
+/// pool is shared between threads behind a [`Mutex`]; otherwise, it's local to a
+/// thread behind a [`RefCell`].
+///
+/// [`Mutex`]: std::sync::Mutex
+/// [`RefCell`]: std::cell::RefCell
+#[cfg(feature = "shared-pool")]
+#[derive(Clone)]
+pub struct DefaultPoolContainer(Arc<Mutex<DefaultPool>>);
+
 impl Default for DefaultPoolContainer {
+	#[cfg(not(feature = "shared-pool"))]
 	fn default() -> Self {
 		Self(Rc::new(DefaultPool::default().into()))
 	}
+
+	#[cfg(feature = "shared-pool")]
+	fn default() -> Self {
+		Self(Arc::new(DefaultPool::default().into()))
+	}
 }
 
 impl Pool<SIZE> for DefaultPoolContainer {
 	type Pool = DefaultPool;
+	#[cfg(not(feature = "shared-pool"))]
 	type Ref<'p> = RefMut<'p, DefaultPool>;
+	#[cfg(feature = "shared-pool")]
+	type Ref<'p> = MutexGuard<'p, DefaultPool>;
 	fn get() -> Self { pool() }
 
+	#[cfg(not(feature = "shared-pool"))]
 	fn try_borrow(&self) -> Result<Self::Ref<'_>> {
 		Ok(self.0.try_borrow_mut()?)
 	}
+
+	#[cfg(feature = "shared-pool")]
+	fn try_borrow(&self) -> Result<Self::Ref<'_>> {
+		Ok(self.0.lock()?)
+	}
 }
 
 /// Clones a shared reference to the default segment pool.
 #[inline]
 pub fn pool() -> DefaultPoolContainer { POOL.clone() }
 
-#[thread_local]
+#[cfg_attr(not(feature = "shared-pool"), thread_local)]
 static POOL: Lazy<DefaultPoolContainer> = Lazy::new(DefaultPoolContainer::default);
 
 impl DefaultPool {
 	fn allocate(count: usize) -> Map<Range<usize>, fn(usize) -> Block> {
 		(0..count).map(|_| alloc_block())
 	}
+
+	/// Returns `true` if segments are zeroed when collected.
+	#[cfg(feature = "zeroize")]
+	pub fn zero_on_collect(&self) -> bool { self.zero_on_collect }
+
+	/// Sets whether segments are zeroed when collected, such that data written to
+	/// them, possibly containing secrets, can't be read after they're claimed by
+	/// another buffer. Zeroing uses volatile writes which can't be optimized away.
+	#[cfg(feature = "zeroize")]
+	pub fn set_zero_on_collect(&mut self, value: bool) {
+		self.zero_on_collect = value;
+	}
+
+	/// Takes the memory block from a segment, zeroing it if needed.
+	fn take_block(&self, segment: Seg) -> Option<Block> {
+		#[cfg_attr(not(feature = "zeroize"), allow(unused_mut))]
+		let mut block = segment.into_block()?;
+		#[cfg(feature = "zeroize")]
+		if self.zero_on_collect {
+			zeroize::Zeroize::zeroize(&mut block[..]);
+		}
+		Some(block)
+	}
 }
 
 impl MutPool for DefaultPool {
 	fn claim_reserve(&mut self, count: usize) {
-		let Self(vec) = self;
+		let Self { blocks: vec, .. } = self;
 		let existing_count = count.min(vec.len());
 		let allocate_count = count - existing_count;
+		trace::event!("allocating segments", segments = allocate_count, bytes = allocate_count * SIZE);
 		vec.extend(Self::allocate(allocate_count));
 	}
 
 	fn claim_one<'d>(&mut self) -> Seg<'d> {
-		self.0.pop().unwrap_or_else(alloc_block).into()
+		trace::event!("claiming segments", segments = 1, allocated = self.blocks.is_empty() as usize);
+		self.blocks.pop().unwrap_or_else(alloc_block).into()
 	}
 
 	fn claim_count<'d>(&mut self, target: &mut impl Extend<Seg<'d>>, count: usize) where Self: Sized {
 		if count == 1 {
 			target.extend_one(self.claim_one());
 		} else {
-			let Self(vec) = self;
-			let existing_count = count.min(vec.len());
+			let Self { blocks, .. } = self;
+			let existing_count = count.min(blocks.len());
 			let allocate_count = count - existing_count;
-			self.0.extend(Self::allocate(allocate_count));
+			trace::event!("claiming segments", segments = count, allocated = allocate_count);
+			blocks.extend(Self::allocate(allocate_count));
 			target.extend(
-				self.0
+				blocks
 					.drain(..count)
 					.map(Into::into)
 			);
@@ -190,18 +297,53 @@ impl MutPool for DefaultPool {
 	}
 
 	fn collect_reserve(&mut self, count: usize) {
-		self.0.reserve(count)
+		self.blocks.reserve(count)
 	}
 
 	fn collect_one(&mut self, segment: Seg) {
-		if let Some(block) = segment.into_block() {
-			self.0.push(block)
+		if let Some(block) = self.take_block(segment) {
+			trace::event!("collecting segments", segments = 1);
+			self.blocks.push(block)
 		}
 	}
 
 	fn collect<'d>(&mut self, segments: impl IntoIterator<Item = Seg<'d>>) {
-		self.0.extend(segments.into_iter().filter_map(Seg::into_block))
+		let segments = segments.into_iter();
+		let pooled = self.blocks.len();
+		self.blocks.reserve(segments.size_hint().0);
+		for segment in segments {
+			if let Some(block) = self.take_block(segment) {
+				self.blocks.push(block)
+			}
+		}
+		trace::event!("collecting segments", segments = self.blocks.len() - pooled);
 	}
 
-	fn shed(&mut self) { self.0.clear() }
+	fn shed(&mut self) { self.blocks.clear() }
+
+	fn pooled_count(&self) -> usize { self.blocks.len() }
+}
+
+#[cfg(test)]
+mod test {
+	use pretty_assertions::assert_eq;
+	use crate::SIZE;
+	use super::{DefaultPoolContainer, Pool};
+
+	#[test]
+	fn preallocate() {
+		let pool = DefaultPoolContainer::default();
+		assert_eq!(pool.pooled_count().unwrap(), 0);
+		pool.preallocate(2).unwrap();
+		assert_eq!(pool.pooled_count().unwrap(), 2);
+		pool.warm(SIZE * 3 + 1).unwrap();
+		assert_eq!(pool.pooled_count().unwrap(), 4);
+		pool.preallocate(1).unwrap();
+		assert_eq!(pool.pooled_count().unwrap(), 4, "pooled segments should count towards preallocation");
+
+		let segment = pool.claim_one().unwrap();
+		assert_eq!(pool.pooled_count().unwrap(), 3);
+		pool.collect_one(segment).unwrap();
+		assert_eq!(pool.pooled_count().unwrap(), 4);
+	}
 }
@@ -68,6 +68,11 @@ impl<const N: usize, P: MutPool<N> + ?Sized> MutPool<N> for &mut P {
 		P::claim_size_spec(self, target, min_size);
 	}
 
+	#[inline]
+	fn claim_jumbo<'d>(&mut self, size: usize) -> Seg<'d, N> {
+		P::claim_jumbo(self, size)
+	}
+
 	#[inline]
 	fn collect_reserve(&mut self, count: usize) {
 		P::collect_reserve(self, count);
@@ -87,4 +92,9 @@ impl<const N: usize, P: MutPool<N> + ?Sized> MutPool<N> for &mut P {
 	fn shed(&mut self) {
 		P::shed(self);
 	}
+
+	#[inline]
+	fn pooled_count(&self) -> usize {
+		P::pooled_count(self)
+	}
 }
@@ -20,6 +20,13 @@ use crate::pool::Pool;
 
 pub const SIZE: usize = 8192;
 
+/// The pointer sharing memory between segments. With the `shared-pool` feature,
+/// its reference count is atomic, so segments can be sent between threads.
+#[cfg(feature = "shared-pool")]
+pub(crate) type Shared<T> = std::sync::Arc<T>;
+#[cfg(not(feature = "shared-pool"))]
+pub(crate) type Shared<T> = std::rc::Rc<T>;
+
 /// A sharable, ring buffer-like memory segment containing borrowed or owned data:
 /// - An [`N`]-sized array (called a *block*)
 /// - A variable-size vector from a boxed slice, or
@@ -96,6 +103,10 @@ impl<'d, const N: usize> Seg<'d, N> {
 	pub fn is_exclusive(&self) -> bool {
 		!self.is_shared()
 	}
+	/// Returns `true` if the segment borrows a slice rather than owning its memory.
+	pub fn is_borrowed(&self) -> bool {
+		matches!(self.0, Buf::Slice(_))
+	}
 
 	/// Clears data from the segment.
 	pub fn clear(&mut self) {
@@ -224,6 +235,16 @@ impl<'d, const N: usize> Seg<'d, N> {
 		}
 	}
 
+	/// Writes the end of `buf` to the front of the segment, returning the number of
+	/// bytes written, or `None` if the segment contains shared data or isn't a
+	/// block.
+	pub fn prepend(&mut self, buf: &[u8]) -> Option<usize> {
+		match &mut self.0 {
+			Buf::Block(block) => block.prepend_n(buf),
+			_ => None
+		}
+	}
+
 	/// Forks shared memory, then writes the contents of `buf` into the segment,
 	/// returning the number of bytes written if successful. If the segment was too
 	/// large to cleanly fit into a block, the remaining shared data is returned in
@@ -342,20 +363,37 @@ impl<'d, const N: usize> Seg<'d, N> {
 
 impl<'a, const N: usize> Seg<'a, N> {
 	pub(crate) unsafe fn set_len(&mut self, count: usize) {
-		let Buf::Block(block) = &mut self.0 else { return };
-		block.set_len(count)
+		match &mut self.0 {
+			Buf::Block(block) => block.set_len(count),
+			Buf::Boxed(boxed) => boxed.set_len(count),
+			Buf::Slice(_) => { }
+		}
 	}
 
 	pub(crate) unsafe fn inc_len(&mut self, count: usize) {
-		let Buf::Block(block) = &mut self.0 else { return };
-		block.set_len(block.len() + count);
+		self.set_len(self.len() + count);
 	}
 
 	pub(crate) fn spare_capacity_mut(&mut self) -> (&mut [MaybeUninit<u8>], &mut [MaybeUninit<u8>]) {
-		let Buf::Block(block) = &mut self.0 else {
-			return (&mut [], &mut [])
-		};
-		block.spare_capacity_mut()
+		match &mut self.0 {
+			Buf::Block(block) => block.spare_capacity_mut(),
+			Buf::Boxed(boxed) => (boxed.spare_capacity_mut(), &mut []),
+			Buf::Slice(_) => (&mut [], &mut [])
+		}
+	}
+
+	/// Zeroes the segment's data and spare capacity, if the segment is exclusive.
+	/// Shared and borrowed memory is owned elsewhere, and is left untouched.
+	#[cfg(feature = "zeroize")]
+	pub(crate) fn zeroize(&mut self) {
+		use zeroize::Zeroize;
+
+		let Some((a, b)) = self.as_mut_slices() else { return };
+		a.zeroize();
+		b.zeroize();
+		let (a, b) = self.spare_capacity_mut();
+		a.zeroize();
+		b.zeroize();
 	}
 }
 
@@ -367,7 +405,7 @@ impl<'d, const N: usize> Index<usize> for Seg<'d, N> {
 		if index < a.len() {
 			&a[index]
 		} else {
-			&b[index]
+			&b[index - a.len()]
 		}
 	}
 }
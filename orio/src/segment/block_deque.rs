@@ -9,8 +9,8 @@ use std::cmp::min;
 use std::iter::{FusedIterator};
 use std::mem::MaybeUninit;
 use std::ops::{IndexMut, Range, RangeBounds};
-use std::rc::Rc;
 use all_asserts::assert_le;
+use super::Shared;
 
 pub type Block<const N: usize = { super::SIZE }> = Box<[MaybeUninit<u8>; N]>;
 
@@ -23,7 +23,7 @@ pub type Block<const N: usize = { super::SIZE }> = Box<[MaybeUninit<u8>; N]>;
 /// [`VecDeque`]: std::collections::VecDeque
 #[derive(Clone)]
 pub struct BlockDeque<const N: usize> {
-	buf: Rc<Block<N>>,
+	buf: Shared<Block<N>>,
 	head: usize,
 	len: usize
 }
@@ -70,7 +70,7 @@ impl<const N: usize> BlockDeque<N> {
 			MaybeUninit::slice_assume_init_mut(&mut b[..])
 		});
 		Self {
-			buf: Rc::new(b),
+			buf: Shared::new(b),
 			head: 0,
 			len
 		}
@@ -85,7 +85,7 @@ impl<const N: usize> BlockDeque<N> {
 	/// Returns `true` if the deque is full.
 	pub fn is_full(&self) -> bool { self.len == N }
 	/// Returns `true` if the deque contains shared data.
-	pub fn is_shared(&self) -> bool { Rc::strong_count(&self.buf) != 1 }
+	pub fn is_shared(&self) -> bool { Shared::strong_count(&self.buf) != 1 }
 	/// Returns `true` if the segment is contiguous.
 	pub fn is_contiguous(&self) -> bool {
 		// Unlike with VecDeque, the capacity is checked by the compiler, so this
@@ -261,6 +261,21 @@ impl<const N: usize> BlockDeque<N> {
 		Some(count)
 	}
 
+	/// Prepends the end of a slice `values` to the front of the deque, returning
+	/// the number of bytes written, or `None` if the deque is not writable.
+	pub fn prepend_n(&mut self, values: &[u8]) -> Option<usize> {
+		let count = min(self.limit(), values.len());
+		let values = &values[values.len() - count..];
+		let head = self.wrap_sub(count);
+		let buf = self.buf()?;
+		let (a, b) = values.split_at(min(count, N - head));
+		buf[head..head + a.len()].copy_from_slice(unsafe { mem::transmute(a) });
+		buf[..b.len()].copy_from_slice(unsafe { mem::transmute(b) });
+		self.head = head;
+		self.len += count;
+		Some(count)
+	}
+
 	/// Extends the deque with a slice `values`, returning the remaining slice, or
 	/// the whole slice if the deque is shared.
 	pub fn extend<'a>(&mut self, values: &'a [u8]) -> &'a [u8] {
@@ -382,7 +397,7 @@ impl<const N: usize> BlockDeque<N> {
 	/// elements of this array are possibly uninitialized; this method is provided
 	/// for pools to collect this memory and pass it back to this struct, where
 	/// initialization is properly handled.
-	pub fn into_inner(self) -> Option<Box<[MaybeUninit<u8>; N]>> { Rc::into_inner(self.buf) }
+	pub fn into_inner(self) -> Option<Box<[MaybeUninit<u8>; N]>> { Shared::into_inner(self.buf) }
 }
 
 impl<const N: usize> BlockDeque<N> {
@@ -397,7 +412,7 @@ impl<const N: usize> BlockDeque<N> {
 	}
 
 	fn buf(&mut self) -> Option<&mut [MaybeUninit<u8>; N]> {
-		Rc::get_mut(&mut self.buf).map(Box::as_mut)
+		Shared::get_mut(&mut self.buf).map(Box::as_mut)
 	}
 
 	fn wrap(&self, idx: usize) -> usize {
@@ -452,7 +467,7 @@ impl<const N: usize> Default for BlockDeque<N> {
 
 impl<const N: usize> From<Box<[MaybeUninit<u8>; N]>> for BlockDeque<N> {
 	fn from(buf: Box<[MaybeUninit<u8>; N]>) -> Self {
-		Self { buf: Rc::new(buf.into()), head: 0, len: 0 }
+		Self { buf: Shared::new(buf.into()), head: 0, len: 0 }
 	}
 }
 
@@ -536,7 +551,7 @@ impl<const N: usize, const O: usize> PartialEq<BlockDeque<O>> for BlockDeque<N>
 mod test {
 	use std::fmt;
 	use std::fmt::{Debug, Formatter};
-	use std::rc::Rc;
+	use crate::segment::Shared;
 	use quickcheck::{Arbitrary, Gen};
 	use quickcheck_macros::quickcheck;
 	use super::BlockDeque;
@@ -571,7 +586,7 @@ mod test {
 	impl<const MIN: usize, const MAX: usize> Clone for TestDeque<MIN, MAX> {
 		fn clone(&self) -> Self {
 			let mut deque = self.deque.clone();
-			Rc::make_mut(&mut deque.buf);
+			Shared::make_mut(&mut deque.buf);
 			Self { deque, ..*self }
 		}
 	}
@@ -676,11 +691,20 @@ mod test {
 		assert_eq!(deque_a.push_back(0), Ok(()), "pushing to previously shared deque should succeed");
 	}
 
+	#[quickcheck]
+	fn prepend(TestDeque { mut deque, len }: TestDeque<0, 12>) {
+		let prefix = b"0123456789AB";
+		assert_eq!(deque.prepend_n(prefix), Some(12 - len));
+		let mut expected = prefix[len..].to_vec();
+		expected.extend_from_slice(&SLICE[..len]);
+		assert_eq!(deque, &expected[..]);
+	}
+
 	#[quickcheck]
 	fn extend(TestDeque { mut deque, len }: TestDeque<0, 12>) {
 		let remaining = deque.extend(&SLICE[len..]);
 		assert_eq!(deque, SLICE);
-		assert_eq!(remaining, &[]);
+		assert_eq!(remaining, &[] as &[u8]);
 	}
 
 	#[quickcheck]
@@ -3,10 +3,10 @@
 use std::borrow::Cow;
 use std::cmp::min;
 use std::collections::{VecDeque, vec_deque::Iter as DequeIter};
-use std::{fmt, slice};
+use std::{fmt, mem, slice};
+use std::mem::MaybeUninit;
 use std::ops::RangeBounds;
-use std::rc::Rc;
-use super::{BlockDeque, Block};
+use super::{BlockDeque, Block, Shared};
 
 /// A segment buffer.
 #[derive(Clone, Debug, Eq)]
@@ -23,14 +23,14 @@ pub enum Buf<'d, const N: usize> {
 
 #[derive(Clone, Eq)]
 pub struct BoxedBuf {
-	pub buf: Rc<VecDeque<u8>>,
+	pub buf: Shared<VecDeque<u8>>,
 	pub off: usize,
 	pub len: usize
 }
 
 impl BoxedBuf {
 	pub fn is_shared(&self) -> bool {
-		Rc::strong_count(&self.buf) != 1
+		Shared::strong_count(&self.buf) != 1
 	}
 
 	pub fn as_slices(&self) -> (&[u8], &[u8]) {
@@ -86,7 +86,7 @@ impl BoxedBuf {
 	pub fn impose(&mut self) {
 		let Self { buf, off, len } = self;
 		if *off > 0 || *len < buf.len() {
-			let Some(buf) = Rc::get_mut(buf) else { return };
+			let Some(buf) = Shared::get_mut(buf) else { return };
 			buf.drain(..self.off);
 			self.off = 0;
 			buf.truncate(self.len);
@@ -95,7 +95,39 @@ impl BoxedBuf {
 	}
 
 	pub fn buf(&mut self) -> Option<&mut VecDeque<u8>> {
-		Rc::get_mut(&mut self.buf)
+		Shared::get_mut(&mut self.buf)
+	}
+
+	/// Returns the spare capacity after the contents, or an empty slice if shared.
+	/// The deque is made contiguous at the start of its memory, so bytes written
+	/// here can be added with [`set_len`](Self::set_len).
+	pub fn spare_capacity_mut(&mut self) -> &mut [MaybeUninit<u8>] {
+		self.impose();
+		let Some(buf) = self.buf() else { return &mut [] };
+		let mut vec = Vec::from(mem::take(buf));
+		let spare = vec.spare_capacity_mut();
+		let (ptr, len) = (spare.as_mut_ptr(), spare.len());
+		*buf = vec.into();
+		unsafe {
+			// Safety: converting a Vec into a VecDeque never reallocates, so the
+			// spare capacity is still owned by the deque, which is borrowed mutably
+			// for the lifetime of the slice.
+			slice::from_raw_parts_mut(ptr, len)
+		}
+	}
+
+	/// Sets the length of the contents, as with [`Vec::set_len`].
+	///
+	/// # Safety
+	///
+	/// `len` must not exceed the capacity, and bytes up to `len` must be initialized.
+	pub unsafe fn set_len(&mut self, len: usize) {
+		self.impose();
+		let Some(buf) = self.buf() else { return };
+		let mut vec = Vec::from(mem::take(buf));
+		vec.set_len(len);
+		*buf = vec.into();
+		self.len = len;
 	}
 
 	pub fn iter(&self) -> DequeIter<u8> {
@@ -180,15 +212,15 @@ impl<const N: usize> From<Block<N>> for Buf<'_, N> {
 	}
 }
 
-impl<const N: usize> From<Rc<VecDeque<u8>>> for Buf<'_, N> {
-	fn from(buf: Rc<VecDeque<u8>>) -> Self {
+impl<const N: usize> From<Shared<VecDeque<u8>>> for Buf<'_, N> {
+	fn from(buf: Shared<VecDeque<u8>>) -> Self {
 		Self::Boxed(BoxedBuf { buf, off: 0, len: 0 })
 	}
 }
 
 impl<const N: usize> From<VecDeque<u8>> for Buf<'_, N> {
 	fn from(value: VecDeque<u8>) -> Self {
-		Rc::new(value).into()
+		Shared::new(value).into()
 	}
 }
 
@@ -4,7 +4,7 @@ use std::cmp::{min, Ordering};
 use std::collections::{vec_deque, VecDeque};
 use std::iter::Skip;
 use std::marker::PhantomData;
-use std::ops::{Deref, DerefMut, Index, IndexMut, RangeBounds};
+use std::ops::{Deref, DerefMut, Index, IndexMut, Range, RangeBounds};
 use std::ptr::NonNull;
 use std::slice;
 use all_asserts::debug_assert_le;
@@ -19,6 +19,13 @@ pub(crate) struct RBuf<T> {
 	len: usize,
 	/// The number of readable bytes in the buffer.
 	count: usize,
+	/// An estimate of the writable space between the front and back segments,
+	/// added to as segments are pushed behind partially filled segments. Segments
+	/// read or written in place aren't tracked, so this may overcount until it's
+	/// refreshed by [`is_fragmented`].
+	///
+	/// [`is_fragmented`]: Self::is_fragmented
+	fragmented: usize,
 }
 
 pub struct SlotMut<'a, 'b, const N: usize> {
@@ -43,11 +50,14 @@ impl<'d, const N: usize> From<Vec<Seg<'d, N>>> for RBuf<Seg<'d, N>> {
 		let len = buf.partition_point(Seg::is_not_empty);
 		let count = buf[..len].iter().map(Seg::len).sum();
 
-		Self {
+		let mut ring = Self {
 			buf: buf.into(),
 			len,
 			count,
-		}
+			fragmented: 0,
+		};
+		ring.fragmented = ring.fragmentation();
+		ring
 	}
 }
 
@@ -58,6 +68,7 @@ impl<T> RBuf<T> {
 			buf: VecDeque::new(),
 			len: 0,
 			count: 0,
+			fragmented: 0,
 		}
 	}
 }
@@ -191,23 +202,29 @@ impl<'a, const N: usize> RBuf<Seg<'a, N>> {
 		self.len += 1;
 		self.count += seg.len();
 		self.buf.push_front(seg);
+		// The previous front segment is now between the front and back.
+		if self.len > 2 {
+			self.fragmented += self.gap_limit(1..2);
+		}
 	}
 
-	/// Pushes `seg` to the back of the buffer.
+	/// Pushes `seg` to the back of the buffer, before any empty segments.
 	pub fn push_back(&mut self, seg: Seg<'a, N>) {
 		if seg.is_empty() {
 			self.push_empty(seg);
 			return
 		}
 
-		self.len += 1;
 		self.count += seg.len();
-		self.buf.push_back(seg);
+		self.buf.insert(self.len, seg);
+		self.len += 1;
+		self.grow_gaps(self.len - 1);
 	}
 
 	/// Pops a readable segment from the front of the buffer.
 	pub fn pop_front(&mut self) -> Option<Seg<'a, N>> {
 		if !self.is_empty() {
+			self.shrink_gaps(1);
 			let seg = self.buf.pop_front()?;
 			self.count -= seg.len();
 			self.len -= 1;
@@ -219,14 +236,24 @@ impl<'a, const N: usize> RBuf<Seg<'a, N>> {
 
 	/// Pops a writable segment from the back of the buffer.
 	pub fn pop_back(&mut self) -> Option<Seg<'a, N>> {
+		// Check the back written segment rather than the back slot, which may be an
+		// empty segment if the written segment is full.
 		let is_full_or_shared = || {
-			let back = self.back().unwrap();
+			let back = &self.buf[self.len - 1];
 			back.is_full() || back.is_shared()
 		};
 		if self.is_empty() || is_full_or_shared() {
 			return self.pop_empty()
 		}
 
+		// The segment before the back is now the back.
+		if self.len > 3 {
+			let gaps = self.gap_limit(self.len - 2..self.len - 1);
+			self.fragmented = self.fragmented.saturating_sub(gaps);
+		} else {
+			self.fragmented = 0;
+		}
+
 		let index = self.back_index().unwrap();
 		let seg = if self.has_empty() {
 			self.buf.swap_remove_back(index)?
@@ -254,6 +281,7 @@ impl<'a, const N: usize> RBuf<Seg<'a, N>> {
 
 	/// Drains up to `count` segments from the buffer.
 	pub fn drain(&mut self, count: usize) -> impl Iterator<Item = Seg<'a, N>> + '_ {
+		self.shrink_gaps(count);
 		// Drain all segments
 		if count >= self.capacity() {
 			self.len = 0;
@@ -276,6 +304,141 @@ impl<'a, const N: usize> RBuf<Seg<'a, N>> {
 		self.buf.drain(..min(count, self.capacity()))
 	}
 
+	/// Truncates the buffer to the first `count` bytes, returning the number of
+	/// segments emptied. Segments emptied by the truncation are kept as empty
+	/// segments behind any spare segments, unless they're shared.
+	pub fn truncate(&mut self, count: usize) -> usize {
+		if count >= self.count {
+			return 0
+		}
+
+		let readable = self.len;
+		let mut remaining = count;
+		let mut len = 0;
+		let mut index = 0;
+		let mut emptied = 0;
+		self.buf.retain_mut(|seg| {
+			let is_readable = index < readable;
+			index += 1;
+			if !is_readable {
+				true
+			} else if remaining > 0 {
+				remaining -= seg.truncate(remaining);
+				len += 1;
+				true
+			} else {
+				seg.clear();
+				let is_exclusive = seg.is_exclusive();
+				emptied += is_exclusive as usize;
+				is_exclusive
+			}
+		});
+		// Move the emptied segments behind the spare segments, so only they are
+		// released by draining empty segments from the back.
+		self.buf.make_contiguous()[len..].rotate_left(emptied);
+		self.len = len;
+		self.count = count;
+		self.fragmented = self.fragmentation();
+		emptied
+	}
+
+	/// Replaces the readable segment at `index` with `segments`, which must contain
+	/// the same data.
+	pub fn replace(&mut self, index: usize, segments: impl IntoIterator<Item = Seg<'a, N>>) {
+		let removed = self.buf.remove(index).expect("replaced segment should be in bounds");
+		let mut end = index;
+		for seg in segments {
+			self.buf.insert(end, seg);
+			end += 1;
+		}
+		debug_assert_eq!(
+			self.buf.range(index..end).map(Seg::len).sum::<usize>(),
+			removed.len(),
+			"replacement segments should contain the same data"
+		);
+		self.len = self.len + end - index - 1;
+		self.fragmented = self.fragmentation();
+	}
+
+	/// Returns the number of bytes of writable space in exclusive segments between
+	/// the front and back segments. These gaps can only be reclaimed by compacting.
+	pub fn fragmentation(&self) -> usize {
+		let end = self.len.saturating_sub(1);
+		self.gap_limit(min(1, end)..end)
+	}
+
+	/// Returns `true` if at least `threshold` bytes are fragmented. The tracked
+	/// estimate is checked first, and only counted exactly once it reaches the
+	/// threshold, so this is constant-time while the buffer isn't fragmented.
+	pub fn is_fragmented(&mut self, threshold: usize) -> bool {
+		if self.fragmented < threshold {
+			return false
+		}
+
+		self.fragmented = self.fragmentation();
+		self.fragmented >= threshold
+	}
+
+	/// Returns the writable space in exclusive segments in `range`.
+	fn gap_limit(&self, range: Range<usize>) -> usize {
+		self.buf
+			.range(range)
+			.filter(|seg| seg.is_exclusive())
+			.map(Seg::limit)
+			.sum()
+	}
+
+	/// Adds gaps left by segments moved between the front and back after the
+	/// length grew from `old_len`.
+	fn grow_gaps(&mut self, old_len: usize) {
+		let start = old_len.saturating_sub(1).max(1);
+		let end = self.len.saturating_sub(1);
+		if start < end {
+			self.fragmented += self.gap_limit(start..end);
+		}
+	}
+
+	/// Removes gaps in segments no longer between the front and back once `count`
+	/// readable segments are removed from the front.
+	fn shrink_gaps(&mut self, count: usize) {
+		let count = min(count, self.len);
+		if self.len - count < 3 {
+			self.fragmented = 0;
+			return
+		}
+
+		let gaps = self.gap_limit(1..count + 1);
+		self.fragmented = self.fragmented.saturating_sub(gaps);
+	}
+
+	/// Compacts the buffer, moving data from each segment into writable space in
+	/// the segment before it. Exclusive segments emptied by compaction are moved to
+	/// the back of the buffer and their number returned, shared segments emptied by
+	/// compaction are dropped.
+	pub fn compact(&mut self) -> usize {
+		let mut compacted = VecDeque::with_capacity(self.buf.len());
+		let mut emptied = Vec::new();
+		for mut seg in self.buf.drain(..self.len) {
+			if let Some(prev) = compacted.back_mut() {
+				let _ = Seg::write_from(prev, &mut seg);
+			}
+
+			if seg.is_not_empty() {
+				compacted.push_back(seg);
+			} else if seg.is_exclusive() {
+				emptied.push(seg);
+			}
+		}
+
+		let count = emptied.len();
+		self.len = compacted.len();
+		compacted.append(&mut self.buf);
+		compacted.extend(emptied);
+		self.buf = compacted;
+		self.fragmented = self.fragmentation();
+		count
+	}
+
 	/// Drains up to `count` empty segments from the buffer.
 	pub fn drain_empty(&mut self, count: usize) -> impl Iterator<Item = Seg<'a, N>> + '_ {
 		let mut range = self.len..self.capacity();
@@ -420,7 +583,7 @@ impl<'a, const N: usize> RBuf<Seg<'a, N>> {
 				break
 			}
 
-			let grow_len = seg.limit().min(count);
+			let grow_len = seg.limit().min(count - counted);
 			seg.set_len(seg.len() + grow_len);
 			counted += grow_len;
 			seg_count += 1;
@@ -445,11 +608,16 @@ impl<'a, const N: usize> RBuf<Seg<'a, N>> {
 				.unwrap_or_default()
 		);
 		self.len = len;
+		if len < 3 {
+			self.fragmented = 0;
+		}
 	}
 
 	/// Increments the tracked length after writing.
 	unsafe fn inc_len(&mut self, len: usize) {
+		let old_len = self.len;
 		self.set_len(self.len + len);
+		self.grow_gaps(old_len);
 	}
 
 	/// Decrements the tracked length after reading.
@@ -492,6 +660,9 @@ impl<T> RBuf<T> {
 	pub fn rotate_back(&mut self, count: usize) {
 		self.buf.rotate_left(count);
 		self.len -= count;
+		if self.len < 3 {
+			self.fragmented = 0;
+		}
 	}
 }
 
@@ -521,26 +692,31 @@ impl<'a, const N: usize> RBuf<Seg<'a, N>> {
 	}
 
 	fn push_many<T: IntoIterator<Item = Seg<'a, N>>>(&mut self, iter: T) {
-		let start = self.len;
+		let empty_count = self.capacity() - self.len;
 		let old_capacity = self.capacity();
 		// Temporarily rotate empty segments to the front before extending, in case
 		// iter contains written segments. The ensures new written segments stay in
 		// chronological order front-to-back tailed by empty segments.
-		self.buf.rotate_right(start);
+		self.buf.rotate_left(self.len);
 		self.buf.extend(iter);
 
 		// Count segments starting from the old capacity until the last written
 		// segment, which is the number of written segments added.
 		let new_len = self.buf
 						  .range(old_capacity..)
-						  .rposition(Seg::is_not_empty);
-		// Push the new length if any were written.
-		if let Some(new_len) = new_len {
-			self.len += new_len;
-		}
+						  .rposition(Seg::is_not_empty)
+						  .map_or(0, |i| i + 1);
+		// Push the new length and count if any were written.
+		let old_len = self.len;
+		self.len += new_len;
+		self.count += self.buf
+						  .range(old_capacity..old_capacity + new_len)
+						  .map(Seg::len)
+						  .sum::<usize>();
 
 		// Rotate the empty segments back.
-		self.rotate_back(start);
+		self.buf.rotate_left(empty_count);
+		self.grow_gaps(old_len);
 	}
 
 	fn segment_index(&self, byte_index: usize) -> (usize, usize) {
@@ -666,7 +842,7 @@ impl<'a: 'b, 'b, const N: usize> Iterator for SliceRangeIter<'a, 'b, N> {
 
 		let offset = if self.index == 0 { self.first_offset } else { 0 };
 		let seg = self.iter.next()?;
-		let range = offset..remaining.min(seg.len()) + offset;
+		let range = offset..remaining.min(seg.len() - offset) + offset;
 		self.cur_count += range.len();
 		self.index += 1;
 		let (a, b) = seg.as_slices_in_range(range);
@@ -737,3 +913,74 @@ impl<'a: 'b, 'b, const N: usize> DoubleEndedIterator for SliceIter<'a, 'b, N> {
 		}
 	}
 }
+
+#[cfg(test)]
+mod test {
+	use crate::SIZE;
+	use super::{RBuf, Seg};
+
+	fn full_seg() -> Seg<'static> {
+		let mut seg = Seg::default();
+		seg.write(&[1; SIZE]);
+		seg
+	}
+
+	#[test]
+	fn pop_back_after_full() {
+		let mut ring = RBuf::from(vec![full_seg(), Seg::default()]);
+		let seg = ring.pop_back().unwrap();
+		assert!(seg.is_empty(), "should pop the empty segment");
+		assert_eq!(ring.len(), 1, "full segment should stay readable");
+		assert_eq!(ring.count(), SIZE);
+		assert_eq!(ring[0], [1; SIZE]);
+	}
+
+	#[test]
+	fn extend_after_empty() {
+		let mut ring: RBuf<Seg> = RBuf::from(vec![Seg::from(&b"a"[..]), Seg::default(), Seg::default()]);
+		ring.extend([Seg::from(&b"b"[..]), Seg::default()]);
+		assert_eq!(ring.len(), 2);
+		assert_eq!(ring.count(), 2);
+		assert_eq!(ring.capacity(), 5);
+		assert_eq!(ring[0], b"a");
+		assert_eq!(ring[1], b"b");
+		assert!(ring.buf.range(2..).all(Seg::is_empty), "empty segments should be at the back");
+	}
+
+	#[test]
+	fn push_back_before_empty() {
+		let mut ring: RBuf<Seg> = RBuf::from(vec![Seg::from(&b"a"[..]), Seg::default()]);
+		ring.push_back(Seg::from(&b"b"[..]));
+		assert_eq!(ring.len(), 2);
+		assert_eq!(ring.count(), 2);
+		assert_eq!(ring[1], b"b", "written segment should be pushed before empty segments");
+		assert!(ring[2].is_empty());
+	}
+
+	#[test]
+	fn track_fragmentation() {
+		fn partial_seg() -> Seg<'static> {
+			let mut seg = Seg::default();
+			seg.write(&[1; 10]);
+			seg
+		}
+
+		let mut ring = RBuf::new();
+		for _ in 0..4 {
+			ring.push_back(partial_seg());
+		}
+		assert_eq!(ring.fragmented, (SIZE - 10) * 2);
+		assert_eq!(ring.fragmented, ring.fragmentation());
+		assert!(ring.is_fragmented(SIZE));
+
+		ring.pop_front();
+		assert_eq!(ring.fragmented, SIZE - 10);
+		ring.push_back(partial_seg());
+		assert_eq!(ring.fragmented, (SIZE - 10) * 2);
+		ring.pop_back();
+		assert_eq!(ring.fragmented, SIZE - 10);
+		ring.drain(1).for_each(drop);
+		assert_eq!(ring.fragmented, 0);
+		assert_eq!(ring.fragmented, ring.fragmentation());
+	}
+}
@@ -2,29 +2,86 @@
 
 mod seeking;
 mod void;
+mod generate;
 mod hashing;
 mod file;
 mod std_io;
+mod codec;
+mod process;
+mod timeout;
+mod retry;
+mod fmt_writer;
+mod pipe;
+mod duplex;
+mod throttle;
+mod record;
+mod fault;
+mod take;
+mod dynamic;
+mod counting;
+mod crypto;
+mod chunking;
+mod positioned;
+#[cfg(feature = "shared-pool")]
+mod prefetch;
+pub mod json;
+pub mod http1;
+pub mod ws;
+pub mod tar;
+pub mod zip;
+pub mod multipart;
+pub mod csv;
+pub mod proto;
+pub mod cbor;
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
 
 pub use seeking::*;
 pub use void::*;
+pub use generate::*;
 pub use hashing::*;
 pub use file::*;
 pub use std_io::*;
-
+pub use codec::*;
+pub use process::*;
+pub use timeout::*;
+pub use retry::*;
+pub use fmt_writer::*;
+pub use pipe::*;
+pub use duplex::*;
+pub use throttle::*;
+pub use record::*;
+pub use fault::*;
+pub use take::*;
+pub use dynamic::*;
+pub use counting::*;
+pub use crypto::*;
+pub use chunking::*;
+pub use positioned::*;
+#[cfg(feature = "shared-pool")]
+pub use prefetch::*;
+
+use std::{fmt, io};
+use std::io::{BorrowedBuf, BorrowedCursor};
+use std::ops::Range;
 use std::result;
 use num_traits::PrimInt;
 use crate::pool::{DefaultPoolContainer, Pool};
-use crate::{Buffer, BufferResult, Error, ErrorSource, ResultContext, SIZE, StreamContext, StreamError};
-pub use crate::buffered_wrappers::{BufferedSink, BufferedSource};
+use crate::{Buffer, BufferOptions, BufferResult, ByteString, Error, ErrorSource, ResultContext, SIZE, StreamContext, StreamError};
+pub use crate::buffered_wrappers::{BufferedSink, BufferedSource, ReadAhead};
 use crate::error::Context;
-use crate::pattern::Pattern;
+use crate::pattern::{Alignment, Matcher, MatchStep, Pattern};
 use crate::StreamContext::{Read, Write};
+use crate::util::partial_utf8::utf8_char_width;
 
 /// An "stream closed" error.
 #[derive(Copy, Clone, Debug, Default, thiserror::Error)]
 #[error("stream closed")]
 pub struct StreamClosed;
+/// An "operation timed out" error.
+#[derive(Copy, Clone, Debug, Default, thiserror::Error)]
+#[error("operation timed out")]
+pub struct TimedOut;
 /// An end-of-stream error.
 #[derive(Copy, Clone, Debug, Default, thiserror::Error)]
 #[error("premature end-of-stream{}", self.format_req())]
@@ -57,6 +114,29 @@ impl StreamError {
 	}
 }
 
+/// Creates an "invalid data" IO error for malformed stream data.
+fn invalid_data(message: &'static str) -> StreamError {
+	StreamError {
+		source: io::Error::new(io::ErrorKind::InvalidData, message).into(),
+		context: Read,
+	}
+}
+
+/// Requests at least one more byte than is available, returning `false` if the
+/// source ended.
+fn request_more<'d, const N: usize>(source: &mut impl BufSource<'d, N>) -> Result<bool> {
+	let count = source.available() + 1;
+	source.request(count)
+}
+
+/// Creates an "invalid input" IO error for data that can't be written.
+fn invalid_input(message: &'static str) -> StreamError {
+	StreamError {
+		source: io::Error::new(io::ErrorKind::InvalidInput, message).into(),
+		context: Write,
+	}
+}
+
 pub type Result<T = (), E = StreamError> = result::Result<T, E>;
 
 pub trait Stream<const N: usize> {
@@ -132,7 +212,8 @@ pub trait Source<'d, const N: usize = SIZE>: Stream<N> {
 	/// number of bytes read. This may not read the source to its end.
 	///
 	/// The default implementation fills free space in `sink`, then fills with `N`
-	/// bytes repeatedly until end-of-stream is reached or no bytes are read.
+	/// bytes repeatedly until end-of-stream is reached, no bytes are read, or the
+	/// sink's [yield threshold](crate::BufferOptions::yield_after) is reached.
 	///
 	/// # Errors
 	///
@@ -142,38 +223,53 @@ pub trait Source<'d, const N: usize = SIZE>: Stream<N> {
 		&mut self,
 		sink: &mut Buffer<'d, N, impl Pool<N>>
 	) -> BufferResult<usize> {
-		let mut total = 0;
-		let mut last = self.fill_free(sink)?;
-		while !self.is_eos() && last > 0 {
-			total += last;
-			last = self.fill(sink, N)?;
+		let mut total = self.fill_free(sink)?;
+		while !self.is_eos() && !sink.should_yield(total) {
+			let read = self.fill(sink, N)?;
+			if read == 0 {
+				break
+			}
+			total += read;
 		}
 		Ok(total)
 	}
 }
 
-pub trait SourceExt<'d, const N: usize, P: Pool<N>>: Source<'d, N> + Sized {
-	type Buffered: BufSource<'d, N, Pool = P>;
-
-	fn buffered(self) -> Self::Buffered {
+pub trait SourceExt<'d, const N: usize = SIZE>: Source<'d, N> + Sized {
+	/// Wraps the source in a [`BufferedSource`] with a default buffer claiming
+	/// segments from the default pool.
+	fn buffered(self) -> BufferedSource<'d, Self, N>
+	where DefaultPoolContainer: Pool<N> {
 		self.buffered_with(Buffer::default())
 	}
 
-	fn buffered_with_capacity(self, capacity: usize) -> Self::Buffered {
+	/// Wraps the source in a [`BufferedSource`] with a buffer reserving at least
+	/// `capacity` bytes from the default pool.
+	fn buffered_with_capacity(self, capacity: usize) -> BufferedSource<'d, Self, N>
+	where DefaultPoolContainer: Pool<N> {
 		self.buffered_with(Buffer::with_capacity(capacity))
 	}
 
-	fn buffered_with(self, buffer: Buffer<'d, N, P>) -> Self::Buffered;
-}
+	/// Wraps the source in a [`BufferedSource`] with a default buffer claiming
+	/// segments from `pool`.
+	fn buffered_in<P: Pool<N>>(self, pool: P) -> BufferedSource<'d, Self, N, P> {
+		self.buffered_with_options(pool, BufferOptions::default())
+	}
 
-impl<'d, S: Source<'d, SIZE>> SourceExt<'d, SIZE, DefaultPoolContainer> for S {
-	type Buffered = BufferedSource<'d, Self, DefaultPoolContainer>;
+	/// Wraps the source in a [`BufferedSource`] with a buffer created with `options`,
+	/// claiming segments from `pool`.
+	fn buffered_with_options<P: Pool<N>>(self, pool: P, options: BufferOptions) -> BufferedSource<'d, Self, N, P> {
+		self.buffered_with(Buffer::new(pool, options))
+	}
 
-	fn buffered_with(self, buffer: Buffer<'d>) -> Self::Buffered {
+	/// Wraps the source in a [`BufferedSource`] with `buffer`.
+	fn buffered_with<P: Pool<N>>(self, buffer: Buffer<'d, N, P>) -> BufferedSource<'d, Self, N, P> {
 		BufferedSource::new(self, buffer)
 	}
 }
 
+impl<'d, const N: usize, S: Source<'d, N>> SourceExt<'d, N> for S { }
+
 pub trait Sink<'d, const N: usize = SIZE>: Stream<N> {
 	/// Drains a buffer by writing up to `count` bytes into the sink, returning the
 	/// number of bytes written.
@@ -202,28 +298,50 @@ pub trait Sink<'d, const N: usize = SIZE>: Stream<N> {
 	fn flush(&mut self) -> Result { Ok(()) }
 }
 
-pub trait SinkExt<'d, const N: usize, P: Pool<N>>: Sink<'d, N> + Sized {
-	type Buffered: BufSink<'d, N, Pool = P>;
-
-	fn buffered(self) -> Self::Buffered {
+pub trait SinkExt<'d, const N: usize = SIZE>: Sink<'d, N> + Sized {
+	/// Wraps the sink in a [`BufferedSink`] with a default buffer claiming
+	/// segments from the default pool.
+	fn buffered(self) -> BufferedSink<'d, Self, N>
+	where DefaultPoolContainer: Pool<N> {
 		self.buffered_with(Buffer::default())
 	}
 
-	fn buffered_with_capacity(self, capacity: usize) -> Self::Buffered {
+	/// Wraps the sink in a [`BufferedSink`] with a buffer reserving at least
+	/// `capacity` bytes from the default pool.
+	fn buffered_with_capacity(self, capacity: usize) -> BufferedSink<'d, Self, N>
+	where DefaultPoolContainer: Pool<N> {
 		self.buffered_with(Buffer::with_capacity(capacity))
 	}
 
-	fn buffered_with(self, buffer: Buffer<'d, N, P>) -> Self::Buffered;
-}
+	/// Wraps the sink in a line-buffered [`BufferedSink`] with a default buffer
+	/// claiming segments from the default pool. See [`BufferedSink::set_line_buffered`].
+	fn line_buffered(self) -> BufferedSink<'d, Self, N>
+	where DefaultPoolContainer: Pool<N> {
+		let mut sink = self.buffered();
+		sink.set_line_buffered(true);
+		sink
+	}
+
+	/// Wraps the sink in a [`BufferedSink`] with a default buffer claiming
+	/// segments from `pool`.
+	fn buffered_in<P: Pool<N>>(self, pool: P) -> BufferedSink<'d, Self, N, P> {
+		self.buffered_with_options(pool, BufferOptions::default())
+	}
 
-impl<'d, S: Sink<'d, SIZE>> SinkExt<'d, SIZE, DefaultPoolContainer> for S {
-	type Buffered = BufferedSink<'d, Self, DefaultPoolContainer>;
+	/// Wraps the sink in a [`BufferedSink`] with a buffer created with `options`,
+	/// claiming segments from `pool`.
+	fn buffered_with_options<P: Pool<N>>(self, pool: P, options: BufferOptions) -> BufferedSink<'d, Self, N, P> {
+		self.buffered_with(Buffer::new(pool, options))
+	}
 
-	fn buffered_with(self, buffer: Buffer<'d>) -> Self::Buffered {
+	/// Wraps the sink in a [`BufferedSink`] with `buffer`.
+	fn buffered_with<P: Pool<N>>(self, buffer: Buffer<'d, N, P>) -> BufferedSink<'d, Self, N, P> {
 		BufferedSink::new(self, buffer)
 	}
 }
 
+impl<'d, const N: usize, S: Sink<'d, N>> SinkExt<'d, N> for S { }
+
 pub trait BufStream<'d, const N: usize = SIZE>: Stream<N> {
 	type Pool: Pool<N>;
 
@@ -305,6 +423,29 @@ pub trait BufSource<'d, const N: usize = SIZE>: BufStream<'d, N> + Source<'d, N>
 		)
 	}
 
+	/// Returns the buffered bytes in the first segment, reading from the source if
+	/// the buffer is empty. An empty slice is returned at end-of-stream. Bytes aren't
+	/// consumed until [`consume`] is called, mirroring [`BufRead::fill_buf`] for code
+	/// reading incrementally without copying.
+	///
+	/// [`consume`]: Self::consume
+	/// [`BufRead::fill_buf`]: std::io::BufRead::fill_buf
+	fn fill_buf<'b>(&'b mut self) -> Result<&'b [u8]> where 'd: 'b {
+		self.check_open(Read)?;
+		if self.available() == 0 {
+			self.request(1)?;
+		}
+		Ok(Self::buf(self).first_slice())
+	}
+
+	/// Consumes `count` bytes, usually after reading them from [`fill_buf`]. The
+	/// count is clamped to the available byte count.
+	///
+	/// [`fill_buf`]: Self::fill_buf
+	fn consume(&mut self, count: usize) {
+		self.buf_mut().skip(count);
+	}
+
 	/// Reads bytes into a slice, returning the number of bytes read.
 	fn read_slice(&mut self, buf: &mut [u8]) -> Result<usize> {
 		let mut read = 0;
@@ -324,6 +465,50 @@ pub trait BufSource<'d, const N: usize = SIZE>: BufStream<'d, N> + Source<'d, N>
 		Ok(read_count)
 	}
 
+	/// Reads bytes into the unfilled part of `cursor`, returning the number of bytes
+	/// read. Unlike [`read_slice`], the cursor memory doesn't need to be initialized
+	/// beforehand, allowing sources to feed [`BorrowedBuf`]-based APIs.
+	///
+	/// [`read_slice`]: Self::read_slice
+	/// [`BorrowedBuf`]: std::io::BorrowedBuf
+	fn read_into_cursor(&mut self, mut cursor: BorrowedCursor) -> Result<usize> {
+		self.read_count_spec(cursor.capacity(), move |src, _|
+			src.read_into_cursor(cursor.reborrow())
+		)
+	}
+
+	/// Reads up to `count` bytes, appending them to `vec` and returning the number
+	/// of bytes read. Bytes are copied directly into the spare capacity of `vec`,
+	/// without initializing it first.
+	fn read_to_vec(&mut self, vec: &mut Vec<u8>, count: usize) -> Result<usize> {
+		let mut read = 0;
+		while read < count {
+			self.request((count - read).min(N))?;
+			let available = self.available().min(count - read);
+			if available == 0 {
+				break
+			}
+
+			vec.reserve(available);
+			let mut buf = BorrowedBuf::from(&mut vec.spare_capacity_mut()[..available]);
+			self.buf_mut().read_into_cursor(buf.unfilled())?;
+			let len = buf.len();
+			unsafe {
+				// Safety: the borrowed buffer guarantees its filled bytes are initialized.
+				vec.set_len(vec.len() + len);
+			}
+			read += len;
+		}
+		Ok(read)
+	}
+
+	/// Reads bytes until end-of-stream, appending them to `vec` and returning the
+	/// number of bytes read. Bytes are copied directly into the spare capacity of
+	/// `vec`, without initializing it first.
+	fn read_to_end_vec(&mut self, vec: &mut Vec<u8>) -> Result<usize> {
+		self.read_to_vec(vec, usize::MAX)
+	}
+
 	/// Reads an array with a size of `T` bytes.
 	fn read_array<const T: usize>(&mut self) -> Result<[u8; T]> {
 		let mut array = [0; T];
@@ -457,21 +642,205 @@ pub trait BufSource<'d, const N: usize = SIZE>: BufStream<'d, N> + Source<'d, N>
 		Ok(buf)
 	}
 
+	/// Reads enough bytes to fill a slice of an arbitrary [`Pod`] data type, in
+	/// native byte order, or returns an end-of-stream error if the slice could not
+	/// be filled. The slice is read in bulk rather than element by element.
+	///
+	/// [`Pod`]: bytemuck::Pod
+	#[inline]
+	fn read_pod_slice<T: bytemuck::Pod>(&mut self, buf: &mut [T]) -> Result {
+		self.read_slice_exact(bytemuck::cast_slice_mut(buf))?;
+		Ok(())
+	}
+
+	/// Reads enough big-endian integers to fill a slice, or returns an end-of-stream
+	/// error if the slice could not be filled.
+	#[inline]
+	fn read_int_slice<T: PrimInt + bytemuck::Pod>(&mut self, buf: &mut [T]) -> Result {
+		self.read_pod_slice(buf)?;
+		if cfg!(target_endian = "little") {
+			for value in buf {
+				*value = value.to_be();
+			}
+		}
+		Ok(())
+	}
+
+	/// Reads enough little-endian integers to fill a slice, or returns an
+	/// end-of-stream error if the slice could not be filled.
+	#[inline]
+	fn read_int_slice_le<T: PrimInt + bytemuck::Pod>(&mut self, buf: &mut [T]) -> Result {
+		self.read_pod_slice(buf)?;
+		if cfg!(target_endian = "big") {
+			for value in buf {
+				*value = value.to_le();
+			}
+		}
+		Ok(())
+	}
+
+	/// Reads big-endian [`u16`]s into `buf`, or returns an end-of-stream error if
+	/// it could not be filled.
+	#[inline]
+	fn read_u16_slice_into(&mut self, buf: &mut [u16]) -> Result {
+		self.read_int_slice(buf)
+	}
+
+	/// Reads little-endian [`u16`]s into `buf`, or returns an end-of-stream error if
+	/// it could not be filled.
+	#[inline]
+	fn read_u16_slice_le_into(&mut self, buf: &mut [u16]) -> Result {
+		self.read_int_slice_le(buf)
+	}
+
+	/// Reads big-endian [`i16`]s into `buf`, or returns an end-of-stream error if
+	/// it could not be filled.
+	#[inline]
+	fn read_i16_slice_into(&mut self, buf: &mut [i16]) -> Result {
+		self.read_int_slice(buf)
+	}
+
+	/// Reads little-endian [`i16`]s into `buf`, or returns an end-of-stream error if
+	/// it could not be filled.
+	#[inline]
+	fn read_i16_slice_le_into(&mut self, buf: &mut [i16]) -> Result {
+		self.read_int_slice_le(buf)
+	}
+
+	/// Reads big-endian [`u32`]s into `buf`, or returns an end-of-stream error if
+	/// it could not be filled.
+	#[inline]
+	fn read_u32_slice_into(&mut self, buf: &mut [u32]) -> Result {
+		self.read_int_slice(buf)
+	}
+
+	/// Reads little-endian [`u32`]s into `buf`, or returns an end-of-stream error if
+	/// it could not be filled.
+	#[inline]
+	fn read_u32_slice_le_into(&mut self, buf: &mut [u32]) -> Result {
+		self.read_int_slice_le(buf)
+	}
+
+	/// Reads big-endian [`i32`]s into `buf`, or returns an end-of-stream error if
+	/// it could not be filled.
+	#[inline]
+	fn read_i32_slice_into(&mut self, buf: &mut [i32]) -> Result {
+		self.read_int_slice(buf)
+	}
+
+	/// Reads little-endian [`i32`]s into `buf`, or returns an end-of-stream error if
+	/// it could not be filled.
+	#[inline]
+	fn read_i32_slice_le_into(&mut self, buf: &mut [i32]) -> Result {
+		self.read_int_slice_le(buf)
+	}
+
+	/// Reads big-endian [`u64`]s into `buf`, or returns an end-of-stream error if
+	/// it could not be filled.
+	#[inline]
+	fn read_u64_slice_into(&mut self, buf: &mut [u64]) -> Result {
+		self.read_int_slice(buf)
+	}
+
+	/// Reads little-endian [`u64`]s into `buf`, or returns an end-of-stream error if
+	/// it could not be filled.
+	#[inline]
+	fn read_u64_slice_le_into(&mut self, buf: &mut [u64]) -> Result {
+		self.read_int_slice_le(buf)
+	}
+
+	/// Reads big-endian [`i64`]s into `buf`, or returns an end-of-stream error if
+	/// it could not be filled.
+	#[inline]
+	fn read_i64_slice_into(&mut self, buf: &mut [i64]) -> Result {
+		self.read_int_slice(buf)
+	}
+
+	/// Reads little-endian [`i64`]s into `buf`, or returns an end-of-stream error if
+	/// it could not be filled.
+	#[inline]
+	fn read_i64_slice_le_into(&mut self, buf: &mut [i64]) -> Result {
+		self.read_int_slice_le(buf)
+	}
+
+	/// Reads big-endian [`f32`]s into `buf`, or returns an end-of-stream error if
+	/// it could not be filled.
+	#[inline]
+	fn read_f32_slice_into(&mut self, buf: &mut [f32]) -> Result {
+		self.read_int_slice::<u32>(bytemuck::cast_slice_mut(buf))
+	}
+
+	/// Reads little-endian [`f32`]s into `buf`, or returns an end-of-stream error if
+	/// it could not be filled.
+	#[inline]
+	fn read_f32_slice_le_into(&mut self, buf: &mut [f32]) -> Result {
+		self.read_int_slice_le::<u32>(bytemuck::cast_slice_mut(buf))
+	}
+
+	/// Reads big-endian [`f64`]s into `buf`, or returns an end-of-stream error if
+	/// it could not be filled.
+	#[inline]
+	fn read_f64_slice_into(&mut self, buf: &mut [f64]) -> Result {
+		self.read_int_slice::<u64>(bytemuck::cast_slice_mut(buf))
+	}
+
+	/// Reads little-endian [`f64`]s into `buf`, or returns an end-of-stream error if
+	/// it could not be filled.
+	#[inline]
+	fn read_f64_slice_le_into(&mut self, buf: &mut [f64]) -> Result {
+		self.read_int_slice_le::<u64>(bytemuck::cast_slice_mut(buf))
+	}
+
 	/// Reads up to `count` UTF-8 bytes into `buf`, returning a slice of `buf`
-	/// containing the read data. If a decode error occurs, no data is consumed and
-	/// `buf` will contain the last valid data.
+	/// containing the read data. Fewer bytes are read only if the stream ends
+	/// first. Characters split between reads from the stream are decoded whole.
+	///
+	/// # Errors
+	///
+	/// If the bytes aren't valid UTF-8, or `count` or end-of-stream falls within a
+	/// character, a UTF-8 error is returned. The invalid or incomplete character
+	/// isn't consumed, and `buf` will contain the valid data before it, though only
+	/// data decoded before the last read from the stream is consumed. To consume
+	/// nothing on error, use [`read_utf8_exact_count`].
+	///
+	/// [`read_utf8_exact_count`]: Self::read_utf8_exact_count
 	fn read_utf8<'s>(&mut self, buf: &'s mut String, count: usize) -> Result<&'s str> {
 		let len = buf.len();
-		self.read_count_spec(count, |src, count| src.read_utf8(buf, count).map(str::len))?;
+		self.read_utf8_spec(buf, count, usize::MAX)?;
+		Ok(&buf[len..])
+	}
+
+	/// Reads exactly `count` UTF-8 bytes into `buf`, returning a slice of `buf`
+	/// containing the read data.
+	///
+	/// # Errors
+	///
+	/// If the stream ends before `count` bytes, an end-of-stream error is returned.
+	/// If the bytes aren't valid UTF-8, or `count` falls within a character, a UTF-8
+	/// error is returned and `buf` will contain the valid data before the error. No
+	/// bytes are consumed if an error is returned.
+	fn read_utf8_exact_count<'s>(&mut self, buf: &'s mut String, count: usize) -> Result<&'s str> {
+		self.require(count)?;
+		let len = buf.len();
+		let read_count = self.buf_mut().read_utf8(buf, count)?.len();
+		assert_eq!(read_count, count, "require should ensure all bytes are available");
 		Ok(&buf[len..])
 	}
 
 	/// Reads UTF-8 bytes into `buf` until end-of-stream, returning a slice of `buf`
-	/// containing the read data. If a decode error occurs, no data is consumed and
-	/// `buf` will contain the last valid data.
+	/// containing the read data. Characters split between reads from the stream are
+	/// decoded whole.
+	///
+	/// # Errors
+	///
+	/// If the bytes aren't valid UTF-8, or end-of-stream falls within a character,
+	/// a UTF-8 error is returned, as with [`read_utf8`].
+	///
+	/// [`read_utf8`]: Self::read_utf8
 	fn read_utf8_to_end<'s>(&mut self, buf: &'s mut String) -> Result<&'s str> {
 		let len = buf.len();
-		self.read_spec(|src| src.read_utf8_to_end(buf).map(|s| (s.len(), false)))?;
+		let chunk_len = self.buf().limit().max(N);
+		self.read_utf8_spec(buf, usize::MAX, chunk_len)?;
 		Ok(&buf[len..])
 	}
 
@@ -492,28 +861,86 @@ pub trait BufSource<'d, const N: usize = SIZE>: BufStream<'d, N> + Source<'d, N>
 			.map(Into::into)
 	}
 
-	/// Reads buffered UTF-8 bytes into `buf` until the `terminator` pattern,
-	/// returning the number of bytes read and whether the pattern was found. If a
+	/// Reads UTF-8 bytes into `buf` until the `terminator` pattern, returning the
+	/// number of bytes read and whether the pattern was found. More bytes are read
+	/// from the source until the pattern is found or the source is exhausted. If a
 	/// decode error occurs, no data is consumed and `buf` will contain the last
 	/// valid data.
-	///
-	/// Note that, unlike methods like [`read_utf8_line`], this method only acts on
-	/// buffered bytes. This is because the terminator pattern must be moved, so it
-	/// cannot be used in a loop. This constraint may be lifted in the future.
 	fn read_utf8_until(&mut self, buf: &mut String, terminator: impl Pattern) -> Result<Utf8Match> {
-		self.buf_mut().read_utf8_until(buf, terminator)
+		self.read_utf8_until_with(buf, &mut terminator.into_matcher())
 	}
 
-	/// Reads buffered UTF-8 bytes into `buf` until and including the `terminator`
-	/// pattern, returning the number of bytes read and whether the pattern was
-	/// found. If a decode error occurs, no data is consumed and `buf` will contain
-	/// the last valid data.
-	///
-	/// Note that, unlike methods like [`read_utf8_line`], this method only acts on
-	/// buffered bytes. This is because the terminator pattern must be moved, so it
-	/// cannot be used in a loop. This constraint may be lifted in the future.
+	/// Reads UTF-8 bytes into `buf` until and including the `terminator` pattern,
+	/// returning the number of bytes read and whether the pattern was found. More
+	/// bytes are read from the source until the pattern is found or the source is
+	/// exhausted. If a decode error occurs, no data is consumed and `buf` will
+	/// contain the last valid data.
 	fn read_utf8_until_inclusive(&mut self, buf: &mut String, terminator: impl Pattern) -> Result<Utf8Match> {
-		self.buf_mut().read_utf8_until_inclusive(buf, terminator)
+		let end = match self.find_spec(&mut terminator.into_matcher())? {
+			Some(Range { end, .. }) => end,
+			None => return self.read_utf8_to_end(buf).map(|str| (str.len(), false).into())
+		};
+		self.read_utf8(buf, end)
+			.map(|str| (str.len(), true).into())
+	}
+
+	/// Reads UTF-8 bytes into `buf` until a match from the `terminator` matcher, as
+	/// with [`read_utf8_until`]. The matcher is reset and borrowed rather than moved,
+	/// so one matcher can be reused across calls.
+	///
+	/// [`read_utf8_until`]: Self::read_utf8_until
+	fn read_utf8_until_with(&mut self, buf: &mut String, terminator: &mut impl Matcher) -> Result<Utf8Match> {
+		let range = match self.find_spec(terminator)? {
+			Some(range) => range,
+			None => return self.read_utf8_to_end(buf).map(|str| (str.len(), false).into())
+		};
+		let count = self.read_utf8(buf, range.start)?.len();
+		self.skip(range.len())?;
+		Ok((count, true).into())
+	}
+
+	/// Reads bytes into `buf` until a line terminator, returning the number of bytes
+	/// read and whether the line terminator was found. Unlike [`read_utf8_line`],
+	/// bytes don't need to be valid UTF-8.
+	///
+	/// [`read_utf8_line`]: Self::read_utf8_line
+	fn read_line_bytes(&mut self, buf: &mut ByteString) -> Result<Utf8Match> {
+		self.read_spec(|src| src.read_line_bytes(buf))
+			.map(Into::into)
+	}
+
+	/// Reads bytes into `sink` until the `terminator` pattern, returning the number
+	/// of bytes read and whether the pattern was found. The terminator is consumed,
+	/// but not written to `sink`. Unlike [`read_utf8_until`], bytes don't need to be
+	/// valid UTF-8.
+	///
+	/// [`read_utf8_until`]: Self::read_utf8_until
+	fn read_until(&mut self, sink: &mut impl Sink<'d, N>, terminator: impl Pattern) -> Result<Utf8Match> {
+		let Some(Range { start, end }) = self.find_spec(&mut terminator.into_matcher())? else {
+			return self.read_all(sink).map(|count| (count, false).into())
+		};
+
+		let count = self.read(sink, start)?;
+		if count < start {
+			return Ok((count, false).into())
+		}
+
+		self.skip(end - start)?;
+		Ok((count, true).into())
+	}
+
+	/// Reads bytes into `sink` until and including the `terminator` pattern,
+	/// returning the number of bytes read and whether the pattern was found. Unlike
+	/// [`read_utf8_until_inclusive`], bytes don't need to be valid UTF-8.
+	///
+	/// [`read_utf8_until_inclusive`]: Self::read_utf8_until_inclusive
+	fn read_until_inclusive(&mut self, sink: &mut impl Sink<'d, N>, terminator: impl Pattern) -> Result<Utf8Match> {
+		let Some(Range { end, .. }) = self.find_spec(&mut terminator.into_matcher())? else {
+			return self.read_all(sink).map(|count| (count, false).into())
+		};
+
+		let count = self.read(sink, end)?;
+		Ok((count, count == end).into())
 	}
 }
 
@@ -527,11 +954,65 @@ trait BufSourceSpec<'d, const N: usize>: BufSource<'d, N> {
 			let (read, term) = read(self.buf_mut())?.into();
 			count += read;
 			if term { return Ok((count, term)) }
-			self.request(self.buf().limit().max(N))?;
+			if !self.request(self.buf().limit().max(N))? && self.available() == 0 {
+				break
+			}
 		}
 		Ok((count, false))
 	}
 
+	/// Finds a match from `matcher`, reading more bytes until a match is found or
+	/// no more bytes can be read. After each read, the search resumes from the last
+	/// scanned byte; the matcher keeps a partial match at the end of the buffered
+	/// bytes, so greedy matchers may extend it.
+	fn find_spec(&mut self, matcher: &mut impl Matcher) -> Result<Option<Range<usize>>> {
+		matcher.reset();
+		let mut scanned = 0;
+		loop {
+			let available = self.available();
+			let mut end = available;
+			if matcher.alignment() == Alignment::Char && !self.is_eos() {
+				// Leave a character split at the end to be scanned once completed.
+				end -= split_utf8_len(self.buf(), end);
+			}
+
+			if let Some(found) = self.buf().resume_find(matcher, scanned..end) {
+				return Ok(Some(found))
+			}
+			scanned = end;
+
+			if self.is_eos() || !self.request(available + 1)? && self.available() == available {
+				return Ok(matcher.end().and_then(MatchStep::into_range))
+			}
+		}
+	}
+
+	/// Reads up to `count` UTF-8 bytes into `buf`, requesting at most `chunk_len`
+	/// bytes at a time. A character split at the end of the buffered bytes is
+	/// completed by reading more before decoding, so it's only decoded as incomplete
+	/// if `count` or end-of-stream falls within it.
+	fn read_utf8_spec(&mut self, buf: &mut String, count: usize, chunk_len: usize) -> Result<usize> {
+		let mut read = 0;
+		while read < count {
+			let remaining = count - read;
+			self.request(remaining.min(chunk_len))?;
+			let mut end = self.available().min(remaining);
+			if end == 0 {
+				break
+			}
+
+			if end < remaining {
+				let missing = missing_utf8_len(self.buf(), end);
+				if missing > 0 {
+					self.request(end + missing)?;
+					end = (end + missing).min(self.available()).min(remaining);
+				}
+			}
+			read += self.buf_mut().read_utf8(buf, end)?.len();
+		}
+		Ok(read)
+	}
+
 	fn read_count_spec<E>(
 		&mut self,
 		mut count: usize,
@@ -553,6 +1034,31 @@ trait BufSourceSpec<'d, const N: usize>: BufSource<'d, N> {
 
 impl<'d, const N: usize, T: BufSource<'d, N> + ?Sized> BufSourceSpec<'d, N> for T { }
 
+/// Returns the number of bytes before `end` in a character split at `end`.
+fn split_utf8_len<const N: usize>(buf: &Buffer<'_, N, impl Pool<N>>, end: usize) -> usize {
+	for len in 1..=end.min(3) {
+		let Some(byte) = buf.get(end - len) else { break };
+		// Skip continuation bytes to find the start of the character.
+		if byte & 0xC0 != 0x80 {
+			return if utf8_char_width(byte) > len { len } else { 0 }
+		}
+	}
+	0
+}
+
+/// Returns the number of bytes missing from a character split at `end`, or zero if
+/// `end` is at a character boundary.
+fn missing_utf8_len<const N: usize>(buf: &Buffer<'_, N, impl Pool<N>>, end: usize) -> usize {
+	for len in 1..=end.min(3) {
+		let Some(byte) = buf.get(end - len) else { break };
+		// Skip continuation bytes to find the start of the character.
+		if byte & 0xC0 != 0x80 {
+			return utf8_char_width(byte).saturating_sub(len)
+		}
+	}
+	0
+}
+
 pub trait BufSink<'d, const N: usize = SIZE>: BufStream<'d, N> + Sink<'d, N> {
 	/// Writes up to `count` bytes from `source`, returning the number of bytes written.
 	fn write(&mut self, source: &mut impl Source<'d, N>, count: usize) -> Result<usize> {
@@ -588,6 +1094,40 @@ pub trait BufSink<'d, const N: usize = SIZE>: BufStream<'d, N> + Sink<'d, N> {
 	/// buffer directly, when this method would otherwise be skipped.
 	fn drain_buffered(&mut self) -> BufferResult;
 
+	/// Makes a single attempt to write bytes from a slice, returning the number of
+	/// bytes written. Like [`io::Write::write`], fewer bytes than the length of
+	/// `buf` may be written, such as when the buffer is near its maximum capacity.
+	/// A count of `0` for a non-empty slice means the sink can't accept any more
+	/// bytes right now. See [`write_all_slice`] to write the entire slice.
+	///
+	/// [`write_all_slice`]: Self::write_all_slice
+	fn write_slice(&mut self, buf: &[u8]) -> Result<usize> {
+		let buffer = self.buf_mut();
+		let len = buf.len().min(buffer.remaining_capacity());
+		let written = buffer.write_from_slice(&buf[..len]).context(Write)?;
+		self.drain_buffered().context(Write)?;
+		Ok(written)
+	}
+
+	/// Writes an entire slice, calling [`write_slice`] until all bytes are written.
+	/// Like [`io::Write::write_all`], this either writes all bytes or fails.
+	///
+	/// # Errors
+	///
+	/// If a write makes no progress, an IO error of kind [`WriteZero`] is returned.
+	///
+	/// [`write_slice`]: Self::write_slice
+	/// [`WriteZero`]: io::ErrorKind::WriteZero
+	fn write_all_slice(&mut self, mut buf: &[u8]) -> Result {
+		while !buf.is_empty() {
+			match self.write_slice(buf)? {
+				0 => return Err(io::Error::from(io::ErrorKind::WriteZero)).context(Write),
+				written => buf = &buf[written..]
+			}
+		}
+		Ok(())
+	}
+
 	/// Writes bytes from a slice, returning the number of bytes written.
 	fn write_from_slice(&mut self, mut buf: &[u8]) -> Result<usize> {
 		let mut count = 0;
@@ -600,6 +1140,18 @@ pub trait BufSink<'d, const N: usize = SIZE>: BufStream<'d, N> + Sink<'d, N> {
 		Ok(count)
 	}
 
+	/// Writes `count` zero bytes, such as to pad fields in binary formats, without
+	/// writing from a temporary slice of zeroes.
+	fn write_zeroes(&mut self, mut count: usize) -> Result {
+		while count > 0 {
+			let len = count.min(N);
+			self.buf_mut().write_zeroes(len).context(Write)?;
+			count -= len;
+			self.drain_buffered().context(Write)?;
+		}
+		Ok(())
+	}
+
 	/// Writes a [`u8`].
 	#[inline]
 	fn write_u8(&mut self, value: u8) -> Result {
@@ -726,11 +1278,161 @@ pub trait BufSink<'d, const N: usize = SIZE>: BufStream<'d, N> + Sink<'d, N> {
 		Ok(())
 	}
 
+	/// Writes a slice of an arbitrary [`Pod`] data type in native byte order. The
+	/// slice is written in bulk rather than element by element.
+	///
+	/// [`Pod`]: bytemuck::Pod
+	#[inline]
+	fn write_pod_slice<T: bytemuck::Pod>(&mut self, values: &[T]) -> Result {
+		self.write_from_slice(bytemuck::cast_slice(values))?;
+		Ok(())
+	}
+
+	/// Writes a slice of big-endian integers.
+	#[inline]
+	fn write_int_slice<T: PrimInt + bytemuck::Pod>(&mut self, values: &[T]) -> Result {
+		if cfg!(target_endian = "big") {
+			self.write_pod_slice(values)
+		} else {
+			self.write_swapped_slice(values)
+		}
+	}
+
+	/// Writes a slice of little-endian integers.
+	#[inline]
+	fn write_int_slice_le<T: PrimInt + bytemuck::Pod>(&mut self, values: &[T]) -> Result {
+		if cfg!(target_endian = "little") {
+			self.write_pod_slice(values)
+		} else {
+			self.write_swapped_slice(values)
+		}
+	}
+
+	/// Writes a slice of big-endian [`u16`]s.
+	#[inline]
+	fn write_u16_slice(&mut self, values: &[u16]) -> Result {
+		self.write_int_slice(values)
+	}
+
+	/// Writes a slice of little-endian [`u16`]s.
+	#[inline]
+	fn write_u16_slice_le(&mut self, values: &[u16]) -> Result {
+		self.write_int_slice_le(values)
+	}
+
+	/// Writes a slice of big-endian [`i16`]s.
+	#[inline]
+	fn write_i16_slice(&mut self, values: &[i16]) -> Result {
+		self.write_int_slice(values)
+	}
+
+	/// Writes a slice of little-endian [`i16`]s.
+	#[inline]
+	fn write_i16_slice_le(&mut self, values: &[i16]) -> Result {
+		self.write_int_slice_le(values)
+	}
+
+	/// Writes a slice of big-endian [`u32`]s.
+	#[inline]
+	fn write_u32_slice(&mut self, values: &[u32]) -> Result {
+		self.write_int_slice(values)
+	}
+
+	/// Writes a slice of little-endian [`u32`]s.
+	#[inline]
+	fn write_u32_slice_le(&mut self, values: &[u32]) -> Result {
+		self.write_int_slice_le(values)
+	}
+
+	/// Writes a slice of big-endian [`i32`]s.
+	#[inline]
+	fn write_i32_slice(&mut self, values: &[i32]) -> Result {
+		self.write_int_slice(values)
+	}
+
+	/// Writes a slice of little-endian [`i32`]s.
+	#[inline]
+	fn write_i32_slice_le(&mut self, values: &[i32]) -> Result {
+		self.write_int_slice_le(values)
+	}
+
+	/// Writes a slice of big-endian [`u64`]s.
+	#[inline]
+	fn write_u64_slice(&mut self, values: &[u64]) -> Result {
+		self.write_int_slice(values)
+	}
+
+	/// Writes a slice of little-endian [`u64`]s.
+	#[inline]
+	fn write_u64_slice_le(&mut self, values: &[u64]) -> Result {
+		self.write_int_slice_le(values)
+	}
+
+	/// Writes a slice of big-endian [`i64`]s.
+	#[inline]
+	fn write_i64_slice(&mut self, values: &[i64]) -> Result {
+		self.write_int_slice(values)
+	}
+
+	/// Writes a slice of little-endian [`i64`]s.
+	#[inline]
+	fn write_i64_slice_le(&mut self, values: &[i64]) -> Result {
+		self.write_int_slice_le(values)
+	}
+
+	/// Writes a slice of big-endian [`f32`]s.
+	#[inline]
+	fn write_f32_slice(&mut self, values: &[f32]) -> Result {
+		self.write_int_slice::<u32>(bytemuck::cast_slice(values))
+	}
+
+	/// Writes a slice of little-endian [`f32`]s.
+	#[inline]
+	fn write_f32_slice_le(&mut self, values: &[f32]) -> Result {
+		self.write_int_slice_le::<u32>(bytemuck::cast_slice(values))
+	}
+
+	/// Writes a slice of big-endian [`f64`]s.
+	#[inline]
+	fn write_f64_slice(&mut self, values: &[f64]) -> Result {
+		self.write_int_slice::<u64>(bytemuck::cast_slice(values))
+	}
+
+	/// Writes a slice of little-endian [`f64`]s.
+	#[inline]
+	fn write_f64_slice_le(&mut self, values: &[f64]) -> Result {
+		self.write_int_slice_le::<u64>(bytemuck::cast_slice(values))
+	}
+
 	/// Writes a UTF-8 string.
 	#[inline]
 	fn write_utf8(&mut self, value: &str) -> Result<usize> {
 		self.write_from_slice(value.as_bytes())
 	}
+
+	/// Writes formatted text, returning the number of bytes written. This allows
+	/// formatting directly into the sink with the [`write!`] macro, without
+	/// allocating an intermediate string:
+	///
+	/// ```ignore
+	/// use orio::Buffer;
+	/// use orio::streams::BufSink;
+	///
+	/// let mut buffer = Buffer::default();
+	/// write!(buffer, "{} + {} = {}", 1, 2, 1 + 2).unwrap();
+	/// assert_eq!(buffer, b"1 + 2 = 3");
+	/// ```
+	#[inline]
+	fn write_fmt(&mut self, args: fmt::Arguments) -> Result<usize> {
+		self.fmt_writer().write_args(args)
+	}
+
+	/// Returns an adapter implementing [`fmt::Write`] for this sink, for use with
+	/// APIs accepting a formatter target.
+	#[inline]
+	fn fmt_writer(&mut self) -> FmtWriter<'_, 'd, Self, N> {
+		FmtWriter::new(self)
+	}
 }
 
 trait BufSinkSpec<'d, const N: usize>: BufSink<'d, N> {
@@ -757,6 +1459,20 @@ trait BufSinkSpec<'d, const N: usize>: BufSink<'d, N> {
 		}
 		Ok(initial - count)
 	}
+
+	/// Writes a slice of integers with their bytes swapped, in chunks converted on
+	/// the stack.
+	fn write_swapped_slice<T: PrimInt + bytemuck::Pod>(&mut self, values: &[T]) -> Result {
+		let mut chunk = [T::zeroed(); 64];
+		for values in values.chunks(chunk.len()) {
+			let chunk = &mut chunk[..values.len()];
+			for (swapped, value) in chunk.iter_mut().zip(values) {
+				*swapped = value.swap_bytes();
+			}
+			self.write_pod_slice(chunk)?;
+		}
+		Ok(())
+	}
 }
 
 impl<'d, const N: usize, T: BufSink<'d, N> + ?Sized> BufSinkSpec<'d, N> for T { }
@@ -817,6 +1533,62 @@ impl<'d, const N: usize, S: Sink<'d, N> + ?Sized> Sink<'d, N> for &mut S {
 	}
 }
 
+impl<const N: usize, T: Stream<N> + ?Sized> Stream<N> for Box<T> {
+	#[inline]
+	fn is_closed(&self) -> bool {
+		T::is_closed(self)
+	}
+
+	#[inline]
+	fn close(&mut self) -> Result {
+		T::close(self)
+	}
+}
+
+impl<'d, const N: usize, S: Source<'d, N> + ?Sized> Source<'d, N> for Box<S> {
+	#[inline]
+	fn is_eos(&self) -> bool {
+		S::is_eos(self)
+	}
+
+	#[inline]
+	fn fill(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		S::fill(self, sink, count)
+	}
+
+	#[inline]
+	fn fill_free(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>) -> BufferResult<usize> {
+		S::fill_free(self, sink)
+	}
+
+	#[inline]
+	fn fill_all(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>) -> BufferResult<usize> {
+		S::fill_all(self, sink)
+	}
+}
+
+impl<'d, const N: usize, S: Sink<'d, N> + ?Sized> Sink<'d, N> for Box<S> {
+	#[inline]
+	fn drain(&mut self, source: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		S::drain(self, source, count)
+	}
+
+	#[inline]
+	fn drain_full(&mut self, source: &mut Buffer<'d, N, impl Pool<N>>) -> BufferResult<usize> {
+		S::drain_full(self, source)
+	}
+
+	#[inline]
+	fn drain_all(&mut self, source: &mut Buffer<'d, N, impl Pool<N>>) -> BufferResult<usize> {
+		S::drain_all(self, source)
+	}
+
+	#[inline]
+	fn flush(&mut self) -> Result {
+		S::flush(self)
+	}
+}
+
 impl<'d, const N: usize, S: BufStream<'d, N> + ?Sized> BufStream<'d, N> for &mut S {
 	type Pool = S::Pool;
 
@@ -862,6 +1634,16 @@ impl<'d, const N: usize, S: BufSource<'d, N> + ?Sized> BufSource<'d, N> for &mut
 		S::skip(self, count)
 	}
 
+	#[inline]
+	fn fill_buf<'b>(&'b mut self) -> Result<&'b [u8]> where 'd: 'b {
+		S::fill_buf(self)
+	}
+
+	#[inline]
+	fn consume(&mut self, count: usize) {
+		S::consume(self, count)
+	}
+
 	#[inline]
 	fn read_slice(&mut self, buf: &mut [u8]) -> Result<usize> {
 		S::read_slice(self, buf)
@@ -872,6 +1654,21 @@ impl<'d, const N: usize, S: BufSource<'d, N> + ?Sized> BufSource<'d, N> for &mut
 		S::read_slice_exact(self, buf)
 	}
 
+	#[inline]
+	fn read_into_cursor(&mut self, cursor: BorrowedCursor) -> Result<usize> {
+		S::read_into_cursor(self, cursor)
+	}
+
+	#[inline]
+	fn read_to_vec(&mut self, vec: &mut Vec<u8>, count: usize) -> Result<usize> {
+		S::read_to_vec(self, vec, count)
+	}
+
+	#[inline]
+	fn read_to_end_vec(&mut self, vec: &mut Vec<u8>) -> Result<usize> {
+		S::read_to_end_vec(self, vec)
+	}
+
 	#[inline]
 	fn read_array<const T: usize>(&mut self) -> Result<[u8; T]> {
 		S::read_array(self)
@@ -1002,11 +1799,111 @@ impl<'d, const N: usize, S: BufSource<'d, N> + ?Sized> BufSource<'d, N> for &mut
 		S::read_pod(self)
 	}
 
+	#[inline]
+	fn read_pod_slice<T: bytemuck::Pod>(&mut self, buf: &mut [T]) -> Result {
+		S::read_pod_slice(self, buf)
+	}
+
+	#[inline]
+	fn read_int_slice<T: PrimInt + bytemuck::Pod>(&mut self, buf: &mut [T]) -> Result {
+		S::read_int_slice(self, buf)
+	}
+
+	#[inline]
+	fn read_int_slice_le<T: PrimInt + bytemuck::Pod>(&mut self, buf: &mut [T]) -> Result {
+		S::read_int_slice_le(self, buf)
+	}
+
+	#[inline]
+	fn read_u16_slice_into(&mut self, buf: &mut [u16]) -> Result {
+		S::read_u16_slice_into(self, buf)
+	}
+
+	#[inline]
+	fn read_u16_slice_le_into(&mut self, buf: &mut [u16]) -> Result {
+		S::read_u16_slice_le_into(self, buf)
+	}
+
+	#[inline]
+	fn read_i16_slice_into(&mut self, buf: &mut [i16]) -> Result {
+		S::read_i16_slice_into(self, buf)
+	}
+
+	#[inline]
+	fn read_i16_slice_le_into(&mut self, buf: &mut [i16]) -> Result {
+		S::read_i16_slice_le_into(self, buf)
+	}
+
+	#[inline]
+	fn read_u32_slice_into(&mut self, buf: &mut [u32]) -> Result {
+		S::read_u32_slice_into(self, buf)
+	}
+
+	#[inline]
+	fn read_u32_slice_le_into(&mut self, buf: &mut [u32]) -> Result {
+		S::read_u32_slice_le_into(self, buf)
+	}
+
+	#[inline]
+	fn read_i32_slice_into(&mut self, buf: &mut [i32]) -> Result {
+		S::read_i32_slice_into(self, buf)
+	}
+
+	#[inline]
+	fn read_i32_slice_le_into(&mut self, buf: &mut [i32]) -> Result {
+		S::read_i32_slice_le_into(self, buf)
+	}
+
+	#[inline]
+	fn read_u64_slice_into(&mut self, buf: &mut [u64]) -> Result {
+		S::read_u64_slice_into(self, buf)
+	}
+
+	#[inline]
+	fn read_u64_slice_le_into(&mut self, buf: &mut [u64]) -> Result {
+		S::read_u64_slice_le_into(self, buf)
+	}
+
+	#[inline]
+	fn read_i64_slice_into(&mut self, buf: &mut [i64]) -> Result {
+		S::read_i64_slice_into(self, buf)
+	}
+
+	#[inline]
+	fn read_i64_slice_le_into(&mut self, buf: &mut [i64]) -> Result {
+		S::read_i64_slice_le_into(self, buf)
+	}
+
+	#[inline]
+	fn read_f32_slice_into(&mut self, buf: &mut [f32]) -> Result {
+		S::read_f32_slice_into(self, buf)
+	}
+
+	#[inline]
+	fn read_f32_slice_le_into(&mut self, buf: &mut [f32]) -> Result {
+		S::read_f32_slice_le_into(self, buf)
+	}
+
+	#[inline]
+	fn read_f64_slice_into(&mut self, buf: &mut [f64]) -> Result {
+		S::read_f64_slice_into(self, buf)
+	}
+
+	#[inline]
+	fn read_f64_slice_le_into(&mut self, buf: &mut [f64]) -> Result {
+		S::read_f64_slice_le_into(self, buf)
+	}
+
 	#[inline]
 	fn read_utf8<'s>(&mut self, buf: &'s mut String, count: usize) -> Result<&'s str> {
 		S::read_utf8(self, buf, count)
 	}
 
+	#[inline]
+	fn read_utf8_exact_count<'s>(&mut self, buf: &'s mut String, count: usize) -> Result<&'s str> {
+		S::read_utf8_exact_count(self, buf, count)
+	}
+
 	#[inline]
 	fn read_utf8_to_end<'s>(&mut self, buf: &'s mut String) -> Result<&'s str> {
 		S::read_utf8_to_end(self, buf)
@@ -1031,6 +1928,26 @@ impl<'d, const N: usize, S: BufSource<'d, N> + ?Sized> BufSource<'d, N> for &mut
 	fn read_utf8_until_inclusive(&mut self, buf: &mut String, terminator: impl Pattern) -> Result<Utf8Match> {
 		S::read_utf8_until_inclusive(self, buf, terminator)
 	}
+
+	#[inline]
+	fn read_utf8_until_with(&mut self, buf: &mut String, terminator: &mut impl Matcher) -> Result<Utf8Match> {
+		S::read_utf8_until_with(self, buf, terminator)
+	}
+
+	#[inline]
+	fn read_line_bytes(&mut self, buf: &mut ByteString) -> Result<Utf8Match> {
+		S::read_line_bytes(self, buf)
+	}
+
+	#[inline]
+	fn read_until(&mut self, sink: &mut impl Sink<'d, N>, terminator: impl Pattern) -> Result<Utf8Match> {
+		S::read_until(self, sink, terminator)
+	}
+
+	#[inline]
+	fn read_until_inclusive(&mut self, sink: &mut impl Sink<'d, N>, terminator: impl Pattern) -> Result<Utf8Match> {
+		S::read_until_inclusive(self, sink, terminator)
+	}
 }
 
 impl<'d, const N: usize, S: BufSink<'d, N> + ?Sized> BufSink<'d, N> for &mut S {
@@ -1054,11 +1971,25 @@ impl<'d, const N: usize, S: BufSink<'d, N> + ?Sized> BufSink<'d, N> for &mut S {
 		S::drain_all_buffered(self)
 	}
 
+	#[inline]
+	fn write_slice(&mut self, buf: &[u8]) -> Result<usize> {
+		S::write_slice(self, buf)
+	}
+
+	#[inline]
+	fn write_all_slice(&mut self, buf: &[u8]) -> Result {
+		S::write_all_slice(self, buf)
+	}
 	#[inline]
 	fn write_from_slice(&mut self, buf: &[u8]) -> Result<usize> {
 		S::write_from_slice(self, buf)
 	}
 
+	#[inline]
+	fn write_zeroes(&mut self, count: usize) -> Result {
+		S::write_zeroes(self, count)
+	}
+
 	#[inline]
 	fn write_u8(&mut self, value: u8) -> Result {
 		S::write_u8(self, value)
@@ -1184,8 +2115,108 @@ impl<'d, const N: usize, S: BufSink<'d, N> + ?Sized> BufSink<'d, N> for &mut S {
 		S::write_pod(self, value)
 	}
 
+	#[inline]
+	fn write_pod_slice<T: bytemuck::Pod>(&mut self, values: &[T]) -> Result {
+		S::write_pod_slice(self, values)
+	}
+
+	#[inline]
+	fn write_int_slice<T: PrimInt + bytemuck::Pod>(&mut self, values: &[T]) -> Result {
+		S::write_int_slice(self, values)
+	}
+
+	#[inline]
+	fn write_int_slice_le<T: PrimInt + bytemuck::Pod>(&mut self, values: &[T]) -> Result {
+		S::write_int_slice_le(self, values)
+	}
+
+	#[inline]
+	fn write_u16_slice(&mut self, values: &[u16]) -> Result {
+		S::write_u16_slice(self, values)
+	}
+
+	#[inline]
+	fn write_u16_slice_le(&mut self, values: &[u16]) -> Result {
+		S::write_u16_slice_le(self, values)
+	}
+
+	#[inline]
+	fn write_i16_slice(&mut self, values: &[i16]) -> Result {
+		S::write_i16_slice(self, values)
+	}
+
+	#[inline]
+	fn write_i16_slice_le(&mut self, values: &[i16]) -> Result {
+		S::write_i16_slice_le(self, values)
+	}
+
+	#[inline]
+	fn write_u32_slice(&mut self, values: &[u32]) -> Result {
+		S::write_u32_slice(self, values)
+	}
+
+	#[inline]
+	fn write_u32_slice_le(&mut self, values: &[u32]) -> Result {
+		S::write_u32_slice_le(self, values)
+	}
+
+	#[inline]
+	fn write_i32_slice(&mut self, values: &[i32]) -> Result {
+		S::write_i32_slice(self, values)
+	}
+
+	#[inline]
+	fn write_i32_slice_le(&mut self, values: &[i32]) -> Result {
+		S::write_i32_slice_le(self, values)
+	}
+
+	#[inline]
+	fn write_u64_slice(&mut self, values: &[u64]) -> Result {
+		S::write_u64_slice(self, values)
+	}
+
+	#[inline]
+	fn write_u64_slice_le(&mut self, values: &[u64]) -> Result {
+		S::write_u64_slice_le(self, values)
+	}
+
+	#[inline]
+	fn write_i64_slice(&mut self, values: &[i64]) -> Result {
+		S::write_i64_slice(self, values)
+	}
+
+	#[inline]
+	fn write_i64_slice_le(&mut self, values: &[i64]) -> Result {
+		S::write_i64_slice_le(self, values)
+	}
+
+	#[inline]
+	fn write_f32_slice(&mut self, values: &[f32]) -> Result {
+		S::write_f32_slice(self, values)
+	}
+
+	#[inline]
+	fn write_f32_slice_le(&mut self, values: &[f32]) -> Result {
+		S::write_f32_slice_le(self, values)
+	}
+
+	#[inline]
+	fn write_f64_slice(&mut self, values: &[f64]) -> Result {
+		S::write_f64_slice(self, values)
+	}
+
+	#[inline]
+	fn write_f64_slice_le(&mut self, values: &[f64]) -> Result {
+		S::write_f64_slice_le(self, values)
+	}
+
 	#[inline]
 	fn write_utf8(&mut self, value: &str) -> Result<usize> {
 		S::write_utf8(self, value)
 	}
+
+	#[inline]
+	fn write_fmt(&mut self, args: fmt::Arguments) -> Result<usize> {
+		S::write_fmt(self, args)
+	}
 }
@@ -0,0 +1,415 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Low-level [CBOR] primitives over [`BufSource`]s and [`BufSink`]s: data item
+//! headers, integers, byte and text strings, array and map headers, and tags, for
+//! building formats such as COSE and CWT. Strings are read without copying,
+//! borrowing their bytes from the source buffer.
+//!
+//! [CBOR]: https://www.rfc-editor.org/rfc/rfc8949
+
+use std::marker::PhantomData;
+use std::ops::Range;
+use crate::{ByteStr, SIZE};
+use super::{invalid_data, BufSink, BufSource, Result};
+
+/// The additional information value marking an indefinite length, or a break.
+const INDEFINITE: u8 = 31;
+/// The default maximum length of a byte or text string.
+const MAX_LEN: usize = 16 * 1024 * 1024;
+
+/// A data item's major type, the high three bits of its initial byte.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum MajorType {
+	/// An unsigned integer.
+	Unsigned = 0,
+	/// A negative integer, `-1` minus the argument.
+	Negative = 1,
+	/// A byte string.
+	Bytes = 2,
+	/// A UTF-8 text string.
+	Text = 3,
+	/// An array of data items.
+	Array = 4,
+	/// A map of key-value pairs of data items.
+	Map = 5,
+	/// A tag, followed by the tagged data item.
+	Tag = 6,
+	/// A simple value, float, or break.
+	Simple = 7,
+}
+
+impl From<u8> for MajorType {
+	/// Converts the high three bits of an initial byte.
+	fn from(value: u8) -> Self {
+		match value >> 5 {
+			0 => Self::Unsigned,
+			1 => Self::Negative,
+			2 => Self::Bytes,
+			3 => Self::Text,
+			4 => Self::Array,
+			5 => Self::Map,
+			6 => Self::Tag,
+			_ => Self::Simple
+		}
+	}
+}
+
+/// A data item header: its major type and argument.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Header {
+	/// The major type.
+	pub major_type: MajorType,
+	/// The argument: an integer value, length, or tag number; or for the simple
+	/// major type, the simple value or the bits of a half, single, or double
+	/// precision float. `None` if the length is indefinite, or for a break.
+	pub argument: Option<u64>,
+}
+
+/// A reader of CBOR data items from a [`BufSource`]. Byte and text strings borrow
+/// their bytes from the source buffer, across segment boundaries if needed; the
+/// buffer holds at most one string at a time, plus however much the source reads
+/// ahead.
+///
+/// Indefinite-length strings are read as a header with no argument, followed by
+/// definite-length chunks and a break, rather than by [`read_bytes`] or
+/// [`read_text`].
+///
+/// [`read_bytes`]: Self::read_bytes
+/// [`read_text`]: Self::read_text
+pub struct CborReader<'d, S: BufSource<'d, N>, const N: usize = SIZE> {
+	source: S,
+	max_len: usize,
+	/// The length of the last string and its header, consumed before reading the
+	/// next item.
+	consumed: usize,
+	__data: PhantomData<&'d ()>
+}
+
+impl<'d, S: BufSource<'d, N>, const N: usize> CborReader<'d, S, N> {
+	/// Creates a new reader, reading from `source`.
+	pub fn new(source: S) -> Self {
+		Self {
+			source,
+			max_len: MAX_LEN,
+			consumed: 0,
+			__data: PhantomData
+		}
+	}
+
+	/// Sets the maximum length of byte and text strings. Defaults to 16MiB.
+	pub fn with_max_len(mut self, value: usize) -> Self {
+		self.max_len = value;
+		self
+	}
+
+	/// Consumes the reader, returning the source. The last string read is consumed
+	/// from the source.
+	pub fn into_inner(mut self) -> Result<S> {
+		self.consume()?;
+		Ok(self.source)
+	}
+
+	/// Returns the major type of the next data item without reading it, or `None`
+	/// if the source ended.
+	pub fn peek_major_type(&mut self) -> Result<Option<MajorType>> {
+		self.consume()?;
+		self.source.request(1)?;
+		Ok(self.source.buf().get(0).map(MajorType::from))
+	}
+
+	/// Reads a data item header.
+	///
+	/// # Errors
+	///
+	/// Reserved additional information values, and indefinite lengths on integers
+	/// or tags, fail with an IO error of kind [`InvalidData`]. No bytes are consumed
+	/// if the header is invalid.
+	///
+	/// [`InvalidData`]: std::io::ErrorKind::InvalidData
+	pub fn read_header(&mut self) -> Result<Header> {
+		self.consume()?;
+		let (header, len) = self.peek_header()?;
+		self.source.skip(len)?;
+		Ok(header)
+	}
+
+	/// Reads an unsigned integer.
+	///
+	/// # Errors
+	///
+	/// Items of any other type fail with an IO error of kind [`InvalidData`], and
+	/// aren't consumed.
+	///
+	/// [`InvalidData`]: std::io::ErrorKind::InvalidData
+	pub fn read_uint(&mut self) -> Result<u64> {
+		self.read_definite(MajorType::Unsigned)
+	}
+
+	/// Reads an unsigned or negative integer.
+	///
+	/// # Errors
+	///
+	/// Items of any other type, or integers not fitting an [`i64`], fail with an IO
+	/// error of kind [`InvalidData`], and aren't consumed.
+	///
+	/// [`InvalidData`]: std::io::ErrorKind::InvalidData
+	pub fn read_int(&mut self) -> Result<i64> {
+		self.consume()?;
+		let (Header { major_type, argument }, len) = self.peek_header()?;
+		let value = match (major_type, argument.map(i64::try_from)) {
+			(MajorType::Unsigned, Some(Ok(value))) => value,
+			(MajorType::Negative, Some(Ok(value))) => -1 - value,
+			(MajorType::Unsigned | MajorType::Negative, _) => return Err(invalid_data("integer out of range")),
+			_ => return Err(invalid_data("expected an integer"))
+		};
+		self.source.skip(len)?;
+		Ok(value)
+	}
+
+	/// Reads a definite-length byte string. The returned bytes borrow from the
+	/// source buffer until the next call.
+	///
+	/// # Errors
+	///
+	/// Items of any other type, and indefinite-length strings or strings longer
+	/// than the maximum length, fail with an IO error of kind [`InvalidData`], and
+	/// aren't consumed. If the source ends within the string, an end-of-stream
+	/// error is returned.
+	///
+	/// [`InvalidData`]: std::io::ErrorKind::InvalidData
+	pub fn read_bytes(&mut self) -> Result<ByteStr<'_>> {
+		let range = self.peek_string(MajorType::Bytes)?;
+		self.consumed = range.end;
+		Ok(self.source.buf().as_byte_str().range(range))
+	}
+
+	/// Reads a definite-length text string, checking that it's valid UTF-8. The
+	/// returned string borrows from the source buffer until the next call, with its
+	/// decoded UTF-8 cached.
+	///
+	/// # Errors
+	///
+	/// In addition to the errors returned by [`read_bytes`], strings that aren't
+	/// valid UTF-8 fail with an IO error of kind [`InvalidData`], and aren't
+	/// consumed.
+	///
+	/// [`read_bytes`]: Self::read_bytes
+	/// [`InvalidData`]: std::io::ErrorKind::InvalidData
+	pub fn read_text(&mut self) -> Result<ByteStr<'_>> {
+		let range = self.peek_string(MajorType::Text)?;
+		let mut str = self.source.buf().as_byte_str().range(range.clone());
+		if str.cache_utf8().is_err() {
+			return Err(invalid_data("invalid UTF-8 text string"))
+		}
+		self.consumed = range.end;
+		Ok(str)
+	}
+
+	/// Reads an array header, returning its length, or `None` if the length is
+	/// indefinite. Indefinite-length arrays end with a break.
+	///
+	/// # Errors
+	///
+	/// Items of any other type fail with an IO error of kind [`InvalidData`], and
+	/// aren't consumed.
+	///
+	/// [`InvalidData`]: std::io::ErrorKind::InvalidData
+	pub fn read_array_len(&mut self) -> Result<Option<u64>> {
+		self.read_len(MajorType::Array)
+	}
+
+	/// Reads a map header, returning its number of key-value pairs, or `None` if the
+	/// length is indefinite. Indefinite-length maps end with a break.
+	///
+	/// # Errors
+	///
+	/// Items of any other type fail with an IO error of kind [`InvalidData`], and
+	/// aren't consumed.
+	///
+	/// [`InvalidData`]: std::io::ErrorKind::InvalidData
+	pub fn read_map_len(&mut self) -> Result<Option<u64>> {
+		self.read_len(MajorType::Map)
+	}
+
+	/// Reads a tag number. The tagged data item follows.
+	///
+	/// # Errors
+	///
+	/// Items of any other type fail with an IO error of kind [`InvalidData`], and
+	/// aren't consumed.
+	///
+	/// [`InvalidData`]: std::io::ErrorKind::InvalidData
+	pub fn read_tag(&mut self) -> Result<u64> {
+		self.read_definite(MajorType::Tag)
+	}
+
+	fn consume(&mut self) -> Result {
+		if self.consumed > 0 {
+			self.source.skip(self.consumed)?;
+			self.consumed = 0;
+		}
+		Ok(())
+	}
+
+	/// Decodes the header at the front of the buffer, returning it and its encoded
+	/// length without consuming it.
+	fn peek_header(&mut self) -> Result<(Header, usize)> {
+		self.source.require(1)?;
+		let Some(initial) = self.source.buf().get(0) else {
+			unreachable!("one byte should be available")
+		};
+		let major_type = MajorType::from(initial);
+		let (argument, len) = match initial & 0x1F {
+			info @ 0..=23 => (Some(info as u64), 1),
+			info @ 24..=27 => {
+				let width = 1 << (info - 24);
+				self.source.require(1 + width)?;
+				let mut bytes = [0; 8];
+				self.source.buf().read_slice_exact_at(1, &mut bytes[8 - width..])?;
+				(Some(u64::from_be_bytes(bytes)), 1 + width)
+			}
+			INDEFINITE => match major_type {
+				MajorType::Unsigned | MajorType::Negative | MajorType::Tag =>
+					return Err(invalid_data("unexpected indefinite length")),
+				_ => (None, 1)
+			},
+			_ => return Err(invalid_data("reserved additional information"))
+		};
+		Ok((Header { major_type, argument }, len))
+	}
+
+	/// Reads the argument of a header with a definite argument and the expected
+	/// major type.
+	fn read_definite(&mut self, expected: MajorType) -> Result<u64> {
+		match self.read_len(expected)? {
+			Some(argument) => Ok(argument),
+			None => unreachable!("integers and tags should have a definite argument")
+		}
+	}
+
+	/// Reads the argument of a header with the expected major type.
+	fn read_len(&mut self, expected: MajorType) -> Result<Option<u64>> {
+		self.consume()?;
+		let (Header { major_type, argument }, len) = self.peek_header()?;
+		if major_type != expected {
+			return Err(invalid_data("unexpected major type"))
+		}
+		self.source.skip(len)?;
+		Ok(argument)
+	}
+
+	/// Decodes a definite-length string with the expected major type, returning
+	/// the range of its bytes in the buffer, after its header, without consuming
+	/// it.
+	fn peek_string(&mut self, expected: MajorType) -> Result<Range<usize>> {
+		self.consume()?;
+		let (Header { major_type, argument }, header_len) = self.peek_header()?;
+		if major_type != expected {
+			return Err(invalid_data("unexpected major type"))
+		}
+		let Some(len) = argument else {
+			return Err(invalid_data("unexpected indefinite length"))
+		};
+		let len = usize::try_from(len).unwrap_or(usize::MAX);
+		if len > self.max_len {
+			return Err(invalid_data("string too long"))
+		}
+
+		let end = header_len.saturating_add(len);
+		self.source.require(end)?;
+		Ok(header_len..end)
+	}
+}
+
+/// Writes a data item header to `sink`, with the argument in its smallest
+/// encoding.
+pub fn write_header<'d, const N: usize>(sink: &mut impl BufSink<'d, N>, major_type: MajorType, argument: u64) -> Result {
+	let major = (major_type as u8) << 5;
+	match argument {
+		0..=23 => sink.write_u8(major | argument as u8),
+		24..=0xFF => {
+			sink.write_u8(major | 24)?;
+			sink.write_u8(argument as u8)
+		}
+		0x100..=0xFFFF => {
+			sink.write_u8(major | 25)?;
+			sink.write_u16(argument as u16)
+		}
+		0x10000..=0xFFFF_FFFF => {
+			sink.write_u8(major | 26)?;
+			sink.write_u32(argument as u32)
+		}
+		_ => {
+			sink.write_u8(major | 27)?;
+			sink.write_u64(argument)
+		}
+	}
+}
+
+/// Writes an unsigned integer to `sink`.
+pub fn write_uint<'d, const N: usize>(sink: &mut impl BufSink<'d, N>, value: u64) -> Result {
+	write_header(sink, MajorType::Unsigned, value)
+}
+
+/// Writes a signed integer to `sink`, as an unsigned integer if non-negative.
+pub fn write_int<'d, const N: usize>(sink: &mut impl BufSink<'d, N>, value: i64) -> Result {
+	if value < 0 {
+		write_header(sink, MajorType::Negative, !value as u64)
+	} else {
+		write_header(sink, MajorType::Unsigned, value as u64)
+	}
+}
+
+/// Writes a definite-length byte string to `sink`.
+pub fn write_bytes<'d, const N: usize>(sink: &mut impl BufSink<'d, N>, value: &[u8]) -> Result {
+	write_header(sink, MajorType::Bytes, value.len() as u64)?;
+	sink.write_from_slice(value)?;
+	Ok(())
+}
+
+/// Writes a definite-length text string to `sink`.
+pub fn write_text<'d, const N: usize>(sink: &mut impl BufSink<'d, N>, value: &str) -> Result {
+	write_header(sink, MajorType::Text, value.len() as u64)?;
+	sink.write_utf8(value)?;
+	Ok(())
+}
+
+/// Writes a definite-length array header to `sink`, to be followed by `len` data
+/// items.
+pub fn write_array_len<'d, const N: usize>(sink: &mut impl BufSink<'d, N>, len: u64) -> Result {
+	write_header(sink, MajorType::Array, len)
+}
+
+/// Writes a definite-length map header to `sink`, to be followed by `len`
+/// key-value pairs.
+pub fn write_map_len<'d, const N: usize>(sink: &mut impl BufSink<'d, N>, len: u64) -> Result {
+	write_header(sink, MajorType::Map, len)
+}
+
+/// Writes a tag number to `sink`, to be followed by the tagged data item.
+pub fn write_tag<'d, const N: usize>(sink: &mut impl BufSink<'d, N>, tag: u64) -> Result {
+	write_header(sink, MajorType::Tag, tag)
+}
+
+/// Writes an indefinite-length header for a byte string, text string, array, or
+/// map to `sink`, to be followed by its chunks or items and a [break].
+///
+/// # Panics
+///
+/// Panics if `major_type` is an integer, tag, or simple type, which can't have
+/// indefinite lengths.
+///
+/// [break]: write_break
+pub fn write_indefinite<'d, const N: usize>(sink: &mut impl BufSink<'d, N>, major_type: MajorType) -> Result {
+	assert!(
+		matches!(major_type, MajorType::Bytes | MajorType::Text | MajorType::Array | MajorType::Map),
+		"{major_type:?} can't have an indefinite length"
+	);
+	sink.write_u8((major_type as u8) << 5 | INDEFINITE)
+}
+
+/// Writes a break to `sink`, ending an indefinite-length item.
+pub fn write_break<'d, const N: usize>(sink: &mut impl BufSink<'d, N>) -> Result {
+	sink.write_u8((MajorType::Simple as u8) << 5 | INDEFINITE)
+}
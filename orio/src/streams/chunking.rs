@@ -0,0 +1,163 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Content-defined chunking with a Gear rolling hash, normalized as in [FastCDC].
+//! Chunk boundaries are chosen by the data itself rather than by its position, so
+//! they're stable regardless of how the inner source splits its reads, and an
+//! insertion or deletion only changes the chunks around it. This makes chunks
+//! suitable as units of deduplication.
+//!
+//! [FastCDC]: https://www.usenix.org/conference/atc16/technical-sessions/presentation/xia
+
+use crate::{Buffer, BufferResult, ResultContext, SIZE};
+use crate::pool::{DefaultPoolContainer, Pool};
+use crate::StreamContext::Read;
+use super::{Result, Sink, Source};
+
+/// Random values mixed into the hash for each byte value.
+const GEAR: [u64; 256] = gear_table();
+
+/// Generates the gear table with SplitMix64. The seed is fixed, so boundaries
+/// are stable across runs and versions.
+const fn gear_table() -> [u64; 256] {
+	let mut table = [0; 256];
+	let mut state = 0x6F72_696F_6364_6321u64;
+	let mut i = 0;
+	while i < table.len() {
+		state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+		let mut z = state;
+		z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+		table[i] = z ^ (z >> 31);
+		i += 1;
+	}
+	table
+}
+
+/// Returns a mask of the `bits` highest bits. The high bits of a gear hash are
+/// influenced by the most bytes, so they make the best boundary conditions.
+const fn mask(bits: u32) -> u64 {
+	if bits == 0 {
+		0
+	} else {
+		!0 << (64 - bits)
+	}
+}
+
+/// A reader of content-defined chunks from a source.
+pub struct ChunkingSource<
+	'd,
+	S: Source<'d, N>,
+	const N: usize = SIZE,
+	P: Pool<N> = DefaultPoolContainer
+> {
+	source: S,
+	buffer: Buffer<'d, N, P>,
+	min_size: usize,
+	avg_size: usize,
+	max_size: usize,
+	/// The boundary mask before the average size is reached, harder to match.
+	small_mask: u64,
+	/// The boundary mask after the average size is reached, easier to match.
+	large_mask: u64,
+}
+
+impl<'d, S: Source<'d, N>, const N: usize, P: Pool<N>> ChunkingSource<'d, S, N, P> {
+	/// Creates a new chunking source reading from `source`, with a minimum chunk
+	/// size of 2KiB, an average of 8KiB, and a maximum of 64KiB.
+	pub fn new(source: S) -> Self {
+		Self::with_sizes(source, 2 * 1024, 8 * 1024, 64 * 1024)
+	}
+
+	/// Creates a new chunking source reading from `source`, with minimum, average,
+	/// and maximum chunk sizes.
+	///
+	/// # Panics
+	///
+	/// Panics if `avg` isn't a power of two, if `min` is zero, or if the sizes
+	/// aren't in increasing order.
+	pub fn with_sizes(source: S, min: usize, avg: usize, max: usize) -> Self {
+		assert!(avg.is_power_of_two(), "average chunk size should be a power of two");
+		assert!(min > 0 && min <= avg && avg <= max, "chunk sizes should be in increasing order");
+		let bits = avg.trailing_zeros();
+		Self {
+			source,
+			buffer: Buffer::default(),
+			min_size: min,
+			avg_size: avg,
+			max_size: max,
+			small_mask: mask(bits + 1),
+			large_mask: mask(bits.saturating_sub(1)),
+		}
+	}
+
+	/// Returns the minimum chunk size. Only the last chunk may be smaller.
+	#[inline]
+	pub fn min_size(&self) -> usize { self.min_size }
+
+	/// Returns the average chunk size.
+	#[inline]
+	pub fn avg_size(&self) -> usize { self.avg_size }
+
+	/// Returns the maximum chunk size.
+	#[inline]
+	pub fn max_size(&self) -> usize { self.max_size }
+
+	/// Returns a reference to the inner source.
+	#[inline]
+	pub fn source(&self) -> &S { &self.source }
+
+	/// Returns a mutable reference to the inner source. Reads from the inner source
+	/// directly skip chunking.
+	#[inline]
+	pub fn source_mut(&mut self) -> &mut S { &mut self.source }
+
+	/// Consumes the chunking source, returning the inner source. Any data read
+	/// from the source but not yet returned as a chunk is discarded.
+	pub fn into_inner(self) -> S {
+		self.source
+	}
+
+	/// Reads the next chunk, or `None` if the source is exhausted.
+	pub fn next_chunk(&mut self) -> Result<Option<Buffer<'d, N, P>>> {
+		self.fill_window().context(Read)?;
+		if self.buffer.is_empty() {
+			return Ok(None)
+		}
+
+		let len = self.find_boundary();
+		let mut chunk = Buffer::default();
+		chunk.drain(&mut self.buffer, len).context(Read)?;
+		Ok(Some(chunk))
+	}
+
+	/// Fills the buffer up to the maximum chunk size, or until the source is
+	/// exhausted. Empty reads don't end the window, so boundaries don't depend on
+	/// how the source splits its reads.
+	fn fill_window(&mut self) -> BufferResult {
+		while self.buffer.count() < self.max_size && !self.source.is_eos() {
+			let count = self.max_size - self.buffer.count();
+			self.source.fill(&mut self.buffer, count)?;
+		}
+		Ok(())
+	}
+
+	/// Returns the length of the chunk at the start of the buffer.
+	fn find_boundary(&self) -> usize {
+		let count = self.buffer.count().min(self.max_size);
+		if count <= self.min_size {
+			return count
+		}
+
+		let normal = self.avg_size.min(count);
+		let mut hash = 0u64;
+		let bytes = self.buffer.as_byte_str();
+		for (i, &byte) in bytes.bytes().enumerate().take(count).skip(self.min_size) {
+			hash = (hash << 1).wrapping_add(GEAR[byte as usize]);
+			let mask = if i < normal { self.small_mask } else { self.large_mask };
+			if hash & mask == 0 {
+				return i + 1
+			}
+		}
+		count
+	}
+}
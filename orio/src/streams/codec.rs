@@ -0,0 +1,539 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Streaming base64 and hex encoders and decoders. [`EncoderSink`] encodes data
+//! drained into it before writing it to its inner sink, and [`DecoderSource`]
+//! decodes data read from its inner source. Partial groups are carried across
+//! segment boundaries and between calls, so data is encoded and decoded the same
+//! as equivalent contiguous data would be, without materializing the payload.
+
+use std::io;
+use arrayvec::ArrayVec;
+use base64::engine::GeneralPurpose;
+use base64::prelude::{BASE64_STANDARD, BASE64_STANDARD_NO_PAD, BASE64_URL_SAFE, BASE64_URL_SAFE_NO_PAD};
+use crate::{Buffer, BufferResult, DecodeError, ResultContext, SIZE};
+use crate::byte_str::{STANDARD_PAD_INDIFFERENT, URL_SAFE_PAD_INDIFFERENT};
+use crate::BufferContext::{Drain, Fill};
+use crate::pool::{DefaultPoolContainer, Pool};
+use super::{BufSink, Result, Sink, Source, Stream};
+
+mod sealed {
+	use std::io;
+
+	pub trait Codec {
+		/// The number of bytes in a decoded group.
+		const DECODED_WIDTH: usize;
+		/// The number of characters in an encoded group.
+		const ENCODED_WIDTH: usize;
+
+		/// Encodes `input`, appending the encoded characters to `output`.
+		fn encode(&self, input: &[u8], output: &mut String);
+		/// Decodes `input`, appending the decoded bytes to `output`.
+		fn decode(&self, input: &[u8], output: &mut Vec<u8>) -> io::Result<()>;
+	}
+}
+
+/// A binary-to-text encoding usable by [`EncoderSink`] and [`DecoderSource`].
+pub trait Codec: sealed::Codec { }
+impl<C: sealed::Codec> Codec for C { }
+
+/// The base64 encoding, using a [`base64::Engine`] to encode and decode.
+#[derive(Clone, Debug)]
+pub struct Base64<E: base64::Engine = GeneralPurpose>(pub E);
+
+/// The hex encoding. Encodes to either lowercase or uppercase digits, and decodes
+/// from both.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Hex {
+	/// Encodes to lowercase hex digits.
+	#[default]
+	Lower,
+	/// Encodes to uppercase hex digits.
+	Upper
+}
+
+impl<E: base64::Engine> sealed::Codec for Base64<E> {
+	const DECODED_WIDTH: usize = 3;
+	const ENCODED_WIDTH: usize = 4;
+
+	fn encode(&self, input: &[u8], output: &mut String) {
+		self.0.encode_string(input, output);
+	}
+
+	fn decode(&self, input: &[u8], output: &mut Vec<u8>) -> io::Result<()> {
+		self.0.decode_vec(input, output).map_err(|err|
+			io::Error::new(io::ErrorKind::InvalidData, DecodeError::from(err))
+		)
+	}
+}
+
+impl sealed::Codec for Hex {
+	const DECODED_WIDTH: usize = 1;
+	const ENCODED_WIDTH: usize = 2;
+
+	fn encode(&self, input: &[u8], output: &mut String) {
+		output.push_str(&match self {
+			Self::Lower => base16ct::lower::encode_string(input),
+			Self::Upper => base16ct::upper::encode_string(input),
+		});
+	}
+
+	fn decode(&self, input: &[u8], output: &mut Vec<u8>) -> io::Result<()> {
+		let decoded = crate::byte_str::decode_hex(input).map_err(|err|
+			io::Error::new(io::ErrorKind::InvalidData, err)
+		)?;
+		output.extend_from_slice(&decoded);
+		Ok(())
+	}
+}
+
+/// A [`Sink`] encoding base64 into its inner sink.
+pub type Base64Sink<'d, S, const N: usize = SIZE, P = DefaultPoolContainer> = EncoderSink<'d, Base64, S, N, P>;
+/// A [`Sink`] encoding hex into its inner sink.
+pub type HexSink<'d, S, const N: usize = SIZE, P = DefaultPoolContainer> = EncoderSink<'d, Hex, S, N, P>;
+/// A [`Source`] decoding base64 from its inner source.
+pub type Base64Source<'d, S, const N: usize = SIZE, P = DefaultPoolContainer> = DecoderSource<'d, Base64, S, N, P>;
+/// A [`Source`] decoding hex from its inner source.
+pub type HexSource<'d, S, const N: usize = SIZE, P = DefaultPoolContainer> = DecoderSource<'d, Hex, S, N, P>;
+
+/// A [`Sink`] that encodes data written to it, writing the encoded characters to
+/// its inner sink. Incomplete groups are held until more data is written, or the
+/// sink is closed or [finished](Self::finish).
+pub struct EncoderSink<
+	'd,
+	C: Codec,
+	S: Sink<'d, N>,
+	const N: usize = SIZE,
+	P: Pool<N> = DefaultPoolContainer
+> {
+	codec: C,
+	sink: Option<S>,
+	buffer: Buffer<'d, N, P>,
+	encoded: String,
+	partial: ArrayVec<u8, 4>,
+	closed: bool,
+}
+
+impl<'d, C: Codec, S: Sink<'d, N>, const N: usize, P: Pool<N>> EncoderSink<'d, C, S, N, P> {
+	/// Creates a new encoder sink, encoding data with `codec` into `sink`.
+	pub fn new(codec: C, sink: S) -> Self {
+		let closed = sink.is_closed();
+		Self {
+			codec,
+			sink: Some(sink),
+			buffer: Buffer::default(),
+			encoded: String::new(),
+			partial: ArrayVec::new(),
+			closed,
+		}
+	}
+
+	/// Returns a reference to the codec.
+	#[inline]
+	pub fn codec(&self) -> &C { &self.codec }
+
+	/// Returns a reference to the inner sink.
+	pub fn sink(&self) -> &S {
+		unsafe {
+			// Safety: option will only be None if into_inner is called, but this
+			// consumes and drops self, making it impossible to ever have a
+			// reference (except on drop, which is guarded).
+			self.sink.as_ref().unwrap_unchecked()
+		}
+	}
+
+	/// Returns a mutable reference to the inner sink, bypassing encoding.
+	pub fn sink_mut(&mut self) -> &mut S {
+		unsafe {
+			// Safety: see Self::sink.
+			self.sink.as_mut().unwrap_unchecked()
+		}
+	}
+
+	/// Consumes the encoder sink without closing, returning the inner sink. Any
+	/// incomplete group or encoded data not yet written to the sink is discarded;
+	/// use [`finish`](Self::finish) to write it first.
+	pub fn into_inner(mut self) -> S {
+		unsafe {
+			// Safety: option will only be None if this method was already called,
+			// which is impossible because we consume self.
+			self.sink.take().unwrap_unchecked()
+		}
+	}
+
+	/// Encodes any incomplete group as the final group, writes all encoded data
+	/// to the inner sink and flushes it, then returns the inner sink without
+	/// closing it.
+	pub fn finish(mut self) -> Result<S> {
+		self.check_open(Drain)?;
+		self.encode_final();
+		self.write_encoded()?;
+		self.sink_mut().flush()?;
+		Ok(self.into_inner())
+	}
+
+	/// Encodes whole groups from `data`, holding the remainder until the next call.
+	fn encode(&mut self, mut data: &[u8]) {
+		let width = C::DECODED_WIDTH;
+
+		if !self.partial.is_empty() {
+			let len = (width - self.partial.len()).min(data.len());
+			self.partial.extend(data[..len].iter().copied());
+			data = &data[len..];
+
+			if self.partial.len() < width {
+				return
+			}
+
+			self.codec.encode(&self.partial, &mut self.encoded);
+			self.partial.clear();
+		}
+
+		let clean_len = data.len() / width * width;
+		self.codec.encode(&data[..clean_len], &mut self.encoded);
+		self.partial.extend(data[clean_len..].iter().copied());
+	}
+
+	/// Encodes the incomplete group, if any.
+	fn encode_final(&mut self) {
+		if !self.partial.is_empty() {
+			self.codec.encode(&self.partial, &mut self.encoded);
+			self.partial.clear();
+		}
+	}
+
+	/// Writes encoded data to the inner sink.
+	fn write_encoded(&mut self) -> BufferResult {
+		if !self.encoded.is_empty() {
+			self.buffer.write_from_slice(self.encoded.as_bytes())?;
+			self.encoded.clear();
+		}
+
+		let Self { sink, buffer, .. } = self;
+		let sink = unsafe {
+			// Safety: see Self::sink.
+			sink.as_mut().unwrap_unchecked()
+		};
+		sink.drain_all(buffer)?;
+		Ok(())
+	}
+}
+
+impl<'d, S: Sink<'d, N>, const N: usize> EncoderSink<'d, Base64, S, N, DefaultPoolContainer>
+where DefaultPoolContainer: Pool<N> {
+	/// Creates a new encoder sink, encoding standard base64 without padding into
+	/// `sink`.
+	#[inline]
+	pub fn base64(sink: S) -> Self {
+		Self::new(Base64(BASE64_STANDARD_NO_PAD), sink)
+	}
+
+	/// Creates a new encoder sink, encoding URL-safe base64 without padding into
+	/// `sink`.
+	#[inline]
+	pub fn base64_url(sink: S) -> Self {
+		Self::new(Base64(BASE64_URL_SAFE_NO_PAD), sink)
+	}
+
+	/// Creates a new encoder sink, encoding standard base64 with padding into
+	/// `sink`.
+	#[inline]
+	pub fn base64_padded(sink: S) -> Self {
+		Self::new(Base64(BASE64_STANDARD), sink)
+	}
+
+	/// Creates a new encoder sink, encoding URL-safe base64 with padding into
+	/// `sink`.
+	#[inline]
+	pub fn base64_url_padded(sink: S) -> Self {
+		Self::new(Base64(BASE64_URL_SAFE), sink)
+	}
+}
+
+impl<'d, S: Sink<'d, N>, const N: usize> EncoderSink<'d, Hex, S, N, DefaultPoolContainer>
+where DefaultPoolContainer: Pool<N> {
+	/// Creates a new encoder sink, encoding lowercase hex into `sink`.
+	#[inline]
+	pub fn hex_lower(sink: S) -> Self {
+		Self::new(Hex::Lower, sink)
+	}
+
+	/// Creates a new encoder sink, encoding uppercase hex into `sink`.
+	#[inline]
+	pub fn hex_upper(sink: S) -> Self {
+		Self::new(Hex::Upper, sink)
+	}
+}
+
+impl<'d, C: Codec, S: Sink<'d, N>, const N: usize, P: Pool<N>> Stream<N> for EncoderSink<'d, C, S, N, P> {
+	#[inline]
+	fn is_closed(&self) -> bool { self.closed }
+
+	/// Encodes any incomplete group as the final group, writes all encoded data,
+	/// then closes the inner sink.
+	fn close(&mut self) -> Result {
+		if !self.closed {
+			self.closed = true;
+			self.encode_final();
+			let write_result = self.write_encoded();
+			let sink_result = self.sink_mut().close();
+			write_result?;
+			sink_result?;
+		}
+		Ok(())
+	}
+}
+
+impl<'d, C: Codec, S: Sink<'d, N>, const N: usize, P: Pool<N>> Sink<'d, N> for EncoderSink<'d, C, S, N, P> {
+	/// Encodes up to `count` bytes from `source`, writing the encoded data to the
+	/// inner sink. Returns the number of bytes consumed from `source`, including
+	/// those held in an incomplete group.
+	fn drain(&mut self, source: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		self.check_open(Drain)?;
+		let count = count.min(source.count());
+		for slice in source.as_byte_str().range(..count).slices() {
+			self.encode(slice);
+		}
+		source.skip(count);
+		self.write_encoded()?;
+		Ok(count)
+	}
+
+	/// Writes encoded data to the inner sink and flushes it. Incomplete groups
+	/// are not written, as they can only be encoded at the end of the data.
+	fn flush(&mut self) -> Result {
+		self.check_open(Drain)?;
+		self.write_encoded()?;
+		self.sink_mut().flush()
+	}
+}
+
+impl<'d, C: Codec, S: Sink<'d, N>, const N: usize, P: Pool<N>> Drop for EncoderSink<'d, C, S, N, P> {
+	fn drop(&mut self) {
+		// If into_inner was called, closing would cause a seg fault.
+		if self.sink.is_some() {
+			let _ = self.close();
+		}
+	}
+}
+
+/// A [`Source`] that decodes data read from its inner source. ASCII whitespace
+/// between encoded characters, such as line breaks, is ignored.
+pub struct DecoderSource<
+	'd,
+	C: Codec,
+	S: Source<'d, N>,
+	const N: usize = SIZE,
+	P: Pool<N> = DefaultPoolContainer
+> {
+	codec: C,
+	source: Option<S>,
+	buffer: Buffer<'d, N, P>,
+	staged: Vec<u8>,
+	decoded: Vec<u8>,
+	closed: bool,
+}
+
+impl<'d, C: Codec, S: Source<'d, N>, const N: usize, P: Pool<N>> DecoderSource<'d, C, S, N, P> {
+	/// Creates a new decoder source, decoding data read from `source` with `codec`.
+	pub fn new(codec: C, source: S) -> Self {
+		let closed = source.is_closed();
+		Self {
+			codec,
+			source: Some(source),
+			buffer: Buffer::default(),
+			staged: Vec::new(),
+			decoded: Vec::new(),
+			closed,
+		}
+	}
+
+	/// Returns a reference to the codec.
+	#[inline]
+	pub fn codec(&self) -> &C { &self.codec }
+
+	/// Returns a reference to the inner source.
+	pub fn source(&self) -> &S {
+		unsafe {
+			// Safety: option will only be None if into_inner is called, but this
+			// consumes and drops self, making it impossible to ever have a
+			// reference (except on drop, which is guarded).
+			self.source.as_ref().unwrap_unchecked()
+		}
+	}
+
+	/// Returns a mutable reference to the inner source, bypassing decoding.
+	pub fn source_mut(&mut self) -> &mut S {
+		unsafe {
+			// Safety: see Self::source.
+			self.source.as_mut().unwrap_unchecked()
+		}
+	}
+
+	/// Consumes the decoder source without closing, returning the inner source.
+	/// Any data read from the source but not yet decoded is discarded.
+	pub fn into_inner(mut self) -> S {
+		unsafe {
+			// Safety: option will only be None if this method was already called,
+			// which is impossible because we consume self.
+			self.source.take().unwrap_unchecked()
+		}
+	}
+
+	/// Returns `true` if all encoded data has been read from the inner source.
+	fn is_input_exhausted(&self) -> bool {
+		self.buffer.is_empty() && self.source().is_eos()
+	}
+
+	/// Decodes up to `count` bytes into `sink`.
+	fn decode_into(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		let mut read = self.take_decoded(sink, count)?;
+		while read < count {
+			let needed = (count - read).div_ceil(C::DECODED_WIDTH) * C::ENCODED_WIDTH;
+			let staged = self.stage(needed)?;
+			let decoded = self.decode_staged()?;
+			if staged == 0 && decoded == 0 {
+				break
+			}
+
+			read += self.take_decoded(sink, count - read)?;
+		}
+		Ok(read)
+	}
+
+	/// Moves encoded characters from the buffer into the staging area until
+	/// `needed` characters are staged, reading from the inner source as required.
+	/// Returns the number of bytes consumed.
+	fn stage(&mut self, needed: usize) -> BufferResult<usize> {
+		let Self { source, buffer, staged, .. } = self;
+		let source = unsafe {
+			// Safety: see Self::source.
+			source.as_mut().unwrap_unchecked()
+		};
+
+		let mut total = 0;
+		while staged.len() < needed {
+			if buffer.is_empty() &&
+				(source.is_eos() || source.fill(buffer, needed - staged.len())? == 0) {
+				break
+			}
+
+			let mut consumed = 0;
+			for &byte in buffer.as_byte_str().bytes() {
+				if staged.len() == needed {
+					break
+				}
+
+				consumed += 1;
+				if !byte.is_ascii_whitespace() {
+					staged.push(byte);
+				}
+			}
+			buffer.skip(consumed);
+			total += consumed;
+		}
+		Ok(total)
+	}
+
+	/// Decodes whole staged groups, or all staged characters if the inner source
+	/// is exhausted. Returns the number of bytes decoded.
+	fn decode_staged(&mut self) -> BufferResult<usize> {
+		let len = if self.is_input_exhausted() {
+			self.staged.len()
+		} else {
+			self.staged.len() / C::ENCODED_WIDTH * C::ENCODED_WIDTH
+		};
+
+		if len == 0 {
+			return Ok(0)
+		}
+
+		let start = self.decoded.len();
+		self.codec
+			.decode(&self.staged[..len], &mut self.decoded)
+			.context(Fill)?;
+		self.staged.drain(..len);
+		Ok(self.decoded.len() - start)
+	}
+
+	/// Writes up to `count` decoded bytes into `sink`.
+	fn take_decoded(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		let count = count.min(self.decoded.len());
+		if count > 0 {
+			sink.write_from_slice(&self.decoded[..count])?;
+			self.decoded.drain(..count);
+		}
+		Ok(count)
+	}
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> DecoderSource<'d, Base64, S, N, DefaultPoolContainer>
+where DefaultPoolContainer: Pool<N> {
+	/// Creates a new decoder source, decoding standard base64 from `source`. Padding
+	/// is accepted but not required.
+	#[inline]
+	pub fn base64(source: S) -> Self {
+		Self::new(Base64(STANDARD_PAD_INDIFFERENT), source)
+	}
+
+	/// Creates a new decoder source, decoding URL-safe base64 from `source`. Padding
+	/// is accepted but not required.
+	#[inline]
+	pub fn base64_url(source: S) -> Self {
+		Self::new(Base64(URL_SAFE_PAD_INDIFFERENT), source)
+	}
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> DecoderSource<'d, Hex, S, N, DefaultPoolContainer>
+where DefaultPoolContainer: Pool<N> {
+	/// Creates a new decoder source, decoding hex from `source`.
+	#[inline]
+	pub fn hex(source: S) -> Self {
+		Self::new(Hex::default(), source)
+	}
+}
+
+impl<'d, C: Codec, S: Source<'d, N>, const N: usize, P: Pool<N>> Stream<N> for DecoderSource<'d, C, S, N, P> {
+	#[inline]
+	fn is_closed(&self) -> bool { self.closed }
+
+	fn close(&mut self) -> Result {
+		if !self.closed {
+			self.closed = true;
+			let buf_result = self.buffer.close();
+			let src_result = self.source_mut().close();
+			buf_result?;
+			src_result?;
+		}
+		Ok(())
+	}
+}
+
+impl<'d, C: Codec, S: Source<'d, N>, const N: usize, P: Pool<N>> Source<'d, N> for DecoderSource<'d, C, S, N, P> {
+	fn is_eos(&self) -> bool {
+		self.decoded.is_empty() &&
+		self.staged.is_empty() &&
+		self.is_input_exhausted()
+	}
+
+	/// Decodes up to `count` bytes read from the inner source into `sink`.
+	///
+	/// # Errors
+	///
+	/// If the data is not validly encoded, an IO error of kind [`InvalidData`] is
+	/// returned.
+	///
+	/// [`InvalidData`]: io::ErrorKind::InvalidData
+	fn fill(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		self.check_open(Fill)?;
+		self.decode_into(sink, count)
+	}
+}
+
+impl<'d, C: Codec, S: Source<'d, N>, const N: usize, P: Pool<N>> Drop for DecoderSource<'d, C, S, N, P> {
+	fn drop(&mut self) {
+		// If into_inner was called, closing would cause a seg fault.
+		if self.source.is_some() {
+			let _ = self.close();
+		}
+	}
+}
@@ -0,0 +1,220 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::marker::PhantomData;
+use crate::{Buffer, BufferResult, SIZE};
+use crate::pool::Pool;
+use super::{Result, Sink, Source, Stream};
+
+/// A [`Source`] counting the number of bytes read from its inner source. The count
+/// includes bytes read before an error, and can be used to enforce message size
+/// limits or report progress.
+pub struct CountingSource<'d, S: Source<'d, N>, const N: usize = SIZE> {
+	source: Option<S>,
+	count: usize,
+	__data: PhantomData<&'d ()>
+}
+
+/// A [`Sink`] counting the number of bytes written to its inner sink. The count
+/// includes bytes written before an error, and can be used to enforce message size
+/// limits or report progress.
+pub struct CountingSink<'d, S: Sink<'d, N>, const N: usize = SIZE> {
+	sink: Option<S>,
+	count: usize,
+	__data: PhantomData<&'d ()>
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> CountingSource<'d, S, N> {
+	/// Creates a new counting source, starting from a count of zero.
+	pub fn new(source: S) -> Self {
+		Self {
+			source: Some(source),
+			count: 0,
+			__data: PhantomData
+		}
+	}
+
+	/// Returns the number of bytes read.
+	#[inline]
+	pub fn count(&self) -> usize { self.count }
+
+	/// Resets the number of bytes read to zero, returning the previous count.
+	#[inline]
+	pub fn reset_count(&mut self) -> usize {
+		std::mem::take(&mut self.count)
+	}
+
+	/// Returns a reference to the inner source.
+	pub fn source(&self) -> &S {
+		unsafe {
+			// Safety: option will only be None if into_inner is called, but this
+			// consumes and drops self, making it impossible to ever have a
+			// reference (except on drop, which is guarded).
+			self.source.as_ref().unwrap_unchecked()
+		}
+	}
+
+	/// Returns a mutable reference to the inner source. Reads from the inner
+	/// source directly are not counted.
+	pub fn source_mut(&mut self) -> &mut S {
+		unsafe {
+			// Safety: see `source`
+			self.source.as_mut().unwrap_unchecked()
+		}
+	}
+
+	/// Consumes the counting source, returning the inner source.
+	pub fn into_inner(mut self) -> S {
+		unsafe {
+			// Safety: option will only be None if this method was already called,
+			// which is impossible because we consume self.
+			self.source.take().unwrap_unchecked()
+		}
+	}
+
+	/// Calls `fill` on the inner source, counting the bytes it adds to `sink`.
+	fn counted<P: Pool<N>>(
+		&mut self,
+		sink: &mut Buffer<'d, N, P>,
+		fill: impl FnOnce(&mut S, &mut Buffer<'d, N, P>) -> BufferResult<usize>
+	) -> BufferResult<usize> {
+		let initial = sink.count();
+		let result = fill(self.source_mut(), sink);
+		self.count += sink.count().saturating_sub(initial);
+		result
+	}
+}
+
+impl<'d, S: Sink<'d, N>, const N: usize> CountingSink<'d, S, N> {
+	/// Creates a new counting sink, starting from a count of zero.
+	pub fn new(sink: S) -> Self {
+		Self {
+			sink: Some(sink),
+			count: 0,
+			__data: PhantomData
+		}
+	}
+
+	/// Returns the number of bytes written.
+	#[inline]
+	pub fn count(&self) -> usize { self.count }
+
+	/// Resets the number of bytes written to zero, returning the previous count.
+	#[inline]
+	pub fn reset_count(&mut self) -> usize {
+		std::mem::take(&mut self.count)
+	}
+
+	/// Returns a reference to the inner sink.
+	pub fn sink(&self) -> &S {
+		unsafe {
+			// Safety: option will only be None if into_inner is called, but this
+			// consumes and drops self, making it impossible to ever have a
+			// reference (except on drop, which is guarded).
+			self.sink.as_ref().unwrap_unchecked()
+		}
+	}
+
+	/// Returns a mutable reference to the inner sink. Writes to the inner sink
+	/// directly are not counted.
+	pub fn sink_mut(&mut self) -> &mut S {
+		unsafe {
+			// Safety: see `sink`
+			self.sink.as_mut().unwrap_unchecked()
+		}
+	}
+
+	/// Consumes the counting sink, returning the inner sink.
+	pub fn into_inner(mut self) -> S {
+		unsafe {
+			// Safety: option will only be None if this method was already called,
+			// which is impossible because we consume self.
+			self.sink.take().unwrap_unchecked()
+		}
+	}
+
+	/// Calls `drain` on the inner sink, counting the bytes it removes from `source`.
+	fn counted<P: Pool<N>>(
+		&mut self,
+		source: &mut Buffer<'d, N, P>,
+		drain: impl FnOnce(&mut S, &mut Buffer<'d, N, P>) -> BufferResult<usize>
+	) -> BufferResult<usize> {
+		let initial = source.count();
+		let result = drain(self.sink_mut(), source);
+		self.count += initial.saturating_sub(source.count());
+		result
+	}
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> Stream<N> for CountingSource<'d, S, N> {
+	fn is_closed(&self) -> bool {
+		self.source().is_closed()
+	}
+
+	fn close(&mut self) -> Result {
+		self.source_mut().close()
+	}
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> Source<'d, N> for CountingSource<'d, S, N> {
+	fn is_eos(&self) -> bool {
+		self.source().is_eos()
+	}
+
+	fn fill(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		self.counted(sink, |source, sink| source.fill(sink, count))
+	}
+
+	fn fill_free(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>) -> BufferResult<usize> {
+		self.counted(sink, |source, sink| source.fill_free(sink))
+	}
+
+	fn fill_all(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>) -> BufferResult<usize> {
+		self.counted(sink, |source, sink| source.fill_all(sink))
+	}
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> Drop for CountingSource<'d, S, N> {
+	fn drop(&mut self) {
+		// If into_inner was called, closing would cause a seg fault.
+		if self.source.is_some() {
+			let _ = self.close();
+		}
+	}
+}
+
+impl<'d, S: Sink<'d, N>, const N: usize> Stream<N> for CountingSink<'d, S, N> {
+	fn is_closed(&self) -> bool {
+		self.sink().is_closed()
+	}
+
+	fn close(&mut self) -> Result {
+		self.sink_mut().close()
+	}
+}
+
+impl<'d, S: Sink<'d, N>, const N: usize> Sink<'d, N> for CountingSink<'d, S, N> {
+	fn drain(&mut self, source: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		self.counted(source, |sink, source| sink.drain(source, count))
+	}
+
+	fn drain_full(&mut self, source: &mut Buffer<'d, N, impl Pool<N>>) -> BufferResult<usize> {
+		self.counted(source, |sink, source| sink.drain_full(source))
+	}
+
+	fn drain_all(&mut self, source: &mut Buffer<'d, N, impl Pool<N>>) -> BufferResult<usize> {
+		self.counted(source, |sink, source| sink.drain_all(source))
+	}
+
+	fn flush(&mut self) -> Result {
+		self.sink_mut().flush()
+	}
+}
+
+impl<'d, S: Sink<'d, N>, const N: usize> Drop for CountingSink<'d, S, N> {
+	fn drop(&mut self) {
+		// If into_inner was called, closing would cause a seg fault.
+		if self.sink.is_some() {
+			let _ = self.close();
+		}
+	}
+}
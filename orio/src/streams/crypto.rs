@@ -0,0 +1,673 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Streaming encryption and decryption. [`CipherSink`] transforms data drained
+//! into it before writing it to its inner sink, and [`CipherSource`] transforms
+//! data read from its inner source. Data is transformed with a [`Cipher`]:
+//!
+//! - [`Keystream`], with the `cipher` feature, applies any RustCrypto stream cipher
+//!   such as AES-CTR or ChaCha20. Encryption and decryption are the same operation.
+//! - [`AeadEncryptor`] and [`AeadDecryptor`], with the `aead` feature, apply any
+//!   RustCrypto AEAD such as AES-GCM or ChaCha20-Poly1305 with the [STREAM]
+//!   construction. Data is split into chunks, each sealed with its own tag, and the
+//!   last chunk is marked such that truncated data fails to decrypt. The final tag
+//!   is verified when the end of the data is reached.
+//!
+//! [STREAM]: https://eprint.iacr.org/2015/189.pdf
+
+use std::{io, mem};
+use crate::{Buffer, BufferResult, ResultContext, SIZE};
+use crate::BufferContext::{Drain, Fill};
+use crate::pool::{DefaultPoolContainer, Pool};
+use super::{BufSink, Result, Sink, Source, Stream};
+
+/// A transformation applied to data by [`CipherSource`] and [`CipherSink`], such
+/// as encryption or decryption.
+pub trait Cipher {
+	/// Returns the number of bytes transformed at once, or `None` if data can be
+	/// transformed in any length. Data is held until a whole chunk is available,
+	/// and a whole chunk is only transformed by [`update`] once more data follows
+	/// it, since the last chunk may be transformed differently by [`finish`].
+	///
+	/// [`update`]: Self::update
+	/// [`finish`]: Self::finish
+	fn chunk_len(&self) -> Option<usize>;
+	/// Transforms a chunk in place. The chunk's length may change, such as when an
+	/// authentication tag is added or removed.
+	fn update(&mut self, chunk: &mut Vec<u8>) -> io::Result<()>;
+	/// Transforms the last chunk in place, which may be partial or empty. No more
+	/// data is transformed after this.
+	fn finish(&mut self, chunk: &mut Vec<u8>) -> io::Result<()>;
+}
+
+/// Data staged for and transformed by a cipher.
+struct CipherState<C> {
+	cipher: C,
+	staged: Vec<u8>,
+	chunk: Vec<u8>,
+	output: Vec<u8>,
+	finished: bool,
+}
+
+impl<C: Cipher> CipherState<C> {
+	fn new(cipher: C) -> Self {
+		Self {
+			cipher,
+			staged: Vec::new(),
+			chunk: Vec::new(),
+			output: Vec::new(),
+			finished: false,
+		}
+	}
+
+	/// Stages `input`, transforming whole chunks into the output.
+	fn update(&mut self, input: &[u8]) -> io::Result<()> {
+		let Self { cipher, staged, chunk, output, .. } = self;
+		let Some(len) = cipher.chunk_len() else {
+			chunk.clear();
+			chunk.extend_from_slice(input);
+			cipher.update(chunk)?;
+			output.extend_from_slice(chunk);
+			return Ok(())
+		};
+
+		staged.extend_from_slice(input);
+		let mut start = 0;
+		while staged.len() - start > len {
+			chunk.clear();
+			chunk.extend_from_slice(&staged[start..][..len]);
+			cipher.update(chunk)?;
+			output.extend_from_slice(chunk);
+			start += len;
+		}
+		staged.drain(..start);
+		Ok(())
+	}
+
+	/// Transforms staged data as the last chunk, if not already finished.
+	fn finish(&mut self) -> io::Result<()> {
+		if !self.finished {
+			self.finished = true;
+			let mut last = mem::take(&mut self.staged);
+			let result = self.cipher.finish(&mut last);
+			self.output.extend_from_slice(&last);
+			#[cfg(feature = "zeroize")]
+			zeroize::Zeroize::zeroize(&mut last);
+			result?;
+		}
+		Ok(())
+	}
+
+	/// Writes up to `count` transformed bytes into `sink`.
+	fn take_output<const N: usize>(&mut self, sink: &mut Buffer<'_, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		let count = count.min(self.output.len());
+		if count > 0 {
+			sink.write_from_slice(&self.output[..count])?;
+			self.output.drain(..count);
+		}
+		Ok(count)
+	}
+}
+
+/// Zeroes staged plaintext or ciphertext, including copies left in spare capacity
+/// by draining, once the state is dropped.
+#[cfg(feature = "zeroize")]
+impl<C> Drop for CipherState<C> {
+	fn drop(&mut self) {
+		use zeroize::Zeroize;
+
+		self.staged.zeroize();
+		self.chunk.zeroize();
+		self.output.zeroize();
+	}
+}
+
+/// A [`Source`] that transforms data read from its inner source with a [`Cipher`],
+/// such as to decrypt it.
+pub struct CipherSource<
+	'd,
+	C: Cipher,
+	S: Source<'d, N>,
+	const N: usize = SIZE,
+	P: Pool<N> = DefaultPoolContainer
+> {
+	state: CipherState<C>,
+	source: Option<S>,
+	buffer: Buffer<'d, N, P>,
+	closed: bool,
+}
+
+impl<'d, C: Cipher, S: Source<'d, N>, const N: usize, P: Pool<N>> CipherSource<'d, C, S, N, P> {
+	/// Creates a new cipher source, transforming data read from `source` with
+	/// `cipher`.
+	pub fn new(cipher: C, source: S) -> Self {
+		let closed = source.is_closed();
+		Self {
+			state: CipherState::new(cipher),
+			source: Some(source),
+			buffer: Buffer::default(),
+			closed,
+		}
+	}
+
+	/// Returns a reference to the cipher.
+	#[inline]
+	pub fn cipher(&self) -> &C { &self.state.cipher }
+
+	/// Returns a reference to the inner source.
+	pub fn source(&self) -> &S {
+		unsafe {
+			// Safety: option will only be None if into_inner is called, but this
+			// consumes and drops self, making it impossible to ever have a
+			// reference (except on drop, which is guarded).
+			self.source.as_ref().unwrap_unchecked()
+		}
+	}
+
+	/// Returns a mutable reference to the inner source, bypassing the cipher.
+	pub fn source_mut(&mut self) -> &mut S {
+		unsafe {
+			// Safety: see Self::source.
+			self.source.as_mut().unwrap_unchecked()
+		}
+	}
+
+	/// Consumes the cipher source without closing, returning the inner source.
+	/// Any data read from the source but not yet transformed is discarded.
+	pub fn into_inner(mut self) -> S {
+		unsafe {
+			// Safety: option will only be None if this method was already called,
+			// which is impossible because we consume self.
+			self.source.take().unwrap_unchecked()
+		}
+	}
+
+	/// Reads up to `count` bytes from the inner source and transforms them,
+	/// returning the number of bytes read. The cipher is finished once the source
+	/// reaches end-of-stream; a source with no data available yet, such as a
+	/// non-blocking source, reads zero bytes without finishing.
+	fn read_input(&mut self, count: usize) -> BufferResult<usize> {
+		let Self { state, source, buffer, .. } = self;
+		let source = unsafe {
+			// Safety: see Self::source.
+			source.as_mut().unwrap_unchecked()
+		};
+
+		let mut eos = source.is_eos();
+		let read = if eos {
+			0
+		} else {
+			match source.fill(buffer, count) {
+				Ok(read) => read,
+				Err(err) if err.is_eos() => {
+					eos = true;
+					0
+				}
+				Err(err) => return Err(err)
+			}
+		};
+		for slice in buffer.as_byte_str().slices() {
+			state.update(slice).context(Fill)?;
+		}
+		buffer.clear();
+
+		if eos || source.is_eos() {
+			state.finish().context(Fill)?;
+		}
+		Ok(read)
+	}
+}
+
+impl<'d, C: Cipher, S: Source<'d, N>, const N: usize, P: Pool<N>> Stream<N> for CipherSource<'d, C, S, N, P> {
+	#[inline]
+	fn is_closed(&self) -> bool { self.closed }
+
+	fn close(&mut self) -> Result {
+		if !self.closed {
+			self.closed = true;
+			let buf_result = self.buffer.close();
+			let src_result = self.source_mut().close();
+			buf_result?;
+			src_result?;
+		}
+		Ok(())
+	}
+}
+
+impl<'d, C: Cipher, S: Source<'d, N>, const N: usize, P: Pool<N>> Source<'d, N> for CipherSource<'d, C, S, N, P> {
+	fn is_eos(&self) -> bool {
+		self.state.finished && self.state.output.is_empty()
+	}
+
+	/// Transforms up to `count` bytes read from the inner source into `sink`.
+	///
+	/// # Errors
+	///
+	/// If the data can't be transformed, such as when it fails authentication, an
+	/// IO error of kind [`InvalidData`] is returned.
+	///
+	/// [`InvalidData`]: io::ErrorKind::InvalidData
+	fn fill(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		self.check_open(Fill)?;
+		let mut read = self.state.take_output(sink, count)?;
+		while read < count && !self.state.finished {
+			let input = self.read_input(count - read)?;
+			read += self.state.take_output(sink, count - read)?;
+			if input == 0 && !self.state.finished {
+				break
+			}
+		}
+		Ok(read)
+	}
+}
+
+impl<'d, C: Cipher, S: Source<'d, N>, const N: usize, P: Pool<N>> Drop for CipherSource<'d, C, S, N, P> {
+	fn drop(&mut self) {
+		// If into_inner was called, closing would cause a seg fault.
+		if self.source.is_some() {
+			let _ = self.close();
+		}
+	}
+}
+
+/// A [`Sink`] that transforms data written to it with a [`Cipher`], such as to
+/// encrypt it, writing the result to its inner sink. Incomplete chunks are held
+/// until more data is written, or the sink is closed or [finished](Self::finish).
+pub struct CipherSink<
+	'd,
+	C: Cipher,
+	S: Sink<'d, N>,
+	const N: usize = SIZE,
+	P: Pool<N> = DefaultPoolContainer
+> {
+	state: CipherState<C>,
+	sink: Option<S>,
+	buffer: Buffer<'d, N, P>,
+	closed: bool,
+}
+
+impl<'d, C: Cipher, S: Sink<'d, N>, const N: usize, P: Pool<N>> CipherSink<'d, C, S, N, P> {
+	/// Creates a new cipher sink, transforming data with `cipher` into `sink`.
+	pub fn new(cipher: C, sink: S) -> Self {
+		let closed = sink.is_closed();
+		Self {
+			state: CipherState::new(cipher),
+			sink: Some(sink),
+			buffer: Buffer::default(),
+			closed,
+		}
+	}
+
+	/// Returns a reference to the cipher.
+	#[inline]
+	pub fn cipher(&self) -> &C { &self.state.cipher }
+
+	/// Returns a reference to the inner sink.
+	pub fn sink(&self) -> &S {
+		unsafe {
+			// Safety: option will only be None if into_inner is called, but this
+			// consumes and drops self, making it impossible to ever have a
+			// reference (except on drop, which is guarded).
+			self.sink.as_ref().unwrap_unchecked()
+		}
+	}
+
+	/// Returns a mutable reference to the inner sink, bypassing the cipher.
+	pub fn sink_mut(&mut self) -> &mut S {
+		unsafe {
+			// Safety: see Self::sink.
+			self.sink.as_mut().unwrap_unchecked()
+		}
+	}
+
+	/// Consumes the cipher sink without closing, returning the inner sink. Any
+	/// incomplete chunk or transformed data not yet written to the sink is
+	/// discarded; use [`finish`](Self::finish) to write it first.
+	pub fn into_inner(mut self) -> S {
+		unsafe {
+			// Safety: option will only be None if this method was already called,
+			// which is impossible because we consume self.
+			self.sink.take().unwrap_unchecked()
+		}
+	}
+
+	/// Transforms any incomplete chunk as the last chunk, writes all transformed
+	/// data to the inner sink and flushes it, then returns the inner sink without
+	/// closing it.
+	pub fn finish(mut self) -> Result<S> {
+		self.check_open(Drain)?;
+		self.state.finish().context(Drain)?;
+		self.write_output()?;
+		self.sink_mut().flush()?;
+		Ok(self.into_inner())
+	}
+
+	/// Writes transformed data to the inner sink.
+	fn write_output(&mut self) -> BufferResult {
+		let Self { state, sink, buffer, .. } = self;
+		state.take_output(buffer, usize::MAX)?;
+		let sink = unsafe {
+			// Safety: see Self::sink.
+			sink.as_mut().unwrap_unchecked()
+		};
+		sink.drain_all(buffer)?;
+		Ok(())
+	}
+}
+
+impl<'d, C: Cipher, S: Sink<'d, N>, const N: usize, P: Pool<N>> Stream<N> for CipherSink<'d, C, S, N, P> {
+	#[inline]
+	fn is_closed(&self) -> bool { self.closed }
+
+	/// Transforms any incomplete chunk as the last chunk, writes all transformed
+	/// data, then closes the inner sink.
+	fn close(&mut self) -> Result {
+		if !self.closed {
+			self.closed = true;
+			let write_result = self.state.finish()
+								   .context(Drain)
+								   .and_then(|_| self.write_output());
+			let sink_result = self.sink_mut().close();
+			write_result?;
+			sink_result?;
+		}
+		Ok(())
+	}
+}
+
+impl<'d, C: Cipher, S: Sink<'d, N>, const N: usize, P: Pool<N>> Sink<'d, N> for CipherSink<'d, C, S, N, P> {
+	/// Transforms up to `count` bytes from `source`, writing the result to the inner
+	/// sink. Returns the number of bytes consumed from `source`, including those
+	/// held in an incomplete chunk.
+	///
+	/// # Errors
+	///
+	/// If the data can't be transformed, such as when it fails authentication, an
+	/// IO error of kind [`InvalidData`] is returned.
+	///
+	/// [`InvalidData`]: io::ErrorKind::InvalidData
+	fn drain(&mut self, source: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		self.check_open(Drain)?;
+		let count = count.min(source.count());
+		for slice in source.as_byte_str().range(..count).slices() {
+			self.state.update(slice).context(Drain)?;
+		}
+		source.skip(count);
+		self.write_output()?;
+		Ok(count)
+	}
+
+	/// Writes transformed data to the inner sink and flushes it. Incomplete chunks
+	/// are not written, as the last chunk can only be transformed at the end of the
+	/// data.
+	fn flush(&mut self) -> Result {
+		self.check_open(Drain)?;
+		self.write_output()?;
+		self.sink_mut().flush()
+	}
+}
+
+impl<'d, C: Cipher, S: Sink<'d, N>, const N: usize, P: Pool<N>> Drop for CipherSink<'d, C, S, N, P> {
+	fn drop(&mut self) {
+		// If into_inner was called, closing would cause a seg fault.
+		if self.sink.is_some() {
+			let _ = self.close();
+		}
+	}
+}
+
+#[cfg(feature = "cipher")]
+pub use keystream::*;
+
+#[cfg(feature = "cipher")]
+mod keystream {
+	use std::io;
+	use ::cipher::StreamCipher;
+	use crate::pool::{DefaultPoolContainer, Pool};
+	use super::{Cipher, CipherSink, CipherSource, Sink, Source};
+
+	/// A [`Cipher`] applying the keystream of a stream cipher, such as AES-CTR or
+	/// ChaCha20. Applying the keystream both encrypts and decrypts data. Stream
+	/// ciphers don't authenticate data; prefer an AEAD where possible.
+	#[derive(Clone, Debug)]
+	pub struct Keystream<C>(pub C);
+
+	impl<C: StreamCipher> Cipher for Keystream<C> {
+		#[inline]
+		fn chunk_len(&self) -> Option<usize> { None }
+
+		fn update(&mut self, chunk: &mut Vec<u8>) -> io::Result<()> {
+			self.0.try_apply_keystream(chunk).map_err(|err|
+				io::Error::new(io::ErrorKind::InvalidData, err.to_string())
+			)
+		}
+
+		fn finish(&mut self, chunk: &mut Vec<u8>) -> io::Result<()> {
+			self.update(chunk)
+		}
+	}
+
+	impl<'d, C: StreamCipher, S: Source<'d, N>, const N: usize> CipherSource<'d, Keystream<C>, S, N, DefaultPoolContainer>
+	where DefaultPoolContainer: Pool<N> {
+		/// Creates a new cipher source, applying the keystream of `cipher` to data
+		/// read from `source`.
+		#[inline]
+		pub fn keystream(cipher: C, source: S) -> Self {
+			Self::new(Keystream(cipher), source)
+		}
+	}
+
+	impl<'d, C: StreamCipher, S: Sink<'d, N>, const N: usize> CipherSink<'d, Keystream<C>, S, N, DefaultPoolContainer>
+	where DefaultPoolContainer: Pool<N> {
+		/// Creates a new cipher sink, applying the keystream of `cipher` to data
+		/// written to `sink`.
+		#[inline]
+		pub fn keystream(cipher: C, sink: S) -> Self {
+			Self::new(Keystream(cipher), sink)
+		}
+	}
+}
+
+#[cfg(feature = "aead")]
+pub use stream_aead::*;
+
+#[cfg(feature = "aead")]
+mod stream_aead {
+	use std::io;
+	use std::ops::Sub;
+	use aead::{AeadCore, AeadInPlace};
+	use aead::generic_array::ArrayLength;
+	use aead::generic_array::typenum::Unsigned;
+	use aead::stream::{Decryptor, Encryptor, NonceSize, StreamBE32, StreamPrimitive};
+	use crate::pool::{DefaultPoolContainer, Pool};
+	use crate::SIZE;
+	use super::{Cipher, CipherSink, CipherSource, Sink, Source};
+
+	/// The default number of plaintext bytes sealed in each chunk.
+	pub const AEAD_CHUNK_LEN: usize = SIZE;
+
+	/// A [`Cipher`] encrypting data with an AEAD using the [STREAM] construction.
+	/// Plaintext is split into chunks of [`AEAD_CHUNK_LEN`] bytes by default, each
+	/// followed by its authentication tag. The last, possibly partial, chunk is
+	/// sealed when the data ends.
+	///
+	/// [STREAM]: https://eprint.iacr.org/2015/189.pdf
+	pub struct AeadEncryptor<A, S = StreamBE32<A>>
+	where A: AeadInPlace,
+		  S: StreamPrimitive<A>,
+		  A::NonceSize: Sub<S::NonceOverhead>,
+		  NonceSize<A, S>: ArrayLength<u8> {
+		stream: Option<Encryptor<A, S>>,
+		chunk_len: usize,
+	}
+
+	/// A [`Cipher`] decrypting data encrypted with an AEAD using the [STREAM]
+	/// construction, such as by [`AeadEncryptor`]. Each chunk's authentication tag
+	/// is verified before its plaintext is returned, and the last chunk's tag is
+	/// verified when the data ends; decryption fails if the data is truncated.
+	///
+	/// [STREAM]: https://eprint.iacr.org/2015/189.pdf
+	pub struct AeadDecryptor<A, S = StreamBE32<A>>
+	where A: AeadInPlace,
+		  S: StreamPrimitive<A>,
+		  A::NonceSize: Sub<S::NonceOverhead>,
+		  NonceSize<A, S>: ArrayLength<u8> {
+		stream: Option<Decryptor<A, S>>,
+		chunk_len: usize,
+	}
+
+	fn aead_error(error: aead::Error) -> io::Error {
+		io::Error::new(io::ErrorKind::InvalidData, error)
+	}
+
+	fn finished_error() -> io::Error {
+		io::Error::new(io::ErrorKind::InvalidInput, "cipher is already finished")
+	}
+
+	impl<A, S> AeadEncryptor<A, S>
+	where A: AeadInPlace,
+		  S: StreamPrimitive<A>,
+		  A::NonceSize: Sub<S::NonceOverhead>,
+		  NonceSize<A, S>: ArrayLength<u8> {
+		/// Creates a new encryptor from a STREAM encryptor.
+		pub fn new(stream: Encryptor<A, S>) -> Self {
+			Self {
+				stream: Some(stream),
+				chunk_len: AEAD_CHUNK_LEN,
+			}
+		}
+
+		/// Sets the number of plaintext bytes sealed in each chunk. The decryptor
+		/// must use the same chunk length.
+		///
+		/// # Panics
+		///
+		/// Panics if `chunk_len` is zero.
+		pub fn with_chunk_len(mut self, chunk_len: usize) -> Self {
+			assert!(chunk_len > 0, "chunk length should be non-zero");
+			self.chunk_len = chunk_len;
+			self
+		}
+	}
+
+	impl<A, S> AeadDecryptor<A, S>
+	where A: AeadInPlace,
+		  S: StreamPrimitive<A>,
+		  A::NonceSize: Sub<S::NonceOverhead>,
+		  NonceSize<A, S>: ArrayLength<u8> {
+		/// Creates a new decryptor from a STREAM decryptor.
+		pub fn new(stream: Decryptor<A, S>) -> Self {
+			Self {
+				stream: Some(stream),
+				chunk_len: AEAD_CHUNK_LEN,
+			}
+		}
+
+		/// Sets the number of plaintext bytes sealed in each chunk, which must match
+		/// the encryptor's chunk length.
+		///
+		/// # Panics
+		///
+		/// Panics if `chunk_len` is zero.
+		pub fn with_chunk_len(mut self, chunk_len: usize) -> Self {
+			assert!(chunk_len > 0, "chunk length should be non-zero");
+			self.chunk_len = chunk_len;
+			self
+		}
+	}
+
+	impl<A, S> Cipher for AeadEncryptor<A, S>
+	where A: AeadInPlace,
+		  S: StreamPrimitive<A>,
+		  A::NonceSize: Sub<S::NonceOverhead>,
+		  NonceSize<A, S>: ArrayLength<u8> {
+		#[inline]
+		fn chunk_len(&self) -> Option<usize> { Some(self.chunk_len) }
+
+		fn update(&mut self, chunk: &mut Vec<u8>) -> io::Result<()> {
+			let stream = self.stream.as_mut().ok_or_else(finished_error)?;
+			stream.encrypt_next_in_place(&[], chunk).map_err(aead_error)
+		}
+
+		fn finish(&mut self, chunk: &mut Vec<u8>) -> io::Result<()> {
+			let stream = self.stream.take().ok_or_else(finished_error)?;
+			stream.encrypt_last_in_place(&[], chunk).map_err(aead_error)
+		}
+	}
+
+	impl<A, S> Cipher for AeadDecryptor<A, S>
+	where A: AeadInPlace,
+		  S: StreamPrimitive<A>,
+		  A::NonceSize: Sub<S::NonceOverhead>,
+		  NonceSize<A, S>: ArrayLength<u8> {
+		#[inline]
+		fn chunk_len(&self) -> Option<usize> {
+			Some(self.chunk_len + <A as AeadCore>::TagSize::USIZE)
+		}
+
+		fn update(&mut self, chunk: &mut Vec<u8>) -> io::Result<()> {
+			let stream = self.stream.as_mut().ok_or_else(finished_error)?;
+			stream.decrypt_next_in_place(&[], chunk).map_err(aead_error)
+		}
+
+		fn finish(&mut self, chunk: &mut Vec<u8>) -> io::Result<()> {
+			let stream = self.stream.take().ok_or_else(finished_error)?;
+			stream.decrypt_last_in_place(&[], chunk).map_err(aead_error)
+		}
+	}
+
+	impl<'d, A, P, S: Source<'d, N>, const N: usize> CipherSource<'d, AeadEncryptor<A, P>, S, N, DefaultPoolContainer>
+	where A: AeadInPlace,
+		  P: StreamPrimitive<A>,
+		  A::NonceSize: Sub<P::NonceOverhead>,
+		  NonceSize<A, P>: ArrayLength<u8>,
+		  DefaultPoolContainer: Pool<N> {
+		/// Creates a new cipher source, encrypting data read from `source` with a
+		/// STREAM encryptor.
+		#[inline]
+		pub fn encrypt(stream: Encryptor<A, P>, source: S) -> Self {
+			Self::new(AeadEncryptor::new(stream), source)
+		}
+	}
+
+	impl<'d, A, P, S: Source<'d, N>, const N: usize> CipherSource<'d, AeadDecryptor<A, P>, S, N, DefaultPoolContainer>
+	where A: AeadInPlace,
+		  P: StreamPrimitive<A>,
+		  A::NonceSize: Sub<P::NonceOverhead>,
+		  NonceSize<A, P>: ArrayLength<u8>,
+		  DefaultPoolContainer: Pool<N> {
+		/// Creates a new cipher source, decrypting data read from `source` with a
+		/// STREAM decryptor.
+		#[inline]
+		pub fn decrypt(stream: Decryptor<A, P>, source: S) -> Self {
+			Self::new(AeadDecryptor::new(stream), source)
+		}
+	}
+
+	impl<'d, A, P, S: Sink<'d, N>, const N: usize> CipherSink<'d, AeadEncryptor<A, P>, S, N, DefaultPoolContainer>
+	where A: AeadInPlace,
+		  P: StreamPrimitive<A>,
+		  A::NonceSize: Sub<P::NonceOverhead>,
+		  NonceSize<A, P>: ArrayLength<u8>,
+		  DefaultPoolContainer: Pool<N> {
+		/// Creates a new cipher sink, encrypting data written to `sink` with a STREAM
+		/// encryptor.
+		#[inline]
+		pub fn encrypt(stream: Encryptor<A, P>, sink: S) -> Self {
+			Self::new(AeadEncryptor::new(stream), sink)
+		}
+	}
+
+	impl<'d, A, P, S: Sink<'d, N>, const N: usize> CipherSink<'d, AeadDecryptor<A, P>, S, N, DefaultPoolContainer>
+	where A: AeadInPlace,
+		  P: StreamPrimitive<A>,
+		  A::NonceSize: Sub<P::NonceOverhead>,
+		  NonceSize<A, P>: ArrayLength<u8>,
+		  DefaultPoolContainer: Pool<N> {
+		/// Creates a new cipher sink, decrypting data written to `sink` with a STREAM
+		/// decryptor.
+		#[inline]
+		pub fn decrypt(stream: Decryptor<A, P>, sink: S) -> Self {
+			Self::new(AeadDecryptor::new(stream), sink)
+		}
+	}
+}
@@ -0,0 +1,261 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! A minimal [CSV] record reader over [`BufSource`]s, with a configurable field
+//! delimiter and quote byte.
+//!
+//! [CSV]: https://www.rfc-editor.org/rfc/rfc4180
+
+use std::marker::PhantomData;
+use std::ops::Range;
+use crate::{ByteStr, SIZE, StreamError};
+use crate::pattern::BytePredicateMatcher;
+use crate::StreamContext::Read;
+use super::{invalid_data, request_more, BufSource, Result};
+
+/// A record read by a [`CsvReader`], borrowing its fields from the reader.
+#[derive(Clone, Debug)]
+pub struct CsvRecord<'b> {
+	fields: Vec<ByteStr<'b>>,
+}
+
+impl<'b> CsvRecord<'b> {
+	/// Returns the number of fields.
+	#[inline]
+	pub fn len(&self) -> usize { self.fields.len() }
+	/// Returns `true` if the record has no fields. Records read by [`CsvReader`]
+	/// always have at least one.
+	#[inline]
+	pub fn is_empty(&self) -> bool { self.fields.is_empty() }
+
+	/// Returns the field at `index`, or `None` if `index` is out of bounds.
+	pub fn get(&self, index: usize) -> Option<&ByteStr<'b>> {
+		self.fields.get(index)
+	}
+
+	/// Returns an iterator over the fields, in order.
+	pub fn iter(&self) -> impl Iterator<Item = &ByteStr<'b>> {
+		self.fields.iter()
+	}
+
+	/// Returns the fields.
+	pub fn into_fields(self) -> Vec<ByteStr<'b>> {
+		self.fields
+	}
+}
+
+/// The location of a field's contents.
+enum Field {
+	/// Bytes in the source buffer.
+	Buffer(Range<usize>),
+	/// Unescaped bytes copied into the reader.
+	Copied(Range<usize>),
+}
+
+/// A reader of CSV records from a [`BufSource`]. Fields borrow their bytes from the
+/// source buffer, across segment boundaries if needed; the buffer holds at most
+/// one record at a time, plus however much the source reads ahead. Only quoted
+/// fields containing escaped quotes are copied, to remove the escapes.
+///
+/// Records end with `"\r\n"`, `'\r'`, or `'\n'`, or at the end of the source.
+/// Empty lines are skipped. Quotes within unquoted fields are read as-is, and
+/// records may have differing numbers of fields.
+pub struct CsvReader<'d, S: BufSource<'d, N>, const N: usize = SIZE> {
+	source: S,
+	delimiter: u8,
+	quote: u8,
+	/// The length of the last record, consumed before reading the next.
+	consumed: usize,
+	/// Unescaped quoted fields of the last record.
+	copied: Vec<u8>,
+	__data: PhantomData<&'d ()>
+}
+
+impl<'d, S: BufSource<'d, N>, const N: usize> CsvReader<'d, S, N> {
+	/// Creates a new reader, reading comma-separated records from `source`.
+	pub fn new(source: S) -> Self {
+		Self {
+			source,
+			delimiter: b',',
+			quote: b'"',
+			consumed: 0,
+			copied: Vec::new(),
+			__data: PhantomData
+		}
+	}
+
+	/// Sets the field delimiter. Defaults to `,`.
+	pub fn with_delimiter(mut self, value: u8) -> Self {
+		self.delimiter = value;
+		self
+	}
+
+	/// Sets the quote byte. Defaults to `"`.
+	pub fn with_quote(mut self, value: u8) -> Self {
+		self.quote = value;
+		self
+	}
+
+	/// Consumes the reader, returning the source. The last record read is consumed
+	/// from the source.
+	pub fn into_inner(mut self) -> Result<S> {
+		self.consume()?;
+		Ok(self.source)
+	}
+
+	/// Reads the next record, or `None` if the source ended. The returned record
+	/// borrows from the source buffer until the next call.
+	///
+	/// # Errors
+	///
+	/// If a quoted field is followed by anything other than a delimiter or line
+	/// terminator, an IO error of kind [`InvalidData`] is returned. If the source
+	/// ends within a quoted field, an end-of-stream error is returned.
+	///
+	/// [`InvalidData`]: std::io::ErrorKind::InvalidData
+	pub fn next_record(&mut self) -> Result<Option<CsvRecord<'_>>> {
+		self.consume()?;
+		self.copied.clear();
+		if !self.skip_empty_lines()? {
+			return Ok(None)
+		}
+
+		let mut fields = Vec::new();
+		let mut pos = 0;
+		loop {
+			let (field, end) = if self.byte_at(pos)? == Some(self.quote) {
+				self.read_quoted(pos)?
+			} else {
+				let end = self.find_unquoted_end(pos)?;
+				(Field::Buffer(pos..end), end)
+			};
+			fields.push(field);
+
+			match self.byte_at(end)? {
+				Some(byte) if byte == self.delimiter => pos = end + 1,
+				Some(b'\n') => {
+					self.consumed = end + 1;
+					break
+				}
+				Some(b'\r') => {
+					self.consumed = end + 1;
+					if self.byte_at(end + 1)? == Some(b'\n') {
+						self.consumed += 1;
+					}
+					break
+				}
+				None => {
+					self.consumed = end;
+					break
+				}
+				Some(_) => return Err(invalid_data("unexpected byte after quoted field"))
+			}
+		}
+
+		let str = self.source.buf().as_byte_str();
+		let fields = fields.into_iter().map(|field|
+			match field {
+				Field::Buffer(range) => str.range(range),
+				Field::Copied(range) => ByteStr::from(&self.copied[range])
+			}
+		).collect();
+		Ok(Some(CsvRecord { fields }))
+	}
+
+	fn consume(&mut self) -> Result {
+		if self.consumed > 0 {
+			self.source.skip(self.consumed)?;
+			self.consumed = 0;
+		}
+		Ok(())
+	}
+
+	/// Skips line terminators, returning `false` if the source ended.
+	fn skip_empty_lines(&mut self) -> Result<bool> {
+		loop {
+			match self.byte_at(0)? {
+				Some(b'\r' | b'\n') => { self.source.skip(1)?; }
+				Some(_) => return Ok(true),
+				None => return Ok(false)
+			}
+		}
+	}
+
+	/// Returns the byte at `pos` in the buffer, reading more if needed, or `None` if
+	/// the source ended first.
+	fn byte_at(&mut self, pos: usize) -> Result<Option<u8>> {
+		self.source.request(pos + 1)?;
+		Ok(self.source.buf().get(pos))
+	}
+
+	/// Finds the end position of an unquoted field starting at `pos`.
+	fn find_unquoted_end(&mut self, pos: usize) -> Result<usize> {
+		let delimiter = self.delimiter;
+		let mut matcher = BytePredicateMatcher::from(
+			move |b: &u8| *b == delimiter || matches!(b, b'\r' | b'\n')
+		);
+		let mut from = pos;
+		loop {
+			let buf = self.source.buf();
+			if let Some(range) = buf.find_in_range(&mut matcher, from..) {
+				// Ranges are relative to the start of the searched range.
+				return Ok(from + range.start)
+			}
+
+			from = buf.count();
+			if !request_more(&mut self.source)? {
+				return Ok(from)
+			}
+		}
+	}
+
+	/// Reads a quoted field starting at `pos`, returning the field and the position
+	/// after its closing quote.
+	fn read_quoted(&mut self, pos: usize) -> Result<(Field, usize)> {
+		let mut from = pos + 1;
+		let mut escaped = false;
+		loop {
+			let buf = self.source.buf();
+			let Some(range) = buf.find_in_range(self.quote, from..) else {
+				from = buf.count();
+				if !request_more(&mut self.source)? {
+					return Err(StreamError::end_of_stream(1, Read))
+				}
+				continue
+			};
+
+			let quote = from + range.start;
+			if self.byte_at(quote + 1)? == Some(self.quote) {
+				escaped = true;
+				from = quote + 2;
+				continue
+			}
+
+			let contents = pos + 1..quote;
+			let field = if escaped {
+				self.unescape(contents)?
+			} else {
+				Field::Buffer(contents)
+			};
+			return Ok((field, quote + 1))
+		}
+	}
+
+	/// Copies a quoted field's contents from the buffer, replacing each pair of
+	/// quotes with one.
+	fn unescape(&mut self, contents: Range<usize>) -> Result<Field> {
+		let start = self.copied.len();
+		self.copied.resize(start + contents.len(), 0);
+		self.source.buf().read_slice_exact_at(contents.start, &mut self.copied[start..])?;
+
+		let mut len = start;
+		let mut pos = start;
+		while pos < self.copied.len() {
+			let byte = self.copied[pos];
+			self.copied[len] = byte;
+			len += 1;
+			pos += if byte == self.quote { 2 } else { 1 };
+		}
+		self.copied.truncate(len);
+		Ok(Field::Copied(start..len))
+	}
+}
@@ -0,0 +1,282 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::cell::RefCell;
+use std::io;
+use std::io::BorrowedCursor;
+use std::rc::Rc;
+use crate::{Buffer, BufferResult, ByteString, Error, ResultContext, SIZE, StreamResult as Result};
+use crate::BufferContext::{Drain, Fill};
+use crate::pattern::Pattern;
+use crate::pool::{DefaultPoolContainer, Pool};
+use crate::StreamContext::{Flush, Read};
+use super::{BufSink, BufSource, BufStream, Sink, Source, Stream, Utf8Match};
+
+/// Creates a pair of connected in-process streams, similar to `tokio::io::duplex`.
+/// Bytes written to one end become readable from the other, allowing code written
+/// against orio traits to be tested without sockets.
+///
+/// At most `max_buffer` bytes can be in transit in each direction. Written bytes
+/// exceeding this stay buffered in the writing end until the other end reads,
+/// and flushing returns a "would block" error. Reading with no data in transit
+/// returns nothing rather than blocking, since both ends live on the same thread.
+///
+/// Closing or dropping one end causes the other to reach end-of-stream once it has
+/// read all data in transit, and makes writes to the other end fail with a
+/// "stream closed" error.
+pub fn duplex<'d>(max_buffer: usize) -> (DuplexStream<'d>, DuplexStream<'d>) {
+	let shared = Rc::new(RefCell::new([Pipe::default(), Pipe::default()]));
+	(
+		DuplexStream::new(shared.clone(), 0, max_buffer),
+		DuplexStream::new(shared, 1, max_buffer)
+	)
+}
+
+/// One direction of a duplex stream.
+#[derive(Default)]
+struct Pipe<'d> {
+	buffer: Buffer<'d>,
+	closed: bool,
+}
+
+/// One end of a [`duplex`] stream.
+///
+/// The stream buffer is used for writing; reads go through a separate buffer
+/// holding data received from the other end.
+pub struct DuplexStream<'d> {
+	write_buf: Buffer<'d>,
+	read_buf: Buffer<'d>,
+	shared: Rc<RefCell<[Pipe<'d>; 2]>>,
+	/// The index of the pipe read from. The other end reads from the opposite pipe.
+	side: usize,
+	max_buffer: usize,
+	closed: bool,
+}
+
+impl<'d> DuplexStream<'d> {
+	fn new(shared: Rc<RefCell<[Pipe<'d>; 2]>>, side: usize, max_buffer: usize) -> Self {
+		Self {
+			write_buf: Buffer::default(),
+			read_buf: Buffer::default(),
+			shared,
+			side,
+			max_buffer,
+			closed: false,
+		}
+	}
+
+	/// Returns the maximum number of bytes in transit in each direction.
+	pub fn max_buffer(&self) -> usize { self.max_buffer }
+
+	/// Moves all data in transit from the other end into the read buffer.
+	fn receive(&mut self) -> BufferResult<usize> {
+		let mut shared = self.shared.borrow_mut();
+		shared[self.side].buffer.fill_all(&mut self.read_buf)
+	}
+
+	/// Moves up to `count` bytes from `source` to the other end, limited by the
+	/// space left in transit.
+	fn send(
+		shared: &RefCell<[Pipe<'d>; 2]>,
+		side: usize,
+		max_buffer: usize,
+		source: &mut Buffer<'d, SIZE, impl Pool>,
+		count: usize
+	) -> BufferResult<usize> {
+		let mut shared = shared.borrow_mut();
+		let pipe = &mut shared[1 - side];
+		if pipe.closed {
+			return Err(Error::closed(Drain))
+		}
+
+		let space = max_buffer.saturating_sub(pipe.buffer.count());
+		source.fill(&mut pipe.buffer, count.min(space))
+	}
+}
+
+impl Stream<SIZE> for DuplexStream<'_> {
+	#[inline]
+	fn is_closed(&self) -> bool { self.closed }
+
+	/// Closes this end, sending any buffered data to the other end if possible.
+	fn close(&mut self) -> Result {
+		if !self.closed {
+			let flush = self.flush();
+			self.closed = true;
+			for pipe in self.shared.borrow_mut().iter_mut() {
+				pipe.closed = true;
+			}
+			let write = self.write_buf.close();
+			let read = self.read_buf.close();
+			flush?;
+			write?;
+			read
+		} else {
+			Ok(())
+		}
+	}
+}
+
+impl<'d> Source<'d, SIZE> for DuplexStream<'d> {
+	/// Returns `true` if the other end is closed and all data sent from it has been
+	/// read.
+	fn is_eos(&self) -> bool {
+		let pipe = &self.shared.borrow()[self.side];
+		pipe.closed && pipe.buffer.is_empty() && self.read_buf.is_empty()
+	}
+
+	fn fill(&mut self, sink: &mut Buffer<'d, SIZE, impl Pool>, count: usize) -> BufferResult<usize> {
+		self.check_open(Fill)?;
+		self.receive()?;
+		self.read_buf.fill(sink, count)
+	}
+
+	fn fill_all(&mut self, sink: &mut Buffer<'d, SIZE, impl Pool>) -> BufferResult<usize> {
+		self.check_open(Fill)?;
+		self.receive()?;
+		self.read_buf.fill_all(sink)
+	}
+}
+
+impl<'d> Sink<'d, SIZE> for DuplexStream<'d> {
+	fn drain(&mut self, source: &mut Buffer<'d, SIZE, impl Pool>, count: usize) -> BufferResult<usize> {
+		self.check_open(Drain)?;
+		Self::send(&self.shared, self.side, self.max_buffer, source, count)
+	}
+
+	fn drain_all(&mut self, source: &mut Buffer<'d, SIZE, impl Pool>) -> BufferResult<usize> {
+		let count = source.count();
+		self.drain(source, count)
+	}
+
+	/// Sends all buffered data to the other end, returning a "would block" error if
+	/// the data doesn't fit.
+	fn flush(&mut self) -> Result {
+		self.check_open(Flush)?;
+		self.drain_all_buffered().context(Flush)?;
+		if self.write_buf.is_not_empty() {
+			return Err(io::Error::from(io::ErrorKind::WouldBlock)).context(Flush)
+		}
+		Ok(())
+	}
+}
+
+impl<'d> BufStream<'d, SIZE> for DuplexStream<'d> {
+	type Pool = DefaultPoolContainer;
+
+	fn buf<'b>(&'b self) -> &'b Buffer<'d> { &self.write_buf }
+	fn buf_mut<'b>(&'b mut self) -> &'b mut Buffer<'d> { &mut self.write_buf }
+}
+
+impl<'d> BufSink<'d, SIZE> for DuplexStream<'d> {
+	fn drain_all_buffered(&mut self) -> BufferResult {
+		self.check_open(Drain)?;
+		let count = self.write_buf.count();
+		Self::send(&self.shared, self.side, self.max_buffer, &mut self.write_buf, count)?;
+		Ok(())
+	}
+
+	/// Sends as much buffered data as fits to the other end. Unlike most sinks,
+	/// this isn't limited to full segments, since data is moved rather than
+	/// written out.
+	fn drain_buffered(&mut self) -> BufferResult {
+		self.drain_all_buffered()
+	}
+}
+
+impl<'d> BufSource<'d, SIZE> for DuplexStream<'d> {
+	fn available(&self) -> usize {
+		self.read_buf.count() + self.shared.borrow()[self.side].buffer.count()
+	}
+
+	fn request(&mut self, count: usize) -> Result<bool> {
+		self.check_open(Read)?;
+		self.receive().context(Read)?;
+		Ok(self.read_buf.count() >= count)
+	}
+
+	fn read(&mut self, sink: &mut impl Sink<'d, SIZE>, count: usize) -> Result<usize> {
+		self.request(count)?;
+		self.read_buf.read(sink, count)
+	}
+
+	fn read_all(&mut self, sink: &mut impl Sink<'d, SIZE>) -> Result<usize> {
+		self.request(0)?;
+		self.read_buf.read_all(sink)
+	}
+
+	fn skip(&mut self, count: usize) -> Result<usize> {
+		self.request(count)?;
+		Ok(self.read_buf.skip(count))
+	}
+
+	fn read_slice(&mut self, buf: &mut [u8]) -> Result<usize> {
+		self.request(buf.len())?;
+		BufSource::read_slice(&mut self.read_buf, buf)
+	}
+
+	fn read_slice_exact(&mut self, buf: &mut [u8]) -> Result<usize> {
+		self.request(buf.len())?;
+		BufSource::read_slice_exact(&mut self.read_buf, buf)
+	}
+
+	fn read_into_cursor(&mut self, cursor: BorrowedCursor) -> Result<usize> {
+		self.request(cursor.capacity())?;
+		self.read_buf.read_into_cursor(cursor)
+	}
+
+	fn read_to_vec(&mut self, vec: &mut Vec<u8>, count: usize) -> Result<usize> {
+		self.request(0)?;
+		self.read_buf.read_to_vec(vec, count)
+	}
+
+	fn read_utf8<'s>(&mut self, buf: &'s mut String, count: usize) -> Result<&'s str> {
+		self.request(count)?;
+		self.read_buf.read_utf8(buf, count)
+	}
+
+	fn read_utf8_to_end<'s>(&mut self, buf: &'s mut String) -> Result<&'s str> {
+		self.request(0)?;
+		self.read_buf.read_utf8_to_end(buf)
+	}
+
+	fn read_utf8_line(&mut self, buf: &mut String) -> Result<Utf8Match> {
+		self.request(0)?;
+		self.read_buf.read_utf8_line(buf)
+	}
+
+	fn read_utf8_line_inclusive(&mut self, buf: &mut String) -> Result<Utf8Match> {
+		self.request(0)?;
+		self.read_buf.read_utf8_line_inclusive(buf)
+	}
+
+	fn read_utf8_until(&mut self, buf: &mut String, terminator: impl Pattern) -> Result<Utf8Match> {
+		self.request(0)?;
+		self.read_buf.read_utf8_until(buf, terminator)
+	}
+
+	fn read_utf8_until_inclusive(&mut self, buf: &mut String, terminator: impl Pattern) -> Result<Utf8Match> {
+		self.request(0)?;
+		self.read_buf.read_utf8_until_inclusive(buf, terminator)
+	}
+
+	fn read_line_bytes(&mut self, buf: &mut ByteString) -> Result<Utf8Match> {
+		self.request(0)?;
+		self.read_buf.read_line_bytes(buf)
+	}
+
+	fn read_until(&mut self, sink: &mut impl Sink<'d, SIZE>, terminator: impl Pattern) -> Result<Utf8Match> {
+		self.request(0)?;
+		self.read_buf.read_until(sink, terminator)
+	}
+
+	fn read_until_inclusive(&mut self, sink: &mut impl Sink<'d, SIZE>, terminator: impl Pattern) -> Result<Utf8Match> {
+		self.request(0)?;
+		self.read_buf.read_until_inclusive(sink, terminator)
+	}
+}
+
+impl Drop for DuplexStream<'_> {
+	fn drop(&mut self) {
+		let _ = self.close();
+	}
+}
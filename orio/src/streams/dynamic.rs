@@ -0,0 +1,224 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Buffer, BufferResult, DefaultBuffer, ResultContext, SIZE};
+use crate::BufferContext::{Drain, Fill};
+use crate::pool::{DefaultPoolContainer, Pool};
+use super::{Result, Sink, Source, Stream};
+
+/// An object-safe [`Source`], reading into buffers claiming segments from the
+/// default pool. [`Source`] isn't object-safe because its methods are generic
+/// over the buffer's pool; this trait is implemented for all sources, so sources
+/// of different types can be stored together as `Box<dyn DynSource>`, created
+/// with [`boxed`].
+///
+/// `dyn DynSource` implements [`Source`] itself, filling buffers claiming from
+/// other pools through a temporary buffer.
+///
+/// [`boxed`]: Self::boxed
+pub trait DynSource<'d> {
+	/// Returns whether the source is closed. See [`Stream::is_closed`].
+	fn is_closed_dyn(&self) -> bool;
+	/// Closes the source if not already closed. See [`Stream::close`].
+	fn close_dyn(&mut self) -> Result;
+	/// Returns `true` if end-of-stream was reached. See [`Source::is_eos`].
+	fn is_eos_dyn(&self) -> bool;
+	/// Fills a buffer with up to `count` bytes read from the source, returning the
+	/// number of bytes read. See [`Source::fill`].
+	fn fill_dyn(&mut self, sink: &mut DefaultBuffer<'d>, count: usize) -> BufferResult<usize>;
+	/// Fills free space in the buffer with bytes read from the source, returning
+	/// the number of bytes read. See [`Source::fill_free`].
+	fn fill_free_dyn(&mut self, sink: &mut DefaultBuffer<'d>) -> BufferResult<usize>;
+	/// Fills a buffer with all available data read from the source, returning the
+	/// number of bytes read. See [`Source::fill_all`].
+	fn fill_all_dyn(&mut self, sink: &mut DefaultBuffer<'d>) -> BufferResult<usize>;
+
+	/// Boxes the source as a trait object.
+	fn boxed<'a>(self) -> Box<dyn DynSource<'d> + 'a> where Self: Sized + 'a {
+		Box::new(self)
+	}
+}
+
+/// An object-safe [`Sink`], writing from buffers claiming segments from the
+/// default pool. This trait is implemented for all sinks, so sinks of different
+/// types can be stored together as `Box<dyn DynSink>`, created with [`boxed`].
+///
+/// `dyn DynSink` implements [`Sink`] itself, draining buffers claiming from other
+/// pools through a temporary buffer.
+///
+/// [`boxed`]: Self::boxed
+pub trait DynSink<'d> {
+	/// Returns whether the sink is closed. See [`Stream::is_closed`].
+	fn is_closed_dyn(&self) -> bool;
+	/// Flushes and closes the sink if not already closed. See [`Stream::close`].
+	fn close_dyn(&mut self) -> Result;
+	/// Drains a buffer by writing up to `count` bytes into the sink, returning the
+	/// number of bytes written. See [`Sink::drain`].
+	fn drain_dyn(&mut self, source: &mut DefaultBuffer<'d>, count: usize) -> BufferResult<usize>;
+	/// Drains a buffer by writing full segments into the sink, returning the number
+	/// of bytes written. See [`Sink::drain_full`].
+	fn drain_full_dyn(&mut self, source: &mut DefaultBuffer<'d>) -> BufferResult<usize>;
+	/// Drains a buffer by writing all its data into the sink, returning the number
+	/// of bytes written. See [`Sink::drain_all`].
+	fn drain_all_dyn(&mut self, source: &mut DefaultBuffer<'d>) -> BufferResult<usize>;
+	/// Writes all buffered data to its final target. See [`Sink::flush`].
+	fn flush_dyn(&mut self) -> Result;
+
+	/// Boxes the sink as a trait object.
+	fn boxed<'a>(self) -> Box<dyn DynSink<'d> + 'a> where Self: Sized + 'a {
+		Box::new(self)
+	}
+}
+
+impl<'d, S: Source<'d, SIZE>> DynSource<'d> for S {
+	#[inline]
+	fn is_closed_dyn(&self) -> bool {
+		Stream::is_closed(self)
+	}
+
+	#[inline]
+	fn close_dyn(&mut self) -> Result {
+		Stream::close(self)
+	}
+
+	#[inline]
+	fn is_eos_dyn(&self) -> bool {
+		Source::is_eos(self)
+	}
+
+	#[inline]
+	fn fill_dyn(&mut self, sink: &mut DefaultBuffer<'d>, count: usize) -> BufferResult<usize> {
+		self.fill(sink, count)
+	}
+
+	#[inline]
+	fn fill_free_dyn(&mut self, sink: &mut DefaultBuffer<'d>) -> BufferResult<usize> {
+		self.fill_free(sink)
+	}
+
+	#[inline]
+	fn fill_all_dyn(&mut self, sink: &mut DefaultBuffer<'d>) -> BufferResult<usize> {
+		self.fill_all(sink)
+	}
+}
+
+impl<'d, S: Sink<'d, SIZE>> DynSink<'d> for S {
+	#[inline]
+	fn is_closed_dyn(&self) -> bool {
+		Stream::is_closed(self)
+	}
+
+	#[inline]
+	fn close_dyn(&mut self) -> Result {
+		Stream::close(self)
+	}
+
+	#[inline]
+	fn drain_dyn(&mut self, source: &mut DefaultBuffer<'d>, count: usize) -> BufferResult<usize> {
+		self.drain(source, count)
+	}
+
+	#[inline]
+	fn drain_full_dyn(&mut self, source: &mut DefaultBuffer<'d>) -> BufferResult<usize> {
+		self.drain_full(source)
+	}
+
+	#[inline]
+	fn drain_all_dyn(&mut self, source: &mut DefaultBuffer<'d>) -> BufferResult<usize> {
+		self.drain_all(source)
+	}
+
+	#[inline]
+	fn flush_dyn(&mut self) -> Result {
+		Sink::flush(self)
+	}
+}
+
+impl<'d> Stream<SIZE> for dyn DynSource<'d> + '_ {
+	#[inline]
+	fn is_closed(&self) -> bool {
+		self.is_closed_dyn()
+	}
+
+	#[inline]
+	fn close(&mut self) -> Result {
+		self.close_dyn()
+	}
+}
+
+impl<'d> Source<'d, SIZE> for dyn DynSource<'d> + '_ {
+	#[inline]
+	fn is_eos(&self) -> bool {
+		self.is_eos_dyn()
+	}
+
+	fn fill(&mut self, sink: &mut Buffer<'d, SIZE, impl Pool<SIZE>>, count: usize) -> BufferResult<usize> {
+		fill_through(sink, |buf| self.fill_dyn(buf, count))
+	}
+
+	fn fill_free(&mut self, sink: &mut Buffer<'d, SIZE, impl Pool<SIZE>>) -> BufferResult<usize> {
+		let limit = sink.limit();
+		fill_through(sink, |buf| self.fill_dyn(buf, limit))
+	}
+
+	fn fill_all(&mut self, sink: &mut Buffer<'d, SIZE, impl Pool<SIZE>>) -> BufferResult<usize> {
+		fill_through(sink, |buf| self.fill_all_dyn(buf))
+	}
+}
+
+impl<'d> Stream<SIZE> for dyn DynSink<'d> + '_ {
+	#[inline]
+	fn is_closed(&self) -> bool {
+		self.is_closed_dyn()
+	}
+
+	#[inline]
+	fn close(&mut self) -> Result {
+		self.close_dyn()
+	}
+}
+
+impl<'d> Sink<'d, SIZE> for dyn DynSink<'d> + '_ {
+	fn drain(&mut self, source: &mut Buffer<'d, SIZE, impl Pool<SIZE>>, count: usize) -> BufferResult<usize> {
+		drain_through(source, |buf| self.drain_dyn(buf, count))
+	}
+
+	fn drain_full(&mut self, source: &mut Buffer<'d, SIZE, impl Pool<SIZE>>) -> BufferResult<usize> {
+		drain_through(source, |buf| self.drain_full_dyn(buf))
+	}
+
+	fn drain_all(&mut self, source: &mut Buffer<'d, SIZE, impl Pool<SIZE>>) -> BufferResult<usize> {
+		drain_through(source, |buf| self.drain_all_dyn(buf))
+	}
+
+	#[inline]
+	fn flush(&mut self) -> Result {
+		self.flush_dyn()
+	}
+}
+
+/// Fills a temporary buffer from the default pool, limited to the remaining
+/// capacity of `sink`, then moves its segments into `sink`. Bytes read before any
+/// error are moved into `sink`.
+fn fill_through<'d>(
+	sink: &mut Buffer<'d, SIZE, impl Pool<SIZE>>,
+	fill: impl FnOnce(&mut DefaultBuffer<'d>) -> BufferResult<usize>
+) -> BufferResult<usize> {
+	let options = sink.options().with_max_capacity(sink.remaining_capacity());
+	let mut buf = Buffer::new(DefaultPoolContainer::get(), options);
+	let result = fill(&mut buf);
+	buf.fill_all(sink).context(Fill)?;
+	result
+}
+
+/// Moves the segments of `source` into a temporary buffer from the default pool
+/// and drains it, then moves the remaining segments back into `source`.
+fn drain_through<'d>(
+	source: &mut Buffer<'d, SIZE, impl Pool<SIZE>>,
+	drain: impl FnOnce(&mut DefaultBuffer<'d>) -> BufferResult<usize>
+) -> BufferResult<usize> {
+	let mut buf = Buffer::new(DefaultPoolContainer::get(), source.options());
+	source.fill_all(&mut buf).context(Drain)?;
+	let result = drain(&mut buf);
+	buf.fill_all(source).context(Drain)?;
+	result
+}
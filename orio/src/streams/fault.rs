@@ -0,0 +1,291 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::io;
+use std::marker::PhantomData;
+use crate::{Buffer, BufferResult, ResultContext};
+use crate::BufferContext::{Drain, Fill};
+use crate::pool::Pool;
+use super::{EndOfStream, Result, Sink, Source, Stream};
+
+/// A set of faults to inject into stream operations, for testing error paths.
+/// No faults are injected by default.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Faults {
+	/// The number of bytes after which the stream ends. A source reaches
+	/// end-of-stream, and a sink fails with an end-of-stream error.
+	pub eos_at: Option<usize>,
+	/// Fails every `k`th read or write with an [`Interrupted`] error.
+	///
+	/// [`Interrupted`]: io::ErrorKind::Interrupted
+	pub interrupt_every: Option<usize>,
+	/// The maximum number of bytes transferred per read or write, simulating
+	/// short reads and writes.
+	pub max_chunk: Option<usize>,
+}
+
+impl Faults {
+	/// Ends the stream after `count` bytes.
+	#[inline]
+	pub fn eos_at(mut self, count: usize) -> Self {
+		self.eos_at = Some(count);
+		self
+	}
+
+	/// Fails every `k`th read or write with an [`Interrupted`] error.
+	///
+	/// [`Interrupted`]: io::ErrorKind::Interrupted
+	///
+	/// # Panics
+	///
+	/// Panics if `k` is zero.
+	#[inline]
+	pub fn interrupt_every(mut self, k: usize) -> Self {
+		assert!(k > 0, "interrupt interval must be greater than zero");
+		self.interrupt_every = Some(k);
+		self
+	}
+
+	/// Limits reads and writes to at most `count` bytes.
+	///
+	/// # Panics
+	///
+	/// Panics if `count` is zero.
+	#[inline]
+	pub fn max_chunk(mut self, count: usize) -> Self {
+		assert!(count > 0, "chunk size must be greater than zero");
+		self.max_chunk = Some(count);
+		self
+	}
+}
+
+/// Tracks the progress of a faulty stream.
+#[derive(Copy, Clone, Debug, Default)]
+struct FaultState {
+	faults: Faults,
+	calls: usize,
+	position: usize,
+}
+
+impl FaultState {
+	fn new(faults: Faults) -> Self {
+		Self { faults, ..Self::default() }
+	}
+
+	/// Counts a call, returning an error if it should be interrupted.
+	fn call(&mut self) -> io::Result<()> {
+		self.calls += 1;
+		match self.faults.interrupt_every {
+			Some(k) if self.calls.is_multiple_of(k) => Err(io::ErrorKind::Interrupted.into()),
+			_ => Ok(())
+		}
+	}
+
+	/// Limits `count` by the chunk size and remaining bytes before end-of-stream.
+	fn limit(&self, count: usize) -> usize {
+		let count = count.min(self.faults.max_chunk.unwrap_or(usize::MAX));
+		count.min(self.remaining())
+	}
+
+	fn remaining(&self) -> usize {
+		self.faults
+			.eos_at
+			.map_or(usize::MAX, |eos| eos.saturating_sub(self.position))
+	}
+
+	fn is_limited(&self) -> bool {
+		self.faults.eos_at.is_some() || self.faults.max_chunk.is_some()
+	}
+}
+
+/// A [`Source`] injecting [`Faults`] into reads from its inner source.
+pub struct FaultySource<'d, S: Source<'d, N>, const N: usize> {
+	source: Option<S>,
+	state: FaultState,
+	__data: PhantomData<&'d ()>
+}
+
+/// A [`Sink`] injecting [`Faults`] into writes to its inner sink.
+pub struct FaultySink<'d, S: Sink<'d, N>, const N: usize> {
+	sink: Option<S>,
+	state: FaultState,
+	__data: PhantomData<&'d ()>
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> FaultySource<'d, S, N> {
+	/// Creates a new faulty source injecting `faults`.
+	pub fn new(source: S, faults: Faults) -> Self {
+		Self {
+			source: Some(source),
+			state: FaultState::new(faults),
+			__data: PhantomData
+		}
+	}
+
+	/// Returns the injected faults.
+	pub fn faults(&self) -> Faults { self.state.faults }
+
+	/// Returns the number of bytes read.
+	pub fn position(&self) -> usize { self.state.position }
+
+	/// Returns a reference to the inner source.
+	pub fn source(&self) -> &S {
+		unsafe {
+			// Safety: option will only be None if into_inner is called, but this
+			// consumes and drops self, making it impossible to ever have a
+			// reference (except on drop, which is guarded).
+			self.source.as_ref().unwrap_unchecked()
+		}
+	}
+
+	/// Returns a mutable reference to the inner source.
+	pub fn source_mut(&mut self) -> &mut S {
+		unsafe {
+			// Safety: see `source`
+			self.source.as_mut().unwrap_unchecked()
+		}
+	}
+
+	/// Consumes the faulty source, returning the inner source.
+	pub fn into_inner(mut self) -> S {
+		unsafe {
+			// Safety: option will only be None if this method was already called,
+			// which is impossible because we consume self.
+			self.source.take().unwrap_unchecked()
+		}
+	}
+}
+
+impl<'d, S: Sink<'d, N>, const N: usize> FaultySink<'d, S, N> {
+	/// Creates a new faulty sink injecting `faults`.
+	pub fn new(sink: S, faults: Faults) -> Self {
+		Self {
+			sink: Some(sink),
+			state: FaultState::new(faults),
+			__data: PhantomData
+		}
+	}
+
+	/// Returns the injected faults.
+	pub fn faults(&self) -> Faults { self.state.faults }
+
+	/// Returns the number of bytes written.
+	pub fn position(&self) -> usize { self.state.position }
+
+	/// Returns a reference to the inner sink.
+	pub fn sink(&self) -> &S {
+		unsafe {
+			// Safety: option will only be None if into_inner is called, but this
+			// consumes and drops self, making it impossible to ever have a
+			// reference (except on drop, which is guarded).
+			self.sink.as_ref().unwrap_unchecked()
+		}
+	}
+
+	/// Returns a mutable reference to the inner sink.
+	pub fn sink_mut(&mut self) -> &mut S {
+		unsafe {
+			// Safety: see `sink`
+			self.sink.as_mut().unwrap_unchecked()
+		}
+	}
+
+	/// Consumes the faulty sink, returning the inner sink.
+	pub fn into_inner(mut self) -> S {
+		unsafe {
+			// Safety: option will only be None if this method was already called,
+			// which is impossible because we consume self.
+			self.sink.take().unwrap_unchecked()
+		}
+	}
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> Stream<N> for FaultySource<'d, S, N> {
+	fn is_closed(&self) -> bool {
+		self.source().is_closed()
+	}
+
+	fn close(&mut self) -> Result {
+		self.source_mut().close()
+	}
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> Source<'d, N> for FaultySource<'d, S, N> {
+	fn is_eos(&self) -> bool {
+		self.state.remaining() == 0 || self.source().is_eos()
+	}
+
+	fn fill(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		self.state.call().context(Fill)?;
+		let count = self.state.limit(count);
+		if count == 0 {
+			return Ok(0)
+		}
+
+		let read = self.source_mut().fill(sink, count)?;
+		self.state.position += read;
+		Ok(read)
+	}
+
+	fn fill_all(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>) -> BufferResult<usize> {
+		if self.state.is_limited() {
+			return self.fill(sink, usize::MAX)
+		}
+
+		self.state.call().context(Fill)?;
+		let read = self.source_mut().fill_all(sink)?;
+		self.state.position += read;
+		Ok(read)
+	}
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> Drop for FaultySource<'d, S, N> {
+	fn drop(&mut self) {
+		// If into_inner was called, closing would cause a seg fault.
+		if self.source.is_some() {
+			let _ = self.close();
+		}
+	}
+}
+
+impl<'d, S: Sink<'d, N>, const N: usize> Stream<N> for FaultySink<'d, S, N> {
+	fn is_closed(&self) -> bool {
+		self.sink().is_closed()
+	}
+
+	fn close(&mut self) -> Result {
+		self.sink_mut().close()
+	}
+}
+
+impl<'d, S: Sink<'d, N>, const N: usize> Sink<'d, N> for FaultySink<'d, S, N> {
+	fn drain(&mut self, source: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		self.state.call().context(Drain)?;
+		let count = count.min(source.count());
+		if count > 0 && self.state.remaining() == 0 {
+			return Err(EndOfStream::from(count)).context(Drain)
+		}
+
+		let count = self.state.limit(count);
+		let written = self.sink_mut().drain(source, count)?;
+		self.state.position += written;
+		Ok(written)
+	}
+
+	fn drain_all(&mut self, source: &mut Buffer<'d, N, impl Pool<N>>) -> BufferResult<usize> {
+		let count = source.count();
+		self.drain(source, count)
+	}
+
+	fn flush(&mut self) -> Result {
+		self.sink_mut().flush()
+	}
+}
+
+impl<'d, S: Sink<'d, N>, const N: usize> Drop for FaultySink<'d, S, N> {
+	fn drop(&mut self) {
+		// If into_inner was called, closing would cause a seg fault.
+		if self.sink.is_some() {
+			let _ = self.close();
+		}
+	}
+}
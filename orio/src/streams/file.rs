@@ -2,10 +2,18 @@
 
 use std::fs::File;
 use std::io;
+#[cfg(any(unix, windows))]
+use std::io::{Read, Write};
+#[cfg(unix)]
+use std::os::unix::fs::FileExt;
+#[cfg(windows)]
+use std::os::windows::fs::FileExt;
 use std::path::Path;
 use crate::{Buffer, BufferResult, StreamResult};
 use crate::pool::Pool;
 use super::{ReaderSource, Seekable, SeekOffset, Source, Stream, WriterSink};
+#[cfg(any(unix, windows))]
+use super::{PositionedSink, PositionedSource};
 
 /// A [`Source`] reading from a [file](File).
 pub struct FileSource {
@@ -63,7 +71,7 @@ impl<const N: usize> Source<'_, N> for FileSource {
 
 	fn fill(&mut self, sink: &mut Buffer<'_, N, impl Pool<N>>, mut count: usize) -> BufferResult<usize> {
 		count = self.len.map_or(count, |len| {
-			let remaining = len - self.read_count;
+			let remaining = len.saturating_sub(self.read_count);
 			count.min(remaining)
 		});
 		let read_count = self.source.fill(sink, count)?;
@@ -74,8 +82,62 @@ impl<const N: usize> Source<'_, N> for FileSource {
 
 impl Seekable for FileSource {
 	fn seek(&mut self, offset: SeekOffset) -> StreamResult<usize> {
-		self.source.seek(offset)
+		// Track the new position so end-of-stream reflects the seek.
+		let pos = self.source.seek(offset)?;
+		self.read_count = pos;
+		Ok(pos)
 	}
 }
 
+/// Reads and writes a file at an offset, advancing the offset.
+#[cfg(any(unix, windows))]
+struct FileAt<'f> {
+	file: &'f File,
+	offset: u64,
+}
+
+#[cfg(any(unix, windows))]
+impl Read for FileAt<'_> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		#[cfg(unix)]
+		let read = self.file.read_at(buf, self.offset)?;
+		#[cfg(windows)]
+		let read = self.file.seek_read(buf, self.offset)?;
+		self.offset += read as u64;
+		Ok(read)
+	}
+}
 
+#[cfg(any(unix, windows))]
+impl Write for FileAt<'_> {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		#[cfg(unix)]
+		let written = self.file.write_at(buf, self.offset)?;
+		#[cfg(windows)]
+		let written = self.file.seek_write(buf, self.offset)?;
+		self.offset += written as u64;
+		Ok(written)
+	}
+
+	fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
+/// Reads with `pread` on Unix. On Windows, reads with `ReadFile` at an offset,
+/// which also moves the file cursor.
+#[cfg(any(unix, windows))]
+impl<const N: usize> PositionedSource<'_, N> for File {
+	fn fill_at(&self, offset: u64, sink: &mut Buffer<'_, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		let mut reader = FileAt { file: self, offset };
+		sink.fill_from_reader(&mut reader, count, false)
+	}
+}
+
+/// Writes with `pwrite` on Unix. On Windows, writes with `WriteFile` at an offset,
+/// which also moves the file cursor.
+#[cfg(any(unix, windows))]
+impl<const N: usize> PositionedSink<'_, N> for File {
+	fn drain_at(&self, offset: u64, source: &mut Buffer<'_, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		let mut writer = FileAt { file: self, offset };
+		source.drain_into_writer(&mut writer, count, false)
+	}
+}
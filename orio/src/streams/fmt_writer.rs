@@ -0,0 +1,65 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{fmt, io};
+use std::marker::PhantomData;
+use crate::{ResultContext, StreamError};
+use crate::StreamContext::Write;
+use super::{BufSink, Result};
+
+/// A [`fmt::Write`] adapter for a [`BufSink`], formatting text directly into its
+/// buffer. Returned by [`BufSink::fmt_writer`].
+pub struct FmtWriter<'a, 'd, S: BufSink<'d, N> + ?Sized, const N: usize> {
+	sink: &'a mut S,
+	count: usize,
+	error: Option<StreamError>,
+	__data: PhantomData<&'d ()>
+}
+
+impl<'a, 'd, S: BufSink<'d, N> + ?Sized, const N: usize> FmtWriter<'a, 'd, S, N> {
+	pub(super) fn new(sink: &'a mut S) -> Self {
+		Self {
+			sink,
+			count: 0,
+			error: None,
+			__data: PhantomData
+		}
+	}
+
+	/// Returns the number of bytes written.
+	pub fn count(&self) -> usize { self.count }
+
+	/// Returns the last error returned by the sink, if a write failed. The
+	/// [`fmt::Error`] returned from writing carries no information, so this can be
+	/// used to retrieve the error.
+	pub fn take_error(&mut self) -> Option<StreamError> {
+		self.error.take()
+	}
+
+	/// Writes formatted text, returning the number of bytes written. If formatting
+	/// fails, returns the underlying sink error if one occurred, or an IO error
+	/// if a formatting trait implementation returned an error.
+	pub(super) fn write_args(mut self, args: fmt::Arguments) -> Result<usize> {
+		match fmt::Write::write_fmt(&mut self, args) {
+			Ok(()) => Ok(self.count),
+			Err(_) => match self.error {
+				Some(error) => Err(error),
+				None => Err(io::Error::other("formatter error")).context(Write)
+			}
+		}
+	}
+}
+
+impl<'d, S: BufSink<'d, N> + ?Sized, const N: usize> fmt::Write for FmtWriter<'_, 'd, S, N> {
+	fn write_str(&mut self, s: &str) -> fmt::Result {
+		match self.sink.write_utf8(s) {
+			Ok(count) => {
+				self.count += count;
+				Ok(())
+			}
+			Err(error) => {
+				self.error = Some(error);
+				Err(fmt::Error)
+			}
+		}
+	}
+}
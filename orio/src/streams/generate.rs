@@ -0,0 +1,202 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Sources generating unlimited data cheaply, writing directly into buffer memory.
+//! These are useful as black boxes to measure the throughput of wrappers in
+//! isolation, without the cost of reading real data.
+
+use std::borrow::Cow;
+use std::io::BorrowedCursor;
+use crate::{Buffer, BufferResult as Result, Error, ResultSetContext, StreamResult};
+use crate::BufferContext::Fill;
+use crate::pool::Pool;
+use super::{Source, Stream};
+
+/// The minimum block length copied at a time. Short patterns are repeated up to
+/// this length, to avoid copying only a few bytes at a time.
+const BLOCK_LEN: usize = 64;
+
+/// Returns a [`Source`] producing `byte` repeatedly, without end.
+pub fn repeat_source(byte: u8) -> RepeatSource { RepeatSource::new(byte) }
+
+/// Returns a [`Source`] producing `pattern` repeatedly, without end.
+///
+/// # Panics
+///
+/// Panics if `pattern` is empty.
+pub fn pattern_source(pattern: &[u8]) -> PatternSource<'_> { PatternSource::new(pattern) }
+
+/// Returns a [`Source`] producing pseudorandom bytes from `seed`, without end.
+pub fn random_source(seed: u64) -> RandomSource { RandomSource::new(seed) }
+
+/// A [`Source`] producing a single byte repeatedly, without end.
+#[derive(Clone, Debug)]
+pub struct RepeatSource {
+	block: [u8; BLOCK_LEN],
+	closed: bool,
+}
+
+impl RepeatSource {
+	/// Creates a new source producing `byte` repeatedly.
+	pub fn new(byte: u8) -> Self {
+		Self {
+			block: [byte; BLOCK_LEN],
+			closed: false,
+		}
+	}
+
+	/// Returns the repeated byte.
+	pub fn byte(&self) -> u8 { self.block[0] }
+
+	fn generate(&mut self, mut cursor: BorrowedCursor<'_>) {
+		while cursor.capacity() > 0 {
+			let len = cursor.capacity().min(BLOCK_LEN);
+			cursor.append(&self.block[..len]);
+		}
+	}
+}
+
+/// A [`Source`] producing a byte pattern repeatedly, without end.
+#[derive(Clone, Debug)]
+pub struct PatternSource<'p> {
+	pattern: Cow<'p, [u8]>,
+	offset: usize,
+	closed: bool,
+}
+
+impl<'p> PatternSource<'p> {
+	/// Creates a new source producing `pattern` repeatedly.
+	///
+	/// # Panics
+	///
+	/// Panics if `pattern` is empty.
+	pub fn new(pattern: &'p [u8]) -> Self {
+		assert!(!pattern.is_empty(), "pattern should not be empty");
+		let pattern = if pattern.len() < BLOCK_LEN {
+			Cow::Owned(pattern.repeat(BLOCK_LEN.div_ceil(pattern.len())))
+		} else {
+			Cow::Borrowed(pattern)
+		};
+
+		Self {
+			pattern,
+			offset: 0,
+			closed: false,
+		}
+	}
+
+	fn generate(&mut self, mut cursor: BorrowedCursor<'_>) {
+		while cursor.capacity() > 0 {
+			let rest = &self.pattern[self.offset..];
+			let len = cursor.capacity().min(rest.len());
+			cursor.append(&rest[..len]);
+			self.offset = (self.offset + len) % self.pattern.len();
+		}
+	}
+}
+
+/// A [`Source`] producing pseudorandom bytes without end, generated by SplitMix64.
+/// The bytes are determined only by the seed, not by how they're read. They're
+/// *not* suitable for cryptographic use.
+#[derive(Clone, Debug)]
+pub struct RandomSource {
+	state: u64,
+	block: [u8; BLOCK_LEN],
+	offset: usize,
+	closed: bool,
+}
+
+impl RandomSource {
+	/// Creates a new source producing pseudorandom bytes from `seed`.
+	pub fn new(seed: u64) -> Self {
+		Self {
+			state: seed,
+			block: [0; BLOCK_LEN],
+			offset: BLOCK_LEN,
+			closed: false,
+		}
+	}
+
+	/// Generates the next block of bytes.
+	fn next_block(&mut self) {
+		for chunk in self.block.chunks_exact_mut(8) {
+			self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+			let mut z = self.state;
+			z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+			z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+			chunk.copy_from_slice(&(z ^ (z >> 31)).to_le_bytes());
+		}
+		self.offset = 0;
+	}
+
+	fn generate(&mut self, mut cursor: BorrowedCursor<'_>) {
+		while cursor.capacity() > 0 {
+			if self.offset == BLOCK_LEN {
+				self.next_block();
+			}
+
+			let rest = &self.block[self.offset..];
+			let len = cursor.capacity().min(rest.len());
+			cursor.append(&rest[..len]);
+			self.offset += len;
+		}
+	}
+}
+
+/// Fills `sink` with up to `count` bytes written by `generate` directly into its
+/// spare capacity.
+fn fill<'d, const N: usize>(
+	closed: bool,
+	sink: &mut Buffer<'d, N, impl Pool<N>>,
+	count: usize,
+	mut generate: impl FnMut(BorrowedCursor<'_>)
+) -> Result<usize> {
+	if closed {
+		// Obey the closing rule.
+		return Err(Error::closed(Fill))
+	}
+
+	if count == 0 { return Ok(0) }
+	let mut filled = 0;
+	loop {
+		let (written, _) = sink.with_spare_capacity(count - filled, &mut generate).set_context(Fill)?;
+		filled += written;
+		if filled == count || sink.remaining_capacity() == 0 {
+			break Ok(filled)
+		}
+	}
+}
+
+macro_rules! source {
+	($($ty:ty),+) => {
+		$(
+		impl<const N: usize> Stream<N> for $ty {
+			fn is_closed(&self) -> bool {
+				self.closed
+			}
+
+			fn close(&mut self) -> StreamResult {
+				self.closed = true;
+				Ok(())
+			}
+		}
+
+		impl<'d, const N: usize> Source<'d, N> for $ty {
+			/// Returns `false`, as the source never ends.
+			fn is_eos(&self) -> bool { false }
+
+			/// Generates `count` bytes into `sink`.
+			fn fill(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> Result<usize> {
+				fill(self.closed, sink, count, |cursor| self.generate(cursor))
+			}
+
+			/// Generates bytes into free space in `sink`, or at least one segment of
+			/// bytes if it has none. The source never ends, so it can't be read fully.
+			fn fill_all(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>) -> Result<usize> {
+				self.fill(sink, sink.limit().max(N))
+			}
+		}
+		)+
+	};
+}
+
+source!(RepeatSource, PatternSource<'_>, RandomSource);
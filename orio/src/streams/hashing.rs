@@ -2,55 +2,73 @@
 
 #![cfg(feature = "hash")]
 
+mod multi;
+
 use std::marker::PhantomData;
 use std::mem;
 use std::ops::Range;
-use digest::{Digest, FixedOutputReset};
+use digest::{FixedOutput, FixedOutputReset, InvalidLength, KeyInit, Mac, Update};
 use crate::{Buffer, BufferResult, ByteString, ResultContext};
 use crate::pattern::Pattern;
 use crate::pool::Pool;
 use crate::StreamContext::Read;
+#[cfg(feature = "xxhash")]
+use crate::xxhash;
 use super::{Sink, Source, Stream, Result, BufSource, BufStream, Utf8Match, BufSink};
 
+pub use multi::*;
+
 mod sealed {
-	use super::{Digest, Source, Sink};
+	use super::{Update, Source, Sink};
 
 	pub trait HashStream { }
 	impl<'d, H, S, const N: usize> HashStream for super::HashSource<'d, H, S, N>
-	where H: Digest,
+	where H: Update,
 		  S: Source<'d, N> { }
 	impl<'d, H, S, const N: usize> HashStream for super::HashSink<'d, H, S, N>
-	where H: Digest,
+	where H: Update,
 		  S: Sink<'d, N> { }
 }
 
-/// A [`Source`] that hashes data read from its inner source.
+/// A [`Source`] that hashes data read from its inner source. The hasher can be a
+/// hash function ([`Digest`](digest::Digest)) or a message authentication code
+/// ([`Mac`]) such as HMAC, to authenticate data as it's read.
 pub struct HashSource<'d, H, S: Source<'d, N>, const N: usize> {
 	hasher: H,
 	source: Option<S>,
 	__data: PhantomData<&'d ()>
 }
 
-/// A [`Sink`] that hashes data written to its inner sink.
+/// A [`Sink`] that hashes data written to its inner sink. The hasher can be a
+/// hash function ([`Digest`](digest::Digest)) or a message authentication code
+/// ([`Mac`]) such as HMAC, to authenticate data as it's written.
 pub struct HashSink<'d, H, S: Sink<'d, N>, const N: usize> {
 	hasher: H,
 	sink: Option<S>,
 	__data: PhantomData<&'d ()>
 }
 
-pub trait HashStream<H: Digest, S>: sealed::HashStream {
+pub trait HashStream<H: Update, S>: sealed::HashStream {
 	fn new(hasher: H, source: S) -> Self;
 
+	/// Creates a new stream, hashing with a keyed hasher such as a [`Mac`]
+	/// initialized with `key`. Fails if the key length is invalid for the hasher.
+	fn keyed(key: &[u8], stream: S) -> Result<Self, InvalidLength>
+	where Self: Sized,
+		  H: KeyInit {
+		Ok(Self::new(H::new_from_slice(key)?, stream))
+	}
+
 	/// Returns a reference to the hasher.
 	fn hasher(&self) -> &H;
 	/// Returns a mutable reference to the hasher.
 	fn hasher_mut(&mut self) -> &mut H;
 
 	/// Returns a clone of the current hash.
-	fn hash(&self) -> ByteString where H: Clone {
+	fn hash(&self) -> ByteString where H: Clone + FixedOutput {
 		self.hasher()
 			.clone()
-			.finalize()
+			.finalize_fixed()
 			.to_vec()
 			.into()
 	}
@@ -58,15 +76,24 @@ pub trait HashStream<H: Digest, S>: sealed::HashStream {
 	/// Takes and returns the current hash, resetting the hash function state.
 	fn take_hash(&mut self) -> ByteString where H: FixedOutputReset {
 		self.hasher_mut()
-			.finalize_reset()
+			.finalize_fixed_reset()
 			.to_vec()
 			.into()
 	}
 
+	/// Returns whether the current authentication code matches `tag`, without
+	/// resetting the hasher. The comparison is done in constant time.
+	fn verify(&self, tag: &[u8]) -> bool where H: Clone + Mac {
+		self.hasher()
+			.clone()
+			.verify_slice(tag)
+			.is_ok()
+	}
+
 	fn into_inner(self) -> S;
 }
 
-impl<'d, H: Digest, S: Source<'d, N>, const N: usize> HashStream<H, S> for HashSource<'d, H, S, N> {
+impl<'d, H: Update, S: Source<'d, N>, const N: usize> HashStream<H, S> for HashSource<'d, H, S, N> {
 	/// Creates a new hash source, hashing data read from `source` with `hasher`.
 	#[inline]
 	fn new(hasher: H, source: S) -> Self {
@@ -114,7 +141,7 @@ impl<'d, H, S: Source<'d, N>, const N: usize> HashSource<'d, H, S, N> {
 	}
 }
 
-impl<'d, H: Digest, S: Source<'d, N>, const N: usize> HashSource<'d, H, S, N> {
+impl<'d, H: Update, S: Source<'d, N>, const N: usize> HashSource<'d, H, S, N> {
 	fn fill_with<P: Pool<N>>(
 		&mut self,
 		sink: &mut Buffer<'d, N, P>,
@@ -123,12 +150,12 @@ impl<'d, H: Digest, S: Source<'d, N>, const N: usize> HashSource<'d, H, S, N> {
 		let start = sink.count();
 		let count = fill(self.source_mut(), sink)?;
 		let range = start..start + count;
-		sink.hash_in_range(range, &mut self.hasher);
+		sink.update_in_range(range, &mut self.hasher);
 		Ok(count)
 	}
 }
 
-impl<'d, H: Digest, S: Sink<'d, N>, const N: usize> HashStream<H, S> for HashSink<'d, H, S, N> {
+impl<'d, H: Update, S: Sink<'d, N>, const N: usize> HashStream<H, S> for HashSink<'d, H, S, N> {
 	/// Creates a new hash sink, hashing data written to `sink` with `hasher`.
 	#[inline]
 	fn new(hasher: H, sink: S) -> Self {
@@ -192,7 +219,7 @@ impl<'d, H, S: Source<'d, N>, const N: usize> Stream<N> for HashSource<'d, H, S,
 	}
 }
 
-impl<'d, H: Digest, S: Source<'d, N>, const N: usize> Source<'d, N> for HashSource<'d, H, S, N> {
+impl<'d, H: Update, S: Source<'d, N>, const N: usize> Source<'d, N> for HashSource<'d, H, S, N> {
 	fn is_eos(&self) -> bool {
 		self.source().is_eos()
 	}
@@ -222,7 +249,7 @@ impl<'d, H, S: BufSource<'d, N>, const N: usize> BufStream<'d, N> for HashSource
 	}
 }
 
-impl<'d, H: Clone + Default + Digest, S: BufSource<'d, N>, const N: usize> BufSource<'d, N> for HashSource<'d, H, S, N> {
+impl<'d, H: Clone + Update, S: BufSource<'d, N>, const N: usize> BufSource<'d, N> for HashSource<'d, H, S, N> {
 	fn available(&self) -> usize {
 		self.source().available()
 	}
@@ -236,7 +263,7 @@ impl<'d, H: Clone + Default + Digest, S: BufSource<'d, N>, const N: usize> BufSo
 		count = count.min(self.available());
 		let mut sink = HashSink::new(self.hasher.clone(), sink);
 		let result = sink.drain(self.buf_mut(), count);
-		self.hasher = mem::take(&mut sink.hasher);
+		mem::swap(&mut self.hasher, &mut sink.hasher);
 		let _ = sink.into_inner(); // Prevent the sink from closing on drop.
 		result.context(Read)
 	}
@@ -244,7 +271,7 @@ impl<'d, H: Clone + Default + Digest, S: BufSource<'d, N>, const N: usize> BufSo
 	fn read_all(&mut self, sink: &mut impl Sink<'d, N>) -> Result<usize> {
 		let mut sink = HashSink::new(self.hasher.clone(), sink);
 		let result = sink.drain_all(self.buf_mut());
-		self.hasher = mem::take(&mut sink.hasher);
+		mem::swap(&mut self.hasher, &mut sink.hasher);
 		let _ = sink.into_inner(); // Prevent the sink from closing on drop.
 		result.context(Read)
 	}
@@ -262,15 +289,22 @@ impl<'d, H: Clone + Default + Digest, S: BufSource<'d, N>, const N: usize> BufSo
 		Ok(count)
 	}
 
+	fn read_to_vec(&mut self, vec: &mut Vec<u8>, count: usize) -> Result<usize> {
+		let start = vec.len();
+		let count = self.source_mut().read_to_vec(vec, count)?;
+		self.hasher.update(&vec[start..]);
+		Ok(count)
+	}
+
 	fn read_utf8<'s>(&mut self, buf: &'s mut String, count: usize) -> Result<&'s str> {
 		let str = self.source_mut().read_utf8(buf, count)?;
-		self.hasher.update(str);
+		self.hasher.update(str.as_bytes());
 		Ok(str)
 	}
 
 	fn read_utf8_to_end<'s>(&mut self, buf: &'s mut String) -> Result<&'s str> {
 		let str = self.source_mut().read_utf8_to_end(buf)?;
-		self.hasher.update(str);
+		self.hasher.update(str.as_bytes());
 		Ok(str)
 	}
 
@@ -278,7 +312,7 @@ impl<'d, H: Clone + Default + Digest, S: BufSource<'d, N>, const N: usize> BufSo
 		let start = buf.len();
 		let r#match = self.source_mut().read_utf8_line(buf)?;
 		let range = start..start + r#match.read_count.min(buf.len());
-		self.hasher.update(&buf[range]);
+		self.hasher.update(buf[range].as_bytes());
 		Ok(r#match)
 	}
 
@@ -286,7 +320,14 @@ impl<'d, H: Clone + Default + Digest, S: BufSource<'d, N>, const N: usize> BufSo
 		let start = buf.len();
 		let r#match = self.source_mut().read_utf8_line_inclusive(buf)?;
 		let range = start..start + r#match.read_count.min(buf.len());
-		self.hasher.update(&buf[range]);
+		self.hasher.update(buf[range].as_bytes());
+		Ok(r#match)
+	}
+
+	fn read_line_bytes(&mut self, buf: &mut ByteString) -> Result<Utf8Match> {
+		let start = buf.len();
+		let r#match = self.source_mut().read_line_bytes(buf)?;
+		self.hasher.update(&buf.as_slice()[start..]);
 		Ok(r#match)
 	}
 
@@ -294,7 +335,7 @@ impl<'d, H: Clone + Default + Digest, S: BufSource<'d, N>, const N: usize> BufSo
 		let start = buf.len();
 		let r#match = self.source_mut().read_utf8_until(buf, terminator)?;
 		let range = start..start + r#match.read_count.min(buf.len());
-		self.hasher.update(&buf[range]);
+		self.hasher.update(buf[range].as_bytes());
 		Ok(r#match)
 	}
 
@@ -302,7 +343,7 @@ impl<'d, H: Clone + Default + Digest, S: BufSource<'d, N>, const N: usize> BufSo
 		let start = buf.len();
 		let r#match = self.source_mut().read_utf8_until_inclusive(buf, terminator)?;
 		let range = start..start + r#match.read_count.min(buf.len());
-		self.hasher.update(&buf[range]);
+		self.hasher.update(buf[range].as_bytes());
 		Ok(r#match)
 	}
 }
@@ -332,7 +373,7 @@ impl<'d, H, S: Sink<'d, N>, const N: usize> Stream<N> for HashSink<'d, H, S, N>
 	}
 }
 
-impl<'d, H: Digest, S: Sink<'d, N>, const N: usize> Sink<'d, N> for HashSink<'d, H, S, N> {
+impl<'d, H: Update, S: Sink<'d, N>, const N: usize> Sink<'d, N> for HashSink<'d, H, S, N> {
 	fn drain(&mut self, source: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
 		let mut clone = source.range(..count.min(source.count()));
 		// Give sink the cloned buffer instead of the full source buffer, in case
@@ -340,7 +381,7 @@ impl<'d, H: Digest, S: Sink<'d, N>, const N: usize> Sink<'d, N> for HashSink<'d,
 		// count. In this scenario, if the clone was hashed instead, this wouldn't
 		// hash all the data written to the sink.
 		let count = self.sink_mut().drain(&mut clone, count)?;
-		source.hash_in_range(..count, &mut self.hasher);
+		source.update_in_range(..count, &mut self.hasher);
 		source.skip(count);
 		Ok(count)
 	}
@@ -360,7 +401,7 @@ impl<'d, H: Digest, S: Sink<'d, N>, const N: usize> Sink<'d, N> for HashSink<'d,
 	}
 }
 
-impl<'d, H: Digest, S: BufSink<'d, N>, const N: usize> HashSink<'d, H, S, N> {
+impl<'d, H: Update, S: BufSink<'d, N>, const N: usize> HashSink<'d, H, S, N> {
 	fn hash_buf(&mut self, range: Range<usize>) {
 		let Self { sink, hasher, .. } = self;
 		// Can't use Self::buf because it would borrow self immutably, but we also
@@ -370,7 +411,7 @@ impl<'d, H: Digest, S: BufSink<'d, N>, const N: usize> HashSink<'d, H, S, N> {
 			// Safety: see Self::sink.
 			sink.as_ref().unwrap_unchecked()
 		};
-		sink.buf().hash_in_range(range, hasher);
+		sink.buf().update_in_range(range, hasher);
 	}
 }
 
@@ -386,7 +427,7 @@ impl<'d, H, S: BufSink<'d, N>, const N: usize> BufStream<'d, N> for HashSink<'d,
 	}
 }
 
-impl<'d, H: Digest, S: BufSink<'d, N>, const N: usize> BufSink<'d, N> for HashSink<'d, H, S, N> {
+impl<'d, H: Update, S: BufSink<'d, N>, const N: usize> BufSink<'d, N> for HashSink<'d, H, S, N> {
 	fn write(&mut self, source: &mut impl Source<'d, N>, count: usize) -> Result<usize> {
 		let start = self.buf().count();
 		let count = source.fill(self.buf_mut(), count)?;
@@ -501,6 +542,31 @@ macro_rules! hash {
 			}
 		}
 	};
+    (fast $module:ident::$ty:ident$feature:literal$name:literal$method:ident) => {
+		#[cfg(feature = $feature)]
+		impl<'d, S: Source<'d, N>, const N: usize> HashSource<'d, $module::$ty, S, N> {
+			/// Creates a new hash source, hashing data read from `source` with
+			#[doc = concat!($name, ".")]
+			/// This hash function is fast, but not designed for cryptography; its
+			/// use there is ***not*** secure. Use for checksums and deduplication only.
+			#[inline]
+			pub fn $method(source: S) -> Self {
+				source.into()
+			}
+		}
+
+		#[cfg(feature = $feature)]
+		impl<'d, S: Sink<'d, N>, const N: usize> HashSink<'d, $module::$ty, S, N> {
+			/// Creates a new hash sink, hashing data written to `sink` with
+			#[doc = concat!($name, ".")]
+			/// This hash function is fast, but not designed for cryptography; its
+			/// use there is ***not*** secure. Use for checksums and deduplication only.
+			#[inline]
+			pub fn $method(sink: S) -> Self {
+				sink.into()
+			}
+		}
+	};
 }
 
 hash! {
@@ -551,20 +617,96 @@ hash! {
 	"Whirlpool" whirlpool Whirlpool
 }
 
-impl<'d, H: Digest + FixedOutputReset, S: Source<'d, N>, const N: usize> From<S> for HashSource<'d, H, S, N> {
+hash! {
+	secure "blake3" blake3
+	"BLAKE3" blake3 Hasher
+}
+
+hash! {
+	fast "xxhash" xxhash
+	"XXH3-64" xxh3_64 Xxh3_64
+	"XXH3-128" xxh3_128 Xxh3_128
+	"XXH64" xxh64 Xxh64
+}
+
+#[cfg(feature = "blake3")]
+impl<'d, S: Source<'d, N>, const N: usize> HashSource<'d, blake3::Hasher, S, N> {
+	/// Creates a new hash source, authenticating data read from `source` with
+	/// BLAKE3 in keyed mode.
+	#[inline]
+	pub fn blake3_keyed(key: &[u8; 32], source: S) -> Self {
+		Self::new(blake3::Hasher::new_keyed(key), source)
+	}
+}
+
+#[cfg(feature = "blake3")]
+impl<'d, S: Sink<'d, N>, const N: usize> HashSink<'d, blake3::Hasher, S, N> {
+	/// Creates a new hash sink, authenticating data written to `sink` with BLAKE3
+	/// in keyed mode.
+	#[inline]
+	pub fn blake3_keyed(key: &[u8; 32], sink: S) -> Self {
+		Self::new(blake3::Hasher::new_keyed(key), sink)
+	}
+}
+
+macro_rules! hmac {
+    ($feature:literal$module:ident$($name:literal$method:ident$ty:ident)+) => {
+		$(
+		#[cfg(all(feature = "hmac", feature = $feature))]
+		impl<'d, S: Source<'d, N>, const N: usize> HashSource<'d, hmac::Hmac<$module::$ty>, S, N> {
+			/// Creates a new hash source, authenticating data read from `source` with
+			#[doc = concat!("HMAC-", $name)]
+			/// keyed with `key`.
+			#[inline]
+			pub fn $method(key: &[u8], source: S) -> Self {
+				Self::keyed(key, source).expect("HMAC should accept keys of any length")
+			}
+		}
+
+		#[cfg(all(feature = "hmac", feature = $feature))]
+		impl<'d, S: Sink<'d, N>, const N: usize> HashSink<'d, hmac::Hmac<$module::$ty>, S, N> {
+			/// Creates a new hash sink, authenticating data written to `sink` with
+			#[doc = concat!("HMAC-", $name)]
+			/// keyed with `key`.
+			#[inline]
+			pub fn $method(key: &[u8], sink: S) -> Self {
+				Self::keyed(key, sink).expect("HMAC should accept keys of any length")
+			}
+		}
+		)+
+	};
+}
+
+hmac! {
+	"sha2" sha2
+	"SHA-224" hmac_sha224 Sha224
+	"SHA-256" hmac_sha256 Sha256
+	"SHA-384" hmac_sha384 Sha384
+	"SHA-512" hmac_sha512 Sha512
+}
+
+hmac! {
+	"sha3" sha3
+	"SHA3-224" hmac_sha3_224 Sha3_224
+	"SHA3-256" hmac_sha3_256 Sha3_256
+	"SHA3-384" hmac_sha3_384 Sha3_384
+	"SHA3-512" hmac_sha3_512 Sha3_512
+}
+
+impl<'d, H: Default + Update, S: Source<'d, N>, const N: usize> From<S> for HashSource<'d, H, S, N> {
 	/// Creates a new hash sink, hashing data read from `source` with hash function
 	/// [`H`].
 	#[inline]
 	fn from(source: S) -> Self {
-		Self::new(H::new(), source)
+		Self::new(H::default(), source)
 	}
 }
 
-impl<'d, H: Digest + FixedOutputReset, S: Sink<'d, N>, const N: usize> From<S> for HashSink<'d, H, S, N> {
+impl<'d, H: Default + Update, S: Sink<'d, N>, const N: usize> From<S> for HashSink<'d, H, S, N> {
 	/// Creates a new hash sink, hashing data written to `sink` with hash function
 	/// [`H`].
 	#[inline]
 	fn from(sink: S) -> Self {
-		Self::new(H::new(), sink)
+		Self::new(H::default(), sink)
 	}
 }
@@ -0,0 +1,119 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::any::TypeId;
+use digest::{DynDigest, Update};
+use crate::{ByteString, SIZE};
+use super::{HashSink, HashSource};
+
+/// A [`HashSource`] hashing data with several hash functions in one pass.
+pub type MultiHashSource<'d, S, const N: usize = SIZE> = HashSource<'d, MultiHasher, S, N>;
+/// A [`HashSink`] hashing data with several hash functions in one pass.
+pub type MultiHashSink<'d, S, const N: usize = SIZE> = HashSink<'d, MultiHasher, S, N>;
+
+/// A hasher updating several hash functions at once, so multiple hashes of the
+/// same data can be computed in one pass. Hashes are retrieved by the index the
+/// hash function was added at, or by its type.
+///
+/// ```
+/// # use orio::streams::{HashStream, MultiHasher, MultiHashSource, Source};
+/// # use orio::{Buffer, DefaultBuffer};
+/// # use sha2::{Sha256, Sha512};
+/// let hasher = MultiHasher::new().with(Sha256::default()).with(Sha512::default());
+/// let mut source = MultiHashSource::new(hasher, Buffer::from_slice(b"data"));
+/// source.fill_all(&mut DefaultBuffer::default()).unwrap();
+/// let sha256 = source.hasher().hash_of::<Sha256>().unwrap();
+/// let sha512 = source.hasher().hash(1).unwrap();
+/// ```
+#[derive(Default)]
+pub struct MultiHasher {
+	hashers: Vec<(TypeId, Box<dyn DynDigest>)>,
+}
+
+impl MultiHasher {
+	/// Creates a new hasher with no hash functions.
+	#[inline]
+	pub fn new() -> Self { Self::default() }
+
+	/// Adds a hash function, returning the hasher.
+	#[inline]
+	pub fn with<D: DynDigest + 'static>(mut self, hasher: D) -> Self {
+		self.push(hasher);
+		self
+	}
+
+	/// Adds a hash function, returning its index.
+	pub fn push<D: DynDigest + 'static>(&mut self, hasher: D) -> usize {
+		self.hashers.push((TypeId::of::<D>(), Box::new(hasher)));
+		self.hashers.len() - 1
+	}
+
+	/// Returns the number of hash functions.
+	#[inline]
+	pub fn len(&self) -> usize { self.hashers.len() }
+
+	/// Returns `true` if there are no hash functions.
+	#[inline]
+	pub fn is_empty(&self) -> bool { self.hashers.is_empty() }
+
+	/// Returns the current hash of the hash function at `index`, or `None` if the
+	/// index is out of bounds.
+	pub fn hash(&self, index: usize) -> Option<ByteString> {
+		let (_, hasher) = self.hashers.get(index)?;
+		Some(finalize(hasher.box_clone()))
+	}
+
+	/// Returns the current hash of the first hash function of type `D`, or `None`
+	/// if there is none.
+	pub fn hash_of<D: 'static>(&self) -> Option<ByteString> {
+		let index = self.hashers.iter().position(|&(id, _)| id == TypeId::of::<D>())?;
+		self.hash(index)
+	}
+
+	/// Returns the current hashes of all hash functions, in the order they were
+	/// added.
+	pub fn hashes(&self) -> Vec<ByteString> {
+		self.hashers
+			.iter()
+			.map(|(_, hasher)| finalize(hasher.box_clone()))
+			.collect()
+	}
+
+	/// Takes and returns the current hashes of all hash functions, in the order
+	/// they were added, resetting their states.
+	pub fn take_hashes(&mut self) -> Vec<ByteString> {
+		self.hashers
+			.iter_mut()
+			.map(|(_, hasher)| Vec::from(hasher.finalize_reset()).into())
+			.collect()
+	}
+
+	/// Resets the states of all hash functions.
+	pub fn reset(&mut self) {
+		for (_, hasher) in &mut self.hashers {
+			hasher.reset();
+		}
+	}
+}
+
+fn finalize(hasher: Box<dyn DynDigest>) -> ByteString {
+	Vec::from(hasher.finalize()).into()
+}
+
+impl Clone for MultiHasher {
+	fn clone(&self) -> Self {
+		Self {
+			hashers: self.hashers
+						 .iter()
+						 .map(|&(id, ref hasher)| (id, hasher.box_clone()))
+						 .collect()
+		}
+	}
+}
+
+impl Update for MultiHasher {
+	fn update(&mut self, data: &[u8]) {
+		for (_, hasher) in &mut self.hashers {
+			hasher.update(data);
+		}
+	}
+}
@@ -0,0 +1,516 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! HTTP/1.1 message framing: zero-copy header block parsing over [`BufSource`]s,
+//! and chunked transfer coding.
+
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::Range;
+use crate::{Buffer, BufferResult, ByteStr, ByteString, ParseBytes, ResultContext, SIZE, StreamError};
+use crate::BufferContext::{Drain, Fill};
+use crate::pattern::LineTerminator;
+use crate::pool::Pool;
+use crate::StreamContext::{Read, Write};
+use super::{invalid_data, request_more, BufSink, BufSource, Result, Sink, Source, Stream};
+
+/// A header field, borrowing its name and value from the source buffer.
+#[derive(Clone, Debug)]
+pub struct Header<'b> {
+	/// The field name.
+	pub name: ByteStr<'b>,
+	/// The field value, without leading and trailing whitespace.
+	pub value: ByteStr<'b>,
+}
+
+/// A header block read by [`read_headers`]. Header names and values are borrowed
+/// from the source buffer, which holds the whole block. The block is consumed from
+/// the source when dropped, or explicitly with [`consume`].
+///
+/// [`consume`]: Self::consume
+pub struct HeaderBlock<'s, 'd, S: BufSource<'d, N>, const N: usize = SIZE> {
+	source: &'s mut S,
+	/// The name and value ranges of each field.
+	fields: Vec<(Range<usize>, Range<usize>)>,
+	/// The length of the block in bytes, including the empty line ending it.
+	len: usize,
+	__data: PhantomData<&'d ()>
+}
+
+/// Reads a header block, a sequence of `name: value` lines ending with an empty
+/// line, from `source`. Lines may end with `"\r\n"` or `'\n'`. The start line
+/// of a request or response must be read first, for example with
+/// [`read_utf8_line`].
+///
+/// The block is buffered in the source until the returned [`HeaderBlock`] is
+/// dropped. Blocks longer than `max_size` bytes, including line terminators, fail
+/// with an [`InvalidData`] error before more than `max_size` bytes are requested.
+/// Obsolete line folding and lines ending with a lone `'\r'` are rejected.
+///
+/// [`read_utf8_line`]: BufSource::read_utf8_line
+/// [`InvalidData`]: std::io::ErrorKind::InvalidData
+pub fn read_headers<'s, 'd, S: BufSource<'d, N>, const N: usize>(
+	source: &'s mut S,
+	max_size: usize
+) -> Result<HeaderBlock<'s, 'd, S, N>> {
+	let mut fields = Vec::new();
+	let mut pos = 0;
+	loop {
+		let Range { start, end } = find_line(source, pos, max_size)?;
+		let buf = source.buf();
+		if start == pos {
+			return Ok(HeaderBlock {
+				source,
+				fields,
+				len: end,
+				__data: PhantomData
+			})
+		}
+
+		fields.push(parse_field(buf, pos..start)?);
+		pos = end;
+	}
+}
+
+impl<'d, S: BufSource<'d, N>, const N: usize> HeaderBlock<'_, 'd, S, N> {
+	/// Returns the number of header fields.
+	#[inline]
+	pub fn count(&self) -> usize { self.fields.len() }
+	/// Returns `true` if the block has no header fields.
+	#[inline]
+	pub fn is_empty(&self) -> bool { self.fields.is_empty() }
+	/// Returns the length of the block in bytes, including line terminators.
+	#[inline]
+	pub fn size(&self) -> usize { self.len }
+
+	/// Returns an iterator over header fields, in the order they were read.
+	pub fn iter(&self) -> impl Iterator<Item = Header<'_>> {
+		let str = self.source.buf().as_byte_str();
+		self.fields.iter().map(move |(name, value)|
+			Header {
+				name: str.range(name.clone()),
+				value: str.range(value.clone()),
+			}
+		)
+	}
+
+	/// Returns the value of the first field named `name`, compared ignoring ASCII
+	/// case.
+	pub fn get(&self, name: &str) -> Option<ByteStr<'_>> {
+		let buf = self.source.buf();
+		let (_, value) = self.fields.iter().find(|(range, _)|
+			range.len() == name.len() &&
+			range.clone()
+				 .zip(name.bytes())
+				 .all(|(i, b)| buf.get(i).is_some_and(|c| c.eq_ignore_ascii_case(&b)))
+		)?;
+		Some(buf.as_byte_str().range(value.clone()))
+	}
+
+	/// Consumes the block from the source.
+	pub fn consume(mut self) -> Result {
+		let len = self.len;
+		self.len = 0;
+		self.source.skip(len)?;
+		Ok(())
+	}
+}
+
+impl<'d, S: BufSource<'d, N>, const N: usize> Drop for HeaderBlock<'_, 'd, S, N> {
+	fn drop(&mut self) {
+		if self.len > 0 {
+			let _ = self.source.skip(self.len);
+		}
+	}
+}
+
+/// The default maximum size of chunk size lines and trailer blocks.
+const MAX_LINE_SIZE: usize = 8192;
+
+/// A [`Source`] decoding HTTP/1.1 chunked transfer coding from its inner source.
+/// Chunk extensions are ignored. Trailer fields following the last chunk are read
+/// into [`trailers`].
+///
+/// [`trailers`]: Self::trailers
+pub struct ChunkedSource<'d, S: BufSource<'d, N>, const N: usize = SIZE> {
+	source: Option<S>,
+	/// The number of bytes remaining in the current chunk.
+	remaining: usize,
+	/// Whether a chunk's data was read, and its terminator is expected next.
+	data_read: bool,
+	finished: bool,
+	max_trailer_size: usize,
+	trailers: Vec<(ByteString, ByteString)>,
+	__data: PhantomData<&'d ()>
+}
+
+/// A [`Sink`] encoding data written to it with HTTP/1.1 chunked transfer coding,
+/// writing each drained range of bytes as one chunk. The last chunk is written by
+/// [`finish`], or when the sink is closed.
+///
+/// [`finish`]: Self::finish
+pub struct ChunkedSink<'d, S: BufSink<'d, N>, const N: usize = SIZE> {
+	sink: Option<S>,
+	finished: bool,
+	__data: PhantomData<&'d ()>
+}
+
+impl<'d, S: BufSource<'d, N>, const N: usize> From<S> for ChunkedSource<'d, S, N> {
+	fn from(source: S) -> Self {
+		Self::new(source)
+	}
+}
+
+impl<'d, S: BufSink<'d, N>, const N: usize> From<S> for ChunkedSink<'d, S, N> {
+	fn from(sink: S) -> Self {
+		Self::new(sink)
+	}
+}
+
+impl<'d, S: BufSource<'d, N>, const N: usize> ChunkedSource<'d, S, N> {
+	/// Creates a new chunked source, decoding from `source`.
+	pub fn new(source: S) -> Self {
+		Self {
+			source: Some(source),
+			remaining: 0,
+			data_read: false,
+			finished: false,
+			max_trailer_size: MAX_LINE_SIZE,
+			trailers: Vec::new(),
+			__data: PhantomData
+		}
+	}
+
+	/// Sets the maximum size of the trailer block, including line terminators.
+	/// Defaults to 8KiB.
+	pub fn with_max_trailer_size(mut self, value: usize) -> Self {
+		self.max_trailer_size = value;
+		self
+	}
+
+	/// Returns the trailer fields read after the last chunk, in the order they
+	/// were read. This is empty until the end of the stream is reached.
+	pub fn trailers(&self) -> &[(ByteString, ByteString)] {
+		&self.trailers
+	}
+
+	/// Returns a reference to the inner source.
+	pub fn source(&self) -> &S {
+		unsafe {
+			// Safety: option will only be None if into_inner is called, but this
+			// consumes and drops self, making it impossible to ever have a
+			// reference (except on drop, which is guarded).
+			self.source.as_ref().unwrap_unchecked()
+		}
+	}
+
+	/// Returns a mutable reference to the inner source.
+	pub fn source_mut(&mut self) -> &mut S {
+		unsafe {
+			// Safety: see `source`
+			self.source.as_mut().unwrap_unchecked()
+		}
+	}
+
+	/// Consumes the chunked source, returning the inner source. Data following
+	/// the chunked body is left unread.
+	pub fn into_inner(mut self) -> S {
+		unsafe {
+			// Safety: option will only be None if this method was already called,
+			// which is impossible because we consume self.
+			self.source.take().unwrap_unchecked()
+		}
+	}
+
+	/// Reads the next chunk size line, or the trailers if the last chunk was
+	/// reached.
+	fn read_chunk_header(&mut self) -> Result {
+		let max_trailer_size = self.max_trailer_size;
+		let data_read = mem::take(&mut self.data_read);
+		let source = self.source_mut();
+		if data_read {
+			let Range { start, end } = find_line(source, 0, 2)?;
+			if start > 0 {
+				return Err(invalid_data("missing chunk terminator"))
+			}
+			source.skip(end)?;
+		}
+
+		let Range { start, end } = find_line(source, 0, MAX_LINE_SIZE)?;
+		let buf = source.buf();
+		let size_end = buf.find_in_range(b';', ..start).map_or(start, |r| r.start);
+		let size = buf.as_byte_str()
+					  .range(trim(buf, 0..size_end))
+					  .parse_hex_int::<usize>()
+					  .map_err(|_| invalid_data("invalid chunk size"))?;
+		source.skip(end)?;
+
+		if size == 0 {
+			let headers = read_headers(source, max_trailer_size)?;
+			let trailers = headers.iter()
+								  .map(|Header { name, value }| (name.to_byte_string(), value.to_byte_string()))
+								  .collect();
+			headers.consume()?;
+			self.trailers = trailers;
+			self.finished = true;
+		} else {
+			self.remaining = size;
+		}
+		Ok(())
+	}
+}
+
+impl<'d, S: BufSink<'d, N>, const N: usize> ChunkedSink<'d, S, N> {
+	/// Creates a new chunked sink, encoding to `sink`.
+	pub fn new(sink: S) -> Self {
+		Self {
+			sink: Some(sink),
+			finished: false,
+			__data: PhantomData
+		}
+	}
+
+	/// Returns a reference to the inner sink.
+	pub fn sink(&self) -> &S {
+		unsafe {
+			// Safety: option will only be None if into_inner is called, but this
+			// consumes and drops self, making it impossible to ever have a
+			// reference (except on drop, which is guarded).
+			self.sink.as_ref().unwrap_unchecked()
+		}
+	}
+
+	/// Returns a mutable reference to the inner sink.
+	pub fn sink_mut(&mut self) -> &mut S {
+		unsafe {
+			// Safety: see `sink`
+			self.sink.as_mut().unwrap_unchecked()
+		}
+	}
+
+	/// Consumes the chunked sink, returning the inner sink. The last chunk is not
+	/// written unless [`finish`] was called.
+	///
+	/// [`finish`]: Self::finish
+	pub fn into_inner(mut self) -> S {
+		unsafe {
+			// Safety: option will only be None if this method was already called,
+			// which is impossible because we consume self.
+			self.sink.take().unwrap_unchecked()
+		}
+	}
+
+	/// Writes the last chunk with no trailer fields, ending the chunked body.
+	/// Subsequent writes fail with a "closed" error.
+	pub fn finish(&mut self) -> Result {
+		self.finish_with_trailers::<&str, &str>([])
+	}
+
+	/// Writes the last chunk followed by trailer `fields`, ending the chunked body.
+	/// Subsequent writes fail with a "closed" error.
+	pub fn finish_with_trailers<K: AsRef<str>, V: AsRef<str>>(
+		&mut self,
+		fields: impl IntoIterator<Item = (K, V)>
+	) -> Result {
+		self.check_open(Write)?;
+		self.finished = true;
+		let sink = self.sink_mut();
+		sink.write_utf8("0\r\n")?;
+		for (name, value) in fields {
+			sink.write_utf8(name.as_ref())?;
+			sink.write_utf8(": ")?;
+			sink.write_utf8(value.as_ref())?;
+			sink.write_utf8("\r\n")?;
+		}
+		sink.write_utf8("\r\n")?;
+		sink.flush()
+	}
+}
+
+impl<'d, S: BufSource<'d, N>, const N: usize> Stream<N> for ChunkedSource<'d, S, N> {
+	fn is_closed(&self) -> bool {
+		self.source().is_closed()
+	}
+
+	fn close(&mut self) -> Result {
+		self.source_mut().close()
+	}
+}
+
+impl<'d, S: BufSource<'d, N>, const N: usize> Source<'d, N> for ChunkedSource<'d, S, N> {
+	#[inline]
+	fn is_eos(&self) -> bool { self.finished }
+
+	/// Decodes up to `count` bytes from the current chunk into `sink`, reading the
+	/// next chunk size line first if the current chunk is exhausted.
+	///
+	/// # Errors
+	///
+	/// If the chunk framing is malformed, an IO error of kind [`InvalidData`] is
+	/// returned. If the inner source ends before the last chunk, an end-of-stream
+	/// error is returned.
+	///
+	/// [`InvalidData`]: std::io::ErrorKind::InvalidData
+	fn fill(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		if self.finished || count == 0 {
+			return Ok(0)
+		}
+
+		if self.remaining == 0 {
+			self.read_chunk_header().context(Fill)?;
+			if self.finished {
+				return Ok(0)
+			}
+		}
+
+		let count = count.min(self.remaining);
+		let source = self.source_mut();
+		source.require(1).context(Fill)?;
+		let read = source.read(sink, count).context(Fill)?;
+		self.remaining -= read;
+		self.data_read = self.remaining == 0;
+		Ok(read)
+	}
+
+	/// Decodes all remaining chunks into `sink`, reading the trailers.
+	fn fill_all(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>) -> BufferResult<usize> {
+		let mut total = 0;
+		while !self.finished {
+			total += self.fill(sink, N)?;
+		}
+		Ok(total)
+	}
+}
+
+impl<'d, S: BufSource<'d, N>, const N: usize> Drop for ChunkedSource<'d, S, N> {
+	fn drop(&mut self) {
+		// If into_inner was called, closing would cause a seg fault.
+		if self.source.is_some() {
+			let _ = self.close();
+		}
+	}
+}
+
+impl<'d, S: BufSink<'d, N>, const N: usize> Stream<N> for ChunkedSink<'d, S, N> {
+	fn is_closed(&self) -> bool {
+		self.finished || self.sink().is_closed()
+	}
+
+	/// Writes the last chunk if not yet written, then closes the inner sink.
+	fn close(&mut self) -> Result {
+		let finish_result = if self.finished { Ok(()) } else { self.finish() };
+		let close_result = self.sink_mut().close();
+		finish_result?;
+		close_result
+	}
+}
+
+impl<'d, S: BufSink<'d, N>, const N: usize> Sink<'d, N> for ChunkedSink<'d, S, N> {
+	/// Writes up to `count` bytes from `source` as one chunk.
+	fn drain(&mut self, source: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		self.check_open(Drain)?;
+		let count = count.min(source.count());
+		if count == 0 {
+			return Ok(0)
+		}
+
+		let sink = self.sink_mut();
+		sink.write_utf8(&format!("{count:x}\r\n")).context(Drain)?;
+		Sink::drain(sink.buf_mut(), source, count).context(Drain)?;
+		sink.write_utf8("\r\n").context(Drain)?;
+		Ok(count)
+	}
+
+	fn flush(&mut self) -> Result {
+		self.sink_mut().flush()
+	}
+}
+
+impl<'d, S: BufSink<'d, N>, const N: usize> Drop for ChunkedSink<'d, S, N> {
+	fn drop(&mut self) {
+		// If into_inner was called, closing would cause a seg fault.
+		if self.sink.is_some() {
+			let _ = self.close();
+		}
+	}
+}
+
+/// Finds the terminator of the line starting at `pos`, reading from `source` until
+/// one is found. Lines end with `"\r\n"` or `'\n'`; a lone `'\r'` fails with an
+/// [`InvalidData`] error. Fails if the line would end past `max_size`.
+///
+/// [`InvalidData`]: std::io::ErrorKind::InvalidData
+pub(super) fn find_line<'d, const N: usize>(
+	source: &mut impl BufSource<'d, N>,
+	pos: usize,
+	max_size: usize
+) -> Result<Range<usize>> {
+	// Bytes before this offset are known not to contain a terminator.
+	let mut scan = pos;
+	loop {
+		let buf = source.buf();
+		let Some(range) = buf.find_in_range(LineTerminator, scan..) else {
+			let count = buf.count();
+			if count >= max_size {
+				return Err(too_large())
+			}
+
+			scan = count;
+			if !request_more(source)? {
+				return Err(StreamError::end_of_stream(count + 1, Read))
+			}
+			continue
+		};
+
+		// Ranges are relative to the start of the searched range.
+		let start = scan + range.start;
+		let end = scan + range.end;
+		if end > max_size {
+			return Err(too_large())
+		}
+
+		if range.len() == 1 && buf.get(start) == Some(b'\r') {
+			// A carriage return at the end of the buffer may be followed by a line
+			// feed not yet read.
+			if end == buf.count() && request_more(source)? {
+				scan = start;
+				continue
+			}
+			return Err(invalid_data("line ends with a lone carriage return"))
+		}
+		return Ok(start..end)
+	}
+}
+
+/// Splits a field line into name and value ranges.
+fn parse_field<const N: usize>(
+	buf: &Buffer<'_, N, impl Pool<N>>,
+	line: Range<usize>
+) -> Result<(Range<usize>, Range<usize>)> {
+	let Some(colon) = buf.find_in_range(b':', line.clone()) else {
+		return Err(invalid_data("header field missing colon"))
+	};
+	let colon = line.start + colon.start;
+	if colon == line.start || is_whitespace(buf, line.start) || is_whitespace(buf, colon - 1) {
+		return Err(invalid_data("invalid header field name"))
+	}
+	Ok((line.start..colon, trim(buf, colon + 1..line.end)))
+}
+
+/// Removes leading and trailing spaces and tabs from a range.
+pub(super) fn trim<const N: usize>(buf: &Buffer<'_, N, impl Pool<N>>, Range { mut start, mut end }: Range<usize>) -> Range<usize> {
+	while start < end && is_whitespace(buf, start) {
+		start += 1;
+	}
+	while end > start && is_whitespace(buf, end - 1) {
+		end -= 1;
+	}
+	start..end
+}
+
+fn is_whitespace<const N: usize>(buf: &Buffer<'_, N, impl Pool<N>>, pos: usize) -> bool {
+	matches!(buf.get(pos), Some(b' ' | b'\t'))
+}
+
+fn too_large() -> StreamError {
+	invalid_data("header block too large")
+}
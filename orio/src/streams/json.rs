@@ -0,0 +1,435 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! A minimal, zero-copy JSON tokenizer over [`BufSource`]s. With the `serde`
+//! feature, [newline-delimited JSON][NDJSON] values can also be read from and
+//! written to buffered streams.
+//!
+//! [NDJSON]: https://github.com/ndjson/ndjson-spec
+
+use std::marker::PhantomData;
+use crate::{ByteStr, SIZE, StreamError};
+use crate::StreamContext::Read;
+use super::{invalid_data, BufSource, Result};
+
+/// A structural JSON token.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum JsonToken<'b> {
+	/// An object start, `{`.
+	ObjectStart,
+	/// An object end, `}`.
+	ObjectEnd,
+	/// An array start, `[`.
+	ArrayStart,
+	/// An array end, `]`.
+	ArrayEnd,
+	/// A key-value separator, `:`.
+	Colon,
+	/// A value separator, `,`.
+	Comma,
+	/// A string, containing the raw bytes between its quotes. Escape sequences are
+	/// not decoded.
+	String(ByteStr<'b>),
+	/// A number, containing its raw bytes. The number is not validated.
+	Number(ByteStr<'b>),
+	/// A boolean literal, `true` or `false`.
+	Bool(bool),
+	/// A `null` literal.
+	Null,
+}
+
+/// A tokenizer reading JSON tokens from a [`BufSource`] without materializing the
+/// document. String and number tokens borrow their bytes from the source buffer,
+/// across segment boundaries if needed; the buffer holds at most one token at a
+/// time, plus however much the source reads ahead.
+///
+/// The tokenizer checks only that tokens are well-formed, not that they form a
+/// valid document.
+pub struct JsonTokenizer<'d, S: BufSource<'d, N>, const N: usize = SIZE> {
+	source: S,
+	/// The length of the last token, consumed before reading the next.
+	consumed: usize,
+	__data: PhantomData<&'d ()>
+}
+
+impl<'d, S: BufSource<'d, N>, const N: usize> JsonTokenizer<'d, S, N> {
+	/// Creates a new tokenizer reading from `source`.
+	pub fn new(source: S) -> Self {
+		Self {
+			source,
+			consumed: 0,
+			__data: PhantomData
+		}
+	}
+
+	/// Consumes the tokenizer, returning the source. The last token read is
+	/// consumed from the source.
+	pub fn into_inner(mut self) -> Result<S> {
+		self.consume()?;
+		Ok(self.source)
+	}
+
+	/// Reads the next token, or `None` if the source ended. The returned token
+	/// borrows from the source buffer until the next call.
+	pub fn next_token(&mut self) -> Result<Option<JsonToken<'_>>> {
+		self.consume()?;
+		if !self.skip_whitespace()? {
+			return Ok(None)
+		}
+
+		let Some(byte) = self.source.buf().get(0) else {
+			return Ok(None)
+		};
+
+		let (token, len) = match byte {
+			b'{' => (JsonToken::ObjectStart, 1),
+			b'}' => (JsonToken::ObjectEnd  , 1),
+			b'[' => (JsonToken::ArrayStart , 1),
+			b']' => (JsonToken::ArrayEnd   , 1),
+			b':' => (JsonToken::Colon      , 1),
+			b',' => (JsonToken::Comma      , 1),
+			b't' => (JsonToken::Bool(true) , self.expect_literal(b"true" )?),
+			b'f' => (JsonToken::Bool(false), self.expect_literal(b"false")?),
+			b'n' => (JsonToken::Null       , self.expect_literal(b"null" )?),
+			b'"' => {
+				let end = self.find_string_end()?;
+				self.consumed = end + 1;
+				let str = self.source.buf().as_byte_str().range(1..end);
+				return Ok(Some(JsonToken::String(str)))
+			}
+			b'-' | b'0'..=b'9' => {
+				let end = self.find_number_end()?;
+				self.consumed = end;
+				let str = self.source.buf().as_byte_str().range(..end);
+				return Ok(Some(JsonToken::Number(str)))
+			}
+			_ => return Err(invalid_data("unexpected byte"))
+		};
+		self.consumed = len;
+		Ok(Some(token))
+	}
+
+	/// Skips the next value, including any nested values, returning `false` if the
+	/// source ended first. Separators before the value are skipped.
+	pub fn skip_value(&mut self) -> Result<bool> {
+		let mut depth = 0usize;
+		loop {
+			let Some(token) = self.next_token()? else {
+				return Ok(false)
+			};
+
+			match token {
+				JsonToken::ObjectStart | JsonToken::ArrayStart => depth += 1,
+				JsonToken::ObjectEnd | JsonToken::ArrayEnd => {
+					let Some(d) = depth.checked_sub(1) else {
+						return Err(invalid_data("unexpected end of container"))
+					};
+					depth = d;
+				}
+				JsonToken::Colon | JsonToken::Comma => continue,
+				_ => { }
+			}
+
+			if depth == 0 {
+				return Ok(true)
+			}
+		}
+	}
+
+	fn consume(&mut self) -> Result {
+		if self.consumed > 0 {
+			self.source.skip(self.consumed)?;
+			self.consumed = 0;
+		}
+		Ok(())
+	}
+
+	/// Skips whitespace, returning `false` if the source ended.
+	fn skip_whitespace(&mut self) -> Result<bool> {
+		let not_whitespace: fn(&u8) -> bool = |b| !matches!(b, b' ' | b'\t' | b'\n' | b'\r');
+		loop {
+			if let Some(range) = self.source.buf().find(not_whitespace) {
+				self.source.skip(range.start)?;
+				return Ok(true)
+			}
+
+			let count = self.source.available();
+			self.source.skip(count)?;
+			if !self.source.request(1)? {
+				return Ok(false)
+			}
+		}
+	}
+
+	/// Requests at least one more byte than is available, returning `false` if the
+	/// source ended.
+	fn request_more(&mut self) -> Result<bool> {
+		let count = self.source.available() + 1;
+		self.source.request(count)
+	}
+
+	/// Finds the position of the closing quote of a string starting at the front of
+	/// the buffer.
+	fn find_string_end(&mut self) -> Result<usize> {
+		let mut pos = 1;
+		loop {
+			let buf = self.source.buf();
+			match buf.find_in_range(b'"', pos..) {
+				Some(range) => {
+					// Ranges are relative to the start of the searched range.
+					let quote = pos + range.start;
+					let escapes = (1..quote)
+						.rev()
+						.take_while(|&i| buf.get(i) == Some(b'\\'))
+						.count();
+					if escapes.is_multiple_of(2) {
+						return Ok(quote)
+					}
+					pos = quote + 1;
+				}
+				None => {
+					pos = buf.count();
+					if !self.request_more()? {
+						return Err(StreamError::end_of_stream(pos + 1, Read))
+					}
+				}
+			}
+		}
+	}
+
+	/// Finds the end position of a number starting at the front of the buffer.
+	fn find_number_end(&mut self) -> Result<usize> {
+		let not_numeric: fn(&u8) -> bool = |b| !matches!(b, b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E');
+		let mut pos = 1;
+		loop {
+			let buf = self.source.buf();
+			if let Some(range) = buf.find_in_range(not_numeric, pos..) {
+				return Ok(pos + range.start)
+			}
+
+			pos = buf.count();
+			if !self.request_more()? {
+				return Ok(pos)
+			}
+		}
+	}
+
+	/// Checks that `literal` is at the front of the buffer, returning its length.
+	fn expect_literal(&mut self, literal: &[u8]) -> Result<usize> {
+		self.source.require(literal.len())?;
+		let mut buf = vec![0; literal.len()];
+		self.source.buf().read_slice_exact_at(0, &mut buf)?;
+		if buf != literal {
+			return Err(invalid_data("invalid literal"))
+		}
+		Ok(literal.len())
+	}
+}
+
+#[cfg(feature = "serde")]
+pub use lines::*;
+
+#[cfg(feature = "serde")]
+mod lines {
+	use std::{io, slice};
+	use std::marker::PhantomData;
+	use serde::de::DeserializeOwned;
+	use serde::Serialize;
+	use crate::{ByteStr, ResultContext, SIZE, StreamError};
+	use crate::StreamContext::{Read, Write};
+	use super::super::{invalid_data, request_more, BufSink, BufSource, Result};
+
+	/// The default maximum length of a line.
+	const MAX_LEN: usize = 16 * 1024 * 1024;
+
+	/// An iterator deserializing newline-delimited JSON values from a [`BufSource`].
+	/// Returned by [`read_json_lines`].
+	pub struct JsonLines<'d, T, S: BufSource<'d, N>, const N: usize = SIZE> {
+		source: S,
+		max_len: usize,
+		__value: PhantomData<fn() -> T>,
+		__data: PhantomData<&'d ()>
+	}
+
+	/// Returns an iterator deserializing values from `source`, one per line. Lines
+	/// end with `'\n'`, optionally preceded by `'\r'`, or at the end of the source.
+	/// Empty lines are skipped.
+	///
+	/// Each value is deserialized directly from the source buffer, without copying
+	/// the line out first, even if it spans multiple segments.
+	pub fn read_json_lines<'d, T: DeserializeOwned, S: BufSource<'d, N>, const N: usize>(
+		source: S
+	) -> JsonLines<'d, T, S, N> {
+		JsonLines {
+			source,
+			max_len: MAX_LEN,
+			__value: PhantomData,
+			__data: PhantomData
+		}
+	}
+
+	/// Serializes `value` as compact JSON followed by `'\n'`, directly into the
+	/// buffer of `sink`. Returns the number of bytes written.
+	///
+	/// # Errors
+	///
+	/// If `value` fails to serialize, an IO error of kind [`InvalidData`] is
+	/// returned. Bytes written before the failure are not removed.
+	///
+	/// [`InvalidData`]: io::ErrorKind::InvalidData
+	pub fn write_json_line<'d, T: Serialize + ?Sized, S: BufSink<'d, N> + ?Sized, const N: usize>(
+		sink: &mut S,
+		value: &T
+	) -> Result<usize> {
+		let mut writer = SinkWriter {
+			sink,
+			count: 0,
+			error: None,
+			__data: PhantomData
+		};
+		if let Err(error) = serde_json::to_writer(&mut writer, value) {
+			return match writer.error {
+				Some(error) => Err(error),
+				None => Err(io::Error::from(error)).context(Write)
+			}
+		}
+		writer.sink.write_u8(b'\n')?;
+		Ok(writer.count + 1)
+	}
+
+	impl<'d, T, S: BufSource<'d, N>, const N: usize> JsonLines<'d, T, S, N> {
+		/// Sets the maximum length of a line, excluding its terminator. Defaults to
+		/// 16MiB.
+		pub fn with_max_len(mut self, value: usize) -> Self {
+			self.max_len = value;
+			self
+		}
+
+		/// Consumes the iterator, returning the source.
+		pub fn into_inner(self) -> S {
+			self.source
+		}
+
+		/// Returns the length of the next line, excluding its terminator, and whether
+		/// the line was terminated. Returns `None` if the source ended. Fails if the
+		/// line is longer than the maximum length, allowing for a carriage return.
+		fn find_line(&mut self) -> Result<Option<(usize, bool)>> {
+			let max_len = self.max_len.saturating_add(1);
+			let mut from = 0;
+			loop {
+				let buf = self.source.buf();
+				// Ranges are relative to the start of the searched range.
+				let found = buf.find_in_range(b'\n', from..).map(|range| from + range.start);
+				if found.unwrap_or(buf.count()) > max_len {
+					return Err(invalid_data("line too long"))
+				}
+				if let Some(len) = found {
+					return Ok(Some((len, true)))
+				}
+
+				from = buf.count();
+				if !request_more(&mut self.source)? {
+					return Ok((from > 0).then_some((from, false)))
+				}
+			}
+		}
+	}
+
+	impl<'d, T: DeserializeOwned, S: BufSource<'d, N>, const N: usize> JsonLines<'d, T, S, N> {
+		/// Reads the next value, or `None` if the source ended.
+		///
+		/// # Errors
+		///
+		/// If a line isn't a single valid JSON value of type `T`, an IO error of kind
+		/// [`InvalidData`] is returned, and the line is skipped. If a line is longer
+		/// than the maximum length, an [`InvalidData`] error is returned before the
+		/// rest of the line is read, and the line is left unread.
+		///
+		/// [`InvalidData`]: io::ErrorKind::InvalidData
+		pub fn next_value(&mut self) -> Result<Option<T>> {
+			loop {
+				let Some((len, terminated)) = self.find_line()? else {
+					return Ok(None)
+				};
+				let line = self.source.buf().as_byte_str().range(..len);
+				let line = match line.as_slices().last() {
+					Some(last) if last.ends_with(b"\r") => line.range(..len - 1),
+					_ => line
+				};
+
+				let value = if line.is_empty() {
+					None
+				} else {
+					Some(deserialize(line))
+				};
+				self.source.skip(len + terminated as usize)?;
+				if let Some(value) = value {
+					return value.map(Some)
+				}
+			}
+		}
+	}
+
+	impl<'d, T: DeserializeOwned, S: BufSource<'d, N>, const N: usize> Iterator for JsonLines<'d, T, S, N> {
+		type Item = Result<T>;
+
+		fn next(&mut self) -> Option<Result<T>> {
+			self.next_value().transpose()
+		}
+	}
+
+	/// Deserializes a value from a line.
+	fn deserialize<T: DeserializeOwned>(line: ByteStr) -> Result<T> {
+		let result = match line.as_slices() {
+			[slice] => serde_json::from_slice(slice),
+			slices => serde_json::from_reader(SlicesReader {
+				slice: &[],
+				rest: slices.iter()
+			})
+		};
+		result.map_err(io::Error::from).context(Read)
+	}
+
+	/// An [`io::Read`] adapter over a sequence of slices.
+	struct SlicesReader<'a, 'b> {
+		slice: &'b [u8],
+		rest: slice::Iter<'a, &'b [u8]>,
+	}
+
+	impl io::Read for SlicesReader<'_, '_> {
+		fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+			while self.slice.is_empty() {
+				let Some(&slice) = self.rest.next() else {
+					return Ok(0)
+				};
+				self.slice = slice;
+			}
+			io::Read::read(&mut self.slice, buf)
+		}
+	}
+
+	/// A [`io::Write`] adapter writing directly into the buffer of a [`BufSink`],
+	/// holding the sink error for [`write_json_line`] to return.
+	struct SinkWriter<'a, 'd, S: BufSink<'d, N> + ?Sized, const N: usize> {
+		sink: &'a mut S,
+		count: usize,
+		error: Option<StreamError>,
+		__data: PhantomData<&'d ()>
+	}
+
+	impl<'d, S: BufSink<'d, N> + ?Sized, const N: usize> io::Write for SinkWriter<'_, 'd, S, N> {
+		fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+			match self.sink.write_from_slice(buf) {
+				Ok(count) => {
+					self.count += count;
+					Ok(count)
+				}
+				Err(error) => {
+					self.error = Some(error);
+					Err(io::Error::other("sink error"))
+				}
+			}
+		}
+
+		fn flush(&mut self) -> io::Result<()> { Ok(()) }
+	}
+}
@@ -0,0 +1,577 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! [MessagePack] encoding and decoding over [`BufSource`]s and [`BufSink`]s. Values
+//! are read and written with the stream's own integer and slice methods, directly
+//! from and into buffer segments, rather than through [`std::io`] adapters.
+//!
+//! [MessagePack]: https://github.com/msgpack/msgpack/blob/master/spec.md
+
+use crate::StreamError;
+use crate::StreamContext::Read;
+use super::{invalid_data, invalid_input, BufSink, BufSource, Result};
+
+/// The maximum depth of nested arrays and maps read by [`read_msgpack_value`].
+const MAX_DEPTH: usize = 256;
+
+/// A format marker, the first byte of every encoded value.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Marker {
+	/// A positive integer from 0 to 127, stored in the marker.
+	FixPos(u8),
+	/// A negative integer from -32 to -1, stored in the marker.
+	FixNeg(i8),
+	/// A map with up to 15 entries, its length stored in the marker.
+	FixMap(u8),
+	/// An array with up to 15 elements, its length stored in the marker.
+	FixArray(u8),
+	/// A string of up to 31 bytes, its length stored in the marker.
+	FixStr(u8),
+	/// Nil.
+	Nil,
+	/// The reserved marker, `0xC1`, which is never used.
+	Reserved,
+	/// Boolean false.
+	False,
+	/// Boolean true.
+	True,
+	/// Binary data with an 8-bit length.
+	Bin8,
+	/// Binary data with a 16-bit length.
+	Bin16,
+	/// Binary data with a 32-bit length.
+	Bin32,
+	/// Extension data with an 8-bit length.
+	Ext8,
+	/// Extension data with a 16-bit length.
+	Ext16,
+	/// Extension data with a 32-bit length.
+	Ext32,
+	/// A single-precision float.
+	F32,
+	/// A double-precision float.
+	F64,
+	/// An 8-bit unsigned integer.
+	U8,
+	/// A 16-bit unsigned integer.
+	U16,
+	/// A 32-bit unsigned integer.
+	U32,
+	/// A 64-bit unsigned integer.
+	U64,
+	/// An 8-bit signed integer.
+	I8,
+	/// A 16-bit signed integer.
+	I16,
+	/// A 32-bit signed integer.
+	I32,
+	/// A 64-bit signed integer.
+	I64,
+	/// Extension data of 1 byte.
+	FixExt1,
+	/// Extension data of 2 bytes.
+	FixExt2,
+	/// Extension data of 4 bytes.
+	FixExt4,
+	/// Extension data of 8 bytes.
+	FixExt8,
+	/// Extension data of 16 bytes.
+	FixExt16,
+	/// A string with an 8-bit length.
+	Str8,
+	/// A string with a 16-bit length.
+	Str16,
+	/// A string with a 32-bit length.
+	Str32,
+	/// An array with a 16-bit length.
+	Array16,
+	/// An array with a 32-bit length.
+	Array32,
+	/// A map with a 16-bit length.
+	Map16,
+	/// A map with a 32-bit length.
+	Map32,
+}
+
+impl From<u8> for Marker {
+	fn from(value: u8) -> Self {
+		match value {
+			0x00..=0x7F => Self::FixPos(value),
+			0x80..=0x8F => Self::FixMap(value & 0x0F),
+			0x90..=0x9F => Self::FixArray(value & 0x0F),
+			0xA0..=0xBF => Self::FixStr(value & 0x1F),
+			0xC0 => Self::Nil,
+			0xC1 => Self::Reserved,
+			0xC2 => Self::False,
+			0xC3 => Self::True,
+			0xC4 => Self::Bin8,
+			0xC5 => Self::Bin16,
+			0xC6 => Self::Bin32,
+			0xC7 => Self::Ext8,
+			0xC8 => Self::Ext16,
+			0xC9 => Self::Ext32,
+			0xCA => Self::F32,
+			0xCB => Self::F64,
+			0xCC => Self::U8,
+			0xCD => Self::U16,
+			0xCE => Self::U32,
+			0xCF => Self::U64,
+			0xD0 => Self::I8,
+			0xD1 => Self::I16,
+			0xD2 => Self::I32,
+			0xD3 => Self::I64,
+			0xD4 => Self::FixExt1,
+			0xD5 => Self::FixExt2,
+			0xD6 => Self::FixExt4,
+			0xD7 => Self::FixExt8,
+			0xD8 => Self::FixExt16,
+			0xD9 => Self::Str8,
+			0xDA => Self::Str16,
+			0xDB => Self::Str32,
+			0xDC => Self::Array16,
+			0xDD => Self::Array32,
+			0xDE => Self::Map16,
+			0xDF => Self::Map32,
+			0xE0..=0xFF => Self::FixNeg(value as i8)
+		}
+	}
+}
+
+impl From<Marker> for u8 {
+	/// Converts a marker to its byte. Lengths and values stored in fixed-size
+	/// markers are truncated to fit.
+	fn from(value: Marker) -> Self {
+		match value {
+			Marker::FixPos(value)   => value & 0x7F,
+			Marker::FixNeg(value)   => value as u8 | 0xE0,
+			Marker::FixMap(len)     => 0x80 | len & 0x0F,
+			Marker::FixArray(len)   => 0x90 | len & 0x0F,
+			Marker::FixStr(len)     => 0xA0 | len & 0x1F,
+			Marker::Nil      => 0xC0,
+			Marker::Reserved => 0xC1,
+			Marker::False    => 0xC2,
+			Marker::True     => 0xC3,
+			Marker::Bin8     => 0xC4,
+			Marker::Bin16    => 0xC5,
+			Marker::Bin32    => 0xC6,
+			Marker::Ext8     => 0xC7,
+			Marker::Ext16    => 0xC8,
+			Marker::Ext32    => 0xC9,
+			Marker::F32      => 0xCA,
+			Marker::F64      => 0xCB,
+			Marker::U8       => 0xCC,
+			Marker::U16      => 0xCD,
+			Marker::U32      => 0xCE,
+			Marker::U64      => 0xCF,
+			Marker::I8       => 0xD0,
+			Marker::I16      => 0xD1,
+			Marker::I32      => 0xD2,
+			Marker::I64      => 0xD3,
+			Marker::FixExt1  => 0xD4,
+			Marker::FixExt2  => 0xD5,
+			Marker::FixExt4  => 0xD6,
+			Marker::FixExt8  => 0xD7,
+			Marker::FixExt16 => 0xD8,
+			Marker::Str8     => 0xD9,
+			Marker::Str16    => 0xDA,
+			Marker::Str32    => 0xDB,
+			Marker::Array16  => 0xDC,
+			Marker::Array32  => 0xDD,
+			Marker::Map16    => 0xDE,
+			Marker::Map32    => 0xDF,
+		}
+	}
+}
+
+/// A decoded MessagePack value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+	/// Nil.
+	Nil,
+	/// A boolean.
+	Bool(bool),
+	/// A negative integer. Non-negative integers are read as [`UInt`], regardless
+	/// of their encoded format.
+	///
+	/// [`UInt`]: Self::UInt
+	Int(i64),
+	/// A non-negative integer.
+	UInt(u64),
+	/// A single-precision float.
+	F32(f32),
+	/// A double-precision float.
+	F64(f64),
+	/// A UTF-8 string.
+	Str(String),
+	/// Binary data.
+	Bin(Vec<u8>),
+	/// An array.
+	Array(Vec<Value>),
+	/// A map, with entries in the order they were read.
+	Map(Vec<(Value, Value)>),
+	/// An extension value, with its type and data.
+	Ext(i8, Vec<u8>),
+}
+
+/// Reads a format marker from `source`.
+pub fn read_marker<'d, const N: usize>(source: &mut impl BufSource<'d, N>) -> Result<Marker> {
+	source.read_u8().map(Marker::from)
+}
+
+/// Reads a value from `source`, including any nested values.
+///
+/// # Errors
+///
+/// The reserved marker, values nested deeper than 256 levels, and strings that
+/// aren't valid UTF-8 fail with an IO error of kind [`InvalidData`]. If the source
+/// ends within the value, an end-of-stream error is returned.
+///
+/// [`InvalidData`]: std::io::ErrorKind::InvalidData
+pub fn read_msgpack_value<'d, const N: usize>(source: &mut impl BufSource<'d, N>) -> Result<Value> {
+	read_value(source, 0)
+}
+
+/// Writes `value` to `sink`, using the smallest encoding for each integer and
+/// length.
+///
+/// # Errors
+///
+/// Strings, binary and extension data, arrays, and maps longer than [`u32::MAX`]
+/// fail with an IO error of kind [`InvalidInput`].
+///
+/// [`InvalidInput`]: std::io::ErrorKind::InvalidInput
+pub fn write_msgpack_value<'d, const N: usize>(sink: &mut impl BufSink<'d, N>, value: &Value) -> Result {
+	match value {
+		Value::Nil => write_nil(sink),
+		Value::Bool(value) => write_bool(sink, *value),
+		Value::Int(value) => write_int(sink, *value),
+		Value::UInt(value) => write_uint(sink, *value),
+		Value::F32(value) => write_f32(sink, *value),
+		Value::F64(value) => write_f64(sink, *value),
+		Value::Str(value) => write_str(sink, value),
+		Value::Bin(value) => write_bin(sink, value),
+		Value::Array(values) => {
+			write_array_len(sink, values.len())?;
+			for value in values {
+				write_msgpack_value(sink, value)?;
+			}
+			Ok(())
+		}
+		Value::Map(entries) => {
+			write_map_len(sink, entries.len())?;
+			for (key, value) in entries {
+				write_msgpack_value(sink, key)?;
+				write_msgpack_value(sink, value)?;
+			}
+			Ok(())
+		}
+		Value::Ext(ty, data) => write_ext(sink, *ty, data),
+	}
+}
+
+/// Writes a nil value to `sink`.
+pub fn write_nil<'d, const N: usize>(sink: &mut impl BufSink<'d, N>) -> Result {
+	write_marker(sink, Marker::Nil)
+}
+
+/// Writes a boolean value to `sink`.
+pub fn write_bool<'d, const N: usize>(sink: &mut impl BufSink<'d, N>, value: bool) -> Result {
+	write_marker(sink, if value { Marker::True } else { Marker::False })
+}
+
+/// Writes an unsigned integer to `sink`, in its smallest encoding.
+pub fn write_uint<'d, const N: usize>(sink: &mut impl BufSink<'d, N>, value: u64) -> Result {
+	match value {
+		0..=0x7F => write_marker(sink, Marker::FixPos(value as u8)),
+		0x80..=0xFF => {
+			write_marker(sink, Marker::U8)?;
+			sink.write_u8(value as u8)
+		}
+		0x100..=0xFFFF => {
+			write_marker(sink, Marker::U16)?;
+			sink.write_u16(value as u16)
+		}
+		0x10000..=0xFFFF_FFFF => {
+			write_marker(sink, Marker::U32)?;
+			sink.write_u32(value as u32)
+		}
+		_ => {
+			write_marker(sink, Marker::U64)?;
+			sink.write_u64(value)
+		}
+	}
+}
+
+/// Writes a signed integer to `sink`, in its smallest encoding. Non-negative
+/// integers are written as unsigned.
+pub fn write_int<'d, const N: usize>(sink: &mut impl BufSink<'d, N>, value: i64) -> Result {
+	match value {
+		0.. => write_uint(sink, value as u64),
+		-32..=-1 => write_marker(sink, Marker::FixNeg(value as i8)),
+		-0x80..=-33 => {
+			write_marker(sink, Marker::I8)?;
+			sink.write_i8(value as i8)
+		}
+		-0x8000..=-0x81 => {
+			write_marker(sink, Marker::I16)?;
+			sink.write_i16(value as i16)
+		}
+		-0x8000_0000..=-0x8001 => {
+			write_marker(sink, Marker::I32)?;
+			sink.write_i32(value as i32)
+		}
+		_ => {
+			write_marker(sink, Marker::I64)?;
+			sink.write_i64(value)
+		}
+	}
+}
+
+/// Writes a single-precision float to `sink`.
+pub fn write_f32<'d, const N: usize>(sink: &mut impl BufSink<'d, N>, value: f32) -> Result {
+	write_marker(sink, Marker::F32)?;
+	sink.write_u32(value.to_bits())
+}
+
+/// Writes a double-precision float to `sink`.
+pub fn write_f64<'d, const N: usize>(sink: &mut impl BufSink<'d, N>, value: f64) -> Result {
+	write_marker(sink, Marker::F64)?;
+	sink.write_u64(value.to_bits())
+}
+
+/// Writes a string to `sink`.
+///
+/// # Errors
+///
+/// Strings longer than [`u32::MAX`] bytes fail with an IO error of kind
+/// [`InvalidInput`].
+///
+/// [`InvalidInput`]: std::io::ErrorKind::InvalidInput
+pub fn write_str<'d, const N: usize>(sink: &mut impl BufSink<'d, N>, value: &str) -> Result {
+	let len = value.len();
+	match len {
+		0..=31 => write_marker(sink, Marker::FixStr(len as u8))?,
+		_ => write_len(sink, len, Some(Marker::Str8), Marker::Str16, Marker::Str32)?
+	}
+	sink.write_utf8(value)?;
+	Ok(())
+}
+
+/// Writes binary data to `sink`.
+///
+/// # Errors
+///
+/// Data longer than [`u32::MAX`] bytes fails with an IO error of kind
+/// [`InvalidInput`].
+///
+/// [`InvalidInput`]: std::io::ErrorKind::InvalidInput
+pub fn write_bin<'d, const N: usize>(sink: &mut impl BufSink<'d, N>, value: &[u8]) -> Result {
+	write_len(sink, value.len(), Some(Marker::Bin8), Marker::Bin16, Marker::Bin32)?;
+	sink.write_from_slice(value)?;
+	Ok(())
+}
+
+/// Writes an array header to `sink`, to be followed by `len` values.
+///
+/// # Errors
+///
+/// Lengths greater than [`u32::MAX`] fail with an IO error of kind
+/// [`InvalidInput`].
+///
+/// [`InvalidInput`]: std::io::ErrorKind::InvalidInput
+pub fn write_array_len<'d, const N: usize>(sink: &mut impl BufSink<'d, N>, len: usize) -> Result {
+	match len {
+		0..=15 => write_marker(sink, Marker::FixArray(len as u8)),
+		_ => write_len(sink, len, None, Marker::Array16, Marker::Array32)
+	}
+}
+
+/// Writes a map header to `sink`, to be followed by `len` key-value pairs.
+///
+/// # Errors
+///
+/// Lengths greater than [`u32::MAX`] fail with an IO error of kind
+/// [`InvalidInput`].
+///
+/// [`InvalidInput`]: std::io::ErrorKind::InvalidInput
+pub fn write_map_len<'d, const N: usize>(sink: &mut impl BufSink<'d, N>, len: usize) -> Result {
+	match len {
+		0..=15 => write_marker(sink, Marker::FixMap(len as u8)),
+		_ => write_len(sink, len, None, Marker::Map16, Marker::Map32)
+	}
+}
+
+/// Writes an extension value of type `ty` to `sink`.
+///
+/// # Errors
+///
+/// Data longer than [`u32::MAX`] bytes fails with an IO error of kind
+/// [`InvalidInput`].
+///
+/// [`InvalidInput`]: std::io::ErrorKind::InvalidInput
+pub fn write_ext<'d, const N: usize>(sink: &mut impl BufSink<'d, N>, ty: i8, data: &[u8]) -> Result {
+	match data.len() {
+		1  => write_marker(sink, Marker::FixExt1)?,
+		2  => write_marker(sink, Marker::FixExt2)?,
+		4  => write_marker(sink, Marker::FixExt4)?,
+		8  => write_marker(sink, Marker::FixExt8)?,
+		16 => write_marker(sink, Marker::FixExt16)?,
+		len => write_len(sink, len, Some(Marker::Ext8), Marker::Ext16, Marker::Ext32)?
+	}
+	sink.write_i8(ty)?;
+	sink.write_from_slice(data)?;
+	Ok(())
+}
+
+fn write_marker<'d, const N: usize>(sink: &mut impl BufSink<'d, N>, marker: Marker) -> Result {
+	sink.write_u8(marker.into())
+}
+
+/// Writes a marker followed by a length, choosing the smallest of the 8, 16, or
+/// 32-bit length markers. Arrays and maps have no 8-bit length marker.
+fn write_len<'d, const N: usize>(
+	sink: &mut impl BufSink<'d, N>,
+	len: usize,
+	len8: Option<Marker>,
+	len16: Marker,
+	len32: Marker
+) -> Result {
+	match (len, len8) {
+		(0..=0xFF, Some(marker)) => {
+			write_marker(sink, marker)?;
+			sink.write_u8(len as u8)
+		}
+		(0..=0xFFFF, _) => {
+			write_marker(sink, len16)?;
+			sink.write_u16(len as u16)
+		}
+		_ => {
+			let Ok(len) = u32::try_from(len) else {
+				return Err(invalid_input("value too long"))
+			};
+			write_marker(sink, len32)?;
+			sink.write_u32(len)
+		}
+	}
+}
+
+fn read_value<'d, const N: usize>(source: &mut impl BufSource<'d, N>, depth: usize) -> Result<Value> {
+	let marker = read_marker(source)?;
+	Ok(
+		match marker {
+			Marker::FixPos(value) => Value::UInt(value as u64),
+			Marker::FixNeg(value) => Value::Int(value as i64),
+			Marker::Nil => Value::Nil,
+			Marker::Reserved => return Err(invalid_data("reserved marker")),
+			Marker::False => Value::Bool(false),
+			Marker::True => Value::Bool(true),
+			Marker::U8  => Value::UInt(source.read_u8 ()? as u64),
+			Marker::U16 => Value::UInt(source.read_u16()? as u64),
+			Marker::U32 => Value::UInt(source.read_u32()? as u64),
+			Marker::U64 => Value::UInt(source.read_u64()?),
+			Marker::I8  => int(source.read_i8 ()? as i64),
+			Marker::I16 => int(source.read_i16()? as i64),
+			Marker::I32 => int(source.read_i32()? as i64),
+			Marker::I64 => int(source.read_i64()?),
+			Marker::F32 => Value::F32(f32::from_bits(source.read_u32()?)),
+			Marker::F64 => Value::F64(f64::from_bits(source.read_u64()?)),
+			Marker::FixStr(len) => Value::Str(read_str(source, len as usize)?),
+			Marker::Str8 | Marker::Str16 | Marker::Str32 => {
+				let len = read_len(source, marker)?;
+				Value::Str(read_str(source, len)?)
+			}
+			Marker::Bin8 | Marker::Bin16 | Marker::Bin32 => {
+				let len = read_len(source, marker)?;
+				Value::Bin(read_bytes(source, len)?)
+			}
+			Marker::FixExt1  => read_ext(source, 1)?,
+			Marker::FixExt2  => read_ext(source, 2)?,
+			Marker::FixExt4  => read_ext(source, 4)?,
+			Marker::FixExt8  => read_ext(source, 8)?,
+			Marker::FixExt16 => read_ext(source, 16)?,
+			Marker::Ext8 | Marker::Ext16 | Marker::Ext32 => {
+				let len = read_len(source, marker)?;
+				read_ext(source, len)?
+			}
+			Marker::FixArray(len) => read_array(source, len as usize, depth)?,
+			Marker::Array16 | Marker::Array32 => {
+				let len = read_len(source, marker)?;
+				read_array(source, len, depth)?
+			}
+			Marker::FixMap(len) => read_map(source, len as usize, depth)?,
+			Marker::Map16 | Marker::Map32 => {
+				let len = read_len(source, marker)?;
+				read_map(source, len, depth)?
+			}
+		}
+	)
+}
+
+/// Reads the length following a variable-length marker.
+fn read_len<'d, const N: usize>(source: &mut impl BufSource<'d, N>, marker: Marker) -> Result<usize> {
+	Ok(
+		match marker {
+			Marker::Str8  | Marker::Bin8  | Marker::Ext8 => source.read_u8()? as usize,
+			Marker::Str16 | Marker::Bin16 | Marker::Ext16 | Marker::Array16 | Marker::Map16 => source.read_u16()? as usize,
+			_ => source.read_u32()? as usize
+		}
+	)
+}
+
+/// Normalizes a signed integer, reading non-negative values as unsigned.
+fn int(value: i64) -> Value {
+	if value < 0 {
+		Value::Int(value)
+	} else {
+		Value::UInt(value as u64)
+	}
+}
+
+fn read_str<'d, const N: usize>(source: &mut impl BufSource<'d, N>, len: usize) -> Result<String> {
+	let Ok(value) = String::from_utf8(read_bytes(source, len)?) else {
+		return Err(invalid_data("invalid UTF-8 string"))
+	};
+	Ok(value)
+}
+
+/// Reads exactly `len` bytes. The bytes are read into the vector as they arrive,
+/// so a corrupt length can't allocate more than the source contains.
+fn read_bytes<'d, const N: usize>(source: &mut impl BufSource<'d, N>, len: usize) -> Result<Vec<u8>> {
+	let mut vec = Vec::new();
+	let read = source.read_to_vec(&mut vec, len)?;
+	if read < len {
+		return Err(StreamError::end_of_stream(len - read, Read))
+	}
+	Ok(vec)
+}
+
+fn read_ext<'d, const N: usize>(source: &mut impl BufSource<'d, N>, len: usize) -> Result<Value> {
+	let ty = source.read_i8()?;
+	Ok(Value::Ext(ty, read_bytes(source, len)?))
+}
+
+fn read_array<'d, const N: usize>(source: &mut impl BufSource<'d, N>, len: usize, depth: usize) -> Result<Value> {
+	check_depth(depth)?;
+	let mut values = Vec::new();
+	for _ in 0..len {
+		values.push(read_value(source, depth + 1)?);
+	}
+	Ok(Value::Array(values))
+}
+
+fn read_map<'d, const N: usize>(source: &mut impl BufSource<'d, N>, len: usize, depth: usize) -> Result<Value> {
+	check_depth(depth)?;
+	let mut entries = Vec::new();
+	for _ in 0..len {
+		let key = read_value(source, depth + 1)?;
+		let value = read_value(source, depth + 1)?;
+		entries.push((key, value));
+	}
+	Ok(Value::Map(entries))
+}
+
+fn check_depth(depth: usize) -> Result {
+	if depth >= MAX_DEPTH {
+		return Err(invalid_data("value nested too deeply"))
+	}
+	Ok(())
+}
@@ -0,0 +1,248 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Streaming [multipart] body parsing over [`BufSource`]s, as used by
+//! `multipart/form-data` and `multipart/mixed` messages. Parts are read one at a
+//! time, each with its header fields and a [`Source`] reading its body up to the
+//! next boundary. Bodies are never buffered whole, and boundaries are found even
+//! when split across segments.
+//!
+//! [multipart]: https://www.rfc-editor.org/rfc/rfc2046#section-5.1
+
+use std::marker::PhantomData;
+use crate::{Buffer, BufferResult, ByteString, ResultContext, SIZE, StreamError};
+use crate::BufferContext::Fill;
+use crate::pool::Pool;
+use crate::StreamContext::Read;
+use super::{invalid_data, request_more, BufSource, Result, Source, Stream};
+use super::http1::{find_line, read_headers, trim, Header};
+
+/// The default maximum size of a part's header block.
+const MAX_HEADER_SIZE: usize = 8192;
+/// The maximum boundary length.
+const MAX_BOUNDARY_LEN: usize = 70;
+
+/// A part read by a [`MultipartReader`].
+pub struct Part<'r, 'd, S: BufSource<'d, N>, const N: usize = SIZE> {
+	/// The header fields, in the order they were read.
+	pub headers: Vec<(ByteString, ByteString)>,
+	/// The part body, ending at the next boundary. Body bytes not read are skipped
+	/// when the next part is read.
+	pub body: &'r mut PartSource<'d, S, N>,
+}
+
+impl<'d, S: BufSource<'d, N>, const N: usize> Part<'_, 'd, S, N> {
+	/// Returns the value of the first header field named `name`, compared ignoring
+	/// ASCII case.
+	pub fn header(&self, name: &str) -> Option<&ByteString> {
+		self.headers
+			.iter()
+			.find(|(field, _)| field.as_slice().eq_ignore_ascii_case(name.as_bytes()))
+			.map(|(_, value)| value)
+	}
+}
+
+/// A [`Source`] reading a part body from a multipart stream, reaching end-of-stream
+/// at the next boundary delimiter.
+pub struct PartSource<'d, S: BufSource<'d, N>, const N: usize = SIZE> {
+	source: S,
+	/// The delimiter ending each body: a line break, two hyphens, and the boundary.
+	delimiter: Vec<u8>,
+	/// Whether the body ended at a delimiter, which is next in the source.
+	ended: bool,
+	/// The number of buffered bytes searched without finding the start of a
+	/// delimiter, from which the next search resumes.
+	scanned: usize,
+	__data: PhantomData<&'d ()>
+}
+
+/// A reader of parts from a multipart stream. The preamble before the first
+/// boundary is discarded, and the epilogue after the last is left unread.
+pub struct MultipartReader<'d, S: BufSource<'d, N>, const N: usize = SIZE> {
+	body: PartSource<'d, S, N>,
+	max_header_size: usize,
+	started: bool,
+	finished: bool,
+}
+
+impl<'d, S: BufSource<'d, N>, const N: usize> MultipartReader<'d, S, N> {
+	/// Creates a new multipart reader, reading parts separated by `boundary` from
+	/// `source`. The boundary is usually taken from the `boundary` parameter of the
+	/// `Content-Type` header.
+	///
+	/// # Errors
+	///
+	/// If the boundary is empty or longer than 70 bytes, an IO error of kind
+	/// [`InvalidData`] is returned.
+	///
+	/// [`InvalidData`]: std::io::ErrorKind::InvalidData
+	pub fn new(source: S, boundary: &str) -> Result<Self> {
+		if boundary.is_empty() || boundary.len() > MAX_BOUNDARY_LEN {
+			return Err(invalid_data("invalid multipart boundary"))
+		}
+
+		Ok(Self {
+			body: PartSource {
+				source,
+				delimiter: [b"\r\n--", boundary.as_bytes()].concat(),
+				ended: true,
+				scanned: 0,
+				__data: PhantomData
+			},
+			max_header_size: MAX_HEADER_SIZE,
+			started: false,
+			finished: false
+		})
+	}
+
+	/// Sets the maximum size of each part's header block, including line
+	/// terminators. Defaults to 8KiB.
+	pub fn with_max_header_size(mut self, value: usize) -> Self {
+		self.max_header_size = value;
+		self
+	}
+
+	/// Consumes the reader, returning the source.
+	pub fn into_inner(self) -> S {
+		self.body.source
+	}
+
+	/// Reads the next part, or `None` if the closing boundary was reached. Unread
+	/// body bytes of the previous part are skipped.
+	///
+	/// # Errors
+	///
+	/// If a boundary line or header block is malformed, an IO error of kind
+	/// [`InvalidData`] is returned. If the source ends before the closing boundary,
+	/// an end-of-stream error is returned.
+	///
+	/// [`InvalidData`]: std::io::ErrorKind::InvalidData
+	pub fn next_part(&mut self) -> Result<Option<Part<'_, 'd, S, N>>> {
+		if self.finished {
+			return Ok(None)
+		}
+
+		let body = &mut self.body;
+		let delimiter_len = body.delimiter.len();
+		if !self.started {
+			self.started = true;
+			// The first boundary may be at the start, without a preceding line break.
+			body.source.request(delimiter_len - 2)?;
+			if body.source.buf().starts_with(&body.delimiter[2..]) {
+				body.source.skip(delimiter_len - 2)?;
+			} else {
+				body.ended = false;
+				body.skip_rest()?;
+				body.source.skip(delimiter_len)?;
+			}
+		} else {
+			body.skip_rest()?;
+			body.source.skip(delimiter_len)?;
+		}
+
+		let source = &mut body.source;
+		if source.request(2)? && source.buf().starts_with(b"--") {
+			source.skip(2)?;
+			self.finished = true;
+			return Ok(None)
+		}
+
+		// Whitespace may follow the boundary before the line break.
+		let line = find_line(source, 0, self.max_header_size)?;
+		if !trim(source.buf(), 0..line.start).is_empty() {
+			return Err(invalid_data("invalid multipart boundary line"))
+		}
+		source.skip(line.end)?;
+
+		let headers = read_headers(source, self.max_header_size)?;
+		let fields = headers.iter()
+							.map(|Header { name, value }| (name.to_byte_string(), value.to_byte_string()))
+							.collect();
+		headers.consume()?;
+		body.ended = false;
+		Ok(Some(Part {
+			headers: fields,
+			body
+		}))
+	}
+}
+
+impl<'d, S: BufSource<'d, N>, const N: usize> PartSource<'d, S, N> {
+	/// Returns the number of body bytes buffered before the next delimiter, or
+	/// before a possible partial delimiter at the end of the buffer, reading more
+	/// if none are buffered. Also returns whether the delimiter was found.
+	fn buffered_len(&mut self) -> Result<(usize, bool)> {
+		loop {
+			let buf = self.source.buf();
+			// Ranges are relative to the start of the searched range.
+			if let Some(range) = buf.find_in_range(&self.delimiter[..], self.scanned..) {
+				self.scanned += range.start;
+				return Ok((self.scanned, true))
+			}
+
+			// A partial delimiter may start within the last few bytes.
+			let len = buf.count().saturating_sub(self.delimiter.len() - 1);
+			self.scanned = self.scanned.max(len);
+			if len > 0 {
+				return Ok((len, false))
+			}
+
+			if !request_more(&mut self.source)? {
+				return Err(StreamError::end_of_stream(self.delimiter.len(), Read))
+			}
+		}
+	}
+
+	/// Skips the rest of the body, up to the next delimiter.
+	fn skip_rest(&mut self) -> Result {
+		while !self.ended {
+			let (len, found) = self.buffered_len()?;
+			self.source.skip(len)?;
+			self.scanned -= len;
+			self.ended = found;
+		}
+		Ok(())
+	}
+}
+
+impl<'d, S: BufSource<'d, N>, const N: usize> Stream<N> for PartSource<'d, S, N> {
+	fn is_closed(&self) -> bool {
+		self.source.is_closed()
+	}
+
+	fn close(&mut self) -> Result {
+		self.source.close()
+	}
+}
+
+impl<'d, S: BufSource<'d, N>, const N: usize> Source<'d, N> for PartSource<'d, S, N> {
+	#[inline]
+	fn is_eos(&self) -> bool { self.ended }
+
+	/// Reads up to `count` bytes of the part body into `sink`, stopping at the next
+	/// boundary delimiter.
+	///
+	/// # Errors
+	///
+	/// If the inner source ends before the delimiter, an end-of-stream error is
+	/// returned.
+	fn fill(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		if self.ended || count == 0 {
+			return Ok(0)
+		}
+
+		let (len, found) = self.buffered_len().context(Fill)?;
+		let read = self.source.read(sink, count.min(len)).context(Fill)?;
+		self.scanned -= read;
+		self.ended = found && read == len;
+		Ok(read)
+	}
+
+	/// Reads the rest of the part body into `sink`.
+	fn fill_all(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>) -> BufferResult<usize> {
+		let mut total = 0;
+		while !self.ended {
+			total += self.fill(sink, N)?;
+		}
+		Ok(total)
+	}
+}
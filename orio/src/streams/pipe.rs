@@ -0,0 +1,155 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::mem;
+use std::sync::mpsc::{Receiver, sync_channel, SyncSender, TryRecvError};
+use crate::{Buffer, BufferResult, Error, ResultSetContext, SendBuffer, SIZE};
+use crate::BufferContext::{Drain, Fill};
+use crate::pool::{DefaultPoolContainer, Pool};
+use super::{Result, Sink, Source, Stream};
+
+/// Creates a pipe for moving bytes between threads. Bytes drained into the
+/// [`PipeSink`] on one thread become readable from the [`PipeSource`] on another.
+///
+/// Data is sent in chunks of up to one segment. Segments are moved out of the
+/// sink's buffer and sent as a [`SendBuffer`], so only segments sharing memory
+/// with other segments are copied. Segments are claimed from the pool of the
+/// draining thread and collected into the pool of the reading thread. At most
+/// `capacity` chunks can be in transit; draining blocks once the pipe is full
+/// until the source catches up. With a `capacity` of zero, each drain blocks
+/// until its chunks are received.
+///
+/// Filling the source blocks until data is available. Once the sink is closed or
+/// dropped and all data is read, the source reaches end-of-stream. If the source
+/// is closed or dropped, draining into the sink fails with a "stream closed"
+/// error.
+pub fn pipe<'d, const N: usize>(capacity: usize) -> (PipeSink<'d, N>, PipeSource<'d, N>)
+where DefaultPoolContainer: Pool<N> {
+	let (sender, receiver) = sync_channel(capacity);
+	(
+		PipeSink { sender: Some(sender) },
+		PipeSource {
+			buffer: Buffer::default(),
+			receiver: Some(receiver),
+			is_eos: false,
+		}
+	)
+}
+
+/// The writing end of a [`pipe`].
+pub struct PipeSink<'d, const N: usize = SIZE> {
+	sender: Option<SyncSender<SendBuffer<'d, N>>>,
+}
+
+/// The reading end of a [`pipe`].
+pub struct PipeSource<'d, const N: usize = SIZE, P: Pool<N> = DefaultPoolContainer> {
+	buffer: Buffer<'d, N, P>,
+	receiver: Option<Receiver<SendBuffer<'d, N>>>,
+	is_eos: bool,
+}
+
+impl<'d, const N: usize, P: Pool<N>> PipeSource<'d, N, P> {
+	/// Receives a chunk into the buffer, blocking if `block` is `true`. Returns
+	/// `false` if no chunk could be received.
+	fn receive(&mut self, block: bool) -> BufferResult<bool> {
+		let Some(receiver) = &self.receiver else { return Ok(false) };
+		let chunk = if block {
+			receiver.recv().ok()
+		} else {
+			match receiver.try_recv() {
+				Ok(chunk) => Some(chunk),
+				Err(TryRecvError::Empty) => return Ok(false),
+				Err(TryRecvError::Disconnected) => None
+			}
+		};
+
+		match chunk {
+			Some(chunk) => {
+				self.buffer.drain_all(&mut Buffer::<N, P>::from(chunk))?;
+				Ok(true)
+			}
+			None => {
+				self.is_eos = true;
+				Ok(false)
+			}
+		}
+	}
+}
+
+impl<const N: usize> Stream<N> for PipeSink<'_, N> {
+	fn is_closed(&self) -> bool {
+		self.sender.is_none()
+	}
+
+	/// Closes the sink. The source reaches end-of-stream after reading the data
+	/// already sent.
+	fn close(&mut self) -> Result {
+		self.sender.take();
+		Ok(())
+	}
+}
+
+impl<'d, const N: usize> Sink<'d, N> for PipeSink<'d, N> {
+	fn drain(&mut self, source: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		let Some(sender) = &self.sender else {
+			return Err(Error::closed(Drain))
+		};
+
+		send_chunks(sender, source, count)
+	}
+}
+
+/// Moves up to `count` bytes from `source` into chunks of up to one segment,
+/// sending them to `sender`. Returns the number of bytes sent.
+fn send_chunks<'d, const N: usize, P: Pool<N>>(
+	sender: &SyncSender<SendBuffer<'d, N>>,
+	source: &mut Buffer<'d, N, P>,
+	count: usize
+) -> BufferResult<usize> {
+	let count = count.min(source.count());
+	let mut chunk = Buffer::<N, P>::default();
+	let mut drained = 0;
+	while drained < count {
+		let len = source.fill(&mut chunk, N.min(count - drained)).set_context(Drain)?;
+		if sender.send(mem::take(&mut chunk).into_send()).is_err() {
+			return Err(Error::closed(Drain))
+		}
+		drained += len;
+	}
+	Ok(drained)
+}
+
+impl<const N: usize, P: Pool<N>> Stream<N> for PipeSource<'_, N, P> {
+	fn is_closed(&self) -> bool {
+		self.receiver.is_none()
+	}
+
+	/// Closes the source. Subsequent drains into the sink will fail.
+	fn close(&mut self) -> Result {
+		self.receiver.take();
+		self.buffer.clear();
+		Ok(())
+	}
+}
+
+impl<'d, const N: usize, P: Pool<N>> Source<'d, N> for PipeSource<'d, N, P> {
+	fn is_eos(&self) -> bool {
+		self.is_eos && self.buffer.is_empty()
+	}
+
+	/// Fills `sink` with up to `count` bytes, blocking until some data is available
+	/// or end-of-stream is reached, then reading what's available without blocking.
+	fn fill(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		if Stream::<N>::is_closed(self) {
+			return Err(Error::closed(Fill))
+		}
+
+		while self.buffer.count() < count && self.receive(self.buffer.is_empty())? { }
+		self.buffer.fill(sink, count)
+	}
+
+	/// Fills `sink` with all available data, blocking until some data is available
+	/// or end-of-stream is reached.
+	fn fill_all(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>) -> BufferResult<usize> {
+		self.fill(sink, usize::MAX)
+	}
+}
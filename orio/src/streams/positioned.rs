@@ -0,0 +1,33 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Buffer, BufferResult, SIZE};
+use crate::pool::Pool;
+
+/// A source which can be read at any position without moving a shared cursor,
+/// such as a file read with `pread`. Reads only borrow the source immutably, so
+/// concurrent readers can share one handle without racing to seek.
+pub trait PositionedSource<'d, const N: usize = SIZE> {
+	/// Fills `sink` with up to `count` bytes read starting at `offset`, returning
+	/// the number of bytes read. Fewer than `count` bytes are read only if the end
+	/// of the source is reached.
+	fn fill_at(
+		&self,
+		offset: u64,
+		sink: &mut Buffer<'d, N, impl Pool<N>>,
+		count: usize
+	) -> BufferResult<usize>;
+}
+
+/// A sink which can be written at any position without moving a shared cursor,
+/// such as a file written with `pwrite`. Writes only borrow the sink immutably,
+/// so concurrent writers can share one handle without racing to seek.
+pub trait PositionedSink<'d, const N: usize = SIZE> {
+	/// Drains up to `count` bytes from `source`, writing them starting at `offset`,
+	/// and returning the number of bytes written.
+	fn drain_at(
+		&self,
+		offset: u64,
+		source: &mut Buffer<'d, N, impl Pool<N>>,
+		count: usize
+	) -> BufferResult<usize>;
+}
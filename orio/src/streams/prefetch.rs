@@ -0,0 +1,221 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{io, mem};
+use std::panic::resume_unwind;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, sync_channel, SyncSender, TryRecvError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use crate::{Buffer, BufferResult, Error, ResultContext, SendBuffer, SIZE};
+use crate::BufferContext::Fill;
+use crate::pool::{DefaultPoolContainer, Pool};
+use super::{Result, Sink, Source, Stream};
+
+/// The longest the background thread sleeps while the inner source has no data.
+const MAX_BACKOFF: Duration = Duration::from_millis(10);
+
+/// A message sent from the background thread.
+enum Message<const N: usize> {
+	/// Data read from the inner source.
+	Data(SendBuffer<'static, N>),
+	/// An error ending prefetching.
+	Error(io::Error),
+	/// The inner source reached end-of-stream.
+	End,
+}
+
+/// A [`Source`] reading its inner source ahead on a background thread, so that
+/// CPU-bound consumers such as decompressors and parsers overlap with IO.
+///
+/// The background thread fills segments from the inner source and sends them to
+/// a bounded queue. At most `capacity` segments are queued; the thread blocks
+/// once the queue is full until the source catches up. Segments are claimed from
+/// the background thread's pool and collected into the pool of the thread reading
+/// them.
+///
+/// Filling the source blocks until a segment is available, then takes what's
+/// queued without blocking. While the inner source has no data available, such
+/// as when reads return zero bytes or [`WouldBlock`] errors, the background thread
+/// backs off and reads again; end-of-stream is only reported once the inner
+/// source ends. Other errors from the inner source are converted to IO errors to
+/// be sent between threads, and end prefetching after the data read before them.
+/// Closing the source stops the background thread, waiting for its current read,
+/// then closes the inner source.
+///
+/// [`WouldBlock`]: io::ErrorKind::WouldBlock
+pub struct PrefetchSource<'d, S: Source<'static, N>, const N: usize = SIZE, P: Pool<N> = DefaultPoolContainer> {
+	buffer: Buffer<'d, N, P>,
+	receiver: Option<Receiver<Message<N>>>,
+	thread: Option<JoinHandle<S>>,
+	stop: Arc<AtomicBool>,
+	error: Option<io::Error>,
+	is_eos: bool,
+}
+
+impl<'d, S: Source<'static, N>, const N: usize, P: Pool<N>> PrefetchSource<'d, S, N, P> {
+	/// Starts prefetching `source` on a background thread, queuing at most `capacity`
+	/// segments.
+	pub fn new(source: S, capacity: usize) -> Self where S: Send + 'static, P: 'static {
+		let (sender, receiver) = sync_channel(capacity);
+		let stop = Arc::<AtomicBool>::default();
+		let thread = {
+			let stop = stop.clone();
+			thread::spawn(move || prefetch::<S, N, P>(source, sender, &stop))
+		};
+		Self {
+			buffer: Buffer::default(),
+			receiver: Some(receiver),
+			thread: Some(thread),
+			stop,
+			error: None,
+			is_eos: false,
+		}
+	}
+
+	/// Stops prefetching and returns the inner source without closing it, along
+	/// with any data read ahead but not yet read from this source. Returns `None`
+	/// if the source was closed, as the inner source is closed with it.
+	pub fn into_inner(mut self) -> Option<(S, Buffer<'d, N, P>)> {
+		let thread = self.thread.take()?;
+		self.stop.store(true, Ordering::Release);
+		// Receive until the background thread exits, it may be blocked sending.
+		while let Ok(true) = self.receive(true) { }
+		Some((join(thread), mem::take(&mut self.buffer)))
+	}
+
+	/// Receives a segment into the buffer, blocking if `block` is `true`. Returns
+	/// `false` if no segment could be received.
+	fn receive(&mut self, block: bool) -> BufferResult<bool> {
+		let Some(receiver) = &self.receiver else { return Ok(false) };
+		let message = if block {
+			receiver.recv().ok()
+		} else {
+			match receiver.try_recv() {
+				Ok(message) => Some(message),
+				Err(TryRecvError::Empty) => return Ok(false),
+				Err(TryRecvError::Disconnected) => None
+			}
+		};
+
+		match message {
+			Some(Message::Data(chunk)) => {
+				self.buffer.drain_all(&mut Buffer::<N, P>::from(chunk))?;
+				return Ok(true)
+			}
+			// Errors end prefetching, so the stream ends once the error is returned.
+			Some(Message::Error(error)) => {
+				self.error = Some(error);
+				self.is_eos = true;
+			}
+			Some(Message::End) => self.is_eos = true,
+			// The background thread stopped without ending the stream.
+			None => { }
+		}
+		self.receiver = None;
+		Ok(false)
+	}
+}
+
+/// Waits for the background thread to exit, returning the inner source.
+fn join<S>(thread: JoinHandle<S>) -> S {
+	thread.join().unwrap_or_else(|panic| resume_unwind(panic))
+}
+
+/// Fills segments from `source`, sending them to `sender` until end-of-stream,
+/// an error, or `stop` is set, then returns the source. While no data is
+/// available, reads are retried with an exponential backoff.
+fn prefetch<S, const N: usize, P: Pool<N>>(
+	mut source: S,
+	sender: SyncSender<Message<N>>,
+	stop: &AtomicBool,
+) -> S where S: Source<'static, N> {
+	let mut buffer = Buffer::<N, P>::default();
+	let mut backoff = Duration::ZERO;
+	while !stop.load(Ordering::Acquire) {
+		if source.is_eos() {
+			let _ = sender.send(Message::End);
+			break
+		}
+
+		let result = source.fill(&mut buffer, N);
+		let is_idle = buffer.is_empty();
+		// Send data read before an error, then the error.
+		if !is_idle && sender.send(Message::Data(mem::take(&mut buffer).into_send())).is_err() {
+			break
+		}
+		match result {
+			Ok(_) => { }
+			Err(error) if error.is_eos() => {
+				let _ = sender.send(Message::End);
+				break
+			}
+			Err(error) if error.is_would_block() => { }
+			Err(error) => {
+				let _ = sender.send(Message::Error(error.into()));
+				break
+			}
+		}
+
+		// No data is available yet, wait before reading again.
+		if !is_idle {
+			backoff = Duration::ZERO;
+		} else if backoff.is_zero() {
+			thread::yield_now();
+			backoff = Duration::from_micros(50);
+		} else {
+			thread::sleep(backoff);
+			backoff = (backoff * 2).min(MAX_BACKOFF);
+		}
+	}
+	source
+}
+
+impl<'d, S: Source<'static, N>, const N: usize, P: Pool<N>> Stream<N> for PrefetchSource<'d, S, N, P> {
+	fn is_closed(&self) -> bool {
+		self.thread.is_none()
+	}
+
+	/// Stops the background thread, then closes the inner source. Data read ahead
+	/// is discarded.
+	fn close(&mut self) -> Result {
+		let Some(thread) = self.thread.take() else {
+			return Ok(())
+		};
+
+		self.stop.store(true, Ordering::Release);
+		// Dropping the receiver unblocks the background thread if the queue is full.
+		self.receiver.take();
+		self.buffer.clear();
+		join(thread).close()
+	}
+}
+
+impl<'d, S: Source<'static, N>, const N: usize, P: Pool<N>> Source<'d, N> for PrefetchSource<'d, S, N, P> {
+	fn is_eos(&self) -> bool {
+		self.is_eos && self.buffer.is_empty() && self.error.is_none()
+	}
+
+	/// Fills `sink` with up to `count` bytes, blocking until a segment is available
+	/// or end-of-stream is reached, then taking queued segments without blocking.
+	fn fill(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		if Stream::<N>::is_closed(self) {
+			return Err(Error::closed(Fill))
+		}
+
+		while self.buffer.count() < count && self.receive(self.buffer.is_empty())? { }
+
+		if self.buffer.is_empty() {
+			if let Some(error) = self.error.take() {
+				return Err(error).context(Fill)
+			}
+		}
+		self.buffer.fill(sink, count)
+	}
+}
+
+impl<'d, S: Source<'static, N>, const N: usize, P: Pool<N>> Drop for PrefetchSource<'d, S, N, P> {
+	fn drop(&mut self) {
+		let _ = self.close();
+	}
+}
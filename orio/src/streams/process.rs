@@ -0,0 +1,61 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::io::{ErrorKind, Write};
+use std::process::{ChildStderr, ChildStdin, ChildStdout, Command, ExitStatus, Stdio};
+use std::thread;
+use crate::{Buffer, copy, ResultContext, StreamResult};
+use crate::StreamContext::Other;
+use super::{ReaderSource, WriterSink};
+
+/// A [`Source`] reading from a child process's standard output.
+///
+/// [`Source`]: super::Source
+pub type ChildStdoutSource = ReaderSource<ChildStdout>;
+/// A [`Source`] reading from a child process's standard error.
+///
+/// [`Source`]: super::Source
+pub type ChildStderrSource = ReaderSource<ChildStderr>;
+/// A [`Sink`] writing to a child process's standard input.
+///
+/// [`Sink`]: super::Sink
+pub type ChildStdinSink = WriterSink<ChildStdin>;
+
+/// Spawns `command` with piped standard input and output, writes all of `input`
+/// to its stdin, and collects its stdout into a new buffer, returning its exit
+/// status and output once it exits. Input is written from a separate thread, so
+/// a command filling its output pipe before reading all input won't deadlock.
+///
+/// A command exiting before reading all input isn't considered an error.
+pub fn pipe_command(
+	command: &mut Command,
+	input: &mut Buffer<'_>
+) -> StreamResult<(ExitStatus, Buffer<'static>)> {
+	let mut child = command.stdin(Stdio::piped())
+						   .stdout(Stdio::piped())
+						   .spawn()
+						   .context(Other("spawning command"))?;
+	let mut stdin = child.stdin.take().expect("stdin should be piped");
+	let stdout = child.stdout.take().expect("stdout should be piped");
+
+	// Segments can't be sent between threads, but their slices can. The input is
+	// written directly from them, without copying.
+	let slices: Vec<&[u8]> = input.as_byte_str().slices().collect();
+	let mut output = Buffer::default();
+	let (copy_result, write_result) = thread::scope(|scope| {
+		// Stdin is dropped when the thread finishes, closing the pipe.
+		let writer = scope.spawn(move || {
+			slices.into_iter().try_for_each(|slice| stdin.write_all(slice))
+		});
+		let copy_result = copy(&mut ChildStdoutSource::from(stdout), &mut output);
+		(copy_result, writer.join().expect("stdin writer thread should not panic"))
+	});
+	input.clear();
+	let status = child.wait().context(Other("waiting for command"))?;
+	copy_result?;
+	match write_result {
+		Err(error) if error.kind() != ErrorKind::BrokenPipe =>
+			Err(error).context(Other("writing to command"))?,
+		_ => { }
+	}
+	Ok((status, output))
+}
@@ -0,0 +1,258 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! [Protocol Buffers] wire-format primitives over [`BufSource`]s and [`BufSink`]s,
+//! for building codecs without copying each message into an intermediate `Vec`.
+//! Length-delimited fields are moved between buffers by sharing segments rather
+//! than copying.
+//!
+//! [Protocol Buffers]: https://protobuf.dev/programming-guides/encoding
+
+use crate::{Buffer, StreamError};
+use crate::pool::Pool;
+use crate::StreamContext::Read;
+use super::{invalid_data, invalid_input, BufSink, BufSource, Result};
+
+/// The maximum encoded length of a varint.
+const MAX_VARINT_LEN: usize = 10;
+/// The maximum field number.
+pub const MAX_FIELD_NUMBER: u32 = (1 << 29) - 1;
+/// The maximum depth of nested groups skipped by [`skip_field`].
+const MAX_DEPTH: usize = 100;
+
+/// A field wire type, the low three bits of a field key.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum WireType {
+	/// A varint: `int32`, `int64`, `uint32`, `uint64`, `sint32`, `sint64`, `bool`,
+	/// or `enum`.
+	Varint = 0,
+	/// A little-endian 64-bit value: `fixed64`, `sfixed64`, or `double`.
+	I64 = 1,
+	/// A length-delimited value: `string`, `bytes`, an embedded message, or a
+	/// packed repeated field.
+	Len = 2,
+	/// A group start. Deprecated.
+	StartGroup = 3,
+	/// A group end. Deprecated.
+	EndGroup = 4,
+	/// A little-endian 32-bit value: `fixed32`, `sfixed32`, or `float`.
+	I32 = 5,
+}
+
+impl TryFrom<u8> for WireType {
+	type Error = u8;
+
+	/// Converts a 3-bit wire type, returning the value back if invalid.
+	fn try_from(value: u8) -> Result<Self, u8> {
+		Ok(
+			match value {
+				0 => Self::Varint,
+				1 => Self::I64,
+				2 => Self::Len,
+				3 => Self::StartGroup,
+				4 => Self::EndGroup,
+				5 => Self::I32,
+				_ => return Err(value)
+			}
+		)
+	}
+}
+
+/// Reads a base-128 varint from `source`.
+///
+/// # Errors
+///
+/// Varints longer than 10 bytes, or overflowing 64 bits, fail with an IO error of
+/// kind [`InvalidData`]. No bytes are consumed if the varint is invalid.
+///
+/// [`InvalidData`]: std::io::ErrorKind::InvalidData
+pub fn read_varint<'d, const N: usize>(source: &mut impl BufSource<'d, N>) -> Result<u64> {
+	let (value, len) = peek_varint(source)?;
+	source.skip(len)?;
+	Ok(value)
+}
+
+/// Reads a field key from `source`, returning its field number and wire type, or
+/// `None` if the source ended before the key. Messages have no end marker, so
+/// this is how the end of a top-level message is found.
+///
+/// # Errors
+///
+/// Keys with an invalid wire type or field number fail with an IO error of kind
+/// [`InvalidData`]. No bytes are consumed if the key is invalid.
+///
+/// [`InvalidData`]: std::io::ErrorKind::InvalidData
+pub fn read_key<'d, const N: usize>(source: &mut impl BufSource<'d, N>) -> Result<Option<(u32, WireType)>> {
+	if !source.request(1)? {
+		return Ok(None)
+	}
+
+	let (key, len) = peek_varint(source)?;
+	let Ok(wire_type) = WireType::try_from((key & 0x7) as u8) else {
+		return Err(invalid_data("invalid wire type"))
+	};
+	let field = key >> 3;
+	if field == 0 || field > MAX_FIELD_NUMBER as u64 {
+		return Err(invalid_data("invalid field number"))
+	}
+	source.skip(len)?;
+	Ok(Some((field as u32, wire_type)))
+}
+
+/// Reads a length-delimited value from `source`: a varint length, then that many
+/// bytes. The bytes are moved from the source buffer without copying.
+///
+/// # Errors
+///
+/// Lengths greater than `max_len` fail with an IO error of kind [`InvalidData`].
+/// No bytes are consumed if the length is invalid.
+///
+/// [`InvalidData`]: std::io::ErrorKind::InvalidData
+pub fn read_length_delimited<'d, S: BufSource<'d, N>, const N: usize>(
+	source: &mut S,
+	max_len: usize
+) -> Result<Buffer<'d, N, S::Pool>> {
+	let len = read_len(source, max_len)?;
+	let mut value = Buffer::default();
+	let mut remaining = len;
+	while remaining > 0 {
+		source.require(1)?;
+		remaining -= source.read(&mut value, remaining)?;
+	}
+	Ok(value)
+}
+
+/// Skips the value of field number `field` with type `wire_type` from `source`,
+/// after its key has been read. Groups are skipped up to and including their end
+/// key.
+///
+/// # Errors
+///
+/// An [`EndGroup`] wire type, an end key not matching its group's field number,
+/// groups nested more than 100 deep, or an invalid key or varint within the value
+/// fails with an IO error of kind [`InvalidData`]. If the source ends within the
+/// value, an end-of-stream error is returned.
+///
+/// [`EndGroup`]: WireType::EndGroup
+/// [`InvalidData`]: std::io::ErrorKind::InvalidData
+pub fn skip_field<'d, const N: usize>(source: &mut impl BufSource<'d, N>, field: u32, wire_type: WireType) -> Result {
+	match wire_type {
+		WireType::Varint => { read_varint(source)?; }
+		WireType::I64 => skip_exact(source, 8)?,
+		WireType::I32 => skip_exact(source, 4)?,
+		WireType::Len => {
+			let len = read_len(source, usize::MAX)?;
+			skip_exact(source, len)?;
+		}
+		WireType::StartGroup => skip_group(source, field)?,
+		WireType::EndGroup => return Err(invalid_data("unexpected group end"))
+	}
+	Ok(())
+}
+
+/// Skips the group started by field number `field`, tracking nested groups in
+/// a stack rather than recursing, so untrusted input can't overflow the stack.
+fn skip_group<'d, const N: usize>(source: &mut impl BufSource<'d, N>, field: u32) -> Result {
+	let mut groups = vec![field];
+	while let Some(&group) = groups.last() {
+		let Some((field, wire_type)) = read_key(source)? else {
+			return Err(StreamError::end_of_stream(1, Read))
+		};
+		match wire_type {
+			WireType::StartGroup if groups.len() >= MAX_DEPTH =>
+				return Err(invalid_data("group nested too deeply")),
+			WireType::StartGroup => groups.push(field),
+			WireType::EndGroup if field == group => { groups.pop(); }
+			WireType::EndGroup => return Err(invalid_data("mismatched group end")),
+			_ => skip_field(source, field, wire_type)?
+		}
+	}
+	Ok(())
+}
+
+/// Writes `value` to `sink` as a base-128 varint.
+pub fn write_varint<'d, const N: usize>(sink: &mut impl BufSink<'d, N>, mut value: u64) -> Result {
+	let mut bytes = [0; MAX_VARINT_LEN];
+	let mut len = 0;
+	loop {
+		let byte = (value & 0x7F) as u8;
+		value >>= 7;
+		if value == 0 {
+			bytes[len] = byte;
+			len += 1;
+			break
+		}
+		bytes[len] = byte | 0x80;
+		len += 1;
+	}
+	sink.write_from_slice(&bytes[..len])?;
+	Ok(())
+}
+
+/// Writes a field key to `sink`.
+///
+/// # Errors
+///
+/// Field numbers of zero or greater than [`MAX_FIELD_NUMBER`] fail with an IO
+/// error of kind [`InvalidInput`].
+///
+/// [`InvalidInput`]: std::io::ErrorKind::InvalidInput
+pub fn write_key<'d, const N: usize>(sink: &mut impl BufSink<'d, N>, field: u32, wire_type: WireType) -> Result {
+	if field == 0 || field > MAX_FIELD_NUMBER {
+		return Err(invalid_input("invalid field number"))
+	}
+	write_varint(sink, (field as u64) << 3 | wire_type as u64)
+}
+
+/// Writes all bytes of `value` to `sink` as a length-delimited value. The bytes
+/// are moved into the sink buffer without copying.
+pub fn write_length_delimited<'d, const N: usize>(
+	sink: &mut impl BufSink<'d, N>,
+	value: &mut Buffer<'d, N, impl Pool<N>>
+) -> Result {
+	write_varint(sink, value.count() as u64)?;
+	sink.write_all(value)?;
+	Ok(())
+}
+
+/// Decodes a varint at the front of the buffer, returning its value and encoded
+/// length without consuming it.
+fn peek_varint<'d, const N: usize>(source: &mut impl BufSource<'d, N>) -> Result<(u64, usize)> {
+	source.request(MAX_VARINT_LEN)?;
+	let buf = source.buf();
+	let mut value = 0;
+	for i in 0..MAX_VARINT_LEN {
+		let Some(byte) = buf.get(i) else {
+			return Err(StreamError::end_of_stream(i + 1, Read))
+		};
+		// The tenth byte holds only the highest bit.
+		if i == MAX_VARINT_LEN - 1 && byte > 1 {
+			break
+		}
+		value |= ((byte & 0x7F) as u64) << (i * 7);
+		if byte & 0x80 == 0 {
+			return Ok((value, i + 1))
+		}
+	}
+	Err(invalid_data("varint too long"))
+}
+
+/// Reads a varint length, checking that it's at most `max_len`.
+fn read_len<'d, const N: usize>(source: &mut impl BufSource<'d, N>, max_len: usize) -> Result<usize> {
+	let (len, varint_len) = peek_varint(source)?;
+	let len = usize::try_from(len).unwrap_or(usize::MAX);
+	if len > max_len {
+		return Err(invalid_data("length-delimited value too long"))
+	}
+	source.skip(varint_len)?;
+	Ok(len)
+}
+
+/// Skips exactly `count` bytes from `source`.
+fn skip_exact<'d, const N: usize>(source: &mut impl BufSource<'d, N>, count: usize) -> Result {
+	let skipped = source.skip(count)?;
+	if skipped < count {
+		return Err(StreamError::end_of_stream(count - skipped, Read))
+	}
+	Ok(())
+}
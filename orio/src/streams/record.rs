@@ -0,0 +1,301 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::marker::PhantomData;
+use std::mem;
+use crate::{Buffer, BufferResult, Error, ResultContext, SIZE, StreamError};
+use crate::BufferContext::Fill;
+use crate::StreamContext::Read;
+use crate::pool::Pool;
+use super::{invalid_data, BufSink, BufSource, Result, Source, Stream};
+
+/// A capture of bytes read from a source, along with the size of each fill. Used
+/// with [`RecordingSource`] and [`ReplaySource`].
+///
+/// Recordings can be saved with [`write_to`] and loaded with [`read_from`]. The
+/// format is a sequence of chunks, each a big-endian [`u64`] length followed by
+/// that many bytes.
+///
+/// [`write_to`]: Self::write_to
+/// [`read_from`]: Self::read_from
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Recording {
+	data: Vec<u8>,
+	chunks: Vec<usize>,
+}
+
+impl Recording {
+	/// Returns all recorded bytes.
+	pub fn data(&self) -> &[u8] { &self.data }
+
+	/// Returns the number of bytes read by each fill, in order.
+	pub fn chunks(&self) -> &[usize] { &self.chunks }
+
+	/// Returns an iterator over the bytes read by each fill, in order.
+	pub fn iter_chunks(&self) -> impl Iterator<Item = &[u8]> {
+		self.chunks.iter().scan(0, |pos, &len| {
+			let chunk = &self.data[*pos..*pos + len];
+			*pos += len;
+			Some(chunk)
+		})
+	}
+
+	/// Returns the total number of bytes recorded.
+	pub fn count(&self) -> usize { self.data.len() }
+
+	/// Returns `true` if no fills were recorded.
+	pub fn is_empty(&self) -> bool { self.chunks.is_empty() }
+
+	/// Appends a chunk.
+	pub fn push_chunk(&mut self, chunk: &[u8]) {
+		self.data.extend_from_slice(chunk);
+		self.chunks.push(chunk.len());
+	}
+
+	/// Clears the recording.
+	pub fn clear(&mut self) {
+		self.data.clear();
+		self.chunks.clear();
+	}
+
+	/// Writes the recording to `sink`.
+	pub fn write_to<'d, const N: usize>(&self, sink: &mut impl BufSink<'d, N>) -> Result {
+		for chunk in self.iter_chunks() {
+			sink.write_u64(chunk.len() as u64)?;
+			sink.write_from_slice(chunk)?;
+		}
+		Ok(())
+	}
+
+	/// Reads a recording from `source` until end-of-stream. Chunks are read as
+	/// they arrive, so a corrupt length can't allocate more than the source
+	/// contains.
+	///
+	/// # Errors
+	///
+	/// If a chunk length doesn't fit in memory, an IO error of kind [`InvalidData`]
+	/// is returned. If the source ends within a chunk, an end-of-stream error is
+	/// returned.
+	///
+	/// [`InvalidData`]: std::io::ErrorKind::InvalidData
+	pub fn read_from<'d, const N: usize>(source: &mut impl BufSource<'d, N>) -> Result<Self> {
+		let mut recording = Self::default();
+		while source.request(1)? {
+			let Ok(len) = usize::try_from(source.read_u64()?) else {
+				return Err(invalid_data("recording chunk too large"))
+			};
+			let read = source.read_to_vec(&mut recording.data, len)?;
+			if read < len {
+				return Err(StreamError::end_of_stream(len - read, Read))
+			}
+			recording.chunks.push(len);
+		}
+		Ok(recording)
+	}
+}
+
+/// A [`Source`] capturing every byte read from its inner source into a
+/// [`Recording`], preserving the size of each fill. Errors are not recorded.
+pub struct RecordingSource<'d, S: Source<'d, N>, const N: usize = SIZE> {
+	source: Option<S>,
+	recording: Recording,
+	__data: PhantomData<&'d ()>
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> From<S> for RecordingSource<'d, S, N> {
+	fn from(source: S) -> Self {
+		Self::new(source)
+	}
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> RecordingSource<'d, S, N> {
+	/// Creates a new recording source.
+	pub fn new(source: S) -> Self {
+		Self {
+			source: Some(source),
+			recording: Recording::default(),
+			__data: PhantomData
+		}
+	}
+
+	/// Returns the recording.
+	pub fn recording(&self) -> &Recording { &self.recording }
+
+	/// Takes the recording, leaving an empty recording in its place.
+	pub fn take_recording(&mut self) -> Recording {
+		mem::take(&mut self.recording)
+	}
+
+	/// Returns a reference to the inner source.
+	pub fn source(&self) -> &S {
+		unsafe {
+			// Safety: option will only be None if into_inner is called, but this
+			// consumes and drops self, making it impossible to ever have a
+			// reference (except on drop, which is guarded).
+			self.source.as_ref().unwrap_unchecked()
+		}
+	}
+
+	/// Returns a mutable reference to the inner source.
+	pub fn source_mut(&mut self) -> &mut S {
+		unsafe {
+			// Safety: see `source`
+			self.source.as_mut().unwrap_unchecked()
+		}
+	}
+
+	/// Consumes the recording source, returning the inner source and the recording.
+	pub fn into_inner(mut self) -> (S, Recording) {
+		let source = unsafe {
+			// Safety: option will only be None if this method was already called,
+			// which is impossible because we consume self.
+			self.source.take().unwrap_unchecked()
+		};
+		(source, self.take_recording())
+	}
+
+	/// Runs a fill operation, recording bytes written to the back of `sink`.
+	fn record<P: Pool<N>>(
+		&mut self,
+		sink: &mut Buffer<'d, N, P>,
+		fill: impl FnOnce(&mut S, &mut Buffer<'d, N, P>) -> BufferResult<usize>
+	) -> BufferResult<usize> {
+		let start = sink.count();
+		let count = fill(self.source_mut(), sink)?;
+		let mut chunk = vec![0; count];
+		sink.read_slice_exact_at(start, &mut chunk)?;
+		self.recording.push_chunk(&chunk);
+		Ok(count)
+	}
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> Stream<N> for RecordingSource<'d, S, N> {
+	fn is_closed(&self) -> bool {
+		self.source().is_closed()
+	}
+
+	fn close(&mut self) -> Result {
+		self.source_mut().close()
+	}
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> Source<'d, N> for RecordingSource<'d, S, N> {
+	fn is_eos(&self) -> bool {
+		self.source().is_eos()
+	}
+
+	fn fill(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		self.record(sink, |source, sink| source.fill(sink, count))
+	}
+
+	fn fill_free(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>) -> BufferResult<usize> {
+		self.record(sink, |source, sink| source.fill_free(sink))
+	}
+
+	fn fill_all(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>) -> BufferResult<usize> {
+		self.record(sink, |source, sink| source.fill_all(sink))
+	}
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> Drop for RecordingSource<'d, S, N> {
+	fn drop(&mut self) {
+		// If into_inner was called, closing would cause a seg fault.
+		if self.source.is_some() {
+			let _ = self.close();
+		}
+	}
+}
+
+/// A [`Source`] replaying a [`Recording`], returning the recorded chunks from
+/// successive fills. This reproduces the fill boundaries of the original source,
+/// as long as each fill requests at least as many bytes as were recorded; a chunk
+/// larger than the requested count is split.
+#[derive(Clone, Debug)]
+pub struct ReplaySource {
+	recording: Recording,
+	chunk: usize,
+	chunk_offset: usize,
+	offset: usize,
+	closed: bool,
+}
+
+impl From<Recording> for ReplaySource {
+	fn from(recording: Recording) -> Self {
+		Self::new(recording)
+	}
+}
+
+impl ReplaySource {
+	/// Creates a new replay source.
+	pub fn new(recording: Recording) -> Self {
+		Self {
+			recording,
+			chunk: 0,
+			chunk_offset: 0,
+			offset: 0,
+			closed: false,
+		}
+	}
+
+	/// Returns the recording.
+	pub fn recording(&self) -> &Recording { &self.recording }
+
+	/// Returns the number of bytes replayed.
+	pub fn position(&self) -> usize { self.offset }
+
+	/// Restarts the replay from the beginning.
+	pub fn rewind(&mut self) {
+		self.chunk = 0;
+		self.chunk_offset = 0;
+		self.offset = 0;
+	}
+
+	/// Replays up to `count` bytes of the next chunk.
+	fn replay<'d, const N: usize>(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		if self.closed {
+			return Err(Error::closed(Fill))
+		}
+
+		let Some(&len) = self.recording.chunks.get(self.chunk) else {
+			return Ok(0)
+		};
+
+		let count = count.min(len - self.chunk_offset);
+		sink.write_from_slice(&self.recording.data[self.offset..][..count]).context(Fill)?;
+		self.offset += count;
+		self.chunk_offset += count;
+		if self.chunk_offset == len {
+			self.chunk += 1;
+			self.chunk_offset = 0;
+		}
+		Ok(count)
+	}
+}
+
+impl<const N: usize> Stream<N> for ReplaySource {
+	fn is_closed(&self) -> bool { self.closed }
+
+	fn close(&mut self) -> Result {
+		self.closed = true;
+		Ok(())
+	}
+}
+
+impl<'d, const N: usize> Source<'d, N> for ReplaySource {
+	fn is_eos(&self) -> bool {
+		self.chunk >= self.recording.chunks.len()
+	}
+
+	fn fill(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		self.replay(sink, count)
+	}
+
+	/// Replays the rest of the next chunk.
+	fn fill_free(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>) -> BufferResult<usize> {
+		self.replay(sink, usize::MAX)
+	}
+
+	/// Replays the rest of the next chunk.
+	fn fill_all(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>) -> BufferResult<usize> {
+		self.replay(sink, usize::MAX)
+	}
+}
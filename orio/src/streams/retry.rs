@@ -0,0 +1,300 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::marker::PhantomData;
+use std::thread;
+use std::time::Duration;
+use crate::{Buffer, BufferResult, Error};
+use crate::error::Context;
+use crate::pool::Pool;
+use super::{Result, Sink, Source, Stream};
+
+/// A policy for retrying stream operations failing with transient errors.
+///
+/// Operations failing with [`Interrupted`] are retried immediately. If enabled,
+/// operations failing with [`WouldBlock`] are retried after sleeping, starting at
+/// `initial_backoff` and doubling up to `max_backoff` with each attempt.
+///
+/// [`Interrupted`]: std::io::ErrorKind::Interrupted
+/// [`WouldBlock`]: std::io::ErrorKind::WouldBlock
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct RetryPolicy {
+	/// The maximum number of times a single operation is retried before the error
+	/// is returned. Defaults to `16`.
+	pub max_retries: usize,
+	/// Whether to retry operations failing with [`WouldBlock`]. Defaults to
+	/// `false`.
+	///
+	/// [`WouldBlock`]: std::io::ErrorKind::WouldBlock
+	pub retry_would_block: bool,
+	/// The first backoff duration. Defaults to 1ms.
+	pub initial_backoff: Duration,
+	/// The maximum backoff duration. Defaults to 100ms.
+	pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+	fn default() -> Self {
+		Self {
+			max_retries: 16,
+			retry_would_block: false,
+			initial_backoff: Duration::from_millis(1),
+			max_backoff: Duration::from_millis(100),
+		}
+	}
+}
+
+impl RetryPolicy {
+	/// Sets the maximum number of retries.
+	#[inline]
+	pub fn max_retries(mut self, value: usize) -> Self {
+		self.max_retries = value;
+		self
+	}
+
+	/// Sets whether to retry [`WouldBlock`] errors.
+	///
+	/// [`WouldBlock`]: std::io::ErrorKind::WouldBlock
+	#[inline]
+	pub fn retry_would_block(mut self, value: bool) -> Self {
+		self.retry_would_block = value;
+		self
+	}
+
+	/// Sets the initial and maximum backoff durations.
+	#[inline]
+	pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+		self.initial_backoff = initial;
+		self.max_backoff = max;
+		self
+	}
+
+	/// Runs `op` until it succeeds, fails with a non-transient error, or the retry
+	/// limit is reached.
+	fn run<T, C: Context>(&self, mut op: impl FnMut() -> core::result::Result<T, Error<C>>) -> core::result::Result<T, Error<C>> {
+		let mut backoff = self.initial_backoff;
+		let mut retries = 0;
+		loop {
+			match op() {
+				Err(err) if retries < self.max_retries && err.is_interrupted() => { }
+				Err(err) if retries < self.max_retries && self.retry_would_block && err.is_would_block() => {
+					thread::sleep(backoff);
+					backoff = (backoff * 2).min(self.max_backoff);
+				}
+				result => break result
+			}
+			retries += 1;
+		}
+	}
+}
+
+/// A [`Source`] retrying reads from its inner source which fail with transient
+/// errors, according to a [`RetryPolicy`].
+pub struct RetrySource<'d, S: Source<'d, N>, const N: usize> {
+	source: Option<S>,
+	/// The retry policy.
+	pub policy: RetryPolicy,
+	__data: PhantomData<&'d ()>
+}
+
+/// A [`Sink`] retrying writes to its inner sink which fail with transient errors,
+/// according to a [`RetryPolicy`].
+pub struct RetrySink<'d, S: Sink<'d, N>, const N: usize> {
+	sink: Option<S>,
+	/// The retry policy.
+	pub policy: RetryPolicy,
+	__data: PhantomData<&'d ()>
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> From<S> for RetrySource<'d, S, N> {
+	fn from(source: S) -> Self {
+		Self::new(source, RetryPolicy::default())
+	}
+}
+
+impl<'d, S: Sink<'d, N>, const N: usize> From<S> for RetrySink<'d, S, N> {
+	fn from(sink: S) -> Self {
+		Self::new(sink, RetryPolicy::default())
+	}
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> RetrySource<'d, S, N> {
+	/// Creates a new retry source with a retry `policy`.
+	pub fn new(source: S, policy: RetryPolicy) -> Self {
+		Self {
+			source: Some(source),
+			policy,
+			__data: PhantomData
+		}
+	}
+
+	/// Returns a reference to the inner source.
+	pub fn source(&self) -> &S {
+		unsafe {
+			// Safety: option will only be None if into_inner is called, but this
+			// consumes and drops self, making it impossible to ever have a
+			// reference (except on drop, which is guarded).
+			self.source.as_ref().unwrap_unchecked()
+		}
+	}
+
+	/// Returns a mutable reference to the inner source.
+	pub fn source_mut(&mut self) -> &mut S {
+		unsafe {
+			// Safety: see `source`
+			self.source.as_mut().unwrap_unchecked()
+		}
+	}
+
+	/// Consumes the retry source, returning the inner source.
+	pub fn into_inner(mut self) -> S {
+		unsafe {
+			// Safety: option will only be None if this method was already called,
+			// which is impossible because we consume self.
+			self.source.take().unwrap_unchecked()
+		}
+	}
+}
+
+impl<'d, S: Sink<'d, N>, const N: usize> RetrySink<'d, S, N> {
+	/// Creates a new retry sink with a retry `policy`.
+	pub fn new(sink: S, policy: RetryPolicy) -> Self {
+		Self {
+			sink: Some(sink),
+			policy,
+			__data: PhantomData
+		}
+	}
+
+	/// Returns a reference to the inner sink.
+	pub fn sink(&self) -> &S {
+		unsafe {
+			// Safety: option will only be None if into_inner is called, but this
+			// consumes and drops self, making it impossible to ever have a
+			// reference (except on drop, which is guarded).
+			self.sink.as_ref().unwrap_unchecked()
+		}
+	}
+
+	/// Returns a mutable reference to the inner sink.
+	pub fn sink_mut(&mut self) -> &mut S {
+		unsafe {
+			// Safety: see `sink`
+			self.sink.as_mut().unwrap_unchecked()
+		}
+	}
+
+	/// Consumes the retry sink, returning the inner sink.
+	pub fn into_inner(mut self) -> S {
+		unsafe {
+			// Safety: option will only be None if this method was already called,
+			// which is impossible because we consume self.
+			self.sink.take().unwrap_unchecked()
+		}
+	}
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> Stream<N> for RetrySource<'d, S, N> {
+	fn is_closed(&self) -> bool {
+		self.source().is_closed()
+	}
+
+	fn close(&mut self) -> Result {
+		self.source_mut().close()
+	}
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> Source<'d, N> for RetrySource<'d, S, N> {
+	fn is_eos(&self) -> bool {
+		self.source().is_eos()
+	}
+
+	/// Fills `sink` with up to `count` bytes, retrying failed reads. Bytes read
+	/// before a failure count toward `count` and the returned byte count.
+	fn fill(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		let policy = self.policy;
+		let source = self.source_mut();
+		let start = sink.count();
+		policy.run(|| {
+			let read = sink.count() - start;
+			Ok(read + source.fill(sink, count - read)?)
+		})
+	}
+
+	fn fill_free(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>) -> BufferResult<usize> {
+		let policy = self.policy;
+		let source = self.source_mut();
+		let start = sink.count();
+		policy.run(|| {
+			let read = sink.count() - start;
+			Ok(read + source.fill_free(sink)?)
+		})
+	}
+
+	fn fill_all(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>) -> BufferResult<usize> {
+		let policy = self.policy;
+		let source = self.source_mut();
+		let start = sink.count();
+		policy.run(|| {
+			let read = sink.count() - start;
+			Ok(read + source.fill_all(sink)?)
+		})
+	}
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> Drop for RetrySource<'d, S, N> {
+	fn drop(&mut self) {
+		// If into_inner was called, closing would cause a seg fault.
+		if self.source.is_some() {
+			let _ = self.close();
+		}
+	}
+}
+
+impl<'d, S: Sink<'d, N>, const N: usize> Stream<N> for RetrySink<'d, S, N> {
+	fn is_closed(&self) -> bool {
+		self.sink().is_closed()
+	}
+
+	fn close(&mut self) -> Result {
+		self.sink_mut().close()
+	}
+}
+
+impl<'d, S: Sink<'d, N>, const N: usize> Sink<'d, N> for RetrySink<'d, S, N> {
+	/// Drains up to `count` bytes from `source`, retrying failed writes. Bytes
+	/// written before a failure count toward `count` and the returned byte count.
+	fn drain(&mut self, source: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		let policy = self.policy;
+		let sink = self.sink_mut();
+		let start = source.count();
+		policy.run(|| {
+			let written = start - source.count();
+			Ok(written + sink.drain(source, count - written)?)
+		})
+	}
+
+	fn drain_all(&mut self, source: &mut Buffer<'d, N, impl Pool<N>>) -> BufferResult<usize> {
+		let policy = self.policy;
+		let sink = self.sink_mut();
+		let start = source.count();
+		policy.run(|| {
+			let written = start - source.count();
+			Ok(written + sink.drain_all(source)?)
+		})
+	}
+
+	fn flush(&mut self) -> Result {
+		let policy = self.policy;
+		let sink = self.sink_mut();
+		policy.run(|| sink.flush())
+	}
+}
+
+impl<'d, S: Sink<'d, N>, const N: usize> Drop for RetrySink<'d, S, N> {
+	fn drop(&mut self) {
+		// If into_inner was called, closing would cause a seg fault.
+		if self.sink.is_some() {
+			let _ = self.close();
+		}
+	}
+}
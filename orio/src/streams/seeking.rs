@@ -56,6 +56,17 @@ impl SeekOffset {
 	}
 }
 
+impl From<SeekOffset> for SeekFrom {
+	/// Converts with [`SeekOffset::into_seek_from`].
+	///
+	/// # Panics
+	///
+	/// Panics when a `usize` offset is too large to convert to an `i64` value.
+	fn from(value: SeekOffset) -> Self {
+		value.into_seek_from()
+	}
+}
+
 impl From<SeekFrom> for SeekOffset {
 	fn from(value: SeekFrom) -> Self {
 		fn conv_signed(off: i64) -> isize {
@@ -113,6 +124,35 @@ pub trait SeekableExt: Seekable {
 		Ok(())
 	}
 
+	/// Resets to the start of the stream. An alias of [`reset`] for parity with
+	/// [`std::io::Seek::rewind`].
+	///
+	/// [`reset`]: Self::reset
+	fn rewind(&mut self) -> Result {
+		self.reset()
+	}
+
+	/// Returns the current position. An alias of [`seek_pos`] for parity with
+	/// [`std::io::Seek::stream_position`].
+	///
+	/// [`seek_pos`]: Seekable::seek_pos
+	fn stream_position(&mut self) -> Result<usize> {
+		self.seek_pos()
+	}
+
+	/// Seeks `offset` bytes relative to the current position, forward if positive
+	/// or back if negative, returning the new position. Equivalent to seeking with
+	/// [`SeekFrom::Current`].
+	fn seek_relative(&mut self, offset: i64) -> Result<usize> {
+		self.seek(SeekFrom::Current(offset).into())
+	}
+
+	/// Seeks to the end of the stream, returning the new position. Shorthand for
+	/// `seek(SeekOffset::FromEnd(0))`.
+	fn seek_to_end(&mut self) -> Result<usize> {
+		self.seek(SeekOffset::FromEnd(0))
+	}
+
 	/// Seeks forward `offset` bytes relative to the current position, returning
 	/// the new position. Shorthand for `seek(SeekOffset::Forward(offset))`.
 	fn seek_forward(&mut self, offset: usize) -> Result<usize> {
@@ -141,3 +181,35 @@ pub trait SeekableExt: Seekable {
 }
 
 impl<S: Seekable> SeekableExt for S { }
+
+#[cfg(test)]
+mod test {
+	use std::io::{Cursor, SeekFrom};
+	use pretty_assertions::assert_eq;
+	use crate::streams::ReaderSource;
+	use super::{SeekableExt, SeekOffset};
+
+	#[test]
+	fn seek_from_round_trip() {
+		let offsets = [
+			SeekOffset::FromStart(5),
+			SeekOffset::FromEnd(-3),
+			SeekOffset::Forward(2),
+			SeekOffset::Back(4),
+		];
+		for offset in offsets {
+			assert_eq!(SeekOffset::from(SeekFrom::from(offset)), offset);
+		}
+	}
+
+	#[test]
+	fn std_parity() {
+		let mut source = ReaderSource::from(Cursor::new(vec![0u8; 16]));
+		assert_eq!(source.seek_to_end().unwrap(), 16);
+		assert_eq!(source.seek_relative(-6).unwrap(), 10);
+		assert_eq!(source.seek_relative(2).unwrap(), 12);
+		assert_eq!(source.stream_position().unwrap(), 12);
+		source.rewind().unwrap();
+		assert_eq!(source.stream_position().unwrap(), 0);
+	}
+}
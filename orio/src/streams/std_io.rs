@@ -1,13 +1,17 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::io;
-use std::io::{Read, Seek, SeekFrom, Write};
+use std::io::{BorrowedCursor, Read, Seek, SeekFrom, Write};
 use std::marker::PhantomData;
+use std::net::TcpStream;
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::time::Duration;
 use crate::{Buffer, BufferResult, DefaultBuffer, Error, ResultContext, SIZE, StreamContext, StreamResult};
 use crate::BufferContext::{Drain, Fill};
 use crate::pool::Pool;
 use crate::StreamContext::Flush;
-use crate::streams::{BufSink, BufSource, Seekable, SeekOffset, Sink, Source, Stream};
+use crate::streams::{BufSink, BufSource, Seekable, SeekOffset, Sink, Source, Stream, Timeout};
 
 /// A [`Source`] reading from a wrapped [`Read`]er.
 pub struct ReaderSource<R: Read> {
@@ -81,7 +85,10 @@ impl<'d, const N: usize, R: Read> Source<'d, N> for ReaderSource<R> {
 		let reader = self.reader
 						 .as_mut()
 						 .ok_or_else(|| Error::closed(Fill))?;
-		sink.fill_from_reader(reader, count, self.allow_vectored)
+		let read = sink.fill_from_reader(reader, count, self.allow_vectored)?;
+		// Readers signal end-of-file by reading zero bytes into a non-empty buffer.
+		self.is_eos = read == 0 && count > 0;
+		Ok(read)
 	}
 }
 
@@ -124,13 +131,14 @@ impl<'d, const N: usize, W: Write> Sink<'d, N> for WriterSink<W> {
 
 impl<R: Read + Seek> Seekable for ReaderSource<R> {
 	fn seek(&mut self, offset: SeekOffset) -> StreamResult<usize> {
-		Ok(
-			self.reader
-				.as_mut()
-				.ok_or_else(|| Error::closed(StreamContext::Seek))?
-				.seek(offset.into_seek_from())
-				.context(StreamContext::Seek)? as usize
-		)
+		let pos = self.reader
+					  .as_mut()
+					  .ok_or_else(|| Error::closed(StreamContext::Seek))?
+					  .seek(offset.into_seek_from())
+					  .context(StreamContext::Seek)? as usize;
+		// More data may be read after seeking back from the end.
+		self.is_eos = false;
+		Ok(pos)
 	}
 }
 
@@ -146,6 +154,32 @@ impl<W: Write + Seek> Seekable for WriterSink<W> {
 	}
 }
 
+impl Timeout for ReaderSource<TcpStream> {
+	fn set_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+		self.reader.as_ref().map_or(Ok(()), |stream| stream.set_read_timeout(timeout))
+	}
+}
+
+impl Timeout for WriterSink<TcpStream> {
+	fn set_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+		self.writer.as_ref().map_or(Ok(()), |stream| stream.set_write_timeout(timeout))
+	}
+}
+
+#[cfg(unix)]
+impl Timeout for ReaderSource<UnixStream> {
+	fn set_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+		self.reader.as_ref().map_or(Ok(()), |stream| stream.set_read_timeout(timeout))
+	}
+}
+
+#[cfg(unix)]
+impl Timeout for WriterSink<UnixStream> {
+	fn set_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+		self.writer.as_ref().map_or(Ok(()), |stream| stream.set_write_timeout(timeout))
+	}
+}
+
 /// A wrapper implementing the [`Read`] trait for a [`Source`].
 pub struct SourceReader<'d, S: Source<'d, SIZE>>(S, PhantomData<&'d ()>);
 
@@ -186,6 +220,14 @@ impl<'d, S: Source<'d, SIZE>> Read for SourceReader<'d, S> {
 		buffer.read_slice(buf)?;
 		Ok(count)
 	}
+
+	default fn read_buf(&mut self, cursor: BorrowedCursor) -> io::Result<()> {
+		let Self(source, ..) = self;
+		let ref mut buffer = DefaultBuffer::default();
+		source.fill(buffer, cursor.capacity())?;
+		buffer.read_into_cursor(cursor)?;
+		Ok(())
+	}
 }
 
 impl<'d, S: BufSource<'d, SIZE>> Read for SourceReader<'d, S> {
@@ -193,6 +235,12 @@ impl<'d, S: BufSource<'d, SIZE>> Read for SourceReader<'d, S> {
 		let Self(source, ..) = self;
 		Ok(source.read_slice(buf)?)
 	}
+
+	fn read_buf(&mut self, cursor: BorrowedCursor) -> io::Result<()> {
+		let Self(source, ..) = self;
+		source.read_into_cursor(cursor)?;
+		Ok(())
+	}
 }
 
 impl<'d, S: Source<'d, SIZE> + Seekable> Seek for SourceReader<'d, S> {
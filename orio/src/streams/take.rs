@@ -0,0 +1,114 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::marker::PhantomData;
+use crate::{Buffer, BufferResult};
+use crate::pool::Pool;
+use super::{Result, Source, Stream};
+
+/// A [`Source`] reading at most a limited number of bytes from its inner source,
+/// reaching end-of-stream once the limit is exhausted.
+pub struct Take<'d, S: Source<'d, N>, const N: usize> {
+	source: Option<S>,
+	limit: usize,
+	__data: PhantomData<&'d ()>
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> Take<'d, S, N> {
+	/// Creates a new take source, reading at most `limit` bytes from `source`.
+	pub fn new(source: S, limit: usize) -> Self {
+		Self {
+			source: Some(source),
+			limit,
+			__data: PhantomData
+		}
+	}
+
+	/// Returns the number of bytes that can be read before the limit is reached.
+	#[inline]
+	pub fn limit(&self) -> usize { self.limit }
+
+	/// Sets the number of bytes that can be read before the limit is reached.
+	#[inline]
+	pub fn set_limit(&mut self, limit: usize) {
+		self.limit = limit;
+	}
+
+	/// Returns a reference to the inner source.
+	pub fn source(&self) -> &S {
+		unsafe {
+			// Safety: option will only be None if into_inner is called, but this
+			// consumes and drops self, making it impossible to ever have a
+			// reference (except on drop, which is guarded).
+			self.source.as_ref().unwrap_unchecked()
+		}
+	}
+
+	/// Returns a mutable reference to the inner source. Reads from the inner
+	/// source directly do not count toward the limit.
+	pub fn source_mut(&mut self) -> &mut S {
+		unsafe {
+			// Safety: see `source`
+			self.source.as_mut().unwrap_unchecked()
+		}
+	}
+
+	/// Consumes the take source, returning the inner source.
+	pub fn into_inner(mut self) -> S {
+		unsafe {
+			// Safety: option will only be None if this method was already called,
+			// which is impossible because we consume self.
+			self.source.take().unwrap_unchecked()
+		}
+	}
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> Stream<N> for Take<'d, S, N> {
+	fn is_closed(&self) -> bool {
+		self.source().is_closed()
+	}
+
+	fn close(&mut self) -> Result {
+		self.source_mut().close()
+	}
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> Source<'d, N> for Take<'d, S, N> {
+	fn is_eos(&self) -> bool {
+		self.limit == 0 || self.source().is_eos()
+	}
+
+	fn fill(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		let count = count.min(self.limit);
+		if count == 0 {
+			return Ok(0)
+		}
+
+		let read = self.source_mut().fill(sink, count)?;
+		self.limit -= read;
+		Ok(read)
+	}
+
+	fn fill_all(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>) -> BufferResult<usize> {
+		let mut total = 0;
+		while !self.is_eos() {
+			let read = self.fill(sink, N)?;
+			if read == 0 {
+				break
+			}
+			total += read;
+			if sink.should_yield(total) {
+				break
+			}
+		}
+		Ok(total)
+	}
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> Drop for Take<'d, S, N> {
+	fn drop(&mut self) {
+		// If into_inner was called, closing would cause a seg fault.
+		if self.source.is_some() {
+			let _ = self.close();
+		}
+	}
+}
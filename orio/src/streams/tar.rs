@@ -0,0 +1,402 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reading and writing [ustar] archives over [`BufSource`]s and [`BufSink`]s.
+//! Extended headers, such as PAX and GNU long names, are read as entries of type
+//! [`EntryType::Other`] and not interpreted.
+//!
+//! [ustar]: https://pubs.opengroup.org/onlinepubs/9799919799/utilities/pax.html#tag_20_94_13_06
+
+use std::marker::PhantomData;
+use std::ops::Range;
+use crate::{SIZE, StreamError};
+use crate::StreamContext::{Read, Write};
+use super::{invalid_data, invalid_input, BufSink, BufSource, Result, Source, Take};
+
+/// The size of header and content blocks.
+const BLOCK: usize = 512;
+const ZEROS: [u8; BLOCK] = [0; BLOCK];
+const MAGIC: &[u8] = b"ustar";
+const CHECKSUM: Range<usize> = 148..156;
+
+/// The type of a tar entry.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum EntryType {
+	/// A regular file.
+	#[default]
+	File,
+	/// A hard link to a previous entry.
+	HardLink,
+	/// A symbolic link.
+	Symlink,
+	/// A character device.
+	CharDevice,
+	/// A block device.
+	BlockDevice,
+	/// A directory.
+	Directory,
+	/// A named pipe.
+	Fifo,
+	/// Another entry type, such as an extended header.
+	Other(u8),
+}
+
+impl From<u8> for EntryType {
+	fn from(value: u8) -> Self {
+		match value {
+			b'0' | b'\0' => Self::File,
+			b'1' => Self::HardLink,
+			b'2' => Self::Symlink,
+			b'3' => Self::CharDevice,
+			b'4' => Self::BlockDevice,
+			b'5' => Self::Directory,
+			b'6' => Self::Fifo,
+			_ => Self::Other(value)
+		}
+	}
+}
+
+impl From<EntryType> for u8 {
+	fn from(value: EntryType) -> Self {
+		match value {
+			EntryType::File        => b'0',
+			EntryType::HardLink    => b'1',
+			EntryType::Symlink     => b'2',
+			EntryType::CharDevice  => b'3',
+			EntryType::BlockDevice => b'4',
+			EntryType::Directory   => b'5',
+			EntryType::Fifo        => b'6',
+			EntryType::Other(value) => value
+		}
+	}
+}
+
+/// A tar entry header.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TarHeader {
+	/// The entry path. Paths longer than 100 bytes are split into the ustar name
+	/// and prefix fields when written.
+	pub path: String,
+	/// The permission bits.
+	pub mode: u32,
+	/// The owner user ID.
+	pub uid: u64,
+	/// The owner group ID.
+	pub gid: u64,
+	/// The content length in bytes.
+	pub size: u64,
+	/// The modification time, in seconds since the Unix epoch.
+	pub mtime: u64,
+	/// The entry type.
+	pub entry_type: EntryType,
+	/// The link target, for link entries.
+	pub link_name: String,
+	/// The owner user name.
+	pub user_name: String,
+	/// The owner group name.
+	pub group_name: String,
+}
+
+impl TarHeader {
+	/// Creates a header for a regular file with mode `644`.
+	pub fn file(path: impl Into<String>, size: u64) -> Self {
+		Self {
+			path: path.into(),
+			mode: 0o644,
+			size,
+			..Self::default()
+		}
+	}
+
+	/// Creates a header for a directory with mode `755`.
+	pub fn directory(path: impl Into<String>) -> Self {
+		Self {
+			path: path.into(),
+			mode: 0o755,
+			entry_type: EntryType::Directory,
+			..Self::default()
+		}
+	}
+
+	/// Parses a header block, verifying its checksum. Text fields are decoded as
+	/// UTF-8 lossily.
+	pub fn parse(block: &[u8; BLOCK]) -> Result<Self> {
+		if parse_octal(&block[CHECKSUM])? != checksum(block) {
+			return Err(invalid_data("tar header checksum mismatch"))
+		}
+
+		let name = parse_str(&block[..100]);
+		let path = if block[257..262] == *MAGIC {
+			let prefix = parse_str(&block[345..500]);
+			if prefix.is_empty() { name } else { format!("{prefix}/{name}") }
+		} else {
+			name
+		};
+
+		Ok(Self {
+			path,
+			mode: parse_octal(&block[100..108])? as u32,
+			uid: parse_octal(&block[108..116])?,
+			gid: parse_octal(&block[116..124])?,
+			size: parse_octal(&block[124..136])?,
+			mtime: parse_octal(&block[136..148])?,
+			entry_type: block[156].into(),
+			link_name: parse_str(&block[157..257]),
+			user_name: parse_str(&block[265..297]),
+			group_name: parse_str(&block[297..329]),
+		})
+	}
+
+	/// Encodes the header into a ustar header block.
+	///
+	/// # Errors
+	///
+	/// If the path can't be split into the name and prefix fields, or any other
+	/// field is too long to encode, an IO error of kind [`InvalidInput`] is
+	/// returned.
+	///
+	/// [`InvalidInput`]: std::io::ErrorKind::InvalidInput
+	pub fn encode(&self) -> Result<[u8; BLOCK]> {
+		let mut block = [0; BLOCK];
+		let (prefix, name) = split_path(&self.path)?;
+		put_str(&mut block[..100], name)?;
+		put_octal(&mut block[100..108], self.mode as u64)?;
+		put_octal(&mut block[108..116], self.uid)?;
+		put_octal(&mut block[116..124], self.gid)?;
+		put_octal(&mut block[124..136], self.size)?;
+		put_octal(&mut block[136..148], self.mtime)?;
+		block[156] = self.entry_type.into();
+		put_str(&mut block[157..257], &self.link_name)?;
+		block[257..263].copy_from_slice(b"ustar\0");
+		block[263..265].copy_from_slice(b"00");
+		put_str(&mut block[265..297], &self.user_name)?;
+		put_str(&mut block[297..329], &self.group_name)?;
+		put_str(&mut block[345..500], prefix)?;
+
+		let checksum = format!("{:06o}\0 ", checksum(&block));
+		block[CHECKSUM].copy_from_slice(checksum.as_bytes());
+		Ok(block)
+	}
+}
+
+/// An entry read by a [`TarReader`].
+pub struct TarEntry<'r, 'd, S: BufSource<'d, N>, const N: usize = SIZE> {
+	/// The entry header.
+	pub header: TarHeader,
+	/// The entry contents, limited to the size in the header. Contents not read
+	/// are skipped when the next entry is read.
+	pub contents: &'r mut Take<'d, S, N>,
+}
+
+/// A reader of entries from a tar archive.
+pub struct TarReader<'d, S: BufSource<'d, N>, const N: usize = SIZE> {
+	source: Take<'d, S, N>,
+	/// The length of padding following the current entry's contents.
+	padding: usize,
+	finished: bool,
+}
+
+impl<'d, S: BufSource<'d, N>, const N: usize> TarReader<'d, S, N> {
+	/// Creates a new tar reader, reading from `source`.
+	pub fn new(source: S) -> Self {
+		Self {
+			source: Take::new(source, 0),
+			padding: 0,
+			finished: false
+		}
+	}
+
+	/// Consumes the reader, returning the source.
+	pub fn into_inner(self) -> S {
+		self.source.into_inner()
+	}
+
+	/// Reads the next entry, or `None` if the end of the archive was reached.
+	/// Unread contents of the previous entry are skipped.
+	pub fn next_entry(&mut self) -> Result<Option<TarEntry<'_, 'd, S, N>>> {
+		if self.finished {
+			return Ok(None)
+		}
+
+		let Some(skip) = self.source.limit().checked_add(self.padding) else {
+			return Err(invalid_data("tar entry too large"))
+		};
+		self.source.set_limit(0);
+		self.padding = 0;
+		let source = self.source.source_mut();
+		let skipped = source.skip(skip)?;
+		if skipped < skip {
+			return Err(StreamError::end_of_stream(skip - skipped, Read))
+		}
+
+		if !source.request(BLOCK)? {
+			if source.available() == 0 {
+				// Tolerate archives missing their end blocks.
+				self.finished = true;
+				return Ok(None)
+			}
+			return Err(StreamError::end_of_stream(BLOCK, Read))
+		}
+
+		let block = source.read_array::<BLOCK>()?;
+		if block == ZEROS {
+			self.finished = true;
+			if source.request(BLOCK)? && source.buf().read_array_at::<BLOCK>(0)? == ZEROS {
+				source.skip(BLOCK)?;
+			}
+			return Ok(None)
+		}
+
+		let header = TarHeader::parse(&block)?;
+		let Some((size, padding)) = entry_size(header.size) else {
+			return Err(invalid_data("tar entry too large"))
+		};
+		self.source.set_limit(size);
+		self.padding = padding;
+		Ok(Some(TarEntry {
+			header,
+			contents: &mut self.source
+		}))
+	}
+}
+
+/// A writer of entries to a tar archive. The archive must be ended with
+/// [`finish`].
+///
+/// [`finish`]: Self::finish
+pub struct TarWriter<'d, S: BufSink<'d, N>, const N: usize = SIZE> {
+	sink: S,
+	finished: bool,
+	__data: PhantomData<&'d ()>
+}
+
+impl<'d, S: BufSink<'d, N>, const N: usize> TarWriter<'d, S, N> {
+	/// Creates a new tar writer, writing to `sink`.
+	pub fn new(sink: S) -> Self {
+		Self {
+			sink,
+			finished: false,
+			__data: PhantomData
+		}
+	}
+
+	/// Consumes the writer, returning the sink.
+	pub fn into_inner(self) -> S {
+		self.sink
+	}
+
+	/// Appends an entry, reading exactly `header.size` bytes of contents from
+	/// `contents`.
+	///
+	/// # Errors
+	///
+	/// If `contents` ends before `header.size` bytes are read, an end-of-stream
+	/// error is returned. If the archive was finished, a "closed" error is
+	/// returned.
+	pub fn append(&mut self, header: &TarHeader, contents: &mut impl Source<'d, N>) -> Result {
+		if self.finished {
+			return Err(StreamError::closed(Write))
+		}
+
+		let Some((size, padding)) = entry_size(header.size) else {
+			return Err(invalid_input("tar entry too large"))
+		};
+		self.sink.write_from_slice(&header.encode()?)?;
+		let mut remaining = size;
+		while remaining > 0 {
+			let written = self.sink.write(contents, remaining)?;
+			if written == 0 && contents.is_eos() {
+				return Err(StreamError::end_of_stream(remaining, Read))
+			}
+			remaining -= written;
+		}
+		self.sink.write_zeroes(padding)?;
+		Ok(())
+	}
+
+	/// Ends the archive by writing two zero blocks, then flushes the sink.
+	pub fn finish(&mut self) -> Result {
+		if !self.finished {
+			self.finished = true;
+			self.sink.write_zeroes(2 * BLOCK)?;
+		}
+		self.sink.flush()
+	}
+}
+
+/// Returns the size of an entry's contents and the length of padding after them,
+/// or `None` if the padded size doesn't fit in memory.
+fn entry_size(size: u64) -> Option<(usize, usize)> {
+	let size = usize::try_from(size).ok()?;
+	Some((size, size.checked_next_multiple_of(BLOCK)? - size))
+}
+
+/// Computes a header checksum, treating the checksum field as spaces.
+fn checksum(block: &[u8; BLOCK]) -> u64 {
+	let sum: u64 = block.iter().map(|&b| b as u64).sum();
+	let field: u64 = block[CHECKSUM].iter().map(|&b| b as u64).sum();
+	sum - field + b' ' as u64 * CHECKSUM.len() as u64
+}
+
+/// Parses a NUL-terminated text field.
+fn parse_str(field: &[u8]) -> String {
+	let len = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+	String::from_utf8_lossy(&field[..len]).into_owned()
+}
+
+/// Parses a numeric field, either as space- or NUL-terminated octal text or, if
+/// the high bit of the first byte is set, as a base-256 big-endian number.
+fn parse_octal(field: &[u8]) -> Result<u64> {
+	if field[0] & 0x80 != 0 {
+		return field[1..].iter().try_fold(field[0] as u64 & 0x7F, |value, &b|
+			value.checked_mul(256)
+				 .map(|value| value | b as u64)
+				 .ok_or_else(|| invalid_data("tar header field overflow"))
+		)
+	}
+
+	let mut digits = field.iter()
+					  .skip_while(|&&b| b == b' ')
+					  .take_while(|&&b| b != 0 && b != b' ');
+	digits.try_fold(0u64, |value, &b| {
+		if !(b'0'..=b'7').contains(&b) {
+			return Err(invalid_data("invalid tar header field"))
+		}
+		value.checked_mul(8)
+			 .map(|value| value | (b - b'0') as u64)
+			 .ok_or_else(|| invalid_data("tar header field overflow"))
+	})
+}
+
+/// Writes `value` as NUL-terminated octal text, filling the field.
+fn put_octal(field: &mut [u8], value: u64) -> Result {
+	let width = field.len() - 1;
+	let text = format!("{value:0width$o}");
+	if text.len() > width {
+		return Err(invalid_input("value too large for tar header field"))
+	}
+	field[..width].copy_from_slice(text.as_bytes());
+	field[width] = 0;
+	Ok(())
+}
+
+/// Writes `value` as text, NUL-padded to fill the field.
+fn put_str(field: &mut [u8], value: &str) -> Result {
+	if value.len() > field.len() {
+		return Err(invalid_input("text too long for tar header field"))
+	}
+	field[..value.len()].copy_from_slice(value.as_bytes());
+	Ok(())
+}
+
+/// Splits a path into ustar prefix and name fields, at a `/` if longer than the
+/// name field.
+fn split_path(path: &str) -> Result<(&str, &str)> {
+	if path.len() <= 100 {
+		return Ok(("", path))
+	}
+
+	path.match_indices('/')
+		.map(|(i, _)| (&path[..i], &path[i + 1..]))
+		.find(|(prefix, name)| prefix.len() <= 155 && name.len() <= 100 && !name.is_empty())
+		.ok_or_else(|| invalid_input("tar path too long"))
+}
@@ -0,0 +1,355 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::marker::PhantomData;
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::{Buffer, BufferResult, SIZE};
+use crate::pool::Pool;
+use super::{Result, Sink, Source, Stream};
+
+/// A token bucket refilling at a fixed rate, up to a burst size.
+#[derive(Copy, Clone, Debug)]
+struct TokenBucket {
+	rate: usize,
+	burst: usize,
+	tokens: f64,
+	last: Instant,
+}
+
+impl TokenBucket {
+	fn new(rate: usize) -> Self {
+		assert!(rate > 0, "rate must be greater than zero");
+		Self {
+			rate,
+			burst: rate,
+			tokens: rate as f64,
+			last: Instant::now(),
+		}
+	}
+
+	fn set_rate(&mut self, rate: usize) {
+		assert!(rate > 0, "rate must be greater than zero");
+		self.refill();
+		self.rate = rate;
+	}
+
+	fn set_burst(&mut self, burst: usize) {
+		assert!(burst > 0, "burst size must be greater than zero");
+		self.refill();
+		self.burst = burst;
+		self.tokens = self.tokens.min(burst as f64);
+	}
+
+	fn refill(&mut self) {
+		let now = Instant::now();
+		let elapsed = now.duration_since(self.last).as_secs_f64();
+		self.tokens = (self.tokens + elapsed * self.rate as f64).min(self.burst as f64);
+		self.last = now;
+	}
+
+	/// Takes tokens for up to `count` bytes, sleeping until enough are available.
+	/// At most the burst size is taken at once.
+	fn acquire(&mut self, count: usize) -> usize {
+		if count == 0 {
+			return 0
+		}
+
+		self.refill();
+		let wanted = count.min(self.burst) as f64;
+		// Sleeping may fall just short of the wanted tokens, which would truncate to
+		// fewer whole tokens than requested or none at all. Sleep again until they're
+		// all available.
+		while self.tokens < wanted {
+			let wait = (wanted - self.tokens) / self.rate as f64;
+			thread::sleep(Duration::from_secs_f64(wait));
+			self.refill();
+		}
+
+		let count = count.min(self.tokens as usize);
+		self.tokens -= count as f64;
+		count
+	}
+
+	/// Returns tokens taken for bytes which weren't transferred.
+	fn release(&mut self, count: usize) {
+		self.tokens = (self.tokens + count as f64).min(self.burst as f64);
+	}
+}
+
+/// A [`Source`] limiting the rate at which bytes are read from its inner source,
+/// using a token bucket. Reads sleep until enough throughput is available, making
+/// this useful for rate limiting or simulating slow peers in tests.
+///
+/// The burst size, the number of bytes which can be read at once after a pause,
+/// defaults to one second of throughput.
+pub struct ThrottleSource<'d, S: Source<'d, N>, const N: usize = SIZE> {
+	source: Option<S>,
+	bucket: TokenBucket,
+	__data: PhantomData<&'d ()>
+}
+
+/// A [`Sink`] limiting the rate at which bytes are written to its inner sink,
+/// using a token bucket. Writes sleep until enough throughput is available, making
+/// this useful for rate limiting or simulating slow peers in tests.
+///
+/// The burst size, the number of bytes which can be written at once after a pause,
+/// defaults to one second of throughput.
+pub struct ThrottleSink<'d, S: Sink<'d, N>, const N: usize = SIZE> {
+	sink: Option<S>,
+	bucket: TokenBucket,
+	__data: PhantomData<&'d ()>
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> ThrottleSource<'d, S, N> {
+	/// Creates a new throttle source reading at most `rate` bytes per second.
+	///
+	/// # Panics
+	///
+	/// Panics if `rate` is zero.
+	pub fn new(source: S, rate: usize) -> Self {
+		Self {
+			source: Some(source),
+			bucket: TokenBucket::new(rate),
+			__data: PhantomData
+		}
+	}
+
+	/// Sets the burst size, returning the throttle source.
+	///
+	/// # Panics
+	///
+	/// Panics if `burst` is zero.
+	pub fn with_burst(mut self, burst: usize) -> Self {
+		self.set_burst(burst);
+		self
+	}
+
+	/// Returns the rate in bytes per second.
+	pub fn rate(&self) -> usize { self.bucket.rate }
+
+	/// Sets the rate in bytes per second.
+	///
+	/// # Panics
+	///
+	/// Panics if `rate` is zero.
+	pub fn set_rate(&mut self, rate: usize) {
+		self.bucket.set_rate(rate);
+	}
+
+	/// Returns the burst size in bytes.
+	pub fn burst(&self) -> usize { self.bucket.burst }
+
+	/// Sets the burst size in bytes.
+	///
+	/// # Panics
+	///
+	/// Panics if `burst` is zero.
+	pub fn set_burst(&mut self, burst: usize) {
+		self.bucket.set_burst(burst);
+	}
+
+	/// Returns a reference to the inner source.
+	pub fn source(&self) -> &S {
+		unsafe {
+			// Safety: option will only be None if into_inner is called, but this
+			// consumes and drops self, making it impossible to ever have a
+			// reference (except on drop, which is guarded).
+			self.source.as_ref().unwrap_unchecked()
+		}
+	}
+
+	/// Returns a mutable reference to the inner source.
+	pub fn source_mut(&mut self) -> &mut S {
+		unsafe {
+			// Safety: see `source`
+			self.source.as_mut().unwrap_unchecked()
+		}
+	}
+
+	/// Consumes the throttle source, returning the inner source.
+	pub fn into_inner(mut self) -> S {
+		unsafe {
+			// Safety: option will only be None if this method was already called,
+			// which is impossible because we consume self.
+			self.source.take().unwrap_unchecked()
+		}
+	}
+}
+
+impl<'d, S: Sink<'d, N>, const N: usize> ThrottleSink<'d, S, N> {
+	/// Creates a new throttle sink writing at most `rate` bytes per second.
+	///
+	/// # Panics
+	///
+	/// Panics if `rate` is zero.
+	pub fn new(sink: S, rate: usize) -> Self {
+		Self {
+			sink: Some(sink),
+			bucket: TokenBucket::new(rate),
+			__data: PhantomData
+		}
+	}
+
+	/// Sets the burst size, returning the throttle sink.
+	///
+	/// # Panics
+	///
+	/// Panics if `burst` is zero.
+	pub fn with_burst(mut self, burst: usize) -> Self {
+		self.set_burst(burst);
+		self
+	}
+
+	/// Returns the rate in bytes per second.
+	pub fn rate(&self) -> usize { self.bucket.rate }
+
+	/// Sets the rate in bytes per second.
+	///
+	/// # Panics
+	///
+	/// Panics if `rate` is zero.
+	pub fn set_rate(&mut self, rate: usize) {
+		self.bucket.set_rate(rate);
+	}
+
+	/// Returns the burst size in bytes.
+	pub fn burst(&self) -> usize { self.bucket.burst }
+
+	/// Sets the burst size in bytes.
+	///
+	/// # Panics
+	///
+	/// Panics if `burst` is zero.
+	pub fn set_burst(&mut self, burst: usize) {
+		self.bucket.set_burst(burst);
+	}
+
+	/// Returns a reference to the inner sink.
+	pub fn sink(&self) -> &S {
+		unsafe {
+			// Safety: option will only be None if into_inner is called, but this
+			// consumes and drops self, making it impossible to ever have a
+			// reference (except on drop, which is guarded).
+			self.sink.as_ref().unwrap_unchecked()
+		}
+	}
+
+	/// Returns a mutable reference to the inner sink.
+	pub fn sink_mut(&mut self) -> &mut S {
+		unsafe {
+			// Safety: see `sink`
+			self.sink.as_mut().unwrap_unchecked()
+		}
+	}
+
+	/// Consumes the throttle sink, returning the inner sink.
+	pub fn into_inner(mut self) -> S {
+		unsafe {
+			// Safety: option will only be None if this method was already called,
+			// which is impossible because we consume self.
+			self.sink.take().unwrap_unchecked()
+		}
+	}
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> Stream<N> for ThrottleSource<'d, S, N> {
+	fn is_closed(&self) -> bool {
+		self.source().is_closed()
+	}
+
+	fn close(&mut self) -> Result {
+		self.source_mut().close()
+	}
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> Source<'d, N> for ThrottleSource<'d, S, N> {
+	fn is_eos(&self) -> bool {
+		self.source().is_eos()
+	}
+
+	/// Fills `sink` with up to `count` bytes, sleeping until throughput is available.
+	/// At most the burst size is read at once.
+	fn fill(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		let allowed = self.bucket.acquire(count);
+		if allowed == 0 {
+			return Ok(0)
+		}
+
+		let result = self.source_mut().fill(sink, allowed);
+		self.bucket.release(allowed - *result.as_ref().unwrap_or(&0));
+		result
+	}
+
+	/// Fills `sink` with all available data, in chunks of at most the burst size.
+	fn fill_all(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>) -> BufferResult<usize> {
+		let mut count = 0;
+		while !self.is_eos() {
+			let read = self.fill(sink, self.bucket.burst)?;
+			if read == 0 {
+				break
+			}
+			count += read;
+		}
+		Ok(count)
+	}
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> Drop for ThrottleSource<'d, S, N> {
+	fn drop(&mut self) {
+		// If into_inner was called, closing would cause a seg fault.
+		if self.source.is_some() {
+			let _ = self.close();
+		}
+	}
+}
+
+impl<'d, S: Sink<'d, N>, const N: usize> Stream<N> for ThrottleSink<'d, S, N> {
+	fn is_closed(&self) -> bool {
+		self.sink().is_closed()
+	}
+
+	fn close(&mut self) -> Result {
+		self.sink_mut().close()
+	}
+}
+
+impl<'d, S: Sink<'d, N>, const N: usize> Sink<'d, N> for ThrottleSink<'d, S, N> {
+	/// Drains up to `count` bytes from `source`, sleeping until throughput is
+	/// available. At most the burst size is written at once.
+	fn drain(&mut self, source: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		let allowed = self.bucket.acquire(count.min(source.count()));
+		if allowed == 0 {
+			return Ok(0)
+		}
+
+		let result = self.sink_mut().drain(source, allowed);
+		self.bucket.release(allowed - *result.as_ref().unwrap_or(&0));
+		result
+	}
+
+	/// Drains all data from `source`, in chunks of at most the burst size.
+	fn drain_all(&mut self, source: &mut Buffer<'d, N, impl Pool<N>>) -> BufferResult<usize> {
+		let mut count = 0;
+		while source.is_not_empty() {
+			let written = self.drain(source, self.bucket.burst)?;
+			if written == 0 {
+				break
+			}
+			count += written;
+		}
+		Ok(count)
+	}
+
+	fn flush(&mut self) -> Result {
+		self.sink_mut().flush()
+	}
+}
+
+impl<'d, S: Sink<'d, N>, const N: usize> Drop for ThrottleSink<'d, S, N> {
+	fn drop(&mut self) {
+		// If into_inner was called, closing would cause a seg fault.
+		if self.sink.is_some() {
+			let _ = self.close();
+		}
+	}
+}
@@ -0,0 +1,232 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::io;
+use std::io::ErrorKind;
+use std::marker::PhantomData;
+use std::time::Duration;
+use crate::{Buffer, BufferResult, Error, ErrorSource};
+use crate::error::Context;
+use crate::pool::Pool;
+use super::{Result, Sink, Source, Stream, TimedOut};
+
+/// A stream whose blocking operations can be bounded by a timeout, such as a
+/// socket.
+pub trait Timeout {
+	/// Sets the timeout for each blocking operation on the stream, or removes it
+	/// if `timeout` is `None`.
+	fn set_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()>;
+}
+
+/// A [`Source`] bounding each read from its inner source by a timeout. Reads
+/// exceeding the timeout fail with a [`TimedOut`] error.
+pub struct TimeoutSource<'d, S: Source<'d, N>, const N: usize> {
+	source: Option<S>,
+	timeout: Duration,
+	__data: PhantomData<&'d ()>
+}
+
+/// A [`Sink`] bounding each write to its inner sink by a timeout. Writes
+/// exceeding the timeout fail with a [`TimedOut`] error.
+pub struct TimeoutSink<'d, S: Sink<'d, N>, const N: usize> {
+	sink: Option<S>,
+	timeout: Duration,
+	__data: PhantomData<&'d ()>
+}
+
+/// Replaces IO errors signaling an elapsed timeout with [`TimedOut`]. Sockets
+/// report these as [`WouldBlock`] on Unix and [`TimedOut`] on Windows.
+///
+/// [`WouldBlock`]: ErrorKind::WouldBlock
+/// [`TimedOut`]: ErrorKind::TimedOut
+fn map_timed_out<C: Context>(error: Error<C>) -> Error<C> {
+	let is_timed_out = error.as_io_error().is_some_and(|error|
+		matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut)
+	);
+
+	if is_timed_out {
+		Error {
+			source: ErrorSource::TimedOut(TimedOut),
+			context: error.context
+		}
+	} else {
+		error
+	}
+}
+
+impl<'d, S: Source<'d, N> + Timeout, const N: usize> TimeoutSource<'d, S, N> {
+	/// Creates a new timeout source, setting the timeout of `source` to `timeout`.
+	pub fn new(mut source: S, timeout: Duration) -> io::Result<Self> {
+		source.set_timeout(Some(timeout))?;
+		Ok(Self {
+			source: Some(source),
+			timeout,
+			__data: PhantomData
+		})
+	}
+
+	/// Sets the timeout.
+	pub fn set_timeout(&mut self, timeout: Duration) -> io::Result<()> {
+		self.source_mut().set_timeout(Some(timeout))?;
+		self.timeout = timeout;
+		Ok(())
+	}
+
+	/// Consumes the timeout source, removing the timeout from and returning the
+	/// inner source.
+	pub fn into_inner(mut self) -> io::Result<S> {
+		let mut source = unsafe {
+			// Safety: option will only be None if this method was already called,
+			// which is impossible because we consume self.
+			self.source.take().unwrap_unchecked()
+		};
+		source.set_timeout(None)?;
+		Ok(source)
+	}
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> TimeoutSource<'d, S, N> {
+	/// Returns the timeout.
+	pub fn timeout(&self) -> Duration { self.timeout }
+
+	/// Returns a reference to the inner source.
+	pub fn source(&self) -> &S {
+		unsafe {
+			// Safety: option will only be None if into_inner is called, but this
+			// consumes and drops self, making it impossible to ever have a
+			// reference (except on drop, which is guarded).
+			self.source.as_ref().unwrap_unchecked()
+		}
+	}
+
+	/// Returns a mutable reference to the inner source.
+	pub fn source_mut(&mut self) -> &mut S {
+		unsafe {
+			// Safety: see `source`
+			self.source.as_mut().unwrap_unchecked()
+		}
+	}
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> Stream<N> for TimeoutSource<'d, S, N> {
+	fn is_closed(&self) -> bool {
+		self.source().is_closed()
+	}
+
+	fn close(&mut self) -> Result {
+		self.source_mut().close()
+	}
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> Source<'d, N> for TimeoutSource<'d, S, N> {
+	fn is_eos(&self) -> bool {
+		self.source().is_eos()
+	}
+
+	fn fill(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		self.source_mut().fill(sink, count).map_err(map_timed_out)
+	}
+
+	fn fill_free(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>) -> BufferResult<usize> {
+		self.source_mut().fill_free(sink).map_err(map_timed_out)
+	}
+
+	fn fill_all(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>) -> BufferResult<usize> {
+		self.source_mut().fill_all(sink).map_err(map_timed_out)
+	}
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> Drop for TimeoutSource<'d, S, N> {
+	fn drop(&mut self) {
+		// If into_inner was called, closing would cause a seg fault.
+		if self.source.is_some() {
+			let _ = self.close();
+		}
+	}
+}
+
+impl<'d, S: Sink<'d, N> + Timeout, const N: usize> TimeoutSink<'d, S, N> {
+	/// Creates a new timeout sink, setting the timeout of `sink` to `timeout`.
+	pub fn new(mut sink: S, timeout: Duration) -> io::Result<Self> {
+		sink.set_timeout(Some(timeout))?;
+		Ok(Self {
+			sink: Some(sink),
+			timeout,
+			__data: PhantomData
+		})
+	}
+
+	/// Sets the timeout.
+	pub fn set_timeout(&mut self, timeout: Duration) -> io::Result<()> {
+		self.sink_mut().set_timeout(Some(timeout))?;
+		self.timeout = timeout;
+		Ok(())
+	}
+
+	/// Consumes the timeout sink, removing the timeout from and returning the
+	/// inner sink.
+	pub fn into_inner(mut self) -> io::Result<S> {
+		let mut sink = unsafe {
+			// Safety: option will only be None if this method was already called,
+			// which is impossible because we consume self.
+			self.sink.take().unwrap_unchecked()
+		};
+		sink.set_timeout(None)?;
+		Ok(sink)
+	}
+}
+
+impl<'d, S: Sink<'d, N>, const N: usize> TimeoutSink<'d, S, N> {
+	/// Returns the timeout.
+	pub fn timeout(&self) -> Duration { self.timeout }
+
+	/// Returns a reference to the inner sink.
+	pub fn sink(&self) -> &S {
+		unsafe {
+			// Safety: option will only be None if into_inner is called, but this
+			// consumes and drops self, making it impossible to ever have a
+			// reference (except on drop, which is guarded).
+			self.sink.as_ref().unwrap_unchecked()
+		}
+	}
+
+	/// Returns a mutable reference to the inner sink.
+	pub fn sink_mut(&mut self) -> &mut S {
+		unsafe {
+			// Safety: see `sink`
+			self.sink.as_mut().unwrap_unchecked()
+		}
+	}
+}
+
+impl<'d, S: Sink<'d, N>, const N: usize> Stream<N> for TimeoutSink<'d, S, N> {
+	fn is_closed(&self) -> bool {
+		self.sink().is_closed()
+	}
+
+	fn close(&mut self) -> Result {
+		self.sink_mut().close().map_err(map_timed_out)
+	}
+}
+
+impl<'d, S: Sink<'d, N>, const N: usize> Sink<'d, N> for TimeoutSink<'d, S, N> {
+	fn drain(&mut self, source: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		self.sink_mut().drain(source, count).map_err(map_timed_out)
+	}
+
+	fn drain_all(&mut self, source: &mut Buffer<'d, N, impl Pool<N>>) -> BufferResult<usize> {
+		self.sink_mut().drain_all(source).map_err(map_timed_out)
+	}
+
+	fn flush(&mut self) -> Result {
+		self.sink_mut().flush().map_err(map_timed_out)
+	}
+}
+
+impl<'d, S: Sink<'d, N>, const N: usize> Drop for TimeoutSink<'d, S, N> {
+	fn drop(&mut self) {
+		// If into_inner was called, closing would cause a seg fault.
+		if self.sink.is_some() {
+			let _ = self.close();
+		}
+	}
+}
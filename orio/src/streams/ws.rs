@@ -0,0 +1,289 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! WebSocket framing as defined by [RFC 6455], over [`BufSource`]s and
+//! [`BufSink`]s. Unmasked payloads are moved between buffers by sharing segments
+//! rather than copying; masked payloads are copied while (un)masking.
+//!
+//! [RFC 6455]: https://www.rfc-editor.org/rfc/rfc6455#section-5
+
+use crate::{Buffer, SIZE};
+use crate::pool::{DefaultPoolContainer, Pool};
+use super::{invalid_data, invalid_input, BufSink, BufSource, Result};
+
+const FIN: u8 = 0x80;
+const RSV: u8 = 0x70;
+const MASK: u8 = 0x80;
+/// The maximum payload length of a control frame.
+const MAX_CONTROL_LEN: usize = 125;
+/// The size of the stack buffer used to (un)mask payloads.
+const MASK_CHUNK: usize = 1024;
+
+/// A frame opcode.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum Opcode {
+	/// A continuation of a fragmented message.
+	Continuation = 0x0,
+	/// A text message, containing UTF-8 data.
+	Text = 0x1,
+	/// A binary message.
+	Binary = 0x2,
+	/// A connection close control frame.
+	Close = 0x8,
+	/// A ping control frame.
+	Ping = 0x9,
+	/// A pong control frame.
+	Pong = 0xA,
+}
+
+impl Opcode {
+	/// Returns `true` if the opcode is a control opcode: [`Close`], [`Ping`], or
+	/// [`Pong`].
+	///
+	/// [`Close`]: Self::Close
+	/// [`Ping`]: Self::Ping
+	/// [`Pong`]: Self::Pong
+	#[inline]
+	pub fn is_control(self) -> bool {
+		self as u8 & 0x8 != 0
+	}
+}
+
+impl TryFrom<u8> for Opcode {
+	type Error = u8;
+
+	/// Converts a 4-bit opcode, returning the value back if reserved.
+	fn try_from(value: u8) -> Result<Self, u8> {
+		Ok(
+			match value {
+				0x0 => Self::Continuation,
+				0x1 => Self::Text,
+				0x2 => Self::Binary,
+				0x8 => Self::Close,
+				0x9 => Self::Ping,
+				0xA => Self::Pong,
+				_ => return Err(value)
+			}
+		)
+	}
+}
+
+/// A WebSocket frame.
+#[derive(Debug)]
+pub struct Frame<'d, const N: usize = SIZE, P: Pool<N> = DefaultPoolContainer> {
+	/// Whether this is the final fragment of a message.
+	pub fin: bool,
+	/// The frame opcode.
+	pub opcode: Opcode,
+	/// The unmasked payload.
+	pub payload: Buffer<'d, N, P>,
+}
+
+impl<'d, const N: usize, P: Pool<N>> Frame<'d, N, P> {
+	/// Creates a final frame.
+	pub fn new(opcode: Opcode, payload: Buffer<'d, N, P>) -> Self {
+		Self { fin: true, opcode, payload }
+	}
+}
+
+/// Reads a frame from `source`, unmasking its payload if masked. Unmasked payloads
+/// are moved from the source buffer without copying.
+///
+/// # Errors
+///
+/// Frames with reserved bits or opcodes set, fragmented or oversized control
+/// frames, or payloads longer than `max_payload` bytes fail with an IO error of
+/// kind [`InvalidData`]. No bytes are consumed if the header is invalid.
+///
+/// [`InvalidData`]: std::io::ErrorKind::InvalidData
+pub fn read_frame<'d, S: BufSource<'d, N>, const N: usize>(
+	source: &mut S,
+	max_payload: usize
+) -> Result<Frame<'d, N, S::Pool>> {
+	source.require(2)?;
+	let buf = source.buf();
+	let (Some(b0), Some(b1)) = (buf.get(0), buf.get(1)) else {
+		unreachable!("two bytes should be available")
+	};
+	if b0 & RSV != 0 {
+		return Err(invalid_data("reserved frame bits set"))
+	}
+	let Ok(opcode) = Opcode::try_from(b0 & 0xF) else {
+		return Err(invalid_data("reserved frame opcode"))
+	};
+	let fin = b0 & FIN != 0;
+	let masked = b1 & MASK != 0;
+	let len_width = match b1 & !MASK {
+		126 => 2,
+		127 => 8,
+		_ => 0
+	};
+
+	let header_len = 2 + len_width + if masked { 4 } else { 0 };
+	source.require(header_len)?;
+	let mut header = [0; 14];
+	source.buf().read_slice_exact_at(0, &mut header[..header_len])?;
+	let len = match len_width {
+		2 => u16::from_be_bytes([header[2], header[3]]) as u64,
+		8 => u64::from_be_bytes(header[2..10].try_into().unwrap()),
+		_ => (b1 & !MASK) as u64
+	};
+	let len = usize::try_from(len).unwrap_or(usize::MAX);
+	if opcode.is_control() && (!fin || len > MAX_CONTROL_LEN) {
+		return Err(invalid_data("invalid control frame"))
+	}
+	if len > max_payload {
+		return Err(invalid_data("frame payload too large"))
+	}
+	source.skip(header_len)?;
+
+	let mut payload = Buffer::default();
+	if masked {
+		let key = header[header_len - 4..header_len].try_into().unwrap();
+		read_masked(source, &mut payload, len, key)?;
+	} else {
+		let mut remaining = len;
+		while remaining > 0 {
+			source.require(1)?;
+			remaining -= source.read(&mut payload, remaining)?;
+		}
+	}
+	Ok(Frame { fin, opcode, payload })
+}
+
+/// Reads a complete message from `source`, reassembling fragmented frames into one
+/// frame. Control frames received between fragments are passed to `on_control`;
+/// a control frame received outside a fragmented message is returned.
+///
+/// # Errors
+///
+/// In addition to the errors returned by [`read_frame`], unexpected continuation
+/// frames and messages longer than `max_size` bytes fail with an IO error of kind
+/// [`InvalidData`].
+///
+/// [`InvalidData`]: std::io::ErrorKind::InvalidData
+pub fn read_message<'d, S: BufSource<'d, N>, const N: usize>(
+	source: &mut S,
+	max_size: usize,
+	mut on_control: impl FnMut(Frame<'d, N, S::Pool>) -> Result
+) -> Result<Frame<'d, N, S::Pool>> {
+	let mut message = read_frame(source, max_size)?;
+	if message.opcode == Opcode::Continuation {
+		return Err(invalid_data("unexpected continuation frame"))
+	}
+
+	while !message.fin {
+		// Control frames between fragments are limited by their own maximum length,
+		// not by what remains of the message size.
+		source.require(1)?;
+		let is_control = source.buf()
+							   .get(0)
+							   .is_some_and(|b0| Opcode::try_from(b0 & 0xF).is_ok_and(Opcode::is_control));
+		let max_payload = if is_control {
+			MAX_CONTROL_LEN
+		} else {
+			max_size - message.payload.count()
+		};
+		let mut frame = read_frame(source, max_payload)?;
+		match frame.opcode {
+			Opcode::Continuation => {
+				message.fin = frame.fin;
+				message.payload.write_all(&mut frame.payload)?;
+			}
+			opcode if opcode.is_control() => on_control(frame)?,
+			_ => return Err(invalid_data("expected continuation frame"))
+		}
+	}
+	Ok(message)
+}
+
+/// Writes `frame` to `sink`, masking its payload with `mask` if present. Clients
+/// must mask frames sent to servers; servers must not mask frames. Unmasked
+/// payloads are moved into the sink buffer without copying.
+///
+/// # Errors
+///
+/// Fragmented control frames, or control frames with payloads longer than 125
+/// bytes, fail with an IO error of kind [`InvalidInput`].
+///
+/// [`InvalidInput`]: std::io::ErrorKind::InvalidInput
+pub fn write_frame<'d, const N: usize>(
+	sink: &mut impl BufSink<'d, N>,
+	mut frame: Frame<'d, N, impl Pool<N>>,
+	mask: Option<[u8; 4]>
+) -> Result {
+	let len = frame.payload.count();
+	if frame.opcode.is_control() && (!frame.fin || len > MAX_CONTROL_LEN) {
+		return Err(invalid_input("invalid control frame"))
+	}
+
+	let b0 = if frame.fin { FIN } else { 0 } | frame.opcode as u8;
+	let mask_bit = if mask.is_some() { MASK } else { 0 };
+	sink.write_u8(b0)?;
+	match len {
+		0..=125 => sink.write_u8(mask_bit | len as u8)?,
+		126..=0xFFFF => {
+			sink.write_u8(mask_bit | 126)?;
+			sink.write_u16(len as u16)?;
+		}
+		_ => {
+			sink.write_u8(mask_bit | 127)?;
+			sink.write_u64(len as u64)?;
+		}
+	}
+
+	match mask {
+		Some(key) => {
+			sink.write_from_slice(&key)?;
+			write_masked(sink, &mut frame.payload, key)?;
+		}
+		None => {
+			sink.write_all(&mut frame.payload)?;
+		}
+	}
+	Ok(())
+}
+
+/// Reads `len` bytes from `source` into `payload`, unmasking them with `key`.
+fn read_masked<'d, const N: usize>(
+	source: &mut impl BufSource<'d, N>,
+	payload: &mut Buffer<'d, N, impl Pool<N>>,
+	len: usize,
+	key: [u8; 4]
+) -> Result {
+	let mut chunk = [0; MASK_CHUNK];
+	let mut offset = 0;
+	while offset < len {
+		let chunk = &mut chunk[..(len - offset).min(MASK_CHUNK)];
+		source.read_slice_exact(chunk)?;
+		apply_mask(chunk, key, offset);
+		payload.write_from_slice(chunk)?;
+		offset += chunk.len();
+	}
+	Ok(())
+}
+
+/// Writes all bytes from `payload` into `sink`, masking them with `key`.
+fn write_masked<'d, const N: usize>(
+	sink: &mut impl BufSink<'d, N>,
+	payload: &mut Buffer<'d, N, impl Pool<N>>,
+	key: [u8; 4]
+) -> Result {
+	let mut chunk = [0; MASK_CHUNK];
+	let mut offset = 0;
+	while payload.is_not_empty() {
+		let chunk = &mut chunk[..payload.count().min(MASK_CHUNK)];
+		payload.read_slice_exact(chunk)?;
+		apply_mask(chunk, key, offset);
+		sink.write_from_slice(chunk)?;
+		offset += chunk.len();
+	}
+	Ok(())
+}
+
+/// Masks or unmasks `data` at `offset` into the payload.
+fn apply_mask(data: &mut [u8], key: [u8; 4], offset: usize) {
+	for (i, byte) in data.iter_mut().enumerate() {
+		*byte ^= key[(offset + i) % 4];
+	}
+}
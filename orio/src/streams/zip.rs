@@ -0,0 +1,303 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reading [zip] archives from seekable sources. The central directory is read
+//! up front; entry contents are then read by seeking to each entry. Only stored
+//! (uncompressed) entries can be read, as no decompressing source is provided.
+//! Zip64 and multi-disk archives are not supported.
+//!
+//! [zip]: https://pkware.cachefly.net/webdocs/casestudies/APPNOTE.TXT
+
+use std::io;
+use std::marker::PhantomData;
+use crate::{Buffer, BufferResult, SIZE, StreamError};
+use crate::pool::{DefaultPoolContainer, Pool};
+use crate::StreamContext::Read;
+use super::{invalid_data, BufSource, Result, Seekable, SeekOffset, Source, Stream};
+
+const LOCAL_HEADER_SIG: u32 = 0x04034b50;
+const CENTRAL_HEADER_SIG: u32 = 0x02014b50;
+const END_SIG: &[u8] = b"PK\x05\x06";
+const LOCAL_HEADER_LEN: usize = 30;
+const CENTRAL_HEADER_LEN: usize = 46;
+const END_LEN: usize = 22;
+/// The maximum length of the archive comment following the end record.
+const MAX_COMMENT_LEN: usize = u16::MAX as usize;
+/// The flag bit indicating names are UTF-8.
+const UTF8_FLAG: u16 = 1 << 11;
+
+/// The compression method of a zip entry.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum CompressionMethod {
+	/// No compression.
+	Stored,
+	/// Deflate compression.
+	Deflated,
+	/// Another compression method.
+	Other(u16),
+}
+
+impl From<u16> for CompressionMethod {
+	fn from(value: u16) -> Self {
+		match value {
+			0 => Self::Stored,
+			8 => Self::Deflated,
+			_ => Self::Other(value)
+		}
+	}
+}
+
+/// An entry in a zip archive's central directory.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ZipEntry {
+	/// The entry name, a path with `/` separators. Directory names end with `/`.
+	pub name: String,
+	/// The compression method.
+	pub method: CompressionMethod,
+	/// The CRC-32 of the uncompressed contents.
+	pub crc32: u32,
+	/// The compressed length in bytes.
+	pub compressed_size: u64,
+	/// The uncompressed length in bytes.
+	pub size: u64,
+	/// The offset of the entry's local header from the start of the archive.
+	pub header_offset: u64,
+}
+
+impl ZipEntry {
+	/// Returns `true` if the entry is a directory.
+	#[inline]
+	pub fn is_dir(&self) -> bool {
+		self.name.ends_with('/')
+	}
+}
+
+/// A reader of zip archives over a seekable source.
+pub struct ZipReader<'d, S: Source<'d, N> + Seekable, const N: usize = SIZE, P: Pool<N> = DefaultPoolContainer> {
+	source: S,
+	buffer: Buffer<'d, N, P>,
+	entries: Vec<ZipEntry>,
+}
+
+/// A [`Source`] reading the contents of a stored zip entry. Closing it ends the
+/// entry without closing the archive source.
+pub struct ZipFile<'r, 'd, S: Source<'d, N>, const N: usize = SIZE> {
+	source: &'r mut S,
+	remaining: usize,
+	__data: PhantomData<&'d ()>
+}
+
+impl<'d, S: Source<'d, N> + Seekable, const N: usize, P: Pool<N>> ZipReader<'d, S, N, P> {
+	/// Creates a new zip reader, reading the central directory from `source`.
+	///
+	/// # Errors
+	///
+	/// If no end of central directory record is found, or the central directory is
+	/// malformed, an IO error of kind [`InvalidData`] is returned. Zip64 and
+	/// multi-disk archives fail with an IO error of kind [`Unsupported`].
+	///
+	/// [`InvalidData`]: io::ErrorKind::InvalidData
+	/// [`Unsupported`]: io::ErrorKind::Unsupported
+	pub fn new(source: S) -> Result<Self> {
+		let mut reader = Self {
+			source,
+			buffer: Buffer::default(),
+			entries: Vec::new(),
+		};
+		reader.read_central_directory()?;
+		Ok(reader)
+	}
+
+	/// Returns the entries in the central directory, in order.
+	#[inline]
+	pub fn entries(&self) -> &[ZipEntry] { &self.entries }
+
+	/// Returns the entry named `name`, if any.
+	pub fn by_name(&self, name: &str) -> Option<&ZipEntry> {
+		self.entries.iter().find(|entry| entry.name == name)
+	}
+
+	/// Consumes the reader, returning the source.
+	pub fn into_inner(self) -> S {
+		self.source
+	}
+
+	/// Opens a stored entry for reading, seeking to its contents.
+	///
+	/// # Errors
+	///
+	/// If the entry is compressed, an IO error of kind [`Unsupported`] is
+	/// returned. If its local header is malformed, an IO error of kind
+	/// [`InvalidData`] is returned.
+	///
+	/// [`InvalidData`]: io::ErrorKind::InvalidData
+	/// [`Unsupported`]: io::ErrorKind::Unsupported
+	pub fn open(&mut self, entry: &ZipEntry) -> Result<ZipFile<'_, 'd, S, N>> {
+		if entry.method != CompressionMethod::Stored {
+			return Err(unsupported("compressed zip entries are not supported"))
+		}
+
+		let offset = to_usize(entry.header_offset)?;
+		self.read_at(offset, LOCAL_HEADER_LEN)?;
+		if self.buffer.read_u32_le()? != LOCAL_HEADER_SIG {
+			return Err(invalid_data("invalid zip local header signature"))
+		}
+		self.buffer.skip(22);
+		let name_len = self.buffer.read_u16_le()? as usize;
+		let extra_len = self.buffer.read_u16_le()? as usize;
+		self.buffer.clear();
+		self.source.seek(SeekOffset::FromStart(offset + LOCAL_HEADER_LEN + name_len + extra_len))?;
+		Ok(ZipFile {
+			source: &mut self.source,
+			remaining: to_usize(entry.compressed_size)?,
+			__data: PhantomData
+		})
+	}
+
+	fn read_central_directory(&mut self) -> Result {
+		let len = self.source.seek_len()?;
+		let tail = len.min(END_LEN + MAX_COMMENT_LEN);
+		self.read_at(len - tail, tail)?;
+		let Some(end) = self.buffer
+							.matches(END_SIG)
+							.map(|m| m.range.start)
+							.filter(|&pos| pos + END_LEN <= tail)
+							.last() else {
+			return Err(invalid_data("zip end of central directory record not found"))
+		};
+
+		self.buffer.skip(end + 4);
+		let disk = self.buffer.read_u16_le()?;
+		let cd_disk = self.buffer.read_u16_le()?;
+		self.buffer.skip(2);
+		let count = self.buffer.read_u16_le()?;
+		let cd_size = self.buffer.read_u32_le()?;
+		let cd_offset = self.buffer.read_u32_le()?;
+		if count == u16::MAX || cd_size == u32::MAX || cd_offset == u32::MAX {
+			return Err(unsupported("zip64 archives are not supported"))
+		}
+		if disk != 0 || cd_disk != 0 {
+			return Err(unsupported("multi-disk zip archives are not supported"))
+		}
+
+		self.read_at(cd_offset as usize, cd_size as usize)?;
+		let mut entries = Vec::with_capacity(count as usize);
+		for _ in 0..count {
+			entries.push(self.read_central_header()?);
+		}
+		self.buffer.clear();
+		self.entries = entries;
+		Ok(())
+	}
+
+	fn read_central_header(&mut self) -> Result<ZipEntry> {
+		let buffer = &mut self.buffer;
+		if buffer.count() < CENTRAL_HEADER_LEN || buffer.read_u32_le()? != CENTRAL_HEADER_SIG {
+			return Err(invalid_data("invalid zip central directory header"))
+		}
+
+		buffer.skip(4);
+		let flags = buffer.read_u16_le()?;
+		let method = buffer.read_u16_le()?.into();
+		buffer.skip(4);
+		let crc32 = buffer.read_u32_le()?;
+		let compressed_size = buffer.read_u32_le()? as u64;
+		let size = buffer.read_u32_le()? as u64;
+		let name_len = buffer.read_u16_le()? as usize;
+		let extra_len = buffer.read_u16_le()? as usize;
+		let comment_len = buffer.read_u16_le()? as usize;
+		buffer.skip(8);
+		let header_offset = buffer.read_u32_le()? as u64;
+
+		if buffer.count() < name_len + extra_len + comment_len {
+			return Err(invalid_data("invalid zip central directory header"))
+		}
+		let mut name = vec![0; name_len];
+		buffer.read_slice_exact(&mut name)?;
+		buffer.skip(extra_len + comment_len);
+		// Names without the UTF-8 flag are usually CP437, which matches UTF-8 for
+		// ASCII. Other bytes are approximated as Latin-1.
+		let name = if flags & UTF8_FLAG != 0 || name.is_ascii() {
+			String::from_utf8(name).map_err(|_| invalid_data("invalid zip entry name"))?
+		} else {
+			name.iter().map(|&b| b as char).collect()
+		};
+		Ok(ZipEntry { name, method, crc32, compressed_size, size, header_offset })
+	}
+
+	/// Seeks to `pos` and reads exactly `len` bytes into the buffer, replacing its
+	/// contents.
+	fn read_at(&mut self, pos: usize, len: usize) -> Result {
+		self.buffer.clear();
+		self.source.seek(SeekOffset::FromStart(pos))?;
+		while self.buffer.count() < len {
+			let remaining = len - self.buffer.count();
+			if self.source.fill(&mut self.buffer, remaining)? == 0 {
+				return Err(StreamError::end_of_stream(remaining, Read))
+			}
+		}
+		Ok(())
+	}
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> ZipFile<'_, 'd, S, N> {
+	/// Returns the number of bytes remaining.
+	#[inline]
+	pub fn remaining(&self) -> usize { self.remaining }
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> Stream<N> for ZipFile<'_, 'd, S, N> {
+	fn is_closed(&self) -> bool {
+		self.source.is_closed()
+	}
+
+	/// Ends the entry. The archive source is left open.
+	fn close(&mut self) -> Result {
+		self.remaining = 0;
+		Ok(())
+	}
+}
+
+impl<'d, S: Source<'d, N>, const N: usize> Source<'d, N> for ZipFile<'_, 'd, S, N> {
+	fn is_eos(&self) -> bool {
+		self.remaining == 0
+	}
+
+	/// Reads up to `count` bytes of the entry into `sink`.
+	///
+	/// # Errors
+	///
+	/// If the archive source ends before the entry, an end-of-stream error is
+	/// returned.
+	fn fill(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		let count = count.min(self.remaining);
+		if count == 0 {
+			return Ok(0)
+		}
+
+		let read = self.source.fill(sink, count)?;
+		if read == 0 {
+			return Err(StreamError::end_of_stream(self.remaining, Read).into())
+		}
+		self.remaining -= read;
+		Ok(read)
+	}
+
+	fn fill_all(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>) -> BufferResult<usize> {
+		let mut total = 0;
+		while self.remaining > 0 {
+			total += self.fill(sink, N)?;
+		}
+		Ok(total)
+	}
+}
+
+fn unsupported(message: &'static str) -> StreamError {
+	StreamError {
+		source: io::Error::new(io::ErrorKind::Unsupported, message).into(),
+		context: Read,
+	}
+}
+
+fn to_usize(value: u64) -> Result<usize> {
+	usize::try_from(value).map_err(|_| invalid_data("zip entry too large"))
+}
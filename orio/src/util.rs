@@ -1,6 +1,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod compare;
 pub mod partial_utf8;
+pub mod trace;
 pub mod utf8;
 
 // Todo: Move these to a separate crate.
@@ -0,0 +1,115 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Comparison of segmented byte sequences. Overlapping parts of segments are
+//! compared as whole slices; only a slice found to differ is searched byte-by-byte
+//! for the mismatch.
+
+/// Returns the length of the longest common prefix of two sequences of slices.
+pub fn common_prefix_len<'a, 'b>(
+	a: impl IntoIterator<Item = &'a [u8]>,
+	b: impl IntoIterator<Item = &'b [u8]>
+) -> usize {
+	let mut a = a.into_iter();
+	let mut b = b.into_iter();
+	let (mut slice_a, mut slice_b): (&[u8], &[u8]) = (&[], &[]);
+	let mut len = 0;
+	loop {
+		if slice_a.is_empty() {
+			let Some(next) = a.next() else { return len };
+			slice_a = next;
+			continue
+		}
+		if slice_b.is_empty() {
+			let Some(next) = b.next() else { return len };
+			slice_b = next;
+			continue
+		}
+
+		let n = slice_a.len().min(slice_b.len());
+		let (head_a, tail_a) = slice_a.split_at(n);
+		let (head_b, tail_b) = slice_b.split_at(n);
+		if head_a != head_b {
+			return len + head_a.iter()
+							   .zip(head_b)
+							   .position(|(a, b)| a != b)
+							   .unwrap_or(n)
+		}
+
+		len += n;
+		slice_a = tail_a;
+		slice_b = tail_b;
+	}
+}
+
+/// Returns the length of the longest common suffix of two sequences of slices.
+pub fn common_suffix_len<'a, 'b>(
+	a: impl IntoIterator<Item = &'a [u8], IntoIter: DoubleEndedIterator>,
+	b: impl IntoIterator<Item = &'b [u8], IntoIter: DoubleEndedIterator>
+) -> usize {
+	let mut a = a.into_iter().rev();
+	let mut b = b.into_iter().rev();
+	let (mut slice_a, mut slice_b): (&[u8], &[u8]) = (&[], &[]);
+	let mut len = 0;
+	loop {
+		if slice_a.is_empty() {
+			let Some(next) = a.next() else { return len };
+			slice_a = next;
+			continue
+		}
+		if slice_b.is_empty() {
+			let Some(next) = b.next() else { return len };
+			slice_b = next;
+			continue
+		}
+
+		let n = slice_a.len().min(slice_b.len());
+		let (head_a, tail_a) = slice_a.split_at(slice_a.len() - n);
+		let (head_b, tail_b) = slice_b.split_at(slice_b.len() - n);
+		if tail_a != tail_b {
+			return len + tail_a.iter()
+							   .rev()
+							   .zip(tail_b.iter().rev())
+							   .position(|(a, b)| a != b)
+							   .unwrap_or(n)
+		}
+
+		len += n;
+		slice_a = head_a;
+		slice_b = head_b;
+	}
+}
+
+/// Returns `true` if two sequences of slices contain the same bytes, ignoring
+/// ASCII case.
+pub fn eq_ignore_ascii_case<'a, 'b>(
+	a: impl IntoIterator<Item = &'a [u8]>,
+	b: impl IntoIterator<Item = &'b [u8]>
+) -> bool {
+	let mut a = a.into_iter();
+	let mut b = b.into_iter();
+	let (mut slice_a, mut slice_b): (&[u8], &[u8]) = (&[], &[]);
+	loop {
+		if slice_a.is_empty() {
+			let Some(next) = a.next() else {
+				return slice_b.is_empty() && b.all(<[u8]>::is_empty)
+			};
+			slice_a = next;
+			continue
+		}
+		if slice_b.is_empty() {
+			let Some(next) = b.next() else { return false };
+			slice_b = next;
+			continue
+		}
+
+		let n = slice_a.len().min(slice_b.len());
+		let (head_a, tail_a) = slice_a.split_at(n);
+		let (head_b, tail_b) = slice_b.split_at(n);
+		if !head_a.eq_ignore_ascii_case(head_b) {
+			return false
+		}
+
+		slice_a = tail_a;
+		slice_b = tail_b;
+	}
+}
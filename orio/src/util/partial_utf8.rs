@@ -114,10 +114,10 @@ pub fn read_partial_utf8_into<'a>(
 
 	if !part.buf.is_empty() {
 		let Some(residual) = part.decode() else {
-			let count = part.buf.len();
-			part.buf.fill(0);
-			let bytes = part.buf.into_inner().unwrap();
-			return Err(Utf8Error::incomplete_char(count, bytes, count))
+			let len = part.buf.len();
+			let mut bytes = [0; 4];
+			bytes[..len].copy_from_slice(&part.buf);
+			return Err(Utf8Error::incomplete_char(count, bytes, len))
 		};
 		sink.push_str(residual);
 		count += residual.len();
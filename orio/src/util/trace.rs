@@ -0,0 +1,36 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Trace-level instrumentation, emitted with the `tracing` feature. Without it,
+//! the macros evaluate their fields by reference and discard them, so values only
+//! computed for tracing don't trigger unused warnings.
+
+/// A placeholder for an entered span, when tracing is disabled.
+#[cfg(not(feature = "tracing"))]
+pub struct Entered;
+
+/// Enters a trace-level span with `name` and fields, returning a guard which
+/// exits the span when dropped.
+macro_rules! span {
+	($name:literal $(, $field:ident = $value:expr)* $(,)?) => {{
+		#[cfg(feature = "tracing")]
+		let span = tracing::trace_span!($name $(, $field = $value)*).entered();
+		#[cfg(not(feature = "tracing"))]
+		let span = {
+			let _ = ($(&$value,)*);
+			$crate::util::trace::Entered
+		};
+		span
+	}};
+}
+
+/// Emits a trace-level event with `message` and fields.
+macro_rules! event {
+	($message:literal $(, $field:ident = $value:expr)* $(,)?) => {
+		#[cfg(feature = "tracing")]
+		tracing::trace!($($field = $value,)* $message);
+		#[cfg(not(feature = "tracing"))]
+		let _ = ($(&$value,)*);
+	};
+}
+
+pub(crate) use {event, span};
@@ -0,0 +1,146 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Integration with the [`winnow`] parser combinator library. [`ByteStr`] implements
+//! winnow's input traits, so parsers can consume buffered bytes across segments
+//! without copying them into a contiguous slice. [`parse_from`] drives a parser
+//! over a [`BufSource`], reading more data when the parser needs it.
+
+use winnow::error::{ErrMode, Needed};
+use winnow::stream::{Offset, Stream, StreamIsPartial};
+use winnow::{Parser, Partial};
+use crate::{ByteStr, ResultContext, StreamError};
+use crate::StreamContext::Read;
+use crate::streams::{BufSource, EndOfStream};
+
+/// An error returned by [`parse_from`].
+#[derive(Debug, thiserror::Error)]
+pub enum ParseError<E> {
+	/// The source returned an error while reading.
+	#[error(transparent)]
+	Stream(#[from] StreamError),
+	/// The parser rejected the input.
+	#[error("failed to parse input")]
+	Parse(E),
+}
+
+/// Parses a value from buffered bytes in `source` with a partial-input `parser`,
+/// consuming the parsed bytes on success. When the parser returns [`Incomplete`],
+/// more bytes are read from the source and the parser is retried from the start.
+/// Once the source reaches end-of-stream or stops returning bytes, the input is
+/// marked as complete for a final attempt.
+///
+/// The parser is called with a [`Partial`] wrapped [`ByteStr`] borrowing from
+/// the source's buffer, so no bytes are copied. Because the bytes are consumed
+/// after parsing, parsers must return owned values.
+///
+/// # Errors
+///
+/// Returns [`ParseError::Parse`] if the parser fails, or [`ParseError::Stream`] if
+/// the source fails to read or ends while the parser still needs input.
+///
+/// [`Incomplete`]: ErrMode::Incomplete
+pub fn parse_from<'d, const N: usize, S, P, O, E>(
+	source: &mut S,
+	mut parser: P
+) -> Result<O, ParseError<E>>
+where S: BufSource<'d, N>,
+	  P: for<'i> Parser<Partial<ByteStr<'i>>, O, ErrMode<E>> {
+	let mut eos = false;
+	loop {
+		let (result, consumed) = {
+			let mut input = Partial::new(source.buf().as_byte_str());
+			if eos {
+				let _ = input.complete();
+			}
+			let start = input.checkpoint();
+			let result = parser.parse_next(&mut input);
+			(result, input.offset_from(&start))
+		};
+
+		match result {
+			Ok(value) => {
+				source.buf_mut().skip(consumed);
+				return Ok(value)
+			}
+			Err(ErrMode::Incomplete(needed)) => {
+				let needed = match needed {
+					Needed::Size(size) => size.get(),
+					Needed::Unknown => 1
+				};
+				if eos {
+					return Err(EndOfStream::from(needed)).context(Read).map_err(ParseError::Stream)
+				}
+
+				let available = source.available();
+				if !source.request(available + needed)? &&
+					(source.is_eos() || source.available() == available) {
+					eos = true;
+				}
+			}
+			Err(ErrMode::Backtrack(error) | ErrMode::Cut(error)) =>
+				return Err(ParseError::Parse(error))
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::collections::VecDeque;
+	use std::io::{self, Read};
+	use pretty_assertions::assert_eq;
+	use winnow::ascii::space0;
+	use winnow::error::ContextError;
+	use winnow::stream::{AsChar, StreamIsPartial};
+	use winnow::token::{literal, take_until, take_while};
+	use winnow::{ModalResult, Parser, Partial};
+	use crate::{Buffer, ByteStr};
+	use crate::buffered_wrappers::BufferedSource;
+	use crate::pool::DefaultPoolContainer;
+	use crate::streams::{BufSource, ReaderSource};
+	use super::{parse_from, ParseError};
+
+	/// A reader returning one chunk per read.
+	struct Chunked(VecDeque<&'static [u8]>);
+
+	impl Read for Chunked {
+		fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+			let Some(chunk) = self.0.pop_front() else { return Ok(0) };
+			buf[..chunk.len()].copy_from_slice(chunk);
+			Ok(chunk.len())
+		}
+	}
+
+	fn header(input: &mut Partial<ByteStr>) -> ModalResult<(String, u32)> {
+		let name = take_until(1.., b':').parse_next(input)?;
+		(b':', space0).parse_next(input)?;
+		let value = take_while(1.., AsChar::is_dec_digit).parse_next(input)?;
+		literal("\r\n").parse_next(input)?;
+		Ok((name.to_utf8_lossy().into_owned(), value.to_utf8_lossy().parse().unwrap()))
+	}
+
+	#[test]
+	fn parse_byte_str() {
+		let input: ByteStr = [&b"Content-"[..], b"Length:", b" 12", b"3\r\nrest"].into_iter().collect();
+		let mut input = Partial::new(input);
+		let _ = input.complete();
+		assert_eq!(header.parse_next(&mut input).unwrap(), ("Content-Length".into(), 123));
+		assert_eq!(*input, *"rest");
+	}
+
+	#[test]
+	fn parse_source() {
+		let reader = Chunked([&b"Content-"[..], b"Length:", b" 12", b"3\r\nrest"].into());
+		let mut source = BufferedSource::new(ReaderSource::from(reader), Buffer::<_, DefaultPoolContainer>::default());
+		assert_eq!(parse_from(&mut source, header).unwrap(), ("Content-Length".into(), 123));
+		assert_eq!(source.available(), 4, "only parsed bytes should be consumed");
+		assert!(matches!(
+			parse_from(&mut source, header),
+			Err(ParseError::Parse(_))
+		), "input should be parsed as complete at end-of-stream");
+
+		let reader = Chunked([&b"Host: x\r\n"[..]].into());
+		let mut source = BufferedSource::new(ReaderSource::from(reader), Buffer::<_, DefaultPoolContainer>::default());
+		let result = parse_from(&mut source, header);
+		assert!(matches!(result, Err(ParseError::Parse(ContextError { .. }))), "{result:?}");
+	}
+}
@@ -0,0 +1,74 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! [`Digest`](digest::Digest) implementations of the xxHash family of fast,
+//! non-cryptographic hash functions, for use with [`HashSource`], [`HashSink`],
+//! and [`HashBytes`]. Hashes are output in big-endian byte order, the canonical
+//! representation used by the reference implementation.
+//!
+//! [`HashSource`]: crate::streams::HashSource
+//! [`HashSink`]: crate::streams::HashSink
+//! [`HashBytes`]: crate::HashBytes
+
+use digest::{FixedOutput, FixedOutputReset, HashMarker, Output, OutputSizeUser, Reset, Update};
+use digest::consts::{U8, U16};
+use xxhash_rust::xxh3::Xxh3Default;
+use xxhash_rust::xxh64::Xxh64 as Xxh64Hasher;
+
+macro_rules! xxhash {
+    ($($(#[$attr:meta])+$name:ident($inner:ty, $size:ty) = $new:expr, $digest:ident;)+) => {
+		$(
+		$(#[$attr])+
+		#[derive(Clone)]
+		pub struct $name($inner);
+
+		impl Default for $name {
+			#[inline]
+			fn default() -> Self {
+				Self($new)
+			}
+		}
+
+		impl HashMarker for $name { }
+
+		impl OutputSizeUser for $name {
+			type OutputSize = $size;
+		}
+
+		impl Update for $name {
+			#[inline]
+			fn update(&mut self, data: &[u8]) {
+				self.0.update(data);
+			}
+		}
+
+		impl Reset for $name {
+			#[inline]
+			fn reset(&mut self) {
+				*self = Self::default();
+			}
+		}
+
+		impl FixedOutput for $name {
+			fn finalize_into(self, out: &mut Output<Self>) {
+				out.copy_from_slice(&self.0.$digest().to_be_bytes());
+			}
+		}
+
+		impl FixedOutputReset for $name {
+			fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+				out.copy_from_slice(&self.0.$digest().to_be_bytes());
+				Reset::reset(self);
+			}
+		}
+		)+
+	};
+}
+
+xxhash! {
+	/// The 64-bit variant of XXH3, with the default seed and secret.
+	Xxh3_64(Xxh3Default, U8) = Xxh3Default::new(), digest;
+	/// The 128-bit variant of XXH3, with the default seed and secret.
+	Xxh3_128(Xxh3Default, U16) = Xxh3Default::new(), digest128;
+	/// XXH64, with a seed of zero.
+	Xxh64(Xxh64Hasher, U8) = Xxh64Hasher::new(0), digest;
+}
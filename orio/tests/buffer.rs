@@ -1,5 +1,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
+#![feature(core_io_borrowed_buf)]
+
 mod dataset;
 
 macro_rules! qc_assert_ok {
@@ -102,6 +104,214 @@ mod write {
 		let buffer = Buffer::from_utf8(&str).detached();
 		assert_eq!(buffer, str.as_bytes());
 	}
+
+	#[quickcheck]
+	fn spare_capacity(chunks: Vec<Vec<u8>>) {
+		let mut buffer = DefaultBuffer::default();
+		for chunk in &chunks {
+			let mut chunk = &chunk[..];
+			while !chunk.is_empty() {
+				let (written, ()) = buffer.with_spare_capacity(chunk.len(), |mut cursor| {
+					let len = cursor.capacity().min(chunk.len());
+					cursor.append(&chunk[..len]);
+				}).unwrap();
+				assert!(written > 0);
+				chunk = &chunk[written..];
+			}
+		}
+		assert_eq!(buffer, chunks.concat());
+	}
+
+	#[quickcheck]
+	fn extend(bytes: Vec<u8>, slices: Vec<Vec<u8>>) {
+		let mut buffer: Buffer = slices.iter().map(Vec::as_slice).collect();
+		assert_eq!(buffer, slices.concat());
+		buffer.extend(bytes.iter().copied());
+		buffer.extend(slices.iter().map(Vec::as_slice));
+		assert_eq!(buffer, [slices.concat(), bytes, slices.concat()].concat());
+	}
+
+	#[quickcheck]
+	fn fmt(a: u64, b: String) {
+		use std::fmt::Write;
+
+		let expected = format!("{a:x}: {b:?}");
+		let mut buffer = DefaultBuffer::default();
+		assert_eq!(write!(buffer, "{a:x}: {b:?}").unwrap(), expected.len());
+		write!(buffer.fmt_writer(), "{a:x}: {b:?}").unwrap();
+		assert_eq!(buffer, expected.repeat(2).as_bytes());
+	}
+
+	#[test]
+	fn max_capacity() {
+		use orio::{BufferOptions, ErrorKind};
+		use orio::streams::Source;
+
+		let mut buffer: DefaultBuffer = BufferOptions::default().with_max_capacity(8).into();
+		buffer.write_from_slice(b"12345").unwrap();
+		assert_eq!(buffer.remaining_capacity(), 3);
+		let error = buffer.write_from_slice(b"6789").err().unwrap();
+		assert_eq!(error.kind(), ErrorKind::CapacityExceeded);
+		assert!(buffer.reserve(4).err().unwrap().is_capacity_exceeded());
+		assert_eq!(buffer, b"12345", "failed writes should leave the buffer unchanged");
+
+		let mut source = Buffer::from_slice(b"6789");
+		assert_eq!(source.fill(&mut buffer, 4).unwrap(), 3, "fill should stop at capacity");
+		assert_eq!(buffer, b"12345678");
+		assert_eq!(source.fill(&mut buffer, 1).err().unwrap().kind(), ErrorKind::CapacityExceeded);
+		assert_eq!(buffer.write_u8(0).err().unwrap().kind(), ErrorKind::CapacityExceeded);
+
+		buffer.clear();
+		buffer.write_from_slice(b"1234567").unwrap();
+		let mut source = Buffer::from_slice(b"abc");
+		assert_eq!(source.fill_all(&mut buffer).err().unwrap().kind(), ErrorKind::CapacityExceeded);
+		assert_eq!(source, b"abc");
+	}
+
+	#[test]
+	fn jumbo_reserve() {
+		use orio::{BufferOptions, SIZE};
+
+		let mut buffer: DefaultBuffer = BufferOptions::default().with_jumbo_threshold(SIZE * 4).into();
+		buffer.reserve(SIZE * 2).unwrap();
+		assert_eq!(buffer.capacity(), SIZE * 2, "small reservations should claim segments");
+		buffer.reserve(SIZE * 10).unwrap();
+		assert!(buffer.capacity() >= SIZE * 10);
+		assert!(buffer.capacity() < SIZE * 11, "large reservations should claim one jumbo segment");
+
+		let data: Vec<u8> = (0..SIZE * 10).map(|i| i as u8).collect();
+		buffer.write_from_slice(&data).unwrap();
+		assert_eq!(buffer, data.as_slice());
+	}
+
+	#[test]
+	fn jumbo_spare_capacity() {
+		use std::io::Cursor;
+		use orio::{BufferOptions, SIZE};
+		use orio::streams::{ReaderSource, Source};
+
+		let mut buffer: DefaultBuffer = BufferOptions::default().with_jumbo_threshold(SIZE * 4).into();
+		buffer.reserve(SIZE * 8).unwrap();
+		let data: Vec<u8> = (0..SIZE * 4).map(|i| i as u8).collect();
+		let mut source = ReaderSource::from(Cursor::new(&data));
+		assert_eq!(source.fill(&mut buffer, data.len()).unwrap(), data.len(), "reads should fill the jumbo segment");
+
+		let (written, ()) = buffer.with_spare_capacity(10, |mut cursor| {
+			cursor.append(&[1; 10]);
+		}).unwrap();
+		assert_eq!(written, 10);
+		assert_eq!(buffer, [&data[..], &[1; 10]].concat());
+	}
+
+	#[test]
+	fn release_spare() {
+		use orio::{BufferOptions, SIZE};
+
+		let mut buffer: DefaultBuffer = BufferOptions::default().with_retain_spare(SIZE).into();
+		buffer.reserve(SIZE * 4).unwrap();
+		buffer.write_from_slice(&[0; 10]).unwrap();
+		assert_eq!(buffer.capacity(), SIZE * 4);
+		buffer.release_spare().unwrap();
+		assert_eq!(buffer.capacity(), SIZE * 2, "one spare segment should be retained");
+		buffer.shrink_to_fit().unwrap();
+		assert_eq!(buffer.capacity(), SIZE, "all empty segments should be returned");
+		buffer.reserve(SIZE * 2).unwrap();
+		buffer.skip(10);
+		assert_eq!(buffer.capacity(), SIZE, "spare capacity should be retained after reading");
+	}
+
+	#[test]
+	fn pin_capacity() {
+		use orio::SIZE;
+		use orio::pool::pool;
+
+		let mut buffer: DefaultBuffer = Buffer::with_reserved(pool(), SIZE * 2);
+		assert_eq!(buffer.capacity(), SIZE * 2);
+		buffer.write_from_slice(&[0; SIZE * 3]).unwrap();
+		buffer.skip(SIZE + 1);
+		assert_eq!(buffer.capacity(), SIZE * 2, "emptied segments should be recycled");
+		buffer.skip(SIZE * 2);
+		assert_eq!(buffer.capacity(), SIZE * 2, "pinned capacity should be kept when empty");
+		buffer.write_from_slice(&[1; SIZE]).unwrap();
+		buffer.clear();
+		assert_eq!(buffer.capacity(), SIZE * 2, "pinned capacity should be kept when cleared");
+
+		let mut buffer = DefaultBuffer::with_capacity(SIZE * 2);
+		buffer.write_from_slice(&[0; SIZE * 2]).unwrap();
+		buffer.skip(SIZE * 2);
+		assert_eq!(buffer.capacity(), 0, "segments should be returned without pinning");
+	}
+
+	#[test]
+	fn write_slice() {
+		use std::io;
+		use orio::{BufferOptions, ErrorKind};
+
+		let mut buffer: DefaultBuffer = BufferOptions::default().with_max_capacity(8).into();
+		assert_eq!(buffer.write_slice(b"12345").unwrap(), 5);
+		assert_eq!(buffer.write_slice(b"6789").unwrap(), 3, "write should be short at capacity");
+		assert_eq!(buffer.write_slice(b"9").unwrap(), 0);
+		assert_eq!(buffer, b"12345678");
+
+		buffer.clear();
+		buffer.write_all_slice(b"1234567").unwrap();
+		assert_eq!(
+			buffer.write_all_slice(b"89").err().unwrap().kind(),
+			ErrorKind::Io(io::ErrorKind::WriteZero)
+		);
+		assert_eq!(buffer, b"12345678");
+	}
+
+	#[test]
+	fn yield_after() {
+		use std::io::Cursor;
+		use orio::{BufferOptions, SIZE};
+		use orio::streams::{ReaderSource, Source};
+
+		let data = vec![7; 3 * SIZE + 10];
+		let mut source = ReaderSource::from(Cursor::new(&data));
+		let mut buffer: DefaultBuffer = BufferOptions::default().with_yield_after(SIZE).into();
+		let mut fills = 0;
+		loop {
+			let read = source.fill_all(&mut buffer).unwrap();
+			if read == 0 { break }
+			assert!(read < data.len(), "fill should yield with partial progress");
+			fills += 1;
+		}
+		assert!(fills > 1);
+		assert_eq!(buffer, data);
+	}
+
+	#[quickcheck]
+	fn write_zeroes(prefix: Vec<u8>, count: u16) {
+		use orio::SIZE;
+
+		let count = count as usize % (3 * SIZE);
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(&prefix).unwrap();
+		buffer.write_zeroes(count).unwrap();
+		buffer.write_u8(1).unwrap();
+		let mut expected = prefix;
+		expected.resize(expected.len() + count, 0);
+		expected.push(1);
+		assert_eq!(buffer.count(), expected.len());
+		assert_eq!(buffer.as_byte_str(), expected[..]);
+	}
+
+	#[test]
+	fn write_zeroes_borrows() {
+		use orio::{BufferOptions, SIZE};
+
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_zeroes(SIZE + 100).unwrap();
+		assert_eq!(buffer.stats().borrowed_bytes, SIZE, "long runs should be borrowed");
+		assert_eq!(buffer.stats().owned_bytes, 100);
+
+		let mut buffer: DefaultBuffer = BufferOptions::lean().with_max_capacity(10).into();
+		buffer.write_zeroes(10).unwrap();
+		assert_eq!(buffer.stats().owned_bytes, 10);
+		assert!(buffer.write_zeroes(1).is_err(), "zeroes should be limited by capacity");
+	}
 }
 
 mod read {
@@ -110,7 +320,7 @@ mod read {
 	use std::mem::size_of;
 	use quickcheck::{Arbitrary, Gen, TestResult};
 	use quickcheck_macros::quickcheck;
-	use orio::{Buffer, BufferOptions, DefaultBuffer, StreamResult};
+	use orio::{Buffer, BufferOptions, ByteString, DefaultBuffer, ErrorKind, SIZE, StreamResult};
 	use orio::streams::{BufSink, BufSource};
 
 	macro_rules! gen_single {
@@ -160,6 +370,124 @@ mod read {
 		assert_eq!(slice, vec);
 	}
 
+	#[test]
+	fn read_until() {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(b"\xFF\x00abc\0\xFEdef").unwrap();
+		let mut sink = DefaultBuffer::default();
+		let r#match = buffer.read_until(&mut sink, 0u8).unwrap();
+		assert!(r#match.found);
+		assert_eq!(r#match.read_count, 1);
+		assert_eq!(sink, b"\xFF");
+
+		let r#match = buffer.read_until_inclusive(&mut sink, 0u8).unwrap();
+		assert!(r#match.found);
+		assert_eq!(r#match.read_count, 4);
+		assert_eq!(sink, b"\xFFabc\0");
+
+		let r#match = buffer.read_until(&mut sink, 0u8).unwrap();
+		assert!(!r#match.found);
+		assert_eq!(sink, b"\xFFabc\0\xFEdef");
+		assert!(buffer.is_empty());
+	}
+
+	#[test]
+	fn read_line_bytes() {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(b"\xC0\xFF\r\nrest").unwrap();
+		let mut line = ByteString::new();
+		let r#match = buffer.read_line_bytes(&mut line).unwrap();
+		assert!(r#match.found);
+		assert_eq!(line, ByteString::from(&b"\xC0\xFF"[..]));
+		line = ByteString::new();
+		assert!(!buffer.read_line_bytes(&mut line).unwrap().found);
+		assert_eq!(line, ByteString::from("rest"));
+	}
+
+	#[test]
+	fn to_utf8_lossy() {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(b"\xF0\x9F\x92\x96 ok \xFF").unwrap();
+		assert_eq!(buffer.to_utf8_lossy(), "\u{1F496} ok \u{FFFD}");
+		assert_eq!(buffer.count(), 9, "buffer should be unchanged");
+	}
+
+	#[test]
+	fn chars() {
+		use orio::SIZE;
+
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(&[b'a'; SIZE - 2]).unwrap();
+		buffer.write_utf8("\u{1F496}\u{E9}").unwrap();
+		buffer.write_u8(0xFF).unwrap();
+		let chars: String = buffer.chars().skip(SIZE - 2).collect();
+		assert_eq!(chars, "\u{1F496}\u{E9}\u{FFFD}");
+		assert_eq!(buffer.chars().count(), SIZE + 1);
+	}
+
+	#[test]
+	fn contiguous() {
+		use orio::SIZE;
+
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(&[b'a'; SIZE - 2]).unwrap();
+		buffer.write_from_slice(b"bcde").unwrap();
+		assert_eq!(buffer.contiguous(4).unwrap(), b"aaaa");
+		buffer.skip(SIZE - 4);
+		assert_eq!(buffer.contiguous(5).unwrap(), b"aabcd");
+		assert_eq!(buffer.contiguous(10).unwrap(), b"aabcde");
+		assert_eq!(buffer.count(), 6, "no bytes should be consumed");
+
+		let vec: Vec<u8> = (0..SIZE * 2 + 7).map(|i| i as u8).collect();
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(&vec).unwrap();
+		assert_eq!(buffer.contiguous(SIZE + 5).unwrap(), &vec[..SIZE + 5]);
+		assert_eq!(buffer, vec.as_slice());
+	}
+
+	#[quickcheck]
+	fn fill_buf(vec: Vec<u8>) {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(&vec).unwrap();
+		buffer.write_from_slice(&vec).unwrap();
+		let mut read = Vec::new();
+		loop {
+			let slice = buffer.fill_buf().unwrap();
+			if slice.is_empty() {
+				break
+			}
+			let count = slice.len().div_ceil(2);
+			read.extend_from_slice(&slice[..count]);
+			buffer.consume(count);
+		}
+		assert_eq!(read, [&vec[..], &vec[..]].concat());
+	}
+
+	#[quickcheck]
+	fn read_to_vec(vec: Vec<u8>, count: usize) {
+		let count = count % (vec.len() + 1);
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(&vec).unwrap();
+		let mut read = b"head".to_vec();
+		assert_eq!(buffer.read_to_vec(&mut read, count).unwrap(), count);
+		assert_eq!(read[4..], vec[..count]);
+		assert_eq!(buffer.read_to_end_vec(&mut read).unwrap(), vec.len() - count);
+		assert_eq!(read[4..], vec);
+		assert!(buffer.is_empty());
+	}
+
+	#[quickcheck]
+	fn into_bytes(vec: Vec<u8>, prefix: Vec<u8>) {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(&vec).unwrap();
+		let mut drained = prefix.clone();
+		assert_eq!(buffer.clone().drain_into_vec(&mut drained), vec.len());
+		assert_eq!(drained[..prefix.len()], prefix);
+		assert_eq!(drained[prefix.len()..], vec);
+		assert_eq!(buffer.clone().into_bytes(), vec);
+		assert_eq!(buffer.into_byte_string(), ByteString::from(vec));
+	}
+
 	#[quickcheck]
 	fn num_vec(vec: Vec<i32>) {
 		let mut buffer = DefaultBuffer::default();
@@ -187,6 +515,36 @@ mod read {
 		assert_str_eq!(string, str);
 	}
 
+	#[test]
+	fn utf8_split_char() {
+		let mut buffer = Buffer::from_utf8("aé");
+		let mut string = String::new();
+		// The count falls within a character: nothing is consumed.
+		assert_eq!(buffer.read_utf8(&mut string, 2).unwrap_err().kind(), ErrorKind::Utf8);
+		assert_eq!(string, "a");
+		assert_eq!(buffer.count(), 3);
+		string.clear();
+		assert_eq!(buffer.read_utf8_exact_count(&mut string, 2).unwrap_err().kind(), ErrorKind::Utf8);
+		assert_eq!(buffer.count(), 3);
+		string.clear();
+		assert_eq!(buffer.read_utf8_exact_count(&mut string, 4).unwrap_err().kind(), ErrorKind::Eos);
+		assert_eq!(buffer.read_utf8_exact_count(&mut string, 3).unwrap(), "aé");
+
+		// The data ends within a character.
+		let mut buffer = Buffer::from_slice(b"a\xE2\x82");
+		let mut string = String::new();
+		assert_eq!(buffer.read_utf8_to_end(&mut string).unwrap_err().kind(), ErrorKind::Utf8);
+		assert_eq!(string, "a");
+		assert_eq!(buffer.count(), 3);
+
+		// Characters split across segments are decoded whole.
+		let text = "€".repeat(SIZE);
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_utf8(&text).unwrap();
+		let mut string = String::new();
+		assert_eq!(buffer.read_utf8_exact_count(&mut string, text.len()).unwrap(), text);
+	}
+
 	#[derive(Clone, Debug)]
 	enum Value {
 		U8(u8),
@@ -381,6 +739,21 @@ mod read {
 		}
 	}
 
+	#[test]
+	fn skip() {
+		use orio::SIZE;
+
+		let data = (0..SIZE * 2).map(|i| i as u8).collect::<Vec<_>>();
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(&data).unwrap();
+		assert_eq!(buffer.skip(SIZE / 2), SIZE / 2);
+		assert_eq!(buffer.count(), SIZE * 3 / 2);
+		assert_eq!(buffer.skip(SIZE), SIZE);
+		assert_eq!(buffer, &data[SIZE * 3 / 2..], "partially skipped segment should be kept");
+		assert_eq!(buffer.skip(SIZE), SIZE / 2, "should skip at most the remaining bytes");
+		assert!(buffer.is_empty());
+	}
+
 	#[quickcheck]
 	fn mixed_vec(vec: Vec<Value>) -> TestResult {
 		let ref mut buffer: DefaultBuffer = BufferOptions::default().always_allocate().into();
@@ -395,6 +768,1227 @@ mod read {
 
 		TestResult::passed()
 	}
+
+	#[quickcheck]
+	fn into_cursor(vec: Vec<u8>, len: u8) {
+		use std::io::BorrowedBuf;
+		use std::mem::MaybeUninit;
+
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(&vec).unwrap();
+		let mut storage = vec![MaybeUninit::uninit(); len as usize];
+		let mut buf = BorrowedBuf::from(&mut storage[..]);
+		let count = buffer.read_into_cursor(buf.unfilled()).unwrap();
+		assert_eq!(count, vec.len().min(len as usize));
+		assert_eq!(buf.filled(), &vec[..count]);
+		assert_eq!(buffer, &vec[count..]);
+	}
+}
+
+mod copy {
+	use pretty_assertions::assert_eq;
+	use quickcheck_macros::quickcheck;
+	use orio::{Buffer, DefaultBuffer, SIZE};
+	use orio::streams::{BufSink, BufSource, Source};
+
+	#[quickcheck]
+	fn copy_range_to(data: Vec<u8>, start: usize, end: usize) {
+		let end = end % (data.len() + 1);
+		let start = start % (end + 1);
+		let buffer = Buffer::from_slice(&data);
+		let mut sink = DefaultBuffer::default();
+		buffer.copy_range_to(start..end, &mut sink).unwrap();
+		assert_eq!(sink, &data[start..end]);
+		assert_eq!(buffer, &data, "source should not be consumed");
+	}
+
+	#[test]
+	fn copy_range_across_segments() {
+		let data = (0..SIZE * 3).map(|i| i as u8).collect::<Vec<_>>();
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(&data).unwrap();
+
+		let mut sink = DefaultBuffer::default();
+		sink.write_from_slice(b"head").unwrap();
+		buffer.copy_range_to(SIZE / 2..SIZE * 2 + 7, &mut sink).unwrap();
+
+		let mut expected = b"head".to_vec();
+		expected.extend_from_slice(&data[SIZE / 2..SIZE * 2 + 7]);
+		assert_eq!(sink, &expected);
+		assert_eq!(buffer, &data, "source should not be consumed");
+	}
+
+	#[test]
+	fn copy_range_after_full_segment() {
+		let data = (0..SIZE * 2).map(|i| i as u8).collect::<Vec<_>>();
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(&data).unwrap();
+
+		let mut sink = DefaultBuffer::default();
+		buffer.copy_range_to(..SIZE + 16, &mut sink).unwrap();
+		assert_eq!(sink, &data[..SIZE + 16], "shared full segment should be kept in order");
+	}
+
+	#[test]
+	fn copy_with_options() {
+		use orio::CopyOptions;
+
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(&[1; SIZE * 2]).unwrap();
+
+		let mut sink = DefaultBuffer::default();
+		buffer.copy_to_with(&mut sink, SIZE * 2, CopyOptions::deep()).unwrap();
+		assert_eq!(sink.stats().shared_bytes, 0, "deep copies should not share");
+		assert_eq!(sink, [1; SIZE * 2]);
+
+		let mut sink = DefaultBuffer::default();
+		buffer.copy_to_with(&mut sink, 16, CopyOptions::shared()).unwrap();
+		assert_eq!(sink.stats().shared_bytes, 16, "forced shares should share small data");
+
+		let mut sink = DefaultBuffer::default();
+		buffer.copy_range_to_with(..16, &mut sink, CopyOptions::new().with_share_threshold(8)).unwrap();
+		assert_eq!(sink.stats().shared_bytes, 16);
+		assert_eq!(buffer.stats().shared_bytes, SIZE, "the source segment should be shared");
+	}
+
+	#[test]
+	fn fill_after_read() {
+		let mut sink = DefaultBuffer::default();
+		sink.write_from_slice(b"abcd").unwrap();
+		let mut buf = [0; 4];
+		sink.read_slice(&mut buf).unwrap();
+
+		let mut source = DefaultBuffer::default();
+		source.write_from_slice(b"efgh").unwrap();
+		source.fill_all(&mut sink).unwrap();
+		assert_eq!(sink.read_slice(&mut buf).unwrap(), 4, "moved segment should be readable");
+		assert_eq!(&buf, b"efgh");
+	}
+}
+
+mod io_slices {
+	use std::io::Write;
+	use pretty_assertions::assert_eq;
+	use orio::{DefaultBuffer, SIZE};
+	use orio::streams::BufSink;
+
+	#[test]
+	fn write_vectored() {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(&[1; SIZE - 1]).unwrap();
+		buffer.push_slice(&[2; SIZE * 2]);
+		buffer.write_from_slice(&[3; 3]).unwrap();
+		let slices = buffer.as_io_slices();
+		assert_eq!(slices.iter().map(|s| s.len()).sum::<usize>(), buffer.count());
+
+		let mut written = Vec::new();
+		let count = written.write_vectored(&slices).unwrap();
+		drop(slices);
+		assert_eq!(buffer.skip(count), count);
+		assert_eq!(written.len(), SIZE * 3 + 2);
+		assert!(buffer.is_empty());
+
+		let slices = buffer.as_io_slices();
+		assert!(slices.is_empty());
+	}
+
+	#[test]
+	fn in_range() {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(b"Hello, ").unwrap();
+		buffer.push_slice(&[b'!'; SIZE]);
+		let slices = buffer.as_io_slices_in_range(4..9);
+		assert_eq!(slices.iter().map(|s| &s[..]).collect::<Vec<_>>(), [&b"o, "[..], b"!!"]);
+	}
+}
+
+mod pin {
+	use std::slice;
+	use pretty_assertions::assert_eq;
+	use orio::{DefaultBuffer, SIZE};
+	use orio::streams::BufSink;
+
+	#[test]
+	fn pin_slices() {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(&[1; SIZE + 1]).unwrap();
+		buffer.push_slice(&[2; SIZE]);
+		let pinned = buffer.pin_slices();
+		assert_eq!(pinned.count(), SIZE * 2 + 1);
+		assert_eq!(pinned.len(), 3);
+
+		let data: Vec<u8> = pinned.raw_slices()
+								  .into_iter()
+								  .flat_map(|raw| unsafe {
+									  slice::from_raw_parts(raw.ptr, raw.len)
+								  })
+								  .copied()
+								  .collect();
+		assert_eq!(data, [vec![1; SIZE + 1], vec![2; SIZE]].concat());
+		drop(pinned);
+		buffer.clear();
+		assert!(buffer.pin_slices().is_empty());
+	}
+}
+
+mod read_at {
+	use pretty_assertions::assert_eq;
+	use quickcheck_macros::quickcheck;
+	use orio::{Buffer, DefaultBuffer, SIZE};
+	use orio::streams::BufSink;
+
+	#[quickcheck]
+	fn read_slice_at(data: Vec<u8>, pos: usize, len: u8) {
+		let pos = pos % (data.len() + 1);
+		let buffer = Buffer::from_slice(&data);
+		let mut buf = vec![0; len as usize];
+		let count = buffer.read_slice_at(pos, &mut buf);
+		let expected = &data[pos..][..(len as usize).min(data.len() - pos)];
+		assert_eq!(&buf[..count], expected);
+		assert_eq!(buffer, &data, "buffer should not be consumed");
+	}
+
+	#[test]
+	fn read_int_across_segments() {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(&[0; SIZE - 2]).unwrap();
+		buffer.write_u32(0xDEADBEEF).unwrap();
+		assert_eq!(buffer.read_u8_at(SIZE - 2).unwrap(), 0xDE);
+		assert_eq!(buffer.read_int_at::<u32>(SIZE - 2).unwrap(), 0xDEADBEEF);
+		assert_eq!(buffer.read_int_le_at::<u32>(SIZE - 2).unwrap(), 0xEFBEADDE);
+		assert_eq!(buffer.read_array_at::<2>(SIZE).unwrap(), [0xBE, 0xEF]);
+		assert_eq!(buffer.count(), SIZE + 2, "buffer should not be consumed");
+	}
+
+	#[test]
+	fn read_at_end() {
+		let buffer = Buffer::from_slice(&[1, 2, 3]);
+		assert!(buffer.read_int_at::<u32>(0).unwrap_err().is_eos());
+		assert!(buffer.read_u8_at(3).unwrap_err().is_eos());
+		assert_eq!(buffer.read_slice_at(5, &mut [0; 4]), 0);
+	}
+}
+
+mod view {
+	use std::io::{BufRead, Read};
+	use pretty_assertions::assert_eq;
+	use quickcheck_macros::quickcheck;
+	use orio::{Buffer, DefaultBuffer, SIZE};
+	use orio::streams::BufSink;
+
+	#[quickcheck]
+	fn read_to_end(data: Vec<u8>, skip: u8) {
+		let buffer = Buffer::from_slice(&data);
+		let mut view = buffer.view();
+		let skipped = view.skip(skip as usize);
+		let mut vec = Vec::new();
+		view.read_to_end(&mut vec).unwrap();
+		assert_eq!(vec, &data[skipped..]);
+		assert!(view.is_empty());
+		assert_eq!(buffer, &data, "buffer should not be consumed");
+	}
+
+	#[test]
+	fn independent_views() {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(&[0; SIZE - 2]).unwrap();
+		buffer.write_u32(0xDEADBEEF).unwrap();
+		buffer.write_from_slice(b"key: value\n").unwrap();
+
+		let mut header = buffer.view();
+		let mut body = buffer.view();
+		body.set_pos(SIZE + 2);
+		assert_eq!(header.skip(SIZE - 2), SIZE - 2);
+		assert_eq!(header.read_u32().unwrap(), 0xDEADBEEF);
+		assert_eq!(body.find(&b": "[..]), Some(3..5));
+		let mut key = String::new();
+		assert_eq!(body.read_utf8(&mut key, 3).unwrap(), "key");
+		assert_eq!(body.pos(), header.pos() + 3);
+
+		// Views are copied with their position.
+		let mut copy = body;
+		copy.consume(2);
+		assert_eq!(copy.fill_buf().unwrap(), b"value\n");
+		assert_eq!(body.remaining(), 8);
+		assert_eq!(buffer.count(), SIZE + 13, "buffer should not be consumed");
+	}
+
+	#[test]
+	fn read_at_end() {
+		let buffer = Buffer::from_slice(&[1, 2, 3]);
+		let mut view = buffer.view();
+		assert!(view.read_u32().unwrap_err().is_eos());
+		assert_eq!(view.pos(), 0, "position shouldn't advance on error");
+		assert_eq!(view.read_array::<3>().unwrap(), [1, 2, 3]);
+		assert!(view.read_u8().unwrap_err().is_eos());
+		assert!(view.fill_buf().unwrap().is_empty());
+		view.set_pos(10);
+		assert_eq!(view.pos(), 3);
+	}
+}
+
+mod replace {
+	use pretty_assertions::assert_eq;
+	use quickcheck_macros::quickcheck;
+	use orio::{Buffer, ByteString, DefaultBuffer, SIZE};
+	use orio::streams::BufSink;
+
+	#[quickcheck]
+	fn replace(data: Vec<u8>, from: u8, to: Vec<u8>) {
+		let expected = ByteString::from(data.clone()).replace(from, &to);
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(&data).unwrap();
+		let count = buffer.replace(from, &to).unwrap();
+		assert_eq!(count, data.iter().filter(|&&b| b == from).count());
+		assert_eq!(buffer, expected);
+	}
+
+	#[test]
+	fn replace_in_place_across_segments() {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(&[0; SIZE - 2]).unwrap();
+		buffer.write_from_slice(b"abcd").unwrap();
+		assert_eq!(buffer.replace(&b"abcd"[..], b"wxyz").unwrap(), 1);
+		assert_eq!(buffer.count(), SIZE + 2);
+		assert_eq!(buffer.read_array_at::<4>(SIZE - 2).unwrap(), *b"wxyz");
+	}
+
+	#[test]
+	fn replace_shared() {
+		let data = b"one two one two";
+		let mut buffer = Buffer::from_slice(data);
+		assert_eq!(buffer.replace(&b"one"[..], b"three").unwrap(), 2);
+		assert_eq!(buffer, b"three two three two");
+		assert_eq!(buffer.replace(&b"two"[..], b"2!2").unwrap(), 2);
+		assert_eq!(buffer, b"three 2!2 three 2!2");
+	}
+
+	#[test]
+	fn fill_range_across_segments() {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(&[1; SIZE + 4]).unwrap();
+		buffer.fill_range(SIZE - 2..SIZE + 2, 0).unwrap();
+		assert_eq!(buffer.count(), SIZE + 4);
+		assert_eq!(buffer.read_array_at::<6>(SIZE - 3).unwrap(), [1, 0, 0, 0, 0, 1]);
+	}
+
+	#[test]
+	fn fill_range_shared() {
+		let data = b"secret: hunter2";
+		let mut buffer = Buffer::from_slice(data);
+		let shared = buffer.clone();
+		buffer.fill_range(8.., b'*').unwrap();
+		assert_eq!(buffer, b"secret: *******");
+		assert_eq!(shared, data, "shared data should be unchanged");
+	}
+
+	#[test]
+	#[cfg(feature = "zeroize")]
+	fn zeroize() {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(b"hunter2").unwrap();
+		buffer.zeroize();
+		assert!(buffer.is_empty());
+	}
+
+	#[test]
+	#[cfg(feature = "zeroize")]
+	fn zero_on_collect() {
+		use std::mem::MaybeUninit;
+		use orio::pool::{DefaultPoolContainer, Pool, PoolExt};
+
+		let pool = DefaultPoolContainer::default();
+		pool.try_use(|pool| pool.set_zero_on_collect(true)).unwrap();
+		let mut segment = pool.claim_one().unwrap();
+		segment.write(b"hunter2").unwrap();
+		pool.collect_one(segment).unwrap();
+
+		let block = pool.claim_one().unwrap().into_block().unwrap();
+		let bytes = unsafe { MaybeUninit::slice_assume_init_ref(&block[..7]) };
+		assert_eq!(bytes, [0; 7], "collected segments should be zeroed");
+	}
+
+	#[test]
+	#[cfg(feature = "zeroize")]
+	fn sensitive() {
+		use std::mem::MaybeUninit;
+		use orio::BufferOptions;
+		use orio::pool::{DefaultPoolContainer, Pool};
+
+		let options = BufferOptions::default().with_sensitive(true);
+		assert!(options.is_sensitive());
+		let pool = DefaultPoolContainer::default();
+		let mut buffer = Buffer::new(pool.clone(), options);
+		assert_eq!(buffer.options().share_threshold(), usize::MAX, "sensitive data should never be shared");
+		assert_eq!(buffer.options().borrow_threshold(), usize::MAX, "sensitive data should never be borrowed");
+
+		let secret = [0xA5; 2048];
+		buffer.write_from_slice(&secret).unwrap();
+		let mut copy = DefaultBuffer::default();
+		buffer.copy_all_to(&mut copy).unwrap();
+		assert_eq!(copy, secret);
+
+		buffer.clear();
+		assert_eq!(pool.pooled_count().unwrap(), 1, "segment should be exclusive after copying");
+		let block = pool.claim_one().unwrap().into_block().unwrap();
+		let bytes = unsafe { MaybeUninit::slice_assume_init_ref(&block[..secret.len()]) };
+		assert!(bytes.iter().all(|&b| b == 0), "sensitive segments should be zeroed");
+	}
+}
+
+mod find {
+	use pretty_assertions::assert_eq;
+	use orio::{Buffer, DefaultBuffer, SIZE};
+	use orio::pattern::SliceMatcher;
+	use orio::streams::{BufSink, BufSource};
+
+	#[test]
+	fn find_match_across_segments() {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(&[0; SIZE - 2]).unwrap();
+		buffer.write_from_slice(b"abcd").unwrap();
+		let found = buffer.find_match(&b"abcd"[..]).unwrap();
+		assert_eq!(found.range, SIZE - 2..SIZE + 2);
+		assert_eq!(found.text, b"abcd"[..]);
+		assert!(buffer.find_match(&b"dcba"[..]).is_none());
+	}
+
+	#[test]
+	fn matches() {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_utf8("one two one two one").unwrap();
+		let matches: Vec<_> = buffer.matches(&b"one"[..]).collect();
+		assert_eq!(matches.len(), 3);
+		assert_eq!(matches[1].range, 8..11);
+		assert!(matches.iter().all(|m| m.text == b"one"[..]));
+		assert_eq!(buffer.count_matches(&b"two"[..]), 2);
+		assert!(buffer.contains(&b"two one"[..]));
+		assert!(!buffer.contains(&b"three"[..]));
+	}
+
+	#[test]
+	fn split() {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_utf8("a,bc,,d,").unwrap();
+		let parts: Vec<_> = buffer.split(&b","[..]).collect();
+		assert_eq!(parts.len(), 5);
+		for (part, expected) in parts.iter().zip([&b"a"[..], b"bc", b"", b"d", b""]) {
+			assert_eq!(*part, expected);
+		}
+		assert_eq!(buffer, b"a,bc,,d,", "buffer should be unchanged");
+	}
+
+	#[test]
+	fn lines() {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(&[b'x'; SIZE - 1]).unwrap();
+		buffer.write_utf8("\r\nsecond\n\nlast\n").unwrap();
+		let lines: Vec<_> = buffer.lines().collect();
+		assert_eq!(lines.len(), 4);
+		assert_eq!(lines[0], [b'x'; SIZE - 1].as_slice());
+		assert_eq!(lines[1], b"second");
+		assert_eq!(lines[2], b"");
+		assert_eq!(lines[3], b"last");
+		assert_eq!(buffer.count(), SIZE + 14, "buffer should be unchanged");
+
+		let buffer = Buffer::from_slice(b"no terminator");
+		let lines: Vec<_> = buffer.lines().collect();
+		assert_eq!(lines.len(), 1);
+		assert_eq!(lines[0], b"no terminator");
+
+		let buffer = Buffer::from_slice(b"lone\rreturn\r\n");
+		let lines: Vec<_> = buffer.lines().collect();
+		assert_eq!(lines.len(), 1, "lone carriage returns shouldn't split lines");
+		assert_eq!(lines[0], b"lone\rreturn");
+		assert_eq!(Buffer::from_slice(b"").lines().count(), 0);
+	}
+
+	#[test]
+	fn find_with() {
+		let mut matcher = SliceMatcher::from("abc");
+		let buffer = Buffer::from_slice(b"xxab");
+		assert_eq!(buffer.find_with(&mut matcher), None);
+		// The partial match left over from the last search should be discarded.
+		let buffer = Buffer::from_slice(b"cabc");
+		assert_eq!(buffer.find_with(&mut matcher), Some(1..4));
+
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_utf8("one, two, three").unwrap();
+		let mut matcher = SliceMatcher::from(", ");
+		let mut parts = Vec::new();
+		while buffer.is_not_empty() {
+			let mut part = String::new();
+			buffer.read_utf8_until_with(&mut part, &mut matcher).unwrap();
+			parts.push(part);
+		}
+		assert_eq!(parts, ["one", "two", "three"]);
+	}
+}
+
+mod hexdump {
+	use pretty_assertions::assert_str_eq;
+	use orio::{Buffer, DefaultBuffer, SIZE};
+	use orio::streams::BufSink;
+
+	#[test]
+	fn hexdump() {
+		let buffer = Buffer::from_slice(b"Hello, world!\n\0\xff");
+		assert_str_eq!(
+			buffer.hexdump().to_string(),
+			"00000000: 4865 6c6c 6f2c 2077 6f72 6c64 210a 00ff  Hello, world!...\n"
+		);
+		assert_str_eq!(
+			buffer.hexdump().width(6).range(2..11).to_string(),
+			"00000002: 6c6c 6f2c 2077  llo, w\n\
+			 00000008: 6f72 6c         orl\n"
+		);
+		assert_str_eq!(buffer.as_byte_str().hexdump().range(..0).to_string(), "");
+	}
+
+	#[test]
+	fn hexdump_across_segments() {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(&[b'a'; SIZE - 1]).unwrap();
+		buffer.write_from_slice(b"bc").unwrap();
+		assert_str_eq!(
+			buffer.hexdump().range(SIZE - 2..).to_string(),
+			format!("{:08x}: 6162 63                                  abc\n", SIZE - 2)
+		);
+	}
+}
+
+mod stats {
+	use pretty_assertions::assert_eq;
+	use orio::{BufferOptions, BufferStats, CompactionPolicy, DefaultBuffer, SIZE};
+	use orio::streams::BufSink;
+
+	#[test]
+	fn stats() {
+		assert_eq!(DefaultBuffer::default().stats(), BufferStats::default());
+
+		let borrowed = [1; 2000];
+		let mut buffer: DefaultBuffer = BufferOptions::default()
+			.with_compaction_policy(CompactionPolicy::Never)
+			.into();
+		buffer.write_from_slice(&[0; SIZE + 10]).unwrap();
+		buffer.push_slice(&borrowed);
+		let stats = buffer.stats();
+		assert_eq!(stats, BufferStats {
+			segments: 3,
+			owned_bytes: SIZE + 10,
+			shared_bytes: 0,
+			borrowed_bytes: 2000,
+			spare_capacity: buffer.limit(),
+			fragmentation: 1.0 - (SIZE + 2010) as f64 / (2 * SIZE + 2000) as f64,
+		});
+
+		let shared = buffer.range(..SIZE);
+		let stats = buffer.stats();
+		assert_eq!(stats.owned_bytes, 10);
+		assert_eq!(stats.shared_bytes, SIZE);
+		assert_eq!(shared.stats().shared_bytes, SIZE);
+	}
+}
+
+mod compaction {
+	use pretty_assertions::assert_eq;
+	use quickcheck_macros::quickcheck;
+	use orio::{BufferOptions, CompactionPolicy, DefaultBuffer, SIZE};
+	use orio::streams::BufSink;
+
+	/// Creates a buffer with a gap of `gap` bytes before a borrowed segment.
+	fn fragmented<'d>(options: BufferOptions, gap: usize, borrowed: &'d [u8]) -> DefaultBuffer<'d> {
+		let mut buffer: DefaultBuffer = options.into();
+		buffer.write_from_slice(&vec![0; 2 * SIZE - gap]).unwrap();
+		buffer.push_slice(borrowed);
+		buffer
+	}
+
+	#[test]
+	fn threshold() {
+		let borrowed = [1; 2000];
+		let buffer = fragmented(BufferOptions::default(), 100, &borrowed);
+		assert_eq!(buffer.fragmentation(), 100, "gaps below the threshold should be kept");
+		assert_eq!(buffer.stats().borrowed_bytes, 2000);
+
+		let buffer = fragmented(BufferOptions::default(), SIZE / 2, &borrowed);
+		assert_eq!(buffer.fragmentation(), 0, "gaps at the threshold should be compacted");
+		assert_eq!(buffer.stats().segments, 2);
+		assert_eq!(buffer.stats().borrowed_bytes, 0);
+		assert_eq!(buffer.count(), 3 * SIZE / 2 + 2000);
+
+		let options = BufferOptions::default().with_fragmentation_threshold(50);
+		let buffer = fragmented(options, 100, &borrowed);
+		assert_eq!(buffer.fragmentation(), 0);
+	}
+
+	#[test]
+	fn policy() {
+		let borrowed = [1; 2000];
+		let never = BufferOptions::default().with_compaction_policy(CompactionPolicy::Never);
+		let mut buffer = fragmented(never, SIZE / 2, &borrowed);
+		assert_eq!(buffer.fragmentation(), SIZE / 2);
+		buffer.compact();
+		assert_eq!(buffer.fragmentation(), 0, "manual compaction should ignore the policy");
+		assert_eq!(buffer.stats().segments, 2);
+
+		let aggressive = BufferOptions::default().with_compaction_policy(CompactionPolicy::Aggressive);
+		let buffer = fragmented(aggressive, 1, &borrowed);
+		assert_eq!(buffer.fragmentation(), 0);
+		assert_eq!(buffer.stats().borrowed_bytes, 1999);
+	}
+
+	#[test]
+	fn copy_compacts_sink() {
+		let mut source = DefaultBuffer::default();
+		source.write_from_slice(&[1; SIZE]).unwrap();
+		let mut sink = DefaultBuffer::default();
+		sink.write_from_slice(&[0; SIZE + 10]).unwrap();
+		source.copy_all_to(&mut sink).unwrap();
+		source.copy_all_to(&mut sink).unwrap();
+		assert_eq!(sink.fragmentation(), 0);
+		assert_eq!(sink.count(), 3 * SIZE + 10);
+		assert_eq!(sink.stats().segments, 4);
+	}
+
+	#[quickcheck]
+	fn preserves_data(chunks: Vec<(Vec<u8>, bool)>) {
+		let options = BufferOptions::default()
+			.with_borrow_threshold(0)
+			.with_compaction_policy(CompactionPolicy::Never);
+		let mut buffer: DefaultBuffer = options.into();
+		for (chunk, borrow) in &chunks {
+			if *borrow {
+				buffer.push_slice(chunk);
+			} else {
+				buffer.write_from_slice(chunk).unwrap();
+			}
+		}
+
+		let data = chunks.iter().flat_map(|(chunk, _)| chunk).copied().collect::<Vec<_>>();
+		buffer.compact();
+		assert_eq!(buffer.fragmentation(), 0);
+		assert_eq!(buffer.count(), data.len());
+		assert_eq!(buffer.as_byte_str(), data[..]);
+	}
+}
+
+mod pod_slice {
+	use pretty_assertions::assert_eq;
+	use quickcheck_macros::quickcheck;
+	use orio::DefaultBuffer;
+	use orio::streams::{BufSink, BufSource};
+
+	macro_rules! gen {
+		($($ty:ident)+) => {
+			paste::paste! {
+				$(
+				#[quickcheck]
+				fn [<$ty _be>](values: Vec<$ty>) {
+					let mut buffer = DefaultBuffer::default();
+					buffer.write_int_slice(&values).unwrap();
+					let expected = values.iter().flat_map(|v| v.to_be_bytes()).collect::<Vec<_>>();
+					assert_eq!(buffer.as_byte_str(), expected[..]);
+					let mut read = vec![0; values.len()];
+					buffer.read_int_slice(&mut read).unwrap();
+					assert_eq!(read, values);
+				}
+
+				#[quickcheck]
+				fn [<$ty _le>](values: Vec<$ty>) {
+					let mut buffer = DefaultBuffer::default();
+					buffer.write_int_slice_le(&values).unwrap();
+					let expected = values.iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<_>>();
+					assert_eq!(buffer.as_byte_str(), expected[..]);
+					let mut read = vec![0; values.len()];
+					buffer.read_int_slice_le(&mut read).unwrap();
+					assert_eq!(read, values);
+				}
+				)+
+			}
+		};
+	}
+
+	gen! { u16 u32 i64 }
+
+	#[quickcheck]
+	fn pod(values: Vec<u32>) {
+		let floats = values.iter().copied().map(f32::from_bits).collect::<Vec<_>>();
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_pod_slice(&floats).unwrap();
+		assert_eq!(buffer.count(), floats.len() * 4);
+		let mut read = vec![0f32; floats.len()];
+		buffer.read_pod_slice(&mut read).unwrap();
+		assert_eq!(read.iter().map(|v| v.to_bits()).collect::<Vec<_>>(), values);
+	}
+
+	#[quickcheck]
+	fn typed(ints: Vec<i16>, floats: Vec<f64>) {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_i16_slice(&ints).unwrap();
+		buffer.write_f64_slice_le(&floats).unwrap();
+		let mut expected = ints.iter().flat_map(|v| v.to_be_bytes()).collect::<Vec<_>>();
+		expected.extend(floats.iter().flat_map(|v| v.to_le_bytes()));
+		assert_eq!(buffer.as_byte_str(), expected[..]);
+
+		let mut read_ints = vec![0; ints.len()];
+		let mut read_floats = vec![0.0; floats.len()];
+		buffer.read_i16_slice_into(&mut read_ints).unwrap();
+		buffer.read_f64_slice_le_into(&mut read_floats).unwrap();
+		assert_eq!(read_ints, ints);
+		assert_eq!(
+			read_floats.iter().map(|v| v.to_bits()).collect::<Vec<_>>(),
+			floats.iter().map(|v| v.to_bits()).collect::<Vec<_>>()
+		);
+	}
+
+	#[test]
+	fn short() {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(&[0; 7]).unwrap();
+		assert!(buffer.read_int_slice(&mut [0u32; 2]).is_err(), "a short read should fail");
+	}
+}
+
+mod shared_byte_str {
+	use pretty_assertions::assert_eq;
+	use quickcheck_macros::quickcheck;
+	use orio::{DefaultBuffer, SharedByteStr};
+	use orio::streams::BufSink;
+
+	fn shared(data: &[u8]) -> SharedByteStr<'static> {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(data).unwrap();
+		buffer.to_shared_byte_str()
+	}
+
+	#[quickcheck]
+	fn outlives_buffer(data: Vec<u8>, start: usize, end: usize) {
+		let end = end % (data.len() + 1);
+		let start = start % (end + 1);
+		let bytes = shared(&data);
+		assert_eq!(bytes.len(), data.len());
+		assert_eq!(bytes, *data.as_slice());
+		let range = bytes.range(start..end);
+		assert_eq!(range, data[start..end]);
+		assert_eq!(range.to_byte_string().as_slice(), &data[start..end]);
+	}
+}
+
+mod checkpoint {
+	use pretty_assertions::assert_eq;
+	use quickcheck_macros::quickcheck;
+	use orio::{Buffer, DefaultBuffer, SIZE};
+	use orio::streams::BufSink;
+
+	#[quickcheck]
+	fn restore(before: Vec<u8>, after: Vec<u8>) {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(&before).unwrap();
+		let checkpoint = buffer.checkpoint();
+		buffer.write_from_slice(&after).unwrap();
+		buffer.restore(checkpoint).unwrap();
+		assert_eq!(buffer, before);
+		buffer.write_from_slice(&after).unwrap();
+		assert_eq!(buffer.count(), before.len() + after.len());
+	}
+
+	#[test]
+	fn restore_across_segments() {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(&[1; SIZE / 2]).unwrap();
+		let checkpoint = buffer.checkpoint();
+		buffer.write_from_slice(&[2; SIZE * 3]).unwrap();
+		buffer.restore(checkpoint).unwrap();
+		assert_eq!(buffer, [1; SIZE / 2]);
+		assert_eq!(buffer.checkpoint(), checkpoint);
+	}
+
+	#[test]
+	fn restore_shared() {
+		let mut buffer = Buffer::from_slice(b"Hello");
+		let checkpoint = buffer.checkpoint();
+		buffer.push_slice(b", world!");
+		buffer.restore(checkpoint).unwrap();
+		assert_eq!(buffer, b"Hello");
+		buffer.write_from_slice(b"!").unwrap();
+		assert_eq!(buffer, b"Hello!");
+	}
+}
+
+mod truncate {
+	use pretty_assertions::assert_eq;
+	use quickcheck_macros::quickcheck;
+	use orio::{Buffer, BufferOptions, DefaultBuffer, SIZE};
+	use orio::streams::BufSink;
+
+	#[quickcheck]
+	fn truncate(data: Vec<u8>, len: usize) {
+		let len = len % (data.len() + 10);
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(&data).unwrap();
+		buffer.truncate(len);
+		assert_eq!(buffer.as_byte_str(), data[..len.min(data.len())]);
+		buffer.write_u8(1).unwrap();
+		assert_eq!(buffer.count(), len.min(data.len()) + 1);
+	}
+
+	#[test]
+	fn discard_back() {
+		let mut buffer = Buffer::from_slice(b"Hello, world!");
+		assert_eq!(buffer.discard_back(8), 8);
+		assert_eq!(buffer, b"Hello");
+		assert_eq!(buffer.discard_back(10), 5);
+		assert!(buffer.is_empty());
+	}
+
+	#[test]
+	fn release() {
+		let mut buffer: DefaultBuffer = BufferOptions::lean().into();
+		buffer.write_from_slice(&[0; SIZE * 3]).unwrap();
+		buffer.truncate(SIZE / 2);
+		assert_eq!(buffer.count(), SIZE / 2);
+		assert_eq!(buffer.capacity(), SIZE, "emptied segments should be released");
+	}
+
+	#[test]
+	fn keep_reserved() {
+		// Reserve a jumbo segment, which differs in size from the emptied segments.
+		let mut buffer: DefaultBuffer = BufferOptions::lean().with_jumbo_threshold(SIZE * 2).into();
+		for _ in 0..3 {
+			buffer.write_from_slice(&[0; SIZE]).unwrap();
+		}
+		buffer.reserve(SIZE * 4).unwrap();
+		let capacity = buffer.capacity();
+		buffer.truncate(SIZE / 2);
+		assert_eq!(buffer.capacity(), capacity - SIZE * 2, "reserved segments should be kept");
+		buffer.truncate(0);
+		assert_eq!(buffer.capacity(), capacity - SIZE * 3);
+	}
+}
+
+mod prepend {
+	use pretty_assertions::assert_eq;
+	use quickcheck_macros::quickcheck;
+	use orio::{BufferOptions, DefaultBuffer, SIZE};
+	use orio::streams::{BufSink, BufSource};
+
+	#[quickcheck]
+	fn push_front_slice(header: Vec<u8>, payload: Vec<u8>, skip: usize) {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(&payload).unwrap();
+		let skip = buffer.skip(skip % (payload.len() + 1));
+		buffer.push_front_slice(&header).unwrap();
+		let mut expected = header;
+		expected.extend_from_slice(&payload[skip..]);
+		assert_eq!(buffer.count(), expected.len());
+		assert_eq!(buffer.as_byte_str(), expected[..]);
+	}
+
+	#[test]
+	fn across_segments() {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(&[2; SIZE / 2]).unwrap();
+		buffer.push_front_slice(&[1; SIZE * 2]).unwrap();
+		buffer.write_front_u8(0).unwrap();
+		let mut expected = vec![0];
+		expected.extend_from_slice(&[1; SIZE * 2]);
+		expected.extend_from_slice(&[2; SIZE / 2]);
+		assert_eq!(buffer.as_byte_str(), expected[..]);
+	}
+
+	#[test]
+	fn length_prefix() {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_utf8("payload").unwrap();
+		buffer.write_front_u32(buffer.count() as u32).unwrap();
+		buffer.write_front_u16_le(0xABCD).unwrap();
+		assert_eq!(buffer.read_u16_le().unwrap(), 0xABCD);
+		assert_eq!(buffer.read_u32().unwrap(), 7);
+		assert_eq!(buffer, b"payload");
+	}
+
+	// Borrowing the shared pool would deadlock rather than fail.
+	#[test]
+	#[cfg(not(feature = "shared-pool"))]
+	fn allocation() {
+		use orio::pool::{pool, Pool};
+
+		let pool = pool();
+		let _borrow = pool.try_borrow().unwrap();
+		let mut buffer = DefaultBuffer::default();
+		buffer.push_front_slice(b"header").unwrap();
+		assert_eq!(buffer, b"header", "a segment should be allocated");
+		let mut buffer: DefaultBuffer = BufferOptions::default().never_allocate().into();
+		assert!(buffer.push_front_slice(b"header").is_err());
+	}
+
+	#[test]
+	fn capacity() {
+		let mut buffer: DefaultBuffer = BufferOptions::default().with_max_capacity(4).into();
+		buffer.write_from_slice(b"ab").unwrap();
+		assert!(buffer.push_front_slice(b"abc").is_err(), "prepending should be limited by capacity");
+		buffer.push_front_slice(b"cd").unwrap();
+		assert_eq!(buffer, b"cdab");
+	}
+}
+
+mod slot {
+	use pretty_assertions::assert_eq;
+	use quickcheck_macros::quickcheck;
+	use orio::{Buffer, DefaultBuffer, SIZE};
+	use orio::streams::{BufSink, BufSource};
+
+	#[quickcheck]
+	fn length_prefix(prefix: Vec<u8>, payload: Vec<u8>) {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(&prefix).unwrap();
+		let slot = buffer.reserve_slot(4).unwrap();
+		assert_eq!(slot.range(), prefix.len()..prefix.len() + 4);
+		buffer.write_from_slice(&payload).unwrap();
+		slot.write_u32(&mut buffer, payload.len() as u32).unwrap();
+		assert_eq!(buffer.skip(prefix.len()), prefix.len());
+		assert_eq!(buffer.read_u32().unwrap(), payload.len() as u32);
+		assert_eq!(buffer.as_byte_str(), payload[..]);
+	}
+
+	#[test]
+	fn across_segments() {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(&[0; SIZE - 2]).unwrap();
+		let slot = buffer.reserve_slot(8).unwrap();
+		buffer.write_from_slice(&[0; SIZE]).unwrap();
+		slot.write_u64_le(&mut buffer, 0x0102_0304_0506_0708).unwrap();
+		assert_eq!(buffer.read_int_le_at::<u64>(SIZE - 2).unwrap(), 0x0102_0304_0506_0708);
+	}
+
+	#[test]
+	fn write_at() {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(b"Hello, world!").unwrap();
+		buffer.write_slice_at(7, b"there").unwrap();
+		buffer.write_u8_at(0, b'J').unwrap();
+		assert_eq!(buffer, b"Jello, there!");
+	}
+
+	#[quickcheck]
+	fn write_shared(data: Vec<u8>, pos: usize, value: Vec<u8>) {
+		if data.is_empty() { return }
+		let pos = pos % data.len();
+		let value = &value[..value.len().min(data.len() - pos)];
+		let source = Buffer::from_slice(&data);
+		let mut buffer = DefaultBuffer::default();
+		source.copy_all_to(&mut buffer).unwrap();
+		buffer.write_slice_at(pos, value).unwrap();
+
+		let mut expected = data.clone();
+		expected[pos..][..value.len()].copy_from_slice(value);
+		assert_eq!(buffer.as_byte_str(), expected[..]);
+		assert_eq!(source, &data, "shared data should not be modified");
+	}
+
+	#[test]
+	fn index() {
+		let data = [1; SIZE + 10];
+		let mut buffer = Buffer::from_slice(&data);
+		assert_eq!(buffer[SIZE], 1);
+		buffer[SIZE] = 2;
+		*buffer.get_mut(0).unwrap() = 3;
+		assert_eq!(buffer.get(SIZE), Some(2));
+		assert_eq!(buffer[0], 3);
+		assert_eq!(buffer.get_mut(SIZE + 10), None);
+		assert_eq!(data, [1; SIZE + 10], "borrowed data should not be modified");
+	}
+
+	#[test]
+	#[should_panic]
+	fn write_out_of_bounds() {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(b"Hello").unwrap();
+		let _ = buffer.write_int_at(2, 0u32);
+	}
+
+	#[test]
+	#[should_panic]
+	fn slot_length_mismatch() {
+		let mut buffer = DefaultBuffer::default();
+		let slot = buffer.reserve_slot(2).unwrap();
+		let _ = slot.write_u32(&mut buffer, 0);
+	}
+}
+
+mod exclusive {
+	use pretty_assertions::assert_eq;
+	use quickcheck_macros::quickcheck;
+	use orio::{Buffer, BufferOptions, DefaultBuffer, SIZE};
+	use orio::streams::BufSink;
+
+	#[quickcheck]
+	fn make_exclusive(a: Vec<u8>, b: Vec<u8>, c: Vec<u8>) {
+		let mut shared = DefaultBuffer::default();
+		shared.write_from_slice(&c).unwrap();
+
+		let mut buffer = Buffer::from_slice(&a);
+		buffer.write_from_slice(&b).unwrap();
+		shared.copy_all_to(&mut buffer).unwrap();
+		buffer.make_exclusive().unwrap();
+
+		let stats = buffer.stats();
+		assert_eq!(stats.shared_bytes + stats.borrowed_bytes, 0);
+		let expected = [&a[..], &b, &c].concat();
+		assert_eq!(buffer.as_byte_str(), expected[..]);
+		assert_eq!(shared.as_byte_str(), c[..], "shared source should be unchanged");
+	}
+
+	#[test]
+	fn write_after() {
+		let data = [1; SIZE * 2];
+		let mut buffer: DefaultBuffer = BufferOptions::default().never_allocate().into();
+		buffer.push_slice(&data);
+		buffer.make_exclusive().unwrap();
+		buffer.write_slice_at(SIZE - 1, &[2, 2]).unwrap();
+		assert_eq!(buffer.stats().owned_bytes, SIZE * 2);
+		assert_eq!(data, [1; SIZE * 2], "borrowed data should not be modified");
+		assert_eq!(buffer.get(SIZE), Some(2));
+	}
+	// Borrowing the shared pool would deadlock rather than fail.
+	#[test]
+	#[cfg(not(feature = "shared-pool"))]
+	fn write_never_allocate() {
+		use orio::pool::{pool, Pool};
+
+		let data = [1; SIZE * 2];
+		let mut buffer: DefaultBuffer = BufferOptions::default().never_allocate().into();
+		buffer.push_slice(&data);
+		// With the pool borrowed, the borrowed segment can't be copied.
+		let pool = pool();
+		let _borrow = pool.try_borrow().unwrap();
+		assert!(buffer.write_u8_at(0, 2).is_err());
+		assert_eq!(buffer.get_mut(0), None);
+		assert_eq!(buffer, data);
+	}
+}
+
+mod compare {
+	use std::collections::HashSet;
+	use std::hash::{BuildHasher, RandomState};
+	use pretty_assertions::assert_eq;
+	use quickcheck_macros::quickcheck;
+	use orio::{ByteStr, ByteString, DefaultBuffer};
+	use orio::streams::BufSink;
+
+	fn buffer(data: &[u8]) -> DefaultBuffer<'static> {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(data).unwrap();
+		buffer
+	}
+
+	#[quickcheck]
+	fn eq_cmp(a: Vec<u8>, b: Vec<u8>) {
+		let buf_a = buffer(&a);
+		let buf_b = buffer(&b);
+		let str_b = ByteStr::from(&b[..]);
+		let string_b = ByteString::from(&b[..]);
+		let expected = a.cmp(&b);
+
+		assert_eq!(buf_a == buf_b, a == b);
+		assert_eq!(buf_a == str_b, a == b);
+		assert_eq!(str_b == buf_a, a == b);
+		assert_eq!(string_b == buf_a, a == b);
+		assert_eq!(buf_a.cmp(&buf_b), expected);
+		assert_eq!(buf_a.partial_cmp(&str_b), Some(expected));
+		assert_eq!(buf_a.partial_cmp(&string_b), Some(expected));
+		assert_eq!(buf_a.partial_cmp(&b[..]), Some(expected));
+		assert_eq!(str_b.partial_cmp(&buf_a), Some(expected.reverse()));
+		assert_eq!(string_b.partial_cmp(&buf_a), Some(expected.reverse()));
+		assert_eq!(string_b.partial_cmp(&ByteStr::from(&a[..])), Some(expected.reverse()));
+	}
+
+	#[quickcheck]
+	fn hash(data: Vec<u8>, split: usize) {
+		let split = split % (data.len() + 1);
+		let mut split_buffer = DefaultBuffer::default();
+		split_buffer.push_slice(&data[..split]);
+		split_buffer.push_slice(&data[split..]);
+		let state = RandomState::new();
+		let hash = state.hash_one(buffer(&data));
+		assert_eq!(state.hash_one(&split_buffer), hash, "segmentation shouldn't affect the hash");
+		assert_eq!(state.hash_one(ByteStr::from(&data[..])), hash, "hash should be consistent with ByteStr");
+		assert_eq!(state.hash_one(&data[..]), hash, "hash should be consistent with slices");
+		assert_eq!(state.hash_one(ByteString::from(&data[..])), hash, "hash should be consistent with ByteString");
+	}
+
+	#[test]
+	// The pool is mutable, but isn't hashed.
+	#[allow(clippy::mutable_key_type)]
+	fn hash_set() {
+		let mut set = HashSet::new();
+		assert!(set.insert(buffer(b"Hello")));
+		assert!(!set.insert(buffer(b"Hello")));
+		assert!(set.insert(buffer(b"world")));
+	}
+
+	fn split_buffer(data: &[u8], split: usize) -> DefaultBuffer<'_> {
+		let split = split % (data.len() + 1);
+		let mut buffer = DefaultBuffer::default();
+		buffer.push_slice(&data[..split]);
+		buffer.push_slice(&data[split..]);
+		buffer
+	}
+
+	#[quickcheck]
+	fn common_prefix_suffix(shared: Vec<u8>, a: Vec<u8>, b: Vec<u8>, split_a: usize, split_b: usize) {
+		let x = [&shared[..], &a, &shared].concat();
+		let y = [&shared[..], &b, &shared].concat();
+		let prefix = x.iter().zip(&y).take_while(|(x, y)| x == y).count();
+		let suffix = x.iter().rev().zip(y.iter().rev()).take_while(|(x, y)| x == y).count();
+		let mismatch = (x != y).then_some(prefix);
+
+		let buf_x = split_buffer(&x, split_a);
+		let buf_y = split_buffer(&y, split_b);
+		assert_eq!(buf_x.common_prefix_len(&buf_y), prefix);
+		assert_eq!(buf_x.common_suffix_len(&buf_y), suffix);
+		assert_eq!(buf_x.first_mismatch(&buf_y), mismatch);
+
+		let str_x = buf_x.as_byte_str();
+		let str_y = buf_y.as_byte_str();
+		assert_eq!(str_x.common_prefix(&str_y), ByteStr::from(&x[..prefix]));
+		assert_eq!(str_x.common_suffix(&str_y), ByteStr::from(&x[x.len() - suffix..]));
+		assert_eq!(str_x.first_mismatch(&str_y), mismatch);
+	}
+
+	#[quickcheck]
+	fn starts_ends_with(data: Vec<u8>, split: usize, len: usize) {
+		let buffer = split_buffer(&data, split);
+		let len = len % (data.len() + 2);
+		if len > data.len() {
+			let mut longer = data.clone();
+			longer.push(0);
+			assert!(!buffer.starts_with(&longer));
+			assert!(!buffer.ends_with(&longer));
+			return
+		}
+
+		let (prefix, suffix) = (&data[..len], &data[data.len() - len..]);
+		assert!(buffer.starts_with(prefix));
+		assert!(buffer.ends_with(suffix));
+		if let Some((&last, rest)) = prefix.split_last() {
+			let prefix = [rest, &[!last]].concat();
+			assert!(!buffer.starts_with(&prefix));
+		}
+		if let Some((&first, rest)) = suffix.split_first() {
+			let suffix = [&[!first], rest].concat();
+			assert!(!buffer.ends_with(&suffix));
+		}
+	}
+
+	#[test]
+	fn magic_numbers() {
+		let mut buffer = DefaultBuffer::default();
+		buffer.push_slice(b"\x89P");
+		buffer.push_slice(b"NG\r\n\x1a\n...IEND");
+		assert!(buffer.starts_with(b"\x89PNG\r\n\x1a\n"));
+		assert!(buffer.ends_with(b"IEND"));
+		assert!(!buffer.starts_with(b"GIF8"));
+		assert!(!buffer.ends_with(b"\x89PNG"));
+	}
+
+	#[test]
+	fn first_mismatch() {
+		assert_eq!(buffer(b"Hello").first_mismatch(&buffer(b"Hello")), None);
+		assert_eq!(buffer(b"Hello").first_mismatch(&buffer(b"Help")), Some(3));
+		assert_eq!(buffer(b"Hello").first_mismatch(&buffer(b"Hello world")), Some(5));
+		assert_eq!(buffer(b"").first_mismatch(&buffer(b"")), None);
+	}
+}
+
+mod send_buffer {
+	use std::thread;
+	use pretty_assertions::assert_eq;
+	use orio::{Buffer, DefaultBuffer, SIZE};
+	use orio::streams::BufSink;
+
+	#[test]
+	fn send_shared() {
+		let data: Vec<u8> = (0..SIZE + 10).map(|i| i as u8).collect();
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_from_slice(&data).unwrap();
+		let shared = buffer.to_shared_byte_str();
+		let send = buffer.into_send();
+		assert_eq!(send.count(), data.len());
+
+		let received = thread::spawn(move || {
+			let buffer: DefaultBuffer = send.into();
+			buffer.into_bytes()
+		}).join().unwrap();
+		assert_eq!(received, data);
+		assert_eq!(shared, *data.as_slice(), "shared data should be unaffected");
+	}
+
+	#[test]
+	fn send_borrowed() {
+		let data = b"Hello, world!";
+		let send = Buffer::from_slice(data).into_send();
+		let received = thread::scope(|scope|
+			scope.spawn(move || {
+				let buffer: DefaultBuffer = send.into();
+				buffer.into_bytes()
+			}).join().unwrap()
+		);
+		assert_eq!(received, data);
+	}
+
+	#[cfg(feature = "shared-pool")]
+	#[test]
+	fn send_buffer() {
+		fn assert_send_sync<T: Send + Sync>(value: T) -> T { value }
+
+		let data: Vec<u8> = (0..SIZE + 10).map(|i| i as u8).collect();
+		let mut buffer = assert_send_sync(DefaultBuffer::default());
+		buffer.write_from_slice(&data).unwrap();
+		let mut shared = DefaultBuffer::default();
+		buffer.copy_to(&mut shared, data.len()).unwrap();
+
+		let received = thread::spawn(move || buffer.into_bytes()).join().unwrap();
+		assert_eq!(received, data);
+		assert_eq!(shared, data, "shared data should be unaffected");
+
+		let str = assert_send_sync(shared.to_shared_byte_str());
+		let received = thread::spawn(move || str.len()).join().unwrap();
+		assert_eq!(received, data.len());
+	}
+}
+
+#[cfg(feature = "quickcheck")]
+mod fixture {
+	use pretty_assertions::assert_eq;
+	use quickcheck_macros::quickcheck;
+	use orio::ByteString;
+	use orio::fixture::BufferFixture;
+	use orio::streams::{BufSink, BufSource};
+
+	#[quickcheck]
+	fn fixture_buffer(fixture: BufferFixture) {
+		let data = fixture.to_vec();
+		let mut buffer = fixture.to_buffer();
+		assert_eq!(buffer.count(), fixture.len());
+		assert_eq!(fixture.as_byte_str(), data[..]);
+		let mut read = vec![0; data.len()];
+		buffer.read_slice_exact(&mut read).unwrap();
+		assert_eq!(read, data);
+
+		// Writing forks shared segments, leaving the fixture's data intact.
+		let mut buffer = fixture.to_buffer();
+		buffer.write_from_slice(b"end").unwrap();
+		let mut read = Vec::new();
+		buffer.read_to_end_vec(&mut read).unwrap();
+		assert_eq!(read, [&data[..], b"end"].concat());
+	}
+
+	#[quickcheck]
+	fn fixture_byte_string(str: ByteString) {
+		assert_eq!(str.as_byte_str().to_byte_string(), str);
+	}
 }
 
 use pretty_assertions::assert_str_eq;
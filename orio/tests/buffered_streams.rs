@@ -4,11 +4,13 @@
 
 mod dataset;
 
+use std::io;
 use std::mem::MaybeUninit;
 use pretty_assertions::{assert_eq, assert_str_eq};
-use orio::{Buffer, BufferResult, DefaultBuffer, SIZE};
+use orio::{Buffer, BufferResult, DefaultBuffer, ErrorKind, SIZE};
+use orio::pattern::{LineTerminator, Matcher, MatchStep, SliceMatcher};
 use orio::pool::Pool;
-use orio::streams::{BufSource, Result, Sink, SourceExt, SinkExt, Stream, BufSink, FileSource};
+use orio::streams::{BufSource, Result, Sink, SourceExt, SinkExt, Stream, BufSink, FileSource, ReaderSource, ReadAhead, BufStream};
 use crate::dataset::{Data, DATASET};
 
 const DATA: Data = DATASET.fields_c;
@@ -32,6 +34,115 @@ fn read() -> Result {
 	Ok(())
 }
 
+/// A reader returning at most `chunk_len` bytes per read.
+struct ChunkedReader<'a> {
+	data: &'a [u8],
+	chunk_len: usize,
+}
+
+impl io::Read for ChunkedReader<'_> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		let len = self.chunk_len.min(self.data.len()).min(buf.len());
+		buf[..len].copy_from_slice(&self.data[..len]);
+		self.data = &self.data[len..];
+		Ok(len)
+	}
+}
+
+#[test]
+fn read_utf8_split_chars() -> Result {
+	let text = "aé€😀".repeat(SIZE / 4);
+	for chunk_len in [1, 2, 3, 5, SIZE - 1] {
+		let reader = || ReaderSource::from(ChunkedReader { data: text.as_bytes(), chunk_len }).buffered();
+		let mut string = String::new();
+		assert_str_eq!(reader().read_utf8_to_end(&mut string)?, text);
+		string.clear();
+		assert_str_eq!(reader().read_utf8(&mut string, text.len())?, text);
+		string.clear();
+		assert_str_eq!(reader().read_utf8_exact_count(&mut string, 11)?, &text[..11]);
+	}
+
+	// End-of-stream within a character is an error.
+	let data = [b'a', 0xF0, 0x9F];
+	let mut source = ReaderSource::from(ChunkedReader { data: &data, chunk_len: 1 }).buffered();
+	let mut string = String::new();
+	assert_eq!(source.read_utf8_to_end(&mut string).unwrap_err().kind(), ErrorKind::Utf8);
+	assert_eq!(string, "a");
+	Ok(())
+}
+
+/// A matcher counting the bytes passed to it.
+struct CountingMatcher<'p>(SliceMatcher<'p>, usize);
+
+impl Matcher for CountingMatcher<'_> {
+	fn next(&mut self, haystack: &[u8], offset: usize) -> Option<MatchStep> {
+		self.1 += haystack.len();
+		self.0.next(haystack, offset)
+	}
+
+	fn end(&mut self) -> Option<MatchStep> { self.0.end() }
+	fn reset(&mut self) { self.0.reset() }
+}
+
+#[test]
+fn read_until_chunked() -> Result {
+	let text = format!("{}\r\nné€ds", "a".repeat(SIZE * 2));
+	let reader = || ReaderSource::from(ChunkedReader { data: text.as_bytes(), chunk_len: 1 }).buffered();
+
+	let mut matcher = CountingMatcher(SliceMatcher::from("\r\n"), 0);
+	let mut string = String::new();
+	let r#match = reader().read_utf8_until_with(&mut string, &mut matcher)?;
+	assert!(r#match.found);
+	assert_eq!(string.len(), SIZE * 2);
+	// Rescanning from the start after each read would scan quadratically many bytes.
+	assert!(matcher.1 < text.len() * 2, "scanned {} bytes", matcher.1);
+
+	// Greedy and char-aligned matches split across reads.
+	let mut source = reader();
+	string.clear();
+	source.read_utf8_until(&mut string, LineTerminator)?;
+	string.clear();
+	assert!(source.read_utf8_until(&mut string, '€')?.found);
+	assert_str_eq!(string, "né");
+	string.clear();
+	source.read_utf8_to_end(&mut string)?;
+	assert_str_eq!(string, "ds");
+	Ok(())
+}
+
+#[test]
+fn adaptive_read_ahead() -> Result {
+	let data = vec![0; SIZE * 3 + 10];
+	let mut source = ReaderSource::from(data.as_slice()).buffered();
+	assert_eq!(source.read_ahead(), ReadAhead::default());
+	source.set_read_ahead(ReadAhead::Adaptive { min: SIZE, max: SIZE * 2 });
+	assert_eq!(source.read_ahead_len(), SIZE);
+
+	let mut counts = Vec::new();
+	let mut lens = Vec::new();
+	while source.request(1)? {
+		counts.push(source.buf().count());
+		lens.push(source.read_ahead_len());
+		source.buf_mut().clear();
+	}
+	// Full reads grow the read-ahead up to the maximum, the short read at the end
+	// shrinks it.
+	assert_eq!(counts, [SIZE, SIZE * 2, 10]);
+	assert_eq!(lens, [SIZE * 2, SIZE * 2, SIZE]);
+	Ok(())
+}
+
+#[test]
+fn huge_read_ahead() -> Result {
+	let data = vec![0; 10];
+	let mut source = ReaderSource::from(data.as_slice()).buffered();
+	source.set_read_ahead(ReadAhead::Fixed(usize::MAX));
+	assert!(source.request(1)?);
+	assert_eq!(source.buf().count(), 10);
+	assert!(!source.request(usize::MAX)?);
+	Ok(())
+}
+
 #[derive(Default)]
 struct VecSink {
 	vec: Vec<u8>
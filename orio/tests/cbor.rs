@@ -0,0 +1,149 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::io;
+use pretty_assertions::assert_eq;
+use quickcheck_macros::quickcheck;
+use orio::{Buffer, DefaultBuffer, ErrorKind, SIZE};
+use orio::streams::cbor::{
+	write_array_len, write_break, write_bytes, write_indefinite, write_int, write_map_len,
+	write_tag, write_text, write_uint, CborReader, Header, MajorType
+};
+
+fn reader<'d>(data: &'d [u8]) -> CborReader<'d, DefaultBuffer<'d>, SIZE> {
+	CborReader::new(Buffer::from_slice(data))
+}
+
+// Examples from RFC 8949 appendix A.
+#[test]
+fn ints() {
+	for (value, data) in [
+		(0, &[0x00][..]),
+		(23, &[0x17]),
+		(24, &[0x18, 0x18]),
+		(1000, &[0x19, 0x03, 0xe8]),
+		(1000000, &[0x1a, 0x00, 0x0f, 0x42, 0x40]),
+		(-1, &[0x20]),
+		(-100, &[0x38, 0x63]),
+		(-1000, &[0x39, 0x03, 0xe7]),
+	] {
+		let mut sink = DefaultBuffer::default();
+		write_int(&mut sink, value).unwrap();
+		assert_eq!(sink, data);
+		assert_eq!(CborReader::<_, SIZE>::new(sink).read_int().unwrap(), value);
+	}
+
+	let data = [0x1b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+	let mut sink = DefaultBuffer::default();
+	write_uint(&mut sink, u64::MAX).unwrap();
+	assert_eq!(sink, data.as_slice());
+	assert_eq!(reader(&data).read_uint().unwrap(), u64::MAX);
+	assert_eq!(
+		reader(&data).read_int().unwrap_err().kind(),
+		ErrorKind::Io(io::ErrorKind::InvalidData)
+	);
+}
+
+#[quickcheck]
+fn int_round_trip(value: i64) {
+	let mut sink = DefaultBuffer::default();
+	write_int(&mut sink, value).unwrap();
+	assert_eq!(CborReader::<_, SIZE>::new(sink).read_int().unwrap(), value);
+}
+
+#[test]
+fn strings() {
+	let mut sink = DefaultBuffer::default();
+	write_bytes(&mut sink, &[1, 2, 3, 4]).unwrap();
+	write_text(&mut sink, "\u{6c34}").unwrap();
+	assert_eq!(sink, [0x44, 0x01, 0x02, 0x03, 0x04, 0x63, 0xe6, 0xb0, 0xb4].as_slice());
+
+	let mut reader = CborReader::<_, SIZE>::new(sink);
+	assert_eq!(reader.read_bytes().unwrap(), [1, 2, 3, 4][..]);
+	assert_eq!(reader.read_text().unwrap().cached_utf8(), Some("\u{6c34}"));
+	assert_eq!(reader.peek_major_type().unwrap(), None);
+}
+
+#[test]
+fn across_segments() {
+	let text = "x".repeat(SIZE * 2 + 5);
+	let mut sink = DefaultBuffer::default();
+	write_bytes(&mut sink, &[0xAB; 10]).unwrap();
+	write_text(&mut sink, &text).unwrap();
+	write_uint(&mut sink, 1).unwrap();
+
+	let mut reader = CborReader::<_, SIZE>::new(sink);
+	assert_eq!(reader.read_bytes().unwrap(), [0xAB; 10][..]);
+	assert_eq!(reader.read_text().unwrap().utf8().unwrap(), text);
+	assert_eq!(reader.read_uint().unwrap(), 1);
+	assert!(reader.into_inner().unwrap().is_empty());
+}
+
+#[test]
+fn containers() {
+	// {"a": 1, "b": [2, 3]}
+	let data = [0xa2, 0x61, 0x61, 0x01, 0x61, 0x62, 0x82, 0x02, 0x03];
+	let mut sink = DefaultBuffer::default();
+	write_map_len(&mut sink, 2).unwrap();
+	write_text(&mut sink, "a").unwrap();
+	write_uint(&mut sink, 1).unwrap();
+	write_text(&mut sink, "b").unwrap();
+	write_array_len(&mut sink, 2).unwrap();
+	write_uint(&mut sink, 2).unwrap();
+	write_uint(&mut sink, 3).unwrap();
+	assert_eq!(sink, data.as_slice());
+
+	let mut reader = reader(&data);
+	assert_eq!(reader.read_map_len().unwrap(), Some(2));
+	assert_eq!(reader.read_text().unwrap(), *"a");
+	assert_eq!(reader.read_uint().unwrap(), 1);
+	assert_eq!(reader.read_text().unwrap(), *"b");
+	assert_eq!(reader.read_array_len().unwrap(), Some(2));
+	assert_eq!(reader.read_uint().unwrap(), 2);
+	assert_eq!(reader.read_uint().unwrap(), 3);
+}
+
+#[test]
+fn indefinite_and_tags() {
+	// 1(1363896240), then [_ 1, 2]
+	let data = [0xc1, 0x1a, 0x51, 0x4b, 0x67, 0xb0, 0x9f, 0x01, 0x02, 0xff];
+	let mut sink = DefaultBuffer::default();
+	write_tag(&mut sink, 1).unwrap();
+	write_uint(&mut sink, 1363896240).unwrap();
+	write_indefinite(&mut sink, MajorType::Array).unwrap();
+	write_uint(&mut sink, 1).unwrap();
+	write_uint(&mut sink, 2).unwrap();
+	write_break(&mut sink).unwrap();
+	assert_eq!(sink, data.as_slice());
+
+	let mut reader = reader(&data);
+	assert_eq!(reader.read_tag().unwrap(), 1);
+	assert_eq!(reader.read_uint().unwrap(), 1363896240);
+	assert_eq!(reader.read_array_len().unwrap(), None);
+	assert_eq!(reader.read_uint().unwrap(), 1);
+	assert_eq!(reader.read_uint().unwrap(), 2);
+	assert_eq!(reader.read_header().unwrap(), Header { major_type: MajorType::Simple, argument: None });
+}
+
+#[test]
+fn invalid() {
+	let invalid_data = ErrorKind::Io(io::ErrorKind::InvalidData);
+	assert_eq!(reader(&[0x1c]).read_header().unwrap_err().kind(), invalid_data);
+	assert_eq!(reader(&[0x1f]).read_header().unwrap_err().kind(), invalid_data);
+	assert_eq!(reader(&[0x5f]).read_bytes().unwrap_err().kind(), invalid_data);
+	assert_eq!(reader(&[0x62, 0xff, 0xfe]).read_text().unwrap_err().kind(), invalid_data);
+	assert_eq!(reader(&[0x44, 0x01]).read_bytes().unwrap_err().kind(), ErrorKind::Eos);
+	assert_eq!(reader(&[0x19, 0x01]).read_uint().unwrap_err().kind(), ErrorKind::Eos);
+	assert_eq!(reader(&[0x44, 0, 0, 0, 0]).with_max_len(3).read_bytes().unwrap_err().kind(), invalid_data);
+
+	// Items of the wrong type aren't consumed.
+	let mut reader = reader(&[0x61, 0x61]);
+	assert_eq!(reader.read_uint().unwrap_err().kind(), invalid_data);
+	assert_eq!(reader.read_text().unwrap(), *"a");
+
+	// Invalid text strings aren't consumed, and can be read as bytes.
+	let mut reader = crate::reader(&[0x62, 0xff, 0xfe, 0x01]);
+	assert_eq!(reader.read_text().unwrap_err().kind(), invalid_data);
+	assert_eq!(reader.read_text().unwrap_err().kind(), invalid_data);
+	assert_eq!(reader.read_header().unwrap(), Header { major_type: MajorType::Text, argument: Some(2) });
+	assert_eq!(reader.into_inner().unwrap(), [0xff, 0xfe, 0x01].as_slice());
+}
@@ -0,0 +1,102 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use pretty_assertions::assert_eq;
+use orio::{Buffer, BufferResult, DefaultBuffer, SIZE, StreamResult};
+use orio::pool::Pool;
+use orio::streams::{BufSink, ChunkingSource, Faults, FaultySource, Source, Stream};
+
+const MIN: usize = 256;
+const AVG: usize = 1024;
+const MAX: usize = 4096;
+
+fn data(len: usize) -> Vec<u8> {
+	let mut state = 0x2545F4914F6CDD1Du64;
+	(0..len).map(|_| {
+		state ^= state << 13;
+		state ^= state >> 7;
+		state ^= state << 17;
+		state as u8
+	}).collect()
+}
+
+fn chunks<'d>(source: impl Source<'d, SIZE>) -> Vec<Vec<u8>> {
+	let mut source = ChunkingSource::<_, SIZE>::with_sizes(source, MIN, AVG, MAX);
+	let mut chunks = Vec::new();
+	while let Some(chunk) = source.next_chunk().unwrap() {
+		chunks.push(chunk.into_bytes());
+	}
+	chunks
+}
+
+#[test]
+fn chunk_sizes() {
+	let data = data(100_000);
+	let chunks = chunks(Buffer::from_slice(&data));
+	let (last, rest) = chunks.split_last().unwrap();
+	assert!(last.len() <= MAX);
+	for chunk in rest {
+		assert!((MIN..=MAX).contains(&chunk.len()), "chunk size {} out of bounds", chunk.len());
+	}
+	assert!(chunks.len() > 100_000 / MAX, "boundaries should be found before the maximum size");
+	assert_eq!(chunks.concat(), data);
+}
+
+#[test]
+fn stable_across_reads() {
+	let data = data(50_000);
+	let source = FaultySource::new(Buffer::from_slice(&data), Faults::default().max_chunk(37));
+	assert_eq!(chunks(source), chunks(Buffer::from_slice(&data)));
+}
+
+/// Reads one byte at a time, returning nothing on every other read.
+struct Trickle {
+	data: Vec<u8>,
+	pos: usize,
+	stall: bool,
+}
+
+impl<const N: usize> Stream<N> for Trickle {
+	fn is_closed(&self) -> bool { false }
+
+	fn close(&mut self) -> StreamResult { Ok(()) }
+}
+
+impl<'d, const N: usize> Source<'d, N> for Trickle {
+	fn is_eos(&self) -> bool {
+		self.pos == self.data.len()
+	}
+
+	fn fill(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		self.stall = !self.stall;
+		if self.stall || count == 0 || self.pos == self.data.len() {
+			return Ok(0)
+		}
+		sink.write_u8(self.data[self.pos])?;
+		self.pos += 1;
+		Ok(1)
+	}
+}
+
+#[test]
+fn stable_across_empty_reads() {
+	let data = data(20_000);
+	let source = Trickle { data: data.clone(), pos: 0, stall: false };
+	assert_eq!(chunks(source), chunks(Buffer::from_slice(&data)));
+}
+
+#[test]
+fn stable_after_insertion() {
+	let data = data(50_000);
+	let mut shifted = b"inserted".to_vec();
+	shifted.extend_from_slice(&data);
+	let original = chunks(Buffer::from_slice(&data));
+	let shifted = chunks(Buffer::from_slice(&shifted));
+	let shared = original.iter().filter(|chunk| shifted.contains(chunk)).count();
+	assert!(shared + 2 >= original.len(), "only chunks near the insertion should change");
+}
+
+#[test]
+fn empty() {
+	let mut source = ChunkingSource::<_, SIZE>::new(DefaultBuffer::default());
+	assert!(source.next_chunk().unwrap().is_none());
+}
@@ -0,0 +1,152 @@
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(all(feature = "aead", feature = "cipher"))]
+
+use std::io;
+use aes_gcm::{Aes256Gcm, KeyInit};
+use aead::stream::{DecryptorBE32, EncryptorBE32};
+use chacha20::ChaCha20;
+use chacha20::cipher::KeyIvInit;
+use chacha20poly1305::ChaCha20Poly1305;
+use pretty_assertions::assert_eq;
+use orio::{Buffer, BufferResult, DefaultBuffer, ErrorKind, SIZE, StreamResult};
+use orio::pool::Pool;
+use orio::streams::{CipherSink, CipherSource, Sink, Source, Stream};
+
+const KEY: [u8; 32] = [0x42; 32];
+const STREAM_NONCE: [u8; 7] = [0x24; 7];
+
+fn data() -> Vec<u8> {
+	(0..SIZE * 3 + 100).map(|i| i as u8).collect()
+}
+
+fn encrypt(data: &[u8]) -> DefaultBuffer<'_> {
+	let aead = ChaCha20Poly1305::new(&KEY.into());
+	let encryptor = EncryptorBE32::from_aead(aead, &STREAM_NONCE.into());
+	let mut source = Buffer::from_slice(data);
+	let mut sink = CipherSink::encrypt(encryptor, DefaultBuffer::default());
+	sink.drain_all(&mut source).unwrap();
+	sink.finish().unwrap()
+}
+
+fn decrypt(ciphertext: DefaultBuffer) -> BufferResult<DefaultBuffer> {
+	let aead = ChaCha20Poly1305::new(&KEY.into());
+	let decryptor = DecryptorBE32::from_aead(aead, &STREAM_NONCE.into());
+	let mut source = CipherSource::decrypt(decryptor, ciphertext);
+	let mut plaintext = DefaultBuffer::default();
+	while !source.is_eos() {
+		source.fill(&mut plaintext, SIZE)?;
+	}
+	Ok(plaintext)
+}
+
+#[test]
+fn aead_round_trip() {
+	let data = data();
+	let ciphertext = encrypt(&data);
+	assert_eq!(ciphertext.count(), data.len() + 4 * 16, "each chunk should have a tag");
+	assert_eq!(decrypt(ciphertext).unwrap(), data);
+}
+
+#[test]
+fn aead_source_matches_sink() {
+	let data = data();
+	let aead = Aes256Gcm::new(&KEY.into());
+	let encryptor = EncryptorBE32::from_aead(aead.clone(), &STREAM_NONCE.into());
+	let mut source = CipherSource::encrypt(encryptor, Buffer::from_slice(&data));
+	let mut from_source = DefaultBuffer::default();
+	while !source.is_eos() {
+		source.fill(&mut from_source, 1000).unwrap();
+	}
+
+	let encryptor = EncryptorBE32::from_aead(aead, &STREAM_NONCE.into());
+	let mut sink = CipherSink::encrypt(encryptor, DefaultBuffer::default());
+	for chunk in data.chunks(1000) {
+		sink.drain_all(&mut Buffer::from_slice(chunk)).unwrap();
+	}
+	assert_eq!(sink.finish().unwrap(), from_source.as_byte_str());
+}
+
+#[test]
+fn aead_empty() {
+	let ciphertext = encrypt(&[]);
+	assert_eq!(ciphertext.count(), 16, "the last chunk should be sealed");
+	assert!(decrypt(ciphertext).unwrap().is_empty());
+}
+
+#[test]
+fn aead_tampered() {
+	let data = data();
+	let mut ciphertext = encrypt(&data);
+	ciphertext.fill_range(SIZE + 20..SIZE + 21, 0).unwrap();
+	let error = decrypt(ciphertext).unwrap_err();
+	assert_eq!(error.kind(), ErrorKind::Io(io::ErrorKind::InvalidData));
+}
+
+#[test]
+fn aead_truncated() {
+	let data = data();
+	let ciphertext = encrypt(&data);
+	let mut truncated = DefaultBuffer::default();
+	ciphertext.copy_range_to(..SIZE + 16, &mut truncated).unwrap();
+	let error = decrypt(truncated).unwrap_err();
+	assert_eq!(error.kind(), ErrorKind::Io(io::ErrorKind::InvalidData), "truncation should be detected");
+}
+
+#[test]
+fn keystream_round_trip() {
+	let data = data();
+	let cipher = || ChaCha20::new(&KEY.into(), &[0x24; 12].into());
+	let mut sink = CipherSink::keystream(cipher(), DefaultBuffer::default());
+	sink.drain_all(&mut Buffer::from_slice(&data)).unwrap();
+	let ciphertext = sink.finish().unwrap();
+	assert_eq!(ciphertext.count(), data.len());
+	assert_ne!(ciphertext, data);
+
+	let mut source = CipherSource::keystream(cipher(), ciphertext);
+	let mut plaintext = DefaultBuffer::default();
+	source.fill_all(&mut plaintext).unwrap();
+	assert!(source.is_eos());
+	assert_eq!(plaintext, data);
+}
+
+/// A source with no data available on every other read, like a non-blocking
+/// source waiting on IO.
+struct Stalling<'d> {
+	buffer: DefaultBuffer<'d>,
+	stalled: bool,
+}
+
+impl Stream<SIZE> for Stalling<'_> {
+	fn is_closed(&self) -> bool { false }
+
+	fn close(&mut self) -> StreamResult { Ok(()) }
+}
+
+impl<'d> Source<'d, SIZE> for Stalling<'d> {
+	fn is_eos(&self) -> bool {
+		self.buffer.is_empty()
+	}
+
+	fn fill(&mut self, sink: &mut Buffer<'d, SIZE, impl Pool<SIZE>>, count: usize) -> BufferResult<usize> {
+		self.stalled = !self.stalled;
+		if self.stalled {
+			return Ok(0)
+		}
+		self.buffer.fill(sink, count.min(100))
+	}
+}
+
+#[test]
+fn aead_stalling_source() {
+	let data = data();
+	let ciphertext = encrypt(&data);
+	let aead = ChaCha20Poly1305::new(&KEY.into());
+	let decryptor = DecryptorBE32::from_aead(aead, &STREAM_NONCE.into());
+	let mut source = CipherSource::decrypt(decryptor, Stalling { buffer: ciphertext, stalled: false });
+	let mut plaintext = DefaultBuffer::default();
+	while !source.is_eos() {
+		source.fill(&mut plaintext, SIZE).unwrap();
+	}
+	assert_eq!(plaintext, data, "empty reads shouldn't end the stream");
+}
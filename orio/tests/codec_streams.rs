@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use pretty_assertions::assert_eq;
+use quickcheck_macros::quickcheck;
+use orio::{Buffer, ByteString, DefaultBuffer, EncodeBytes, SIZE};
+use orio::streams::{Base64Sink, Base64Source, HexSink, HexSource, Sink, Source};
+
+fn read_to_end<'d>(source: &mut impl Source<'d, SIZE>, read_size: u8) -> Buffer<'d> {
+	let mut buffer = Buffer::default();
+	let read_size = read_size.max(1) as usize;
+	while !source.is_eos() {
+		source.fill(&mut buffer, read_size).unwrap();
+	}
+	buffer
+}
+
+#[quickcheck]
+fn base64_sink(chunks: Vec<Vec<u8>>) {
+	let mut sink = Base64Sink::base64(DefaultBuffer::default());
+	for chunk in &chunks {
+		sink.drain_all(&mut Buffer::from_slice(chunk)).unwrap();
+	}
+
+	let encoded = sink.finish().unwrap();
+	let expected = ByteString::from(chunks.concat()).base64_string();
+	assert_eq!(encoded, expected.as_bytes());
+}
+
+#[quickcheck]
+fn hex_sink(chunks: Vec<Vec<u8>>) {
+	let mut sink = HexSink::hex_upper(DefaultBuffer::default());
+	for chunk in &chunks {
+		sink.drain_all(&mut Buffer::from_slice(chunk)).unwrap();
+	}
+
+	let encoded = sink.finish().unwrap();
+	let expected = ByteString::from(chunks.concat()).hex_upper_string();
+	assert_eq!(encoded, expected.as_bytes());
+}
+
+#[quickcheck]
+fn base64_source(data: Vec<u8>, read_size: u8) {
+	let encoded = ByteString::from(data.clone()).base64_url_string();
+	let mut source = Base64Source::base64_url(Buffer::from_utf8(&encoded));
+	assert_eq!(read_to_end(&mut source, read_size), data);
+}
+
+#[quickcheck]
+fn base64_padded(data: Vec<u8>, read_size: u8) {
+	let mut sink = Base64Sink::base64_padded(DefaultBuffer::default());
+	sink.drain_all(&mut Buffer::from_slice(&data)).unwrap();
+	let encoded = sink.finish().unwrap();
+	assert_eq!(encoded.count() % 4, 0, "padded output should be whole groups");
+
+	let mut source = Base64Source::base64(encoded);
+	assert_eq!(read_to_end(&mut source, read_size), data);
+}
+
+#[quickcheck]
+fn hex_source(data: Vec<u8>, read_size: u8) {
+	let encoded = ByteString::from(data.clone()).hex_lower_string();
+	let mut source = HexSource::hex(Buffer::from_utf8(&encoded));
+	assert_eq!(read_to_end(&mut source, read_size), data);
+}
+
+#[test]
+fn decode_ignores_whitespace() {
+	let mut source = Base64Source::base64(Buffer::from_utf8("SGVs\nbG8s\r\nIHdv cmxk"));
+	assert_eq!(read_to_end(&mut source, 5), b"Hello, world");
+}
+
+#[test]
+fn decode_padded() {
+	let mut source = Base64Source::base64(Buffer::from_utf8("SGVsbG8=\n"));
+	assert_eq!(read_to_end(&mut source, 1), b"Hello");
+}
+
+#[test]
+fn decode_invalid() {
+	let mut source = HexSource::hex(Buffer::from_utf8("0g"));
+	let error = source.fill(&mut DefaultBuffer::default(), 1).unwrap_err();
+	assert!(error.is_io_error());
+}
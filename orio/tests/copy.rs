@@ -0,0 +1,30 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::io::Cursor;
+use pretty_assertions::assert_eq;
+use quickcheck_macros::quickcheck;
+use orio::{Buffer, DefaultBuffer, copy, copy_with_progress};
+use orio::streams::{ReaderSource, WriterSink};
+
+#[quickcheck]
+fn copy_reader_to_buffer(data: Vec<u8>) {
+	let mut source = ReaderSource::from(Cursor::new(data.clone()));
+	let mut sink = DefaultBuffer::default();
+	let mut last = 0;
+	let count = copy_with_progress(&mut source, &mut sink, |total| {
+		assert!(total > last, "progress should increase");
+		last = total;
+	}).unwrap();
+	assert_eq!(count, data.len() as u64);
+	assert_eq!(last, count);
+	assert_eq!(sink, data);
+}
+
+#[quickcheck]
+fn copy_buffer_to_writer(data: Vec<u8>) {
+	let mut source = Buffer::from_slice(&data);
+	let mut sink = WriterSink::from(Vec::new());
+	assert_eq!(copy(&mut source, &mut sink).unwrap(), data.len() as u64);
+	assert!(source.is_empty());
+	assert_eq!(sink.into_inner().unwrap(), data);
+}
@@ -0,0 +1,39 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use pretty_assertions::assert_eq;
+use orio::{Buffer, BufferOptions, DefaultBuffer, SIZE};
+use orio::streams::{CountingSink, CountingSource, Sink, Source};
+
+const DATA: &[u8] = &[0xA5; 300];
+
+#[test]
+fn counting_source() {
+	let mut source = CountingSource::<_, SIZE>::new(Buffer::from_slice(DATA));
+	let mut sink = DefaultBuffer::default();
+	assert_eq!(source.fill(&mut sink, 100).unwrap(), 100);
+	assert_eq!(source.count(), 100);
+	assert_eq!(source.fill_all(&mut sink).unwrap(), 200);
+	assert_eq!(source.reset_count(), 300);
+	assert_eq!(source.count(), 0);
+	assert_eq!(sink, DATA);
+}
+
+#[test]
+fn counting_source_error() {
+	let mut source = CountingSource::<_, SIZE>::new(Buffer::from_slice(DATA));
+	let mut sink = DefaultBuffer::new(orio::pool::pool(), BufferOptions::default().with_max_capacity(100));
+	assert_eq!(source.fill(&mut sink, 150).unwrap(), 100, "fill should be limited by capacity");
+	assert!(source.fill(&mut sink, 50).is_err());
+	assert_eq!(source.count(), 100, "failed fills shouldn't be counted");
+}
+
+#[test]
+fn counting_sink() {
+	let mut sink = CountingSink::<_, SIZE>::new(DefaultBuffer::default());
+	let mut source = Buffer::from_slice(DATA);
+	assert_eq!(sink.drain(&mut source, 250).unwrap(), 250);
+	assert_eq!(sink.count(), 250);
+	assert_eq!(sink.drain_all(&mut source).unwrap(), 50);
+	assert_eq!(sink.count(), 300);
+	assert_eq!(sink.into_inner(), DATA);
+}
@@ -0,0 +1,73 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::io;
+use pretty_assertions::assert_eq;
+use orio::{DefaultBuffer, ErrorKind, SIZE};
+use orio::streams::BufSink;
+use orio::streams::csv::CsvReader;
+
+fn reader(csv: &str) -> CsvReader<'static, DefaultBuffer<'static>, SIZE> {
+	let mut buffer = DefaultBuffer::default();
+	buffer.write_utf8(csv).unwrap();
+	CsvReader::new(buffer)
+}
+
+fn records<'d>(mut reader: CsvReader<'d, DefaultBuffer<'d>, SIZE>) -> Vec<Vec<String>> {
+	let mut records = Vec::new();
+	while let Some(record) = reader.next_record().unwrap() {
+		records.push(record.iter().map(|field| field.utf8().unwrap().into_owned()).collect());
+	}
+	records
+}
+
+#[test]
+fn read() {
+	assert_eq!(
+		records(reader("a,b,c\r\n1,\"two, three\",\"say \"\"hi\"\"\"\n\n,,\rlast")),
+		[
+			vec!["a", "b", "c"],
+			vec!["1", "two, three", "say \"hi\""],
+			vec!["", "", ""],
+			vec!["last"],
+		]
+	);
+	assert_eq!(records(reader("x,\n\"\"")), [vec!["x", ""], vec![""]]);
+	assert!(records(reader("\r\n\n")).is_empty());
+}
+
+#[test]
+fn delimiter_and_quote() {
+	let reader = reader("a;'b;c';'it''s'\n").with_delimiter(b';').with_quote(b'\'');
+	assert_eq!(records(reader), [vec!["a", "b;c", "it's"]]);
+}
+
+#[test]
+fn record() {
+	let mut reader = reader("a,\"b\"\nrest");
+	let record = reader.next_record().unwrap().unwrap();
+	assert_eq!(record.len(), 2);
+	assert_eq!(*record.get(1).unwrap(), b"b"[..]);
+	assert!(record.get(2).is_none());
+	assert_eq!(reader.into_inner().unwrap(), b"rest");
+}
+
+#[test]
+fn across_segments() {
+	let long = "x".repeat(SIZE + 100);
+	let csv = format!("{long},\"{long}\"\"\"\n1,2");
+	assert_eq!(
+		records(reader(&csv)),
+		[vec![long.clone(), format!("{long}\"")], vec!["1".into(), "2".into()]]
+	);
+}
+
+#[test]
+fn invalid() {
+	let mut reader = reader("\"a\"b,c");
+	assert_eq!(
+		reader.next_record().err().unwrap().kind(),
+		ErrorKind::Io(io::ErrorKind::InvalidData)
+	);
+	let mut reader = self::reader("a,\"unterminated");
+	assert_eq!(reader.next_record().err().unwrap().kind(), ErrorKind::Eos);
+}
@@ -0,0 +1,51 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use pretty_assertions::assert_eq;
+use orio::ErrorKind;
+use orio::streams::{duplex, BufSink, BufSource, Sink, Source, Stream};
+
+#[test]
+fn round_trip() {
+	let (mut client, mut server) = duplex(64);
+	client.write_utf8("ping\n").unwrap();
+	client.write_u32(42).unwrap();
+
+	let mut line = String::new();
+	assert!(server.read_utf8_line(&mut line).unwrap().found);
+	assert_eq!(line, "ping");
+	assert_eq!(server.read_u32().unwrap(), 42);
+
+	server.write_utf8("pong").unwrap();
+	let mut reply = String::new();
+	assert_eq!(client.read_utf8_to_end(&mut reply).unwrap(), "pong");
+	assert!(!client.is_eos());
+}
+
+#[test]
+fn backpressure() {
+	let (mut a, mut b) = duplex(4);
+	a.write_from_slice(b"abcdefgh").unwrap();
+	assert_eq!(b.available(), 4);
+	assert!(a.flush().unwrap_err().is_would_block());
+
+	let mut buf = [0; 4];
+	assert_eq!(b.read_slice(&mut buf).unwrap(), 4);
+	assert_eq!(&buf, b"abcd");
+	a.flush().unwrap();
+	assert_eq!(b.read_slice(&mut buf).unwrap(), 4);
+	assert_eq!(&buf, b"efgh");
+}
+
+#[test]
+fn close() {
+	let (mut a, mut b) = duplex(16);
+	a.write_from_slice(b"bye").unwrap();
+	a.close().unwrap();
+	assert!(!b.is_eos());
+
+	let mut buf = [0; 3];
+	b.read_slice_exact(&mut buf).unwrap();
+	assert_eq!(&buf, b"bye");
+	assert!(b.is_eos());
+	assert!(b.write_from_slice(b"hello?").unwrap_err().kind() == ErrorKind::Closed);
+}
@@ -0,0 +1,70 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use pretty_assertions::assert_eq;
+use orio::{Buffer, BufferOptions, DefaultBuffer};
+use orio::streams::{BufSink, BufSource, DynSink, DynSource, ReaderSource, Result, Sink, Source, Stream, WriterSink};
+
+#[test]
+fn boxed_sources() -> Result {
+	let mut sources: Vec<Box<dyn DynSource>> = vec![
+		DynSource::boxed(Buffer::from_utf8("Hello, ")),
+		ReaderSource::from(&b"World!"[..]).boxed(),
+	];
+	let mut buffer = DefaultBuffer::default();
+	for source in &mut sources {
+		source.fill_all(&mut buffer)?;
+		source.close()?;
+	}
+	assert_eq!(buffer.read_utf8_to_end(&mut String::new())?, "Hello, World!");
+	Ok(())
+}
+
+#[test]
+fn boxed_source_capacity() -> Result {
+	let mut source = DynSource::boxed(Buffer::from_utf8("Hello, World!"));
+	let mut buffer = DefaultBuffer::new(
+		orio::pool::pool(),
+		BufferOptions::default().with_max_capacity(5)
+	);
+	assert_eq!(source.fill(&mut buffer, 8)?, 5);
+	assert_eq!(buffer.read_utf8_to_end(&mut String::new())?, "Hello");
+	assert!(!source.is_eos());
+	Ok(())
+}
+
+#[test]
+fn boxed_sinks() -> Result {
+	let mut first = Vec::new();
+	let mut second = Vec::new();
+	{
+		let mut sinks: Vec<Box<dyn DynSink>> = vec![
+			WriterSink::from(&mut first).boxed(),
+			WriterSink::from(&mut second).boxed(),
+		];
+		let mut buffer = Buffer::from_utf8("Hello, World!");
+		sinks[0].drain(&mut buffer, 7)?;
+		assert_eq!(buffer.count(), 6, "partially drained bytes should remain");
+		sinks[1].drain_all(&mut buffer)?;
+		assert!(buffer.is_empty());
+		for sink in &mut sinks {
+			sink.close()?;
+		}
+	}
+	assert_eq!(first, b"Hello, ");
+	assert_eq!(second, b"World!");
+	Ok(())
+}
+
+#[test]
+fn boxed_sink_order() -> Result {
+	let mut vec = Vec::new();
+	{
+		let mut sink = WriterSink::from(&mut vec).boxed();
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_utf8("Hello, World!")?;
+		sink.drain(&mut buffer, 5)?;
+		assert_eq!(buffer.read_utf8_to_end(&mut String::new())?, ", World!");
+	}
+	assert_eq!(vec, b"Hello");
+	Ok(())
+}
@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use pretty_assertions::assert_eq;
+use orio::{Buffer, DefaultBuffer, SIZE};
+use orio::streams::{Faults, FaultySink, FaultySource, RetryPolicy, RetrySource, Sink, Source};
+
+const DATA: &[u8] = b"Hello world!";
+
+#[test]
+fn short_reads() {
+	let mut source = FaultySource::<_, SIZE>::new(Buffer::from_slice(DATA), Faults::default().max_chunk(5));
+	let mut sink = DefaultBuffer::default();
+	assert_eq!(source.fill(&mut sink, 12).unwrap(), 5);
+	assert_eq!(source.fill_all(&mut sink).unwrap(), 5);
+	assert_eq!(source.fill(&mut sink, 12).unwrap(), 2);
+	assert_eq!(sink, DATA);
+}
+
+#[test]
+fn eos_at() {
+	let mut source = FaultySource::<_, SIZE>::new(Buffer::from_slice(DATA), Faults::default().eos_at(5));
+	let mut sink = DefaultBuffer::default();
+	assert_eq!(source.fill_all(&mut sink).unwrap(), 5);
+	assert!(source.is_eos());
+	assert_eq!(source.fill(&mut sink, 1).unwrap(), 0);
+	assert_eq!(sink, b"Hello");
+
+	let mut sink = FaultySink::<_, SIZE>::new(DefaultBuffer::default(), Faults::default().eos_at(5));
+	let mut source = Buffer::from_slice(DATA);
+	assert_eq!(sink.drain_all(&mut source).unwrap(), 5);
+	assert!(sink.drain_all(&mut source).unwrap_err().is_eos());
+	assert_eq!(sink.position(), 5);
+}
+
+#[test]
+fn interrupt_every() {
+	let faults = Faults::default().interrupt_every(2).max_chunk(4);
+	let mut source = FaultySource::<_, SIZE>::new(Buffer::from_slice(DATA), faults);
+	let mut sink = DefaultBuffer::default();
+	assert_eq!(source.fill(&mut sink, 12).unwrap(), 4);
+	assert!(source.fill(&mut sink, 12).unwrap_err().is_interrupted());
+
+	let mut source = RetrySource::new(source, RetryPolicy::default());
+	assert_eq!(source.fill(&mut sink, 12).unwrap(), 4);
+	assert_eq!(source.fill(&mut sink, 12).unwrap(), 4);
+	assert_eq!(sink, DATA);
+}
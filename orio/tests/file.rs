@@ -1,9 +1,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::io::{Read, Seek};
-use pretty_assertions::assert_str_eq;
+use std::thread;
+use pretty_assertions::{assert_eq, assert_str_eq};
 use tempfile::tempfile;
-use orio::streams::{BufSource, FileSource, SourceExt, Result, FileSink, SinkExt, BufSink};
+use orio::{Buffer, DefaultBuffer, SIZE};
+use orio::streams::{BufSource, FileSource, SourceExt, Result, FileSink, SinkExt, BufSink, PositionedSink, PositionedSource, Seekable, SeekOffset, Source};
 use crate::dataset::{Data, DATASET};
 
 mod dataset;
@@ -22,6 +24,16 @@ fn file_source() -> Result {
 	Ok(())
 }
 
+#[test]
+fn file_source_seek_past_end() -> Result {
+	let Data { path, size, .. } = DATA;
+	let mut source = FileSource::open(path)?;
+	assert_eq!(source.seek(SeekOffset::FromStart(size + 10))?, size + 10);
+	assert!(Source::<SIZE>::is_eos(&source));
+	assert_eq!(source.fill(&mut DefaultBuffer::default(), 10)?, 0);
+	Ok(())
+}
+
 #[test]
 fn file_sink() -> Result {
 	let Data { size, text, .. } = DATA;
@@ -39,3 +51,47 @@ fn file_sink() -> Result {
 	assert_str_eq!(target, text);
 	Ok(())
 }
+
+#[test]
+fn positioned() -> Result {
+	let text = DATA.text.as_bytes();
+	let half = text.len() / 2;
+	let file = tempfile()?;
+
+	// Write the halves concurrently, second half first.
+	thread::scope(|scope| {
+		for range in [half..text.len(), 0..half] {
+			let file = &file;
+			scope.spawn(move || {
+				let mut buffer = Buffer::<SIZE>::from_slice(&text[range.clone()]);
+				assert_eq!(file.drain_at(range.start as u64, &mut buffer, range.len()).unwrap(), range.len());
+			});
+		}
+	});
+
+	// Read overlapping ranges concurrently.
+	thread::scope(|scope| {
+		for offset in [0, 1000, text.len() - SIZE - 1] {
+			let file = &file;
+			scope.spawn(move || {
+				let mut buffer = DefaultBuffer::default();
+				assert_eq!(file.fill_at(offset as u64, &mut buffer, SIZE + 1).unwrap(), SIZE + 1);
+				assert_eq!(buffer, &text[offset..][..SIZE + 1]);
+			});
+		}
+	});
+
+	// Reading past the end is short.
+	let mut buffer = DefaultBuffer::default();
+	assert_eq!(file.fill_at(text.len() as u64 - 5, &mut buffer, 10)?, 5);
+	assert_eq!(file.fill_at(text.len() as u64 + 5, &mut buffer, 10)?, 0);
+
+	// Offsets past 4GiB aren't truncated.
+	let offset = 5 << 30;
+	let file = tempfile()?;
+	file.drain_at(offset, &mut Buffer::<SIZE>::from_slice(b"far"), 3)?;
+	let mut buffer = DefaultBuffer::default();
+	assert_eq!(file.fill_at(offset, &mut buffer, 3)?, 3);
+	assert_eq!(buffer, b"far");
+	Ok(())
+}
@@ -0,0 +1,57 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use pretty_assertions::assert_eq;
+use quickcheck_macros::quickcheck;
+use orio::{BufferOptions, DefaultBuffer, SIZE};
+use orio::streams::{pattern_source, random_source, repeat_source, Source, Stream};
+
+/// Reads `len` bytes from `source` in reads of `chunk` bytes.
+fn read<'d>(source: &mut impl Source<'d, SIZE>, len: usize, chunk: usize) -> Vec<u8> {
+	let mut sink = DefaultBuffer::default();
+	while sink.count() < len {
+		let count = chunk.min(len - sink.count());
+		assert_eq!(source.fill(&mut sink, count).unwrap(), count);
+	}
+	sink.into_bytes()
+}
+
+#[test]
+fn repeat() {
+	let mut source = repeat_source(0xA5);
+	assert_eq!(read(&mut source, SIZE * 2 + 10, SIZE + 3), vec![0xA5; SIZE * 2 + 10]);
+	assert!(!Source::<SIZE>::is_eos(&source));
+}
+
+#[quickcheck]
+fn pattern(pattern: Vec<u8>, chunk: usize) {
+	if pattern.is_empty() { return }
+	let chunk = chunk % 1000 + 1;
+	let len = pattern.len() * 3 + 100;
+	let expected = pattern.iter().copied().cycle().take(len).collect::<Vec<_>>();
+	assert_eq!(read(&mut pattern_source(&pattern), len, chunk), expected);
+}
+
+#[quickcheck]
+fn random_is_deterministic(seed: u64, chunk: usize) {
+	let chunk = chunk % 1000 + 1;
+	let expected = read(&mut random_source(seed), 5000, 5000);
+	assert_eq!(read(&mut random_source(seed), 5000, chunk), expected, "reads should not change the data");
+	assert_ne!(read(&mut random_source(seed.wrapping_add(1)), 5000, 5000), expected);
+}
+
+#[test]
+fn fill_all() {
+	let mut sink = DefaultBuffer::default();
+	assert_eq!(repeat_source(0).fill_all(&mut sink).unwrap(), SIZE, "an empty sink should get one segment");
+	let mut sink = DefaultBuffer::new(orio::pool::pool(), BufferOptions::default().with_max_capacity(100));
+	assert_eq!(random_source(0).fill_all(&mut sink).unwrap(), 100, "fill should be limited by capacity");
+	assert!(random_source(0).fill(&mut sink, 1).is_err());
+}
+
+#[test]
+fn closed() {
+	let mut source = pattern_source(b"abc");
+	Stream::<SIZE>::close(&mut source).unwrap();
+	assert!(Stream::<SIZE>::is_closed(&source));
+	assert!(source.fill(&mut DefaultBuffer::default(), 1).is_err());
+}
@@ -78,3 +78,177 @@ fn buf_hash_sink() {
 	assert_eq!(written, size, "should write to end");
 	assert_str_eq!(hash, sink_hash, "hashes should match");
 }
+
+#[cfg(feature = "hmac")]
+mod hmac {
+	use pretty_assertions::{assert_eq, assert_str_eq};
+	use orio::{Buffer, DefaultBuffer, EncodeBytes};
+	use orio::streams::{HashSink, HashSource, HashStream, Sink, Source, void_sink};
+
+	// RFC 4231, test case 2
+	const KEY: &[u8] = b"Jefe";
+	const DATA: &[u8] = b"what do ya want for nothing?";
+	const HMAC_SHA256: &str = "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843";
+
+	#[test]
+	fn hmac_source() {
+		let mut source = HashSource::hmac_sha256(KEY, Buffer::from_slice(DATA));
+		let mut buffer = DefaultBuffer::default();
+		source.fill_all(&mut buffer).unwrap();
+		assert_eq!(buffer, DATA);
+		assert_str_eq!(source.hash().hex_lower_string(), HMAC_SHA256);
+	}
+
+	#[test]
+	fn hmac_sink() {
+		let mut sink = HashSink::hmac_sha256(KEY, void_sink());
+		let mut source = Buffer::from_slice(DATA);
+		sink.drain(&mut source, 10).unwrap();
+		sink.drain_all(&mut source).unwrap();
+		assert_str_eq!(sink.take_hash().hex_lower_string(), HMAC_SHA256);
+	}
+
+	#[test]
+	fn verify() {
+		let mut sink = HashSink::hmac_sha256(KEY, void_sink());
+		sink.drain_all(&mut Buffer::from_slice(DATA)).unwrap();
+		let mut tag = sink.hash().as_ref().to_vec();
+		assert!(sink.verify(&tag), "tag should match");
+		tag[0] ^= 1;
+		assert!(!sink.verify(&tag), "modified tag shouldn't match");
+		assert!(!sink.verify(&[]), "empty tag shouldn't match");
+	}
+
+	#[test]
+	fn buffer_update_mac() {
+		use hmac::{Hmac, Mac};
+		use sha2::{Digest, Sha256};
+
+		let buffer = Buffer::from_slice(DATA);
+		let mut mac = Hmac::<Sha256>::new_from_slice(KEY).unwrap();
+		buffer.update_mac(&mut mac);
+		let mut sink = HashSink::hmac_sha256(KEY, void_sink());
+		sink.drain_all(&mut Buffer::from_slice(DATA)).unwrap();
+		assert_eq!(&mac.finalize().into_bytes()[..], sink.hash().as_ref());
+
+		let mut hasher = Sha256::new();
+		buffer.hash(&mut hasher);
+		assert_eq!(hasher.finalize(), Sha256::digest(DATA));
+	}
+}
+
+#[cfg(feature = "blake3")]
+mod blake3 {
+	use pretty_assertions::assert_eq;
+	use orio::{Buffer, DefaultBuffer};
+	use orio::streams::{HashSink, HashSource, HashStream, Sink, Source, void_sink};
+
+	const DATA: &[u8] = &[0x5A; 3000];
+	const KEY: &[u8; 32] = &[0x42; 32];
+
+	#[test]
+	fn blake3_source() {
+		let mut source = HashSource::blake3(Buffer::from_slice(DATA));
+		source.fill_all(&mut DefaultBuffer::default()).unwrap();
+		assert_eq!(source.hash().as_ref(), &::blake3::hash(DATA).as_bytes()[..]);
+	}
+
+	#[test]
+	fn blake3_keyed_sink() {
+		let mut sink = HashSink::blake3_keyed(KEY, void_sink());
+		sink.drain_all(&mut Buffer::from_slice(DATA)).unwrap();
+		let tag = ::blake3::keyed_hash(KEY, DATA);
+		assert!(sink.verify(tag.as_bytes()), "tag should match");
+		assert_eq!(sink.take_hash().as_ref(), &tag.as_bytes()[..]);
+	}
+}
+
+#[cfg(feature = "xxhash")]
+mod xxhash {
+	use pretty_assertions::assert_eq;
+	use orio::{Buffer, DefaultBuffer, SIZE};
+	use orio::streams::{HashSink, HashSource, HashStream, Sink, Source, void_sink};
+	use xxhash_rust::{xxh3, xxh64};
+
+	fn data() -> Vec<u8> {
+		(0..SIZE * 2 + 100).map(|i| i as u8).collect()
+	}
+
+	#[test]
+	fn xxh3_source() {
+		let data = data();
+		let mut source = HashSource::xxh3_64(Buffer::from_slice(&data));
+		let mut buffer = DefaultBuffer::default();
+		while !source.is_eos() {
+			source.fill(&mut buffer, 1000).unwrap();
+		}
+		assert_eq!(source.take_hash().as_ref(), &xxh3::xxh3_64(&data).to_be_bytes()[..]);
+		assert_eq!(source.take_hash().as_ref(), &xxh3::xxh3_64(&[]).to_be_bytes()[..], "hasher should be reset");
+	}
+
+	#[test]
+	fn xxh3_128_sink() {
+		let data = data();
+		let mut sink = HashSink::xxh3_128(void_sink());
+		sink.drain_all(&mut Buffer::from_slice(&data)).unwrap();
+		assert_eq!(sink.hash().as_ref(), &xxh3::xxh3_128(&data).to_be_bytes()[..]);
+	}
+
+	#[test]
+	fn xxh64_sink() {
+		let data = data();
+		let mut sink = HashSink::xxh64(void_sink());
+		sink.drain_all(&mut Buffer::from_slice(&data)).unwrap();
+		assert_eq!(sink.hash().as_ref(), &xxh64::xxh64(&data, 0).to_be_bytes()[..]);
+	}
+}
+
+mod multi {
+	use pretty_assertions::assert_eq;
+	use orio::{Buffer, DefaultBuffer, SIZE};
+	use orio::streams::{HashSink, HashSource, HashStream, MultiHasher, MultiHashSink, MultiHashSource, Sink, Source, void_sink};
+	use sha2::{Digest, Sha256, Sha512};
+
+	fn data() -> Vec<u8> {
+		(0..SIZE * 2 + 100).map(|i| i as u8).collect()
+	}
+
+	fn hasher() -> MultiHasher {
+		MultiHasher::new().with(Sha256::default()).with(Sha512::default())
+	}
+
+	#[test]
+	fn multi_hash_source() {
+		let data = data();
+		let mut source = MultiHashSource::new(hasher(), Buffer::from_slice(&data));
+		let mut buffer = DefaultBuffer::default();
+		while !source.is_eos() {
+			source.fill(&mut buffer, 1000).unwrap();
+		}
+
+		let mut sha256 = HashSource::sha256(Buffer::from_slice(&data));
+		let mut sha512 = HashSource::sha512(Buffer::from_slice(&data));
+		sha256.fill_all(&mut DefaultBuffer::default()).unwrap();
+		sha512.fill_all(&mut DefaultBuffer::default()).unwrap();
+		let hasher = source.hasher();
+		assert_eq!(hasher.len(), 2);
+		assert_eq!(hasher.hash(0), Some(sha256.hash()));
+		assert_eq!(hasher.hash_of::<Sha512>(), Some(sha512.hash()));
+		assert_eq!(hasher.hashes(), [sha256.hash(), sha512.hash()]);
+		assert_eq!(hasher.hash(2), None);
+	}
+
+	#[test]
+	fn multi_hash_sink() {
+		let data = data();
+		let mut sink = MultiHashSink::new(hasher(), void_sink());
+		sink.drain_all(&mut Buffer::from_slice(&data)).unwrap();
+
+		let mut sha256 = HashSink::sha256(void_sink());
+		sha256.drain_all(&mut Buffer::from_slice(&data)).unwrap();
+		let hashes = sink.hasher_mut().take_hashes();
+		assert_eq!(hashes[0], sha256.hash());
+		let empty = Sha512::digest([]);
+		assert_eq!(sink.hasher().hash_of::<Sha512>().unwrap().as_ref(), &empty[..], "hashers should be reset");
+	}
+}
@@ -0,0 +1,134 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::io;
+use pretty_assertions::assert_eq;
+use orio::{Buffer, ByteString, DefaultBuffer, ErrorKind, SIZE};
+use orio::streams::{BufSink, BufSource, Faults, FaultySource, Sink, Source, SourceExt};
+use orio::streams::http1::{read_headers, ChunkedSink, ChunkedSource};
+
+fn fields(block: &str, max_size: usize) -> Vec<(String, String)> {
+	let mut buffer = DefaultBuffer::default();
+	buffer.write_utf8(block).unwrap();
+	let headers = read_headers(&mut buffer, max_size).unwrap();
+	headers.iter()
+		   .map(|h| (h.name.utf8().unwrap().into_owned(), h.value.utf8().unwrap().into_owned()))
+		   .collect()
+}
+
+#[test]
+fn read() {
+	let mut buffer = DefaultBuffer::default();
+	buffer.write_utf8("GET / HTTP/1.1\r\nHost: example.com\r\nContent-Length:  5 \r\n\r\nhello").unwrap();
+	let mut line = String::new();
+	buffer.read_utf8_line(&mut line).unwrap();
+	assert_eq!(line, "GET / HTTP/1.1");
+
+	let headers = read_headers(&mut buffer, 1024).unwrap();
+	assert_eq!(headers.count(), 2);
+	assert_eq!(headers.size(), 42);
+	assert_eq!(headers.get("host").unwrap(), "example.com"[..]);
+	assert_eq!(headers.get("CONTENT-LENGTH").unwrap(), "5"[..]);
+	assert!(headers.get("Accept").is_none());
+	headers.consume().unwrap();
+	assert_eq!(buffer, b"hello");
+
+	assert_eq!(
+		fields("A: 1\nB:\t2\n\n", 1024),
+		[("A".into(), "1".into()), ("B".into(), "2".into())]
+	);
+	assert!(fields("\r\n", 1024).is_empty());
+}
+
+#[test]
+fn across_segments() {
+	// Place the first carriage return at the end of the first segment.
+	let value = "x".repeat(SIZE - 4);
+	let block = format!("A: {value}\r\nB: 2\r\n\r\nbody");
+	let mut buffer = DefaultBuffer::default();
+	buffer.write_utf8(&block).unwrap();
+	{
+		let headers = read_headers(&mut buffer, SIZE * 2).unwrap();
+		assert_eq!(headers.count(), 2);
+		assert_eq!(headers.get("a").unwrap().utf8().unwrap(), value);
+		assert_eq!(headers.get("b").unwrap(), "2"[..]);
+	}
+	assert_eq!(buffer, b"body");
+
+	// Read one byte at a time, so every carriage return ends the buffer.
+	let block = b"A: 1\r\nB: 2\r\n\r\n";
+	let mut source = FaultySource::new(Buffer::from_slice(block), Faults::default().max_chunk(1)).buffered();
+	let headers = read_headers(&mut source, 1024).unwrap();
+	assert_eq!(headers.count(), 2);
+	assert_eq!(headers.size(), block.len());
+}
+
+#[test]
+fn invalid() {
+	fn error(block: &str, max_size: usize) -> ErrorKind {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_utf8(block).unwrap();
+		read_headers(&mut buffer, max_size).err().unwrap().kind()
+	}
+
+	let invalid_data = ErrorKind::Io(io::ErrorKind::InvalidData);
+	assert_eq!(error("A: 1\r\nB: 2\r\n\r\n", 10), invalid_data, "block too large");
+	assert_eq!(error("A: 1234567890", 10), invalid_data, "unterminated line too large");
+	assert_eq!(error("A: 1\r\n folded\r\n\r\n", 1024), invalid_data, "folded line");
+	assert_eq!(error("A 1\r\n\r\n", 1024), invalid_data, "missing colon");
+	assert_eq!(error("A : 1\r\n\r\n", 1024), invalid_data, "whitespace before colon");
+	assert_eq!(error("A: 1\rB: 2\r\n\r\n", 1024), invalid_data, "lone carriage return");
+	assert_eq!(error("A: 1\r\n\r", 1024), invalid_data, "carriage return at end of stream");
+	assert_eq!(error("A: 1\r\n", 1024), ErrorKind::Eos, "end of stream");
+}
+
+#[test]
+fn chunked_source() {
+	let mut buffer = DefaultBuffer::default();
+	buffer.write_utf8("5\r\nhello\r\n7;ext=1\r\n, world\r\n0\r\nExpires: never\r\n\r\nnext").unwrap();
+	let mut source = ChunkedSource::<_, SIZE>::new(buffer);
+	let mut sink = DefaultBuffer::default();
+	let mut sizes = Vec::new();
+	while !source.is_eos() {
+		sizes.push(source.fill(&mut sink, 3).unwrap());
+	}
+	assert_eq!(sizes, [3, 2, 3, 3, 1, 0]);
+	assert_eq!(sink, b"hello, world");
+	assert_eq!(source.trailers().len(), 1);
+	assert_eq!(source.trailers()[0].0, ByteString::from(b"Expires".to_vec()));
+	assert_eq!(source.trailers()[0].1, ByteString::from(b"never".to_vec()));
+	assert_eq!(source.into_inner(), b"next");
+}
+
+#[test]
+fn chunked_source_invalid() {
+	fn error(body: &str) -> ErrorKind {
+		let mut buffer = DefaultBuffer::default();
+		buffer.write_utf8(body).unwrap();
+		let mut source = ChunkedSource::<_, SIZE>::new(buffer);
+		let mut sink = DefaultBuffer::default();
+		source.fill_all(&mut sink).err().unwrap().kind()
+	}
+
+	let invalid_data = ErrorKind::Io(io::ErrorKind::InvalidData);
+	assert_eq!(error("x\r\n"), invalid_data, "invalid size");
+	assert_eq!(error("2\r\nabc\r\n0\r\n\r\n"), invalid_data, "missing terminator");
+	assert_eq!(error("5\r\nab"), ErrorKind::Eos, "truncated chunk");
+}
+
+#[test]
+fn chunked_sink() {
+	let mut sink = ChunkedSink::<_, SIZE>::new(DefaultBuffer::default());
+	sink.drain_all(&mut Buffer::from_slice(b"hello, world")).unwrap();
+	sink.drain_all(&mut Buffer::from_slice(&[b'x'; 26])).unwrap();
+	sink.drain_all(&mut DefaultBuffer::default()).unwrap();
+	sink.finish_with_trailers([("Expires", "never")]).unwrap();
+	assert!(sink.drain_all(&mut Buffer::from_slice(b"!")).is_err(), "writes should fail after finishing");
+	let encoded = sink.into_inner();
+	assert_eq!(encoded.as_byte_str().utf8().unwrap(), format!("c\r\nhello, world\r\n1a\r\n{}\r\n0\r\nExpires: never\r\n\r\n", "x".repeat(26)));
+
+	let mut source = ChunkedSource::<_, SIZE>::new(encoded);
+	let mut decoded = DefaultBuffer::default();
+	source.fill_all(&mut decoded).unwrap();
+	assert!(source.is_eos());
+	assert_eq!(decoded.count(), 38);
+}
@@ -0,0 +1,71 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use pretty_assertions::assert_eq;
+use orio::{DefaultBuffer, SIZE};
+use orio::streams::BufSink;
+use orio::streams::json::{JsonToken, JsonTokenizer};
+
+fn tokens(json: &str) -> Vec<String> {
+	let mut buffer = DefaultBuffer::default();
+	buffer.write_utf8(json).unwrap();
+	let mut tokenizer = JsonTokenizer::new(buffer);
+	let mut tokens = Vec::new();
+	while let Some(token) = tokenizer.next_token().unwrap() {
+		tokens.push(match token {
+			JsonToken::String(str) => format!("s:{}", str.utf8().unwrap()),
+			JsonToken::Number(str) => format!("n:{}", str.utf8().unwrap()),
+			token => format!("{token:?}")
+		});
+	}
+	tokens
+}
+
+#[test]
+fn tokenize() {
+	assert_eq!(
+		tokens(r#" {"a": [1, -2.5e3, true], "b\"c": null, "d": false} "#),
+		[
+			"ObjectStart", "s:a", "Colon", "ArrayStart", "n:1", "Comma", "n:-2.5e3",
+			"Comma", "Bool(true)", "ArrayEnd", "Comma", r#"s:b\"c"#, "Colon", "Null",
+			"Comma", "s:d", "Colon", "Bool(false)", "ObjectEnd"
+		]
+	);
+	assert_eq!(tokens("42"), ["n:42"]);
+}
+
+#[test]
+fn across_segments() {
+	let long = "x".repeat(SIZE + 100);
+	let json = format!(r#"["{long}", 123]"#);
+	let tokens = tokens(&json);
+	assert_eq!(tokens.len(), 5);
+	assert_eq!(tokens[1], format!("s:{long}"));
+	assert_eq!(tokens[3], "n:123");
+}
+
+#[test]
+fn skip_value() {
+	let mut buffer = DefaultBuffer::default();
+	buffer.write_utf8(r#"{"skip": {"a": [1, {"b": 2}]}, "keep": 3}"#).unwrap();
+	let mut tokenizer = JsonTokenizer::new(buffer);
+	assert_eq!(tokenizer.next_token().unwrap(), Some(JsonToken::ObjectStart));
+	assert!(tokenizer.skip_value().unwrap(), "should skip key");
+	assert!(tokenizer.skip_value().unwrap(), "should skip nested object");
+	assert_eq!(tokenizer.next_token().unwrap(), Some(JsonToken::Comma));
+	assert!(matches!(tokenizer.next_token().unwrap(), Some(JsonToken::String(key)) if key == "keep"[..]));
+}
+
+#[test]
+fn invalid() {
+	let mut buffer = DefaultBuffer::default();
+	buffer.write_utf8(r#"["unterminated"#).unwrap();
+	let mut tokenizer = JsonTokenizer::new(buffer);
+	tokenizer.next_token().unwrap();
+	assert!(tokenizer.next_token().unwrap_err().is_eos());
+
+	let mut buffer = DefaultBuffer::default();
+	buffer.write_utf8("[tru]").unwrap();
+	let mut tokenizer = JsonTokenizer::new(buffer);
+	tokenizer.next_token().unwrap();
+	assert!(tokenizer.next_token().is_err());
+}
@@ -0,0 +1,97 @@
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "msgpack")]
+
+use std::io;
+use pretty_assertions::assert_eq;
+use quickcheck_macros::quickcheck;
+use orio::{Buffer, DefaultBuffer, ErrorKind};
+use orio::streams::msgpack::{
+	read_marker, read_msgpack_value, write_int, write_msgpack_value, write_uint, Marker, Value
+};
+
+fn encode(value: &Value) -> Vec<u8> {
+	let mut buffer = DefaultBuffer::default();
+	write_msgpack_value(&mut buffer, value).unwrap();
+	buffer.into_bytes()
+}
+
+fn decode(data: &[u8]) -> Value {
+	let mut buffer = Buffer::from_slice(data);
+	let value = read_msgpack_value(&mut buffer).unwrap();
+	assert!(buffer.is_empty());
+	value
+}
+
+#[test]
+fn encoding() {
+	// {"compact": true, "schema": 0}, from the msgpack.org example.
+	let value = Value::Map(vec![
+		(Value::Str("compact".into()), Value::Bool(true)),
+		(Value::Str("schema".into()), Value::UInt(0)),
+	]);
+	let data = b"\x82\xA7compact\xC3\xA6schema\x00";
+	assert_eq!(encode(&value), data);
+	assert_eq!(decode(data), value);
+
+	assert_eq!(encode(&Value::Nil), [0xC0]);
+	assert_eq!(encode(&Value::Int(-1)), [0xFF]);
+	assert_eq!(encode(&Value::Int(-33)), [0xD0, 0xDF]);
+	assert_eq!(encode(&Value::UInt(128)), [0xCC, 0x80]);
+	assert_eq!(encode(&Value::UInt(0x10000)), [0xCE, 0, 1, 0, 0]);
+	assert_eq!(encode(&Value::F32(1.5)), [0xCA, 0x3F, 0xC0, 0, 0]);
+	assert_eq!(encode(&Value::Bin(vec![1, 2])), [0xC4, 2, 1, 2]);
+	assert_eq!(encode(&Value::Ext(-1, vec![0; 4])), [0xD6, 0xFF, 0, 0, 0, 0]);
+	assert_eq!(encode(&Value::Ext(5, vec![7; 3])), [0xC7, 3, 5, 7, 7, 7]);
+	assert_eq!(encode(&Value::Array(vec![Value::Nil; 16]))[..3], [0xDC, 0, 16]);
+}
+
+#[test]
+fn round_trip() {
+	let value = Value::Array(vec![
+		Value::Int(i64::MIN),
+		Value::UInt(u64::MAX),
+		Value::F64(-0.25),
+		Value::Str("é".repeat(200)),
+		Value::Bin(vec![0xAB; 70000]),
+		Value::Map((0..20).map(|i| (Value::UInt(i), Value::Array(vec![]))).collect()),
+		Value::Ext(1, vec![]),
+	]);
+	assert_eq!(decode(&encode(&value)), value);
+}
+
+#[quickcheck]
+fn ints(value: i64) {
+	let mut buffer = DefaultBuffer::default();
+	write_int(&mut buffer, value).unwrap();
+	let expected = if value < 0 { Value::Int(value) } else { Value::UInt(value as u64) };
+	assert_eq!(read_msgpack_value(&mut buffer).unwrap(), expected);
+}
+
+#[test]
+fn non_negative_signed() {
+	// Non-negative values in signed formats are read as unsigned.
+	assert_eq!(decode(&[0xD1, 0, 5]), Value::UInt(5));
+}
+
+#[test]
+fn markers() {
+	for byte in 0..=u8::MAX {
+		assert_eq!(u8::from(Marker::from(byte)), byte);
+	}
+
+	let mut buffer = DefaultBuffer::default();
+	write_uint(&mut buffer, 300).unwrap();
+	assert_eq!(read_marker(&mut buffer).unwrap(), Marker::U16);
+}
+
+#[test]
+fn invalid() {
+	let error = |data: &[u8]| read_msgpack_value(&mut Buffer::from_slice(data)).unwrap_err().kind();
+	let invalid_data = ErrorKind::Io(io::ErrorKind::InvalidData);
+	assert_eq!(error(&[0xC1]), invalid_data);
+	assert_eq!(error(&[0xA2, 0xFF, 0xFE]), invalid_data);
+	assert_eq!(error(&[0x91; 300]), invalid_data);
+	assert_eq!(error(&[0xDB, 0xFF, 0xFF, 0xFF, 0xFF, b'a']), ErrorKind::Eos);
+	assert_eq!(error(&[0xCD, 0]), ErrorKind::Eos);
+}
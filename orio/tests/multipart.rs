@@ -0,0 +1,132 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::io;
+use pretty_assertions::assert_eq;
+use orio::{DefaultBuffer, ErrorKind, SIZE};
+use orio::streams::{BufSink, BufSource, Faults, FaultySource, Source, SourceExt};
+use orio::streams::multipart::MultipartReader;
+
+const BODY: &str = "preamble\r\n\
+	--frontier\r\n\
+	Content-Disposition: form-data; name=\"a\"\r\n\
+	\r\n\
+	first\r\n\
+	--frontier  \r\n\
+	Content-Disposition: form-data; name=\"b\"; filename=\"b.txt\"\r\n\
+	Content-Type: text/plain\r\n\
+	\r\n\
+	second\r\nline\r\n\
+	--frontier--\r\n\
+	epilogue";
+
+fn source(body: &str) -> DefaultBuffer<'static> {
+	let mut buffer = DefaultBuffer::default();
+	buffer.write_utf8(body).unwrap();
+	buffer
+}
+
+/// A part's header fields and body.
+type ReadPart = (Vec<(String, String)>, Vec<u8>);
+
+/// Reads all parts, returning each part's headers and body.
+fn parts<'d>(reader: &mut MultipartReader<'d, impl BufSource<'d, SIZE>, SIZE>) -> Vec<ReadPart> {
+	let mut parts = Vec::new();
+	while let Some(part) = reader.next_part().unwrap() {
+		let headers = part.headers
+						  .iter()
+						  .map(|(name, value)| (name.to_string(), value.to_string()))
+						  .collect();
+		let mut body = DefaultBuffer::default();
+		part.body.fill_all(&mut body).unwrap();
+		assert!(part.body.is_eos());
+		parts.push((headers, body.into_bytes()));
+	}
+	parts
+}
+
+#[test]
+fn read() {
+	let mut reader = MultipartReader::new(source(BODY), "frontier").unwrap();
+	assert_eq!(parts(&mut reader), [
+		(
+			vec![("Content-Disposition".into(), "form-data; name=\"a\"".into())],
+			b"first".to_vec()
+		),
+		(
+			vec![
+				("Content-Disposition".into(), "form-data; name=\"b\"; filename=\"b.txt\"".into()),
+				("Content-Type".into(), "text/plain".into()),
+			],
+			b"second\r\nline".to_vec()
+		),
+	]);
+	assert!(reader.next_part().unwrap().is_none());
+	assert_eq!(reader.into_inner(), b"\r\nepilogue");
+}
+
+#[test]
+fn header() {
+	let mut reader = MultipartReader::new(source(BODY), "frontier").unwrap();
+	let part = reader.next_part().unwrap().unwrap();
+	assert_eq!(part.header("content-disposition").unwrap().as_slice(), b"form-data; name=\"a\"");
+	assert!(part.header("Content-Type").is_none());
+}
+
+#[test]
+fn skip_unread_body() {
+	let mut reader = MultipartReader::new(source(BODY), "frontier").unwrap();
+	let part = reader.next_part().unwrap().unwrap();
+	let mut body = DefaultBuffer::default();
+	assert_eq!(part.body.fill(&mut body, 2).unwrap(), 2);
+	assert_eq!(body, b"fi");
+	let part = reader.next_part().unwrap().unwrap();
+	assert_eq!(part.headers.len(), 2);
+	assert!(reader.next_part().unwrap().is_none());
+}
+
+#[test]
+fn across_segments() {
+	// Split the delimiter across the end of the first segment.
+	let data = "x".repeat(SIZE - 20);
+	let body = format!("--b0undary\r\n\r\n{data}\r\n--b0undary\r\n\r\n\r\n--b0undary--");
+	let mut reader = MultipartReader::new(source(&body), "b0undary").unwrap();
+	assert_eq!(parts(&mut reader), [
+		(vec![], data.into_bytes()),
+		(vec![], vec![]),
+	]);
+}
+
+#[test]
+fn small_reads() {
+	let trickle = FaultySource::new(source(BODY), Faults::default().max_chunk(1)).buffered();
+	let mut reader = MultipartReader::new(trickle, "frontier").unwrap();
+	let mut expected = MultipartReader::new(source(BODY), "frontier").unwrap();
+	assert_eq!(parts(&mut reader), parts(&mut expected));
+}
+
+#[test]
+fn invalid() {
+	fn error(body: &str) -> ErrorKind {
+		let mut reader = MultipartReader::new(source(body), "b").unwrap();
+		loop {
+			match reader.next_part() {
+				Ok(Some(part)) => {
+					if let Err(err) = part.body.fill_all(&mut DefaultBuffer::default()) {
+						break err.kind()
+					}
+				}
+				Ok(None) => panic!("expected an error"),
+				Err(err) => break err.kind()
+			}
+		}
+	}
+
+	let invalid_data = ErrorKind::Io(io::ErrorKind::InvalidData);
+	assert_eq!(error("--b\r\n\r\nunterminated"), ErrorKind::Eos);
+	assert_eq!(error("no boundary"), ErrorKind::Eos);
+	assert_eq!(error("--b junk\r\n\r\n\r\n--b--"), invalid_data);
+	assert_eq!(error("--b\r\nno colon\r\n\r\n\r\n--b--"), invalid_data);
+	assert_eq!(error("--b\rA: 1\r\n\r\n\r\n--b--"), invalid_data, "lone carriage return");
+	assert!(MultipartReader::new(source(""), "").is_err());
+	assert!(MultipartReader::new(source(""), &"b".repeat(71)).is_err());
+}
@@ -0,0 +1,84 @@
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "serde")]
+
+use std::io;
+use pretty_assertions::assert_eq;
+use serde::{Deserialize, Serialize};
+use orio::{DefaultBuffer, ErrorKind, SIZE};
+use orio::streams::BufSink;
+use orio::streams::json::{read_json_lines, write_json_line};
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+struct Entry {
+	level: String,
+	message: String,
+}
+
+fn entry(level: &str, message: &str) -> Entry {
+	Entry {
+		level: level.into(),
+		message: message.into()
+	}
+}
+
+fn source(body: &str) -> DefaultBuffer<'static> {
+	let mut buffer = DefaultBuffer::default();
+	buffer.write_utf8(body).unwrap();
+	buffer
+}
+
+#[test]
+fn read() {
+	let body = "{\"level\":\"info\",\"message\":\"a\"}\n\
+				\n\
+				{\"level\": \"warn\", \"message\": \"b\\nc\"}\r\n\
+				{\"level\":\"error\",\"message\":\"d\"}";
+	let entries = read_json_lines(source(body)).collect::<Result<Vec<Entry>, _>>().unwrap();
+	assert_eq!(entries, [
+		entry("info", "a"),
+		entry("warn", "b\nc"),
+		entry("error", "d"),
+	]);
+}
+
+#[test]
+fn write() {
+	let mut buffer = DefaultBuffer::default();
+	let count = write_json_line(&mut buffer, &entry("info", "a\nb")).unwrap();
+	assert_eq!(count, 34);
+	write_json_line(&mut buffer, &[1, 2, 3]).unwrap();
+	assert_eq!(buffer, "{\"level\":\"info\",\"message\":\"a\\nb\"}\n[1,2,3]\n".as_bytes());
+}
+
+#[test]
+fn round_trip_across_segments() {
+	let entries: Vec<_> = (0..8).map(|i| entry("debug", &"x".repeat(SIZE / 3 + i))).collect();
+	let mut buffer = DefaultBuffer::default();
+	for entry in &entries {
+		write_json_line(&mut buffer, entry).unwrap();
+	}
+	let read = read_json_lines(buffer).collect::<Result<Vec<Entry>, _>>().unwrap();
+	assert_eq!(read, entries);
+}
+
+#[test]
+fn invalid() {
+	let mut lines = read_json_lines::<Entry, _, SIZE>(source("{\"level\":1}\n{\"level\":\"info\",\"message\":\"a\"}"));
+	let error = lines.next().unwrap().unwrap_err();
+	assert_eq!(error.kind(), ErrorKind::Io(io::ErrorKind::InvalidData));
+	// The invalid line is skipped.
+	assert_eq!(lines.next().unwrap().unwrap(), entry("info", "a"));
+	assert!(lines.next().is_none());
+}
+
+#[test]
+fn too_long() {
+	let mut lines = read_json_lines::<Entry, _, SIZE>(source(&"x".repeat(SIZE * 2))).with_max_len(16);
+	let error = lines.next().unwrap().unwrap_err();
+	assert_eq!(error.kind(), ErrorKind::Io(io::ErrorKind::InvalidData));
+
+	let line = "{\"level\":\"info\",\"message\":\"a\"}\r\n";
+	let mut lines = read_json_lines::<Entry, _, SIZE>(source(line)).with_max_len(line.len() - 2);
+	assert_eq!(lines.next().unwrap().unwrap(), entry("info", "a"));
+}
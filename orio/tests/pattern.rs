@@ -8,7 +8,9 @@ use std::iter::repeat_with;
 use std::ops::Range;
 use std::str::from_utf8_unchecked;
 use itertools::Itertools;
-use orio::pattern::{LineTerminator, Pattern};
+use orio::{ByteStr, ByteString, DefaultBuffer, SIZE};
+use orio::streams::BufSink;
+use orio::pattern::{ascii_ignore_case, LineTerminator, Pattern};
 use pretty_assertions::assert_eq;
 use quickcheck::{Arbitrary, Gen, TestResult};
 use quickcheck_macros::quickcheck;
@@ -52,6 +54,26 @@ fn match_slice(FullSplits { slice_a, slice_b, pattern, .. }: FullSplits<ALPHABET
 	TestResult::passed()
 }
 
+#[quickcheck]
+fn match_slice_ignore_case(FullSplits { slice_a, slice_b, pattern, .. }: FullSplits<ALPHABET, &'static str>) -> TestResult {
+	if pattern.is_empty() {
+		return TestResult::discard()
+	}
+	let swapped: String = pattern.chars().map(|c|
+		if c.is_ascii_lowercase() {
+			c.to_ascii_uppercase()
+		} else {
+			c.to_ascii_lowercase()
+		}
+	).collect();
+	let range = ascii_ignore_case(swapped.as_bytes()).find_in([slice_a.as_bytes(), slice_b.as_bytes()]);
+	let match_str = range.map(|range| &ALPHABET[range]);
+	// The case-swapped pattern may also match the opposite-case half of the alphabet
+	// first.
+	assert!(match_str.is_some_and(|str| str.eq_ignore_ascii_case(pattern)));
+	TestResult::passed()
+}
+
 impl<const HAYSTACK: &'static str> Arbitrary for FullSplits<HAYSTACK, u8> {
 	fn arbitrary(g: &mut Gen) -> Self {
 		let split_point = usize::arbitrary(g) % HAYSTACK.len();
@@ -255,3 +277,80 @@ fn match_line_terminator() {
 		);
 	}
 }
+
+#[test]
+fn match_repeated() {
+	let haystack = [&b"a,b,"[..], b"c,,d"];
+	let matches = b','.matches_in(haystack).collect_vec();
+	assert_eq!(matches, [1..2, 3..4, 5..6, 6..7]);
+	let matches = "ab".matches_in([&b"abxab"[..], b"a", b"bab"]).collect_vec();
+	assert_eq!(matches, [0..2, 3..5, 5..7, 7..9]);
+}
+
+#[test]
+fn match_ignore_case() {
+	let haystack = [&b"Host: example.com\r\nCONTENT-le"[..], b"ngth: 5\r\ncontent-length"];
+	let matches = ascii_ignore_case(b"Content-Length").matches_in(haystack).collect_vec();
+	assert_eq!(matches, [19..33, 38..52]);
+	assert!(!ascii_ignore_case(b"content-type").contained_in(haystack));
+	assert!(!ascii_ignore_case("\u{e9}".as_bytes()).contained_in(["\u{c9}".as_bytes()]));
+}
+
+#[test]
+fn match_owned() {
+	struct Delimited {
+		delimiter: Vec<u8>,
+	}
+
+	let haystack = [&b"key: a"[..], b"b\r", b"\nkey: c\r\n"];
+	let fields = Delimited { delimiter: b"\r\n".to_vec() };
+	assert_eq!(fields.delimiter.clone().matches_in(haystack).collect_vec(), [7..9, 15..17]);
+	assert_eq!((*b"\r\n").find_in(haystack), Some(7..9));
+	assert_eq!(b"key".matches_in(haystack).collect_vec(), [0..3, 9..12]);
+	assert_eq!(String::from(": ").find_in(haystack), Some(3..5));
+	assert_eq!((&ByteString::from("b\r\nk")).find_in(haystack), Some(6..10));
+	assert!(!vec![b'x'].contained_in(haystack));
+}
+
+#[quickcheck]
+fn match_segmented(haystack: Vec<u8>, start: usize, len: usize, splits: Vec<usize>) -> TestResult {
+	if haystack.is_empty() {
+		return TestResult::discard()
+	}
+
+	let start = start % haystack.len();
+	let len = len % (haystack.len() - start) + 1;
+	let needle = &haystack[start..start + len];
+	let mut fragments = Vec::new();
+	let mut rest = needle;
+	for split in splits {
+		let (fragment, tail) = rest.split_at(split % (rest.len() + 1));
+		fragments.push(fragment);
+		rest = tail;
+	}
+	fragments.push(rest);
+
+	let (a, b) = haystack.split_at(haystack.len() / 2);
+	let segmented = ByteStr::from(fragments);
+	assert_eq!(
+		(&segmented).matches_in([a, b]).collect_vec(),
+		needle.matches_in([a, b]).collect_vec()
+	);
+	TestResult::passed()
+}
+
+#[test]
+fn match_byte_str_in_buffer() {
+	let mut source = DefaultBuffer::default();
+	source.write_from_slice(&[b'x'; SIZE - 3]).unwrap();
+	source.write_from_slice(b"token").unwrap();
+	let token = source.as_byte_str().range(SIZE - 3..);
+	assert_eq!(token.slices().count(), 2, "token should span a segment boundary");
+
+	let mut target = DefaultBuffer::default();
+	target.write_from_slice(b"a token, another tok").unwrap();
+	target.write_from_slice(b"en").unwrap();
+	assert_eq!(target.find(&token), Some(2..7));
+	assert_eq!(target.as_byte_str().matches(&token).collect_vec(), [2..7, 17..22]);
+	assert!(!(&ByteStr::from(vec![&b"tok"[..], b"ens"])).contained_in([&b"a token"[..]]));
+}
@@ -0,0 +1,56 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::thread;
+use pretty_assertions::assert_eq;
+use orio::{copy, DefaultBuffer, SIZE};
+use orio::streams::{pipe, BufSink, Sink, Source, Stream};
+
+#[test]
+fn pipe_across_threads() {
+	let data: Vec<u8> = (0..SIZE * 5 + 3).map(|i| i as u8).collect();
+	let (mut sink, mut source) = pipe(2);
+
+	let writer = {
+		let data = data.clone();
+		thread::spawn(move || {
+			for chunk in data.chunks(1000) {
+				let mut buffer = DefaultBuffer::default();
+				buffer.write_from_slice(chunk).unwrap();
+				assert_eq!(sink.drain_all(&mut buffer).unwrap(), chunk.len());
+			}
+		})
+	};
+
+	let mut buffer = DefaultBuffer::default();
+	assert_eq!(copy::<SIZE>(&mut source, &mut buffer).unwrap(), data.len() as u64);
+	assert!(Source::<SIZE>::is_eos(&source));
+	assert_eq!(buffer, data);
+	writer.join().unwrap();
+}
+
+#[test]
+fn closed_source() {
+	let (mut sink, mut source) = pipe(0);
+	Stream::<SIZE>::close(&mut source).unwrap();
+	let mut buffer = DefaultBuffer::default();
+	buffer.write_from_slice(b"Hello").unwrap();
+	assert!(sink.drain_all(&mut buffer).unwrap_err().is_closed());
+	assert!(source.fill(&mut buffer, 5).unwrap_err().is_closed());
+}
+
+#[test]
+fn partial_drain() {
+	let data: Vec<u8> = (0..SIZE * 3).map(|i| i as u8).collect();
+	let (mut sink, mut source) = pipe(4);
+	let mut buffer = DefaultBuffer::default();
+	buffer.write_from_slice(&data).unwrap();
+	assert_eq!(sink.drain(&mut buffer, SIZE + 5).unwrap(), SIZE + 5);
+	assert_eq!(buffer, &data[SIZE + 5..], "undrained bytes should remain in the buffer");
+	drop(sink);
+
+	let mut received = DefaultBuffer::default();
+	assert_eq!(source.fill(&mut received, 5).unwrap(), 5);
+	assert_eq!(source.fill_all(&mut received).unwrap(), SIZE);
+	assert!(Source::<SIZE>::is_eos(&source));
+	assert_eq!(received, &data[..SIZE + 5]);
+}
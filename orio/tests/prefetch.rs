@@ -0,0 +1,117 @@
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "shared-pool")]
+
+use std::io;
+use pretty_assertions::assert_eq;
+use orio::{copy, DefaultBuffer, ErrorKind, SIZE};
+use orio::streams::{PrefetchSource, ReaderSource, Source, Stream};
+
+fn data() -> Vec<u8> {
+	(0..SIZE * 5 + 3).map(|i| i as u8).collect()
+}
+
+fn prefetch<S: Source<'static, SIZE> + Send + 'static>(source: S, capacity: usize) -> PrefetchSource<'static, S, SIZE> {
+	PrefetchSource::new(source, capacity)
+}
+
+#[test]
+fn read() {
+	let data = data();
+	let mut source = prefetch(ReaderSource::from(io::Cursor::new(data.clone())), 2);
+	let mut buffer = DefaultBuffer::default();
+	assert_eq!(copy::<SIZE>(&mut source, &mut buffer).unwrap(), data.len() as u64);
+	assert!(source.is_eos());
+	assert_eq!(buffer, data);
+}
+
+#[test]
+fn into_inner() {
+	let data = data();
+	let mut source = prefetch(ReaderSource::from(io::Cursor::new(data.clone())), 1);
+	let mut buffer = DefaultBuffer::default();
+	assert_eq!(source.fill(&mut buffer, 10).unwrap(), 10);
+
+	// Data read ahead is returned along with the inner source.
+	let (mut inner, mut read_ahead) = source.into_inner().unwrap();
+	let count = read_ahead.count();
+	assert_eq!(copy::<SIZE>(&mut read_ahead, &mut buffer).unwrap() as usize, count);
+	copy::<SIZE>(&mut inner, &mut buffer).unwrap();
+	assert_eq!(buffer, data);
+}
+
+/// A reader returning `len` bytes, then failing.
+struct FailingReader {
+	len: usize,
+}
+
+impl io::Read for FailingReader {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		if self.len == 0 {
+			return Err(io::ErrorKind::ConnectionReset.into())
+		}
+
+		let len = self.len.min(buf.len());
+		buf[..len].fill(1);
+		self.len -= len;
+		Ok(len)
+	}
+}
+
+#[test]
+fn error() {
+	let mut source = prefetch(ReaderSource::from(FailingReader { len: 5 }), 2);
+	let mut buffer = DefaultBuffer::default();
+	// Data read before the error is returned first.
+	assert_eq!(source.fill(&mut buffer, 10).unwrap(), 5);
+	assert_eq!(
+		source.fill(&mut buffer, 10).unwrap_err().kind(),
+		ErrorKind::Io(io::ErrorKind::ConnectionReset)
+	);
+}
+
+/// A reader with no data available on every other read, like a non-blocking
+/// reader waiting on IO.
+struct StallingReader {
+	data: io::Cursor<Vec<u8>>,
+	stalled: bool,
+}
+
+impl io::Read for StallingReader {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		self.stalled = !self.stalled;
+		if self.stalled {
+			return Err(io::ErrorKind::WouldBlock.into())
+		}
+		let len = buf.len().min(100);
+		self.data.read(&mut buf[..len])
+	}
+}
+
+#[test]
+fn stalling() {
+	let data = data();
+	let reader = StallingReader { data: io::Cursor::new(data.clone()), stalled: false };
+	let mut source = prefetch(ReaderSource::from(reader), 2);
+	let mut buffer = DefaultBuffer::default();
+	while !source.is_eos() {
+		source.fill(&mut buffer, SIZE).unwrap();
+	}
+	assert_eq!(buffer, data, "stalled reads shouldn't end the stream");
+}
+
+#[test]
+fn close() {
+	let mut source = prefetch(ReaderSource::from(io::Cursor::new(data())), 1);
+	source.close().unwrap();
+	assert!(source.is_closed());
+	assert!(source.fill(&mut DefaultBuffer::default(), 1).unwrap_err().is_closed());
+}
+
+#[test]
+fn close_into_inner() {
+	let mut source = prefetch(ReaderSource::from(io::Cursor::new(data())), 1);
+	source.close().unwrap();
+	// The inner source was closed along with the prefetching source.
+	assert!(source.into_inner().is_none());
+}
@@ -0,0 +1,55 @@
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(unix)]
+
+use std::process::{Command, Stdio};
+use pretty_assertions::assert_eq;
+use orio::{Buffer, copy, DefaultBuffer, SIZE};
+use orio::streams::{BufSink, ChildStderrSource, ChildStdinSink, ChildStdoutSource, pipe_command, Sink, Stream};
+
+#[test]
+fn pipe_through_cat() {
+	let data = (0..SIZE * 8).map(|i| i as u8).collect::<Vec<_>>();
+	let mut input = Buffer::from_slice(&data);
+	let (status, output) = pipe_command(&mut Command::new("cat"), &mut input).unwrap();
+	assert!(status.success());
+	assert!(input.is_empty());
+	assert_eq!(output, data);
+}
+
+#[test]
+fn pipe_segments() {
+	// Larger than the pipe buffers, written from many segments.
+	let data = (0..SIZE * 256).map(|i| (i / 7) as u8).collect::<Vec<_>>();
+	let mut input = DefaultBuffer::default();
+	input.write_from_slice(&data).unwrap();
+	let (status, output) = pipe_command(&mut Command::new("cat"), &mut input).unwrap();
+	assert!(status.success());
+	assert!(input.is_empty());
+	assert_eq!(output, data);
+}
+
+#[test]
+fn child_streams() {
+	let mut child = Command::new("sh")
+		.args(["-c", "cat; echo error >&2"])
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.spawn()
+		.unwrap();
+	let mut stdin = ChildStdinSink::from(child.stdin.take().unwrap());
+	let mut stdout = ChildStdoutSource::from(child.stdout.take().unwrap());
+	let mut stderr = ChildStderrSource::from(child.stderr.take().unwrap());
+
+	Sink::<SIZE>::drain_all(&mut stdin, &mut Buffer::from_utf8("input")).unwrap();
+	Stream::<SIZE>::close(&mut stdin).unwrap();
+
+	let mut output = DefaultBuffer::default();
+	copy(&mut stdout, &mut output).unwrap();
+	let mut error = DefaultBuffer::default();
+	copy(&mut stderr, &mut error).unwrap();
+	assert!(child.wait().unwrap().success());
+	assert_eq!(output, b"input");
+	assert_eq!(error, b"error\n");
+}
@@ -0,0 +1,118 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::io;
+use pretty_assertions::assert_eq;
+use quickcheck_macros::quickcheck;
+use orio::{Buffer, DefaultBuffer, ErrorKind, SIZE};
+use orio::streams::BufSink;
+use orio::streams::proto::{
+	read_key, read_length_delimited, read_varint, skip_field, write_key, write_length_delimited,
+	write_varint, WireType, MAX_FIELD_NUMBER
+};
+
+// Example from the encoding guide: field 1 set to 150.
+const TEST1: &[u8] = &[0x08, 0x96, 0x01];
+
+#[test]
+fn read() {
+	let mut buffer = Buffer::from_slice(TEST1);
+	assert_eq!(read_key(&mut buffer).unwrap(), Some((1, WireType::Varint)));
+	assert_eq!(read_varint(&mut buffer).unwrap(), 150);
+	assert_eq!(read_key(&mut buffer).unwrap(), None);
+}
+
+#[test]
+fn write() {
+	let mut sink = DefaultBuffer::default();
+	write_key(&mut sink, 1, WireType::Varint).unwrap();
+	write_varint(&mut sink, 150).unwrap();
+	assert_eq!(sink, TEST1);
+	assert!(write_key(&mut sink, 0, WireType::Varint).is_err());
+	assert!(write_key(&mut sink, MAX_FIELD_NUMBER + 1, WireType::Varint).is_err());
+}
+
+#[quickcheck]
+fn varint(value: u64) {
+	let mut buffer = DefaultBuffer::default();
+	write_varint(&mut buffer, value).unwrap();
+	assert_eq!(read_varint(&mut buffer).unwrap(), value);
+	assert!(buffer.is_empty());
+}
+
+#[test]
+fn length_delimited() {
+	let data = vec![0xAB; SIZE + 100];
+	let mut sink = DefaultBuffer::default();
+	write_key(&mut sink, 2, WireType::Len).unwrap();
+	write_length_delimited(&mut sink, &mut Buffer::from_slice(&data)).unwrap();
+	write_key(&mut sink, 3, WireType::Len).unwrap();
+	write_length_delimited(&mut sink, &mut Buffer::from_slice(b"hi")).unwrap();
+
+	assert_eq!(read_key(&mut sink).unwrap(), Some((2, WireType::Len)));
+	let count = sink.count();
+	assert!(read_length_delimited(&mut sink, SIZE).is_err());
+	// Nothing is consumed when the length is too long.
+	assert_eq!(sink.count(), count);
+	assert_eq!(read_length_delimited(&mut sink, data.len()).unwrap(), data.as_slice());
+	assert_eq!(read_key(&mut sink).unwrap(), Some((3, WireType::Len)));
+	assert_eq!(read_length_delimited(&mut sink, 2).unwrap(), b"hi");
+}
+
+#[test]
+fn skip() {
+	let mut sink = DefaultBuffer::default();
+	write_key(&mut sink, 1, WireType::Varint).unwrap();
+	write_varint(&mut sink, u64::MAX).unwrap();
+	write_key(&mut sink, 2, WireType::I64).unwrap();
+	sink.write_u64_le(1).unwrap();
+	write_key(&mut sink, 3, WireType::I32).unwrap();
+	sink.write_u32_le(1).unwrap();
+	write_key(&mut sink, 4, WireType::Len).unwrap();
+	write_length_delimited(&mut sink, &mut Buffer::from_slice(b"skipped")).unwrap();
+	write_key(&mut sink, 5, WireType::StartGroup).unwrap();
+	write_key(&mut sink, 1, WireType::StartGroup).unwrap();
+	write_key(&mut sink, 1, WireType::EndGroup).unwrap();
+	write_key(&mut sink, 2, WireType::Varint).unwrap();
+	write_varint(&mut sink, 1).unwrap();
+	write_key(&mut sink, 5, WireType::EndGroup).unwrap();
+	write_key(&mut sink, 6, WireType::Varint).unwrap();
+	write_varint(&mut sink, 42).unwrap();
+
+	let mut fields = Vec::new();
+	while let Some((field, wire_type)) = read_key(&mut sink).unwrap() {
+		fields.push(field);
+		if field == 6 {
+			assert_eq!(read_varint(&mut sink).unwrap(), 42);
+		} else {
+			skip_field(&mut sink, field, wire_type).unwrap();
+		}
+	}
+	assert_eq!(fields, [1, 2, 3, 4, 5, 6]);
+}
+
+#[test]
+fn invalid() {
+	let invalid_data = ErrorKind::Io(io::ErrorKind::InvalidData);
+	let error = |data: &[u8]| read_key(&mut Buffer::from_slice(data)).unwrap_err().kind();
+	assert_eq!(error(&[0x0F]), invalid_data);
+	assert_eq!(error(&[0x00]), invalid_data);
+	assert_eq!(error(&[0x80]), ErrorKind::Eos);
+	assert_eq!(error(&[0xFF; 11]), invalid_data);
+
+	let mut buffer = Buffer::from_slice(&[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x02]);
+	assert_eq!(read_varint(&mut buffer).unwrap_err().kind(), invalid_data);
+	assert_eq!(buffer.count(), 10);
+
+	let mut buffer = Buffer::from_slice(&[0x05, 1, 2]);
+	assert_eq!(skip_field(&mut buffer, 1, WireType::Len).unwrap_err().kind(), ErrorKind::Eos);
+	let mut buffer = DefaultBuffer::default();
+	assert_eq!(skip_field(&mut buffer, 1, WireType::EndGroup).unwrap_err().kind(), invalid_data);
+
+	// End keys must match their group's field number.
+	let mut buffer = Buffer::from_slice(&[0x14]);
+	assert_eq!(skip_field(&mut buffer, 1, WireType::StartGroup).unwrap_err().kind(), invalid_data);
+
+	// Deeply nested groups fail rather than overflowing the stack.
+	let mut buffer = Buffer::from_slice(&[0x0B; 100_000]);
+	assert_eq!(skip_field(&mut buffer, 1, WireType::StartGroup).unwrap_err().kind(), invalid_data);
+}
@@ -0,0 +1,61 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use pretty_assertions::assert_eq;
+use orio::{Buffer, DefaultBuffer, ErrorKind, SIZE};
+use orio::streams::{BufSink, Recording, RecordingSource, ReplaySource, Source};
+
+#[test]
+fn record_replay() {
+	let data = b"Hello world! This is a recorded stream.";
+	let mut source = RecordingSource::<_, SIZE>::new(Buffer::from_slice(data));
+	let mut sink = DefaultBuffer::default();
+	for count in [5, 1, 7, 100, 3] {
+		source.fill(&mut sink, count).unwrap();
+	}
+	assert_eq!(sink, data);
+
+	let (_, recording) = source.into_inner();
+	assert_eq!(recording.chunks(), &[5, 1, 7, 26, 0]);
+	assert_eq!(recording.data(), data);
+
+	let mut replay = ReplaySource::new(recording);
+	let mut sink = DefaultBuffer::default();
+	let mut sizes = Vec::new();
+	while !Source::<SIZE>::is_eos(&replay) {
+		sizes.push(replay.fill(&mut sink, SIZE).unwrap());
+	}
+	assert_eq!(sizes, [5, 1, 7, 26, 0]);
+	assert_eq!(sink, data);
+}
+
+#[test]
+fn split_chunk() {
+	let mut recording = Recording::default();
+	recording.push_chunk(b"abcdef");
+	let mut replay = ReplaySource::from(recording);
+	let mut sink = DefaultBuffer::default();
+	assert_eq!(replay.fill(&mut sink, 4).unwrap(), 4);
+	assert_eq!(replay.fill(&mut sink, 4).unwrap(), 2);
+	assert!(Source::<SIZE>::is_eos(&replay));
+	assert_eq!(sink, b"abcdef");
+}
+
+#[test]
+fn save_load() {
+	let mut recording = Recording::default();
+	recording.push_chunk(b"abc");
+	recording.push_chunk(b"");
+	recording.push_chunk(b"defgh");
+
+	let mut buffer = DefaultBuffer::default();
+	recording.write_to(&mut buffer).unwrap();
+	assert_eq!(Recording::read_from(&mut buffer).unwrap(), recording);
+}
+
+#[test]
+fn load_corrupt_length() {
+	let mut buffer = DefaultBuffer::default();
+	buffer.write_u64(u64::MAX).unwrap();
+	buffer.write_from_slice(b"abc").unwrap();
+	assert_eq!(Recording::read_from(&mut buffer).unwrap_err().kind(), ErrorKind::Eos);
+}
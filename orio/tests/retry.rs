@@ -0,0 +1,153 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::io;
+use std::io::ErrorKind;
+use orio::{Buffer, BufferResult, DefaultBuffer, SIZE, StreamResult};
+use orio::pool::Pool;
+use orio::streams::{BufSink, BufSource, RetryPolicy, RetrySink, RetrySource, Sink, Source, Stream};
+
+/// Fails with each error kind in turn before reading or writing normally.
+struct Flaky {
+	errors: Vec<ErrorKind>,
+	data: Vec<u8>,
+}
+
+impl Flaky {
+	fn new(errors: Vec<ErrorKind>, data: &[u8]) -> Self {
+		Self { errors, data: data.to_vec() }
+	}
+
+	fn next_error(&mut self) -> io::Result<()> {
+		match self.errors.pop() {
+			Some(kind) => Err(kind.into()),
+			None => Ok(())
+		}
+	}
+}
+
+impl<const N: usize> Stream<N> for Flaky {
+	fn is_closed(&self) -> bool { false }
+
+	fn close(&mut self) -> StreamResult { Ok(()) }
+}
+
+impl<'d, const N: usize> Source<'d, N> for Flaky {
+	fn is_eos(&self) -> bool {
+		self.data.is_empty()
+	}
+
+	fn fill(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		self.next_error()?;
+		let count = count.min(self.data.len());
+		sink.write_from_slice(&self.data[..count])?;
+		self.data.drain(..count);
+		Ok(count)
+	}
+}
+
+impl<'d, const N: usize> Sink<'d, N> for Flaky {
+	fn drain(&mut self, source: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		self.next_error()?;
+		let start = self.data.len();
+		self.data.resize(start + count.min(source.count()), 0);
+		source.read_slice_exact(&mut self.data[start..])?;
+		Ok(self.data.len() - start)
+	}
+}
+
+#[test]
+fn retry_interrupted() {
+	let flaky = Flaky::new(vec![ErrorKind::Interrupted; 3], b"Hello");
+	let mut source = RetrySource::<_, SIZE>::new(flaky, RetryPolicy::default());
+	let mut buffer = DefaultBuffer::default();
+	assert_eq!(source.fill(&mut buffer, 5).unwrap(), 5);
+	assert_eq!(buffer, b"Hello"[..]);
+}
+
+#[test]
+fn retry_limit() {
+	let flaky = Flaky::new(vec![ErrorKind::Interrupted; 3], b"Hello");
+	let policy = RetryPolicy::default().max_retries(2);
+	let mut source = RetrySource::<_, SIZE>::new(flaky, policy);
+	let mut buffer = DefaultBuffer::default();
+	assert!(source.fill(&mut buffer, 5).unwrap_err().is_interrupted());
+}
+
+#[test]
+fn retry_would_block() {
+	let flaky = Flaky::new(vec![ErrorKind::WouldBlock], b"Hello");
+	let mut source = RetrySource::<_, SIZE>::new(flaky, RetryPolicy::default());
+	let mut buffer = DefaultBuffer::default();
+	assert!(source.fill(&mut buffer, 5).unwrap_err().is_would_block());
+
+	let flaky = Flaky::new(vec![ErrorKind::WouldBlock; 2], b"Hello");
+	let policy = RetryPolicy::default().retry_would_block(true);
+	let mut source = RetrySource::<_, SIZE>::new(flaky, policy);
+	assert_eq!(source.fill(&mut buffer, 5).unwrap(), 5);
+}
+
+#[test]
+fn retry_sink() {
+	let flaky = Flaky::new(vec![ErrorKind::Interrupted; 2], b"");
+	let mut sink = RetrySink::<_, SIZE>::new(flaky, RetryPolicy::default());
+	let mut buffer = DefaultBuffer::from_slice(b"Hello");
+	assert_eq!(sink.drain_all(&mut buffer).unwrap(), 5);
+	assert_eq!(sink.into_inner().data, b"Hello");
+}
+
+/// Moves up to `partial` bytes, then fails with an interrupted error, once.
+struct Partial {
+	partial: Option<usize>,
+	data: Vec<u8>,
+}
+
+impl<const N: usize> Stream<N> for Partial {
+	fn is_closed(&self) -> bool { false }
+
+	fn close(&mut self) -> StreamResult { Ok(()) }
+}
+
+impl<'d, const N: usize> Source<'d, N> for Partial {
+	fn is_eos(&self) -> bool {
+		self.data.is_empty()
+	}
+
+	fn fill(&mut self, sink: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		let partial = self.partial.take();
+		let count = count.min(partial.unwrap_or(count)).min(self.data.len());
+		sink.write_from_slice(&self.data[..count])?;
+		self.data.drain(..count);
+		match partial {
+			Some(_) => Err(io::Error::from(ErrorKind::Interrupted).into()),
+			None => Ok(count)
+		}
+	}
+}
+
+impl<'d, const N: usize> Sink<'d, N> for Partial {
+	fn drain(&mut self, source: &mut Buffer<'d, N, impl Pool<N>>, count: usize) -> BufferResult<usize> {
+		let partial = self.partial.take();
+		let start = self.data.len();
+		self.data.resize(start + count.min(partial.unwrap_or(count)).min(source.count()), 0);
+		source.read_slice_exact(&mut self.data[start..])?;
+		match partial {
+			Some(_) => Err(io::Error::from(ErrorKind::Interrupted).into()),
+			None => Ok(self.data.len() - start)
+		}
+	}
+}
+
+#[test]
+fn retry_partial() {
+	let partial = Partial { partial: Some(3), data: b"Hello, world!".to_vec() };
+	let mut source = RetrySource::<_, SIZE>::new(partial, RetryPolicy::default());
+	let mut buffer = DefaultBuffer::default();
+	assert_eq!(source.fill(&mut buffer, 5).unwrap(), 5);
+	assert_eq!(buffer, b"Hello"[..]);
+
+	let partial = Partial { partial: Some(3), data: Vec::new() };
+	let mut sink = RetrySink::<_, SIZE>::new(partial, RetryPolicy::default());
+	let mut buffer = DefaultBuffer::from_slice(b"Hello, world!");
+	assert_eq!(sink.drain(&mut buffer, 5).unwrap(), 5);
+	assert_eq!(sink.into_inner().data, b"Hello");
+}
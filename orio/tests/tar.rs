@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::io;
+use pretty_assertions::assert_eq;
+use orio::{Buffer, DefaultBuffer, ErrorKind};
+use orio::streams::Source;
+use orio::streams::tar::{EntryType, TarHeader, TarReader, TarWriter};
+
+fn archive<'d>(entries: &'d [(TarHeader, &'d [u8])]) -> DefaultBuffer<'d> {
+	let mut writer = TarWriter::new(DefaultBuffer::default());
+	for (header, contents) in entries {
+		writer.append(header, &mut Buffer::from_slice(contents)).unwrap();
+	}
+	writer.finish().unwrap();
+	writer.into_inner()
+}
+
+#[test]
+fn round_trip() {
+	let long_path = format!("{}/{}", "d".repeat(120), "f".repeat(90));
+	let entries: [(TarHeader, &[u8]); 4] = [
+		(TarHeader::directory("dir/"), b""),
+		(TarHeader { mtime: 1_700_000_000, user_name: "orio".into(), ..TarHeader::file("dir/a.txt", 6) }, b"hello\n"),
+		(TarHeader::file(long_path, 1000), &[7; 1000]),
+		(TarHeader { link_name: "a.txt".into(), entry_type: EntryType::Symlink, ..TarHeader::file("dir/b", 0) }, b""),
+	];
+	let buffer = archive(&entries);
+	assert_eq!(buffer.count(), 512 * (1 + 2 + 3 + 1 + 2));
+
+	let mut reader = TarReader::new(buffer);
+	for (header, contents) in &entries {
+		let entry = reader.next_entry().unwrap().expect("entry should be present");
+		assert_eq!(&entry.header, header);
+		let mut read = DefaultBuffer::default();
+		entry.contents.fill_all(&mut read).unwrap();
+		assert_eq!(read, *contents);
+	}
+	assert!(reader.next_entry().unwrap().is_none());
+	assert!(reader.next_entry().unwrap().is_none());
+	assert!(reader.into_inner().is_empty(), "end blocks should be consumed");
+}
+
+#[test]
+fn skip_unread_contents() {
+	let entries: [(TarHeader, &[u8]); 2] = [
+		(TarHeader::file("a", 700), &[1; 700]),
+		(TarHeader::file("b", 3), b"abc"),
+	];
+	let buffer = archive(&entries);
+	let mut reader = TarReader::new(buffer);
+	let entry = reader.next_entry().unwrap().unwrap();
+	let mut read = DefaultBuffer::default();
+	assert_eq!(entry.contents.fill(&mut read, 10).unwrap(), 10);
+
+	let entry = reader.next_entry().unwrap().unwrap();
+	assert_eq!(entry.header.path, "b");
+	let mut read = DefaultBuffer::default();
+	entry.contents.fill_all(&mut read).unwrap();
+	assert_eq!(read, b"abc");
+}
+
+#[test]
+fn invalid() {
+	let entries: [(TarHeader, &[u8]); 1] = [(TarHeader::file("a", 3), b"abc")];
+	let mut bytes = archive(&entries).into_bytes();
+	bytes[0] = b'b';
+	assert_eq!(
+		TarReader::new(Buffer::from_slice(&bytes)).next_entry().err().unwrap().kind(),
+		ErrorKind::Io(io::ErrorKind::InvalidData),
+		"checksum mismatch"
+	);
+
+	bytes[0] = b'a';
+	let mut reader = TarReader::new(Buffer::from_slice(&bytes[..600]));
+	assert!(reader.next_entry().unwrap().is_some());
+	assert_eq!(reader.next_entry().err().unwrap().kind(), ErrorKind::Eos, "truncated");
+
+	let mut writer = TarWriter::new(DefaultBuffer::default());
+	assert_eq!(
+		writer.append(&TarHeader::file("a", 4), &mut Buffer::from_slice(b"abc")).err().unwrap().kind(),
+		ErrorKind::Eos,
+		"short contents"
+	);
+	assert_eq!(
+		writer.append(&TarHeader::file("a".repeat(101), 0), &mut DefaultBuffer::default()).err().unwrap().kind(),
+		ErrorKind::Io(io::ErrorKind::InvalidInput),
+		"path too long"
+	);
+}
+
+#[test]
+fn entry_too_large() {
+	let mut block = TarHeader::file("a", 0).encode().unwrap();
+	// Encode the largest base-256 size, then recompute the checksum.
+	block[124..136].copy_from_slice(&[0x80, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]);
+	block[148..156].fill(b' ');
+	let checksum: u64 = block.iter().map(|&b| b as u64).sum();
+	block[148..156].copy_from_slice(format!("{checksum:06o}\0 ").as_bytes());
+	assert_eq!(
+		TarReader::new(Buffer::from_slice(&block)).next_entry().err().unwrap().kind(),
+		ErrorKind::Io(io::ErrorKind::InvalidData)
+	);
+
+	let mut writer = TarWriter::new(DefaultBuffer::default());
+	assert_eq!(
+		writer.append(&TarHeader::file("a", u64::MAX), &mut DefaultBuffer::default()).err().unwrap().kind(),
+		ErrorKind::Io(io::ErrorKind::InvalidInput)
+	);
+}
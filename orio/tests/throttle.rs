@@ -0,0 +1,44 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::{Duration, Instant};
+use pretty_assertions::assert_eq;
+use orio::{Buffer, DefaultBuffer, SIZE};
+use orio::streams::{Sink, Source, ThrottleSink, ThrottleSource};
+
+const DATA: &[u8] = &[0xA5; 300];
+
+#[test]
+fn throttle_source() {
+	let mut source = ThrottleSource::<_, SIZE>::new(Buffer::from_slice(DATA), 1000).with_burst(100);
+	let mut sink = DefaultBuffer::default();
+	let start = Instant::now();
+	assert_eq!(source.fill(&mut sink, 100).unwrap(), 100);
+	assert!(start.elapsed() < Duration::from_millis(50), "initial burst should not wait");
+	assert_eq!(source.fill_all(&mut sink).unwrap(), 200);
+	assert!(start.elapsed() >= Duration::from_millis(150));
+	assert_eq!(sink, DATA);
+}
+
+#[test]
+fn throttle_single_bytes() {
+	let mut source = ThrottleSource::<_, SIZE>::new(Buffer::from_slice(&DATA[..50]), 1000).with_burst(1);
+	let mut sink = DefaultBuffer::default();
+	assert_eq!(source.fill_all(&mut sink).unwrap(), 50, "every wait should acquire a whole token");
+	assert_eq!(sink, &DATA[..50]);
+
+	let mut sink = ThrottleSink::<_, SIZE>::new(DefaultBuffer::default(), 1000).with_burst(1);
+	let mut source = Buffer::from_slice(&DATA[..50]);
+	assert_eq!(sink.drain_all(&mut source).unwrap(), 50, "every wait should acquire a whole token");
+	assert_eq!(sink.into_inner(), &DATA[..50]);
+}
+
+#[test]
+fn throttle_sink() {
+	let mut sink = ThrottleSink::<_, SIZE>::new(DefaultBuffer::default(), 1000).with_burst(100);
+	let mut source = Buffer::from_slice(DATA);
+	let start = Instant::now();
+	assert_eq!(sink.drain(&mut source, 250).unwrap(), 100, "drain should be limited to burst size");
+	assert_eq!(sink.drain_all(&mut source).unwrap(), 200);
+	assert!(start.elapsed() >= Duration::from_millis(150));
+	assert_eq!(sink.into_inner(), DATA);
+}
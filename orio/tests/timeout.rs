@@ -0,0 +1,27 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::io::Write;
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+use orio::{DefaultBuffer, SIZE};
+use orio::streams::{ReaderSource, Source, TimeoutSource};
+
+#[test]
+fn fill_times_out() {
+	let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+	let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+	let (mut peer, _) = listener.accept().unwrap();
+
+	let mut source = TimeoutSource::<_, SIZE>::new(
+		ReaderSource::from(stream),
+		Duration::from_millis(50)
+	).unwrap();
+	let mut buffer = DefaultBuffer::default();
+	let error = source.fill(&mut buffer, 5).unwrap_err();
+	assert!(error.is_timed_out(), "should time out: {error:?}");
+
+	peer.write_all(b"Hello").unwrap();
+	assert_eq!(source.fill(&mut buffer, 5).unwrap(), 5);
+	assert_eq!(buffer, b"Hello"[..]);
+	assert_eq!(source.timeout(), Duration::from_millis(50));
+}
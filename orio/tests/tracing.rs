@@ -0,0 +1,107 @@
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "tracing")]
+
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use pretty_assertions::assert_eq;
+use tracing::{Event, Id, Metadata, Subscriber};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Record};
+use orio::{DefaultBuffer, SIZE};
+use orio::pool::{DefaultPoolContainer, Pool};
+use orio::streams::{BufSink, Sink};
+
+/// A recorded span or event, with its name or message and fields.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct Trace {
+	name: String,
+	fields: Vec<(&'static str, String)>,
+}
+
+impl Visit for Trace {
+	fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+		if field.name() == "message" {
+			self.name = format!("{value:?}");
+		} else {
+			self.fields.push((field.name(), format!("{value:?}")));
+		}
+	}
+}
+
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Vec<Trace>>>);
+
+impl Recorder {
+	fn record(&self, f: impl FnOnce()) -> Vec<Trace> {
+		tracing::subscriber::with_default(self.clone(), f);
+		self.0.lock().unwrap().drain(..).collect()
+	}
+}
+
+impl Subscriber for Recorder {
+	fn enabled(&self, _: &Metadata<'_>) -> bool { true }
+
+	fn new_span(&self, span: &Attributes<'_>) -> Id {
+		let mut trace = Trace { name: span.metadata().name().into(), ..Trace::default() };
+		span.record(&mut trace);
+		let mut traces = self.0.lock().unwrap();
+		traces.push(trace);
+		Id::from_u64(traces.len() as u64)
+	}
+
+	fn record(&self, _: &Id, _: &Record<'_>) { }
+
+	fn record_follows_from(&self, _: &Id, _: &Id) { }
+
+	fn event(&self, event: &Event<'_>) {
+		let mut trace = Trace::default();
+		event.record(&mut trace);
+		self.0.lock().unwrap().push(trace);
+	}
+
+	fn enter(&self, _: &Id) { }
+
+	fn exit(&self, _: &Id) { }
+}
+
+fn trace(name: &str, fields: &[(&'static str, usize)]) -> Trace {
+	Trace {
+		name: name.into(),
+		fields: fields.iter().map(|&(name, value)| (name, value.to_string())).collect(),
+	}
+}
+
+#[test]
+fn drain() {
+	let mut source = DefaultBuffer::default();
+	source.write_from_slice(&[0; SIZE * 2 + 10]).unwrap();
+	let mut sink = DefaultBuffer::default();
+	let traces = Recorder::default().record(|| {
+		sink.drain(&mut source, SIZE * 2 + 5).unwrap();
+	});
+	assert_eq!(traces[..5], [
+		trace("drain", &[("requested", SIZE * 2 + 5)]),
+		trace("fill", &[("requested", SIZE * 2 + 5)]),
+		trace("moving segments", &[("bytes", SIZE * 2), ("segments", 2)]),
+		trace("copying bytes", &[("bytes", 5)]),
+		trace("reserving segments", &[("bytes", 5), ("segments", 1)]),
+	]);
+	// Whether the claimed segment is allocated depends on the state of the pool.
+	assert_eq!(traces[5].name, "claiming segments");
+	assert_eq!(traces[5].fields[0], ("segments", "1".into()));
+}
+
+#[test]
+fn claim_collect() {
+	let pool = DefaultPoolContainer::default();
+	let traces = Recorder::default().record(|| {
+		let mut segments = Vec::new();
+		pool.claim_count(&mut segments, 3).unwrap();
+		pool.collect(segments).unwrap();
+	});
+	assert_eq!(traces, [
+		trace("claiming segments", &[("segments", 3), ("allocated", 3)]),
+		trace("collecting segments", &[("segments", 3)]),
+	]);
+}
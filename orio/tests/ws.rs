@@ -0,0 +1,122 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::io;
+use pretty_assertions::assert_eq;
+use orio::{Buffer, DefaultBuffer, ErrorKind};
+use orio::streams::BufSink;
+use orio::streams::ws::{read_frame, read_message, write_frame, Frame, Opcode};
+
+// Examples from RFC 6455 section 5.7.
+const UNMASKED_HELLO: &[u8] = &[0x81, 0x05, 0x48, 0x65, 0x6c, 0x6c, 0x6f];
+const MASKED_HELLO: &[u8] = &[0x81, 0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d, 0x51, 0x58];
+const FRAGMENTED_HELLO: &[u8] = &[0x01, 0x03, 0x48, 0x65, 0x6c, 0x80, 0x02, 0x6c, 0x6f];
+const MASK_KEY: [u8; 4] = [0x37, 0xfa, 0x21, 0x3d];
+
+#[test]
+fn read() {
+	for data in [UNMASKED_HELLO, MASKED_HELLO] {
+		let mut buffer = Buffer::from_slice(data);
+		let frame = read_frame(&mut buffer, 1024).unwrap();
+		assert!(frame.fin);
+		assert_eq!(frame.opcode, Opcode::Text);
+		assert_eq!(frame.payload, b"Hello");
+		assert!(buffer.is_empty());
+	}
+
+	let mut buffer = Buffer::from_slice(FRAGMENTED_HELLO);
+	let frame = read_frame(&mut buffer, 1024).unwrap();
+	assert!(!frame.fin);
+	assert_eq!(frame.opcode, Opcode::Text);
+	assert_eq!(frame.payload, b"Hel");
+}
+
+#[test]
+fn write() {
+	let mut sink = DefaultBuffer::default();
+	write_frame(&mut sink, Frame::new(Opcode::Text, Buffer::from_slice(b"Hello")), None).unwrap();
+	assert_eq!(sink, UNMASKED_HELLO);
+
+	let mut sink = DefaultBuffer::default();
+	write_frame(&mut sink, Frame::new(Opcode::Text, Buffer::from_slice(b"Hello")), Some(MASK_KEY)).unwrap();
+	assert_eq!(sink, MASKED_HELLO);
+}
+
+#[test]
+fn extended_length() {
+	for len in [125, 126, 0xFFFF, 0x10000] {
+		let payload = vec![0xAB; len];
+		let mut sink = DefaultBuffer::default();
+		write_frame(&mut sink, Frame::new(Opcode::Binary, Buffer::from_slice(&payload)), Some(MASK_KEY)).unwrap();
+		let header_len = match len {
+			..126 => 2,
+			126..=0xFFFF => 4,
+			_ => 10
+		} + 4;
+		assert_eq!(sink.count(), header_len + len);
+
+		let frame = read_frame(&mut sink, len).unwrap();
+		assert_eq!(frame.opcode, Opcode::Binary);
+		assert_eq!(frame.payload, payload.as_slice());
+	}
+}
+
+#[test]
+fn message() {
+	let mut buffer = DefaultBuffer::default();
+	buffer.write_from_slice(&FRAGMENTED_HELLO[..5]).unwrap();
+	write_frame(&mut buffer, Frame::new(Opcode::Ping, Buffer::from_slice(b"ping")), None).unwrap();
+	buffer.write_from_slice(&FRAGMENTED_HELLO[5..]).unwrap();
+
+	let mut pings = Vec::new();
+	let message = read_message(&mut buffer, 1024, |frame| {
+		pings.push(frame.opcode);
+		Ok(())
+	}).unwrap();
+	assert!(message.fin);
+	assert_eq!(message.opcode, Opcode::Text);
+	assert_eq!(message.payload, b"Hello");
+	assert_eq!(pings, [Opcode::Ping]);
+}
+
+#[test]
+fn control_frame_near_limit() {
+	let mut buffer = DefaultBuffer::default();
+	buffer.write_from_slice(&FRAGMENTED_HELLO[..5]).unwrap();
+	write_frame(&mut buffer, Frame::new(Opcode::Ping, Buffer::from_slice(&[0; 125])), None).unwrap();
+	buffer.write_from_slice(&FRAGMENTED_HELLO[5..]).unwrap();
+
+	// The ping is longer than the remaining message size, but within the control
+	// frame limit.
+	let mut pings = Vec::new();
+	let message = read_message(&mut buffer, 5, |frame| {
+		pings.push(frame.payload.count());
+		Ok(())
+	}).unwrap();
+	assert_eq!(message.payload, b"Hello");
+	assert_eq!(pings, [125]);
+}
+
+#[test]
+fn invalid() {
+	fn error(data: &[u8], max_size: usize) -> ErrorKind {
+		let mut buffer = Buffer::from_slice(data);
+		read_message(&mut buffer, max_size, |_| Ok(())).err().unwrap().kind()
+	}
+
+	let invalid_data = ErrorKind::Io(io::ErrorKind::InvalidData);
+	assert_eq!(error(&[0xC1, 0x00], 1024), invalid_data, "reserved bit");
+	assert_eq!(error(&[0x83, 0x00], 1024), invalid_data, "reserved opcode");
+	assert_eq!(error(&[0x09, 0x00], 1024), invalid_data, "fragmented control frame");
+	assert_eq!(error(&[0x89, 0x7E, 0x00, 0x7E], 1024), invalid_data, "oversized control frame");
+	assert_eq!(error(UNMASKED_HELLO, 4), invalid_data, "payload too large");
+	assert_eq!(error(FRAGMENTED_HELLO, 4), invalid_data, "message too large");
+	assert_eq!(error(&[0x80, 0x00], 1024), invalid_data, "unexpected continuation");
+	assert_eq!(error(&UNMASKED_HELLO[..4], 1024), ErrorKind::Eos, "truncated payload");
+
+	let mut sink = DefaultBuffer::default();
+	let frame = Frame { fin: false, opcode: Opcode::Close, payload: DefaultBuffer::default() };
+	assert_eq!(
+		write_frame(&mut sink, frame, None).err().unwrap().kind(),
+		ErrorKind::Io(io::ErrorKind::InvalidInput)
+	);
+}
@@ -0,0 +1,92 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::io;
+use std::io::Cursor;
+use pretty_assertions::assert_eq;
+use orio::{DefaultBuffer, ErrorKind, SIZE};
+use orio::pool::DefaultPoolContainer;
+use orio::streams::{ReaderSource, Source};
+use orio::streams::zip::{CompressionMethod, ZipReader};
+
+/// An archive containing a directory, a stored file, and a deflated file, with an
+/// archive comment. Created with Python's `zipfile`.
+const ARCHIVE: &[u8] = &[
+	0x50, 0x4b, 0x03, 0x04, 0x14, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x21, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00,
+	0x64, 0x69, 0x72, 0x2f, 0x50, 0x4b, 0x03, 0x04, 0x14, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x21, 0x00, 0x18, 0xa7, 0x55, 0x7b, 0x0e, 0x00, 0x00, 0x00, 0x0e, 0x00, 0x00, 0x00,
+	0x0d, 0x00, 0x00, 0x00, 0x64, 0x69, 0x72, 0x2f, 0x68, 0x65, 0x6c, 0x6c, 0x6f, 0x2e, 0x74,
+	0x78, 0x74, 0x48, 0x65, 0x6c, 0x6c, 0x6f, 0x2c, 0x20, 0x77, 0x6f, 0x72, 0x6c, 0x64, 0x21,
+	0x0a, 0x50, 0x4b, 0x03, 0x04, 0x14, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x21, 0x00,
+	0x64, 0x7a, 0x70, 0xaf, 0x06, 0x00, 0x00, 0x00, 0x64, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00,
+	0x00, 0x70, 0x61, 0x63, 0x6b, 0x65, 0x64, 0x2e, 0x74, 0x78, 0x74, 0x4b, 0x4c, 0xa4, 0x3d,
+	0x00, 0x00, 0x50, 0x4b, 0x01, 0x02, 0x14, 0x03, 0x14, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x21, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80, 0x01, 0x00,
+	0x00, 0x00, 0x00, 0x64, 0x69, 0x72, 0x2f, 0x50, 0x4b, 0x01, 0x02, 0x14, 0x03, 0x14, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x21, 0x00, 0x18, 0xa7, 0x55, 0x7b, 0x0e, 0x00, 0x00,
+	0x00, 0x0e, 0x00, 0x00, 0x00, 0x0d, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x80, 0x01, 0x22, 0x00, 0x00, 0x00, 0x64, 0x69, 0x72, 0x2f, 0x68, 0x65, 0x6c,
+	0x6c, 0x6f, 0x2e, 0x74, 0x78, 0x74, 0x50, 0x4b, 0x01, 0x02, 0x14, 0x03, 0x14, 0x00, 0x00,
+	0x00, 0x08, 0x00, 0x00, 0x00, 0x21, 0x00, 0x64, 0x7a, 0x70, 0xaf, 0x06, 0x00, 0x00, 0x00,
+	0x64, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x80, 0x01, 0x5b, 0x00, 0x00, 0x00, 0x70, 0x61, 0x63, 0x6b, 0x65, 0x64, 0x2e, 0x74,
+	0x78, 0x74, 0x50, 0x4b, 0x05, 0x06, 0x00, 0x00, 0x00, 0x00, 0x03, 0x00, 0x03, 0x00, 0xa5,
+	0x00, 0x00, 0x00, 0x89, 0x00, 0x00, 0x00, 0x09, 0x00, 0x6f, 0x72, 0x69, 0x6f, 0x20, 0x74,
+	0x65, 0x73, 0x74
+];
+
+fn reader(data: &[u8]) -> orio::streams::Result<ZipReader<'static, ReaderSource<Cursor<Vec<u8>>>, SIZE, DefaultPoolContainer>> {
+	ZipReader::new(ReaderSource::from(Cursor::new(data.to_vec())))
+}
+
+#[test]
+fn central_directory() {
+	let reader = reader(ARCHIVE).unwrap();
+	let names: Vec<_> = reader.entries().iter().map(|e| e.name.as_str()).collect();
+	assert_eq!(names, ["dir/", "dir/hello.txt", "packed.txt"]);
+	assert!(reader.entries()[0].is_dir());
+
+	let entry = reader.by_name("dir/hello.txt").unwrap();
+	assert_eq!(entry.method, CompressionMethod::Stored);
+	assert_eq!(entry.size, 14);
+	assert_eq!(entry.crc32, 0x7b55a718);
+
+	let entry = reader.by_name("packed.txt").unwrap();
+	assert_eq!(entry.method, CompressionMethod::Deflated);
+	assert_eq!(entry.size, 100);
+	assert!(reader.by_name("missing").is_none());
+}
+
+#[test]
+fn open() {
+	let mut reader = reader(ARCHIVE).unwrap();
+	let hello = reader.by_name("dir/hello.txt").unwrap().clone();
+	let packed = reader.by_name("packed.txt").unwrap().clone();
+
+	// Read twice to check entries can be reopened after seeking.
+	for _ in 0..2 {
+		let mut file = reader.open(&hello).unwrap();
+		let mut contents = DefaultBuffer::default();
+		assert_eq!(file.fill(&mut contents, 5).unwrap(), 5);
+		file.fill_all(&mut contents).unwrap();
+		assert!(file.is_eos());
+		assert_eq!(contents, b"Hello, world!\n");
+	}
+
+	assert_eq!(
+		reader.open(&packed).err().unwrap().kind(),
+		ErrorKind::Io(io::ErrorKind::Unsupported)
+	);
+}
+
+#[test]
+fn invalid() {
+	let invalid_data = ErrorKind::Io(io::ErrorKind::InvalidData);
+	assert_eq!(reader(b"not a zip archive").err().unwrap().kind(), invalid_data, "no end record");
+
+	let mut corrupted = ARCHIVE.to_vec();
+	let central = corrupted.windows(4).position(|w| w == b"PK\x01\x02").unwrap();
+	corrupted[central] = 0;
+	assert_eq!(reader(&corrupted).err().unwrap().kind(), invalid_data, "bad central header");
+}